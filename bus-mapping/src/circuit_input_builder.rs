@@ -4,8 +4,10 @@
 mod access;
 mod block;
 mod call;
+mod chunk;
 mod execution;
 mod input_state_ref;
+mod post_state;
 // mod protocol_instance;
 ///
 pub mod protocol_instance;
@@ -13,23 +15,27 @@ pub mod protocol_instance;
 #[cfg(test)]
 mod tracer_tests;
 mod transaction;
+mod usage;
 
 use self::access::gen_state_access_trace;
 use crate::{
     error::Error,
     evm::opcodes::{gen_associated_ops, gen_associated_steps},
-    operation::{CallContextField, Operation, RWCounter, StartOp, RW},
+    operation::{CallContextField, Operation, RWCounter, StartOp, TxLogField, RW},
     rpc::GethClient,
     state_db::{self, CodeDB, StateDB},
 };
-pub use access::{Access, AccessSet, AccessValue, CodeSource};
-pub use block::{Block, BlockContext};
+pub use access::{Access, AccessSet, AccessSetMismatch, AccessValue, CodeSource};
+pub use block::{Block, BlockContext, BlockHead};
 pub use call::{Call, CallContext, CallKind};
+pub use chunk::{check_chunks, ChunkContext};
 use core::fmt::Debug;
 use eth_types::{
-    self, geth_types,
+    self,
+    evm_types::ChainSpec,
+    geth_types,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
-    Address, GethExecStep, GethExecTrace, ToWord, Word,
+    Address, GethExecStep, GethExecTrace, GethPrestateTrace, ToBigEndian, ToWord, Word,
 };
 use ethers_providers::JsonRpcClient;
 pub use execution::{
@@ -38,9 +44,11 @@ pub use execution::{
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
 use log::warn;
+pub use post_state::{PostState, PostStateAccount, PostStateDiff, PostStateMismatch};
 pub use protocol_instance::{BlockMetadata, ProtocolInstance, ANCHOR_METHOD_SIGNATURE};
 use std::collections::HashMap;
 pub use transaction::{Transaction, TransactionContext};
+pub use usage::{CircuitLimit, CircuitsOverflow, CircuitsRowCounter, CircuitsUsage};
 
 /// Circuit Setup Parameters
 #[derive(Debug, Clone, Copy)]
@@ -73,6 +81,11 @@ pub struct CircuitsParams {
     /// calculated, so the same circuit will not be able to prove different
     /// witnesses.
     pub max_keccak_rows: usize,
+    /// Pad the Receipt circuit to this number of items (one per transaction
+    /// and one per log address and topic).  When 0, the number of items will
+    /// be dynamically calculated, so the same circuit will not be able to
+    /// prove different witnesses.
+    pub max_receipt_items: usize,
 }
 
 impl Default for CircuitsParams {
@@ -89,6 +102,7 @@ impl Default for CircuitsParams {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         }
     }
 }
@@ -162,16 +176,9 @@ impl<'a> CircuitInputBuilder {
     ) -> Result<Transaction, Error> {
         let call_id = self.block_ctx.rwc.0;
 
-        self.block_ctx.call_map.insert(
-            call_id,
-            (
-                eth_tx
-                    .transaction_index
-                    .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                    .as_u64() as usize,
-                0,
-            ),
-        );
+        self.block_ctx
+            .call_map
+            .insert(call_id, (self.block.txs.len(), 0));
 
         Transaction::new(
             call_id,
@@ -211,20 +218,40 @@ impl<'a> CircuitInputBuilder {
         &mut self,
         eth_block: &EthBlock,
         geth_traces: &[eth_types::GethExecTrace],
+    ) -> Result<(), Error> {
+        self.handle_blocks(&[(eth_block, geth_traces)])
+    }
+
+    /// Handle a batch of consecutive blocks, each with its execution traces,
+    /// so that all their transactions end up in a single witness.  The first
+    /// block must be the one the builder was created with; the heads of the
+    /// following ones are appended to [`Block::headers`].  The rw counter,
+    /// the state and the transaction ids continue across block boundaries.
+    pub fn handle_blocks(
+        &mut self,
+        blocks: &[(&EthBlock, &[eth_types::GethExecTrace])],
     ) -> Result<(), Error> {
         if !cfg!(feature = "disable_l2_trace_block") {
-            // accumulates gas across all txs in the block
-            for (tx_index, tx) in eth_block.transactions.iter().enumerate() {
-                let geth_trace = &geth_traces[tx_index];
-                self.handle_tx(
-                    eth_block,
-                    tx,
-                    geth_trace,
-                    tx_index + 1 == eth_block.transactions.len(),
-                )?;
+            let total_txs: usize = blocks
+                .iter()
+                .map(|(eth_block, _)| eth_block.transactions.len())
+                .sum();
+            let mut handled_txs = 0;
+            for (block_index, (eth_block, geth_traces)) in blocks.iter().enumerate() {
+                if block_index > 0 {
+                    self.block.push_head(eth_block)?;
+                }
+                // accumulates gas across all txs in the block
+                for (tx_index, tx) in eth_block.transactions.iter().enumerate() {
+                    let geth_trace = &geth_traces[tx_index];
+                    handled_txs += 1;
+                    self.handle_tx(eth_block, tx, geth_trace, handled_txs == total_txs)?;
+                }
             }
             self.set_value_ops_call_context_rwc_eor();
             self.set_end_block();
+            #[cfg(feature = "coverage")]
+            crate::coverage::update(|coverage| coverage.record(&self.block));
         }
         Ok(())
     }
@@ -291,6 +318,8 @@ impl<'a> CircuitInputBuilder {
     ) -> Result<(), Error> {
         let mut tx = self.new_tx(eth_block, eth_tx, !geth_trace.failed)?;
         let mut tx_ctx = TransactionContext::new(eth_tx, geth_trace, is_last_tx)?;
+        // transaction ids are global across all the blocks handled
+        tx_ctx.id = self.block.txs.len() + 1;
 
         // Generate BeginTx step
         let begin_tx_step = gen_associated_steps(
@@ -322,24 +351,87 @@ impl<'a> CircuitInputBuilder {
     }
 }
 
+/// Circuit whose data is hashed by a keccak input, used to report the keccak
+/// circuit usage of each one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeccakInputSource {
+    /// Signature verification of the transactions
+    Tx,
+    /// Hash of the bytecodes used in the block
+    Bytecode,
+    /// Inputs of the SHA3 opcode
+    Sha3,
+    /// Log addresses and topics, hashed into the logs bloom by the receipt
+    /// circuit
+    Receipt,
+    /// Public input commitment of the PI circuit.  Its input depends on the
+    /// layout of the PI circuit, so it isn't returned by
+    /// [`keccak_inputs_with_source`] but added by the zkevm-circuits
+    /// `KeccakCapacityReport::from_builder`.
+    Pi,
+}
+
 /// Return all the keccak inputs used during the processing of the current
 /// block.
 pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Error> {
+    Ok(keccak_inputs_with_source(block, code_db)?
+        .into_iter()
+        .map(|(_, input)| input)
+        .collect())
+}
+
+/// Return all the keccak inputs used during the processing of the current
+/// block, together with the circuit they come from.
+pub fn keccak_inputs_with_source(
+    block: &Block,
+    code_db: &CodeDB,
+) -> Result<Vec<(KeccakInputSource, Vec<u8>)>, Error> {
     let mut keccak_inputs = Vec::new();
     // Tx Circuit
     let txs: Vec<geth_types::Transaction> = block.txs.iter().map(|tx| tx.tx.clone()).collect();
-    keccak_inputs.extend_from_slice(&keccak_inputs_tx_circuit(&txs, block.chain_id.as_u64())?);
+    keccak_inputs.extend(
+        keccak_inputs_tx_circuit(&txs, block.chain_id.as_u64())?
+            .into_iter()
+            .map(|input| (KeccakInputSource::Tx, input)),
+    );
     // Bytecode Circuit
     for bytecode in code_db.0.values() {
-        keccak_inputs.push(bytecode.clone());
+        keccak_inputs.push((KeccakInputSource::Bytecode, bytecode.clone()));
     }
     // EVM Circuit
-    keccak_inputs.extend_from_slice(&block.sha3_inputs);
+    keccak_inputs.extend(
+        block
+            .sha3_inputs
+            .iter()
+            .map(|input| (KeccakInputSource::Sha3, input.clone())),
+    );
+    // Receipt Circuit
+    keccak_inputs.extend(
+        keccak_inputs_receipt_circuit(block)
+            .into_iter()
+            .map(|input| (KeccakInputSource::Receipt, input)),
+    );
     // MPT Circuit
     // TODO https://github.com/privacy-scaling-explorations/zkevm-circuits/issues/696
     Ok(keccak_inputs)
 }
 
+/// Generate the keccak inputs required by the Receipt Circuit from the logs:
+/// the address and each topic of every log, hashed into the logs bloom.
+pub fn keccak_inputs_receipt_circuit(block: &Block) -> Vec<Vec<u8>> {
+    block
+        .container
+        .tx_log
+        .iter()
+        .map(Operation::op)
+        .filter_map(|op| match op.field {
+            TxLogField::Address => Some(op.value.to_be_bytes()[12..].to_vec()),
+            TxLogField::Topic => Some(op.value.to_be_bytes().to_vec()),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Generate the keccak inputs required by the SignVerify Chip from the
 /// signature datas.
 pub fn keccak_inputs_sign_verify(sigs: &[SignData]) -> Vec<Vec<u8>> {
@@ -415,6 +507,7 @@ pub struct BuilderClient<P: JsonRpcClient> {
     chain_id: Word,
     circuits_params: CircuitsParams,
     protocol_instance: Option<ProtocolInstance>,
+    chain_spec: ChainSpec,
 }
 
 /// Get State Accesses from TxExecTraces
@@ -485,6 +578,46 @@ pub fn build_state_code_db(
     (sdb, code_db)
 }
 
+/// Build a partial StateDB from the geth `prestateTracer` traces of every tx
+/// of a block.  Each trace holds the state right before its tx, so the first
+/// tx that touches an account (or a storage key) gives its state at the
+/// beginning of the block.
+pub fn build_state_code_db_from_prestate(prestates: &[GethPrestateTrace]) -> (StateDB, CodeDB) {
+    let mut accounts: HashMap<Address, state_db::Account> = HashMap::new();
+    let mut code_db = CodeDB::new();
+    for prestate in prestates {
+        for (address, prestate_account) in prestate {
+            let account = accounts.entry(*address).or_insert_with(|| {
+                let code = prestate_account
+                    .code
+                    .as_ref()
+                    .map(|code| code.to_vec())
+                    .unwrap_or_default();
+                state_db::Account {
+                    nonce: prestate_account.nonce.unwrap_or_default(),
+                    balance: prestate_account.balance.unwrap_or_default(),
+                    storage: HashMap::new(),
+                    code_hash: code_db.insert(code),
+                }
+            });
+            if let Some(storage) = &prestate_account.storage {
+                for (key, value) in storage {
+                    account
+                        .storage
+                        .entry(key.to_word())
+                        .or_insert_with(|| value.to_word());
+                }
+            }
+        }
+    }
+
+    let mut sdb = StateDB::new();
+    for (address, account) in accounts {
+        sdb.set_account(&address, account);
+    }
+    (sdb, code_db)
+}
+
 impl<P: JsonRpcClient> BuilderClient<P> {
     /// Create a new BuilderClient
     pub async fn new(
@@ -499,9 +632,17 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             chain_id: chain_id.into(),
             circuits_params,
             protocol_instance,
+            chain_spec: ChainSpec::default(),
         })
     }
 
+    /// Build the blocks with the rules of `chain_spec`, which must be the
+    /// ones the node executed them with.
+    pub fn with_chain_spec(mut self, chain_spec: ChainSpec) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
     /// Step 1. Query geth for Block, Txs, TxExecTraces, history block hashes
     /// and previous state root.
     pub async fn get_block(
@@ -596,6 +737,58 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         }
     }
 
+    /// Step 3 (alternative). Query geth for the state touched by the block
+    /// with a single `debug_traceBlockByNumber` call using the
+    /// `prestateTracer` in its default mode, which reports every account and
+    /// storage key read or written by each tx, and build the partial StateDB
+    /// from it.  Diff mode would leave out the read-only state, so it is only
+    /// used for the post-state check.  The accesses found in the struct logs
+    /// are cross-checked against the prestate, and the ones the tracer doesn't
+    /// report (state touched outside of the EVM) are queried with
+    /// `eth_getProof`/`eth_getCode`.
+    pub async fn get_state_from_prestate(
+        &self,
+        block_num: u64,
+        access_set: AccessSet,
+    ) -> Result<(StateDB, CodeDB, AccessSetMismatch), Error> {
+        if cfg!(feature = "disable_l2_trace_block") {
+            return Ok((StateDB::new(), CodeDB::new(), AccessSetMismatch::default()));
+        }
+        let prestates = self
+            .cli
+            .trace_block_prestate_by_number(block_num.into())
+            .await?;
+        let (mut sdb, mut code_db) = build_state_code_db_from_prestate(&prestates);
+        let mismatch =
+            AccessSetMismatch::new(&access_set, &AccessSet::from_prestate_traces(&prestates));
+
+        for (address, key_set) in mismatch.missing_in_prestate.iter() {
+            let mut keys: Vec<Word> = key_set.iter().cloned().collect();
+            keys.sort();
+            let proof = self
+                .cli
+                .get_proof(*address, keys, (block_num - 1).into())
+                .await?;
+            let (found, account) = sdb.get_account_mut(address);
+            if !found {
+                account.nonce = proof.nonce.as_u64();
+                account.balance = proof.balance;
+                account.code_hash = proof.code_hash;
+                if access_set.code.contains(address) {
+                    let code = self.cli.get_code(*address, (block_num - 1).into()).await?;
+                    code_db.insert(code);
+                }
+            }
+            for storage_proof in proof.storage_proof {
+                account
+                    .storage
+                    .insert(storage_proof.key, storage_proof.value);
+            }
+        }
+
+        Ok((sdb, code_db, mismatch))
+    }
+
     /// Step 4. Build a partial StateDB from step 3
     pub fn build_state_code_db(
         proofs: Vec<eth_types::EIP1186ProofResponse>,
@@ -622,12 +815,34 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             eth_block,
             self.circuits_params,
             self.protocol_instance.clone(),
-        )?;
+        )?
+        .with_chain_spec(self.chain_spec);
         let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
         builder.handle_block(eth_block, geth_traces)?;
         Ok(builder)
     }
 
+    /// Step 6 (optional). Check that the state left by the block in the
+    /// StateDB of `builder` matches the post-state computed by geth, queried
+    /// with the `prestateTracer` in diff mode. Returns [`Error::Unsupported`]
+    /// when built with `disable_l2_trace_block`.
+    pub async fn check_post_state(
+        &self,
+        block_num: u64,
+        builder: &CircuitInputBuilder,
+    ) -> Result<(), Error> {
+        if cfg!(feature = "disable_l2_trace_block") {
+            return Err(Error::Unsupported(
+                "post-state check needs the prestate tracer, disabled by disable_l2_trace_block",
+            ));
+        }
+        let diffs = self
+            .cli
+            .trace_block_prestate_diff_by_number(block_num.into())
+            .await?;
+        builder.check_post_state(&PostState::from_prestate_diffs(&diffs))
+    }
+
     /// Perform all the steps to generate the circuit inputs
     pub async fn gen_inputs(
         &self,
@@ -655,4 +870,89 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         )?;
         Ok((builder, eth_block))
     }
+
+    /// Perform all the steps to generate the circuit inputs, splitting the
+    /// transactions of the block into chunks that each fit the circuits
+    /// params of the client.  See
+    /// [`CircuitInputBuilder::handle_block_in_chunks`].
+    pub async fn gen_inputs_in_chunks(
+        &self,
+        block_num: u64,
+        keccak_rows: impl Fn(&[u8]) -> usize,
+    ) -> Result<
+        (
+            Vec<CircuitInputBuilder>,
+            eth_types::Block<eth_types::Transaction>,
+        ),
+        Error,
+    > {
+        let (eth_block, geth_traces, history_hashes, prev_state_root) =
+            self.get_block(block_num).await?;
+        let access_set =
+            Self::get_state_accesses(&eth_block, &geth_traces, &self.protocol_instance)?;
+        let (proofs, codes) = self.get_state(block_num, access_set).await?;
+        let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
+        let intermediate_roots: Vec<Word> = self
+            .cli
+            .intermediate_roots(
+                eth_block
+                    .hash
+                    .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
+            )
+            .await?
+            .iter()
+            .map(|root| root.to_word())
+            .collect();
+        let block = Block::new(
+            self.chain_id,
+            history_hashes,
+            prev_state_root,
+            &eth_block,
+            self.circuits_params,
+            self.protocol_instance.clone(),
+        )?
+        .with_chain_spec(self.chain_spec);
+        let chunks = CircuitInputBuilder::new(state_db, code_db, block).handle_block_in_chunks(
+            &eth_block,
+            &geth_traces,
+            &intermediate_roots,
+            keccak_rows,
+        )?;
+        Ok((chunks, eth_block))
+    }
+
+    /// Perform all the steps to generate the circuit inputs, acquiring the
+    /// state with the `prestateTracer` instead of querying each account.
+    pub async fn gen_inputs_with_prestate(
+        &self,
+        block_num: u64,
+    ) -> Result<
+        (
+            CircuitInputBuilder,
+            eth_types::Block<eth_types::Transaction>,
+        ),
+        Error,
+    > {
+        let (eth_block, geth_traces, history_hashes, prev_state_root) =
+            self.get_block(block_num).await?;
+        let access_set =
+            Self::get_state_accesses(&eth_block, &geth_traces, &self.protocol_instance)?;
+        let (state_db, code_db, mismatch) =
+            self.get_state_from_prestate(block_num, access_set).await?;
+        if !mismatch.missing_in_trace.is_empty() {
+            warn!(
+                "state accesses missing in the struct logs of block {}: {:?}",
+                block_num, mismatch.missing_in_trace
+            );
+        }
+        let builder = self.gen_inputs_from_state(
+            state_db,
+            code_db,
+            &eth_block,
+            &geth_traces,
+            history_hashes,
+            prev_state_root,
+        )?;
+        Ok((builder, eth_block))
+    }
 }
@@ -1,5 +1,8 @@
 use crate::{operation::RW, Error};
-use eth_types::{evm_types::OpcodeId, Address, GethExecStep, GethExecTrace, ToAddress, Word};
+use eth_types::{
+    evm_types::OpcodeId, Address, GethExecStep, GethExecTrace, GethPrestateTrace, ToAddress,
+    ToWord, Word,
+};
 use ethers_core::utils::get_contract_address;
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 
@@ -94,6 +97,79 @@ impl From<Vec<Access>> for AccessSet {
     }
 }
 
+impl AccessSet {
+    /// Build the [`AccessSet`] of the accounts and storage keys reported by
+    /// the geth `prestateTracer` for each tx of a block.  The tracer reports
+    /// the code of every touched contract, so every reported account is also
+    /// a code access.
+    pub fn from_prestate_traces(prestates: &[GethPrestateTrace]) -> Self {
+        let mut state: HashMap<Address, HashSet<Word>> = HashMap::new();
+        let mut code: HashSet<Address> = HashSet::new();
+        for prestate in prestates {
+            for (address, account) in prestate {
+                let keys = state.entry(*address).or_insert_with(HashSet::new);
+                if let Some(storage) = &account.storage {
+                    keys.extend(storage.keys().map(|key| key.to_word()));
+                }
+                code.insert(*address);
+            }
+        }
+        Self { state, code }
+    }
+
+    /// Return the accounts and storage keys of `self` that are missing in
+    /// `other`.  An account present in both sets is only returned when some
+    /// of its storage keys are missing in `other`.
+    pub fn state_difference(&self, other: &Self) -> HashMap<Address, HashSet<Word>> {
+        let mut diff = HashMap::new();
+        for (address, keys) in &self.state {
+            match other.state.get(address) {
+                None => {
+                    diff.insert(*address, keys.clone());
+                }
+                Some(other_keys) => {
+                    let missing: HashSet<Word> = keys.difference(other_keys).cloned().collect();
+                    if !missing.is_empty() {
+                        diff.insert(*address, missing);
+                    }
+                }
+            }
+        }
+        diff
+    }
+}
+
+/// Mismatch between the [`AccessSet`] found by [`gen_state_access_trace`] and
+/// the state reported by the geth `prestateTracer` for the same block.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AccessSetMismatch {
+    /// Accounts and storage keys reported by the `prestateTracer` that
+    /// [`gen_state_access_trace`] missed.  These point to missing-access bugs
+    /// in the struct logs replay.
+    pub missing_in_trace: HashMap<Address, HashSet<Word>>,
+    /// Accounts and storage keys found by [`gen_state_access_trace`] that the
+    /// `prestateTracer` didn't report.  This is expected for state touched
+    /// outside of the EVM, like the Taiko treasury.
+    pub missing_in_prestate: HashMap<Address, HashSet<Word>>,
+}
+
+impl AccessSetMismatch {
+    /// Compare the [`AccessSet`] from the struct logs with the one from the
+    /// `prestateTracer`.
+    pub fn new(trace_access_set: &AccessSet, prestate_access_set: &AccessSet) -> Self {
+        Self {
+            missing_in_trace: prestate_access_set.state_difference(trace_access_set),
+            missing_in_prestate: trace_access_set.state_difference(prestate_access_set),
+        }
+    }
+
+    /// Return true if both access sets contain the same accounts and storage
+    /// keys.
+    pub fn is_empty(&self) -> bool {
+        self.missing_in_trace.is_empty() && self.missing_in_prestate.is_empty()
+    }
+}
+
 /// Source of the code in the EVM execution.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CodeSource {
@@ -275,3 +351,53 @@ pub fn gen_state_access_trace<TX>(
     }
     Ok(accs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, GethPrestateAccount, H256};
+
+    #[test]
+    fn prestate_access_set_mismatch() {
+        let contract = address!("0x00000000000000000000000000000000000000c0");
+        let eoa = address!("0x00000000000000000000000000000000000000e0");
+        let treasury = address!("0x00000000000000000000000000000000000000f0");
+        let prestate = GethPrestateTrace::from([
+            (eoa, GethPrestateAccount::default()),
+            (
+                contract,
+                GethPrestateAccount {
+                    storage: Some(HashMap::from([
+                        (H256::from_low_u64_be(1), H256::zero()),
+                        (H256::from_low_u64_be(2), H256::zero()),
+                    ])),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let prestate_access_set = AccessSet::from_prestate_traces(&[prestate]);
+        assert!(prestate_access_set.code.contains(&eoa));
+
+        let trace_access_set = AccessSet::from(vec![
+            Access::new(None, WRITE, Account { address: eoa }),
+            Access::new(None, WRITE, Account { address: treasury }),
+            Access::new(
+                Some(0),
+                READ,
+                Storage {
+                    address: contract,
+                    key: Word::from(1),
+                },
+            ),
+        ]);
+        let mismatch = AccessSetMismatch::new(&trace_access_set, &prestate_access_set);
+        assert_eq!(
+            mismatch.missing_in_trace,
+            HashMap::from([(contract, HashSet::from([Word::from(2)]))])
+        );
+        assert_eq!(
+            mismatch.missing_in_prestate,
+            HashMap::from([(treasury, HashSet::new())])
+        );
+    }
+}
@@ -1,18 +1,18 @@
 //! Block-related utility module
 
 use super::{
-    execution::ExecState, transaction::Transaction, CircuitsParams, CopyEvent, ExecStep, ExpEvent,
-    ProtocolInstance,
+    chunk::ChunkContext, execution::ExecState, transaction::Transaction, CircuitsParams, CopyEvent,
+    ExecStep, ExpEvent, ProtocolInstance,
 };
 use crate::{
     operation::{OperationContainer, RWCounter},
     Error,
 };
-use eth_types::{evm_unimplemented, Address, Hash, Word};
-use std::collections::HashMap;
+use eth_types::{evm_types::ChainSpec, evm_unimplemented, Address, Hash, ToWord, Word};
+use std::collections::{BTreeMap, HashMap};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlockContext {
     /// Used to track the global counter in every operation in the block.
     /// Contains the next available value.
@@ -52,6 +52,63 @@ pub struct BlockSteps {
     pub end_block_last: ExecStep,
 }
 
+/// Header fields of one of the blocks processed by the [`Block`].  When a
+/// batch of consecutive blocks is proven together there is one head per block.
+#[derive(Debug, Clone)]
+pub struct BlockHead {
+    /// history hashes contains most recent 256 block hashes in history, where
+    /// the lastest one is at history_hashes[history_hashes.len() - 1].
+    pub history_hashes: Vec<Word>,
+    /// coinbase
+    pub coinbase: Address,
+    /// time
+    pub gas_limit: u64,
+    /// number
+    pub number: Word,
+    /// difficulty
+    pub timestamp: Word,
+    /// mix hash
+    pub mix_hash: Hash,
+    /// base fee
+    pub base_fee: Word,
+    /// Original block from geth
+    pub eth_block: eth_types::Block<eth_types::Transaction>,
+}
+
+impl BlockHead {
+    /// Create a new block head.
+    pub fn new(
+        history_hashes: Vec<Word>,
+        eth_block: &eth_types::Block<eth_types::Transaction>,
+    ) -> Result<Self, Error> {
+        if eth_block.base_fee_per_gas.is_none() {
+            // FIXME: resolve this once we have proper EIP-1559 support
+            evm_unimplemented!(
+                "This does not look like a EIP-1559 block - base_fee_per_gas defaults to zero"
+            );
+        }
+
+        Ok(Self {
+            history_hashes,
+            coinbase: eth_block
+                .author
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
+            gas_limit: eth_block.gas_limit.low_u64(),
+            number: eth_block
+                .number
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+                .low_u64()
+                .into(),
+            timestamp: eth_block.timestamp,
+            mix_hash: eth_block
+                .mix_hash
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
+            base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            eth_block: eth_block.clone(),
+        })
+    }
+}
+
 // TODO: Remove fields that are duplicated in`eth_block`
 /// Circuit Input related to a block.
 #[derive(Debug)]
@@ -94,6 +151,14 @@ pub struct Block {
     /// Protocol instance from protocol
     /// If this is set, means we are in the taiko context
     pub protocol_instance: Option<ProtocolInstance>,
+    /// Heads of all the blocks processed, indexed by block number.  The fields
+    /// above mirror the first (lowest numbered) one.
+    pub headers: BTreeMap<u64, BlockHead>,
+    /// Boundaries of the chunk of transactions held by this block when it is
+    /// split into several proofs.
+    pub chunk: Option<ChunkContext>,
+    /// Hardfork rules the block is executed with
+    pub chain_spec: ChainSpec,
 }
 
 impl Block {
@@ -106,30 +171,17 @@ impl Block {
         circuits_params: CircuitsParams,
         protocol_instance: Option<ProtocolInstance>,
     ) -> Result<Self, Error> {
-        if eth_block.base_fee_per_gas.is_none() {
-            // FIXME: resolve this once we have proper EIP-1559 support
-            evm_unimplemented!(
-                "This does not look like a EIP-1559 block - base_fee_per_gas defaults to zero"
-            );
-        }
+        let head = BlockHead::new(history_hashes, eth_block)?;
 
         Ok(Self {
             chain_id,
-            history_hashes,
-            coinbase: eth_block
-                .author
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
-            gas_limit: eth_block.gas_limit.low_u64(),
-            number: eth_block
-                .number
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                .low_u64()
-                .into(),
-            timestamp: eth_block.timestamp,
-            mix_hash: eth_block
-                .mix_hash
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?,
-            base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            history_hashes: head.history_hashes.clone(),
+            coinbase: head.coinbase,
+            gas_limit: head.gas_limit,
+            number: head.number,
+            timestamp: head.timestamp,
+            mix_hash: head.mix_hash,
+            base_fee: head.base_fee,
             prev_state_root,
             container: OperationContainer::new(),
             txs: Vec::new(),
@@ -149,9 +201,57 @@ impl Block {
             circuits_params,
             eth_block: eth_block.clone(),
             protocol_instance,
+            headers: BTreeMap::from([(head.number.as_u64(), head)]),
+            chunk: None,
+            chain_spec: ChainSpec::default(),
         })
     }
 
+    /// Execute the block with the rules of `chain_spec` instead of the ones of
+    /// the latest supported hardfork.
+    pub fn with_chain_spec(mut self, chain_spec: ChainSpec) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Append the head of the block following the last processed one, so that
+    /// its transactions can be handled in the same witness.  The history
+    /// hashes of the new head are derived from the previous one.
+    pub fn push_head(
+        &mut self,
+        eth_block: &eth_types::Block<eth_types::Transaction>,
+    ) -> Result<(), Error> {
+        let prev = self
+            .headers
+            .values()
+            .last()
+            .expect("block has at least one head");
+        let prev_hash = prev
+            .eth_block
+            .hash
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        if eth_block.number.map(|n| n.as_u64()) != Some(prev.number.as_u64() + 1)
+            || eth_block.parent_hash != prev_hash
+        {
+            return Err(Error::InternalError("batch blocks are not consecutive"));
+        }
+        let mut history_hashes = prev.history_hashes.clone();
+        history_hashes.push(prev_hash.to_word());
+        if history_hashes.len() > 256 {
+            history_hashes.drain(..history_hashes.len() - 256);
+        }
+        let head = BlockHead::new(history_hashes, eth_block)?;
+        self.headers.insert(head.number.as_u64(), head);
+        Ok(())
+    }
+
+    /// Return the head of the block with the given number.
+    pub fn head(&self, number: u64) -> Result<&BlockHead, Error> {
+        self.headers
+            .get(&number)
+            .ok_or(Error::InternalError("block head not found"))
+    }
+
     /// Return the list of transactions of this block.
     pub fn txs(&self) -> &[Transaction] {
         &self.txs
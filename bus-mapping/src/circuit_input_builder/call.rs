@@ -138,6 +138,9 @@ pub struct CallContext {
     pub memory: Memory,
     /// return data buffer
     pub return_data: Vec<u8>,
+    /// memory of the last callee when it returned, from which the return
+    /// data buffer is copied
+    pub last_callee_memory: Memory,
 }
 
 /// A reversion group is the collection of calls and the operations which are
@@ -0,0 +1,510 @@
+//! Splitting of a block into chunks of consecutive transactions that can each
+//! be proven with the same fixed [`CircuitsParams`].
+
+use super::{
+    keccak_inputs_tx_circuit, keccak_inputs_with_source, Block, CircuitInputBuilder,
+    CircuitsParams, EthBlock, KeccakInputSource,
+};
+use crate::{state_db::CodeDB, Error};
+use eth_types::{GethExecTrace, Word};
+use std::collections::{HashMap, HashSet};
+
+/// Boundaries of a chunk of transactions of a block.  The `initial_*` values
+/// of a chunk must match the `end_*` values of the previous one, which is what
+/// the aggregation of the chunk proofs has to check.  All the values are
+/// relative to the whole block, as if it was proven without splitting it.
+///
+/// The witness of a chunk numbers its rw operations and transactions from 1,
+/// as the circuits expect, so the block-level values are the ones of the
+/// witness shifted by [`Self::rwc_offset`] and [`Self::tx_id_offset`].  The
+/// cumulative gas of the receipts continues from the previous chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkContext {
+    /// Index of the chunk in the block
+    pub index: usize,
+    /// Number of chunks the block is split into
+    pub total: usize,
+    /// Block rw counter at the first operation of the chunk
+    pub initial_rwc: usize,
+    /// Block rw counter following the last operation of the chunk
+    pub end_rwc: usize,
+    /// Index in the block of the first transaction of the chunk
+    pub initial_tx_index: usize,
+    /// Index in the block following the last transaction of the chunk
+    pub end_tx_index: usize,
+    /// State root before the first transaction of the chunk
+    pub initial_state_root: Word,
+    /// State root after the last transaction of the chunk
+    pub end_state_root: Word,
+    /// Gas used by the transactions of the block preceding the chunk
+    pub initial_cumulative_gas_used: u64,
+    /// Gas used by the transactions of the block up to the end of the chunk
+    pub end_cumulative_gas_used: u64,
+}
+
+impl ChunkContext {
+    /// Create the context of a chunk starting at the given block-level
+    /// values, with no transaction yet.
+    fn starting_at(
+        index: usize,
+        total: usize,
+        initial_rwc: usize,
+        initial_tx_index: usize,
+        initial_state_root: Word,
+        initial_cumulative_gas_used: u64,
+    ) -> Self {
+        Self {
+            index,
+            total,
+            initial_rwc,
+            end_rwc: initial_rwc,
+            initial_tx_index,
+            end_tx_index: initial_tx_index,
+            initial_state_root,
+            end_state_root: initial_state_root,
+            initial_cumulative_gas_used,
+            end_cumulative_gas_used: initial_cumulative_gas_used,
+        }
+    }
+
+    /// Number of rw operations of the block preceding the chunk, which added
+    /// to a rw counter of the chunk witness gives the block rw counter.
+    pub fn rwc_offset(&self) -> usize {
+        self.initial_rwc - 1
+    }
+
+    /// Number of transactions of the block preceding the chunk, which added
+    /// to a tx id of the chunk witness gives the tx id in the block.
+    pub fn tx_id_offset(&self) -> usize {
+        self.initial_tx_index
+    }
+
+    /// Values of the chunk exposed as public inputs of its proof, in the order
+    /// expected by the aggregation.  The PI circuit of the chunk commits to
+    /// [`Self::rwc_offset`], [`Self::tx_id_offset`] and
+    /// `initial_cumulative_gas_used`, which the EVM circuit uses to continue
+    /// the block from the previous chunk, and to the `end_*` values, which the
+    /// EVM circuit checks at the end of the chunk.
+    pub fn public_inputs(&self) -> Vec<Word> {
+        vec![
+            Word::from(self.initial_rwc),
+            Word::from(self.end_rwc),
+            Word::from(self.initial_tx_index),
+            Word::from(self.end_tx_index),
+            self.initial_state_root,
+            self.end_state_root,
+            Word::from(self.initial_cumulative_gas_used),
+            Word::from(self.end_cumulative_gas_used),
+        ]
+    }
+
+    /// Return true if `next` starts where this chunk ends.
+    pub fn connects(&self, next: &ChunkContext) -> bool {
+        next.index == self.index + 1
+            && next.total == self.total
+            && next.initial_rwc == self.end_rwc
+            && next.initial_tx_index == self.end_tx_index
+            && next.initial_state_root == self.end_state_root
+            && next.initial_cumulative_gas_used == self.end_cumulative_gas_used
+    }
+}
+
+/// Check that the chunks cover all the `num_txs` transactions of a block
+/// starting from `prev_state_root`, and that their boundaries connect.
+pub fn check_chunks(
+    chunks: &[ChunkContext],
+    num_txs: usize,
+    prev_state_root: Word,
+) -> Result<(), Error> {
+    let (first, last) = match (chunks.first(), chunks.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(Error::InternalError("block without chunks")),
+    };
+    if first.index != 0
+        || first.initial_rwc != 1
+        || first.initial_tx_index != 0
+        || first.initial_state_root != prev_state_root
+        || first.initial_cumulative_gas_used != 0
+    {
+        return Err(Error::InternalError("first chunk doesn't start the block"));
+    }
+    if last.total != chunks.len() || last.end_tx_index != num_txs {
+        return Err(Error::InternalError("last chunk doesn't end the block"));
+    }
+    if chunks.windows(2).any(|pair| !pair[0].connects(&pair[1])) {
+        return Err(Error::InternalError("chunk boundaries don't connect"));
+    }
+    Ok(())
+}
+
+/// Resources used by a transaction when handled in a chunk.
+#[derive(Debug, Clone, Copy, Default)]
+struct TxUsage {
+    rws: usize,
+    keccak_rows: usize,
+    calldata: usize,
+}
+
+impl CircuitInputBuilder {
+    /// Handle a block splitting its transactions into consecutive chunks, each
+    /// one fitting the [`CircuitsParams`] of the builder, and return one
+    /// builder per chunk with its [`ChunkContext`] in [`Block::chunk`].  The
+    /// builder must not have handled any transaction yet.  Each chunk builder
+    /// gets its context before handling its transactions, so that they're
+    /// handled as part of the whole block.
+    ///
+    /// `intermediate_roots` contains the state root after each transaction of
+    /// the block (as returned by geth's `debug_intermediateRoots`), and
+    /// `keccak_rows` the rows used by the Keccak circuit to hash an input.
+    /// Chunks are sized by rw operations, keccak rows, transactions and
+    /// calldata; the remaining params must be large enough for any chunk.  The
+    /// keccak rows of a chunk are the ones of the distinct inputs of
+    /// [`keccak_inputs_with_source`], as hashed by the Keccak circuit, leaving
+    /// out the public input commitment of the PI circuit.
+    pub fn handle_block_in_chunks(
+        self,
+        eth_block: &EthBlock,
+        geth_traces: &[GethExecTrace],
+        intermediate_roots: &[Word],
+        keccak_rows: impl Fn(&[u8]) -> usize,
+    ) -> Result<Vec<CircuitInputBuilder>, Error> {
+        let num_txs = eth_block.transactions.len();
+        if geth_traces.len() != num_txs || intermediate_roots.len() != num_txs {
+            return Err(Error::InternalError(
+                "traces or intermediate roots don't match the block txs",
+            ));
+        }
+        let params = self.block.circuits_params;
+        let (base_keccak_rows, usages) = self.tx_usages(eth_block, geth_traces, &keccak_rows)?;
+        let ranges = split_txs(&usages, base_keccak_rows, &params)?;
+
+        let mut chunks = Vec::with_capacity(ranges.len());
+        let (mut sdb, mut code_db) = (self.sdb, self.code_db);
+        let mut initial_rwc = 1;
+        let mut initial_state_root = self.block.prev_state_root;
+        let mut initial_cumulative_gas_used = 0;
+        for (index, (start, end)) in ranges.iter().copied().enumerate() {
+            let mut block = chunk_block(&self.block, initial_state_root, eth_block, params)?;
+            block.chunk = Some(ChunkContext::starting_at(
+                index,
+                ranges.len(),
+                initial_rwc,
+                start,
+                initial_state_root,
+                initial_cumulative_gas_used,
+            ));
+            let mut builder = CircuitInputBuilder::new(sdb, code_db, block);
+            builder.block_ctx.cumulative_gas_used = initial_cumulative_gas_used;
+            for tx_index in start..end {
+                builder.handle_tx(
+                    eth_block,
+                    &eth_block.transactions[tx_index],
+                    &geth_traces[tx_index],
+                    tx_index + 1 == end,
+                )?;
+            }
+            builder.set_value_ops_call_context_rwc_eor();
+            let rws = builder.block_ctx.rwc.0 - 1;
+            let cumulative_gas_used = builder.block_ctx.cumulative_gas_used;
+            builder.set_end_block();
+
+            let chunk = ChunkContext {
+                end_rwc: initial_rwc + rws,
+                end_tx_index: end,
+                end_state_root: if end > start {
+                    intermediate_roots[end - 1]
+                } else {
+                    initial_state_root
+                },
+                end_cumulative_gas_used: cumulative_gas_used,
+                ..builder.block.chunk.expect("chunk context is set")
+            };
+            builder.block.chunk = Some(chunk);
+            initial_rwc = chunk.end_rwc;
+            initial_state_root = chunk.end_state_root;
+            initial_cumulative_gas_used = chunk.end_cumulative_gas_used;
+
+            sdb = builder.sdb.clone();
+            code_db = builder.code_db.clone();
+            chunks.push(builder);
+        }
+        Ok(chunks)
+    }
+
+    /// Handle all the transactions of the block in a scratch builder without
+    /// size limits to measure the resources each one uses.  Also returns the
+    /// keccak rows used by every chunk whatever its transactions: the padding
+    /// signature of the Tx circuit and the bytecodes.
+    fn tx_usages(
+        &self,
+        eth_block: &EthBlock,
+        geth_traces: &[GethExecTrace],
+        keccak_rows: &impl Fn(&[u8]) -> usize,
+    ) -> Result<(usize, Vec<TxUsage>), Error> {
+        let unbounded = CircuitsParams {
+            max_rws: usize::MAX,
+            ..self.block.circuits_params
+        };
+        let block = chunk_block(
+            &self.block,
+            self.block.prev_state_root,
+            eth_block,
+            unbounded,
+        )?;
+        let mut scratch = CircuitInputBuilder::new(self.sdb.clone(), self.code_db.clone(), block);
+
+        let num_txs = eth_block.transactions.len();
+        let mut usages = Vec::with_capacity(num_txs);
+        let mut tx_inputs = Vec::with_capacity(num_txs);
+        let mut inputs = tx_keccak_inputs(&scratch.block, &scratch.code_db)?;
+        for (tx_index, eth_tx) in eth_block.transactions.iter().enumerate() {
+            let rwc = scratch.block_ctx.rwc.0;
+            scratch.handle_tx(
+                eth_block,
+                eth_tx,
+                &geth_traces[tx_index],
+                tx_index + 1 == num_txs,
+            )?;
+            let prev_inputs = inputs;
+            inputs = tx_keccak_inputs(&scratch.block, &scratch.code_db)?;
+            tx_inputs.push(new_inputs(&prev_inputs, &inputs));
+            usages.push(TxUsage {
+                rws: scratch.block_ctx.rwc.0 - rwc,
+                keccak_rows: 0,
+                calldata: eth_tx.input.len(),
+            });
+        }
+
+        // The code db only grows, so that its final bytecodes are an upper
+        // bound of the ones hashed in any chunk.
+        let base_inputs: HashSet<Vec<u8>> =
+            keccak_inputs_tx_circuit(&[], scratch.block.chain_id.as_u64())?
+                .into_iter()
+                .chain(scratch.code_db.0.values().cloned())
+                .collect();
+        for (usage, inputs) in usages.iter_mut().zip(tx_inputs) {
+            // The inputs of a chunk are deduplicated by the Keccak circuit, so
+            // the distinct inputs of each tx are an upper bound of the ones it
+            // adds to any chunk.
+            usage.keccak_rows = inputs
+                .difference(&base_inputs)
+                .map(|input| keccak_rows(input))
+                .sum();
+        }
+        let base_keccak_rows = base_inputs.iter().map(|input| keccak_rows(input)).sum();
+        Ok((base_keccak_rows, usages))
+    }
+}
+
+/// Keccak inputs of [`keccak_inputs_with_source`] but the bytecodes, which are
+/// hashed in every chunk.
+fn tx_keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Error> {
+    Ok(keccak_inputs_with_source(block, code_db)?
+        .into_iter()
+        .filter(|(source, _)| *source != KeccakInputSource::Bytecode)
+        .map(|(_, input)| input)
+        .collect())
+}
+
+/// Distinct inputs of `inputs` which aren't in `prev_inputs`, counting
+/// repeated inputs.
+fn new_inputs(prev_inputs: &[Vec<u8>], inputs: &[Vec<u8>]) -> HashSet<Vec<u8>> {
+    let mut prev_counts: HashMap<&[u8], usize> = HashMap::new();
+    for input in prev_inputs {
+        *prev_counts.entry(input.as_slice()).or_default() += 1;
+    }
+    inputs
+        .iter()
+        .filter(|input| match prev_counts.get_mut(input.as_slice()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Create an empty block for a chunk with the same head as `block`.
+fn chunk_block(
+    block: &Block,
+    prev_state_root: Word,
+    eth_block: &EthBlock,
+    circuits_params: CircuitsParams,
+) -> Result<Block, Error> {
+    Block::new(
+        block.chain_id,
+        block.history_hashes.clone(),
+        prev_state_root,
+        eth_block,
+        circuits_params,
+        block.protocol_instance.clone(),
+    )
+}
+
+/// Greedily group consecutive transactions into ranges `[start, end)` that fit
+/// in `params`.  The rws measured for a transaction handled in the middle of
+/// the block are an upper bound of the ones it uses at a chunk boundary, where
+/// the previous receipt read or the next tx id write are skipped.
+fn split_txs(
+    usages: &[TxUsage],
+    base_keccak_rows: usize,
+    params: &CircuitsParams,
+) -> Result<Vec<(usize, usize)>, Error> {
+    // The Start row and the tx id read by the last EndBlock are always there.
+    let max_rws = params.max_rws.saturating_sub(2);
+    let fits = |chunk: &TxUsage, num_txs: usize| {
+        chunk.rws <= max_rws
+            && (params.max_keccak_rows == 0
+                || base_keccak_rows + chunk.keccak_rows <= params.max_keccak_rows)
+            && num_txs <= params.max_txs
+            && chunk.calldata <= params.max_calldata
+    };
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chunk = TxUsage::default();
+    for (tx_index, usage) in usages.iter().enumerate() {
+        if !fits(usage, 1) {
+            return Err(Error::InternalError("tx doesn't fit in a chunk"));
+        }
+        let extended = TxUsage {
+            rws: chunk.rws + usage.rws,
+            keccak_rows: chunk.keccak_rows + usage.keccak_rows,
+            calldata: chunk.calldata + usage.calldata,
+        };
+        if fits(&extended, tx_index + 1 - start) {
+            chunk = extended;
+        } else {
+            ranges.push((start, tx_index));
+            start = tx_index;
+            chunk = *usage;
+        }
+    }
+    ranges.push((start, usages.len()));
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::BlockData,
+        operation::{TxReceiptField, RW},
+    };
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{test_ctx::helpers::*, TestContext};
+
+    /// Block of 3 txs hashing the same SHA3 input
+    fn sha3_block() -> GethData {
+        TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! {
+                PUSH1(0x20)
+                PUSH1(0)
+                SHA3
+                STOP
+            }),
+            |mut txs, accs| {
+                for tx in txs.iter_mut() {
+                    tx.to(accs[0].address).from(accs[1].address);
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn block_split_in_connected_chunks() {
+        let block = sha3_block();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block_rws = builder.block_ctx.rwc.0;
+
+        // Room for a bit more than one tx per chunk
+        let params = CircuitsParams {
+            max_rws: block_rws / 2,
+            max_txs: 3,
+            ..Default::default()
+        };
+        let roots: Vec<Word> = (1..=3).map(Word::from).collect();
+        let chunks = BlockData::new_from_geth_data_with_params(block.clone(), params)
+            .new_circuit_input_builder()
+            .handle_block_in_chunks(&block.eth_block, &block.geth_traces, &roots, |input| {
+                input.len() / 136 + 1
+            })
+            .unwrap();
+
+        let contexts: Vec<ChunkContext> = chunks
+            .iter()
+            .map(|chunk| chunk.block.chunk.unwrap())
+            .collect();
+        assert_eq!(contexts.len(), 3);
+        check_chunks(&contexts, 3, Word::zero()).unwrap();
+        // The receipts of the chunks continue the cumulative gas of the block
+        let cumulative_gas_used = |builder: &CircuitInputBuilder| -> Vec<u64> {
+            builder
+                .block
+                .container
+                .tx_receipt
+                .iter()
+                .filter(|op| op.rw() == RW::WRITE)
+                .filter(|op| op.op().field == TxReceiptField::CumulativeGasUsed)
+                .map(|op| op.op().value)
+                .collect()
+        };
+        let block_cumulative_gas_used = cumulative_gas_used(&builder);
+        for (chunk, context) in chunks.iter().zip(contexts.iter()) {
+            assert_eq!(chunk.block.txs.len(), 1);
+            assert_eq!(chunk.block.sha3_inputs.len(), 1);
+            assert_eq!(context.end_state_root, roots[context.initial_tx_index]);
+            assert_eq!(context.tx_id_offset(), context.initial_tx_index);
+            assert_eq!(
+                cumulative_gas_used(chunk),
+                vec![block_cumulative_gas_used[context.initial_tx_index]]
+            );
+        }
+        assert_eq!(contexts[0].rwc_offset(), 0);
+        assert_eq!(
+            contexts[2].end_cumulative_gas_used,
+            builder.block_ctx.cumulative_gas_used
+        );
+    }
+
+    #[test]
+    fn block_split_by_keccak_rows() {
+        let block = sha3_block();
+        let builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let base_rows = keccak_inputs_tx_circuit(&[], builder.block.chain_id.as_u64())
+            .unwrap()
+            .into_iter()
+            .chain(builder.code_db.0.values().cloned())
+            .collect::<HashSet<_>>()
+            .len();
+
+        // Every tx hashes its signature and the SHA3 input, one row each.
+        let roots: Vec<Word> = (1..=3).map(Word::from).collect();
+        let num_chunks = |max_keccak_rows| {
+            let params = CircuitsParams {
+                max_rws: 10_000,
+                max_txs: 3,
+                max_keccak_rows,
+                ..Default::default()
+            };
+            BlockData::new_from_geth_data_with_params(block.clone(), params)
+                .new_circuit_input_builder()
+                .handle_block_in_chunks(&block.eth_block, &block.geth_traces, &roots, |_| 1)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(num_chunks(base_rows + 2), 3);
+        assert_eq!(num_chunks(base_rows + 4), 2);
+        assert_eq!(num_chunks(base_rows + 6), 1);
+    }
+}
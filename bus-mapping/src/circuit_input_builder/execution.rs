@@ -5,11 +5,12 @@ use crate::{
     operation::RWCounter, precompile::PrecompileCalls,
 };
 use eth_types::{
-    evm_types::{Gas, GasCost, OpcodeId, ProgramCounter},
+    evm_types::{Gas, GasCost, Memory, MemoryAddress, OpcodeId, ProgramCounter},
     GethExecStep, Word, H256,
 };
 use gadgets::impl_expr;
 use halo2_proofs::plonk::Expression;
+use std::ops::Range;
 use strum_macros::EnumIter;
 
 /// An execution step of the EVM.
@@ -220,6 +221,9 @@ impl Default for CopyDataType {
 
 impl_expr!(CopyDataType);
 
+/// Number of bytes in a memory word.
+const N_BYTES_WORD: u64 = 32;
+
 /// Defines a single copy step in a copy event. This type is unified over the
 /// source/destination row in the copy table.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -243,6 +247,13 @@ pub enum NumberOrHash {
 /// Defines a copy event associated with EVM opcodes such as CALLDATACOPY,
 /// CODECOPY, CREATE, etc. More information:
 /// <https://github.com/privacy-scaling-explorations/zkevm-specs/blob/master/specs/copy-proof.md>.
+///
+/// Each step copies a 32-byte word. A memory side of the event is extended to
+/// the whole words that contain the copied bytes, so its first word has
+/// [`CopyEvent::src_front_len`] (resp. [`CopyEvent::dst_front_len`]) masked
+/// bytes before the copied bytes and its last word may have masked bytes
+/// after them. Both sides span [`CopyEvent::num_steps`] steps; the steps of a
+/// side past its last copied byte are fully masked.
 #[derive(Clone, Debug)]
 pub struct CopyEvent {
     /// Represents the start address at the source of the copy event.
@@ -266,9 +277,139 @@ pub struct CopyEvent {
     pub rw_counter_start: RWCounter,
     /// Represents the list of (bytes, is_code) copied during this copy event
     pub bytes: Vec<(u8, bool)>,
+    /// Content of the memory words read at the source, starting at the word
+    /// that contains `src_addr`.  Empty unless the source is memory.
+    pub src_memory: Vec<u8>,
+    /// Content of the memory words at the destination before the copy,
+    /// starting at the word that contains `dst_addr`.  Empty unless the
+    /// destination is memory.
+    pub dst_memory_prev: Vec<u8>,
 }
 
 impl CopyEvent {
+    /// Number of read-write steps of a copy of `length` bytes between the
+    /// given source and destination: one per 32-byte word, including the
+    /// masked bytes of the memory words at the edges.
+    pub fn num_steps_of(
+        src_type: CopyDataType,
+        src_addr: u64,
+        dst_type: CopyDataType,
+        dst_addr: u64,
+        length: u64,
+    ) -> u64 {
+        std::cmp::max(
+            Self::side_steps(src_type, src_addr, length),
+            Self::side_steps(dst_type, dst_addr, length),
+        )
+    }
+
+    /// Number of rw operations performed by a copy of `length` bytes between
+    /// the given source and destination.
+    pub fn rw_counter_delta_of(
+        src_type: CopyDataType,
+        src_addr: u64,
+        dst_type: CopyDataType,
+        dst_addr: u64,
+        length: u64,
+    ) -> u64 {
+        let num_steps = Self::num_steps_of(src_type, src_addr, dst_type, dst_addr, length);
+        Self::side_rw_increase(src_type, src_addr, length, num_steps)
+            + Self::side_rw_increase(dst_type, dst_addr, length, num_steps)
+    }
+
+    /// Number of read-write steps of this copy event.
+    pub fn num_steps(&self) -> usize {
+        Self::num_steps_of(
+            self.src_type,
+            self.src_addr,
+            self.dst_type,
+            self.dst_addr,
+            self.bytes.len() as u64,
+        ) as usize
+    }
+
+    /// Number of steps spanned by the source, past which the source steps are
+    /// fully masked.
+    pub fn src_num_steps(&self) -> usize {
+        Self::side_steps(self.src_type, self.src_addr, self.bytes.len() as u64) as usize
+    }
+
+    /// Number of steps spanned by the destination, past which the destination
+    /// steps are fully masked.
+    pub fn dst_num_steps(&self) -> usize {
+        Self::side_steps(self.dst_type, self.dst_addr, self.bytes.len() as u64) as usize
+    }
+
+    /// Number of masked bytes before the first copied byte at the source.
+    pub fn src_front_len(&self) -> usize {
+        Self::front_len(self.src_type, self.src_addr)
+    }
+
+    /// Number of masked bytes before the first copied byte at the
+    /// destination.
+    pub fn dst_front_len(&self) -> usize {
+        Self::front_len(self.dst_type, self.dst_addr)
+    }
+
+    /// Address of the first memory word read at the source.
+    pub fn src_word_addr(&self) -> u64 {
+        self.src_addr - self.src_front_len() as u64
+    }
+
+    /// Address of the first memory word written at the destination.
+    pub fn dst_word_addr(&self) -> u64 {
+        self.dst_addr - self.dst_front_len() as u64
+    }
+
+    /// Read the memory words spanned by the source from `memory`.
+    pub fn read_src_memory(&mut self, memory: &Memory) {
+        self.src_memory = memory.read_chunk(
+            MemoryAddress::from(self.src_word_addr() as usize),
+            MemoryAddress::from(self.src_num_steps() * N_BYTES_WORD as usize),
+        );
+    }
+
+    /// Read the memory words spanned by the destination from `memory`, which
+    /// must not contain the copied bytes yet.
+    pub fn read_dst_memory_prev(&mut self, memory: &Memory) {
+        self.dst_memory_prev = memory.read_chunk(
+            MemoryAddress::from(self.dst_word_addr() as usize),
+            MemoryAddress::from(self.dst_num_steps() * N_BYTES_WORD as usize),
+        );
+    }
+
+    /// Content of the memory words at the destination after the copy.
+    pub fn dst_memory(&self) -> Vec<u8> {
+        let mut memory = self.dst_memory_prev.clone();
+        let front_len = self.dst_front_len();
+        for (byte, (value, _)) in memory[front_len..].iter_mut().zip(self.bytes.iter()) {
+            *byte = *value;
+        }
+        memory
+    }
+
+    /// Indices of the bytes of the word of step `step` that carry copied
+    /// bytes at the source.
+    pub fn src_data_range(&self, step: usize) -> Range<usize> {
+        Self::data_range(self.src_front_len(), self.bytes.len(), step)
+    }
+
+    /// Indices of the bytes of the word of step `step` that carry copied
+    /// bytes at the destination.
+    pub fn dst_data_range(&self, step: usize) -> Range<usize> {
+        Self::data_range(self.dst_front_len(), self.bytes.len(), step)
+    }
+
+    /// Number of bytes copied by the source before step `step`.
+    pub fn src_bytes_done(&self, step: usize) -> usize {
+        Self::bytes_done(self.src_front_len(), self.bytes.len(), step)
+    }
+
+    /// Number of bytes copied by the destination before step `step`.
+    pub fn dst_bytes_done(&self, step: usize) -> usize {
+        Self::bytes_done(self.dst_front_len(), self.bytes.len(), step)
+    }
+
     /// rw counter at step index
     pub fn rw_counter(&self, step_index: usize) -> u64 {
         u64::try_from(self.rw_counter_start.0).unwrap() + self.rw_counter_increase(step_index)
@@ -276,31 +417,67 @@ impl CopyEvent {
 
     /// rw counter increase left at step index
     pub fn rw_counter_increase_left(&self, step_index: usize) -> u64 {
-        self.rw_counter(self.bytes.len() * 2) - self.rw_counter(step_index)
+        self.rw_counter(self.num_steps() * 2) - self.rw_counter(step_index)
     }
 
     /// Number of rw operations performed by this copy event
     pub fn rw_counter_delta(&self) -> u64 {
-        self.rw_counter_increase(self.bytes.len() * 2)
+        self.rw_counter_increase(self.num_steps() * 2)
+    }
+
+    fn front_len(data_type: CopyDataType, addr: u64) -> usize {
+        if data_type == CopyDataType::Memory {
+            (addr % N_BYTES_WORD) as usize
+        } else {
+            0
+        }
+    }
+
+    fn side_steps(data_type: CopyDataType, addr: u64, length: u64) -> u64 {
+        if length == 0 {
+            0
+        } else {
+            (Self::front_len(data_type, addr) as u64 + length + N_BYTES_WORD - 1) / N_BYTES_WORD
+        }
+    }
+
+    fn data_range(front_len: usize, length: usize, step: usize) -> Range<usize> {
+        let word_start = step * N_BYTES_WORD as usize;
+        let word_end = word_start + N_BYTES_WORD as usize;
+        let index = |addr: usize| addr.clamp(word_start, word_end) - word_start;
+        index(front_len)..index(front_len + length)
+    }
+
+    fn bytes_done(front_len: usize, length: usize, step: usize) -> usize {
+        (step * N_BYTES_WORD as usize)
+            .saturating_sub(front_len)
+            .min(length)
+    }
+
+    // number of rw operations done by one side of the copy in its first
+    // `steps` steps.  Memory words are accessed once per step spanned by the
+    // side, and tx log bytes are written one by one.
+    fn side_rw_increase(data_type: CopyDataType, addr: u64, length: u64, steps: u64) -> u64 {
+        match data_type {
+            CopyDataType::Memory => std::cmp::min(steps, Self::side_steps(data_type, addr, length)),
+            CopyDataType::TxLog => std::cmp::min(steps * N_BYTES_WORD, length),
+            CopyDataType::Bytecode | CopyDataType::TxCalldata | CopyDataType::RlcAcc => 0,
+            CopyDataType::Padding => unreachable!(),
+        }
     }
 
     // increase in rw counter from the start of the copy event to step index
     fn rw_counter_increase(&self, step_index: usize) -> u64 {
-        let source_rw_increase = match self.src_type {
-            CopyDataType::Bytecode | CopyDataType::TxCalldata => 0,
-            CopyDataType::Memory => std::cmp::min(
-                u64::try_from(step_index + 1).unwrap() / 2,
-                self.src_addr_end
-                    .checked_sub(self.src_addr)
-                    .unwrap_or_default(),
-            ),
-            CopyDataType::RlcAcc | CopyDataType::TxLog | CopyDataType::Padding => unreachable!(),
-        };
-        let destination_rw_increase = match self.dst_type {
-            CopyDataType::RlcAcc | CopyDataType::Bytecode => 0,
-            CopyDataType::TxLog | CopyDataType::Memory => u64::try_from(step_index).unwrap() / 2,
-            CopyDataType::TxCalldata | CopyDataType::Padding => unreachable!(),
-        };
+        let length = self.bytes.len() as u64;
+        // the source of a step is read before its destination is written.
+        let source_rw_increase = Self::side_rw_increase(
+            self.src_type,
+            self.src_addr,
+            length,
+            (step_index as u64 + 1) / 2,
+        );
+        let destination_rw_increase =
+            Self::side_rw_increase(self.dst_type, self.dst_addr, length, step_index as u64 / 2);
         source_rw_increase + destination_rw_increase
     }
 }
@@ -2,8 +2,8 @@
 
 use super::{
     get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, Transaction,
-    TransactionContext,
+    CallKind, CodeSource, CopyDataType, CopyEvent, ExecState, ExecStep, ExpEvent, NumberOrHash,
+    Transaction, TransactionContext,
 };
 use crate::{
     error::{get_step_reported_error, ExecError},
@@ -46,7 +46,7 @@ impl<'a> CircuitInputStateRef<'a> {
     /// Check if is a anchor transaction.
     pub fn is_anchor_tx(&self) -> bool {
         // set protocol_instance and is the first tx
-        self.block.is_taiko() && self.tx_ctx.is_first_tx()
+        self.block.is_taiko() && self.tx_ctx.is_first_tx_in_block()
     }
 
     /// Create a new step from a `GethExecStep`
@@ -197,37 +197,51 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
-    /// Push a read type [`MemoryOp`] into the
+    /// Push a read type [`MemoryOp`] of the word at `address` in the memory
+    /// of the current call into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter) and `call_id`, and then
     /// adds a reference to the stored operation ([`OperationRef`]) inside
     /// the bus-mapping instance of the current [`ExecStep`].  Then increase
     /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter) by one.
-    pub fn memory_read(
+    /// Returns the word read.
+    pub fn memory_read_word(
         &mut self,
         step: &mut ExecStep,
         address: MemoryAddress,
-        value: u8,
-    ) -> Result<(), Error> {
+    ) -> Result<Word, Error> {
         let call_id = self.call()?.call_id;
-        self.push_op(step, RW::READ, MemoryOp::new(call_id, address, value));
-        Ok(())
+        let value = self.call_ctx()?.memory.read_word(address);
+        self.push_op(
+            step,
+            RW::READ,
+            MemoryOp::new(call_id, address, value, value),
+        );
+        Ok(value)
     }
 
-    /// Push a write type [`MemoryOp`] into the
+    /// Push a write type [`MemoryOp`] of the word at `address` in the memory
+    /// of the current call into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter) and `call_id`, and then
     /// adds a reference to the stored operation ([`OperationRef`]) inside
     /// the bus-mapping instance of the current [`ExecStep`].  Then increase
     /// the `block_ctx` [`RWCounter`](crate::operation::RWCounter)  by one.
-    pub fn memory_write(
+    /// The previous value of the word is read from the memory of the call,
+    /// which is left untouched.
+    pub fn memory_write_word(
         &mut self,
         step: &mut ExecStep,
         address: MemoryAddress,
-        value: u8,
+        value: Word,
     ) -> Result<(), Error> {
         let call_id = self.call()?.call_id;
-        self.push_op(step, RW::WRITE, MemoryOp::new(call_id, address, value));
+        let value_prev = self.call_ctx()?.memory.read_word(address);
+        self.push_op(
+            step,
+            RW::WRITE,
+            MemoryOp::new(call_id, address, value, value_prev),
+        );
         Ok(())
     }
 
@@ -699,7 +713,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Check if address is a precompiled or not.
     pub fn is_precompiled(&self, address: &Address) -> bool {
-        address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+        self.block.chain_spec.is_precompiled(address)
     }
 
     /// Parse [`Call`] from a *CALL*/CREATE* step.
@@ -938,6 +952,7 @@ impl<'a> CircuitInputStateRef<'a> {
                             caller_ctx.return_data[0..length]
                                 .copy_from_slice(&callee_memory.0[offset..offset + length]);
                         }
+                        caller_ctx.last_callee_memory = callee_memory;
                         (offset, length)
                     }
                     _ => {
@@ -1145,8 +1160,63 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
-    /// Push a copy event to the state.
+    /// Push the rw operations of a copy event, in the order in which the copy
+    /// circuit looks them up, and then push the copy event to the state.  The
+    /// memory words of the event must have been read beforehand.
     pub fn push_copy(&mut self, step: &mut ExecStep, event: CopyEvent) {
+        debug_assert_eq!(event.rw_counter_start, self.block_ctx.rwc);
+        let dst_memory = event.dst_memory();
+        let number = |id: &NumberOrHash| match id {
+            NumberOrHash::Number(number) => *number,
+            NumberOrHash::Hash(_) => unreachable!("memory and tx log ids are numbers"),
+        };
+        for step_idx in 0..event.num_steps() {
+            let word_range = step_idx * 32..(step_idx + 1) * 32;
+            if event.src_type == CopyDataType::Memory && step_idx < event.src_num_steps() {
+                let word = Word::from_big_endian(&event.src_memory[word_range.clone()]);
+                self.push_op(
+                    step,
+                    RW::READ,
+                    MemoryOp::new(
+                        number(&event.src_id),
+                        (event.src_word_addr() as usize + word_range.start).into(),
+                        word,
+                        word,
+                    ),
+                );
+            }
+            match event.dst_type {
+                CopyDataType::Memory if step_idx < event.dst_num_steps() => {
+                    self.push_op(
+                        step,
+                        RW::WRITE,
+                        MemoryOp::new(
+                            number(&event.dst_id),
+                            (event.dst_word_addr() as usize + word_range.start).into(),
+                            Word::from_big_endian(&dst_memory[word_range.clone()]),
+                            Word::from_big_endian(&event.dst_memory_prev[word_range]),
+                        ),
+                    );
+                }
+                CopyDataType::TxLog => {
+                    let bytes_done = event.dst_bytes_done(step_idx);
+                    for byte_idx in bytes_done..bytes_done + event.dst_data_range(step_idx).len() {
+                        self.push_op(
+                            step,
+                            RW::WRITE,
+                            TxLogOp::new(
+                                number(&event.dst_id),
+                                event.log_id.expect("tx log destination has a log id") as usize,
+                                TxLogField::Data,
+                                event.dst_addr as usize + byte_idx,
+                                Word::from(event.bytes[byte_idx].0),
+                            ),
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
         step.copy_rw_counter_delta += event.rw_counter_delta();
         self.block.add_copy_event(event);
     }
@@ -1165,7 +1235,11 @@ impl<'a> CircuitInputStateRef<'a> {
             return Ok(Some(get_step_reported_error(&step.op, error)));
         }
 
-        if matches!(step.op, OpcodeId::INVALID(_)) {
+        // Opcodes of later hardforks are undefined, which geth doesn't report
+        // in the step.
+        if matches!(step.op, OpcodeId::INVALID(_))
+            || !self.block.chain_spec.is_opcode_available(step.op)
+        {
             return Ok(Some(ExecError::InvalidOpcode));
         }
 
@@ -1360,11 +1434,9 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Generate copy steps for bytecode.
     pub(crate) fn gen_copy_steps_for_bytecode(
-        &mut self,
-        exec_step: &mut ExecStep,
+        &self,
         bytecode: &Bytecode,
         src_addr: u64,
-        dst_addr: u64,
         src_addr_end: u64,
         bytes_left: u64,
     ) -> Result<Vec<(u8, bool)>, Error> {
@@ -1378,7 +1450,6 @@ impl<'a> CircuitInputStateRef<'a> {
                 (0, false)
             };
             copy_steps.push(step);
-            self.memory_write(exec_step, (dst_addr + idx).into(), step.0)?;
         }
 
         Ok(copy_steps)
@@ -1386,10 +1457,8 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Generate copy steps for call data.
     pub(crate) fn gen_copy_steps_for_call_data(
-        &mut self,
-        exec_step: &mut ExecStep,
+        &self,
         src_addr: u64,
-        dst_addr: u64,
         src_addr_end: u64,
         bytes_left: u64,
     ) -> Result<Vec<(u8, bool)>, Error> {
@@ -1397,21 +1466,11 @@ impl<'a> CircuitInputStateRef<'a> {
         for idx in 0..bytes_left {
             let addr = src_addr.checked_add(idx).unwrap_or(src_addr_end);
             let value = if addr < src_addr_end {
-                let byte =
-                    self.call_ctx()?.call_data[(addr - self.call()?.call_data_offset) as usize];
-                if !self.call()?.is_root {
-                    self.push_op(
-                        exec_step,
-                        RW::READ,
-                        MemoryOp::new(self.call()?.caller_id, addr.into(), byte),
-                    );
-                }
-                byte
+                self.call_ctx()?.call_data[(addr - self.call()?.call_data_offset) as usize]
             } else {
                 0
             };
             copy_steps.push((value, false));
-            self.memory_write(exec_step, (dst_addr + idx).into(), value)?;
         }
 
         Ok(copy_steps)
@@ -0,0 +1,386 @@
+//! Validation of the state left by a block in the [`StateDB`] against the
+//! post-state computed by geth.
+
+use super::CircuitInputBuilder;
+use crate::{
+    state_db::{CodeDB, StateDB},
+    Error,
+};
+use eth_types::{
+    geth_types, Address, GethPrestateAccount, GethPrestateDiffTrace, Hash, ToWord, Word,
+};
+use std::{collections::BTreeMap, fmt};
+
+/// Expected state of an account after a block.  Fields set to `None` and
+/// storage slots that are absent aren't checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostStateAccount {
+    /// Balance
+    pub balance: Option<Word>,
+    /// Nonce
+    pub nonce: Option<u64>,
+    /// Code hash
+    pub code_hash: Option<Hash>,
+    /// Storage slots
+    pub storage: BTreeMap<Word, Word>,
+}
+
+impl PostStateAccount {
+    fn update(&mut self, post: &GethPrestateAccount) {
+        if let Some(balance) = post.balance {
+            self.balance = Some(balance);
+        }
+        if let Some(nonce) = post.nonce {
+            self.nonce = Some(nonce);
+        }
+        if let Some(code) = &post.code {
+            self.code_hash = Some(CodeDB::hash(code));
+        }
+        for (key, value) in post.storage.iter().flatten() {
+            self.storage.insert(key.to_word(), value.to_word());
+        }
+    }
+}
+
+/// Expected state of the accounts touched by a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostState(pub BTreeMap<Address, PostStateAccount>);
+
+impl PostState {
+    /// Build the post-state from the geth `prestateTracer` traces in diff mode
+    /// of every tx of a block, in order.  Only the accounts modified by the
+    /// block are described, with the fields that changed.
+    pub fn from_prestate_diffs(diffs: &[GethPrestateDiffTrace]) -> Self {
+        let mut accounts: BTreeMap<Address, PostStateAccount> = BTreeMap::new();
+        for diff in diffs {
+            for (address, pre) in &diff.pre {
+                let account = accounts.entry(*address).or_default();
+                match diff.post.get(address) {
+                    Some(post) => {
+                        // Slots cleared by the tx are only reported in the pre-state
+                        for key in pre.storage.iter().flatten().map(|(key, _)| key) {
+                            account.storage.insert(key.to_word(), Word::zero());
+                        }
+                        account.update(post);
+                    }
+                    // The account was deleted by the tx
+                    None => {
+                        account.balance = Some(Word::zero());
+                        account.nonce = Some(0);
+                        account.code_hash = Some(CodeDB::empty_code_hash());
+                        for value in account.storage.values_mut() {
+                            *value = Word::zero();
+                        }
+                        for key in pre.storage.iter().flatten().map(|(key, _)| key) {
+                            account.storage.insert(key.to_word(), Word::zero());
+                        }
+                    }
+                }
+            }
+            // Accounts created by the tx only appear in the post-state
+            for (address, post) in &diff.post {
+                if !diff.pre.contains_key(address) {
+                    accounts.entry(*address).or_default().update(post);
+                }
+            }
+        }
+        Self(accounts)
+    }
+
+    /// Build the post-state from the full state of some accounts, like the
+    /// expected state reported by test fixtures.
+    pub fn from_accounts<'a>(accounts: impl IntoIterator<Item = &'a geth_types::Account>) -> Self {
+        Self(
+            accounts
+                .into_iter()
+                .map(|account| {
+                    let expected = PostStateAccount {
+                        balance: Some(account.balance),
+                        nonce: Some(account.nonce.as_u64()),
+                        code_hash: Some(CodeDB::hash(&account.code)),
+                        storage: account
+                            .storage
+                            .iter()
+                            .map(|(key, value)| (*key, *value))
+                            .collect(),
+                    };
+                    (account.address, expected)
+                })
+                .collect(),
+        )
+    }
+
+    /// Differences between the expected post-state and the state in `sdb`.
+    pub fn diff(&self, sdb: &StateDB) -> PostStateDiff {
+        let mut diff = PostStateDiff::default();
+        for (address, expected) in &self.0 {
+            let (_, account) = sdb.get_account(address);
+            let mut mismatches = Vec::new();
+            if let Some(balance) = expected.balance {
+                if balance != account.balance {
+                    mismatches.push(PostStateMismatch::Balance {
+                        expected: balance,
+                        found: account.balance,
+                    });
+                }
+            }
+            if let Some(nonce) = expected.nonce {
+                if nonce != account.nonce {
+                    mismatches.push(PostStateMismatch::Nonce {
+                        expected: nonce,
+                        found: account.nonce,
+                    });
+                }
+            }
+            if let Some(code_hash) = expected.code_hash {
+                // Accounts fetched without code have a zero code hash
+                let found = if account.code_hash.is_zero() {
+                    CodeDB::empty_code_hash()
+                } else {
+                    account.code_hash
+                };
+                if code_hash != found {
+                    mismatches.push(PostStateMismatch::CodeHash {
+                        expected: code_hash,
+                        found,
+                    });
+                }
+            }
+            for (key, value) in &expected.storage {
+                let (_, found) = sdb.get_storage(address, key);
+                if value != found {
+                    mismatches.push(PostStateMismatch::Storage {
+                        key: *key,
+                        expected: *value,
+                        found: *found,
+                    });
+                }
+            }
+            if !mismatches.is_empty() {
+                diff.0.insert(*address, mismatches);
+            }
+        }
+        diff
+    }
+}
+
+/// Field of an account whose value differs from the expected post-state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostStateMismatch {
+    /// Balance
+    Balance {
+        /// Expected value
+        expected: Word,
+        /// Value in the StateDB
+        found: Word,
+    },
+    /// Nonce
+    Nonce {
+        /// Expected value
+        expected: u64,
+        /// Value in the StateDB
+        found: u64,
+    },
+    /// Code hash
+    CodeHash {
+        /// Expected value
+        expected: Hash,
+        /// Value in the StateDB
+        found: Hash,
+    },
+    /// Storage slot
+    Storage {
+        /// Storage key
+        key: Word,
+        /// Expected value
+        expected: Word,
+        /// Value in the StateDB
+        found: Word,
+    },
+}
+
+/// Mismatches between the [`StateDB`] and the expected post-state, by
+/// account.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostStateDiff(pub BTreeMap<Address, Vec<PostStateMismatch>>);
+
+impl PostStateDiff {
+    /// Whether the StateDB matches the expected post-state.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for PostStateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (address, mismatches) in &self.0 {
+            for mismatch in mismatches {
+                match mismatch {
+                    PostStateMismatch::Balance { expected, found } => writeln!(
+                        f,
+                        "{:?}: balance expected {} found {}",
+                        address, expected, found
+                    )?,
+                    PostStateMismatch::Nonce { expected, found } => writeln!(
+                        f,
+                        "{:?}: nonce expected {} found {}",
+                        address, expected, found
+                    )?,
+                    PostStateMismatch::CodeHash { expected, found } => writeln!(
+                        f,
+                        "{:?}: code hash expected {:?} found {:?}",
+                        address, expected, found
+                    )?,
+                    PostStateMismatch::Storage {
+                        key,
+                        expected,
+                        found,
+                    } => writeln!(
+                        f,
+                        "{:?}: storage[{:#x}] expected {:#x} found {:#x}",
+                        address, key, expected, found
+                    )?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CircuitInputBuilder {
+    /// Check that the state left in the [`StateDB`] by the handled block
+    /// matches `post_state`, returning the differences otherwise.
+    pub fn check_post_state(&self, post_state: &PostState) -> Result<(), Error> {
+        let diff = post_state.diff(&self.sdb);
+        if diff.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::PostStateMismatch(Box::new(diff)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, H256};
+    use mock::{TestContext, MOCK_ACCOUNTS};
+    use std::collections::HashMap;
+
+    #[test]
+    fn post_state_from_prestate_diffs() {
+        let slot = |value: u64| H256::from_low_u64_be(value);
+        let diffs = vec![GethPrestateDiffTrace {
+            pre: HashMap::from([
+                (
+                    MOCK_ACCOUNTS[0],
+                    GethPrestateAccount {
+                        balance: Some(Word::from(0x10)),
+                        nonce: Some(1),
+                        code: None,
+                        storage: Some(HashMap::from([(slot(1), slot(2)), (slot(2), slot(3))])),
+                    },
+                ),
+                (
+                    MOCK_ACCOUNTS[1],
+                    GethPrestateAccount {
+                        balance: Some(Word::from(1)),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            post: HashMap::from([
+                (
+                    MOCK_ACCOUNTS[0],
+                    GethPrestateAccount {
+                        nonce: Some(2),
+                        storage: Some(HashMap::from([(slot(1), slot(4))])),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    MOCK_ACCOUNTS[2],
+                    GethPrestateAccount {
+                        balance: Some(Word::from(5)),
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        }];
+
+        let post_state = PostState::from_prestate_diffs(&diffs);
+        assert_eq!(
+            post_state.0[&MOCK_ACCOUNTS[0]],
+            PostStateAccount {
+                balance: None,
+                nonce: Some(2),
+                code_hash: None,
+                storage: BTreeMap::from([
+                    (Word::from(1), Word::from(4)),
+                    (Word::from(2), Word::zero())
+                ]),
+            }
+        );
+        // Deleted account
+        assert_eq!(post_state.0[&MOCK_ACCOUNTS[1]].balance, Some(Word::zero()));
+        assert_eq!(post_state.0[&MOCK_ACCOUNTS[1]].nonce, Some(0));
+        // Created account
+        assert_eq!(post_state.0[&MOCK_ACCOUNTS[2]].balance, Some(Word::from(5)));
+    }
+
+    #[test]
+    fn post_state_mismatch() {
+        let code = bytecode! {
+            PUSH1(0x2a)
+            PUSH1(0x01)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let sender = &block.accounts[1];
+        let mut post_state = PostState::default();
+        post_state.0.insert(
+            sender.address,
+            PostStateAccount {
+                nonce: Some(sender.nonce.as_u64() + 1),
+                ..Default::default()
+            },
+        );
+        post_state.0.insert(
+            block.accounts[0].address,
+            PostStateAccount {
+                code_hash: Some(CodeDB::hash(&block.accounts[0].code)),
+                storage: BTreeMap::from([(Word::from(1), Word::from(0x2a))]),
+                ..Default::default()
+            },
+        );
+        builder.check_post_state(&post_state).unwrap();
+
+        // A wrong expected value is reported
+        post_state
+            .0
+            .get_mut(&block.accounts[0].address)
+            .unwrap()
+            .storage
+            .insert(Word::from(1), Word::from(0x2b));
+        match builder.check_post_state(&post_state) {
+            Err(Error::PostStateMismatch(diff)) => assert_eq!(
+                diff.0[&block.accounts[0].address],
+                vec![PostStateMismatch::Storage {
+                    key: Word::from(1),
+                    expected: Word::from(0x2b),
+                    found: Word::from(0x2a),
+                }]
+            ),
+            res => panic!("unexpected result {:?}", res),
+        }
+    }
+}
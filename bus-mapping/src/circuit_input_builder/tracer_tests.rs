@@ -10,14 +10,14 @@ use crate::{
 };
 use eth_types::{
     address, bytecode,
-    evm_types::{stack::Stack, Gas, OpcodeId},
+    evm_types::{stack::Stack, ChainSpec, Gas, Hardfork, OpcodeId},
     geth_types::GethData,
     word, Bytecode, Hash, ToAddress, ToWord, Word,
 };
 use lazy_static::lazy_static;
 use mock::{
     test_ctx::{helpers::*, LoggerConfig, TestContext},
-    MOCK_COINBASE,
+    TestContextBuilder, MOCK_ACCOUNTS, MOCK_COINBASE,
 };
 use pretty_assertions::assert_eq;
 use std::collections::HashSet;
@@ -1493,6 +1493,44 @@ fn tracer_err_invalid_opcode() {
     );
 }
 
+#[test]
+fn tracer_err_unavailable_opcode() {
+    // PUSH0 and BASEFEE are undefined before the hardforks introducing them
+    for (hardfork, opcode) in [
+        (Hardfork::Berlin, OpcodeId::BASEFEE),
+        (Hardfork::London, OpcodeId::PUSH0),
+    ] {
+        let mut code = Bytecode::default();
+        code.write_op(opcode).op_stop();
+        let blocks = TestContextBuilder::new()
+            .chain_spec(ChainSpec::new(hardfork))
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[0]).code(code);
+            })
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[1])
+                    .balance(Word::from(1u64 << 20));
+            })
+            .tx(|tx| {
+                tx.from(MOCK_ACCOUNTS[1]).to(MOCK_ACCOUNTS[0]);
+            })
+            .build()
+            .unwrap();
+        let block = &blocks[0];
+
+        let step = &block.geth_traces[0].struct_logs[0];
+        let next_step = block.geth_traces[0].struct_logs.get(1);
+        assert_eq!(step.op, opcode);
+        assert!(block.geth_traces[0].failed);
+
+        let mut builder = CircuitInputBuilderTx::new(block, step);
+        assert_eq!(
+            builder.state_ref().get_step_err(step, next_step).unwrap(),
+            Some(ExecError::InvalidOpcode)
+        );
+    }
+}
+
 #[test]
 fn test_tracer_err_write_protection() {
     // test write_protection error happens in sstore
@@ -15,8 +15,11 @@ use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, EthBl
 #[derive(Debug, Default)]
 /// Context of a [`Transaction`] which can mutate in an [`ExecStep`].
 pub struct TransactionContext {
-    /// Unique identifier of transaction of the block. The value is `index + 1`.
-    id: usize,
+    /// Unique identifier of transaction of the block. The value is `index + 1`,
+    /// where `index` counts across all the blocks handled by the builder.
+    pub(crate) id: usize,
+    /// Identifier if this transaction is the first one of its block or not.
+    is_first_tx_in_block: bool,
     /// The index of logs made in the transaction.
     pub(crate) log_id: usize,
     /// Identifier if this transaction is last one of the block or not.
@@ -67,12 +70,13 @@ impl TransactionContext {
                 .collect()
         };
 
+        let tx_index = eth_tx
+            .transaction_index
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+            .as_u64() as usize;
         let mut tx_ctx = Self {
-            id: eth_tx
-                .transaction_index
-                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
-                .as_u64() as usize
-                + 1,
+            id: tx_index + 1,
+            is_first_tx_in_block: tx_index == 0,
             log_id: 0,
             is_last_tx,
             call_is_success,
@@ -94,6 +98,11 @@ impl TransactionContext {
         self.id == 1
     }
 
+    /// Return true if it is the first transaction of its block.
+    pub fn is_first_tx_in_block(&self) -> bool {
+        self.is_first_tx_in_block
+    }
+
     /// Return is_last_tx of the this transaction.
     pub fn is_last_tx(&self) -> bool {
         self.is_last_tx
@@ -166,6 +175,7 @@ impl TransactionContext {
             call_data,
             memory: Memory::default(),
             return_data: vec![],
+            last_callee_memory: Memory::default(),
         });
     }
 
@@ -186,6 +196,8 @@ impl TransactionContext {
 pub struct Transaction {
     /// The raw transaction fields
     pub tx: geth_types::Transaction,
+    /// Number of the block which includes the transaction
+    pub block_num: u64,
     /// Calls made in the transaction
     pub(crate) calls: Vec<Call>,
     /// Execution steps
@@ -264,6 +276,10 @@ impl Transaction {
 
         Ok(Self {
             tx,
+            block_num: eth_block
+                .number
+                .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?
+                .as_u64(),
             calls: vec![call],
             steps: Vec::new(),
         })
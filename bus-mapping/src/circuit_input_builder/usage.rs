@@ -0,0 +1,266 @@
+//! Incremental inclusion of transactions in a block, checking that the
+//! circuits can still prove it under fixed [`CircuitsParams`].
+
+use super::{keccak_inputs, BlockContext, CircuitInputBuilder, CircuitsParams, EthBlock, ExecStep};
+use crate::{
+    operation::{CallContextField, CallContextOp, Operation, OperationContainerLens, RW},
+    Error,
+};
+use eth_types::{GethExecTrace, Word};
+use itertools::Itertools;
+
+/// Rows used by the circuits which depend on their layout, and so are not
+/// known by the bus-mapping.
+pub trait CircuitsRowCounter {
+    /// Rows used by an execution step in the EVM circuit
+    fn evm_step_rows(&self, step: &ExecStep) -> usize;
+    /// Rows of the Keccak circuit needed to hash the (distinct) `inputs`,
+    /// comparable with [`CircuitsParams::max_keccak_rows`]
+    fn keccak_rows(&self, inputs: &[Vec<u8>]) -> usize;
+}
+
+/// Circuit limits of [`CircuitsParams`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitLimit {
+    /// [`CircuitsParams::max_rws`]
+    Rws,
+    /// [`CircuitsParams::max_txs`]
+    Txs,
+    /// [`CircuitsParams::max_calldata`]
+    Calldata,
+    /// [`CircuitsParams::max_copy_rows`]
+    CopyRows,
+    /// [`CircuitsParams::max_exp_steps`]
+    ExpSteps,
+    /// [`CircuitsParams::max_bytecode`]
+    Bytecode,
+    /// [`CircuitsParams::max_keccak_rows`]
+    KeccakRows,
+    /// [`CircuitsParams::max_evm_rows`]
+    EvmRows,
+}
+
+/// Usage of each circuit by the transactions handled so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CircuitsUsage {
+    /// Read-write operations, including the ones done by the EndBlock step
+    pub rws: usize,
+    /// Transactions
+    pub txs: usize,
+    /// Bytes of calldata of all the transactions
+    pub calldata: usize,
+    /// Rows of the Copy circuit
+    pub copy_rows: usize,
+    /// Steps of the Exponentiation circuit
+    pub exp_steps: usize,
+    /// Rows of the Bytecode circuit, one per byte plus one per bytecode
+    pub bytecode_bytes: usize,
+    /// Rows of the Keccak circuit
+    pub keccak_rows: usize,
+    /// Rows of the EVM circuit, including the last EndBlock step
+    pub evm_rows: usize,
+}
+
+impl CircuitsUsage {
+    /// Limits of `params` exceeded by this usage.  The EVM and Keccak rows
+    /// are only checked when their limit is set, since a zero limit means that
+    /// the circuit is sized by its witness.
+    pub fn overflows(&self, params: &CircuitsParams) -> Vec<CircuitLimit> {
+        [
+            // The State circuit needs at least one Start row
+            (CircuitLimit::Rws, self.rws + 1 > params.max_rws),
+            (CircuitLimit::Txs, self.txs > params.max_txs),
+            (CircuitLimit::Calldata, self.calldata > params.max_calldata),
+            (
+                CircuitLimit::CopyRows,
+                self.copy_rows > params.max_copy_rows,
+            ),
+            (
+                CircuitLimit::ExpSteps,
+                self.exp_steps > params.max_exp_steps,
+            ),
+            (
+                CircuitLimit::Bytecode,
+                self.bytecode_bytes > params.max_bytecode,
+            ),
+            (
+                CircuitLimit::KeccakRows,
+                params.max_keccak_rows != 0 && self.keccak_rows > params.max_keccak_rows,
+            ),
+            (
+                CircuitLimit::EvmRows,
+                params.max_evm_rows != 0 && self.evm_rows > params.max_evm_rows,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(limit, overflow)| overflow.then_some(limit))
+        .collect()
+    }
+}
+
+/// Error returned when including a transaction would exceed some limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitsOverflow {
+    /// Usage of the circuits with the rejected transaction
+    pub usage: CircuitsUsage,
+    /// Limits exceeded
+    pub limits: Vec<CircuitLimit>,
+}
+
+/// State of a [`CircuitInputBuilder`] before handling a transaction.  The
+/// accounts and codes changed by the transaction are recorded by the journals
+/// of the [`StateDB`](crate::state_db::StateDB) and
+/// [`CodeDB`](crate::state_db::CodeDB).
+struct Checkpoint {
+    block_ctx: BlockContext,
+    container: OperationContainerLens,
+    txs: usize,
+    // EndTx step of the previous transaction, before it's linked to the new one
+    prev_end_tx: Option<ExecStep>,
+    copy_events: usize,
+    exp_events: usize,
+    sha3_inputs: usize,
+}
+
+impl CircuitInputBuilder {
+    /// Handle one more transaction of `eth_block` with its execution trace,
+    /// and return the usage of the circuits with it included.  If a limit of
+    /// the block [`CircuitsParams`] is exceeded, or the transaction can't be
+    /// handled, the builder is rolled back to its state before the call and
+    /// the error is returned.
+    ///
+    /// Transactions are handled as if each was the last one of the block, so
+    /// all the transactions of the block must be included with this method,
+    /// and [`Self::finalize_block`] called once done.
+    pub fn try_handle_tx(
+        &mut self,
+        eth_block: &EthBlock,
+        eth_tx: &eth_types::Transaction,
+        geth_trace: &GethExecTrace,
+        counter: &impl CircuitsRowCounter,
+    ) -> Result<CircuitsUsage, Error> {
+        let checkpoint = self.checkpoint();
+        let usage = self
+            .link_last_tx()
+            .and_then(|_| self.handle_tx(eth_block, eth_tx, geth_trace, true))
+            .and_then(|_| self.circuits_usage(counter));
+        let err = match usage {
+            Ok(usage) => {
+                let limits = usage.overflows(&self.block.circuits_params);
+                if limits.is_empty() {
+                    self.sdb.discard_journal();
+                    self.code_db.discard_journal();
+                    return Ok(usage);
+                }
+                Error::CircuitsOverflow(Box::new(CircuitsOverflow { usage, limits }))
+            }
+            Err(err) => err,
+        };
+        self.rollback(checkpoint);
+        Err(err)
+    }
+
+    /// Complete the block after its transactions were included with
+    /// [`Self::try_handle_tx`].
+    pub fn finalize_block(&mut self) {
+        self.set_value_ops_call_context_rwc_eor();
+        self.set_end_block();
+    }
+
+    /// Usage of the circuits by the transactions handled so far.
+    pub fn circuits_usage(
+        &self,
+        counter: &impl CircuitsRowCounter,
+    ) -> Result<CircuitsUsage, Error> {
+        let txs = &self.block.txs;
+        let keccak_inputs = keccak_inputs(&self.block, &self.code_db)?
+            .into_iter()
+            .unique()
+            .collect_vec();
+        Ok(CircuitsUsage {
+            // The EndBlock step reads the id of the last tx
+            rws: self.block_ctx.rwc.0 - 1 + usize::from(!txs.is_empty()),
+            txs: txs.len(),
+            calldata: txs.iter().map(|tx| tx.tx.call_data.len()).sum(),
+            copy_rows: self
+                .block
+                .copy_events
+                .iter()
+                .map(|event| event.num_steps() * 2)
+                .sum::<usize>()
+                + 2,
+            exp_steps: self
+                .block
+                .exp_events
+                .iter()
+                .map(|event| event.steps.len())
+                .sum(),
+            bytecode_bytes: self.code_db.0.values().map(|code| code.len() + 1).sum(),
+            keccak_rows: counter.keccak_rows(&keccak_inputs),
+            evm_rows: txs
+                .iter()
+                .flat_map(|tx| tx.steps())
+                .map(|step| counter.evm_step_rows(step))
+                .sum::<usize>()
+                + 1,
+        })
+    }
+
+    /// Write the id of the next transaction at the end of the last one, which
+    /// was handled as the last transaction of the block.
+    fn link_last_tx(&mut self) -> Result<(), Error> {
+        let next_tx_id = self.block.txs.len() + 1;
+        let end_tx_step = match self.block.txs.last_mut() {
+            Some(tx) => tx
+                .steps_mut()
+                .last_mut()
+                .ok_or(Error::InternalError("transaction without EndTx step"))?,
+            None => return Ok(()),
+        };
+        let rwc = self.block_ctx.rwc.inc_pre();
+        // The next transaction's call id is the rw counter after this write
+        let op = CallContextOp {
+            call_id: rwc.0 + 1,
+            field: CallContextField::TxId,
+            value: Word::from(next_tx_id),
+        };
+        let op_ref = self
+            .block
+            .container
+            .insert(Operation::new(rwc, RW::WRITE, op));
+        end_tx_step.bus_mapping_instance.push(op_ref);
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) -> Checkpoint {
+        self.sdb.start_journal();
+        self.code_db.start_journal();
+        Checkpoint {
+            block_ctx: self.block_ctx.clone(),
+            container: self.block.container.lens(),
+            txs: self.block.txs.len(),
+            prev_end_tx: self
+                .block
+                .txs
+                .last()
+                .and_then(|tx| tx.steps().last().cloned()),
+            copy_events: self.block.copy_events.len(),
+            exp_events: self.block.exp_events.len(),
+            sha3_inputs: self.block.sha3_inputs.len(),
+        }
+    }
+
+    fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.sdb.revert_journal();
+        self.code_db.revert_journal();
+        self.block_ctx = checkpoint.block_ctx;
+        self.block.container.truncate(checkpoint.container);
+        self.block.txs.truncate(checkpoint.txs);
+        if let (Some(tx), Some(end_tx_step)) = (self.block.txs.last_mut(), checkpoint.prev_end_tx) {
+            *tx.steps_mut().last_mut().expect("EndTx step") = end_tx_step;
+        }
+        self.block.copy_events.truncate(checkpoint.copy_events);
+        self.block.exp_events.truncate(checkpoint.exp_events);
+        self.block.sha3_inputs.truncate(checkpoint.sha3_inputs);
+    }
+}
@@ -0,0 +1,251 @@
+//! Coverage of the execution steps, RW targets and copy data types reached by
+//! the handled blocks.
+//!
+//! With the `coverage` feature, [`CircuitInputBuilder::handle_blocks`] adds
+//! every handled block to the coverage of the process, which is written to its
+//! own JSON report in the directory set in the [`COVERAGE_ENV`] environment
+//! variable.  Each process writes a separate report, so that concurrent test
+//! binaries never write to the same file; [`Coverage::load_dir`] merges them to
+//! add up the runs of a test corpus.
+//!
+//! [`CircuitInputBuilder::handle_blocks`]:
+//! crate::circuit_input_builder::CircuitInputBuilder::handle_blocks
+
+use crate::{circuit_input_builder::Block, operation::Target};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+/// Environment variable holding the directory of the coverage reports
+pub const COVERAGE_ENV: &str = "ZKEVM_COVERAGE";
+
+/// Counts of the steps, RW operations and copy events of the handled blocks
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Coverage {
+    /// Number of steps per execution state of the bus mapping and error, as
+    /// `state/error` with `-` for a missing error
+    pub exec_steps: BTreeMap<String, u64>,
+    /// Number of RW operations per target
+    pub rw_targets: BTreeMap<String, u64>,
+    /// Number of copy events per source and destination data types, as
+    /// `src->dst`
+    pub copy_types: BTreeMap<String, u64>,
+    /// Number of steps per execution state of the EVM circuit, recorded when
+    /// the blocks are converted to witnesses
+    pub execution_states: BTreeMap<String, u64>,
+    /// Number of steps per execution state of the EVM circuit, opcode and
+    /// error, as `state/opcode/error` with `-` for a missing opcode or error
+    pub steps: BTreeMap<String, u64>,
+}
+
+/// Add `n` to the count of `key`.
+pub fn count(counts: &mut BTreeMap<String, u64>, key: String, n: u64) {
+    *counts.entry(key).or_default() += n;
+}
+
+impl Coverage {
+    /// Add the steps, RW operations and copy events of the block.
+    pub fn record(&mut self, block: &Block) {
+        let steps = block
+            .txs
+            .iter()
+            .flat_map(|tx| tx.steps())
+            .chain([&block.block_steps.end_block_last]);
+        for step in steps {
+            let error = step
+                .error
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |error| format!("{:?}", error));
+            count(
+                &mut self.exec_steps,
+                format!("{:?}/{}", step.exec_state, error),
+                1,
+            );
+        }
+        let container = &block.container;
+        for (target, n) in [
+            (Target::Start, container.start.len()),
+            (Target::Memory, container.memory.len()),
+            (Target::Stack, container.stack.len()),
+            (Target::Storage, container.storage.len()),
+            (
+                Target::TxAccessListAccount,
+                container.tx_access_list_account.len(),
+            ),
+            (
+                Target::TxAccessListAccountStorage,
+                container.tx_access_list_account_storage.len(),
+            ),
+            (Target::TxRefund, container.tx_refund.len()),
+            (Target::Account, container.account.len()),
+            (Target::CallContext, container.call_context.len()),
+            (Target::TxReceipt, container.tx_receipt.len()),
+            (Target::TxLog, container.tx_log.len()),
+        ] {
+            if n > 0 {
+                count(&mut self.rw_targets, format!("{:?}", target), n as u64);
+            }
+        }
+        for event in &block.copy_events {
+            count(
+                &mut self.copy_types,
+                format!("{:?}->{:?}", event.src_type, event.dst_type),
+                1,
+            );
+        }
+    }
+
+    /// Add the counts of another coverage.
+    pub fn merge(&mut self, other: &Self) {
+        for (counts, other_counts) in [
+            (&mut self.exec_steps, &other.exec_steps),
+            (&mut self.rw_targets, &other.rw_targets),
+            (&mut self.copy_types, &other.copy_types),
+            (&mut self.execution_states, &other.execution_states),
+            (&mut self.steps, &other.steps),
+        ] {
+            for (key, n) in other_counts {
+                count(counts, key.clone(), *n);
+            }
+        }
+    }
+
+    /// Read the report at `path`, or an empty coverage if there is none yet.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(report) => serde_json::from_str(&report).map_err(io::Error::from),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Merge the JSON reports in the directory at `dir`.
+    pub fn load_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut coverage = Self::default();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(false, |extension| extension == "json")
+            {
+                coverage.merge(&Self::load(&path)?);
+            }
+        }
+        Ok(coverage)
+    }
+
+    /// Write the report to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        // Write to a temporary file next to the report first so that a report
+        // is never left half written.
+        let path = path.as_ref();
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, counts) in [
+            ("bus mapping steps", &self.exec_steps),
+            ("rw targets", &self.rw_targets),
+            ("copy types", &self.copy_types),
+            ("execution states", &self.execution_states),
+            ("steps", &self.steps),
+        ] {
+            writeln!(f, "{}:", title)?;
+            for (key, n) in counts {
+                writeln!(f, "  {:<60} {}", key, n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add to the coverage of the process with `record`, and write it to the
+/// report of the process in the directory set in [`COVERAGE_ENV`], if any.
+#[cfg(feature = "coverage")]
+pub fn update(record: impl FnOnce(&mut Coverage)) {
+    use once_cell::sync::Lazy;
+    use std::{path::PathBuf, sync::Mutex, time::SystemTime};
+
+    // Coverage of the process, shared by its threads.
+    static COVERAGE: Mutex<Coverage> = Mutex::new(Coverage {
+        exec_steps: BTreeMap::new(),
+        rw_targets: BTreeMap::new(),
+        copy_types: BTreeMap::new(),
+        execution_states: BTreeMap::new(),
+        steps: BTreeMap::new(),
+    });
+    // The start time tells apart the reports of processes that reuse an id.
+    static REPORT: Lazy<Option<PathBuf>> = Lazy::new(|| {
+        let dir = std::env::var_os(COVERAGE_ENV)?;
+        let start = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Some(Path::new(&dir).join(format!("coverage-{}-{}.json", std::process::id(), start)))
+    });
+
+    let Some(path) = REPORT.as_ref() else {
+        return;
+    };
+    let mut coverage = COVERAGE.lock().unwrap_or_else(|err| err.into_inner());
+    record(&mut coverage);
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| coverage.save(path));
+    if let Err(err) = result {
+        log::warn!(
+            "failed to write the coverage report {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::TestContext;
+
+    #[test]
+    fn coverage_records_and_merges_reports() {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let mut coverage = Coverage::default();
+        coverage.record(&builder.block);
+        assert_eq!(coverage.exec_steps["Op(PUSH1)/-"], 2);
+        assert!(coverage.exec_steps.contains_key("BeginTx/-"));
+        assert!(coverage.rw_targets.contains_key("Stack"));
+
+        let dir = std::env::temp_dir().join(format!("coverage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(
+            Coverage::load(dir.join("a.json")).unwrap(),
+            Coverage::default()
+        );
+        coverage.save(dir.join("a.json")).unwrap();
+        coverage.save(dir.join("b.json")).unwrap();
+        assert_eq!(Coverage::load(dir.join("a.json")).unwrap(), coverage);
+
+        let merged = Coverage::load_dir(&dir).unwrap();
+        assert_eq!(merged.exec_steps["Op(PUSH1)/-"], 4);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -5,9 +5,12 @@ use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
 use ethers_providers::ProviderError;
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    circuit_input_builder::{CircuitsOverflow, PostStateDiff},
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
+        GETH_ERR_STACK_UNDERFLOW,
+    },
 };
 
 /// Error type for any BusMapping related failure.
@@ -40,6 +43,12 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// Operation not supported with the enabled features
+    Unsupported(&'static str),
+    /// Circuit limits exceeded when including a transaction
+    CircuitsOverflow(Box<CircuitsOverflow>),
+    /// State left by the block differs from the expected post-state
+    PostStateMismatch(Box<PostStateDiff>),
 }
 
 impl From<eth_types::Error> for Error {
@@ -3,7 +3,7 @@ use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecState, ExecStep},
     error::{ExecError, OogError},
     evm::OpcodeId,
-    operation::TxAccessListAccountOp,
+    operation::{TxAccessListAccountOp, TxRefundOp},
     Error,
 };
 use core::fmt::Debug;
@@ -434,6 +434,19 @@ fn dummy_gen_selfdestruct_ops(
     // exists.
     state.transfer(&mut exec_step, sender, receiver, true, false, value)?;
 
+    // The first self destruct of an account is refunded before London.
+    let selfdestruct_refund = state.block.chain_spec.selfdestruct_refund();
+    if selfdestruct_refund > 0 && !state.sdb.is_account_destructed(&sender) {
+        state.push_op_reversible(
+            &mut exec_step,
+            TxRefundOp {
+                tx_id: state.tx_ctx.id(),
+                value_prev: state.sdb.refund(),
+                value: state.sdb.refund() + selfdestruct_refund,
+            },
+        )?;
+    }
+
     if state.call()?.is_persistent {
         state.sdb.destruct_account(sender);
     }
@@ -5,10 +5,7 @@ use crate::{
     state_db::CodeDB,
     Error,
 };
-use eth_types::{
-    evm_types::{GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    evm_unimplemented, ToWord, Word,
-};
+use eth_types::{evm_types::GasCost, evm_unimplemented, ToWord, Word};
 use ethers_core::utils::get_contract_address;
 
 #[derive(Clone, Copy, Debug)]
@@ -59,8 +56,14 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         nonce_prev.into(),
     )?;
 
-    // Add caller, callee and coinbase (for EIP-3651) to access list.
-    for address in [call.caller_address, call.address, state.block.coinbase] {
+    // Add caller, callee and coinbase (for EIP-3651 from Shanghai) to access
+    // list.
+    let coinbase = state.block.head(state.tx.block_num)?.coinbase;
+    let mut warm_addresses = vec![call.caller_address, call.address];
+    if state.block.chain_spec.is_coinbase_warm() {
+        warm_addresses.push(coinbase);
+    }
+    for address in warm_addresses {
         let is_warm_prev = !state.sdb.add_account_to_access_list(address);
         state.tx_accesslist_account_write(
             &mut exec_step,
@@ -71,9 +74,17 @@ fn gen_begin_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
         )?;
     }
 
+    let chain_spec = state.block.chain_spec;
     let init_code_gas_cost = if state.tx.is_create() {
-        // Calculate gas cost of init code for EIP-3860.
-        (state.tx.tx.call_data.len() as u64 + 31) / 32 * eth_types::evm_types::INIT_CODE_WORD_GAS
+        // Limit and calculate gas cost of init code for EIP-3860.
+        if let Some(max_init_code_size) = chain_spec.max_init_code_size() {
+            if state.tx.tx.call_data.len() > max_init_code_size {
+                return Err(Error::InvalidGethExecTrace(
+                    "init code of the creation transaction is too large",
+                ));
+            }
+        }
+        (state.tx.tx.call_data.len() as u64 + 31) / 32 * chain_spec.init_code_word_gas()
     } else {
         0
     };
@@ -255,8 +266,9 @@ fn gen_end_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error>
         },
     );
 
-    let effective_refund = refund
-        .min((state.tx.gas() - exec_step.gas_left.0) / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+    let effective_refund = refund.min(
+        (state.tx.gas() - exec_step.gas_left.0) / state.block.chain_spec.max_refund_quotient(),
+    );
     let (found, caller_account) = state.sdb.get_account(&call.caller_address);
     if !found {
         return Err(Error::AccountNotFound(call.caller_address));
@@ -272,23 +284,25 @@ fn gen_end_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error>
         caller_balance_prev,
     )?;
 
+    let block_head = state.block.head(state.tx.block_num)?;
+    let coinbase = block_head.coinbase;
     let base_fee = if state.is_anchor_tx() {
         0.into()
     } else {
-        state.block.base_fee
+        block_head.base_fee
     };
 
     let effective_tip = state.tx.tx.gas_price - base_fee;
-    let (found, coinbase_account) = state.sdb.get_account(&state.block.coinbase);
+    let (found, coinbase_account) = state.sdb.get_account(&coinbase);
     if !found {
-        return Err(Error::AccountNotFound(state.block.coinbase));
+        return Err(Error::AccountNotFound(coinbase));
     }
     let coinbase_balance_prev = coinbase_account.balance;
     let coinbase_balance =
         coinbase_balance_prev + effective_tip * (state.tx.gas() - exec_step.gas_left.0);
     state.account_write(
         &mut exec_step,
-        state.block.coinbase,
+        coinbase,
         AccountField::Balance,
         coinbase_balance,
         coinbase_balance_prev,
@@ -340,6 +354,10 @@ fn gen_end_tx_steps(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error>
         )?;
     }
 
+    // cumulative gas restarts at every block of a batch
+    if state.tx_ctx.is_first_tx_in_block() {
+        state.block_ctx.cumulative_gas_used = 0;
+    }
     state.block_ctx.cumulative_gas_used += state.tx.gas() - exec_step.gas_left.0;
     state.tx_receipt_write(
         &mut exec_step,
@@ -18,6 +18,7 @@ impl Opcode for Calldatacopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_calldatacopy_step(state, geth_step)?];
+        let copy_event = gen_copy_event(state, geth_step)?;
 
         // reconstruction
         let memory_offset = geth_step.stack.nth_last(0)?;
@@ -28,7 +29,6 @@ impl Opcode for Calldatacopy {
 
         memory.copy_from(memory_offset, data_offset, length, &call_ctx.call_data);
 
-        let copy_event = gen_copy_event(state, geth_step)?;
         state.push_copy(&mut exec_steps[0], copy_event);
         Ok(exec_steps)
     }
@@ -115,14 +115,7 @@ fn gen_copy_event(
         .unwrap_or(src_addr_end)
         .min(src_addr_end);
 
-    let mut exec_step = state.new_step(geth_step)?;
-    let copy_steps = state.gen_copy_steps_for_call_data(
-        &mut exec_step,
-        src_addr,
-        dst_addr,
-        src_addr_end,
-        length,
-    )?;
+    let copy_steps = state.gen_copy_steps_for_call_data(src_addr, src_addr_end, length)?;
 
     let (src_type, src_id) = if state.call()?.is_root {
         (CopyDataType::TxCalldata, state.tx_ctx.id())
@@ -130,7 +123,7 @@ fn gen_copy_event(
         (CopyDataType::Memory, state.call()?.caller_id)
     };
 
-    Ok(CopyEvent {
+    let mut copy_event = CopyEvent {
         src_type,
         src_id: NumberOrHash::Number(src_id),
         src_addr,
@@ -141,7 +134,15 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         bytes: copy_steps,
-    })
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    if src_type == CopyDataType::Memory {
+        copy_event.read_src_memory(&state.caller_ctx()?.memory);
+    }
+    copy_event.read_dst_memory_prev(&state.call_ctx()?.memory);
+
+    Ok(copy_event)
 }
 
 #[cfg(test)]
@@ -153,7 +154,7 @@ mod calldatacopy_tests {
     };
     use eth_types::{
         bytecode,
-        evm_types::{OpcodeId, StackAddress},
+        evm_types::{MemoryAddress, OpcodeId, StackAddress},
         geth_types::GethData,
         Word,
     };
@@ -227,8 +228,9 @@ mod calldatacopy_tests {
         let caller_id = builder.block.txs()[0].calls()[step.call_index].caller_id;
         let expected_call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
 
-        // 3 stack reads + 3 call context reads.
-        assert_eq!(step.bus_mapping_instance.len(), 6);
+        // 3 stack reads + 3 call context reads + 1 memory word read and 1
+        // memory word write of the copy.
+        assert_eq!(step.bus_mapping_instance.len(), 8);
 
         // 3 stack reads.
         assert_eq!(
@@ -287,39 +289,37 @@ mod calldatacopy_tests {
 
         // Memory reads/writes.
         //
-        // 1. First `call_data_length` memory ops are RW::WRITE and come from the `CALL`
-        // opcode. We skip checking those.
+        // 1. First 2 memory ops are RW::WRITE and come from the `MSTORE` in code
+        // A. We skip checking those.
         //
-        // 2. Following that, we should have tuples of (RW::READ and RW::WRITE) where
-        // the caller memory is read and the current call's memory is written to.
-        assert_eq!(
-            builder.block.container.memory.len(),
-            call_data_length + 2 * copy_size
+        // 2. Following that, the caller memory word that contains the call data
+        // is read, and the current call's memory word is written to.
+        assert_eq!(builder.block.container.memory.len(), 4);
+        let caller_word = Word::from_big_endian(&memory_a);
+        let mut callee_word = [0u8; 32];
+        callee_word[dst_offset..dst_offset + copy_size].copy_from_slice(
+            &memory_a[call_data_offset + offset..call_data_offset + offset + copy_size],
         );
         assert_eq!(
-            (call_data_length..(call_data_length + (2 * copy_size)))
+            (2..4)
                 .map(|idx| &builder.block.container.memory[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            {
-                let mut memory_ops = Vec::with_capacity(2 * copy_size);
-                (0..copy_size).for_each(|idx| {
-                    let value = if offset + call_data_offset + idx < memory_a.len() {
-                        memory_a[offset + call_data_offset + idx]
-                    } else {
-                        0
-                    };
-                    memory_ops.push((
-                        RW::READ,
-                        MemoryOp::new(caller_id, (call_data_offset + offset + idx).into(), value),
-                    ));
-                    memory_ops.push((
-                        RW::WRITE,
-                        MemoryOp::new(expected_call_id, (dst_offset + idx).into(), value),
-                    ));
-                });
-                memory_ops
-            },
+            vec![
+                (
+                    RW::READ,
+                    MemoryOp::new(caller_id, MemoryAddress(0), caller_word, caller_word),
+                ),
+                (
+                    RW::WRITE,
+                    MemoryOp::new(
+                        expected_call_id,
+                        MemoryAddress(0),
+                        Word::from_big_endian(&callee_word),
+                        Word::zero(),
+                    ),
+                ),
+            ],
         );
 
         let copy_events = builder.block.copy_events.clone();
@@ -431,7 +431,8 @@ mod calldatacopy_tests {
             .unwrap();
 
         let expected_call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
-        assert_eq!(step.bus_mapping_instance.len(), 5);
+        // 3 stack reads + 2 call context reads + 2 memory word writes of the copy.
+        assert_eq!(step.bus_mapping_instance.len(), 7);
 
         assert_eq!(
             [0, 1, 2]
@@ -481,28 +482,28 @@ mod calldatacopy_tests {
         // Memory reads/writes.
         //
         // 1. Since its a root call, we should only have memory RW::WRITE where the
-        // current call's memory is written to.
-        assert_eq!(builder.block.container.memory.len(), size);
+        // current call's memory words are written to.
+        let mut memory = vec![0u8; size];
+        memory[..calldata_len - offset].copy_from_slice(&calldata[offset..]);
+        assert_eq!(builder.block.container.memory.len(), size / 32);
         assert_eq!(
-            (0..size)
+            (0..size / 32)
                 .map(|idx| &builder.block.container.memory[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            {
-                let mut memory_ops = Vec::with_capacity(size);
-                (0..size).for_each(|idx| {
-                    let value = if offset + idx < calldata_len {
-                        calldata[offset + idx]
-                    } else {
-                        0
-                    };
-                    memory_ops.push((
-                        RW::WRITE,
-                        MemoryOp::new(expected_call_id, (dst_offset + idx).into(), value),
-                    ));
-                });
-                memory_ops
-            },
+            memory
+                .chunks(32)
+                .enumerate()
+                .map(|(idx, word)| (
+                    RW::WRITE,
+                    MemoryOp::new(
+                        expected_call_id,
+                        MemoryAddress(dst_offset + 32 * idx),
+                        Word::from_big_endian(word),
+                        Word::zero(),
+                    ),
+                ))
+                .collect::<Vec<(RW, MemoryOp)>>(),
         );
 
         let copy_events = builder.block.copy_events.clone();
@@ -3,7 +3,7 @@ use crate::{
     operation::{CallContextField, MemoryOp, RW},
     Error,
 };
-use eth_types::{GethExecStep, U256};
+use eth_types::{evm_types::MemoryAddress, GethExecStep, Word, U256};
 
 use super::Opcode;
 
@@ -74,21 +74,36 @@ impl Opcode for Calldataload {
                 .map(|idx| {
                     let addr = src_addr.checked_add(idx).unwrap_or(src_addr_end);
                     if addr < src_addr_end {
-                        let byte = call_data[(addr - call_data_offset) as usize];
-                        if !is_root {
-                            state.push_op(
-                                &mut exec_step,
-                                RW::READ,
-                                MemoryOp::new(caller_id, (src_addr + idx).into(), byte),
-                            );
-                        }
-                        byte
+                        call_data[(addr - call_data_offset) as usize]
                     } else {
                         0
                     }
                 })
                 .collect();
 
+            // For an internal call, the call data is read from the two words of
+            // the caller's memory that contain it.
+            if !is_root {
+                let word_addr = src_addr - src_addr % 32;
+                let caller_memory = state.caller_ctx()?.memory.read_chunk(
+                    MemoryAddress::from(word_addr as usize),
+                    MemoryAddress::from(64),
+                );
+                for (idx, word) in caller_memory.chunks(32).enumerate() {
+                    let word = Word::from_big_endian(word);
+                    state.push_op(
+                        &mut exec_step,
+                        RW::READ,
+                        MemoryOp::new(
+                            caller_id,
+                            (word_addr as usize + 32 * idx).into(),
+                            word,
+                            word,
+                        ),
+                    );
+                }
+            }
+
             U256::from_big_endian(&calldata)
         } else {
             // Stack push `0` as result if overflow.
@@ -140,13 +155,12 @@ mod calldataload_tests {
             STOP
         };
 
+        let word_addr = (call_data_offset + offset) / 32 * 32;
         let mut memory_a = std::iter::repeat(0)
-            .take(32 - pushdata.len() - call_data_offset)
+            .take(32 - pushdata.len())
             .chain(pushdata.clone())
             .collect::<Vec<u8>>();
-        if memory_a.len() < call_data_length {
-            memory_a.resize(call_data_length, 0);
-        }
+        memory_a.resize(std::cmp::max(memory_a.len(), word_addr + 64), 0);
         let code_a = generate_mock_call_bytecode(MockCallBytecodeParams {
             address: addr_b,
             pushdata,
@@ -187,12 +201,12 @@ mod calldataload_tests {
         let call_id = builder.block.txs()[0].calls()[step.call_index].call_id;
         let caller_id = builder.block.txs()[0].calls()[step.call_index].caller_id;
 
-        // 1 stack read, 3 call context reads, 32 memory reads and 1 stack write.
-        assert_eq!(step.bus_mapping_instance.len(), 37);
+        // 1 stack read, 3 call context reads, 2 memory reads and 1 stack write.
+        assert_eq!(step.bus_mapping_instance.len(), 7);
 
         // stack read and write.
         assert_eq!(
-            [0, 36]
+            [0, 6]
                 .map(|idx| &builder.block.container.stack[step.bus_mapping_instance[idx].as_usize()])
                 .map(|op| (op.rw(), op.op())),
             [
@@ -241,23 +255,18 @@ mod calldataload_tests {
             ],
         );
 
-        // 32 memory reads from caller memory
+        // 2 memory word reads from caller memory
         assert_eq!(
-            (0..32)
+            (0..2)
                 .map(|idx| &builder.block.container.memory
                     [step.bus_mapping_instance[4 + idx].as_usize()])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            (0..32)
+            (0..2)
                 .map(|idx| {
-                    (
-                        RW::READ,
-                        MemoryOp::new(
-                            caller_id,
-                            (call_data_offset + offset + idx).into(),
-                            memory_a[offset + idx],
-                        ),
-                    )
+                    let addr = word_addr + 32 * idx;
+                    let word = Word::from_big_endian(&memory_a[addr..addr + 32]);
+                    (RW::READ, MemoryOp::new(caller_id, addr.into(), word, word))
                 })
                 .collect::<Vec<(RW, MemoryOp)>>(),
         );
@@ -4,15 +4,15 @@ use crate::{
         CallKind, CircuitInputStateRef, CodeSource, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
     },
     evm::opcodes::precompiles::gen_associated_ops as precompile_associated_ops,
-    operation::{AccountField, CallContextField, MemoryOp, TxAccessListAccountOp, RW},
-    precompile::{execute_precompiled, is_precompiled, PrecompileCalls},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp},
+    precompile::{execute_precompiled, PrecompileCalls},
     state_db::CodeDB,
     Error,
 };
 use eth_types::{
     evm_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
-        GasCost,
+        GasCost, Memory,
     },
     GethExecStep, ToWord, Word,
 };
@@ -172,7 +172,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
 
         let code_address = call.code_address();
         let is_precompile = code_address
-            .map(|ref addr| is_precompiled(addr))
+            .map(|ref addr| state.is_precompiled(addr))
             .unwrap_or(false);
         // TODO: What about transfer for CALLCODE?
         // Transfer value only for CALL opcode, is_precheck_ok = true.
@@ -227,12 +227,13 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
             (false, true, _) => {
                 assert!(call.is_success, "call to precompile should not fail");
                 let caller_ctx = state.caller_ctx()?;
-                let caller_memory = caller_ctx.memory.0.clone();
+                let caller_memory = caller_ctx.memory.clone();
                 let code_address = code_address.unwrap();
                 let (result, contract_gas_cost) = execute_precompiled(
+                    &state.block.chain_spec,
                     &code_address,
                     if args_length != 0 {
-                        &caller_memory[args_offset..args_offset + args_length]
+                        &caller_memory.0[args_offset..args_offset + args_length]
                     } else {
                         &[]
                     },
@@ -296,93 +297,65 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                     state.call_context_write(&mut exec_step, current_call.call_id, field, value);
                 }
 
-                // insert a copy event (input) for this step
-                let rw_counter_start = state.block_ctx.rwc;
-                if call.is_success && call.call_data_length > 0 {
-                    let bytes: Vec<(u8, bool)> = caller_memory
+                // the precompile memory holds the input bytes.
+                let callee_memory = Memory::from(
+                    caller_memory
+                        .0
                         .iter()
                         .skip(call.call_data_offset as usize)
                         .take(call.call_data_length as usize)
-                        .map(|b| (*b, false))
-                        .collect();
-                    for (i, &(byte, _is_code)) in bytes.iter().enumerate() {
-                        // push caller memory read
-                        state.push_op(
-                            &mut exec_step,
-                            RW::READ,
-                            MemoryOp::new(
-                                call.caller_id,
-                                (call.call_data_offset + i as u64).into(),
-                                byte,
-                            ),
-                        );
-                        // push callee memory write
-                        state.push_op(
-                            &mut exec_step,
-                            RW::WRITE,
-                            MemoryOp::new(call.call_id, i.into(), byte),
-                        );
-                    }
-                    state.push_copy(
-                        &mut exec_step,
-                        CopyEvent {
-                            src_id: NumberOrHash::Number(call.caller_id),
-                            src_type: CopyDataType::Memory,
-                            src_addr: call.call_data_offset,
-                            src_addr_end: call.call_data_offset + call.call_data_length,
-                            dst_id: NumberOrHash::Number(call.call_id),
-                            dst_type: CopyDataType::Memory,
-                            dst_addr: 0,
-                            log_id: None,
-                            rw_counter_start,
-                            bytes,
-                        },
-                    );
+                        .copied()
+                        .collect::<Vec<u8>>(),
+                );
+
+                // insert a copy event (input) for this step
+                if call.is_success && call.call_data_length > 0 {
+                    let mut copy_event = CopyEvent {
+                        src_id: NumberOrHash::Number(call.caller_id),
+                        src_type: CopyDataType::Memory,
+                        src_addr: call.call_data_offset,
+                        src_addr_end: call.call_data_offset + call.call_data_length,
+                        dst_id: NumberOrHash::Number(call.call_id),
+                        dst_type: CopyDataType::Memory,
+                        dst_addr: 0,
+                        log_id: None,
+                        rw_counter_start: state.block_ctx.rwc,
+                        bytes: callee_memory.0.iter().map(|b| (*b, false)).collect(),
+                        src_memory: vec![],
+                        dst_memory_prev: vec![],
+                    };
+                    copy_event.read_src_memory(&caller_memory);
+                    copy_event.read_dst_memory_prev(&Memory::default());
+                    state.push_copy(&mut exec_step, copy_event);
                 }
 
                 // insert another copy event (output) for this step.
-                let rw_counter_start = state.block_ctx.rwc;
                 if call.is_success && call.call_data_length > 0 && length > 0 {
-                    let bytes: Vec<(u8, bool)> = caller_memory
-                        .iter()
-                        .skip(call.call_data_offset as usize)
-                        .take(length)
-                        .map(|b| (*b, false))
-                        .collect();
-                    for (i, &(byte, _is_code)) in bytes.iter().enumerate() {
-                        // push callee memory read
-                        state.push_op(
-                            &mut exec_step,
-                            RW::READ,
-                            MemoryOp::new(call.call_id, i.into(), byte),
-                        );
-                        // push caller memory write
-                        state.push_op(
-                            &mut exec_step,
-                            RW::WRITE,
-                            MemoryOp::new(
-                                call.caller_id,
-                                (call.return_data_offset + i as u64).into(),
-                                byte,
-                            ),
-                        );
-                    }
-                    state.push_copy(
-                        &mut exec_step,
-                        CopyEvent {
-                            src_id: NumberOrHash::Number(call.call_id),
-                            src_type: CopyDataType::Memory,
-                            src_addr: 0,
-                            src_addr_end: length as u64,
-                            dst_id: NumberOrHash::Number(call.caller_id),
-                            dst_type: CopyDataType::Memory,
-                            dst_addr: call.return_data_offset,
-                            log_id: None,
-                            rw_counter_start,
-                            bytes,
-                        },
-                    );
+                    let mut copy_event = CopyEvent {
+                        src_id: NumberOrHash::Number(call.call_id),
+                        src_type: CopyDataType::Memory,
+                        src_addr: 0,
+                        src_addr_end: length as u64,
+                        dst_id: NumberOrHash::Number(call.caller_id),
+                        dst_type: CopyDataType::Memory,
+                        dst_addr: call.return_data_offset,
+                        log_id: None,
+                        rw_counter_start: state.block_ctx.rwc,
+                        bytes: caller_memory
+                            .0
+                            .iter()
+                            .skip(call.call_data_offset as usize)
+                            .take(length)
+                            .map(|b| (*b, false))
+                            .collect(),
+                        src_memory: vec![],
+                        dst_memory_prev: vec![],
+                    };
+                    copy_event.read_src_memory(&callee_memory);
+                    copy_event.read_dst_memory_prev(&caller_memory);
+                    state.push_copy(&mut exec_step, copy_event);
                 }
+                state.caller_ctx_mut()?.last_callee_memory = callee_memory;
 
                 // TODO: when more precompiles are supported and each have their own different
                 // behaviour, we can separate out the logic specified here.
@@ -18,6 +18,7 @@ impl Opcode for Codecopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_codecopy_step(state, geth_step)?];
+        let copy_event = gen_copy_event(state, geth_step)?;
 
         // reconstruction
         let dst_offset = geth_step.stack.nth_last(0)?;
@@ -32,7 +33,6 @@ impl Opcode for Codecopy {
 
         memory.copy_from(dst_offset, code_offset, length, &code);
 
-        let copy_event = gen_copy_event(state, geth_step)?;
         state.push_copy(&mut exec_steps[0], copy_event);
         Ok(exec_steps)
     }
@@ -86,17 +86,10 @@ fn gen_copy_event(
         .unwrap_or(src_addr_end)
         .min(src_addr_end);
 
-    let mut exec_step = state.new_step(geth_step)?;
-    let copy_steps = state.gen_copy_steps_for_bytecode(
-        &mut exec_step,
-        &bytecode,
-        src_addr,
-        dst_addr,
-        src_addr_end,
-        length,
-    )?;
+    let copy_steps =
+        state.gen_copy_steps_for_bytecode(&bytecode, src_addr, src_addr_end, length)?;
 
-    Ok(CopyEvent {
+    let mut copy_event = CopyEvent {
         src_type: CopyDataType::Bytecode,
         src_id: NumberOrHash::Hash(code_hash),
         src_addr,
@@ -107,7 +100,12 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         bytes: copy_steps,
-    })
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    copy_event.read_dst_memory_prev(&state.call_ctx()?.memory);
+
+    Ok(copy_event)
 }
 
 #[cfg(test)]
@@ -187,24 +185,31 @@ mod codecopy_tests {
             ]
         );
 
-        // RW table memory writes.
+        // RW table memory word writes.
+        let memory = (0..size)
+            .map(|idx| {
+                code.to_vec()
+                    .get(code_offset + idx)
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<u8>>();
         assert_eq!(
-            (0..size)
+            (0..size / 32)
                 .map(|idx| &builder.block.container.memory[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            (0..size)
-                .map(|idx| {
+            memory
+                .chunks(32)
+                .enumerate()
+                .map(|(idx, word)| {
                     (
                         RW::WRITE,
                         MemoryOp::new(
                             1,
-                            MemoryAddress::from(dst_offset + idx),
-                            if code_offset + idx < code.to_vec().len() {
-                                code.to_vec()[code_offset + idx]
-                            } else {
-                                0
-                            },
+                            MemoryAddress::from(dst_offset + 32 * idx),
+                            Word::from_big_endian(word),
+                            Word::zero(),
                         ),
                     )
                 })
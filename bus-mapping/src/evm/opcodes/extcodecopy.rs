@@ -21,6 +21,7 @@ impl Opcode for Extcodecopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_extcodecopy_step(state, geth_step)?];
+        let copy_event = gen_copy_event(state, geth_step)?;
 
         // reconstruction
         let address = geth_steps[0].stack.nth_last(0)?.to_address();
@@ -37,7 +38,6 @@ impl Opcode for Extcodecopy {
 
         memory.copy_from(dst_offset, code_offset, length, &code);
 
-        let copy_event = gen_copy_event(state, geth_step)?;
         state.push_copy(&mut exec_steps[0], copy_event);
         Ok(exec_steps)
     }
@@ -145,17 +145,10 @@ fn gen_copy_event(
         .unwrap_or(src_addr_end)
         .min(src_addr_end);
 
-    let mut exec_step = state.new_step(geth_step)?;
-    let copy_steps = state.gen_copy_steps_for_bytecode(
-        &mut exec_step,
-        &bytecode,
-        src_addr,
-        dst_addr,
-        src_addr_end,
-        length,
-    )?;
+    let copy_steps =
+        state.gen_copy_steps_for_bytecode(&bytecode, src_addr, src_addr_end, length)?;
 
-    Ok(CopyEvent {
+    let mut copy_event = CopyEvent {
         src_addr,
         src_addr_end,
         src_type: CopyDataType::Bytecode,
@@ -166,7 +159,12 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         bytes: copy_steps,
-    })
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    copy_event.read_dst_memory_prev(&state.call_ctx()?.memory);
+
+    Ok(copy_event)
 }
 
 #[cfg(test)]
@@ -399,23 +397,33 @@ mod extcodecopy_tests {
 
         let expected_call_id = transaction.calls()[step.call_index].call_id;
 
+        // The memory words spanned by the copy, which are all zero before it.
+        let word_addr = memory_offset / 32 * 32;
+        let num_words = (memory_offset - word_addr + copy_size + 31) / 32;
+        let mut memory = vec![0u8; 32 * num_words];
+        for idx in 0..copy_size {
+            memory[memory_offset - word_addr + idx] = bytecode_ext
+                .to_vec()
+                .get(data_offset + idx)
+                .copied()
+                .unwrap_or_default();
+        }
         assert_eq!(
-            (0..copy_size)
+            (0..num_words)
                 .map(|idx| &builder.block.container.memory[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            (0..copy_size)
-                .map(|idx| {
+            memory
+                .chunks(32)
+                .enumerate()
+                .map(|(idx, word)| {
                     (
                         RW::WRITE,
                         MemoryOp::new(
                             expected_call_id,
-                            MemoryAddress::from(memory_offset + idx),
-                            if data_offset + idx < bytecode_ext.to_vec().len() {
-                                bytecode_ext.to_vec()[data_offset + idx]
-                            } else {
-                                0
-                            },
+                            MemoryAddress::from(word_addr + 32 * idx),
+                            Word::from_big_endian(word),
+                            Word::zero(),
                         ),
                     )
                 })
@@ -19,7 +19,7 @@ impl Opcode for Log {
         let geth_step = &geth_steps[0];
         let mut exec_step = gen_log_step(state, geth_step)?;
         if state.call()?.is_persistent {
-            let copy_event = gen_copy_event(state, geth_step, &mut exec_step)?;
+            let copy_event = gen_copy_event(state, geth_step)?;
             state.push_copy(&mut exec_step, copy_event);
             state.tx_ctx.log_id += 1;
         }
@@ -126,48 +126,31 @@ fn gen_log_step(
         }
     }
 
-    Ok(exec_step)
-}
-
-fn gen_copy_steps(
-    state: &mut CircuitInputStateRef,
-    exec_step: &mut ExecStep,
-    src_addr: u64,
-    bytes_left: usize,
-) -> Result<Vec<(u8, bool)>, Error> {
-    // Get memory data
-    let mem = state
-        .call_ctx()?
-        .memory
-        .read_chunk(src_addr.into(), bytes_left.into());
-
-    let mut copy_steps = Vec::with_capacity(bytes_left);
-    for (idx, byte) in mem.iter().enumerate() {
-        let addr = src_addr + idx as u64;
-
-        // Read memory
-        state.memory_read(exec_step, (addr as usize).into(), *byte)?;
-
-        copy_steps.push((*byte, false));
-
-        // Write log
+    if state.call()?.is_persistent {
+        state.tx_log_write(
+            &mut exec_step,
+            state.tx_ctx.id(),
+            state.tx_ctx.log_id + 1,
+            TxLogField::TopicLength,
+            0,
+            Word::from(topic_count),
+        )?;
         state.tx_log_write(
-            exec_step,
+            &mut exec_step,
             state.tx_ctx.id(),
             state.tx_ctx.log_id + 1,
-            TxLogField::Data,
-            idx,
-            Word::from(*byte),
+            TxLogField::DataLength,
+            0,
+            msize,
         )?;
     }
 
-    Ok(copy_steps)
+    Ok(exec_step)
 }
 
 fn gen_copy_event(
     state: &mut CircuitInputStateRef,
     geth_step: &GethExecStep,
-    exec_step: &mut ExecStep,
 ) -> Result<CopyEvent, Error> {
     let rw_counter_start = state.block_ctx.rwc;
 
@@ -177,9 +160,16 @@ fn gen_copy_event(
 
     let (src_addr, src_addr_end) = (memory_start, memory_start + msize as u64);
 
-    let steps = gen_copy_steps(state, exec_step, src_addr, msize)?;
+    // Get memory data
+    let steps = state
+        .call_ctx()?
+        .memory
+        .read_chunk(src_addr.into(), msize.into())
+        .into_iter()
+        .map(|byte| (byte, false))
+        .collect();
 
-    Ok(CopyEvent {
+    let mut copy_event = CopyEvent {
         src_type: CopyDataType::Memory,
         src_id: NumberOrHash::Number(state.call()?.call_id),
         src_addr,
@@ -190,7 +180,12 @@ fn gen_copy_event(
         log_id: Some(state.tx_ctx.log_id as u64 + 1),
         rw_counter_start,
         bytes: steps,
-    })
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    copy_event.read_src_memory(&state.call_ctx()?.memory);
+
+    Ok(copy_event)
 }
 
 #[cfg(test)]
@@ -394,43 +389,72 @@ mod log_tests {
             { log_topic_ops },
         );
 
-        // memory reads.
-        let mut log_data_ops = Vec::with_capacity(msize);
+        // topic and data length writes
+        assert_eq!(
+            (1 + topic_count..3 + topic_count)
+                .map(|idx| &builder.block.container.tx_log[idx])
+                .map(|op| (op.rw(), op.op().clone()))
+                .collect::<Vec<(RW, TxLogOp)>>(),
+            vec![
+                (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1,
+                        TxLogField::TopicLength,
+                        0,
+                        Word::from(topic_count)
+                    )
+                ),
+                (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1,
+                        TxLogField::DataLength,
+                        0,
+                        Word::from(msize)
+                    )
+                ),
+            ],
+        );
+
+        // memory word reads, skipping the 4 word writes of the MSTORE ops.
         assert_eq!(
-            // skip first 32 writes of MSTORE ops
-            (mstart + 64..(mstart + 64 + msize))
+            (4..4 + msize / 32)
                 .map(|idx| &builder.block.container.memory[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            {
-                let mut memory_ops = Vec::with_capacity(msize);
-                (mstart..msize).for_each(|idx| {
-                    memory_ops.push((
+            memory_data
+                .chunks(32)
+                .enumerate()
+                .map(|(idx, chunk)| {
+                    let word = Word::from_big_endian(chunk);
+                    (
                         RW::READ,
-                        MemoryOp::new(1, (mstart + idx).into(), memory_data[mstart + idx]),
-                    ));
-                    // tx log addition
-                    log_data_ops.push((
-                        RW::WRITE,
-                        TxLogOp::new(
-                            1,
-                            step.log_id + 1, // because it is in next CopyToLog step
-                            TxLogField::Data,
-                            idx - mstart,
-                            Word::from(memory_data[mstart + idx]),
-                        ),
-                    ));
-                });
-
-                memory_ops
-            },
+                        MemoryOp::new(1, (mstart + idx * 32).into(), word, word),
+                    )
+                })
+                .collect::<Vec<(RW, MemoryOp)>>(),
         );
+        // tx log data writes.
         assert_eq!(
-            ((1 + topic_count)..msize + 1 + topic_count)
+            ((3 + topic_count)..msize + 3 + topic_count)
                 .map(|idx| &builder.block.container.tx_log[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, TxLogOp)>>(),
-            { log_data_ops },
+            (mstart..msize)
+                .map(|idx| (
+                    RW::WRITE,
+                    TxLogOp::new(
+                        1,
+                        step.log_id + 1, // because it is in next CopyToLog step
+                        TxLogField::Data,
+                        idx - mstart,
+                        Word::from(memory_data[mstart + idx]),
+                    ),
+                ))
+                .collect::<Vec<(RW, TxLogOp)>>(),
         );
 
         let copy_events = builder.block.copy_events.clone();
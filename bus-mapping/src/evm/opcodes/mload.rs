@@ -3,7 +3,7 @@ use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
     Error,
 };
-use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian};
+use eth_types::{evm_types::MemoryAddress, GethExecStep};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::MLOAD`](crate::evm::OpcodeId::MLOAD)
@@ -28,24 +28,18 @@ impl Opcode for Mload {
         // Manage first stack read at latest stack position
         state.stack_read(&mut exec_step, stack_position, stack_value_read)?;
 
-        // Read the memory
-        let mut mem_read_addr: MemoryAddress = stack_value_read.try_into()?;
-        // Accesses to memory that hasn't been initialized are valid, and return
-        // 0.
-        let mem_read_value = geth_steps[1].stack.last()?;
-
         // First stack write
         //
+        let mem_read_value = geth_steps[1].stack.last()?;
         state.stack_write(&mut exec_step, stack_position, mem_read_value)?;
 
-        // First mem read -> 32 MemoryOp generated.
-        //
-        for byte in mem_read_value.to_be_bytes() {
-            state.memory_read(&mut exec_step, mem_read_addr, byte)?;
-
-            // Update mem_read_addr to next byte's one
-            mem_read_addr += MemoryAddress::from(1);
-        }
+        // Read the two memory words that contain the 32 bytes at the address.
+        // Accesses to memory that hasn't been initialized are valid, and return
+        // 0.
+        let mem_read_addr: MemoryAddress = stack_value_read.try_into()?;
+        let word_addr = mem_read_addr.map(|addr| addr - addr % 32);
+        state.memory_read_word(&mut exec_step, word_addr)?;
+        state.memory_read_word(&mut exec_step, word_addr.map(|addr| addr + 32))?;
 
         // reconstruction
         let offset = geth_step.stack.nth_last(0)?;
@@ -124,17 +118,14 @@ mod mload_tests {
         );
 
         assert_eq!(
-            (2..34)
+            (2..4)
                 .map(|idx| &builder.block.container.memory
                     [step.bus_mapping_instance[idx].as_usize()])
                 .map(|operation| (operation.rw(), operation.op().clone()))
                 .collect_vec(),
-            Word::from(0x80)
-                .to_be_bytes()
-                .into_iter()
-                .enumerate()
-                .map(|(idx, byte)| (RW::READ, MemoryOp::new(1, MemoryAddress(idx + 0x40), byte)))
-                .collect_vec()
+            [(0x40, Word::from(0x80)), (0x60, Word::zero())]
+                .map(|(addr, word)| (RW::READ, MemoryOp::new(1, MemoryAddress(addr), word, word)))
+                .to_vec()
         )
     }
 }
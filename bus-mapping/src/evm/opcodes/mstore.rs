@@ -3,7 +3,7 @@ use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
     Error,
 };
-use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian, ToLittleEndian};
+use eth_types::{evm_types::MemoryAddress, GethExecStep, ToBigEndian, ToLittleEndian, Word};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the [`OpcodeId::MSTORE`](crate::evm::OpcodeId::MSTORE)
@@ -28,25 +28,26 @@ impl<const IS_MSTORE8: bool> Opcode for Mstore<IS_MSTORE8> {
         let value_pos = geth_step.stack.nth_last_filled(1);
         state.stack_read(&mut exec_step, value_pos, value)?;
 
-        // First mem write -> 32 MemoryOp generated.
+        // Write the memory words that contain the stored bytes: two words for
+        // MSTORE and one word for MSTORE8.
         let offset_addr: MemoryAddress = offset.try_into()?;
-
+        let shift = offset_addr.0 % 32;
+        let word_addr = offset_addr.0 - shift;
+        let num_words = if IS_MSTORE8 { 1 } else { 2 };
+        let mut words = state
+            .call_ctx()?
+            .memory
+            .read_chunk(word_addr.into(), (32 * num_words).into());
         match IS_MSTORE8 {
-            true => {
-                // stack write operation for mstore8
-                state.memory_write(
-                    &mut exec_step,
-                    offset_addr,
-                    *value.to_le_bytes().first().unwrap(),
-                )?;
-            }
-            false => {
-                // stack write each byte for mstore
-                let bytes = value.to_be_bytes();
-                for (i, byte) in bytes.iter().enumerate() {
-                    state.memory_write(&mut exec_step, offset_addr.map(|a| a + i), *byte)?;
-                }
-            }
+            true => words[shift] = *value.to_le_bytes().first().unwrap(),
+            false => words[shift..shift + 32].copy_from_slice(&value.to_be_bytes()),
+        }
+        for (idx, word) in words.chunks(32).enumerate() {
+            state.memory_write_word(
+                &mut exec_step,
+                (word_addr + 32 * idx).into(),
+                Word::from_big_endian(word),
+            )?;
         }
 
         // reconstruction
@@ -143,20 +144,17 @@ mod mstore_tests {
         );
 
         assert_eq!(
-            (2..34)
+            (2..4)
                 .map(|idx| &builder.block.container.memory
                     [step.bus_mapping_instance[idx].as_usize()])
                 .map(|operation| (operation.rw(), operation.op().clone()))
                 .collect_vec(),
-            Word::from(0x1234u64)
-                .to_be_bytes()
-                .into_iter()
-                .enumerate()
-                .map(|(idx, byte)| (
+            [(0x100, Word::from(0x1234u64)), (0x120, Word::zero())]
+                .map(|(addr, word)| (
                     RW::WRITE,
-                    MemoryOp::new(1, MemoryAddress(idx + 0x100), byte)
+                    MemoryOp::new(1, MemoryAddress(addr), word, Word::zero())
                 ))
-                .collect_vec()
+                .to_vec()
         )
     }
 
@@ -210,7 +208,15 @@ mod mstore_tests {
         let memory_op = &builder.block.container.memory[step.bus_mapping_instance[2].as_usize()];
         assert_eq!(
             (memory_op.rw(), memory_op.op()),
-            (RW::WRITE, &MemoryOp::new(1, MemoryAddress(0x100), 0x34))
+            (
+                RW::WRITE,
+                &MemoryOp::new(
+                    1,
+                    MemoryAddress(0x100),
+                    Word::from(0x34) << 248,
+                    Word::zero()
+                )
+            )
         )
     }
 }
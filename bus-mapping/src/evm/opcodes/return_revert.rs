@@ -2,7 +2,7 @@ use super::Opcode;
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, CopyDataType, CopyEvent, NumberOrHash},
     evm::opcodes::ExecStep,
-    operation::{AccountField, AccountOp, CallContextField, MemoryOp, RW},
+    operation::{AccountField, AccountOp, CallContextField},
     state_db::CodeDB,
     Error,
 };
@@ -107,14 +107,7 @@ impl Opcode for ReturnRevert {
             let return_data_length = usize::try_from(call.return_data_length).unwrap();
             let copy_length = std::cmp::min(return_data_length, length);
             if copy_length > 0 {
-                // reconstruction
-                let callee_memory = state.call_ctx()?.memory.clone();
-                let caller_ctx = state.caller_ctx_mut()?;
                 let return_offset = call.return_data_offset.try_into().unwrap();
-
-                caller_ctx.memory.0[return_offset..return_offset + copy_length]
-                    .copy_from_slice(&callee_memory.0[offset..offset + copy_length]);
-
                 handle_copy(
                     state,
                     &mut exec_step,
@@ -129,6 +122,12 @@ impl Opcode for ReturnRevert {
                         length: return_data_length,
                     },
                 )?;
+
+                // reconstruction
+                let callee_memory = state.call_ctx()?.memory.clone();
+                let caller_ctx = state.caller_ctx_mut()?;
+                caller_ctx.memory.0[return_offset..return_offset + copy_length]
+                    .copy_from_slice(&callee_memory.0[offset..offset + copy_length]);
             }
         }
 
@@ -161,35 +160,23 @@ fn handle_copy(
         .map(|byte| (*byte, false))
         .collect();
 
-    let rw_counter_start = state.block_ctx.rwc;
-    for (i, (byte, _is_code)) in bytes.iter().enumerate() {
-        state.push_op(
-            step,
-            RW::READ,
-            MemoryOp::new(source.id, (source.offset + i).into(), *byte),
-        );
-        state.push_op(
-            step,
-            RW::WRITE,
-            MemoryOp::new(destination.id, (destination.offset + i).into(), *byte),
-        );
-    }
-
-    state.push_copy(
-        step,
-        CopyEvent {
-            rw_counter_start,
-            src_type: CopyDataType::Memory,
-            src_id: NumberOrHash::Number(source.id),
-            src_addr: source.offset.try_into().unwrap(),
-            src_addr_end: (source.offset + source.length).try_into().unwrap(),
-            dst_type: CopyDataType::Memory,
-            dst_id: NumberOrHash::Number(destination.id),
-            dst_addr: destination.offset.try_into().unwrap(),
-            log_id: None,
-            bytes,
-        },
-    );
+    let mut copy_event = CopyEvent {
+        rw_counter_start: state.block_ctx.rwc,
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(source.id),
+        src_addr: source.offset.try_into().unwrap(),
+        src_addr_end: (source.offset + source.length).try_into().unwrap(),
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(destination.id),
+        dst_addr: destination.offset.try_into().unwrap(),
+        log_id: None,
+        bytes,
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    copy_event.read_src_memory(&state.call_ctx()?.memory);
+    copy_event.read_dst_memory_prev(&state.caller_ctx()?.memory);
+    state.push_copy(step, copy_event);
 
     Ok(())
 }
@@ -208,30 +195,22 @@ fn handle_create(
         .map(|element| (element.value, element.is_code))
         .collect();
 
-    let rw_counter_start = state.block_ctx.rwc;
-    for (i, (byte, _)) in bytes.iter().enumerate() {
-        state.push_op(
-            step,
-            RW::READ,
-            MemoryOp::new(source.id, (source.offset + i).into(), *byte),
-        );
-    }
-
-    state.push_copy(
-        step,
-        CopyEvent {
-            rw_counter_start,
-            src_type: CopyDataType::Memory,
-            src_id: NumberOrHash::Number(source.id),
-            src_addr: source.offset.try_into().unwrap(),
-            src_addr_end: (source.offset + source.length).try_into().unwrap(),
-            dst_type: CopyDataType::Bytecode,
-            dst_id,
-            dst_addr: 0,
-            log_id: None,
-            bytes,
-        },
-    );
+    let mut copy_event = CopyEvent {
+        rw_counter_start: state.block_ctx.rwc,
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(source.id),
+        src_addr: source.offset.try_into().unwrap(),
+        src_addr_end: (source.offset + source.length).try_into().unwrap(),
+        dst_type: CopyDataType::Bytecode,
+        dst_id,
+        dst_addr: 0,
+        log_id: None,
+        bytes,
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    copy_event.read_src_memory(&state.call_ctx()?.memory);
+    state.push_copy(step, copy_event);
 
     Ok(code_hash)
 }
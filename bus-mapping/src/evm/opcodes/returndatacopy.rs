@@ -3,7 +3,7 @@ use crate::{
         CircuitInputStateRef, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
     },
     evm::Opcode,
-    operation::{CallContextField, MemoryOp, RW},
+    operation::CallContextField,
     Error,
 };
 use eth_types::GethExecStep;
@@ -18,6 +18,7 @@ impl Opcode for Returndatacopy {
     ) -> Result<Vec<ExecStep>, Error> {
         let geth_step = &geth_steps[0];
         let mut exec_steps = vec![gen_returndatacopy_step(state, geth_step)?];
+        let copy_event = gen_copy_event(state, geth_step)?;
 
         // reconstruction
         let geth_step = &geth_steps[0];
@@ -32,7 +33,6 @@ impl Opcode for Returndatacopy {
         let memory = &mut call_ctx.memory;
         memory.copy_from(dst_offset, src_offset, length, &return_data);
 
-        let copy_event = gen_copy_event(state, geth_step)?;
         state.push_copy(&mut exec_steps[0], copy_event);
         Ok(exec_steps)
     }
@@ -89,10 +89,8 @@ fn gen_returndatacopy_step(
 }
 
 fn gen_copy_steps(
-    state: &mut CircuitInputStateRef,
-    exec_step: &mut ExecStep,
+    state: &CircuitInputStateRef,
     src_addr: u64,
-    dst_addr: u64,
     src_addr_end: u64,
     bytes_left: u64,
 ) -> Result<Vec<(u8, bool)>, Error> {
@@ -105,16 +103,7 @@ fn gen_copy_steps(
         } else {
             unreachable!("return data copy out of bound")
         };
-        // Read
-        state.push_op(
-            exec_step,
-            RW::READ,
-            MemoryOp::new(state.call()?.last_callee_id, addr.into(), value),
-        );
-
-        // Write
         copy_steps.push((value, false));
-        state.memory_write(exec_step, (dst_addr + idx).into(), value)?;
     }
     Ok(copy_steps)
 }
@@ -135,15 +124,7 @@ fn gen_copy_event(
     );
 
     let rw_counter_start = state.block_ctx.rwc;
-    let mut exec_step = state.new_step(geth_step)?;
-    let copy_steps = gen_copy_steps(
-        state,
-        &mut exec_step,
-        src_addr,
-        dst_addr,
-        src_addr_end,
-        length,
-    )?;
+    let copy_steps = gen_copy_steps(state, src_addr, src_addr_end, length)?;
 
     let (src_type, dst_type, src_id, dst_id) = (
         CopyDataType::Memory,
@@ -152,7 +133,7 @@ fn gen_copy_event(
         NumberOrHash::Number(state.call()?.call_id),
     );
 
-    Ok(CopyEvent {
+    let mut copy_event = CopyEvent {
         src_type,
         src_id,
         src_addr,
@@ -163,7 +144,15 @@ fn gen_copy_event(
         log_id: None,
         rw_counter_start,
         bytes: copy_steps,
-    })
+        src_memory: vec![],
+        dst_memory_prev: vec![],
+    };
+    // The return data is copied from the memory of the last callee.
+    let call_ctx = state.call_ctx()?;
+    copy_event.read_src_memory(&call_ctx.last_callee_memory);
+    copy_event.read_dst_memory_prev(&call_ctx.memory);
+
+    Ok(copy_event)
 }
 
 #[cfg(test)]
@@ -53,30 +53,26 @@ impl Opcode for Sha3 {
 
         // Memory read operations
         let rw_counter_start = state.block_ctx.rwc;
-        let mut steps = Vec::with_capacity(size.as_usize());
-        for (i, byte) in memory.iter().enumerate() {
-            // Read step
-            state.memory_read(&mut exec_step, (offset.as_usize() + i).into(), *byte)?;
-            steps.push((*byte, false));
-        }
+        let steps = memory.iter().map(|byte| (*byte, false)).collect();
         state.block.sha3_inputs.push(memory);
 
         let call_id = state.call()?.call_id;
-        state.push_copy(
-            &mut exec_step,
-            CopyEvent {
-                src_addr: offset.as_u64(),
-                src_addr_end: offset.as_u64() + size.as_u64(),
-                src_type: CopyDataType::Memory,
-                src_id: NumberOrHash::Number(call_id),
-                dst_addr: 0,
-                dst_type: CopyDataType::RlcAcc,
-                dst_id: NumberOrHash::Number(call_id),
-                log_id: None,
-                rw_counter_start,
-                bytes: steps,
-            },
-        );
+        let mut copy_event = CopyEvent {
+            src_addr: offset.as_u64(),
+            src_addr_end: offset.as_u64() + size.as_u64(),
+            src_type: CopyDataType::Memory,
+            src_id: NumberOrHash::Number(call_id),
+            dst_addr: 0,
+            dst_type: CopyDataType::RlcAcc,
+            dst_id: NumberOrHash::Number(call_id),
+            log_id: None,
+            rw_counter_start,
+            bytes: steps,
+            src_memory: vec![],
+            dst_memory_prev: vec![],
+        };
+        copy_event.read_src_memory(&state.call_ctx()?.memory);
+        state.push_copy(&mut exec_step, copy_event);
 
         Ok(vec![exec_step])
     }
@@ -202,6 +198,17 @@ pub(crate) mod sha3_tests {
         let (size, offset) = (gen.size, gen.offset);
         let memory_len = memory.len();
 
+        // The memory words that contain the hashed bytes.
+        let word_addr = offset / 32 * 32;
+        let num_words = (offset - word_addr + size + 31) / 32;
+        let mut memory_words = memory
+            .iter()
+            .copied()
+            .skip(word_addr)
+            .take(32 * num_words)
+            .collect::<Vec<u8>>();
+        memory_words.resize(32 * num_words, 0);
+
         // The memory that is hashed.
         let mut memory_view = memory
             .into_iter()
@@ -262,24 +269,25 @@ pub(crate) mod sha3_tests {
         );
 
         // Memory reads.
-        // Initial memory_len bytes are the memory writes from MSTORE instruction, so we
-        // skip them.
+        // Initial memory_len / 16 memory ops are the word writes from MSTORE
+        // instructions, so we skip them.
+        let num_mstore_ops = memory_len / 16;
         assert_eq!(
-            (memory_len..(memory_len + size))
+            (num_mstore_ops..(num_mstore_ops + num_words))
                 .map(|idx| &builder.block.container.memory[idx])
                 .map(|op| (op.rw(), op.op().clone()))
                 .collect::<Vec<(RW, MemoryOp)>>(),
-            {
-                let mut memory_ops = Vec::with_capacity(size);
-                (0..size).for_each(|idx| {
-                    let value = memory_view[idx];
-                    memory_ops.push((
+            memory_words
+                .chunks(32)
+                .enumerate()
+                .map(|(idx, word)| {
+                    let word = Word::from_big_endian(word);
+                    (
                         RW::READ,
-                        MemoryOp::new(call_id, (offset + idx).into(), value),
-                    ));
-                });
-                memory_ops
-            },
+                        MemoryOp::new(call_id, (word_addr + 32 * idx).into(), word, word),
+                    )
+                })
+                .collect::<Vec<(RW, MemoryOp)>>(),
         );
 
         let copy_events = builder.block.copy_events.clone();
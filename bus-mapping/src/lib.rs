@@ -227,6 +227,7 @@ extern crate alloc;
 extern crate core;
 
 pub mod circuit_input_builder;
+pub mod coverage;
 pub mod error;
 pub mod evm;
 pub mod exec_trace;
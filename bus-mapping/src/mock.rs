@@ -4,7 +4,8 @@ use crate::{
     circuit_input_builder::{get_state_accesses, Block, CircuitInputBuilder, CircuitsParams},
     state_db::{self, CodeDB, StateDB},
 };
-use eth_types::{geth_types::GethData, Word};
+use eth_types::{evm_types::ChainSpec, geth_types::GethData, Word};
+use std::collections::HashSet;
 
 /// BlockData is a type that contains all the information from a block required
 /// to build the circuit inputs.
@@ -25,6 +26,8 @@ pub struct BlockData {
     pub geth_traces: Vec<eth_types::GethExecTrace>,
     /// Circuits setup parameters
     pub circuits_params: CircuitsParams,
+    /// Hardfork rules the block was executed with
+    pub chain_spec: ChainSpec,
 }
 
 impl BlockData {
@@ -42,7 +45,8 @@ impl BlockData {
                 self.circuits_params,
                 None,
             )
-            .unwrap(),
+            .unwrap()
+            .with_chain_spec(self.chain_spec),
         )
     }
     /// Create a new block from the given Geth data.
@@ -73,6 +77,53 @@ impl BlockData {
             eth_block: geth_data.eth_block,
             geth_traces: geth_data.geth_traces,
             circuits_params,
+            chain_spec: geth_data.chain_spec,
+        }
+    }
+
+    /// Create a new block from the Geth data of a batch of consecutive blocks,
+    /// whose transactions are then handled with
+    /// [`CircuitInputBuilder::handle_blocks`].  The initial state of an
+    /// account is taken from the first block of the batch that touches it.
+    pub fn new_from_geth_data_batch(
+        geth_datas: &[GethData],
+        circuits_params: CircuitsParams,
+    ) -> Self {
+        let mut sdb = StateDB::new();
+        let mut code_db = CodeDB::new();
+
+        // Accounts already described by a previous block of the batch
+        let mut seen = HashSet::new();
+        for geth_data in geth_datas {
+            let access_set =
+                get_state_accesses(&geth_data.eth_block, &geth_data.geth_traces, &None)
+                    .expect("state accesses");
+            // Initialize all accesses accounts to zero
+            for addr in access_set.state.keys() {
+                if !seen.contains(addr) {
+                    sdb.set_account(addr, state_db::Account::zero());
+                }
+            }
+            for account in &geth_data.accounts {
+                if !seen.contains(&account.address) {
+                    code_db.insert(account.code.to_vec());
+                    sdb.set_account(&account.address, state_db::Account::from(account.clone()));
+                }
+            }
+            seen.extend(access_set.state.keys().copied());
+            seen.extend(geth_data.accounts.iter().map(|account| account.address));
+        }
+
+        let first = geth_datas.first().expect("batch has at least one block");
+        Self {
+            sdb,
+            code_db,
+            chain_id: first.chain_id,
+            history_hashes: first.history_hashes.clone(),
+            eth_block: first.eth_block.clone(),
+            geth_traces: first.geth_traces.clone(),
+            circuits_params,
+            chain_spec: first.chain_spec,
         }
     }
 
@@ -81,3 +132,74 @@ impl BlockData {
         Self::new_from_geth_data_with_params(geth_data, CircuitsParams::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{bytecode, evm_types::OpcodeId};
+    use mock::{eth, TestContextBuilder, MOCK_ACCOUNTS};
+
+    #[test]
+    fn test_context_builder_blocks_share_state() {
+        // Increment the counter at slot 0
+        let code = bytecode! {
+            PUSH1(0)
+            SLOAD
+            PUSH1(1)
+            ADD
+            PUSH1(0)
+            SSTORE
+            STOP
+        };
+        let mut ctx = TestContextBuilder::new();
+        ctx.account(|acc| {
+            acc.address(MOCK_ACCOUNTS[0]).code(code);
+        })
+        .account(|acc| {
+            acc.address(MOCK_ACCOUNTS[1]).balance(eth(10));
+        });
+        for i in 0..3u64 {
+            ctx.block(|block| block.number(0xcafe + i * 10)).tx(|tx| {
+                tx.from(MOCK_ACCOUNTS[1]).to(MOCK_ACCOUNTS[0]);
+            });
+        }
+        let blocks = ctx.build().unwrap();
+
+        for (i, block) in blocks.iter().enumerate() {
+            // Only the first block number is kept, the next ones follow it
+            assert_eq!(block.eth_block.number.unwrap().as_u64(), 0xcafe + i as u64);
+            if i > 0 {
+                assert_eq!(
+                    block.eth_block.parent_hash,
+                    blocks[i - 1].eth_block.hash.unwrap()
+                );
+            }
+            assert_eq!(block.eth_block.transactions[0].nonce, Word::from(i));
+            // The counter read by each block was written by the previous one
+            let steps = &block.geth_traces[0].struct_logs;
+            let sload = steps
+                .iter()
+                .position(|step| step.op == OpcodeId::SLOAD)
+                .unwrap();
+            assert_eq!(steps[sload + 1].stack.last().unwrap(), Word::from(i));
+        }
+
+        let circuits_params = CircuitsParams {
+            max_txs: 3,
+            ..Default::default()
+        };
+        let mut builder = BlockData::new_from_geth_data_batch(&blocks, circuits_params)
+            .new_circuit_input_builder();
+        let batch: Vec<_> = blocks
+            .iter()
+            .map(|block| (&block.eth_block, block.geth_traces.as_slice()))
+            .collect();
+        builder.handle_blocks(&batch).unwrap();
+        assert_eq!(builder.block.txs().len(), 3);
+        assert_eq!(
+            builder.sdb.get_storage(&MOCK_ACCOUNTS[0], &Word::zero()).1,
+            &Word::from(3)
+        );
+        assert_eq!(builder.sdb.get_account(&MOCK_ACCOUNTS[1]).1.nonce, 3);
+    }
+}
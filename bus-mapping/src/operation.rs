@@ -4,7 +4,7 @@
 //! - Define structures that interact with operations such as [`OperationContainer`].
 pub(crate) mod container;
 
-pub use container::OperationContainer;
+pub use container::{OperationContainer, OperationContainerLens};
 pub use eth_types::evm_types::{MemoryAddress, StackAddress};
 use gadgets::impl_expr;
 use halo2_proofs::plonk::Expression;
@@ -152,22 +152,27 @@ pub trait Op: Clone + Eq + Ord {
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the memory implied
 /// by an specific [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
 /// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
+///
+/// Memory is accessed in 32-byte words: the operation reads or writes the whole
+/// word that starts at `address`.
 #[derive(Clone, PartialEq, Eq)]
 pub struct MemoryOp {
     /// Call ID
     pub call_id: usize,
-    /// Memory Address
+    /// Memory Address of the word, which is a multiple of 32
     pub address: MemoryAddress,
-    /// Value
-    pub value: u8,
+    /// Value of the word, whose most significant byte is the one at `address`
+    pub value: Word,
+    /// Value of the word before the operation
+    pub value_prev: Word,
 }
 
 impl fmt::Debug for MemoryOp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("MemoryOp { ")?;
         f.write_fmt(format_args!(
-            "call_id: {:?}, addr: {:?}, value: 0x{:02x}",
-            self.call_id, self.address, self.value
+            "call_id: {:?}, addr: {:?}, value: {:?}, value_prev: {:?}",
+            self.call_id, self.address, self.value, self.value_prev
         ))?;
         f.write_str(" }")
     }
@@ -175,11 +180,13 @@ impl fmt::Debug for MemoryOp {
 
 impl MemoryOp {
     /// Create a new instance of a `MemoryOp` from it's components.
-    pub fn new(call_id: usize, address: MemoryAddress, value: u8) -> MemoryOp {
+    pub fn new(call_id: usize, address: MemoryAddress, value: Word, value_prev: Word) -> MemoryOp {
+        debug_assert_eq!(address.0 % 32, 0, "memory word address is not aligned");
         MemoryOp {
             call_id,
             address,
             value,
+            value_prev,
         }
     }
 
@@ -198,9 +205,14 @@ impl MemoryOp {
         &self.address
     }
 
-    /// Returns the bytes read or written by this operation.
-    pub fn value(&self) -> u8 {
-        self.value
+    /// Returns the word read or written by this operation.
+    pub const fn value(&self) -> &Word {
+        &self.value
+    }
+
+    /// Returns the word before this operation.
+    pub const fn value_prev(&self) -> &Word {
+        &self.value_prev
     }
 }
 
@@ -769,8 +781,10 @@ pub enum TxLogField {
     Topic,
     /// data of log entry
     Data,
-    // TODO: Add `TopicLength` and `DataLength`, which will be used for the RLP encoding of the
-    // Tx Receipt
+    /// number of topics of log entry, used for the RLP encoding of the Tx Receipt
+    TopicLength,
+    /// byte length of the data of log entry, used for the RLP encoding of the Tx Receipt
+    DataLength,
 }
 
 /// Represents TxLog read/write operation.
@@ -1102,7 +1116,7 @@ mod operation_tests {
 
         let stack_op_as_operation = Operation::new(RWCounter(1), RW::WRITE, stack_op.clone());
 
-        let memory_op = MemoryOp::new(1, MemoryAddress(0x40), 0x40);
+        let memory_op = MemoryOp::new(1, MemoryAddress(0x40), Word::from(0x40), Word::zero());
 
         let memory_op_as_operation = Operation::new(RWCounter(1), RW::WRITE, memory_op.clone());
 
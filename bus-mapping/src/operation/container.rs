@@ -184,6 +184,57 @@ impl OperationContainer {
     pub fn sorted_storage(&self) -> Vec<Operation<StorageOp>> {
         self.storage.iter().sorted().cloned().collect()
     }
+
+    /// Returns the number of operations of each target, which can be passed to
+    /// [`Self::truncate`] to drop the operations inserted afterwards.
+    pub fn lens(&self) -> OperationContainerLens {
+        OperationContainerLens {
+            memory: self.memory.len(),
+            stack: self.stack.len(),
+            storage: self.storage.len(),
+            tx_access_list_account: self.tx_access_list_account.len(),
+            tx_access_list_account_storage: self.tx_access_list_account_storage.len(),
+            tx_refund: self.tx_refund.len(),
+            account: self.account.len(),
+            call_context: self.call_context.len(),
+            tx_receipt: self.tx_receipt.len(),
+            tx_log: self.tx_log.len(),
+            start: self.start.len(),
+        }
+    }
+
+    /// Drops the operations inserted after `lens` were taken.
+    pub fn truncate(&mut self, lens: OperationContainerLens) {
+        self.memory.truncate(lens.memory);
+        self.stack.truncate(lens.stack);
+        self.storage.truncate(lens.storage);
+        self.tx_access_list_account
+            .truncate(lens.tx_access_list_account);
+        self.tx_access_list_account_storage
+            .truncate(lens.tx_access_list_account_storage);
+        self.tx_refund.truncate(lens.tx_refund);
+        self.account.truncate(lens.account);
+        self.call_context.truncate(lens.call_context);
+        self.tx_receipt.truncate(lens.tx_receipt);
+        self.tx_log.truncate(lens.tx_log);
+        self.start.truncate(lens.start);
+    }
+}
+
+/// Number of operations of each target in an [`OperationContainer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationContainerLens {
+    memory: usize,
+    stack: usize,
+    storage: usize,
+    tx_access_list_account: usize,
+    tx_access_list_account_storage: usize,
+    tx_refund: usize,
+    account: usize,
+    call_context: usize,
+    tx_receipt: usize,
+    tx_log: usize,
+    start: usize,
 }
 
 #[cfg(test)]
@@ -208,7 +259,7 @@ mod container_test {
         let memory_operation = Operation::new(
             global_counter.inc_pre(),
             RW::WRITE,
-            MemoryOp::new(1, MemoryAddress::from(1), 1),
+            MemoryOp::new(1, MemoryAddress::from(32), Word::from(1), Word::zero()),
         );
         let storage_operation = Operation::new(
             global_counter.inc_pre(),
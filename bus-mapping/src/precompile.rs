@@ -1,18 +1,26 @@
 //! precompile helpers
 
-use eth_types::{evm_types::GasCost, Address};
+use eth_types::{
+    evm_types::{ChainSpec, GasCost, Hardfork},
+    Address,
+};
 use revm_precompile::{Precompile, Precompiles};
 use strum::EnumIter;
 
-/// Check if address is a precompiled or not.
-pub fn is_precompiled(address: &Address) -> bool {
-    Precompiles::berlin()
-        .get(address.as_fixed_bytes())
-        .is_some()
+/// Precompiled contracts of the hardfork of `chain_spec`.
+fn precompiles(chain_spec: &ChainSpec) -> &'static Precompiles {
+    match chain_spec.hardfork {
+        Hardfork::Berlin | Hardfork::London | Hardfork::Shanghai => Precompiles::berlin(),
+    }
 }
 
-pub(crate) fn execute_precompiled(address: &Address, input: &[u8], gas: u64) -> (Vec<u8>, u64) {
-    let Some(Precompile::Standard(precompile_fn)) = Precompiles::berlin()
+pub(crate) fn execute_precompiled(
+    chain_spec: &ChainSpec,
+    address: &Address,
+    input: &[u8],
+    gas: u64,
+) -> (Vec<u8>, u64) {
+    let Some(Precompile::Standard(precompile_fn)) = precompiles(chain_spec)
         .get(address.as_fixed_bytes())  else {
         panic!("calling non-exist precompiled contract address")
     };
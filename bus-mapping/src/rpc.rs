@@ -3,12 +3,12 @@
 
 use crate::Error;
 use eth_types::{
-    Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, Hash, ResultGethExecTraces,
-    Transaction, Word, U64,
+    Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, GethPrestateDiffTrace,
+    GethPrestateTrace, Hash, ResultGethExecTraces, ResultGethTracerTraces, Transaction, Word, U64,
 };
 pub use ethers_core::types::BlockNumber;
 use ethers_providers::JsonRpcClient;
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
 /// Serialize a type.
 ///
@@ -47,6 +47,25 @@ impl Default for GethLoggerConfig {
     }
 }
 
+#[derive(Serialize)]
+#[doc(hidden)]
+pub(crate) struct GethPrestateTracerConfig {
+    /// report the state before and after each tx instead of only the
+    /// state before it
+    #[serde(rename = "diffMode")]
+    diff_mode: bool,
+}
+
+#[derive(Serialize)]
+#[doc(hidden)]
+pub(crate) struct GethTracerOptions<C: Serialize> {
+    /// name of the native tracer
+    tracer: &'static str,
+    /// configuration of the native tracer
+    #[serde(rename = "tracerConfig")]
+    tracer_config: C,
+}
+
 /// Placeholder structure designed to contain the methods that the BusMapping
 /// needs in order to enable Geth queries.
 pub struct GethClient<P: JsonRpcClient>(pub P);
@@ -119,6 +138,16 @@ impl<P: JsonRpcClient> GethClient<P> {
         }
     }
 
+    /// Calls `debug_intermediateRoots` via JSON-RPC returning the state root
+    /// after each transaction of the block.
+    pub async fn intermediate_roots(&self, hash: Hash) -> Result<Vec<Hash>, Error> {
+        let hash = serialize(&hash);
+        self.0
+            .request("debug_intermediateRoots", [hash])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))
+    }
+
     /// Calls `debug_traceBlockByNumber` via JSON-RPC returning a
     /// [`Vec<GethExecTrace>`] with each GethTrace corresponding to 1
     /// transaction of the block.
@@ -141,6 +170,47 @@ impl<P: JsonRpcClient> GethClient<P> {
         }
     }
 
+    /// Calls `debug_traceBlockByNumber` with the `prestateTracer` via
+    /// JSON-RPC returning a [`Vec<GethPrestateTrace>`] with the state of
+    /// every account touched by each transaction of the block, before the
+    /// transaction is executed.
+    pub async fn trace_block_prestate_by_number(
+        &self,
+        block_num: BlockNumber,
+    ) -> Result<Vec<GethPrestateTrace>, Error> {
+        self.trace_block_with_prestate_tracer(block_num, false)
+            .await
+    }
+
+    /// Calls `debug_traceBlockByNumber` with the `prestateTracer` in diff
+    /// mode via JSON-RPC returning a [`Vec<GethPrestateDiffTrace>`] with the
+    /// state before and after each transaction of the block of every account
+    /// modified by it.
+    pub async fn trace_block_prestate_diff_by_number(
+        &self,
+        block_num: BlockNumber,
+    ) -> Result<Vec<GethPrestateDiffTrace>, Error> {
+        self.trace_block_with_prestate_tracer(block_num, true).await
+    }
+
+    async fn trace_block_with_prestate_tracer<T: DeserializeOwned + Send>(
+        &self,
+        block_num: BlockNumber,
+        diff_mode: bool,
+    ) -> Result<Vec<T>, Error> {
+        let num = serialize(&block_num);
+        let cfg = serialize(&GethTracerOptions {
+            tracer: "prestateTracer",
+            tracer_config: GethPrestateTracerConfig { diff_mode },
+        });
+        let resp: ResultGethTracerTraces<T> = self
+            .0
+            .request("debug_traceBlockByNumber", [num, cfg])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))?;
+        Ok(resp.0.into_iter().map(|trace| trace.result).collect())
+    }
+
     /// Calls `eth_getCode` via JSON-RPC returning a contract code
     pub async fn get_code(
         &self,
@@ -21,7 +21,11 @@ const VALUE_ZERO: Word = Word::zero();
 
 /// Memory storage for contract code by code hash.
 #[derive(Debug, Clone)]
-pub struct CodeDB(pub HashMap<Hash, Vec<u8>>);
+pub struct CodeDB(
+    pub HashMap<Hash, Vec<u8>>,
+    /// Hashes of the codes inserted since the journal was started
+    Option<Vec<Hash>>,
+);
 
 impl Default for CodeDB {
     fn default() -> Self {
@@ -32,15 +36,36 @@ impl Default for CodeDB {
 impl CodeDB {
     /// Create a new empty Self.
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self(HashMap::new(), None)
     }
     /// Insert code indexed by code hash, and return the code hash.
     pub fn insert(&mut self, code: Vec<u8>) -> Hash {
         let hash = Self::hash(&code);
-        self.0.insert(hash, code);
+        if self.0.insert(hash, code).is_none() {
+            if let Some(journal) = self.1.as_mut() {
+                journal.push(hash);
+            }
+        }
         hash
     }
 
+    /// Start recording the inserted codes, to be able to revert them.
+    pub fn start_journal(&mut self) {
+        self.1 = Some(Vec::new());
+    }
+
+    /// Stop recording the inserted codes, keeping them.
+    pub fn discard_journal(&mut self) {
+        self.1 = None;
+    }
+
+    /// Remove the codes inserted since [`Self::start_journal`].
+    pub fn revert_journal(&mut self) {
+        for hash in self.1.take().unwrap_or_default() {
+            self.0.remove(&hash);
+        }
+    }
+
     /// Compute hash of given code.
     pub fn hash(code: &[u8]) -> Hash {
         H256(keccak256(code))
@@ -112,6 +137,9 @@ pub struct StateDB {
     // `true`. These accounts will be reset once `commit_tx` is called.
     destructed_account: HashSet<Address>,
     refund: u64,
+    // Accounts before their first change since the journal was started, `None`
+    // for the accounts that didn't exist.
+    journal: Option<HashMap<Address, Option<Account>>>,
 }
 
 impl StateDB {
@@ -122,6 +150,7 @@ impl StateDB {
 
     /// Set an [`Account`] at `addr` in the StateDB.
     pub fn set_account(&mut self, addr: &Address, acc: Account) {
+        self.journal_account(addr);
         self.state.insert(*addr, acc);
     }
 
@@ -138,6 +167,7 @@ impl StateDB {
     /// [`Account`] is not found in the state, a zero one will be inserted
     /// and returned along with false.
     pub fn get_account_mut(&mut self, addr: &Address) -> (bool, &mut Account) {
+        self.journal_account(addr);
         let found = if self.state.contains_key(addr) {
             true
         } else {
@@ -248,6 +278,11 @@ impl StateDB {
         self.destructed_account.insert(addr);
     }
 
+    /// Check if account has been self destructed in the transaction.
+    pub fn is_account_destructed(&self, addr: &Address) -> bool {
+        self.destructed_account.contains(addr)
+    }
+
     /// Retrieve refund.
     pub fn refund(&self) -> u64 {
         self.refund
@@ -275,6 +310,41 @@ impl StateDB {
         }
         self.refund = 0;
     }
+
+    /// Start recording the accounts changed from now on, to be able to revert
+    /// them.  It should be invoked between transactions, once committed.
+    pub fn start_journal(&mut self) {
+        self.journal = Some(HashMap::new());
+    }
+
+    /// Stop recording the changed accounts, keeping their changes.
+    pub fn discard_journal(&mut self) {
+        self.journal = None;
+    }
+
+    /// Revert the accounts changed since [`Self::start_journal`], and clear
+    /// the fields of transaction lifespan.
+    pub fn revert_journal(&mut self) {
+        for (addr, account) in self.journal.take().unwrap_or_default() {
+            match account {
+                Some(account) => self.state.insert(addr, account),
+                None => self.state.remove(&addr),
+            };
+        }
+        self.access_list_account = HashSet::new();
+        self.access_list_account_storage = HashSet::new();
+        self.dirty_storage = HashMap::new();
+        self.destructed_account = HashSet::new();
+        self.refund = 0;
+    }
+
+    fn journal_account(&mut self, addr: &Address) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal
+                .entry(*addr)
+                .or_insert_with(|| self.state.get(addr).cloned());
+        }
+    }
 }
 
 #[cfg(test)]
@@ -336,4 +406,46 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn statedb_revert_journal() {
+        let addr_a = address!("0x0000000000000000000000000000000000000001");
+        let addr_b = address!("0x0000000000000000000000000000000000000002");
+        let mut statedb = StateDB::new();
+        statedb.get_account_mut(&addr_a).1.nonce = 1;
+
+        statedb.start_journal();
+        statedb.get_account_mut(&addr_a).1.nonce = 2;
+        *statedb.get_storage_mut(&addr_b, &Word::from(3)).1 = Word::from(4);
+        statedb.set_storage(&addr_a, &Word::from(5), &Word::from(6));
+        statedb.revert_journal();
+
+        assert_eq!(statedb.get_nonce(&addr_a), 1);
+        assert!(!statedb.get_account(&addr_b).0);
+        assert_eq!(
+            statedb.get_storage(&addr_a, &Word::from(5)),
+            (false, &Word::zero())
+        );
+
+        // Changes after the journal is discarded are kept
+        statedb.start_journal();
+        statedb.get_account_mut(&addr_a).1.nonce = 2;
+        statedb.discard_journal();
+        statedb.revert_journal();
+        assert_eq!(statedb.get_nonce(&addr_a), 2);
+    }
+
+    #[test]
+    fn codedb_revert_journal() {
+        let mut code_db = CodeDB::new();
+        let kept = code_db.insert(vec![0x00]);
+
+        code_db.start_journal();
+        code_db.insert(vec![0x00]);
+        let reverted = code_db.insert(vec![0x01]);
+        code_db.revert_journal();
+
+        assert!(code_db.0.contains_key(&kept));
+        assert!(!code_db.0.contains_key(&reverted));
+    }
 }
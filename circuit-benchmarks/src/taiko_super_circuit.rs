@@ -175,6 +175,7 @@ mod tests {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         };
         let protocol_instance = ProtocolInstance::default();
         let (_, super_circuit, _, _) =
@@ -477,6 +478,7 @@ mod tests {
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         };
         let (_, circuit, instance, _) =
             SuperCircuit::build(block, circuits_params, ProtocolInstance::default()).unwrap();
@@ -3,12 +3,14 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub mod chain_spec;
 pub mod gas_utils;
 pub mod memory;
 pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
+pub use chain_spec::{ChainSpec, Hardfork};
 pub use memory::{Memory, MemoryAddress};
 pub use opcode_ids::OpcodeId;
 pub use stack::{Stack, StackAddress};
@@ -0,0 +1,177 @@
+//! Rules of the EVM that change between hardforks.
+
+use super::{GasCost, OpcodeId, INIT_CODE_WORD_GAS, MAX_REFUND_QUOTIENT_OF_GAS_USED};
+use crate::Address;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+/// Maximum size of the init code of a contract creation, introduced by
+/// EIP-3860.
+pub const MAX_INIT_CODE_SIZE: usize = 2 * 24576;
+
+/// Ethereum hardforks whose rules can be proven, starting from the access lists
+/// of Berlin that the circuits rely on.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    EnumIter,
+)]
+pub enum Hardfork {
+    /// Berlin
+    Berlin,
+    /// London: `BASEFEE` (EIP-3198) and reduced refunds (EIP-3529)
+    London,
+    /// Shanghai: `PUSH0` (EIP-3855) and init code limits (EIP-3860)
+    #[default]
+    Shanghai,
+}
+
+/// Rules of the chain whose blocks are proven.  Taiko chains follow the rules
+/// of the Ethereum hardfork they are based on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChainSpec {
+    /// Hardfork active in the proven blocks
+    pub hardfork: Hardfork,
+}
+
+impl ChainSpec {
+    /// Create the rules of `hardfork`.
+    pub const fn new(hardfork: Hardfork) -> Self {
+        Self { hardfork }
+    }
+
+    /// Returns `true` unless `opcode` is introduced by a later hardfork, in
+    /// which case it's an invalid opcode.
+    pub fn is_opcode_available(&self, opcode: OpcodeId) -> bool {
+        match opcode {
+            OpcodeId::BASEFEE => self.hardfork >= Hardfork::London,
+            OpcodeId::PUSH0 => self.hardfork >= Hardfork::Shanghai,
+            _ => true,
+        }
+    }
+
+    /// Returns the constant gas cost of `opcode`, which is zero when it's not
+    /// available.
+    pub fn constant_gas_cost(&self, opcode: OpcodeId) -> GasCost {
+        if self.is_opcode_available(opcode) {
+            opcode.constant_gas_cost()
+        } else {
+            GasCost::ZERO
+        }
+    }
+
+    /// Number of precompiled contracts, stored from address 0x01.
+    pub fn num_precompiles(&self) -> u8 {
+        match self.hardfork {
+            // Blake2F of Istanbul is the last one
+            Hardfork::Berlin | Hardfork::London | Hardfork::Shanghai => 9,
+        }
+    }
+
+    /// Check if address is a precompiled or not.
+    pub fn is_precompiled(&self, address: &Address) -> bool {
+        address.0[0..19] == [0u8; 19] && (1..=self.num_precompiles()).contains(&address.0[19])
+    }
+
+    /// Quotient of the gas used capping the refund of a transaction.
+    pub fn max_refund_quotient(&self) -> u64 {
+        match self.hardfork {
+            Hardfork::Berlin => 2,
+            Hardfork::London | Hardfork::Shanghai => MAX_REFUND_QUOTIENT_OF_GAS_USED as u64,
+        }
+    }
+
+    /// Refund of the first `SELFDESTRUCT` of an account in a transaction,
+    /// removed by EIP-3529 in London.
+    pub fn selfdestruct_refund(&self) -> u64 {
+        match self.hardfork {
+            Hardfork::Berlin => 24000,
+            Hardfork::London | Hardfork::Shanghai => 0,
+        }
+    }
+
+    /// Returns `true` if the coinbase is added to the access list at the
+    /// beginning of a transaction, following EIP-3651 of Shanghai.
+    pub fn is_coinbase_warm(&self) -> bool {
+        self.hardfork >= Hardfork::Shanghai
+    }
+
+    /// Refund of a storage clear.
+    pub fn sstore_clears_schedule(&self) -> GasCost {
+        match self.hardfork {
+            Hardfork::Berlin => GasCost(15000),
+            Hardfork::London | Hardfork::Shanghai => GasCost::SSTORE_CLEARS_SCHEDULE,
+        }
+    }
+
+    /// Gas paid per word of the init code of a creation transaction.
+    pub fn init_code_word_gas(&self) -> u64 {
+        match self.hardfork {
+            Hardfork::Berlin | Hardfork::London => 0,
+            Hardfork::Shanghai => INIT_CODE_WORD_GAS,
+        }
+    }
+
+    /// Maximum size of the init code of a creation, if limited.
+    pub fn max_init_code_size(&self) -> Option<usize> {
+        match self.hardfork {
+            Hardfork::Berlin | Hardfork::London => None,
+            Hardfork::Shanghai => Some(MAX_INIT_CODE_SIZE),
+        }
+    }
+}
+
+#[cfg(test)]
+mod chain_spec_tests {
+    use super::*;
+
+    #[test]
+    fn opcode_availability() {
+        let berlin = ChainSpec::new(Hardfork::Berlin);
+        let shanghai = ChainSpec::default();
+        assert!(!berlin.is_opcode_available(OpcodeId::BASEFEE));
+        assert!(!berlin.is_opcode_available(OpcodeId::PUSH0));
+        assert_eq!(berlin.constant_gas_cost(OpcodeId::PUSH0), GasCost::ZERO);
+        assert!(ChainSpec::new(Hardfork::London).is_opcode_available(OpcodeId::BASEFEE));
+        assert!(shanghai.is_opcode_available(OpcodeId::PUSH0));
+        assert_eq!(shanghai.constant_gas_cost(OpcodeId::PUSH0), GasCost::QUICK);
+    }
+
+    #[test]
+    fn hardfork_rules() {
+        let berlin = ChainSpec::new(Hardfork::Berlin);
+        let london = ChainSpec::new(Hardfork::London);
+        let shanghai = ChainSpec::default();
+        assert_eq!(berlin.selfdestruct_refund(), 24000);
+        assert_eq!(london.selfdestruct_refund(), 0);
+        assert!(!berlin.is_coinbase_warm());
+        assert!(!london.is_coinbase_warm());
+        assert!(shanghai.is_coinbase_warm());
+        assert_eq!(london.init_code_word_gas(), 0);
+        assert_eq!(london.max_init_code_size(), None);
+        assert_eq!(shanghai.max_init_code_size(), Some(MAX_INIT_CODE_SIZE));
+    }
+
+    #[test]
+    fn precompiles() {
+        let spec = ChainSpec::default();
+        let address = |byte| {
+            let mut address = Address::zero();
+            address.0[19] = byte;
+            address
+        };
+        assert!(!spec.is_precompiled(&address(0x00)));
+        assert!(spec.is_precompiled(&address(0x01)));
+        assert!(spec.is_precompiled(&address(0x09)));
+        assert!(!spec.is_precompiled(&address(0x0a)));
+    }
+}
@@ -1,6 +1,7 @@
 //! Types needed for generating Ethereum traces
 
 use crate::{
+    evm_types::ChainSpec,
     sign_types::{biguint_to_32bytes_le, ct_option_ok_or, recover_pk, SignData, SECP256K1_Q},
     AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
     ToWord, Word, U64,
@@ -8,7 +9,7 @@ use crate::{
 use ethers_core::{
     types::{
         transaction::{eip2718::TypedTransaction, response},
-        Eip1559TransactionRequest, NameOrAddress,
+        Eip1559TransactionRequest, Eip2930TransactionRequest, NameOrAddress, TransactionRequest,
     },
     utils::get_contract_address,
 };
@@ -143,6 +144,9 @@ pub struct Transaction {
     pub call_data: Bytes,
     /// Access list
     pub access_list: Option<AccessList>,
+    /// EIP-2718 type of the transaction: 0 for legacy, 1 for EIP-2930 and 2
+    /// for EIP-1559.  Transactions without type are EIP-1559 ones.
+    pub transaction_type: Option<U64>,
 
     /// "v" value of the transaction signature
     pub v: u64,
@@ -165,6 +169,7 @@ impl From<&Transaction> for crate::Transaction {
             max_fee_per_gas: Some(tx.gas_fee_cap),
             input: tx.call_data.clone(),
             access_list: tx.access_list.clone(),
+            transaction_type: tx.transaction_type,
             v: tx.v.into(),
             r: tx.r,
             s: tx.s,
@@ -186,6 +191,7 @@ impl From<&crate::Transaction> for Transaction {
             gas_fee_cap: tx.max_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
+            transaction_type: tx.transaction_type,
             v: tx.v.as_u64(),
             r: tx.r,
             s: tx.s,
@@ -193,6 +199,21 @@ impl From<&crate::Transaction> for Transaction {
     }
 }
 
+impl From<&Transaction> for TransactionRequest {
+    fn from(tx: &Transaction) -> TransactionRequest {
+        TransactionRequest {
+            from: Some(tx.from),
+            to: tx.to.map(NameOrAddress::Address),
+            gas: Some(tx.gas_limit.to_word()),
+            gas_price: Some(tx.gas_price),
+            value: Some(tx.value),
+            data: Some(tx.call_data.clone()),
+            nonce: Some(tx.nonce.to_word()),
+            ..Default::default()
+        }
+    }
+}
+
 impl From<&Transaction> for Eip1559TransactionRequest {
     fn from(tx: &Transaction) -> Eip1559TransactionRequest {
         Eip1559TransactionRequest {
@@ -204,6 +225,7 @@ impl From<&Transaction> for Eip1559TransactionRequest {
             value: Some(tx.value),
             data: Some(tx.call_data.clone()),
             nonce: Some(tx.nonce.to_word()),
+            access_list: tx.access_list.clone().unwrap_or_default(),
             ..Default::default()
         }
     }
@@ -221,6 +243,34 @@ fn recover_v(v: u64, chain_id: u64) -> u64 {
 }
 
 impl Transaction {
+    /// Return the unsigned transaction of the type of this one, whose hash is
+    /// signed by the sender.  Legacy transactions include the chain id as in
+    /// EIP-155, unless they're signed with a `v` of 27 or 28.
+    pub fn typed_request(&self, chain_id: u64) -> TypedTransaction {
+        match self.transaction_type.map(|tx_type| tx_type.as_u64()) {
+            Some(0) => {
+                let req: TransactionRequest = self.into();
+                if self.v == 27 || self.v == 28 {
+                    req.into()
+                } else {
+                    req.chain_id(chain_id).into()
+                }
+            }
+            Some(1) => {
+                let req: TransactionRequest = self.into();
+                Eip2930TransactionRequest::new(
+                    req.chain_id(chain_id),
+                    self.access_list.clone().unwrap_or_default(),
+                )
+                .into()
+            }
+            _ => {
+                let req: Eip1559TransactionRequest = self.into();
+                req.chain_id(chain_id).into()
+            }
+        }
+    }
+
     /// Return the SignData associated with this Transaction.
     pub fn sign_data(&self, chain_id: u64) -> Result<SignData, Error> {
         let sig_r_le = self.r.to_le_bytes();
@@ -234,12 +284,8 @@ impl Transaction {
             Error::Signature(libsecp256k1::Error::InvalidSignature),
         )?;
         // msg = rlp([chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gas, to, value, data,
-        // accessList])
-        let req: Eip1559TransactionRequest = self.into();
-        let req = req.chain_id(chain_id);
-        // insert 0x2 at the begin of rlp
-        let req: TypedTransaction = req.into();
-        let msg = req.rlp();
+        // accessList]) for EIP-1559 txs, prefixed by the tx type
+        let msg = self.typed_request(chain_id).rlp();
         let msg_hash: [u8; 32] = Keccak256::digest(&msg)
             .as_slice()
             .to_vec()
@@ -296,7 +342,10 @@ impl Transaction {
             value: self.value,
             input: self.call_data.clone(),
             gas_price: Some(self.gas_price),
+            max_priority_fee_per_gas: Some(self.gas_tip_cap),
+            max_fee_per_gas: Some(self.gas_fee_cap),
             access_list: self.access_list.clone(),
+            transaction_type: self.transaction_type,
             nonce: self.nonce.to_word(),
             gas: self.gas_limit.to_word(),
             transaction_index: Some(transaction_index),
@@ -324,6 +373,8 @@ pub struct GethData {
     pub geth_traces: Vec<GethExecTrace>,
     /// Accounts
     pub accounts: Vec<Account>,
+    /// Hardfork rules the block was executed with
+    pub chain_spec: ChainSpec,
 }
 
 impl GethData {
@@ -333,11 +384,80 @@ impl GethData {
             let wallet = wallets.get(&tx.from).unwrap();
             assert_eq!(Word::from(wallet.chain_id()), self.chain_id);
             let geth_tx: Transaction = (&*tx).into();
-            let req: Eip1559TransactionRequest = (&geth_tx).into();
-            let sig = wallet.sign_transaction_sync(&req.chain_id(self.chain_id.as_u64()).into());
+            let sig = wallet.sign_transaction_sync(&geth_tx.typed_request(self.chain_id.as_u64()));
             tx.v = U64::from(sig.v);
             tx.r = sig.r;
             tx.s = sig.s;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sign_types::{pk_bytes_le, pk_bytes_swap_endianness},
+        H256,
+    };
+    use ethers_core::types::transaction::eip2930::AccessListItem;
+    use std::str::FromStr;
+
+    const CHAIN_ID: u64 = 1337;
+
+    /// Sign `tx` with a test key and check that its sign data recovers the
+    /// signer.
+    fn check_sign_data(mut tx: Transaction) {
+        let wallet = LocalWallet::from_str(
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap()
+        .with_chain_id(CHAIN_ID);
+        tx.from = wallet.address();
+        let sig = wallet.sign_transaction_sync(&tx.typed_request(CHAIN_ID));
+        tx.v = sig.v;
+        tx.r = sig.r;
+        tx.s = sig.s;
+
+        let sign_data = tx.sign_data(CHAIN_ID).unwrap();
+        let pk_be = pk_bytes_swap_endianness(&pk_bytes_le(&sign_data.pk));
+        let signer = Address::from_slice(&Keccak256::digest(pk_be)[12..]);
+        assert_eq!(signer, wallet.address());
+    }
+
+    fn tx(transaction_type: u64) -> Transaction {
+        Transaction {
+            to: Some(Address::repeat_byte(0xbb)),
+            nonce: 3.into(),
+            gas_limit: 100_000.into(),
+            value: 1000.into(),
+            gas_price: 10.into(),
+            gas_fee_cap: 20.into(),
+            gas_tip_cap: 2.into(),
+            call_data: vec![1, 2, 3].into(),
+            access_list: Some(AccessList(vec![AccessListItem {
+                address: Address::repeat_byte(0xcc),
+                storage_keys: vec![H256::from_low_u64_be(1)],
+            }])),
+            transaction_type: Some(transaction_type.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sign_data_legacy_tx() {
+        check_sign_data(Transaction {
+            access_list: None,
+            ..tx(0)
+        });
+    }
+
+    #[test]
+    fn sign_data_eip2930_tx() {
+        check_sign_data(tx(1));
+    }
+
+    #[test]
+    fn sign_data_eip1559_tx() {
+        check_sign_data(tx(2));
+    }
+}
@@ -454,6 +454,57 @@ pub struct GethExecTrace {
     pub struct_logs: Vec<GethExecStep>,
 }
 
+/// Account state reported by the geth `prestateTracer`.  Fields that the
+/// tracer omits (for example the code of an EOA, or the unchanged fields of
+/// an account in diff mode) are `None`.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct GethPrestateAccount {
+    /// Balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Nonce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Storage slots touched by the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<HashMap<H256, H256>>,
+}
+
+/// The result of the geth `prestateTracer` in its default mode: the state,
+/// before the transaction is executed, of every account touched by it.
+pub type GethPrestateTrace = HashMap<Address, GethPrestateAccount>;
+
+/// The result of the geth `prestateTracer` with `diffMode` enabled: the
+/// state before and after the transaction of every account modified by it.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct GethPrestateDiffTrace {
+    /// State of the modified accounts before the transaction.
+    pub pre: GethPrestateTrace,
+    /// State of the modified accounts after the transaction.  Only the fields
+    /// that changed are present.
+    #[serde(default)]
+    pub post: GethPrestateTrace,
+}
+
+/// Helper type built to deal with the `result` field wrapping each
+/// transaction trace returned by `debug_traceBlockByNumber` when it's called
+/// with a custom tracer.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[doc(hidden)]
+pub struct ResultGethTracerTraces<T>(pub Vec<ResultGethTracerTrace<T>>);
+
+/// Helper type built to deal with the `result` field wrapping each
+/// transaction trace returned by `debug_traceBlockByNumber` when it's called
+/// with a custom tracer.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[doc(hidden)]
+pub struct ResultGethTracerTrace<T> {
+    pub result: T,
+}
+
 #[macro_export]
 /// Create an [`Address`] from a hex string.  Panics on invalid input.
 macro_rules! address {
@@ -626,6 +677,60 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn deserialize_geth_prestate_traces() {
+        let trace_json = r#"
+[
+  {
+    "result": {
+      "0x35a9f94af726f07b5162df7e828cc9dc8439e7d0": {
+        "balance": "0x7a48429e177130a",
+        "nonce": 1146
+      },
+      "0xc8ba32cab1757528daf49033e3673fae77dcf05d": {
+        "balance": "0x0",
+        "nonce": 1,
+        "code": "0x6080",
+        "storage": {
+          "0x0000000000000000000000000000000000000000000000000000000000000001": "0x000000000000000000000000000000000000000000000000000000000000002a"
+        }
+      }
+    }
+  }
+]
+        "#;
+        let traces: ResultGethTracerTraces<GethPrestateTrace> =
+            serde_json::from_str(trace_json).expect("json-deserialize prestate traces");
+        let trace = &traces.0[0].result;
+        let eoa = &trace[&address!("0x35a9f94af726f07b5162df7e828cc9dc8439e7d0")];
+        assert_eq!(eoa.balance, Some(word!("7a48429e177130a")));
+        assert_eq!(eoa.nonce, Some(1146));
+        assert_eq!(eoa.code, None);
+        assert_eq!(eoa.storage, None);
+        let contract = &trace[&address!("0xc8ba32cab1757528daf49033e3673fae77dcf05d")];
+        assert_eq!(contract.code, Some(Bytes::from(vec![0x60, 0x80])));
+        assert_eq!(
+            contract.storage.as_ref().unwrap()[&H256::from_low_u64_be(1)],
+            H256::from_low_u64_be(0x2a)
+        );
+
+        let diff_json = r#"
+{
+  "pre": {
+    "0x35a9f94af726f07b5162df7e828cc9dc8439e7d0": { "balance": "0x2", "nonce": 1 }
+  },
+  "post": {
+    "0x35a9f94af726f07b5162df7e828cc9dc8439e7d0": { "balance": "0x1", "nonce": 2 }
+  }
+}
+        "#;
+        let diff: GethPrestateDiffTrace =
+            serde_json::from_str(diff_json).expect("json-deserialize prestate diff");
+        let addr = address!("0x35a9f94af726f07b5162df7e828cc9dc8439e7d0");
+        assert_eq!(diff.pre[&addr].nonce, Some(1));
+        assert_eq!(diff.post[&addr].balance, Some(Word::one()));
+    }
 }
 
 #[cfg(test)]
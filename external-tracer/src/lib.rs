@@ -1,6 +1,7 @@
 //! This module generates traces by connecting to an external tracer
 
 use eth_types::{
+    evm_types::ChainSpec,
     geth_types::{Account, BlockConstants, Transaction},
     Address, Error, GethExecTrace, Word,
 };
@@ -25,6 +26,24 @@ pub struct TraceConfig {
     pub logger_config: LoggerConfig,
     /// taiko
     pub taiko: bool,
+    /// hardfork rules the transactions are executed with
+    pub chain_spec: ChainSpec,
+    /// blocks executed, without being traced, between the state of
+    /// `accounts` and the traced block
+    pub prev_blocks: Vec<PrevBlock>,
+    /// also return the traces of the transactions of `prev_blocks`, before
+    /// the ones of the traced block
+    pub trace_prev_blocks: bool,
+}
+
+/// Block executed before the traced one, so that the traced block starts from
+/// the state it leaves.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct PrevBlock {
+    /// block constants
+    pub block_constants: BlockConstants,
+    /// transactions
+    pub transactions: Vec<Transaction>,
 }
 
 /// Configuration structure for `logger.Config`
@@ -73,3 +92,22 @@ pub fn trace(config: &TraceConfig) -> Result<Vec<GethExecTrace>, Error> {
     let trace = serde_json::from_str(&trace_string).map_err(Error::SerdeError)?;
     Ok(trace)
 }
+
+/// Creates the traces of the transactions of `config.prev_blocks` and of the
+/// traced block in a single run of the tracer, returning them grouped by
+/// block.
+pub fn trace_blocks(config: &TraceConfig) -> Result<Vec<Vec<GethExecTrace>>, Error> {
+    let mut traces = trace(&TraceConfig {
+        trace_prev_blocks: true,
+        ..config.clone()
+    })?
+    .into_iter();
+
+    Ok(config
+        .prev_blocks
+        .iter()
+        .map(|block| block.transactions.len())
+        .chain(std::iter::once(config.transactions.len()))
+        .map(|num_txs| traces.by_ref().take(num_txs).collect())
+        .collect())
+}
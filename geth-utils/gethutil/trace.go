@@ -114,6 +114,13 @@ type Transaction struct {
 	} `json:"access_list"`
 }
 
+// PrevBlock is a block executed without tracing before the traced one, so
+// that the traced block starts from the state left by it.
+type PrevBlock struct {
+	Block        Block         `json:"block_constants"`
+	Transactions []Transaction `json:"transactions"`
+}
+
 type TraceConfig struct {
 	ChainID *hexutil.Big `json:"chain_id"`
 	// HistoryHashes contains most recent 256 block hashes in history,
@@ -124,11 +131,43 @@ type TraceConfig struct {
 	Transactions  []Transaction              `json:"transactions"`
 	LoggerConfig  *logger.Config             `json:"logger_config"`
 	Taiko         bool                       `json:"taiko"`
+	ChainSpec     ChainSpec                  `json:"chain_spec"`
+	// PrevBlocks are the blocks between the accounts state and the traced
+	// block, in order.  Their hashes are the last ones of HistoryHashes.
+	PrevBlocks []PrevBlock `json:"prev_blocks"`
+	// TracePrevBlocks returns the traces of the transactions of PrevBlocks
+	// too, before the ones of the traced block.
+	TracePrevBlocks bool `json:"trace_prev_blocks"`
+}
+
+// ChainSpec holds the hardfork whose rules the transactions are executed with,
+// one of "Berlin", "London" or "Shanghai".
+type ChainSpec struct {
+	Hardfork string `json:"hardfork"`
 }
 
 func newUint64(val uint64) *uint64 { return &val }
 
+// hardforkActivation returns the activation block of London and the activation
+// time of Shanghai for hardfork, nil for the ones coming after it.
+func hardforkActivation(hardfork string) (*big.Int, *uint64, error) {
+	switch hardfork {
+	case "Berlin":
+		return nil, nil, nil
+	case "London":
+		return big.NewInt(0), nil, nil
+	case "Shanghai", "":
+		return big.NewInt(0), newUint64(0), nil
+	default:
+		return nil, nil, fmt.Errorf("unsupported hardfork %q", hardfork)
+	}
+}
+
 func Trace(config TraceConfig) ([]*ExecutionResult, error) {
+	londonBlock, shanghaiTime, err := hardforkActivation(config.ChainSpec.Hardfork)
+	if err != nil {
+		return nil, err
+	}
 	chainConfig := params.ChainConfig{
 		ChainID:                       toBigInt(config.ChainID),
 		HomesteadBlock:                big.NewInt(0),
@@ -143,20 +182,56 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		IstanbulBlock:                 big.NewInt(0),
 		MuirGlacierBlock:              big.NewInt(0),
 		BerlinBlock:                   big.NewInt(0),
-		LondonBlock:                   big.NewInt(0),
-		ShanghaiTime:                  newUint64(0),
+		LondonBlock:                   londonBlock,
+		ShanghaiTime:                  shanghaiTime,
 		MergeNetsplitBlock:            nil,
 		TerminalTotalDifficulty:       common.Big0,
 		TerminalTotalDifficultyPassed: true,
 		Taiko:                         config.Taiko,
 	}
 
+	// Setup state db with accounts from argument
+	stateDB, _ := state.New(common.Hash{}, state.NewDatabase(rawdb.NewMemoryDatabase()), nil)
+	for address, account := range config.Accounts {
+		stateDB.SetNonce(address, uint64(account.Nonce))
+		stateDB.SetCode(address, account.Code)
+		if account.Balance != nil {
+			stateDB.SetBalance(address, toBigInt(account.Balance))
+		}
+		for key, value := range account.Storage {
+			stateDB.SetState(address, key, value)
+		}
+	}
+	stateDB.Finalise(true)
+
+	// Replay the previous blocks to reach the state of the traced block.
+	var executionResults []*ExecutionResult
+	for i, block := range config.PrevBlocks {
+		results, err := applyBlock(&config, &chainConfig, stateDB, &block.Block, block.Transactions)
+		if err != nil {
+			return nil, fmt.Errorf("Failed to apply config.PrevBlocks[%d]: %w", i, err)
+		}
+		if config.TracePrevBlocks {
+			executionResults = append(executionResults, results...)
+		}
+	}
+
+	results, err := applyBlock(&config, &chainConfig, stateDB, &config.Block, config.Transactions)
+	if err != nil {
+		return nil, err
+	}
+	return append(executionResults, results...), nil
+}
+
+// applyBlock executes the transactions of a block on top of stateDB and
+// returns their traces.
+func applyBlock(config *TraceConfig, chainConfig *params.ChainConfig, stateDB *state.StateDB, block *Block, transactions []Transaction) ([]*ExecutionResult, error) {
 	var txsGasLimit uint64
-	blockGasLimit := toBigInt(config.Block.GasLimit).Uint64()
-	messages := make([]core.Message, len(config.Transactions))
-	for i, tx := range config.Transactions {
+	blockGasLimit := toBigInt(block.GasLimit).Uint64()
+	messages := make([]core.Message, len(transactions))
+	for i, tx := range transactions {
 		// only support EIP-1559 txs
-		gasPrice := math.BigMin(new(big.Int).Add(toBigInt(tx.GasTipCap), toBigInt(config.Block.BaseFee)), toBigInt(tx.GasFeeCap))
+		gasPrice := math.BigMin(new(big.Int).Add(toBigInt(tx.GasTipCap), toBigInt(block.BaseFee)), toBigInt(tx.GasFeeCap))
 
 		txAccessList := make(types.AccessList, len(tx.AccessList))
 		for i, accessList := range tx.AccessList {
@@ -190,44 +265,32 @@ func Trace(config TraceConfig) ([]*ExecutionResult, error) {
 		CanTransfer: core.CanTransfer,
 		Transfer:    core.Transfer,
 		GetHash: func(n uint64) common.Hash {
-			number := config.Block.Number.ToInt().Uint64()
-			if number > n && number-n <= 256 {
-				index := uint64(len(config.HistoryHashes)) - number + n
+			number := block.Number.ToInt().Uint64()
+			// HistoryHashes ends with the parent of the traced block
+			head := config.Block.Number.ToInt().Uint64()
+			if number > n && number-n <= 256 && head-n <= uint64(len(config.HistoryHashes)) {
+				index := uint64(len(config.HistoryHashes)) - head + n
 				return common.BigToHash(toBigInt(config.HistoryHashes[index]))
 			}
 			return common.Hash{}
 		},
-		Coinbase:    config.Block.Coinbase,
-		BlockNumber: toBigInt(config.Block.Number),
-		Time:        toBigInt(config.Block.Timestamp).Uint64(),
-		BaseFee:     toBigInt(config.Block.BaseFee),
+		Coinbase:    block.Coinbase,
+		BlockNumber: toBigInt(block.Number),
+		Time:        toBigInt(block.Timestamp).Uint64(),
+		BaseFee:     toBigInt(block.BaseFee),
 		GasLimit:    blockGasLimit,
-		Random:      &config.Block.MixHash,
-	}
-
-	// Setup state db with accounts from argument
-	stateDB, _ := state.New(common.Hash{}, state.NewDatabase(rawdb.NewMemoryDatabase()), nil)
-	for address, account := range config.Accounts {
-		stateDB.SetNonce(address, uint64(account.Nonce))
-		stateDB.SetCode(address, account.Code)
-		if account.Balance != nil {
-			stateDB.SetBalance(address, toBigInt(account.Balance))
-		}
-		for key, value := range account.Storage {
-			stateDB.SetState(address, key, value)
-		}
+		Random:      &block.MixHash,
 	}
-	stateDB.Finalise(true)
 
 	// Run the transactions with tracing enabled.
-	executionResults := make([]*ExecutionResult, len(config.Transactions))
+	executionResults := make([]*ExecutionResult, len(transactions))
 	for i, message := range messages {
 		tracer := logger.NewStructLogger(config.LoggerConfig)
-		evm := vm.NewEVM(blockCtx, core.NewEVMTxContext(&message), stateDB, &chainConfig, vm.Config{Tracer: tracer, NoBaseFee: true})
+		evm := vm.NewEVM(blockCtx, core.NewEVMTxContext(&message), stateDB, chainConfig, vm.Config{Tracer: tracer, NoBaseFee: true})
 
 		result, err := core.ApplyMessage(evm, &message, new(core.GasPool).AddGas(message.GasLimit))
 		if err != nil {
-			return nil, fmt.Errorf("Failed to apply config.Transactions[%d]: %w", i, err)
+			return nil, fmt.Errorf("Failed to apply transactions[%d]: %w", i, err)
 		}
 		stateDB.Finalise(true)
 
@@ -0,0 +1,77 @@
+//! Print the rows used by each sub-circuit of the SuperCircuit to prove a
+//! block of the geth node at `GETH0_URL`, the keccak rows used by each source
+//! of keccak inputs, and whether the block fits in degree `k`.
+//!
+//! Usage: `capacity <block_num> [k]`
+
+use bus_mapping::circuit_input_builder::{BuilderClient, CircuitsParams};
+use halo2_proofs::halo2curves::bn256::Fr;
+use integration_tests::{get_client, log_init};
+use std::{env, process};
+use zkevm_circuits::{
+    keccak_circuit::KeccakCapacityReport, super_circuit::capacity::CircuitCapacity,
+};
+
+// Generous enough to build the circuit input of any block that we may want to
+// prove; the actual params are derived from the built witness.
+const BUILDER_PARAMS: CircuitsParams = CircuitsParams {
+    max_rws: 1 << 22,
+    max_txs: 1 << 10,
+    max_calldata: 1 << 20,
+    max_copy_rows: 1 << 22,
+    max_exp_steps: 1 << 16,
+    max_bytecode: 1 << 20,
+    max_evm_rows: 0,
+    max_keccak_rows: 0,
+    max_receipt_items: 0,
+};
+
+#[tokio::main]
+async fn main() {
+    log_init();
+
+    let args: Vec<String> = env::args().collect();
+    let block_num: u64 = match args.get(1).map(|arg| arg.parse()) {
+        Some(Ok(block_num)) => block_num,
+        _ => {
+            eprintln!("usage: {} <block_num> [k]", args[0]);
+            process::exit(2);
+        }
+    };
+    let k: Option<u32> = args.get(2).map(|arg| arg.parse().expect("invalid k"));
+
+    let cli = BuilderClient::new(get_client(), BUILDER_PARAMS, Default::default())
+        .await
+        .expect("cannot create builder client");
+    let (builder, _) = cli
+        .gen_inputs(block_num)
+        .await
+        .expect("cannot generate circuit input");
+    let capacity =
+        CircuitCapacity::from_builder::<Fr>(&builder).expect("cannot convert witness block");
+
+    println!("block {} with {} txs", block_num, builder.block.txs().len());
+    println!("{:<10} {:>12} {:>12}", "circuit", "rows", "padded");
+    for rows in &capacity.rows {
+        println!(
+            "{:<10} {:>12} {:>12}",
+            rows.kind.to_string(),
+            rows.rows,
+            rows.rows_padded
+        );
+    }
+    println!("unusable rows: {}", capacity.unusable_rows);
+    let keccak_report =
+        KeccakCapacityReport::from_builder::<Fr>(&builder).expect("cannot collect keccak inputs");
+    println!("{}", keccak_report);
+    println!("minimum k: {}", capacity.min_k());
+    println!("tightest params: {:#?}", capacity.params);
+
+    if let Some(k) = k {
+        if let Err(err) = capacity.params_for_k(k) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        println!("block fits in k = {}", k);
+    }
+}
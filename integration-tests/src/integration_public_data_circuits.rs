@@ -437,6 +437,7 @@ mod public_data_test {
             max_exp_steps: 27900,
             max_evm_rows: 80000,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         };
         let protocol_instance = gen_requests()[0].clone();
         let block = gen_block(circuits_params, protocol_instance).await;
@@ -455,6 +456,7 @@ mod public_data_test {
             max_exp_steps: 27900,
             max_evm_rows: 80000,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         };
 
         let protocol_instance: ProtocolInstance = ProtocolInstance::default();
@@ -480,6 +482,7 @@ mod public_data_test {
             max_exp_steps: 27900,
             max_evm_rows: 80000,
             max_keccak_rows: 20000,
+            max_receipt_items: 0,
         };
 
         let requests = gen_requests();
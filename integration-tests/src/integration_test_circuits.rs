@@ -69,6 +69,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_evm_rows: MAX_EVM_ROWS,
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
+    max_receipt_items: 0,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 20;
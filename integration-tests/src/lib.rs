@@ -162,6 +162,7 @@ pub async fn build_circuit_input_builder_block(block_num: u64) {
             max_evm_rows: 0,
             max_exp_steps: 1000,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         },
         Default::default(),
     )
@@ -199,6 +200,57 @@ pub async fn build_circuit_input_builder_block(block_num: u64) {
     trace!("CircuitInputBuilder: {:#?}", builder);
 }
 
+/// Build circuit input builder for a block, acquiring the state with the
+/// `prestateTracer`, and check that the struct logs replay didn't miss any
+/// state access reported by the tracer.
+pub async fn build_circuit_input_builder_block_with_prestate(block_num: u64) {
+    let cli = get_client();
+    let cli = BuilderClient::new(
+        cli,
+        CircuitsParams {
+            max_rws: 800000,
+            max_txs: 10,
+            max_calldata: 4000,
+            max_bytecode: 4000,
+            max_copy_rows: 800000,
+            max_evm_rows: 0,
+            max_exp_steps: 1000,
+            max_keccak_rows: 0,
+            max_receipt_items: 0,
+        },
+        Default::default(),
+    )
+    .await
+    .unwrap();
+
+    let (eth_block, geth_trace, history_hashes, prev_state_root) =
+        cli.get_block(block_num).await.unwrap();
+    let access_set = get_state_accesses(&eth_block, &geth_trace, &None).unwrap();
+
+    let (state_db, code_db, mismatch) = cli
+        .get_state_from_prestate(block_num, access_set)
+        .await
+        .unwrap();
+    assert!(
+        mismatch.missing_in_trace.is_empty(),
+        "state accesses missing in the struct logs: {:?}",
+        mismatch.missing_in_trace
+    );
+
+    let builder = cli
+        .gen_inputs_from_state(
+            state_db,
+            code_db,
+            &eth_block,
+            &geth_trace,
+            history_hashes,
+            prev_state_root,
+        )
+        .unwrap();
+
+    trace!("CircuitInputBuilder: {:#?}", builder);
+}
+
 /// Block explorer URL is https://explorer.internal.taiko.xyz
 /// The block that has only one anchor
 pub const TAIKO_BLOCK_ANCHOR_ONLY: u64 = 5368;
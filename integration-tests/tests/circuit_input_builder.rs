@@ -1,7 +1,11 @@
 #![cfg(feature = "circuit_input_builder")]
 
-use integration_tests::{build_circuit_input_builder_block, log_init, GenDataOutput};
+use integration_tests::{
+    build_circuit_input_builder_block, build_circuit_input_builder_block_with_prestate, log_init,
+    GenDataOutput,
+};
 use lazy_static::lazy_static;
+use paste::paste;
 
 lazy_static! {
     pub static ref GEN_DATA: GenDataOutput = GenDataOutput::load();
@@ -9,11 +13,20 @@ lazy_static! {
 
 macro_rules! declare_tests {
     ($test_name:ident, $block_tag:expr) => {
-        #[tokio::test]
-        async fn $test_name() {
-            log_init();
-            let block_num = GEN_DATA.blocks.get($block_tag).unwrap();
-            build_circuit_input_builder_block(*block_num).await;
+        paste! {
+            #[tokio::test]
+            async fn $test_name() {
+                log_init();
+                let block_num = GEN_DATA.blocks.get($block_tag).unwrap();
+                build_circuit_input_builder_block(*block_num).await;
+            }
+
+            #[tokio::test]
+            async fn [<$test_name _prestate>]() {
+                log_init();
+                let block_num = GEN_DATA.blocks.get($block_tag).unwrap();
+                build_circuit_input_builder_block_with_prestate(*block_num).await;
+            }
         }
     };
 }
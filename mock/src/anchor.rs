@@ -80,12 +80,16 @@ fn fixd_k_sign(anchor_tx: &Transaction, chain_id: u64) -> Result<SignData, eth_t
 
 /// The signature of anchor transaction
 pub fn sign(tx: &mut MockTransaction) {
-    let chain_id = (*MOCK_CHAIN_ID).as_u64();
+    sign_with_chain_id(tx, (*MOCK_CHAIN_ID).as_u64())
+}
+
+/// The signature of anchor transaction on the chain `chain_id`
+pub fn sign_with_chain_id(tx: &mut MockTransaction, chain_id: u64) {
     let _tx: Transaction = tx.to_owned().into();
     let sig_data = fixd_k_sign(&_tx, chain_id).unwrap();
     let sig_r = U256::from_little_endian(sig_data.signature.0.to_bytes().as_slice());
     let sig_s = U256::from_little_endian(sig_data.signature.1.to_bytes().as_slice());
-    tx.sig_data((2712, sig_r, sig_s));
+    tx.sig_data((chain_id * 2 + 36, sig_r, sig_s));
 }
 
 /// gen anchor call
@@ -9,13 +9,14 @@ use rand_chacha::ChaCha20Rng;
 mod account;
 mod anchor;
 mod block;
+pub mod program;
 pub mod test_ctx;
 mod transaction;
 
 pub use account::MockAccount;
 pub use anchor::ANCHOR_METHOD_SIGNATURE;
 pub(crate) use block::MockBlock;
-pub use test_ctx::TestContext;
+pub use test_ctx::{TestContext, TestContextBuilder};
 pub use transaction::{AddrOrWallet, MockTransaction, CORRECT_MOCK_TXS};
 
 lazy_static! {
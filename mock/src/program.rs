@@ -0,0 +1,468 @@
+//! Generator of random EVM programs and call trees, to fuzz the circuits with
+//! [proptest](https://docs.rs/proptest).
+//!
+//! A [`CallTree`] is a set of contracts where each contract runs a
+//! [`Program`] of random operations, and may call the contracts that follow
+//! it in the tree with any of the call opcodes.  Programs are valid enough to
+//! execute, but still hit the unusual paths: calls with too little gas, reverts
+//! and out of gas at random points, large memory copies and state changes in
+//! static calls.
+
+use crate::{eth, TestContextBuilder, MOCK_ACCOUNTS};
+use eth_types::{
+    bytecode::Bytecode, evm_types::OpcodeId, geth_types::GethData, Address, Error, ToWord, Word,
+};
+use proptest::{
+    collection::vec,
+    prelude::*,
+    sample::select,
+    strategy::{BoxedStrategy, Union},
+};
+
+/// Limits of the generated call trees.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramConfig {
+    /// Maximum number of contracts in a call tree
+    pub max_contracts: usize,
+    /// Maximum number of operations of a program, besides its terminator
+    pub max_ops: usize,
+    /// Memory offsets and sizes used by the operations are below this
+    pub max_memory: u64,
+}
+
+impl Default for ProgramConfig {
+    fn default() -> Self {
+        Self {
+            max_contracts: 4,
+            max_ops: 12,
+            max_memory: 0x200,
+        }
+    }
+}
+
+/// Operation of a generated program.  Operations leave the stack as they find
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramOp {
+    /// Binary arithmetic, comparison or bitwise operation on constants
+    Arith {
+        /// Opcode
+        op: OpcodeId,
+        /// First operand, at the top of the stack
+        a: Word,
+        /// Second operand
+        b: Word,
+    },
+    /// MSTORE of a constant
+    MStore {
+        /// Memory offset
+        offset: u64,
+        /// Stored value
+        value: Word,
+    },
+    /// MLOAD
+    MLoad {
+        /// Memory offset
+        offset: u64,
+    },
+    /// CALLDATACOPY or CODECOPY
+    Copy {
+        /// Opcode
+        op: OpcodeId,
+        /// Destination memory offset
+        dest_offset: u64,
+        /// Source offset
+        offset: u64,
+        /// Bytes copied
+        size: u64,
+    },
+    /// RETURNDATACOPY of all the return data of the last call
+    ReturnDataCopy {
+        /// Destination memory offset
+        dest_offset: u64,
+    },
+    /// SHA3 of a memory range
+    Sha3 {
+        /// Memory offset
+        offset: u64,
+        /// Bytes hashed
+        size: u64,
+    },
+    /// SSTORE of a constant
+    SStore {
+        /// Storage key
+        key: u8,
+        /// Stored value
+        value: Word,
+    },
+    /// SLOAD
+    SLoad {
+        /// Storage key
+        key: u8,
+    },
+    /// LOG of a memory range
+    Log {
+        /// Number of topics, up to 4
+        topics: u8,
+        /// Memory offset
+        offset: u64,
+        /// Bytes logged
+        size: u64,
+    },
+    /// Call to another contract of the tree
+    Call {
+        /// CALL, CALLCODE, DELEGATECALL or STATICCALL
+        op: OpcodeId,
+        /// Index of the callee in the tree
+        callee: usize,
+        /// Gas passed to the callee, or all the available gas when `None`
+        gas: Option<u64>,
+        /// Value transferred by CALL and CALLCODE
+        value: u64,
+        /// Memory offset of the call data
+        args_offset: u64,
+        /// Size of the call data
+        args_size: u64,
+        /// Memory offset of the return data
+        ret_offset: u64,
+        /// Size of the return data
+        ret_size: u64,
+    },
+}
+
+/// Last operation of a generated program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminator {
+    /// STOP
+    Stop,
+    /// RETURN of a memory range
+    Return {
+        /// Memory offset
+        offset: u64,
+        /// Bytes returned
+        size: u64,
+    },
+    /// REVERT of a memory range
+    Revert {
+        /// Memory offset
+        offset: u64,
+        /// Bytes returned
+        size: u64,
+    },
+    /// Loop until running out of gas
+    OutOfGas,
+    /// INVALID opcode
+    Invalid,
+}
+
+/// Program run by a contract of a [`CallTree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    /// Operations
+    pub ops: Vec<ProgramOp>,
+    /// Last operation
+    pub terminator: Terminator,
+}
+
+impl Program {
+    /// Bytecode of the program, calling the contracts at `addresses`.
+    pub fn bytecode(&self, addresses: &[Address]) -> Bytecode {
+        let mut code = Bytecode::default();
+        for op in &self.ops {
+            op.append_to(&mut code, addresses);
+        }
+        match self.terminator {
+            Terminator::Stop => {
+                code.op_stop();
+            }
+            Terminator::Return { offset, size } => {
+                code.op_return(offset, size);
+            }
+            Terminator::Revert { offset, size } => {
+                code.op_revert(offset, size);
+            }
+            Terminator::OutOfGas => {
+                let dest = code.op_jumpdest();
+                code.op_jump(dest);
+            }
+            Terminator::Invalid => {
+                code.write(0xfe, true);
+            }
+        }
+        code
+    }
+}
+
+impl ProgramOp {
+    fn append_to(&self, code: &mut Bytecode, addresses: &[Address]) {
+        match *self {
+            Self::Arith { op, a, b } => {
+                code.op_push32(b).op_push32(a).write_op(op).op_pop();
+            }
+            Self::MStore { offset, value } => {
+                code.op_mstore(offset, value);
+            }
+            Self::MLoad { offset } => {
+                code.op_mload(offset).op_pop();
+            }
+            Self::Copy {
+                op,
+                dest_offset,
+                offset,
+                size,
+            } => {
+                code.op_push32(size)
+                    .op_push32(offset)
+                    .op_push32(dest_offset)
+                    .write_op(op);
+            }
+            Self::ReturnDataCopy { dest_offset } => {
+                code.op_returndatasize()
+                    .op_push32(0)
+                    .op_push32(dest_offset)
+                    .write_op(OpcodeId::RETURNDATACOPY);
+            }
+            Self::Sha3 { offset, size } => {
+                code.op_sha3(offset, size).op_pop();
+            }
+            Self::SStore { key, value } => {
+                code.op_sstore(key as u64, value);
+            }
+            Self::SLoad { key } => {
+                code.op_sload(key as u64).op_pop();
+            }
+            Self::Log {
+                topics,
+                offset,
+                size,
+            } => {
+                for topic in 0..topics {
+                    code.op_push32(topic as u64);
+                }
+                code.op_push32(size)
+                    .op_push32(offset)
+                    .write_op(OpcodeId::from(OpcodeId::LOG0.as_u8() + topics));
+            }
+            Self::Call {
+                op,
+                callee,
+                gas,
+                value,
+                args_offset,
+                args_size,
+                ret_offset,
+                ret_size,
+            } => {
+                code.op_push32(ret_size)
+                    .op_push32(ret_offset)
+                    .op_push32(args_size)
+                    .op_push32(args_offset);
+                if matches!(op, OpcodeId::CALL | OpcodeId::CALLCODE) {
+                    code.op_push32(value);
+                }
+                code.op_push32(addresses[callee].to_word());
+                match gas {
+                    Some(gas) => code.op_push32(gas),
+                    None => code.op_gas(),
+                };
+                code.write_op(op).op_pop();
+            }
+        }
+    }
+}
+
+/// Contracts calling each other, run by a single transaction to the first
+/// one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallTree {
+    /// Programs of the contracts, where a contract only calls the ones after it
+    pub contracts: Vec<Program>,
+    /// Call data of the transaction
+    pub calldata: Vec<u8>,
+    /// Gas limit of the transaction
+    pub gas: u64,
+}
+
+impl CallTree {
+    /// Address of the contract at `index` in the tree.
+    pub fn address(index: usize) -> Address {
+        Address::from_low_u64_be(0xc0de_0000 + index as u64)
+    }
+
+    /// Build the block with the transaction running the tree, and trace it.
+    pub fn build(&self) -> Result<GethData, Error> {
+        let addresses: Vec<Address> = (0..self.contracts.len()).map(Self::address).collect();
+        let mut ctx = TestContextBuilder::new();
+        for (address, program) in addresses.iter().zip(&self.contracts) {
+            let code = program.bytecode(&addresses);
+            ctx.account(|acc| {
+                acc.address(*address).balance(Word::from(100u64)).code(code);
+            });
+        }
+        ctx.account(|acc| {
+            acc.address(MOCK_ACCOUNTS[0]).balance(eth(10));
+        })
+        .tx(|tx| {
+            tx.from(MOCK_ACCOUNTS[0])
+                .to(addresses[0])
+                .input(self.calldata.clone().into())
+                .gas(Word::from(self.gas));
+        });
+        Ok(ctx.build()?.remove(0))
+    }
+}
+
+fn arb_word() -> impl Strategy<Value = Word> {
+    prop_oneof![
+        (0u64..0x100).prop_map(Word::from),
+        any::<[u8; 32]>().prop_map(|bytes| Word::from_big_endian(&bytes)),
+        Just(Word::MAX),
+    ]
+}
+
+fn arb_op(config: ProgramConfig, index: usize, contracts: usize) -> BoxedStrategy<ProgramOp> {
+    let memory = 0..config.max_memory;
+    let arith_ops = vec![
+        OpcodeId::ADD,
+        OpcodeId::MUL,
+        OpcodeId::SUB,
+        OpcodeId::DIV,
+        OpcodeId::SDIV,
+        OpcodeId::MOD,
+        OpcodeId::SMOD,
+        OpcodeId::EXP,
+        OpcodeId::SIGNEXTEND,
+        OpcodeId::LT,
+        OpcodeId::GT,
+        OpcodeId::SLT,
+        OpcodeId::SGT,
+        OpcodeId::EQ,
+        OpcodeId::AND,
+        OpcodeId::OR,
+        OpcodeId::XOR,
+        OpcodeId::BYTE,
+        OpcodeId::SHL,
+        OpcodeId::SHR,
+        OpcodeId::SAR,
+    ];
+    let mut ops = vec![
+        (select(arith_ops), arb_word(), arb_word())
+            .prop_map(|(op, a, b)| ProgramOp::Arith { op, a, b })
+            .boxed(),
+        (memory.clone(), arb_word())
+            .prop_map(|(offset, value)| ProgramOp::MStore { offset, value })
+            .boxed(),
+        memory
+            .clone()
+            .prop_map(|offset| ProgramOp::MLoad { offset })
+            .boxed(),
+        (
+            select(vec![OpcodeId::CALLDATACOPY, OpcodeId::CODECOPY]),
+            memory.clone(),
+            0u64..0x100,
+            memory.clone(),
+        )
+            .prop_map(|(op, dest_offset, offset, size)| ProgramOp::Copy {
+                op,
+                dest_offset,
+                offset,
+                size,
+            })
+            .boxed(),
+        memory
+            .clone()
+            .prop_map(|dest_offset| ProgramOp::ReturnDataCopy { dest_offset })
+            .boxed(),
+        (memory.clone(), memory.clone())
+            .prop_map(|(offset, size)| ProgramOp::Sha3 { offset, size })
+            .boxed(),
+        (0u8..4, arb_word())
+            .prop_map(|(key, value)| ProgramOp::SStore { key, value })
+            .boxed(),
+        (0u8..4).prop_map(|key| ProgramOp::SLoad { key }).boxed(),
+        (0u8..=4, memory.clone(), 0u64..0x40)
+            .prop_map(|(topics, offset, size)| ProgramOp::Log {
+                topics,
+                offset,
+                size,
+            })
+            .boxed(),
+    ];
+    if index + 1 < contracts {
+        let call = (
+            select(vec![
+                OpcodeId::CALL,
+                OpcodeId::CALLCODE,
+                OpcodeId::DELEGATECALL,
+                OpcodeId::STATICCALL,
+            ]),
+            index + 1..contracts,
+            // Little gas makes the callee run out of gas early
+            prop::option::weighted(0.2, 0u64..5000),
+            0u64..3,
+            (memory.clone(), 0u64..0x40, memory, 0u64..0x40),
+        )
+            .prop_map(
+                |(op, callee, gas, value, (args_offset, args_size, ret_offset, ret_size))| {
+                    ProgramOp::Call {
+                        op,
+                        callee,
+                        gas,
+                        value,
+                        args_offset,
+                        args_size,
+                        ret_offset,
+                        ret_size,
+                    }
+                },
+            )
+            .boxed();
+        // Calls are the most interesting operations, make them frequent
+        ops.extend([call.clone(), call]);
+    }
+    Union::new(ops).boxed()
+}
+
+fn arb_terminator(config: ProgramConfig) -> impl Strategy<Value = Terminator> {
+    let memory = 0..config.max_memory;
+    prop_oneof![
+        4 => Just(Terminator::Stop),
+        2 => (memory.clone(), 0u64..0x40)
+            .prop_map(|(offset, size)| Terminator::Return { offset, size }),
+        2 => (memory, 0u64..0x40).prop_map(|(offset, size)| Terminator::Revert { offset, size }),
+        1 => Just(Terminator::OutOfGas),
+        1 => Just(Terminator::Invalid),
+    ]
+}
+
+/// Strategy generating the program of the contract at `index` in a call tree
+/// of `contracts` contracts.
+pub fn arb_program(
+    config: ProgramConfig,
+    index: usize,
+    contracts: usize,
+) -> impl Strategy<Value = Program> {
+    (
+        vec(arb_op(config, index, contracts), 0..=config.max_ops),
+        arb_terminator(config),
+    )
+        .prop_map(|(ops, terminator)| Program { ops, terminator })
+}
+
+/// Strategy generating call trees within the limits of `config`.
+pub fn arb_call_tree(config: ProgramConfig) -> impl Strategy<Value = CallTree> {
+    (1..=config.max_contracts).prop_flat_map(move |contracts| {
+        (
+            (0..contracts)
+                .map(|index| arb_program(config, index, contracts))
+                .collect::<Vec<_>>(),
+            vec(any::<u8>(), 0..0x40),
+            100_000u64..1_000_000,
+        )
+            .prop_map(|(contracts, calldata, gas)| CallTree {
+                contracts,
+                calldata,
+                gas,
+            })
+    })
+}
@@ -5,6 +5,7 @@ use crate::{
     MOCK_TAIKO_TREASURY_ADDRESS,
 };
 use eth_types::{
+    evm_types::ChainSpec,
     geth_types::{Account, BlockConstants, GethData},
     Block, Bytecode, Error, GethExecTrace, Transaction, Word,
 };
@@ -13,6 +14,9 @@ use external_tracer::{trace, TraceConfig};
 use helpers::*;
 use itertools::Itertools;
 
+mod builder;
+pub use builder::TestContextBuilder;
+
 /// TestContext is a type that contains all the information from a block
 /// required to build the circuit inputs.
 ///
@@ -103,6 +107,9 @@ impl<const NACC: usize, const NTX: usize> From<TestContext<NACC, NTX>> for GethD
             eth_block: ctx.eth_block,
             geth_traces: ctx.geth_traces.to_vec(),
             accounts: ctx.accounts,
+            // Traced with the rules of the latest hardfork, see
+            // `TestContextBuilder::chain_spec` for the other ones.
+            chain_spec: ChainSpec::default(),
         }
     }
 }
@@ -320,6 +327,7 @@ pub fn gen_geth_traces(
             .collect(),
         logger_config,
         taiko: is_taiko,
+        ..Default::default()
     };
     let traces = trace(&trace_config)?;
     Ok(traces)
@@ -0,0 +1,277 @@
+//! Runtime sized, multi-block counterpart of [`TestContext`](super::TestContext).
+
+use crate::{
+    anchor, MockAccount, MockBlock, MockTransaction, GOLDEN_TOUCH, MOCK_CODES,
+    MOCK_TAIKO_L2_ADDRESS, MOCK_TAIKO_TREASURY_ADDRESS,
+};
+use eth_types::{
+    evm_types::ChainSpec,
+    geth_types::{self, Account, BlockConstants, GethData},
+    Address, Block, Error, Hash, ToWord, Transaction, Word,
+};
+use external_tracer::{trace_blocks, LoggerConfig, PrevBlock, TraceConfig};
+use itertools::Itertools;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+
+/// Builder of a chain of consecutive blocks sharing the same initial state,
+/// with any number of accounts and transactions.
+///
+/// Each block starts from the state left by the previous one: its number
+/// follows the previous block number and its parent hash is the previous block
+/// hash.  Unless set, block hashes are derived from the block number and
+/// parent hash.  The index, block number and nonce of the transactions are set
+/// by the builder, where the nonces of the senders defined as accounts of the
+/// context are correlative across all the blocks.
+///
+/// The blocks are traced in a single run of the tracer, each one on top of the
+/// state left by the previous ones.  The resulting [`GethData`] all describe
+/// the accounts as they are before the first block, so the blocks must be
+/// handled together, e.g. with
+/// `BlockData::new_from_geth_data_batch` and
+/// `CircuitInputBuilder::handle_blocks`.
+///
+/// ## Example
+/// ```rust
+/// use eth_types::{bytecode, geth_types::GethData, Word};
+/// use mock::{eth, TestContextBuilder, MOCK_ACCOUNTS};
+///
+/// let blocks: Vec<GethData> = TestContextBuilder::new()
+///     .account(|acc| {
+///         acc.address(MOCK_ACCOUNTS[0]).code(bytecode! { STOP });
+///     })
+///     .account(|acc| {
+///         acc.address(MOCK_ACCOUNTS[1]).balance(eth(10));
+///     })
+///     .block(|block| block.number(0xcafeu64))
+///     .tx(|tx| {
+///         tx.from(MOCK_ACCOUNTS[1]).to(MOCK_ACCOUNTS[0]);
+///     })
+///     .block(|block| block)
+///     .tx(|tx| {
+///         tx.from(MOCK_ACCOUNTS[1])
+///             .to(MOCK_ACCOUNTS[0])
+///             .value(Word::from(1u64));
+///     })
+///     .build()
+///     .unwrap();
+/// assert_eq!(blocks[1].eth_block.parent_hash, blocks[0].eth_block.hash.unwrap());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TestContextBuilder {
+    accounts: Vec<MockAccount>,
+    blocks: Vec<(MockBlock, Vec<MockTransaction>)>,
+    history_hashes: Vec<Word>,
+    logger_config: LoggerConfig,
+    is_taiko: bool,
+    chain_spec: ChainSpec,
+}
+
+impl TestContextBuilder {
+    /// Create a builder without accounts nor blocks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder for the taiko environment, where the taiko accounts
+    /// are added to the context and each block starts with an anchor
+    /// transaction.
+    pub fn new_with_taiko() -> Self {
+        Self {
+            is_taiko: true,
+            ..Self::default()
+        }
+    }
+
+    /// Add an account set up by `func_acc`.
+    pub fn account<FAcc>(&mut self, func_acc: FAcc) -> &mut Self
+    where
+        FAcc: FnOnce(&mut MockAccount),
+    {
+        let mut account = MockAccount::default();
+        func_acc(&mut account);
+        self.accounts.push(account.build());
+        self
+    }
+
+    /// Start a new block set up by `func_block`.  The number and parent hash
+    /// of every block but the first one are overridden to follow the previous
+    /// block.
+    pub fn block<Fb>(&mut self, func_block: Fb) -> &mut Self
+    where
+        Fb: FnOnce(&mut MockBlock) -> &mut MockBlock,
+    {
+        let mut block = MockBlock::default();
+        func_block(&mut block);
+        self.blocks.push((block, Vec::new()));
+        self
+    }
+
+    /// Add a transaction set up by `func_tx` to the last block, starting a
+    /// default one if there's none.
+    pub fn tx<FTx>(&mut self, func_tx: FTx) -> &mut Self
+    where
+        FTx: FnOnce(&mut MockTransaction),
+    {
+        if self.blocks.is_empty() {
+            self.block(|block| block);
+        }
+        let mut tx = MockTransaction::default();
+        func_tx(&mut tx);
+        self.blocks.last_mut().expect("a block").1.push(tx);
+        self
+    }
+
+    /// Set the hashes of the blocks before the first one, where the latest
+    /// one is at history_hashes[history_hashes.len() - 1].
+    pub fn history_hashes(&mut self, history_hashes: Vec<Word>) -> &mut Self {
+        self.history_hashes = history_hashes;
+        self
+    }
+
+    /// Set the logger config of the external tracer.
+    pub fn logger_config(&mut self, logger_config: LoggerConfig) -> &mut Self {
+        self.logger_config = logger_config;
+        self
+    }
+
+    /// Set the hardfork rules the blocks are executed with, the ones of the
+    /// latest supported hardfork by default.
+    pub fn chain_spec(&mut self, chain_spec: ChainSpec) -> &mut Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Build the blocks and generate the execution traces of their
+    /// transactions, returning one [`GethData`] per block.
+    pub fn build(&self) -> Result<Vec<GethData>, Error> {
+        let accounts = self.accounts();
+        let mut nonces: HashMap<Address, u64> = accounts
+            .iter()
+            .map(|acc| (acc.address, acc.nonce.as_u64()))
+            .collect();
+        let accounts_map: HashMap<Address, Account> = accounts
+            .iter()
+            .map(|acc| (acc.address, acc.clone()))
+            .collect();
+
+        let mut history_hashes = self.history_hashes.clone();
+        let mut blocks: Vec<PrevBlock> = Vec::new();
+        let mut parent: Option<(u64, Hash)> = None;
+        let mut geth_datas = Vec::with_capacity(self.blocks.len());
+        for (block, txs) in &self.blocks {
+            let mut block = block.clone();
+            if let Some((number, hash)) = parent {
+                block.number(number + 1).parent_hash(hash);
+            }
+            let head = Block::<()>::from(block.clone());
+            let number = head.number.expect("block number").as_u64();
+            let hash = match head.hash {
+                Some(hash) if !hash.is_zero() => hash,
+                _ => mock_block_hash(number, head.parent_hash),
+            };
+            block.hash(hash);
+
+            let anchor_tx = self.is_taiko.then(MockTransaction::new_anchor);
+            let txs = anchor_tx
+                .into_iter()
+                .chain(txs.iter().cloned())
+                .enumerate()
+                .map(|(idx, mut tx)| {
+                    tx.transaction_idx(idx as u64)
+                        .block_number(number)
+                        .block_hash(hash);
+                    if let Some(nonce) = nonces.get_mut(&tx.from.address()) {
+                        tx.nonce(*nonce);
+                        *nonce += 1;
+                    }
+                    if self.is_taiko && idx == 0 {
+                        // The anchor is signed with its nonce
+                        anchor::sign(&mut tx);
+                    }
+                    tx.build()
+                })
+                .collect_vec();
+            block.transactions(txs);
+
+            let chain_id = block.chain_id;
+            let eth_block = Block::<Transaction>::from(block.build());
+            let block_constants = BlockConstants::try_from(&eth_block)?;
+            let transactions = eth_block
+                .transactions
+                .iter()
+                .map(geth_types::Transaction::from)
+                .collect_vec();
+
+            geth_datas.push(GethData {
+                chain_id,
+                history_hashes: history_hashes.clone(),
+                eth_block,
+                geth_traces: Vec::new(),
+                accounts: accounts.clone(),
+                chain_spec: self.chain_spec,
+            });
+            blocks.push(PrevBlock {
+                block_constants,
+                transactions,
+            });
+            history_hashes.push(hash.to_word());
+            if history_hashes.len() > 256 {
+                history_hashes.remove(0);
+            }
+            parent = Some((number, hash));
+        }
+
+        // All the blocks are traced in a single run of the tracer, the last one
+        // on top of the state left by the previous ones.
+        let last_block = match blocks.pop() {
+            Some(block) => block,
+            None => return Ok(geth_datas),
+        };
+        let last_geth_data = geth_datas.last().expect("geth data of the last block");
+        let traces = trace_blocks(&TraceConfig {
+            chain_id: last_geth_data.chain_id,
+            history_hashes: last_geth_data.history_hashes.clone(),
+            block_constants: last_block.block_constants,
+            accounts: accounts_map,
+            transactions: last_block.transactions,
+            logger_config: self.logger_config.clone(),
+            taiko: self.is_taiko,
+            chain_spec: self.chain_spec,
+            prev_blocks: blocks,
+            ..Default::default()
+        })?;
+        for (geth_data, geth_traces) in geth_datas.iter_mut().zip_eq(traces) {
+            geth_data.geth_traces = geth_traces;
+        }
+        Ok(geth_datas)
+    }
+
+    /// Accounts of the context, including the taiko ones first in the taiko
+    /// environment.
+    fn accounts(&self) -> Vec<Account> {
+        let mut accounts = Vec::new();
+        if self.is_taiko {
+            let mut treasury = MockAccount::default();
+            treasury.address(*MOCK_TAIKO_TREASURY_ADDRESS);
+            let mut golden_touch = MockAccount::default();
+            golden_touch.address(*GOLDEN_TOUCH);
+            let mut l2_contract = MockAccount::default();
+            l2_contract
+                .address(*MOCK_TAIKO_L2_ADDRESS)
+                .code(MOCK_CODES[0].clone());
+            accounts.extend([treasury, golden_touch, l2_contract]);
+        }
+        accounts.extend(self.accounts.iter().cloned());
+        accounts.into_iter().map(Account::from).collect()
+    }
+}
+
+/// Hash of a mock block, derived from its number and parent hash so that it's
+/// unique in a chain.
+fn mock_block_hash(number: u64, parent_hash: Hash) -> Hash {
+    let mut hasher = Keccak256::new();
+    hasher.update(number.to_be_bytes());
+    hasher.update(parent_hash.as_bytes());
+    Hash::from_slice(hasher.finalize().as_slice())
+}
@@ -207,7 +207,13 @@ impl From<MockTransaction> for GethTransaction {
 impl MockTransaction {
     /// create a mock anchor transaction
     pub fn new_anchor() -> Self {
+        Self::new_anchor_with_chain_id(MOCK_CHAIN_ID.as_u64())
+    }
+
+    /// Create a new anchor tx signed for the chain `chain_id`
+    pub fn new_anchor_with_chain_id(chain_id: u64) -> Self {
         let mut tx = MockTransaction::default();
+        tx.chain_id(Word::from(chain_id));
         tx.from(*GOLDEN_TOUCH);
         tx.to(*MOCK_TAIKO_L2_ADDRESS);
         tx.gas(*MOCK_ANCHOR_GAS_LIMIT)
@@ -219,7 +225,7 @@ impl MockTransaction {
             .input(crate::anchor::anchor_call())
             .nonce(0)
             .value(*MOCK_ANCHOR_VALUE);
-        crate::anchor::sign(&mut tx);
+        crate::anchor::sign_with_chain_id(&mut tx, chain_id);
         tx
     }
 }
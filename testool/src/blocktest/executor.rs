@@ -0,0 +1,228 @@
+use super::{BlockHeader, BlockchainTest};
+use crate::{
+    config::TestSuite,
+    statetest::{check_post, CircuitsConfig, StateTestError},
+};
+use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
+use eth_types::{
+    evm_types::ChainSpec,
+    geth_types::{BlockConstants, GethData},
+    ToWord, U256, U64,
+};
+use external_tracer::{PrevBlock, TraceConfig};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use zkevm_circuits::{
+    evm_circuit::witness::block_convert,
+    super_circuit::SuperCircuit,
+    test_util::CircuitTestBuilder,
+    witness::{logs_bloom, receipts_convert, receipts_root, Block, Receipt},
+};
+
+/// Trace each block of the chain on top of the state left by the previous
+/// ones, and check them all together with the state carried across blocks.
+pub fn run_blockchain_test(
+    test: &BlockchainTest,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+) -> Result<(), StateTestError> {
+    if circuits_config.taiko {
+        return Err(StateTestError::CircuitInput(
+            "taiko mode is only supported for state tests".to_string(),
+        ));
+    }
+    let chain_id = U256::one();
+    let chain_spec = ChainSpec::default();
+    let mut history_hashes = vec![test.genesis.hash.to_word()];
+    let mut prev_blocks = Vec::new();
+    let mut geth_datas = Vec::new();
+
+    for block in &test.blocks {
+        let block_constants = BlockConstants::from(&block.header);
+        let geth_traces = external_tracer::trace(&TraceConfig {
+            chain_id,
+            chain_spec,
+            history_hashes: history_hashes.clone(),
+            block_constants: block_constants.clone(),
+            accounts: test.pre.clone(),
+            transactions: block.transactions.clone(),
+            prev_blocks: prev_blocks.clone(),
+            ..Default::default()
+        })
+        .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+
+        for geth_trace in &geth_traces {
+            if geth_trace.struct_logs.len() as u64 > suite.max_steps {
+                return Err(StateTestError::SkipTestMaxSteps(
+                    geth_trace.struct_logs.len(),
+                ));
+            }
+            if suite.max_gas > 0 && geth_trace.gas.0 > suite.max_gas {
+                return Err(StateTestError::SkipTestMaxGasLimit(geth_trace.gas.0));
+            }
+        }
+
+        let transactions = block
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| tx.to_response(U64::from(index), chain_id, block_constants.number))
+            .collect();
+        let eth_block = eth_types::Block {
+            hash: Some(block.header.hash),
+            parent_hash: block.header.parent_hash,
+            author: Some(block_constants.coinbase),
+            timestamp: block_constants.timestamp,
+            number: Some(block_constants.number),
+            mix_hash: Some(block_constants.mix_hash),
+            gas_limit: block_constants.gas_limit,
+            base_fee_per_gas: Some(block_constants.base_fee),
+            transactions,
+            ..eth_types::Block::default()
+        };
+
+        geth_datas.push(GethData {
+            chain_id,
+            history_hashes: history_hashes.clone(),
+            eth_block,
+            geth_traces,
+            accounts: test.pre.values().cloned().collect(),
+            chain_spec,
+        });
+        prev_blocks.push(PrevBlock {
+            block_constants,
+            transactions: block.transactions.clone(),
+        });
+        history_hashes.push(block.header.hash.to_word());
+        if history_hashes.len() > 256 {
+            history_hashes.remove(0);
+        }
+    }
+
+    if geth_datas.is_empty() {
+        return Ok(());
+    }
+    let max_txs = std::cmp::max(
+        test.blocks
+            .iter()
+            .map(|block| block.transactions.len())
+            .sum::<usize>(),
+        1,
+    );
+
+    let builder = if !circuits_config.super_circuit {
+        let circuits_params = CircuitsParams {
+            max_txs,
+            max_rws: 55000,
+            max_calldata: 5000,
+            max_bytecode: 5000,
+            max_copy_rows: 55000,
+            max_evm_rows: 0,
+            max_exp_steps: 5000,
+            max_keccak_rows: 0,
+            max_receipt_items: 0,
+        };
+        let mut builder = BlockData::new_from_geth_data_batch(&geth_datas, circuits_params)
+            .new_circuit_input_builder();
+        let blocks: Vec<_> = geth_datas
+            .iter()
+            .map(|geth_data| (&geth_data.eth_block, geth_data.geth_traces.as_slice()))
+            .collect();
+        builder
+            .handle_blocks(&blocks)
+            .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
+
+        let block: Block<Fr> = block_convert(&builder.block, &builder.code_db).unwrap();
+        check_headers(test, &block)?;
+
+        CircuitTestBuilder::<1, 1>::new_from_block(block).run();
+        builder
+    } else {
+        let circuits_params = CircuitsParams {
+            max_txs,
+            max_calldata: 32,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_exp_steps: 256,
+            max_bytecode: 512,
+            max_evm_rows: 0,
+            max_keccak_rows: 0,
+            max_receipt_items: 0,
+        };
+        let (k, circuit, instance, builder) =
+            SuperCircuit::<Fr>::build_batch(geth_datas, circuits_params, Fr::from(0x100)).unwrap();
+
+        let block: Block<Fr> = block_convert(&builder.block, &builder.code_db).unwrap();
+        check_headers(test, &block)?;
+
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied_par();
+        builder
+    };
+
+    if let Some(post) = &test.post {
+        check_post(&builder, post)?;
+    }
+
+    Ok(())
+}
+
+/// Check the fields of the filled headers that follow from the execution of
+/// the blocks: the chain linkage, the gas used, the receipts root and the
+/// logs bloom. The state root is left to the post-state check, since the
+/// state trie isn't built here.
+fn check_headers(test: &BlockchainTest, block: &Block<Fr>) -> Result<(), StateTestError> {
+    let tx_types: Vec<u64> = block
+        .eth_blocks()
+        .flat_map(|eth_block| eth_block.transactions.iter())
+        .map(|tx| tx.transaction_type.unwrap_or_default().as_u64())
+        .collect();
+    let mut receipts = receipts_convert(&block.rws, &tx_types).into_iter();
+
+    let mut parent = &test.genesis;
+    for test_block in &test.blocks {
+        let header = &test_block.header;
+        let block_receipts: Vec<Receipt> = receipts
+            .by_ref()
+            .take(test_block.transactions.len())
+            .collect();
+        let gas_used = block_receipts
+            .last()
+            .map(|receipt| receipt.cumulative_gas_used)
+            .unwrap_or_default();
+
+        check_header_field(header, "parentHash", header.parent_hash, parent.hash)?;
+        check_header_field(header, "number", header.number, parent.number + 1)?;
+        check_header_field(header, "gasUsed", header.gas_used, gas_used)?;
+        check_header_field(
+            header,
+            "receiptTrie",
+            header.receipts_root,
+            receipts_root(&block_receipts),
+        )?;
+        check_header_field(
+            header,
+            "bloom",
+            header.logs_bloom.to_vec(),
+            logs_bloom(&block_receipts).to_vec(),
+        )?;
+        parent = header;
+    }
+    Ok(())
+}
+
+fn check_header_field<T: PartialEq + std::fmt::Debug>(
+    header: &BlockHeader,
+    field: &'static str,
+    expected: T,
+    found: T,
+) -> Result<(), StateTestError> {
+    if expected != found {
+        return Err(StateTestError::HeaderMismatch {
+            number: header.number,
+            field,
+            expected: format!("{:?}", expected),
+            found: format!("{:?}", found),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,408 @@
+use super::spec::{BlockHeader, BlockchainTest, TestBlock};
+use crate::{
+    statetest::{parse, AccountMatch, StateTestResult},
+    utils::{MainnetFork, TEST_FORK},
+};
+use anyhow::{Context, Result};
+use eth_types::{geth_types, geth_types::Account, AccessList, Address, BigEndianHash, H256, U256};
+use ethers_core::types::transaction::eip2930::AccessListItem;
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlockHeader {
+    base_fee_per_gas: Option<String>,
+    bloom: String,
+    coinbase: String,
+    gas_limit: String,
+    gas_used: String,
+    hash: String,
+    mix_hash: String,
+    number: String,
+    parent_hash: String,
+    receipt_trie: String,
+    timestamp: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTransaction {
+    access_list: Option<Vec<JsonAccessListItem>>,
+    data: String,
+    gas_limit: String,
+    gas_price: Option<String>,
+    max_fee_per_gas: Option<String>,
+    max_priority_fee_per_gas: Option<String>,
+    nonce: String,
+    r: String,
+    s: String,
+    sender: Option<String>,
+    to: String,
+    #[serde(rename = "type")]
+    tx_type: Option<String>,
+    v: String,
+    value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonAccessListItem {
+    address: String,
+    storage_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlock {
+    block_header: Option<JsonBlockHeader>,
+    expect_exception: Option<String>,
+    #[serde(default)]
+    transactions: Vec<JsonTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct JsonAccount {
+    balance: String,
+    code: String,
+    nonce: String,
+    storage: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBlockchainTest {
+    blocks: Vec<JsonBlock>,
+    genesis_block_header: JsonBlockHeader,
+    network: String,
+    post_state: Option<HashMap<String, JsonAccount>>,
+    pre: HashMap<String, JsonAccount>,
+}
+
+#[derive(Default)]
+pub struct JsonBlockchainTestBuilder;
+
+impl JsonBlockchainTestBuilder {
+    /// generates `BlockchainTest` vectors from a filled ethereum blockchain
+    /// test, skipping the ones of other networks
+    pub fn load_json(&self, path: &str, source: &str) -> Result<Vec<BlockchainTest>> {
+        let mut blockchain_tests = Vec::new();
+        let tests: HashMap<String, JsonBlockchainTest> = serde_json::from_str(source)?;
+
+        for (test_name, test) in tests {
+            if MainnetFork::from_str(&test.network).ok() != Some(TEST_FORK) {
+                continue;
+            }
+
+            let mut blocks = Vec::new();
+            for block in &test.blocks {
+                // invalid blocks are rejected by the clients and don't extend the chain
+                let header = match (&block.block_header, &block.expect_exception) {
+                    (Some(header), None) => Self::parse_header(header)?,
+                    _ => continue,
+                };
+                let transactions = block
+                    .transactions
+                    .iter()
+                    .map(|tx| Self::parse_transaction(tx, header.base_fee))
+                    .collect::<Result<_>>()
+                    .context(test_name.clone())?;
+                blocks.push(TestBlock {
+                    header,
+                    transactions,
+                });
+            }
+
+            blockchain_tests.push(BlockchainTest {
+                path: path.to_string(),
+                id: test_name,
+                genesis: Self::parse_header(&test.genesis_block_header)?,
+                pre: Self::parse_accounts_pre(&test.pre)?,
+                blocks,
+                post: test
+                    .post_state
+                    .as_ref()
+                    .map(Self::parse_accounts_post)
+                    .transpose()?,
+            });
+        }
+
+        Ok(blockchain_tests)
+    }
+
+    /// parse a block header, where a missing base fee means a pre-London block
+    fn parse_header(header: &JsonBlockHeader) -> Result<BlockHeader> {
+        Ok(BlockHeader {
+            coinbase: parse::parse_address(&header.coinbase)?,
+            mix_hash: parse::parse_hash(&header.mix_hash)?,
+            gas_limit: parse::parse_u64(&header.gas_limit)?,
+            number: parse::parse_u64(&header.number)?,
+            timestamp: parse::parse_u64(&header.timestamp)?,
+            base_fee: header
+                .base_fee_per_gas
+                .as_ref()
+                .map(|v| parse::parse_u256(v))
+                .transpose()?
+                .unwrap_or_default(),
+            hash: parse::parse_hash(&header.hash)?,
+            parent_hash: parse::parse_hash(&header.parent_hash)?,
+            gas_used: parse::parse_u64(&header.gas_used)?,
+            receipts_root: parse::parse_hash(&header.receipt_trie)?,
+            logs_bloom: parse::parse_bytes(&header.bloom)?,
+        })
+    }
+
+    /// parse a signed transaction included in a block with `base_fee`
+    fn parse_transaction(tx: &JsonTransaction, base_fee: U256) -> Result<geth_types::Transaction> {
+        let parse_optional_u256 =
+            |value: &Option<String>| value.as_ref().map(|v| parse::parse_u256(v)).transpose();
+
+        // legacy and EIP-2930 txs pay the gas price whatever the base fee is, so
+        // they behave as EIP-1559 txs with both fee caps set to the gas price.
+        let (gas_fee_cap, gas_tip_cap) = match parse_optional_u256(&tx.max_fee_per_gas)? {
+            Some(max_fee_per_gas) => (
+                max_fee_per_gas,
+                parse_optional_u256(&tx.max_priority_fee_per_gas)?.unwrap_or_default(),
+            ),
+            None => {
+                let gas_price = parse_optional_u256(&tx.gas_price)?.context("gasPrice")?;
+                (gas_price, gas_price)
+            }
+        };
+
+        let transaction_type = match &tx.tx_type {
+            Some(tx_type) => parse::parse_u64(tx_type)?,
+            None if tx.max_fee_per_gas.is_some() => 2,
+            None if tx.access_list.is_some() => 1,
+            None => 0,
+        };
+
+        Ok(geth_types::Transaction {
+            from: parse::parse_address(tx.sender.as_ref().context("sender")?)?,
+            to: parse::parse_to_address(&tx.to)?,
+            nonce: parse::parse_u64(&tx.nonce)?.into(),
+            gas_limit: parse::parse_u64(&tx.gas_limit)?.into(),
+            value: parse::parse_u256(&tx.value)?,
+            gas_price: std::cmp::min(base_fee + gas_tip_cap, gas_fee_cap),
+            gas_fee_cap,
+            gas_tip_cap,
+            call_data: parse::parse_bytes(&tx.data)?,
+            access_list: tx
+                .access_list
+                .as_ref()
+                .map(|items| Self::parse_access_list(items))
+                .transpose()?,
+            transaction_type: Some(transaction_type.into()),
+            v: parse::parse_u64(&tx.v)?,
+            r: parse::parse_u256(&tx.r)?,
+            s: parse::parse_u256(&tx.s)?,
+        })
+    }
+
+    /// parse a list of address=>storageKeys entries
+    fn parse_access_list(items: &[JsonAccessListItem]) -> Result<AccessList> {
+        let items = items
+            .iter()
+            .map(|item| {
+                Ok(AccessListItem {
+                    address: parse::parse_address(&item.address)?,
+                    storage_keys: item
+                        .storage_keys
+                        .iter()
+                        .map(|key| Ok(H256::from_uint(&parse::parse_u256(key)?)))
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(AccessList(items))
+    }
+
+    /// parse a vector of address=>(storage,balance,code,nonce) entry
+    fn parse_accounts_pre(
+        accounts: &HashMap<String, JsonAccount>,
+    ) -> Result<HashMap<Address, Account>> {
+        let mut accounts_pre = HashMap::new();
+        for (address, acc) in accounts {
+            let address = parse::parse_address(address)?;
+            let mut storage = HashMap::new();
+            for (k, v) in &acc.storage {
+                storage.insert(parse::parse_u256(k)?, parse::parse_u256(v)?);
+            }
+            let account = Account {
+                address,
+                balance: parse::parse_u256(&acc.balance)?,
+                nonce: parse::parse_u64(&acc.nonce)?.into(),
+                code: parse::parse_bytes(&acc.code)?,
+                storage,
+            };
+            accounts_pre.insert(address, account);
+        }
+        Ok(accounts_pre)
+    }
+
+    /// parse the full expected state after the last block
+    fn parse_accounts_post(accounts: &HashMap<String, JsonAccount>) -> Result<StateTestResult> {
+        Ok(Self::parse_accounts_pre(accounts)?
+            .into_iter()
+            .map(|(address, account)| {
+                let expected = AccountMatch {
+                    address,
+                    balance: Some(account.balance),
+                    code: Some(account.code),
+                    nonce: Some(account.nonce.as_u64()),
+                    storage: account.storage,
+                };
+                (address, expected)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const JSON: &str = r#"
+{
+  "transfers_Merge": {
+    "blocks": [
+      {
+        "blockHeader": {
+          "baseFeePerGas": "0x0a",
+          "bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+          "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+          "gasLimit": "0x0f4240",
+          "gasUsed": "0xa410",
+          "hash": "0x1111111111111111111111111111111111111111111111111111111111111111",
+          "mixHash": "0x0000000000000000000000000000000000000000000000000000000000020000",
+          "number": "0x01",
+          "parentHash": "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
+          "receiptTrie": "0x2222222222222222222222222222222222222222222222222222222222222222",
+          "timestamp": "0x03e8"
+        },
+        "transactions": [
+          {
+            "data": "0x",
+            "gasLimit": "0x5208",
+            "gasPrice": "0x0a",
+            "nonce": "0x00",
+            "r": "0x01",
+            "s": "0x02",
+            "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+            "to": "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+            "v": "0x1b",
+            "value": "0x01"
+          },
+          {
+            "accessList": [],
+            "chainId": "0x01",
+            "data": "0x",
+            "gasLimit": "0x5208",
+            "maxFeePerGas": "0x14",
+            "maxPriorityFeePerGas": "0x02",
+            "nonce": "0x01",
+            "r": "0x01",
+            "s": "0x02",
+            "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b",
+            "to": "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+            "type": "0x02",
+            "v": "0x00",
+            "value": "0x01"
+          }
+        ]
+      },
+      {
+        "expectException": "TR_NoFunds",
+        "rlp": "0x00"
+      }
+    ],
+    "genesisBlockHeader": {
+      "baseFeePerGas": "0x0a",
+      "bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+      "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+      "gasLimit": "0x0f4240",
+      "gasUsed": "0x00",
+      "hash": "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
+      "mixHash": "0x0000000000000000000000000000000000000000000000000000000000020000",
+      "number": "0x00",
+      "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+      "receiptTrie": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+      "timestamp": "0x00"
+    },
+    "network": "Merge",
+    "postState": {
+      "0x095e7baea6a6c7c4c2dfeb977efac326af552d87": {
+        "balance": "0x02",
+        "code": "0x",
+        "nonce": "0x00",
+        "storage": {}
+      }
+    },
+    "pre": {
+      "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b": {
+        "balance": "0x0de0b6b3a7640000",
+        "code": "0x",
+        "nonce": "0x00",
+        "storage": {}
+      }
+    }
+  },
+  "transfers_London": {
+    "blocks": [],
+    "genesisBlockHeader": {
+      "bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+      "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+      "gasLimit": "0x0f4240",
+      "gasUsed": "0x00",
+      "hash": "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
+      "mixHash": "0x0000000000000000000000000000000000000000000000000000000000020000",
+      "number": "0x00",
+      "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+      "receiptTrie": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+      "timestamp": "0x00"
+    },
+    "network": "London",
+    "pre": {}
+  }
+}
+"#;
+
+    #[test]
+    fn test_json_parse_blockchain() -> Result<()> {
+        let mut tests = JsonBlockchainTestBuilder.load_json("test_path", JSON)?;
+        assert_eq!(tests.len(), 1);
+        let test = tests.remove(0);
+
+        let acc095e = Address::from_str("0x095e7baea6a6c7c4c2dfeb977efac326af552d87")?;
+        assert_eq!(test.id, "transfers_Merge");
+        assert_eq!(test.genesis.number, 0);
+        assert_eq!(test.pre.len(), 1);
+
+        // the invalid block is dropped
+        assert_eq!(test.blocks.len(), 1);
+        let block = &test.blocks[0];
+        assert_eq!(block.header.number, 1);
+        assert_eq!(block.header.parent_hash, test.genesis.hash);
+        assert_eq!(block.header.base_fee, U256::from(10));
+        assert_eq!(block.header.gas_used, 42000);
+        assert_eq!(block.header.receipts_root, H256::repeat_byte(0x22));
+        assert_eq!(block.header.logs_bloom.len(), 256);
+
+        let txs = &block.transactions;
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].to, Some(acc095e));
+        assert_eq!(txs[0].gas_price, U256::from(10));
+        assert_eq!(txs[0].v, 27);
+        assert_eq!(txs[1].nonce.as_u64(), 1);
+        assert_eq!(txs[1].gas_fee_cap, U256::from(20));
+        assert_eq!(txs[1].gas_tip_cap, U256::from(2));
+        // base fee plus tip
+        assert_eq!(txs[1].gas_price, U256::from(12));
+        assert_eq!(txs[1].access_list, Some(AccessList(vec![])));
+
+        let post = test.post.expect("post state");
+        assert_eq!(post[&acc095e].balance, Some(U256::from(2)));
+        assert_eq!(post[&acc095e].nonce, Some(0));
+        Ok(())
+    }
+}
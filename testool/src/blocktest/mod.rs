@@ -0,0 +1,9 @@
+mod executor;
+mod json;
+pub mod spec;
+mod suite;
+
+pub use executor::run_blockchain_test;
+pub use json::JsonBlockchainTestBuilder;
+pub use spec::{BlockHeader, BlockchainTest, TestBlock};
+pub use suite::load_blocktests_suite;
@@ -0,0 +1,74 @@
+use crate::{
+    config::TestSuite,
+    statetest::{CircuitsConfig, StateTestError, StateTestResult, SuiteTest},
+};
+use eth_types::{
+    geth_types::{self, Account, BlockConstants},
+    Address, Bytes, H256, U256, U64,
+};
+use std::collections::HashMap;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BlockHeader {
+    pub coinbase: Address,
+    pub mix_hash: H256,
+    pub gas_limit: u64,
+    pub number: u64,
+    pub timestamp: u64,
+    pub base_fee: U256,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub gas_used: u64,
+    pub receipts_root: H256,
+    pub logs_bloom: Bytes,
+}
+
+impl From<&BlockHeader> for BlockConstants {
+    fn from(header: &BlockHeader) -> Self {
+        Self {
+            coinbase: header.coinbase,
+            timestamp: U256::from(header.timestamp),
+            number: U64::from(header.number),
+            mix_hash: header.mix_hash,
+            gas_limit: U256::from(header.gas_limit),
+            base_fee: header.base_fee,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestBlock {
+    pub header: BlockHeader,
+    /// Signed transactions, with the gas price they pay in this block
+    pub transactions: Vec<geth_types::Transaction>,
+}
+
+/// A chain of blocks built on top of a genesis state.  Only the valid blocks
+/// of the test are kept, since the invalid ones aren't part of the chain.
+#[derive(Debug, Clone)]
+pub struct BlockchainTest {
+    pub path: String,
+    pub id: String,
+    pub genesis: BlockHeader,
+    pub pre: HashMap<Address, Account>,
+    pub blocks: Vec<TestBlock>,
+    /// Expected state after the last block, missing when the test only
+    /// provides its root
+    pub post: Option<StateTestResult>,
+}
+
+impl SuiteTest for BlockchainTest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn path(&self) -> &str {
+        &self.path
+    }
+    fn run(
+        &self,
+        suite: &TestSuite,
+        circuits_config: &CircuitsConfig,
+    ) -> Result<(), StateTestError> {
+        super::run_blockchain_test(self, suite, circuits_config)
+    }
+}
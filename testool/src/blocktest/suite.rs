@@ -0,0 +1,34 @@
+use super::{BlockchainTest, JsonBlockchainTestBuilder};
+use crate::config::Config;
+use anyhow::{Context, Result};
+
+pub fn load_blocktests_suite(path: &str, config: Config) -> Result<Vec<BlockchainTest>> {
+    let skip_paths: Vec<&String> = config.skip_paths.iter().flat_map(|t| &t.paths).collect();
+    let skip_tests: Vec<&String> = config.skip_tests.iter().flat_map(|t| &t.tests).collect();
+
+    let files = glob::glob(path)
+        .context("failed to read glob")?
+        .filter_map(|v| v.ok())
+        .filter(|f| {
+            !skip_paths
+                .iter()
+                .any(|e| f.as_path().to_string_lossy().contains(*e))
+        });
+
+    let mut tests = Vec::new();
+    for file in files {
+        if file.extension().map(|ext| ext == "json") != Some(true) {
+            continue;
+        }
+        let path = file.as_path().to_string_lossy();
+        let src = std::fs::read_to_string(&file)?;
+        log::debug!(target: "testool", "Reading file {:?}", file);
+        let mut tcs = JsonBlockchainTestBuilder
+            .load_json(&path, &src)
+            .with_context(|| format!("parsing {}", path))?;
+
+        tcs.retain(|v| !skip_tests.contains(&&v.id));
+        tests.append(&mut tcs);
+    }
+    Ok(tests)
+}
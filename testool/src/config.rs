@@ -17,11 +17,24 @@ pub struct TestSuite {
     pub path: String,
     pub max_gas: u64,
     pub max_steps: u64,
+    #[serde(default)]
+    pub format: TestFormat,
 
     ignore_tests: Option<Vec<String>>,
     allow_tests: Option<Vec<String>>,
 }
 
+/// Format of the test files of a suite
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFormat {
+    /// `GeneralStateTestsFiller` yaml and json fillers
+    #[default]
+    State,
+    /// Filled `BlockchainTests` json files
+    Blockchain,
+}
+
 impl Default for TestSuite {
     fn default() -> Self {
         Self {
@@ -29,6 +42,7 @@ impl Default for TestSuite {
             path: String::default(),
             max_gas: u64::MAX,
             max_steps: u64::MAX,
+            format: TestFormat::default(),
             ignore_tests: Some(Vec::new()),
             allow_tests: None,
         }
@@ -1,4 +1,5 @@
 pub mod abi;
+pub mod blocktest;
 pub mod compiler;
 pub mod config;
 pub mod statetest;
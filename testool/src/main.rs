@@ -1,15 +1,19 @@
 /// Execute the bytecode from an empty state and run the EVM and State circuits
-use testool::{compiler, config, statetest, utils};
+use testool::{blocktest, compiler, config, statetest, utils};
 
-use crate::{config::TestSuite, statetest::ResultLevel};
+use crate::{
+    config::{TestFormat, TestSuite},
+    statetest::ResultLevel,
+};
 use anyhow::{bail, Result};
+use blocktest::load_blocktests_suite;
 use clap::Parser;
 use compiler::Compiler;
 use config::Config;
 use log::info;
 use statetest::{
     geth_trace, load_statetests_suite, run_statetests_suite, run_test, CircuitsConfig, Results,
-    StateTest,
+    StateTest, SuiteTest,
 };
 use std::{collections::HashSet, path::PathBuf, time::SystemTime};
 use strum::EnumString;
@@ -60,6 +64,11 @@ struct Args {
     #[clap(long)]
     circuits: Option<Circuits>,
 
+    /// Run the state tests in a taiko block, after an anchor tx, with the taiko
+    /// SuperCircuit
+    #[clap(long)]
+    taiko: bool,
+
     /// Verbose
     #[clap(short, long)]
     v: bool,
@@ -67,7 +76,7 @@ struct Args {
 
 fn run_single_test(test: StateTest, circuits_config: CircuitsConfig) -> Result<()> {
     println!("{}", &test);
-    let trace = geth_trace(test.clone())?;
+    let trace = geth_trace(test.clone(), circuits_config.taiko)?;
     crate::utils::print_trace(trace)?;
     println!(
         "result={:?}",
@@ -86,6 +95,7 @@ fn go() -> Result<()> {
     if args.circuits == Some(Circuits::sc) {
         circuits_config.super_circuit = true;
     }
+    circuits_config.taiko = args.taiko;
 
     if let Some(oneliner) = &args.oneliner {
         let test = StateTest::parse_oneline_spec(oneliner)?;
@@ -98,37 +108,66 @@ fn go() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     log::info!("Using suite '{}'", args.suite);
-    log::info!("Parsing and compliling tests...");
-    let compiler = Compiler::new(true, Some(PathBuf::from(CODEHASH_FILE)))?;
     let suite = config.suite(&args.suite)?.clone();
-    let state_tests = load_statetests_suite(&suite.path, config, compiler)?;
-    log::info!("{} tests collected in {}", state_tests.len(), suite.path);
+    match suite.format {
+        TestFormat::State => {
+            log::info!("Parsing and compliling tests...");
+            let compiler = Compiler::new(true, Some(PathBuf::from(CODEHASH_FILE)))?;
+            let state_tests = load_statetests_suite(&suite.path, config, compiler)?;
+            if let Some(test_id) = &args.inspect {
+                let test = find_test(&state_tests, test_id)?;
+                return run_single_test(test.clone(), circuits_config);
+            }
+            run_suite(&args, &suite, &circuits_config, state_tests)
+        }
+        TestFormat::Blockchain => {
+            log::info!("Parsing tests...");
+            let blockchain_tests = load_blocktests_suite(&suite.path, config)?;
+            if let Some(test_id) = &args.inspect {
+                let test = find_test(&blockchain_tests, test_id)?;
+                println!(
+                    "result={:?}",
+                    test.run(&TestSuite::default(), &circuits_config)
+                );
+                return Ok(());
+            }
+            run_suite(&args, &suite, &circuits_config, blockchain_tests)
+        }
+    }
+}
+
+/// Find the test with `test_id`, listing the ones that partially match if not
+/// found
+fn find_test<'a, T: SuiteTest>(tests: &'a [T], test_id: &str) -> Result<&'a T> {
+    if let Some(test) = tests.iter().find(|t| t.id() == test_id) {
+        return Ok(test);
+    }
+    info!(
+        "Test '{}' not found but found some that partially matches:",
+        test_id
+    );
+    for test in tests.iter().filter(|t| t.id().contains(test_id)) {
+        info!("{}", test.id());
+    }
+    bail!("test '{}' not found", test_id);
+}
+
+fn run_suite<T: SuiteTest>(
+    args: &Args,
+    suite: &TestSuite,
+    circuits_config: &CircuitsConfig,
+    tests: Vec<T>,
+) -> Result<()> {
+    log::info!("{} tests collected in {}", tests.len(), suite.path);
 
     if args.ls {
-        let mut list: Vec<_> = state_tests.into_iter().map(|t| t.id).collect();
+        let mut list: Vec<_> = tests.iter().map(|t| t.id().to_string()).collect();
         list.sort();
         for test in list {
             info!("{}", test);
         }
         return Ok(());
     }
-    if let Some(test_id) = args.inspect {
-        // Test only one and return
-        let mut state_tests_filtered: Vec<_> =
-            state_tests.iter().filter(|t| t.id == test_id).collect();
-        if state_tests_filtered.is_empty() {
-            info!(
-                "Test '{}' not found but found some that partially matches:",
-                test_id
-            );
-            for test in state_tests.iter().filter(|t| t.id.contains(&test_id)) {
-                info!("{}", test.id);
-            }
-            bail!("test '{}' not found", test_id);
-        }
-        run_single_test(state_tests_filtered.remove(0).clone(), circuits_config)?;
-        return Ok(());
-    };
 
     if args.report {
         let git_hash = utils::current_git_commit()?;
@@ -151,8 +190,8 @@ fn go() -> Result<()> {
         // when running a report, the tests result of the containing cache file
         // are used, but by default removing all Ignored tests
         // Another way is to skip the test which level not in whitelist_levels
-        let mut previous_results = if let Some(cache_filename) = args.cache {
-            let whitelist_levels = HashSet::<ResultLevel>::from_iter(args.levels);
+        let mut previous_results = if let Some(cache_filename) = &args.cache {
+            let whitelist_levels = HashSet::<ResultLevel>::from_iter(args.levels.iter().copied());
 
             let mut previous_results = Results::from_file(PathBuf::from(cache_filename))?;
             if !whitelist_levels.is_empty() {
@@ -172,7 +211,7 @@ fn go() -> Result<()> {
             Results::default()
         };
         previous_results.set_cache(PathBuf::from(csv_filename));
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut previous_results)?;
+        run_statetests_suite(tests, circuits_config, suite, &mut previous_results)?;
 
         // filter non-csv files and files from the same commit
         let mut files: Vec<_> = std::fs::read_dir(REPORT_FOLDER)
@@ -201,14 +240,14 @@ fn go() -> Result<()> {
         report.print_tty()?;
         info!("{}", html_filename);
     } else {
-        let mut results = if let Some(cache_filename) = args.cache {
+        let mut results = if let Some(cache_filename) = &args.cache {
             Results::with_cache(PathBuf::from(cache_filename))?
         } else {
             Results::default()
         };
 
         log::info!("Executing...");
-        run_statetests_suite(state_tests, &circuits_config, &suite, &mut results)?;
+        run_statetests_suite(tests, circuits_config, suite, &mut results)?;
         let success = results.success();
 
         log::info!("Generating report...");
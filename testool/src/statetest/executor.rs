@@ -1,20 +1,34 @@
 use super::{AccountMatch, StateTest, StateTestResult};
 use crate::config::TestSuite;
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{
+        protocol_instance::{Transition, TREASURY},
+        BlockMetadata, CircuitInputBuilder, CircuitsParams, ProtocolInstance,
+    },
     mock::BlockData,
 };
-use eth_types::{geth_types, Address, Bytes, GethExecTrace, U256, U64};
+use eth_types::{geth_types, Address, Bytes, GethExecTrace, H256, U256, U64};
 use ethers_core::{
     k256::ecdsa::SigningKey,
-    types::{transaction::eip2718::TypedTransaction, TransactionRequest},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip2930::Eip2930TransactionRequest},
+        Eip1559TransactionRequest, TransactionRequest,
+    },
+    utils::keccak256,
 };
 use ethers_signers::{LocalWallet, Signer};
 use external_tracer::TraceConfig;
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use mock::{
+    MockTransaction, GOLDEN_TOUCH, MOCK_ANCHOR_L1_HASH, MOCK_ANCHOR_L1_HIGHT,
+    MOCK_ANCHOR_SIGNAL_ROOT, MOCK_CODES, MOCK_TAIKO_L2_ADDRESS,
+};
 use std::{collections::HashMap, str::FromStr};
 use thiserror::Error;
-use zkevm_circuits::{super_circuit::SuperCircuit, test_util::CircuitTestBuilder, witness::Block};
+use zkevm_circuits::{
+    super_circuit::SuperCircuit, taiko_super_circuit::SuperCircuit as TaikoSuperCircuit,
+    test_util::CircuitTestBuilder, witness::Block,
+};
 
 #[derive(PartialEq, Eq, Error, Debug)]
 pub enum StateTestError {
@@ -38,6 +52,13 @@ pub enum StateTestError {
     SkipTestMaxSteps(usize),
     #[error("Exception(expected:{expected:?}, found:{found:?})")]
     Exception { expected: bool, found: String },
+    #[error("HeaderMismatch(block:{number}, field:{field}, expected:{expected}, found:{found})")]
+    HeaderMismatch {
+        number: u64,
+        field: &'static str,
+        expected: String,
+        found: String,
+    },
 }
 
 impl StateTestError {
@@ -52,9 +73,12 @@ impl StateTestError {
 #[derive(Default, Debug, Clone)]
 pub struct CircuitsConfig {
     pub super_circuit: bool,
+    /// Run the test in a taiko block, after an anchor tx, with the taiko
+    /// SuperCircuit
+    pub taiko: bool,
 }
 
-fn check_post(
+pub(crate) fn check_post(
     builder: &CircuitInputBuilder,
     post: &HashMap<Address, AccountMatch>,
 ) -> Result<(), StateTestError> {
@@ -103,29 +127,108 @@ fn check_post(
     Ok(())
 }
 
-fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
+fn into_traceconfig(st: StateTest, taiko: bool) -> (String, TraceConfig, StateTestResult) {
     let chain_id = 1;
     let wallet = LocalWallet::from_str(&hex::encode(st.secret_key.0)).unwrap();
-    let mut tx = TransactionRequest::new()
-        .chain_id(chain_id)
-        .from(st.from)
-        .nonce(st.nonce)
-        .value(st.value)
-        .data(st.data.clone())
-        .gas(st.gas_limit)
-        .gas_price(st.gas_price);
-
-    if let Some(to) = st.to {
-        tx = tx.to(to);
-    }
-    let tx: TypedTransaction = tx.into();
+
+    // legacy and EIP-2930 txs pay the gas price whatever the base fee is, so
+    // they behave as EIP-1559 txs with both fee caps set to the gas price.
+    let (gas_fee_cap, gas_tip_cap) = match st.max_fee_per_gas {
+        Some(max_fee_per_gas) => (
+            max_fee_per_gas,
+            st.max_priority_fee_per_gas.unwrap_or_default(),
+        ),
+        None => (st.gas_price, st.gas_price),
+    };
+    let gas_price = std::cmp::min(st.env.current_base_fee + gas_tip_cap, gas_fee_cap);
+
+    let transaction_type = if st.max_fee_per_gas.is_some() {
+        2
+    } else if st.access_list.is_some() {
+        1
+    } else {
+        0
+    };
+    let tx: TypedTransaction = if st.max_fee_per_gas.is_some() {
+        let mut tx = Eip1559TransactionRequest::new()
+            .chain_id(chain_id)
+            .from(st.from)
+            .nonce(st.nonce)
+            .value(st.value)
+            .data(st.data.clone())
+            .gas(st.gas_limit)
+            .max_fee_per_gas(gas_fee_cap)
+            .max_priority_fee_per_gas(gas_tip_cap);
+        if let Some(to) = st.to {
+            tx = tx.to(to);
+        }
+        if let Some(access_list) = &st.access_list {
+            tx = tx.access_list(access_list.clone());
+        }
+        tx.into()
+    } else {
+        let mut tx = TransactionRequest::new()
+            .chain_id(chain_id)
+            .from(st.from)
+            .nonce(st.nonce)
+            .value(st.value)
+            .data(st.data.clone())
+            .gas(st.gas_limit)
+            .gas_price(st.gas_price);
+        if let Some(to) = st.to {
+            tx = tx.to(to);
+        }
+        match &st.access_list {
+            Some(access_list) => Eip2930TransactionRequest::new(tx, access_list.clone()).into(),
+            None => tx.into(),
+        }
+    };
 
     let sig = wallet.sign_transaction_sync(&tx);
 
+    let mut transactions = vec![geth_types::Transaction {
+        from: st.from,
+        to: st.to,
+        nonce: U64::from(st.nonce),
+        value: st.value,
+        gas_limit: U64::from(st.gas_limit),
+        gas_price,
+        gas_fee_cap,
+        gas_tip_cap,
+        call_data: st.data,
+        access_list: st.access_list,
+        transaction_type: Some(transaction_type.into()),
+        v: sig.v,
+        r: sig.r,
+        s: sig.s,
+    }];
+    let mut accounts = st.pre;
+    if taiko {
+        transactions.insert(
+            0,
+            geth_types::Transaction::from(
+                MockTransaction::new_anchor_with_chain_id(chain_id).build(),
+            ),
+        );
+        for (address, code) in [
+            (*GOLDEN_TOUCH, Bytes::default()),
+            (*MOCK_TAIKO_L2_ADDRESS, MOCK_CODES[0].clone()),
+            (*TREASURY, Bytes::default()),
+        ] {
+            accounts
+                .entry(address)
+                .or_insert_with(|| geth_types::Account {
+                    address,
+                    code,
+                    ..Default::default()
+                });
+        }
+    }
+
     (
         st.id,
         TraceConfig {
-            chain_id: U256::one(),
+            chain_id: U256::from(chain_id),
             history_hashes: vec![U256::from_big_endian(st.env.previous_hash.as_bytes())],
             block_constants: geth_types::BlockConstants {
                 coinbase: st.env.current_coinbase,
@@ -133,38 +236,25 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 number: U64::from(st.env.current_number),
                 mix_hash: st.env.current_mix_hash,
                 gas_limit: U256::from(st.env.current_gas_limit),
-                base_fee: U256::one(),
+                base_fee: st.env.current_base_fee,
             },
-
-            transactions: vec![geth_types::Transaction {
-                from: st.from,
-                to: st.to,
-                nonce: U64::from(st.nonce),
-                value: st.value,
-                gas_limit: U64::from(st.gas_limit),
-                gas_price: st.gas_price,
-                gas_fee_cap: U256::zero(),
-                gas_tip_cap: U256::zero(),
-                call_data: st.data,
-                access_list: None,
-                v: sig.v,
-                r: sig.r,
-                s: sig.s,
-            }],
-            accounts: st.pre,
+            transactions,
+            accounts,
+            taiko,
             ..Default::default()
         },
         st.result,
     )
 }
 
-pub fn geth_trace(st: StateTest) -> Result<GethExecTrace, StateTestError> {
-    let (_, trace_config, _) = into_traceconfig(st);
+pub fn geth_trace(st: StateTest, taiko: bool) -> Result<GethExecTrace, StateTestError> {
+    let (_, trace_config, _) = into_traceconfig(st, taiko);
 
     let mut geth_traces = external_tracer::trace(&trace_config)
         .map_err(|err| StateTestError::CircuitInput(err.to_string()))?;
 
-    Ok(geth_traces.remove(0))
+    // the test tx goes after the anchor tx
+    Ok(geth_traces.pop().expect("test tx trace"))
 }
 
 pub fn run_test(
@@ -174,7 +264,7 @@ pub fn run_test(
 ) -> Result<(), StateTestError> {
     // get the geth traces
 
-    let (_, trace_config, post) = into_traceconfig(st.clone());
+    let (_, trace_config, mut post) = into_traceconfig(st.clone(), circuits_config.taiko);
 
     let geth_traces = external_tracer::trace(&trace_config);
 
@@ -195,14 +285,29 @@ pub fn run_test(
         }
     };
 
-    if geth_traces[0].struct_logs.len() as u64 > suite.max_steps {
+    let test_trace = geth_traces.last().expect("test tx trace");
+    if test_trace.struct_logs.len() as u64 > suite.max_steps {
         return Err(StateTestError::SkipTestMaxSteps(
-            geth_traces[0].struct_logs.len(),
+            test_trace.struct_logs.len(),
         ));
     }
 
-    if suite.max_gas > 0 && geth_traces[0].gas.0 > suite.max_gas {
-        return Err(StateTestError::SkipTestMaxGasLimit(geth_traces[0].gas.0));
+    if suite.max_gas > 0 && test_trace.gas.0 > suite.max_gas {
+        return Err(StateTestError::SkipTestMaxGasLimit(test_trace.gas.0));
+    }
+
+    if circuits_config.taiko {
+        // the base fee of the test tx is transferred to the treasury instead of
+        // being burnt
+        let base_fee = st.env.current_base_fee * U256::from(test_trace.gas.0);
+        let treasury = post.entry(*TREASURY).or_insert_with(|| AccountMatch {
+            address: *TREASURY,
+            ..Default::default()
+        });
+        let treasury_balance = treasury
+            .balance
+            .unwrap_or(trace_config.accounts[&*TREASURY].balance);
+        treasury.balance = Some(treasury_balance + base_fee);
     }
 
     let transactions = trace_config
@@ -218,7 +323,7 @@ pub fn run_test(
         })
         .collect();
 
-    let eth_block = eth_types::Block {
+    let mut eth_block = eth_types::Block {
         author: Some(trace_config.block_constants.coinbase),
         timestamp: trace_config.block_constants.timestamp,
         number: Some(U64::from(trace_config.block_constants.number.as_u64())),
@@ -228,6 +333,12 @@ pub fn run_test(
         transactions,
         ..eth_types::Block::default()
     };
+    if circuits_config.taiko {
+        // the taiko PI circuit looks up the parent hash and the block hash in
+        // the block table
+        eth_block.hash = Some(H256::from(keccak256(st.env.previous_hash)));
+        eth_block.parent_hash = st.env.previous_hash;
+    }
 
     let wallet: LocalWallet = SigningKey::from_bytes(&st.secret_key).unwrap().into();
     let mut wallets = HashMap::new();
@@ -243,11 +354,34 @@ pub fn run_test(
         geth_traces: geth_traces.clone(),
         accounts: trace_config.accounts.values().cloned().collect(),
         eth_block: eth_block.clone(),
+        chain_spec: trace_config.chain_spec,
     };
 
     let mut builder;
 
-    if !circuits_config.super_circuit {
+    if circuits_config.taiko {
+        let circuits_params = CircuitsParams {
+            max_txs: 2,
+            max_calldata: 200,
+            max_rws: 256,
+            max_copy_rows: 256,
+            max_exp_steps: 256,
+            max_bytecode: 512,
+            max_evm_rows: 0,
+            max_keccak_rows: 0,
+            max_receipt_items: 0,
+        };
+        let (k, circuit, instance, _builder) = TaikoSuperCircuit::<Fr>::build(
+            geth_data,
+            circuits_params,
+            protocol_instance(&trace_config),
+        )
+        .unwrap();
+        builder = _builder;
+
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied_par();
+    } else if !circuits_config.super_circuit {
         let circuits_params = CircuitsParams {
             max_txs: 1,
             max_rws: 55000,
@@ -257,6 +391,7 @@ pub fn run_test(
             max_evm_rows: 0,
             max_exp_steps: 5000,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         };
         let block_data = BlockData::new_from_geth_data_with_params(geth_data, circuits_params);
 
@@ -282,6 +417,7 @@ pub fn run_test(
             max_bytecode: 512,
             max_evm_rows: 0,
             max_keccak_rows: 0,
+            max_receipt_items: 0,
         };
         let (k, circuit, instance, _builder) =
             SuperCircuit::<Fr>::build(geth_data, circuits_params, Fr::from(0x100)).unwrap();
@@ -295,3 +431,24 @@ pub fn run_test(
 
     Ok(())
 }
+
+/// Protocol instance of the taiko block of a test, matching its anchor tx
+fn protocol_instance(trace_config: &TraceConfig) -> ProtocolInstance {
+    let block = &trace_config.block_constants;
+    ProtocolInstance {
+        block_metadata: BlockMetadata {
+            l1Hash: MOCK_ANCHOR_L1_HASH.as_fixed_bytes().into(),
+            coinbase: block.coinbase.as_fixed_bytes().into(),
+            id: block.number.as_u64(),
+            gasLimit: block.gas_limit.low_u32(),
+            timestamp: block.timestamp.as_u64(),
+            l1Height: *MOCK_ANCHOR_L1_HIGHT,
+            ..Default::default()
+        },
+        transition: Transition {
+            signalRoot: MOCK_ANCHOR_SIGNAL_ROOT.as_fixed_bytes().into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
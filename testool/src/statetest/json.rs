@@ -1,11 +1,14 @@
 use super::{
     parse,
-    spec::{AccountMatch, Env, StateTest},
+    spec::{AccountMatch, Env, StateTest, DEFAULT_BASE_FEE},
 };
 use crate::{compiler::Compiler, utils::MainnetFork};
 use anyhow::{bail, Result};
-use eth_types::{geth_types::Account, Address, U256};
-use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
+use eth_types::{geth_types::Account, AccessList, Address, BigEndianHash, H256, U256};
+use ethers_core::{
+    k256::ecdsa::SigningKey, types::transaction::eip2930::AccessListItem,
+    utils::secret_key_to_address,
+};
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -17,6 +20,7 @@ struct TestEnv {
     current_gas_limit: String,
     current_number: String,
     current_timestamp: String,
+    current_base_fee: Option<String>,
     previous_hash: String,
 }
 
@@ -61,15 +65,25 @@ struct JsonStateTest {
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct Transaction {
+    access_lists: Option<Vec<Option<Vec<JsonAccessListItem>>>>,
     data: Vec<String>,
     gas_limit: Vec<String>,
-    gas_price: String,
+    gas_price: Option<String>,
+    max_fee_per_gas: Option<String>,
+    max_priority_fee_per_gas: Option<String>,
     nonce: String,
     secret_key: String,
     to: String,
     value: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonAccessListItem {
+    address: String,
+    storage_keys: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 enum Ref {
     Any,
@@ -109,7 +123,25 @@ impl<'a> JsonStateTestBuilder<'a> {
             let secret_key = parse::parse_bytes(&test.transaction.secret_key)?;
             let from = secret_key_to_address(&SigningKey::from_bytes(&secret_key)?);
             let nonce = parse::parse_u64(&test.transaction.nonce)?;
-            let gas_price = parse::parse_u256(&test.transaction.gas_price)?;
+            let gas_price =
+                Self::parse_optional_u256(&test.transaction.gas_price)?.unwrap_or_else(U256::one);
+            let max_fee_per_gas = Self::parse_optional_u256(&test.transaction.max_fee_per_gas)?;
+            let max_priority_fee_per_gas =
+                Self::parse_optional_u256(&test.transaction.max_priority_fee_per_gas)?;
+
+            // one optional access list for each data
+            let access_list_s: Vec<_> = test
+                .transaction
+                .access_lists
+                .iter()
+                .flatten()
+                .map(|access_list| {
+                    access_list
+                        .as_ref()
+                        .map(|items| Self::parse_access_list(items))
+                        .transpose()
+                })
+                .collect::<Result<_>>()?;
 
             let data_s: Vec<_> = test
                 .transaction
@@ -174,6 +206,9 @@ impl<'a> JsonStateTestBuilder<'a> {
                                 secret_key: secret_key.clone(),
                                 nonce,
                                 gas_price,
+                                max_fee_per_gas,
+                                max_priority_fee_per_gas,
+                                access_list: access_list_s.get(idx_data).cloned().flatten(),
                                 gas_limit: *gas_limit,
                                 value: *value,
                                 data: data.0.clone(),
@@ -196,6 +231,8 @@ impl<'a> JsonStateTestBuilder<'a> {
             current_gas_limit: parse::parse_u64(&env.current_gas_limit)?,
             current_number: parse::parse_u64(&env.current_number)?,
             current_timestamp: parse::parse_u64(&env.current_timestamp)?,
+            current_base_fee: Self::parse_optional_u256(&env.current_base_fee)?
+                .unwrap_or_else(|| U256::from(DEFAULT_BASE_FEE)),
             previous_hash: parse::parse_hash(&env.previous_hash)?,
         })
     }
@@ -262,6 +299,29 @@ impl<'a> JsonStateTestBuilder<'a> {
         Ok(accounts)
     }
 
+    /// parse an uint256 entry that may be missing
+    fn parse_optional_u256(value: &Option<String>) -> Result<Option<U256>> {
+        value.as_ref().map(|v| parse::parse_u256(v)).transpose()
+    }
+
+    /// parse a list of address=>storageKeys entries
+    fn parse_access_list(items: &[JsonAccessListItem]) -> Result<AccessList> {
+        let items = items
+            .iter()
+            .map(|item| {
+                Ok(AccessListItem {
+                    address: parse::parse_address(&item.address)?,
+                    storage_keys: item
+                        .storage_keys
+                        .iter()
+                        .map(|key| Ok(H256::from_uint(&parse::parse_u256(key)?)))
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(AccessList(items))
+    }
+
     /// parse a unique or a list of references,
     ///   -1 => Ref::Any
     ///   a int value => Ref::Index(value)
@@ -377,6 +437,7 @@ mod test {
                 current_gas_limit: 0xFF112233445566,
                 current_number: 1,
                 current_timestamp: 1000,
+                current_base_fee: U256::from(DEFAULT_BASE_FEE),
                 previous_hash: H256::from_str(
                     "0x5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
                 )?,
@@ -390,6 +451,9 @@ mod test {
             )?),
             gas_limit: 400000,
             gas_price: U256::from(10u64),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: None,
             nonce: 0,
             value: U256::from(100000u64),
             data: Bytes::from(hex::decode("6001")?),
@@ -419,4 +483,50 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_json_parse_typed_tx() -> Result<()> {
+        let json = JSON
+            .replace(
+                r#""currentTimestamp" : "1000","#,
+                r#""currentTimestamp" : "1000", "currentBaseFee" : "7","#,
+            )
+            .replace(
+                r#""gasPrice" : "10","#,
+                r#""maxFeePerGas" : "1000",
+                "maxPriorityFeePerGas" : "10",
+                "accessLists" : [
+                    [
+                        {
+                            "address" : "0x095e7baea6a6c7c4c2dfeb977efac326af552d87",
+                            "storageKeys" : ["0x00", "0x01"]
+                        }
+                    ],
+                    null
+                ],"#,
+            );
+        let mut compiler = Compiler::new(true, None)?;
+        let mut builder = JsonStateTestBuilder::new(&mut compiler);
+        let tests: HashMap<_, _> = builder
+            .load_json("test_path", &json)?
+            .into_iter()
+            .map(|test| (test.id.clone(), test))
+            .collect();
+
+        let d0 = &tests["add11_d0_g0_v0"];
+        assert_eq!(d0.env.current_base_fee, U256::from(7u64));
+        assert_eq!(d0.gas_price, U256::one());
+        assert_eq!(d0.max_fee_per_gas, Some(U256::from(1000u64)));
+        assert_eq!(d0.max_priority_fee_per_gas, Some(U256::from(10u64)));
+        assert_eq!(
+            d0.access_list,
+            Some(AccessList(vec![AccessListItem {
+                address: Address::from_str("0x095e7baea6a6c7c4c2dfeb977efac326af552d87")?,
+                storage_keys: vec![H256::zero(), H256::from_low_u64_be(1)],
+            }]))
+        );
+        assert_eq!(tests["add11_d1_g0_v0"].access_list, None);
+
+        Ok(())
+    }
 }
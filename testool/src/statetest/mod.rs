@@ -1,15 +1,16 @@
 mod executor;
 mod json;
-mod parse;
+pub(crate) mod parse;
 mod results;
 pub mod spec;
 mod suite;
 mod yaml;
 
+pub(crate) use executor::check_post;
 pub use executor::{geth_trace, run_test, CircuitsConfig, StateTestError};
 pub use json::JsonStateTestBuilder;
 pub use parse::{parse_address, parse_hash};
 pub use results::{ResultLevel, Results};
 pub use spec::{AccountMatch, Env, StateTest, StateTestResult};
-pub use suite::{load_statetests_suite, run_statetests_suite};
+pub use suite::{load_statetests_suite, run_statetests_suite, SuiteTest};
 pub use yaml::YamlStateTestBuilder;
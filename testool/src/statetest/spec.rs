@@ -1,9 +1,13 @@
 use anyhow::{anyhow, bail, Context};
-use eth_types::{geth_types::Account, Address, Bytes, Word, H256, U256, U64};
+use eth_types::{geth_types::Account, AccessList, Address, Bytes, Word, H256, U256, U64};
 use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
 use prettytable::row;
 use std::{collections::HashMap, str::FromStr};
 
+/// Base fee used by retesteth when the env of a filler doesn't set
+/// `currentBaseFee`.
+pub const DEFAULT_BASE_FEE: u64 = 10;
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Env {
     pub current_coinbase: Address,
@@ -11,6 +15,7 @@ pub struct Env {
     pub current_gas_limit: u64,
     pub current_number: u64,
     pub current_timestamp: u64,
+    pub current_base_fee: U256,
     pub previous_hash: H256,
 }
 
@@ -48,6 +53,9 @@ pub struct StateTest {
     pub to: Option<Address>,
     pub gas_limit: u64,
     pub gas_price: U256,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub access_list: Option<AccessList>,
     pub nonce: u64,
     pub value: U256,
     pub data: Bytes,
@@ -94,12 +102,33 @@ impl std::fmt::Display for StateTest {
         table.add_row(row!["mix_hash", format!("{}", self.env.current_mix_hash)]);
         table.add_row(row!["number", format!("{}", self.env.current_number)]);
         table.add_row(row!["timestamp", format!("{}", self.env.current_timestamp)]);
+        table.add_row(row!["base_fee", format!("{}", self.env.current_base_fee)]);
         table.add_row(row!["prev_hash", format!("{:?}", self.env.previous_hash)]);
         table.add_row(row!["sk", hex::encode(&self.secret_key)]);
         table.add_row(row!["from", format!("{:?}", self.from)]);
         table.add_row(row!["to", format!("{:?}", self.to)]);
         table.add_row(row!["gas_limit", format!("{}", self.gas_limit)]);
         table.add_row(row!["gas_price", format!("{}", self.gas_price)]);
+        if let Some(max_fee_per_gas) = self.max_fee_per_gas {
+            table.add_row(row!["max_fee_per_gas", format!("{}", max_fee_per_gas)]);
+        }
+        if let Some(max_priority_fee_per_gas) = self.max_priority_fee_per_gas {
+            table.add_row(row![
+                "max_priority_fee_per_gas",
+                format!("{}", max_priority_fee_per_gas)
+            ]);
+        }
+        if let Some(access_list) = &self.access_list {
+            let mut text = String::new();
+            for item in &access_list.0 {
+                text.push_str(&format!("{:?}", item.address));
+                for key in &item.storage_keys {
+                    text.push_str(&format!("\n  {:?}", key));
+                }
+                text.push('\n');
+            }
+            table.add_row(row!["access_list", text]);
+        }
         table.add_row(row!["nonce", format!("{}", self.nonce)]);
         table.add_row(row!["value", format!("{}", self.value)]);
         table.add_row(row!["data", format(&hex::encode(&self.data), "")]);
@@ -264,6 +293,7 @@ impl StateTest {
                 current_gas_limit: 16000000,
                 current_number: 1,
                 current_timestamp: 1,
+                current_base_fee: U256::one(),
                 previous_hash: H256::default(),
             },
             secret_key,
@@ -271,6 +301,9 @@ impl StateTest {
             to,
             gas_limit,
             gas_price: U256::one(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: None,
             nonce: 0,
             value,
             data: data.into(),
@@ -1,4 +1,6 @@
-use super::{executor::run_test, CircuitsConfig, JsonStateTestBuilder, Results, StateTest};
+use super::{
+    executor::run_test, CircuitsConfig, JsonStateTestBuilder, Results, StateTest, StateTestError,
+};
 use crate::{
     compiler::Compiler,
     config::{Config, TestSuite},
@@ -14,6 +16,33 @@ use std::{
     sync::{Arc, RwLock},
 };
 
+/// A test that can be run as part of a suite
+pub trait SuiteTest: Send + Sync {
+    fn id(&self) -> &str;
+    fn path(&self) -> &str;
+    fn run(
+        &self,
+        suite: &TestSuite,
+        circuits_config: &CircuitsConfig,
+    ) -> Result<(), StateTestError>;
+}
+
+impl SuiteTest for StateTest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn path(&self) -> &str {
+        &self.path
+    }
+    fn run(
+        &self,
+        suite: &TestSuite,
+        circuits_config: &CircuitsConfig,
+    ) -> Result<(), StateTestError> {
+        run_test(self.clone(), suite.clone(), circuits_config.clone())
+    }
+}
+
 pub fn load_statetests_suite(
     path: &str,
     config: Config,
@@ -54,17 +83,17 @@ pub fn load_statetests_suite(
     Ok(tests)
 }
 
-pub fn run_statetests_suite(
-    tcs: Vec<StateTest>,
+pub fn run_statetests_suite<T: SuiteTest>(
+    tcs: Vec<T>,
     circuits_config: &CircuitsConfig,
     suite: &TestSuite,
     results: &mut Results,
 ) -> Result<()> {
     // Filter already cached entries
     let all_test_count = tcs.len();
-    let tcs: Vec<StateTest> = tcs
+    let tcs: Vec<T> = tcs
         .into_iter()
-        .filter(|t| !results.contains(&format!("{}#{}", t.id, t.path)))
+        .filter(|t| !results.contains(&format!("{}#{}", t.id(), t.path())))
         .collect();
 
     log::info!(
@@ -78,7 +107,7 @@ pub fn run_statetests_suite(
     // for each test
     let test_count = tcs.len();
     tcs.into_par_iter().for_each(|ref tc| {
-        let (test_id, path) = (tc.id.clone(), tc.path.clone());
+        let (test_id, path) = (tc.id().to_string(), tc.path().to_string());
         if !suite.allowed(&test_id) {
             results
                 .write()
@@ -103,9 +132,7 @@ pub fn run_statetests_suite(
             test_id,
             path,
         );
-        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
-            run_test(tc.clone(), suite.clone(), circuits_config.clone())
-        }));
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| tc.run(suite, circuits_config)));
 
         // handle panic
         let result = match result {
@@ -1,11 +1,14 @@
 use super::{
     parse,
-    spec::{AccountMatch, Env, StateTest},
+    spec::{AccountMatch, Env, StateTest, DEFAULT_BASE_FEE},
 };
 use crate::{compiler::Compiler, utils::MainnetFork};
 use anyhow::{bail, Context, Result};
-use eth_types::{geth_types::Account, Address, Bytes, H256, U256};
-use ethers_core::{k256::ecdsa::SigningKey, utils::secret_key_to_address};
+use eth_types::{geth_types::Account, AccessList, Address, BigEndianHash, Bytes, H256, U256};
+use ethers_core::{
+    k256::ecdsa::SigningKey, types::transaction::eip2930::AccessListItem,
+    utils::secret_key_to_address,
+};
 use std::{collections::HashMap, convert::TryInto, str::FromStr};
 use yaml_rust::Yaml;
 
@@ -100,10 +103,24 @@ impl<'a> YamlStateTestBuilder<'a> {
                 .map(Self::parse_u256)
                 .collect::<Result<_>>()?;
 
+            // one optional access list for each data
+            let access_list_s: Vec<_> = if yaml_transaction["accessLists"].is_badvalue() {
+                Vec::new()
+            } else {
+                yaml_transaction["accessLists"]
+                    .as_vec()
+                    .context("as_vec")?
+                    .iter()
+                    .map(Self::parse_access_list)
+                    .collect::<Result<_>>()?
+            };
+
             let gas_price =
                 Self::parse_u256(&yaml_transaction["gasPrice"]).unwrap_or_else(|_| U256::one());
+            let max_fee_per_gas = Self::parse_optional_u256(&yaml_transaction["maxFeePerGas"])?;
+            let max_priority_fee_per_gas =
+                Self::parse_optional_u256(&yaml_transaction["maxPriorityFeePerGas"])?;
 
-            // TODO handle maxPriorityFeePerGas & maxFeePerGas
             let nonce = Self::parse_u64(&yaml_transaction["nonce"])?;
             let to = Self::parse_to_address(&yaml_transaction["to"])?;
             let secret_key = Self::parse_bytes(&yaml_transaction["secretKey"])?;
@@ -185,6 +202,9 @@ impl<'a> YamlStateTestBuilder<'a> {
                                 to,
                                 gas_limit: *gas_limit,
                                 gas_price,
+                                max_fee_per_gas,
+                                max_priority_fee_per_gas,
+                                access_list: access_list_s.get(idx_data).cloned().flatten(),
                                 nonce,
                                 value: *value,
                                 data: data.0.clone(),
@@ -208,6 +228,8 @@ impl<'a> YamlStateTestBuilder<'a> {
             current_gas_limit: Self::parse_u64(&yaml["currentGasLimit"])?,
             current_number: Self::parse_u64(&yaml["currentNumber"])?,
             current_timestamp: Self::parse_u64(&yaml["currentTimestamp"])?,
+            current_base_fee: Self::parse_optional_u256(&yaml["currentBaseFee"])?
+                .unwrap_or_else(|| U256::from(DEFAULT_BASE_FEE)),
             previous_hash: Self::parse_hash(&yaml["previousHash"])?,
         })
     }
@@ -363,6 +385,36 @@ impl<'a> YamlStateTestBuilder<'a> {
         }
     }
 
+    /// parse an uint256 entry that may be missing
+    fn parse_optional_u256(yaml: &Yaml) -> Result<Option<U256>> {
+        if yaml.is_badvalue() || yaml.is_null() {
+            Ok(None)
+        } else {
+            Self::parse_u256(yaml).map(Some)
+        }
+    }
+
+    /// parse an access list, a list of address=>storageKeys entries, or null
+    fn parse_access_list(yaml: &Yaml) -> Result<Option<AccessList>> {
+        if yaml.is_null() {
+            return Ok(None);
+        }
+        let mut items = Vec::new();
+        for item in yaml.as_vec().context("as_vec")? {
+            let storage_keys = item["storageKeys"]
+                .as_vec()
+                .context("as_vec")?
+                .iter()
+                .map(|key| Ok(H256::from_uint(&Self::parse_u256(key)?)))
+                .collect::<Result<_>>()?;
+            items.push(AccessListItem {
+                address: Self::parse_address(&item["address"])?,
+                storage_keys,
+            });
+        }
+        Ok(Some(AccessList(items)))
+    }
+
     /// parse u64 entry
     #[allow(clippy::cast_sign_loss)]
     fn parse_u64(yaml: &Yaml) -> Result<u64> {
@@ -605,6 +657,7 @@ arith:
                 )?,
                 current_number: 1,
                 current_timestamp: 1000,
+                current_base_fee: U256::from(DEFAULT_BASE_FEE),
                 current_gas_limit: 100000000,
                 previous_hash: H256::from_slice(&hex::decode(
                     "5e20a0453cecd065ea59c37ac63e079ee08998b6045136a8ce6635c7912ec0b6",
@@ -617,6 +670,9 @@ arith:
             to: Some(ccccc),
             gas_limit: 80000000,
             gas_price: U256::from(10u64),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: None,
             nonce: 0,
             value: U256::one(),
             data: Bytes::from(&[0]),
@@ -785,6 +841,27 @@ arith:
         Ok(())
     }
 
+    #[test]
+    fn taiko_chain_id() -> Result<()> {
+        // CHAINID PUSH1 0 SSTORE STOP
+        let mut tc = YamlStateTestBuilder::new(&mut Compiler::default()).load_yaml(
+            "",
+            &Template {
+                pre_code: ":raw 0x4660005500".into(),
+                res_code: ":raw 0x4660005500".into(),
+                res_storage: "0x01".into(),
+                ..Default::default()
+            }
+            .to_string(),
+        )?;
+        let config = CircuitsConfig {
+            taiko: true,
+            ..Default::default()
+        };
+        run_test(tc.remove(0), TestSuite::default(), config)?;
+        Ok(())
+    }
+
     #[test]
     fn marked_as_exception_and_fails() -> Result<()> {
         let mut tc = YamlStateTestBuilder::new(&mut Compiler::default()).load_yaml(
@@ -0,0 +1,27 @@
+use std::{env, process};
+use zkevm_circuits::witness::{
+    reached_dummy_execution_states, Coverage, CoverageReport, COVERAGE_ENV,
+};
+
+/// Prints the coverage merged from the reports in the directory given as
+/// argument, or set in `ZKEVM_COVERAGE`, and exits with an error if any step
+/// was assigned to a dummy gadget.
+fn main() {
+    let dir = env::args()
+        .nth(1)
+        .or_else(|| env::var(COVERAGE_ENV).ok())
+        .unwrap_or_else(|| panic!("missing the directory of the coverage reports"));
+    let coverage = Coverage::load_dir(&dir)
+        .unwrap_or_else(|err| panic!("failed to read the coverage reports in {}: {}", dir, err));
+    print!("{}", CoverageReport(&coverage));
+
+    let dummies = reached_dummy_execution_states(&coverage);
+    if !dummies.is_empty() {
+        eprintln!(
+            "{} dummy execution states reached by the traces: {:?}",
+            dummies.len(),
+            dummies
+        );
+        process::exit(1);
+    }
+}
@@ -0,0 +1,377 @@
+//! Machine-readable cost model of the circuits, with the rows used by each
+//! execution state, opcode and precompile, to price the proof of a transaction.
+
+use crate::helpers::{
+    bytecode_prefix_op_sized, bytecode_prefix_precompile_call, handle_main_code, is_sized_op,
+    main_code, main_code_step_index,
+};
+use bus_mapping::{circuit_input_builder::CircuitsUsage, precompile::PrecompileCalls};
+use eth_types::{evm_types::OpcodeId, Bytecode};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use strum::IntoEnumIterator;
+use zkevm_circuits::{
+    evm_circuit::{
+        step::{ExecutionState, ResponsibleOp},
+        EvmCircuitParams,
+    },
+    super_circuit::capacity::SuperCircuitRowCounter,
+};
+
+/// Size added to the dynamic size of an opcode to measure its cost per byte.
+const SIZE_INCREMENT: usize = 0x1000;
+
+/// Tolerance when comparing the costs per byte with the baseline.
+const EPSILON: f64 = 1e-9;
+
+/// Rows used in each circuit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Cost {
+    /// Rows of the EVM circuit
+    pub(crate) evm_rows: usize,
+    /// Read-write operations, one row each in the State circuit
+    pub(crate) rws: usize,
+    /// Rows of the Copy circuit
+    pub(crate) copy_rows: usize,
+    /// Rows of the Keccak circuit
+    pub(crate) keccak_rows: usize,
+}
+
+impl Cost {
+    /// Cost of `usage` on top of `baseline`
+    fn from_usage(usage: &CircuitsUsage, baseline: &CircuitsUsage) -> Self {
+        Self {
+            evm_rows: usage.evm_rows.saturating_sub(baseline.evm_rows),
+            rws: usage.rws.saturating_sub(baseline.rws),
+            copy_rows: usage.copy_rows.saturating_sub(baseline.copy_rows),
+            keccak_rows: usage.keccak_rows.saturating_sub(baseline.keccak_rows),
+        }
+    }
+
+    fn fields(&self) -> [(&'static str, f64); 4] {
+        [
+            ("evm_rows", self.evm_rows as f64),
+            ("rws", self.rws as f64),
+            ("copy_rows", self.copy_rows as f64),
+            ("keccak_rows", self.keccak_rows as f64),
+        ]
+    }
+}
+
+/// Rows used in each circuit per byte of dynamic size
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CostPerByte {
+    /// Rows of the EVM circuit
+    pub(crate) evm_rows: f64,
+    /// Read-write operations, one row each in the State circuit
+    pub(crate) rws: f64,
+    /// Rows of the Copy circuit
+    pub(crate) copy_rows: f64,
+    /// Rows of the Keccak circuit
+    pub(crate) keccak_rows: f64,
+}
+
+impl CostPerByte {
+    /// Average cost of the bytes between `base` and `large`, which was
+    /// measured with `bytes` more bytes.
+    fn new(base: &Cost, large: &Cost, bytes: usize) -> Self {
+        let per_byte = |base: usize, large: usize| (large as f64 - base as f64) / bytes as f64;
+        Self {
+            evm_rows: per_byte(base.evm_rows, large.evm_rows),
+            rws: per_byte(base.rws, large.rws),
+            copy_rows: per_byte(base.copy_rows, large.copy_rows),
+            keccak_rows: per_byte(base.keccak_rows, large.keccak_rows),
+        }
+    }
+
+    fn fields(&self) -> [(&'static str, f64); 4] {
+        [
+            ("evm_rows", self.evm_rows),
+            ("rws", self.rws),
+            ("copy_rows", self.copy_rows),
+            ("keccak_rows", self.keccak_rows),
+        ]
+    }
+}
+
+/// Cost of an opcode, including the calls it makes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OpcodeCost {
+    /// Execution state of the opcode
+    pub(crate) execution_state: String,
+    /// Gas cost of the opcode with an empty dynamic size
+    pub(crate) gas_cost: u64,
+    /// Cost with an empty dynamic size
+    pub(crate) base: Cost,
+    /// Cost per byte of dynamic size, for the opcodes that have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) per_byte: Option<CostPerByte>,
+}
+
+/// Cost of a `STATICCALL` to a precompile
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PrecompileCost {
+    /// Execution state of the precompile
+    pub(crate) execution_state: String,
+    /// Size of the input of the call measured for the base cost
+    pub(crate) input_size: usize,
+    /// Cost with `input_size` bytes of input
+    pub(crate) base: Cost,
+    /// Cost per byte of input above `input_size`, for the precompiles with a
+    /// dynamic input size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) per_byte: Option<CostPerByte>,
+}
+
+/// Cost model of the circuits, keyed by the names of the execution states,
+/// opcodes and precompiles.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CostModel {
+    /// Height of each execution state in the EVM circuit
+    pub(crate) execution_states: BTreeMap<String, usize>,
+    /// Cost of each opcode
+    pub(crate) opcodes: BTreeMap<String, OpcodeCost>,
+    /// Cost of each precompile
+    pub(crate) precompiles: BTreeMap<String, PrecompileCost>,
+}
+
+/// Run the opcode after `prefix` in the main code, and measure what it adds to
+/// the circuits compared to the same code with a STOP in its place.  Return
+/// also the execution state of the opcode and its gas cost.
+fn measure_opcode(prefix: &Bytecode, opcode: OpcodeId) -> (Cost, ExecutionState, u64) {
+    let counter = SuperCircuitRowCounter::default();
+    let (baseline_code, _) = main_code(prefix, None);
+    let (_, baseline_builder) = handle_main_code(baseline_code);
+    let baseline = baseline_builder.circuits_usage(&counter).unwrap();
+
+    let (code, opcode_pc) = main_code(prefix, Some(opcode));
+    let (block, builder) = handle_main_code(code);
+    let usage = builder.circuits_usage(&counter).unwrap();
+    let step_index = main_code_step_index(&builder, opcode_pc);
+    let state = ExecutionState::from(&builder.block.txs[0].steps()[step_index]);
+    // Substract 1 to step_index to remove the `BeginTx` step, which doesn't appear
+    // in the geth trace.
+    let gas_cost = block.geth_traces[0].struct_logs[step_index - 1].gas_cost.0;
+
+    (Cost::from_usage(&usage, &baseline), state, gas_cost)
+}
+
+/// Input size of the precompile for its base cost, and the size added to
+/// measure its cost per byte when its input size is dynamic.  The input is
+/// all zeros, which is valid for every precompile at these sizes.
+fn precompile_input_sizes(precompile: PrecompileCalls) -> (usize, Option<usize>) {
+    match precompile {
+        PrecompileCalls::ECRecover => (128, None),
+        PrecompileCalls::Sha256 | PrecompileCalls::Ripemd160 | PrecompileCalls::Identity => {
+            (0, Some(SIZE_INCREMENT))
+        }
+        PrecompileCalls::Modexp => (96, None),
+        PrecompileCalls::Bn128Add => (128, None),
+        PrecompileCalls::Bn128Mul => (96, None),
+        // Pairs of points are 192 bytes long
+        PrecompileCalls::Bn128Pairing => (0, Some(192 * 2)),
+        PrecompileCalls::Blake2F => (213, None),
+    }
+}
+
+impl CostModel {
+    /// Measure the cost model of the circuits.  Opcodes are measured in the
+    /// TestContext of [`crate::helpers::print_circuit_stats_by_states`].
+    pub(crate) fn measure() -> Self {
+        let execution_states = ExecutionState::iter()
+            .filter_map(|state| {
+                let height = state.get_step_height_option(EvmCircuitParams::default())?;
+                Some((format!("{:?}", state), height))
+            })
+            .collect();
+
+        let opcodes = ExecutionState::iter()
+            .filter(|state| {
+                *state != ExecutionState::ErrorInvalidOpcode
+                    && state
+                        .get_step_height_option(EvmCircuitParams::default())
+                        .is_some()
+            })
+            .flat_map(|state| state.responsible_opcodes())
+            .filter_map(|responsible_op| match responsible_op {
+                ResponsibleOp::Op(opcode) => Some(opcode),
+                ResponsibleOp::InvalidStackPtr(..) => None,
+            })
+            .unique()
+            .map(|opcode| {
+                // Error states list the opcodes that lead to them, which here
+                // run in their successful state.
+                let prefix = bytecode_prefix_op_sized(opcode, 0);
+                let (base, state, gas_cost) = measure_opcode(&prefix, opcode);
+                let per_byte = is_sized_op(opcode).then(|| {
+                    let prefix = bytecode_prefix_op_sized(opcode, SIZE_INCREMENT);
+                    let (large, ..) = measure_opcode(&prefix, opcode);
+                    CostPerByte::new(&base, &large, SIZE_INCREMENT)
+                });
+                let cost = OpcodeCost {
+                    execution_state: format!("{:?}", state),
+                    gas_cost,
+                    base,
+                    per_byte,
+                };
+                (format!("{:?}", opcode), cost)
+            })
+            .collect();
+
+        let precompiles = PrecompileCalls::iter()
+            .map(|precompile| {
+                let measure = |input_size| {
+                    let prefix = bytecode_prefix_precompile_call(precompile, input_size);
+                    measure_opcode(&prefix, OpcodeId::STATICCALL).0
+                };
+                let (input_size, size_increment) = precompile_input_sizes(precompile);
+                let base = measure(input_size);
+                let per_byte = size_increment.map(|increment| {
+                    CostPerByte::new(&base, &measure(input_size + increment), increment)
+                });
+                let cost = PrecompileCost {
+                    execution_state: format!("{:?}", ExecutionState::from(precompile)),
+                    input_size,
+                    base,
+                    per_byte,
+                };
+                (format!("{:?}", precompile), cost)
+            })
+            .collect();
+
+        Self {
+            execution_states,
+            opcodes,
+            precompiles,
+        }
+    }
+
+    /// Whether the model has no execution state, opcode nor precompile
+    pub(crate) fn is_empty(&self) -> bool {
+        self.execution_states.is_empty() && self.opcodes.is_empty() && self.precompiles.is_empty()
+    }
+
+    /// Costs of every entry, by `"<kind> <name>"` and field
+    fn fields(&self) -> BTreeMap<String, Vec<(String, f64)>> {
+        let cost_fields = |base: &Cost, per_byte: &Option<CostPerByte>| {
+            let base = base
+                .fields()
+                .into_iter()
+                .map(|(field, value)| (format!("base.{}", field), value));
+            let per_byte = per_byte
+                .iter()
+                .flat_map(|per_byte| per_byte.fields())
+                .map(|(field, value)| (format!("per_byte.{}", field), value));
+            base.chain(per_byte).collect_vec()
+        };
+
+        let states = self.execution_states.iter().map(|(state, height)| {
+            let fields = vec![("evm_rows".to_string(), *height as f64)];
+            (format!("state {}", state), fields)
+        });
+        let opcodes = self.opcodes.iter().map(|(opcode, cost)| {
+            let fields = cost_fields(&cost.base, &cost.per_byte);
+            (format!("opcode {}", opcode), fields)
+        });
+        let precompiles = self.precompiles.iter().map(|(precompile, cost)| {
+            let fields = cost_fields(&cost.base, &cost.per_byte);
+            (format!("precompile {}", precompile), fields)
+        });
+        states.chain(opcodes).chain(precompiles).collect()
+    }
+
+    /// Compare the costs with the ones of a `baseline` model.
+    pub(crate) fn diff(&self, baseline: &Self) -> CostModelDiff {
+        let current = self.fields();
+        let baseline = baseline.fields();
+
+        let mut diff = CostModelDiff::default();
+        for (entry, fields) in &current {
+            let Some(baseline_fields) = baseline.get(entry) else {
+                diff.added.push(entry.clone());
+                continue;
+            };
+            let baseline_fields: BTreeMap<_, _> = baseline_fields.iter().cloned().collect();
+            for (field, value) in fields {
+                let baseline_value = baseline_fields.get(field).copied().unwrap_or_default();
+                if (value - baseline_value).abs() > EPSILON {
+                    diff.changes.push(CostChange {
+                        entry: entry.clone(),
+                        field: field.clone(),
+                        baseline: baseline_value,
+                        current: *value,
+                    });
+                }
+            }
+        }
+        diff.removed = baseline
+            .keys()
+            .filter(|entry| !current.contains_key(*entry))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+/// A cost that differs from the baseline
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CostChange {
+    /// Execution state, opcode or precompile
+    pub(crate) entry: String,
+    /// Field of the cost
+    pub(crate) field: String,
+    /// Value in the baseline
+    pub(crate) baseline: f64,
+    /// Value measured now
+    pub(crate) current: f64,
+}
+
+impl CostChange {
+    /// Whether the cost increased
+    pub(crate) fn is_regression(&self) -> bool {
+        self.current > self.baseline
+    }
+}
+
+/// Differences between a cost model and its baseline
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct CostModelDiff {
+    /// Costs that changed
+    pub(crate) changes: Vec<CostChange>,
+    /// Entries missing in the baseline
+    pub(crate) added: Vec<String>,
+    /// Entries of the baseline that are not measured anymore
+    pub(crate) removed: Vec<String>,
+}
+
+impl CostModelDiff {
+    /// Costs that increased
+    pub(crate) fn regressions(&self) -> impl Iterator<Item = &CostChange> {
+        self.changes.iter().filter(|change| change.is_regression())
+    }
+}
+
+impl fmt::Display for CostModelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let kind = if change.is_regression() {
+                "regression"
+            } else {
+                "improvement"
+            };
+            writeln!(
+                f,
+                "{}: {} {} {} -> {}",
+                kind, change.entry, change.field, change.baseline, change.current
+            )?;
+        }
+        for entry in &self.added {
+            writeln!(f, "added: {}", entry)?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "removed: {}", entry)?;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
 use std::cmp::Ordering;
 
 use bus_mapping::{
-    circuit_input_builder::{self, CircuitsParams, ExecState},
+    circuit_input_builder::{self, CircuitInputBuilder, CircuitsParams, ExecState},
     mock::BlockData,
+    precompile::PrecompileCalls,
 };
 use cli_table::{
     format::{Justify, Separator},
@@ -11,14 +12,42 @@ use cli_table::{
 use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Address, Bytecode, ToWord};
 use mock::{eth, test_ctx::TestContext, MOCK_ACCOUNTS};
 use strum::IntoEnumIterator;
-use zkevm_circuits::evm_circuit::step::ExecutionState;
+use zkevm_circuits::evm_circuit::{step::ExecutionState, EvmCircuitParams};
 
 /// Generate the prefix bytecode to trigger a big amount of rw operations
 pub(crate) fn bytecode_prefix_op_big_rws(opcode: OpcodeId) -> Bytecode {
+    bytecode_prefix_op_sized(opcode, 0x1000)
+}
+
+/// Whether the cost of the opcode depends on a size argument, set by
+/// [`bytecode_prefix_op_sized`]
+pub(crate) fn is_sized_op(opcode: OpcodeId) -> bool {
+    matches!(
+        opcode,
+        OpcodeId::CODECOPY
+            | OpcodeId::CALLDATACOPY
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::LOG0
+            | OpcodeId::LOG1
+            | OpcodeId::LOG2
+            | OpcodeId::LOG3
+            | OpcodeId::LOG4
+            | OpcodeId::SHA3
+            | OpcodeId::RETURN
+            | OpcodeId::REVERT
+            | OpcodeId::EXTCODECOPY
+            | OpcodeId::CREATE
+            | OpcodeId::CREATE2
+    )
+}
+
+/// Generate the prefix bytecode that sets up the arguments of the opcode, with
+/// `size` bytes of memory/code/data for the opcodes that take a size.
+pub(crate) fn bytecode_prefix_op_sized(opcode: OpcodeId, size: usize) -> Bytecode {
     match opcode {
         OpcodeId::CODECOPY | OpcodeId::CALLDATACOPY => {
             bytecode! {
-                PUSH4(0x1000) // size
+                PUSH4(size) // size
                 PUSH2(0x00) // offset
                 PUSH2(0x00) // destOffset
             }
@@ -33,7 +62,7 @@ pub(crate) fn bytecode_prefix_op_big_rws(opcode: OpcodeId) -> Bytecode {
                 PUSH32(MOCK_ACCOUNTS[3].to_word())
                 PUSH32(0x1_0000) // gas
                 CALL
-                PUSH4(0x1000) // size
+                PUSH4(size) // size
                 PUSH2(0x00) // offset
                 PUSH2(0x00) // destOffset
             }
@@ -46,15 +75,26 @@ pub(crate) fn bytecode_prefix_op_big_rws(opcode: OpcodeId) -> Bytecode {
         | OpcodeId::SHA3
         | OpcodeId::RETURN
         | OpcodeId::REVERT => bytecode! {
-            PUSH4(0x1000) // size
+            PUSH4(size) // size
             PUSH2(0x00) // offset
         },
         OpcodeId::EXTCODECOPY => bytecode! {
-            PUSH4(0x1000) // size
+            PUSH4(size) // size
             PUSH2(0x00) // offset
             PUSH2(0x00) // destOffset
             PUSH2(0x00) // address
         },
+        OpcodeId::CREATE => bytecode! {
+            PUSH4(size) // size
+            PUSH2(0x00) // offset
+            PUSH1(0x00) // value
+        },
+        OpcodeId::CREATE2 => bytecode! {
+            PUSH1(0x00) // salt
+            PUSH4(size) // size
+            PUSH2(0x00) // offset
+            PUSH1(0x00) // value
+        },
         _ => bytecode! {
             PUSH2(0x40)
             PUSH2(0x50)
@@ -62,6 +102,121 @@ pub(crate) fn bytecode_prefix_op_big_rws(opcode: OpcodeId) -> Bytecode {
     }
 }
 
+/// Generate the bytecode that calls the precompile with `input_size` bytes of
+/// (zero) input, to be followed by a `STATICCALL`.
+pub(crate) fn bytecode_prefix_precompile_call(
+    precompile: PrecompileCalls,
+    input_size: usize,
+) -> Bytecode {
+    bytecode! {
+        PUSH1(0x20) // retLength
+        PUSH1(0x00) // retOffset
+        PUSH4(input_size) // argsLength
+        PUSH1(0x00) // argsOffset
+        PUSH32(Address::from(precompile).to_word())
+        PUSH32(0x1_0000) // gas
+    }
+}
+
+/// Code of the main contract, with `opcode` at the returned pc after its
+/// `prefix`.  Without opcode a STOP takes its place, so that the code keeps
+/// the same length.
+pub(crate) fn main_code(prefix: &Bytecode, opcode: Option<OpcodeId>) -> (Bytecode, usize) {
+    let mut code = bytecode! {
+        PUSH2(0x00)
+        EXTCODESIZE // Warm up 0x0 address
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH1(0x00)
+        PUSH2(0x00)
+        PUSH2(0x10)
+        PUSH2(0x20)
+        PUSH2(0x30)
+    };
+    code.append(prefix);
+    code.write_op(opcode.unwrap_or(OpcodeId::STOP));
+    let opcode_pc = code.code.len() - 1;
+    code.op_stop();
+    (code, opcode_pc)
+}
+
+/// Run the main code in the TestContext described in
+/// [`print_circuit_stats_by_states`], and return the block with the builder
+/// that handled it.
+pub(crate) fn handle_main_code(code: Bytecode) -> (GethData, CircuitInputBuilder) {
+    let smallcode = bytecode! {
+        PUSH4(0x1000) // size
+        PUSH2(0x00) // offset
+        RETURN
+    };
+    let proxy_code = bytecode! {
+        PUSH2(0x1000) // retLength
+        PUSH1(0x00) // retOffset
+        PUSH1(0x00) // argsLength
+        PUSH1(0x00) // argsOffset
+        PUSH1(0x00) // value
+        PUSH32(MOCK_ACCOUNTS[2].to_word())
+        PUSH32(800_000) // gas
+        CALL
+        STOP
+    };
+    let block: GethData = TestContext::<10, 1>::new(
+        None,
+        |accs| {
+            accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+            accs[1]
+                .address(MOCK_ACCOUNTS[1])
+                .balance(eth(10))
+                .code(proxy_code);
+            accs[2]
+                .address(MOCK_ACCOUNTS[2])
+                .balance(eth(10))
+                .code(code.clone());
+            accs[3].address(MOCK_ACCOUNTS[3]).code(smallcode);
+            accs[4].address(Address::zero()).balance(eth(10)).code(code);
+        },
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[0].address)
+                .to(accs[1].address)
+                .input(vec![1, 2, 3, 4, 5, 6, 7].into());
+        },
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+    let mut builder = BlockData::new_from_geth_data_with_params(
+        block.clone(),
+        CircuitsParams {
+            max_rws: 16_000,
+            max_copy_rows: 8_000,
+            ..CircuitsParams::default()
+        },
+    )
+    .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    (block, builder)
+}
+
+/// Index of the step that executed the opcode at `opcode_pc` of the main code,
+/// found by filtering on second call (because we run it via proxy).
+pub(crate) fn main_code_step_index(builder: &CircuitInputBuilder, opcode_pc: usize) -> usize {
+    builder.block.txs[0]
+        .steps()
+        .iter()
+        .position(|s| s.call_index == 1 && s.pc.0 == opcode_pc)
+        .unwrap()
+}
+
 /// Wrap f64 for both sorting and pretty formatting
 #[derive(PartialEq, PartialOrd)]
 struct PrettyF64(f64);
@@ -117,27 +272,11 @@ pub(crate) fn print_circuit_stats_by_states(
 ) {
     let mut implemented_states = Vec::new();
     for state in ExecutionState::iter() {
-        let height = state.get_step_height_option(false);
+        let height = state.get_step_height_option(EvmCircuitParams::default());
         if height.is_some() {
             implemented_states.push(state);
         }
     }
-    let smallcode = bytecode! {
-        PUSH4(0x1000) // size
-        PUSH2(0x00) // offset
-        RETURN
-    };
-    let proxy_code = bytecode! {
-        PUSH2(0x1000) // retLength
-        PUSH1(0x00) // retOffset
-        PUSH1(0x00) // argsLength
-        PUSH1(0x00) // argsOffset
-        PUSH1(0x00) // value
-        PUSH32(MOCK_ACCOUNTS[2].to_word())
-        PUSH32(800_000) // gas
-        CALL
-        STOP
-    };
 
     let mut rows = vec![];
     for state in implemented_states {
@@ -146,75 +285,10 @@ pub(crate) fn print_circuit_stats_by_states(
         }
         for responsible_op in state.responsible_opcodes() {
             let opcode = responsible_op.opcode();
-            let mut code = bytecode! {
-                PUSH2(0x00)
-                EXTCODESIZE // Warm up 0x0 address
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH1(0x00)
-                PUSH2(0x00)
-                PUSH2(0x10)
-                PUSH2(0x20)
-                PUSH2(0x30)
-            };
-            let bytecode_prefix_op = fn_bytecode_prefix_op(opcode);
-            code.append(&bytecode_prefix_op);
-            code.write_op(opcode);
-            let opcode_pc = code.code.len() - 1;
-            // let opcode_step_index = (proxy_code.num_opcodes - 1 + code.num_opcodes) - 1;
-            code.op_stop();
-            let block: GethData = TestContext::<10, 1>::new(
-                None,
-                |accs| {
-                    accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
-                    accs[1]
-                        .address(MOCK_ACCOUNTS[1])
-                        .balance(eth(10))
-                        .code(proxy_code.clone());
-                    accs[2]
-                        .address(MOCK_ACCOUNTS[2])
-                        .balance(eth(10))
-                        .code(code.clone());
-                    accs[3].address(MOCK_ACCOUNTS[3]).code(smallcode.clone());
-                    accs[4].address(Address::zero()).balance(eth(10)).code(code);
-                },
-                |mut txs, accs| {
-                    txs[0]
-                        .from(accs[0].address)
-                        .to(accs[1].address)
-                        .input(vec![1, 2, 3, 4, 5, 6, 7].into());
-                },
-                |block, _tx| block.number(0xcafeu64),
-            )
-            .unwrap()
-            .into();
-            let mut builder = BlockData::new_from_geth_data_with_params(
-                block.clone(),
-                CircuitsParams {
-                    max_rws: 16_000,
-                    max_copy_rows: 8_000,
-                    ..CircuitsParams::default()
-                },
-            )
-            .new_circuit_input_builder();
-            builder
-                .handle_block(&block.eth_block, &block.geth_traces)
-                .unwrap();
-            // Find the step that executed our opcode by filtering on second call (because
-            // we run it via proxy) and the PC where we wrote the opcode.
-            let (step_index, step) = builder.block.txs[0]
-                .steps()
-                .iter()
-                .enumerate()
-                .find(|(_, s)| s.call_index == 1 && s.pc.0 == opcode_pc)
-                .unwrap();
+            let (code, opcode_pc) = main_code(&fn_bytecode_prefix_op(opcode), Some(opcode));
+            let (block, builder) = handle_main_code(code);
+            let step_index = main_code_step_index(&builder, opcode_pc);
+            let step = &builder.block.txs[0].steps()[step_index];
             assert_eq!(ExecState::Op(opcode), step.exec_state);
             let height = fn_height(&builder.block, state, step_index);
 
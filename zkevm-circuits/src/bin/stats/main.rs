@@ -4,16 +4,27 @@ use halo2_proofs::{
     halo2curves::bn256::Fr,
     plonk::{Circuit, ConstraintSystem},
 };
+mod cost_model;
 mod helpers;
+use cost_model::CostModel;
 use helpers::{bytecode_prefix_op_big_rws, print_circuit_stats_by_states};
 use itertools::Itertools;
 use mock::MOCK_ACCOUNTS;
-use std::env;
+use std::{env, fs, iter, process};
+use strum::IntoEnumIterator;
 use zkevm_circuits::evm_circuit::{
-    param::{LOOKUP_CONFIG, N_BYTE_LOOKUPS, N_COPY_COLUMNS, N_PHASE1_COLUMNS, N_PHASE2_COLUMNS},
+    param::{
+        EvmLayout, LOOKUP_CONFIG, N_BYTE_LOOKUPS, N_COPY_COLUMNS, N_PHASE1_COLUMNS,
+        N_PHASE2_COLUMNS,
+    },
     step::ExecutionState,
-    EvmCircuit,
+    EvmCircuit, EvmCircuitParams,
 };
+
+/// Cost model committed as the baseline of the `check` mode
+const COST_MODEL_BASELINE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/src/bin/stats/cost_model.json");
+
 fn main() {
     let args: Vec<String> = env::args().collect();
 
@@ -22,23 +33,52 @@ fn main() {
         "state" => state_states_stats(),
         "copy" => copy_states_stats(),
         "exec" => get_exec_steps_occupancy(),
+        "layouts" => evm_layouts_stats(),
+        "json" => cost_model_json(),
+        "check" => cost_model_check(args.get(2).map_or(COST_MODEL_BASELINE, String::as_str)),
         &_ => unreachable!("Unsupported arg"),
     }
 }
 
+/// Prints the cost model of the circuits as JSON.
+fn cost_model_json() {
+    let cost_model = CostModel::measure();
+    println!("{}", serde_json::to_string_pretty(&cost_model).unwrap());
+}
+
+/// Compares the cost model of the circuits with the baseline at `path`, and
+/// exits with an error if the baseline is empty or misses some entries, or if
+/// any cost increased.
+fn cost_model_check(path: &str) {
+    let baseline = fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("failed to read the baseline {}: {}", path, err));
+    let baseline: CostModel = serde_json::from_str(&baseline).unwrap();
+    if baseline.is_empty() {
+        eprintln!(
+            "the baseline {} is empty, generate it with `make stats_cost_model`",
+            path
+        );
+        process::exit(1);
+    }
+    let diff = CostModel::measure().diff(&baseline);
+    print!("{}", diff);
+
+    let regressions = diff.regressions().count();
+    if regressions > 0 || !diff.added.is_empty() {
+        eprintln!(
+            "{} costs regressed and {} entries are missing from {}, regenerate it with `make stats_cost_model` if expected",
+            regressions,
+            diff.added.len(),
+            path
+        );
+        process::exit(1);
+    }
+}
+
 /// Prints the stats of EVM circuit per execution state.
 fn evm_states_stats() {
     print_circuit_stats_by_states(
-        |state| {
-            // TODO: Enable CREATE/CREATE2 once they are supported
-            !matches!(
-                state,
-                ExecutionState::ErrorInvalidOpcode
-                    | ExecutionState::CREATE
-                    | ExecutionState::CREATE2
-                    | ExecutionState::SELFDESTRUCT
-            )
-        },
+        |state| state != ExecutionState::ErrorInvalidOpcode,
         |opcode| match opcode {
             OpcodeId::RETURNDATACOPY => {
                 bytecode! {
@@ -60,23 +100,80 @@ fn evm_states_stats() {
                 PUSH2(0x50)
             },
         },
-        |_, state, _| state.get_step_height_option(false).unwrap(),
+        |_, state, _| {
+            state
+                .get_step_height_option(EvmCircuitParams::default())
+                .unwrap()
+        },
     );
 }
 
+/// Prints the columns of each layout of the EVM circuit, and the height of
+/// each execution state with them.
+fn evm_layouts_stats() {
+    let layouts = EvmLayout::iter().collect_vec();
+
+    let columns = layouts
+        .iter()
+        .map(|layout| {
+            vec![
+                format!("{:?}", layout),
+                format!("{}", layout.step_width()),
+                format!("{}", layout.lookup_columns()),
+                format!("{}", layout.n_phase2_columns()),
+                format!("{}", layout.n_copy_columns()),
+                format!("{}", layout.n_byte_lookups()),
+                format!("{}", layout.n_phase1_columns()),
+                format!("{}", layout.max_step_height()),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    let table = columns.table().title(vec![
+        "layout".cell().bold(true),
+        "width".cell().bold(true),
+        "lookup".cell().bold(true),
+        "phase2".cell().bold(true),
+        "copy".cell().bold(true),
+        "byte_lookup".cell().bold(true),
+        "phase1".cell().bold(true),
+        "max_step_height".cell().bold(true),
+    ]);
+    print_stdout(table).unwrap();
+
+    let heights = ExecutionState::iter()
+        .filter_map(|state| {
+            let heights = layouts
+                .iter()
+                .map(|&layout| {
+                    state.get_step_height_option(EvmCircuitParams {
+                        layout,
+                        ..Default::default()
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(
+                iter::once(format!("{:?}", state))
+                    .chain(heights.iter().map(|height| format!("{}", height)))
+                    .collect_vec(),
+            )
+        })
+        .collect::<Vec<Vec<String>>>();
+    let table = heights.table().title(
+        iter::once("state".cell().bold(true))
+            .chain(
+                layouts
+                    .iter()
+                    .map(|layout| format!("{:?}", layout).cell().bold(true)),
+            )
+            .collect_vec(),
+    );
+    print_stdout(table).unwrap();
+}
+
 /// Prints the stats of State circuit per execution state.
 fn state_states_stats() {
     print_circuit_stats_by_states(
-        |state| {
-            // TODO: Enable CREATE/CREATE2 once they are supported
-            !matches!(
-                state,
-                ExecutionState::ErrorInvalidOpcode
-                    | ExecutionState::CREATE
-                    | ExecutionState::CREATE2
-                    | ExecutionState::SELFDESTRUCT
-            )
-        },
+        |state| state != ExecutionState::ErrorInvalidOpcode,
         bytecode_prefix_op_big_rws,
         |block, _, step_index| {
             let step = &block.txs[0].steps()[step_index];
@@ -107,7 +204,7 @@ fn copy_states_stats() {
             block
                 .copy_events
                 .iter()
-                .map(|c| c.bytes.len() * 2)
+                .map(|c| c.num_steps() * 2)
                 .sum::<usize>()
         },
     );
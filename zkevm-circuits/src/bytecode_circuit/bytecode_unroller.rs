@@ -3,7 +3,7 @@ use crate::{
     util::{get_push_size, keccak},
 };
 use eth_types::{Field, Word};
-use std::vec;
+use std::{collections::HashSet, vec};
 
 /// Public data for the bytecode
 #[derive(Clone, Debug, PartialEq)]
@@ -25,6 +25,11 @@ pub struct UnrolledBytecode<F: Field> {
 /// Get unrolled bytecode from raw bytes
 pub fn unroll<F: Field>(bytes: Vec<u8>) -> UnrolledBytecode<F> {
     let code_hash = keccak(&bytes[..]);
+    unroll_with_codehash(code_hash, bytes)
+}
+
+/// Get unrolled bytecode from raw bytes and their already known code hash
+pub fn unroll_with_codehash<F: Field>(code_hash: Word, bytes: Vec<u8>) -> UnrolledBytecode<F> {
     let mut rows = vec![BytecodeRow::<F> {
         code_hash,
         tag: F::from(BytecodeFieldTag::Header as u64),
@@ -53,3 +58,55 @@ pub fn unroll<F: Field>(bytes: Vec<u8>) -> UnrolledBytecode<F> {
     }
     UnrolledBytecode { bytes, rows }
 }
+
+/// Bytecodes whose code hash has been proven once, referenced by code hash by
+/// the blocks that run them.
+///
+/// The registered bytecodes are assigned to fixed columns of the bytecode
+/// circuit, so they are committed in its verifying key: a verifier checks the
+/// keccak of each registered bytecode once, when it accepts the key, and the
+/// circuit then binds the rows of a registered bytecode to the fixed bytes and
+/// code hash instead of hashing it again in the keccak circuit.
+#[derive(Clone, Debug, Default)]
+pub struct BytecodeRegistry<F: Field> {
+    bytecodes: Vec<UnrolledBytecode<F>>,
+    code_hashes: HashSet<Word>,
+}
+
+impl<F: Field> BytecodeRegistry<F> {
+    /// Register the bytecode, unless its code hash is already registered, and
+    /// return its code hash.
+    pub fn register(&mut self, bytes: Vec<u8>) -> Word {
+        let bytecode = unroll::<F>(bytes);
+        let code_hash = bytecode.rows[0].code_hash;
+        if self.code_hashes.insert(code_hash) {
+            self.bytecodes.push(bytecode);
+        }
+        code_hash
+    }
+
+    /// Whether the bytecode with this code hash is registered
+    pub fn contains(&self, code_hash: &Word) -> bool {
+        self.code_hashes.contains(code_hash)
+    }
+
+    /// Registered bytecodes, in registration order
+    pub fn bytecodes(&self) -> &[UnrolledBytecode<F>] {
+        &self.bytecodes
+    }
+
+    /// Number of rows the registered bytecodes take in the bytecode circuit
+    pub fn num_rows(&self) -> usize {
+        self.bytecodes.iter().map(|b| b.rows.len()).sum()
+    }
+
+    /// Number of registered bytecodes
+    pub fn len(&self) -> usize {
+        self.bytecodes.len()
+    }
+
+    /// Whether no bytecode is registered
+    pub fn is_empty(&self) -> bool {
+        self.bytecodes.is_empty()
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
-    evm_circuit::util::{
-        and,
-        constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
-        not, or, rlc, select,
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::{
+            and,
+            constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+            not, or, rlc, select,
+        },
     },
     table::{BytecodeFieldTag, BytecodeTable, KeccakTable, LookupTable},
     util::{get_push_size, Challenges, Expr, SubCircuit, SubCircuitConfig},
@@ -12,7 +15,7 @@ use bus_mapping::state_db::EMPTY_CODE_HASH_LE;
 use eth_types::{Field, ToLittleEndian};
 use gadgets::is_zero::{IsZeroChip, IsZeroInstruction};
 use halo2_proofs::{
-    circuit::{Layouter, Region, Value},
+    circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{
         Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
     },
@@ -22,7 +25,7 @@ use log::trace;
 use std::vec;
 
 use super::{
-    bytecode_unroller::{unroll, UnrolledBytecode},
+    bytecode_unroller::{unroll_with_codehash, BytecodeRegistry, UnrolledBytecode},
     param::PUSH_TABLE_WIDTH,
 };
 
@@ -78,6 +81,13 @@ pub struct BytecodeCircuitConfig<F> {
     push_data_left_is_zero: IsZeroChip<F>,
     index_length_diff_is_zero: IsZeroChip<F>,
     push_table: [Column<Fixed>; PUSH_TABLE_WIDTH],
+    q_registry: Column<Fixed>,
+    registry_tag: Column<Fixed>,
+    registry_value: Column<Fixed>,
+    q_registry_hash: Column<Fixed>,
+    registry_hash_continue: Column<Fixed>,
+    registry_hash_byte: Column<Fixed>,
+    registry_hash_rlc: Column<Advice>,
     // External tables
     pub(crate) keccak_table: KeccakTable,
 }
@@ -115,6 +125,18 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
         let push_data_left_inv = meta.advice_column();
         let index_length_diff_inv = meta.advice_column();
         let push_table = array_init::array_init(|_| meta.fixed_column());
+        let q_registry = meta.fixed_column();
+        let registry_tag = meta.fixed_column();
+        let registry_value = meta.fixed_column();
+        let q_registry_hash = meta.fixed_column();
+        let registry_hash_continue = meta.fixed_column();
+        let registry_hash_byte = meta.fixed_column();
+        let registry_hash_rlc = meta.advice_column_in(SecondPhase);
+
+        // The code hash of a registered bytecode is copied from the registry
+        // code hashes.
+        meta.enable_equality(bytecode_table.code_hash);
+        meta.enable_equality(registry_hash_rlc);
 
         // annotate columns
         bytecode_table.annotate_columns(meta);
@@ -429,9 +451,12 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
         meta.lookup_any(
             "keccak256_table_lookup(cur.value_rlc, cur.length, cur.hash)",
             |meta| {
+                // Registered bytecodes are bound to their code hash by the
+                // registry instead.
                 let enable = and::expr(vec![
                     meta.query_fixed(q_enable, Rotation::cur()),
                     not::expr(meta.query_fixed(q_last, Rotation::cur())),
+                    not::expr(meta.query_fixed(q_registry, Rotation::cur())),
                     is_byte_to_header(meta),
                 ]);
 
@@ -453,6 +478,44 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
             },
         );
 
+        // When q_registry ->
+        // assert cur.tag == registry_tag
+        // assert cur.value == registry_value
+        meta.create_gate("Registered bytecode row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "cur.tag == registry_tag",
+                meta.query_advice(bytecode_table.tag, Rotation::cur()),
+                meta.query_fixed(registry_tag, Rotation::cur()),
+            );
+
+            cb.require_equal(
+                "cur.value == registry_value",
+                meta.query_advice(bytecode_table.value, Rotation::cur()),
+                meta.query_fixed(registry_value, Rotation::cur()),
+            );
+
+            cb.gate(meta.query_fixed(q_registry, Rotation::cur()))
+        });
+
+        // When q_registry_hash ->
+        // assert cur.hash_rlc == cur.hash_byte + (cur.continue ? next.hash_rlc * randomness : 0)
+        meta.create_gate("Registry code hash row", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            cb.require_equal(
+                "cur.hash_rlc == cur.hash_byte + cur.continue * next.hash_rlc * randomness",
+                meta.query_advice(registry_hash_rlc, Rotation::cur()),
+                meta.query_fixed(registry_hash_byte, Rotation::cur())
+                    + meta.query_fixed(registry_hash_continue, Rotation::cur())
+                        * meta.query_advice(registry_hash_rlc, Rotation::next())
+                        * challenges.evm_word(),
+            );
+
+            cb.gate(meta.query_fixed(q_registry_hash, Rotation::cur()))
+        });
+
         let push_data_left_is_zero = IsZeroChip::construct(push_data_left_is_zero);
         let index_length_diff_is_zero = IsZeroChip::construct(index_length_diff_is_zero);
 
@@ -469,6 +532,13 @@ impl<F: Field> SubCircuitConfig<F> for BytecodeCircuitConfig<F> {
             push_data_left_is_zero,
             index_length_diff_is_zero,
             push_table,
+            q_registry,
+            registry_tag,
+            registry_value,
+            q_registry_hash,
+            registry_hash_continue,
+            registry_hash_byte,
+            registry_hash_rlc,
             keccak_table,
         }
     }
@@ -480,6 +550,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         layouter: &mut impl Layouter<F>,
         size: usize,
         witness: &[UnrolledBytecode<F>],
+        registry: &BytecodeRegistry<F>,
         overwrite: &UnrolledBytecode<F>,
         challenges: &Challenges<Value<F>>,
         fail_fast: bool,
@@ -495,6 +566,8 @@ impl<F: Field> BytecodeCircuitConfig<F> {
             last_row_offset
         );
 
+        let registry_hashes = self.assign_registry_hashes(layouter, registry, challenges)?;
+
         layouter.assign_region(
             || "assign bytecode",
             |mut region| {
@@ -502,6 +575,41 @@ impl<F: Field> BytecodeCircuitConfig<F> {
                 self.annotate_circuit(&mut region);
 
                 let mut offset = 0;
+                // Registered bytecodes go first, at the rows fixed by the registry
+                for (bytecode, code_hash) in registry.bytecodes().iter().zip(&registry_hashes) {
+                    if offset + bytecode.rows.len() > last_row_offset {
+                        log::error!(
+                            "Bytecode Circuit: registered bytecodes exceed last_row_offset={}",
+                            last_row_offset
+                        );
+                        return Err(Error::Synthesis);
+                    }
+                    for (idx, row) in bytecode.rows.iter().enumerate() {
+                        for (name, column, value) in [
+                            ("q_registry", self.q_registry, F::ONE),
+                            ("registry_tag", self.registry_tag, row.tag),
+                            ("registry_value", self.registry_value, row.value),
+                        ] {
+                            region.assign_fixed(
+                                || format!("assign {} {}", name, offset + idx),
+                                column,
+                                offset + idx,
+                                || Value::known(value),
+                            )?;
+                        }
+                    }
+                    let header_code_hash = self.assign_bytecode(
+                        &mut region,
+                        bytecode,
+                        challenges,
+                        &mut offset,
+                        last_row_offset,
+                        fail_fast,
+                    )?;
+                    if let Some(header_code_hash) = header_code_hash {
+                        region.constrain_equal(header_code_hash.cell(), code_hash.cell())?;
+                    }
+                }
                 for bytecode in witness.iter() {
                     self.assign_bytecode(
                         &mut region,
@@ -568,6 +676,69 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         )
     }
 
+    /// Assign the code hashes of the registered bytecodes from their fixed
+    /// bytes, and return the cells of their RLC.
+    fn assign_registry_hashes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        registry: &BytecodeRegistry<F>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "assign registry code hashes",
+            |mut region| {
+                let mut code_hashes = Vec::with_capacity(registry.len());
+                for (idx, bytecode) in registry.bytecodes().iter().enumerate() {
+                    let bytes = bytecode.rows[0].code_hash.to_le_bytes();
+                    // The RLC is accumulated from the last byte, so that the
+                    // row of the first byte holds the RLC of the whole hash.
+                    let mut hash_rlc = Value::known(F::ZERO);
+                    let mut code_hash = None;
+                    for (i, byte) in bytes.iter().enumerate().rev() {
+                        let offset = idx * N_BYTES_WORD + i;
+                        hash_rlc =
+                            hash_rlc
+                                .zip(challenges.evm_word())
+                                .map(|(hash_rlc, challenge)| {
+                                    hash_rlc * challenge + F::from(*byte as u64)
+                                });
+                        for (name, column, value) in [
+                            ("q_registry_hash", self.q_registry_hash, F::ONE),
+                            (
+                                "registry_hash_continue",
+                                self.registry_hash_continue,
+                                F::from((i + 1 < N_BYTES_WORD).into()),
+                            ),
+                            (
+                                "registry_hash_byte",
+                                self.registry_hash_byte,
+                                F::from(*byte as u64),
+                            ),
+                        ] {
+                            region.assign_fixed(
+                                || format!("assign {} {}", name, offset),
+                                column,
+                                offset,
+                                || Value::known(value),
+                            )?;
+                        }
+                        let cell = region.assign_advice(
+                            || format!("assign registry_hash_rlc {}", offset),
+                            self.registry_hash_rlc,
+                            offset,
+                            || hash_rlc,
+                        )?;
+                        code_hash = Some(cell);
+                    }
+                    code_hashes.push(code_hash.expect("a code hash has 32 bytes"));
+                }
+                Ok(code_hashes)
+            },
+        )
+    }
+
+    /// Assign the rows of the bytecode, and return the code hash cell of its
+    /// header row if it fits.
     fn assign_bytecode(
         &self,
         region: &mut Region<'_, F>,
@@ -576,7 +747,8 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         offset: &mut usize,
         last_row_offset: usize,
         fail_fast: bool,
-    ) -> Result<(), Error> {
+    ) -> Result<Option<AssignedCell<F, F>>, Error> {
+        let mut header_code_hash = None;
         // Run over all the bytes
         let mut push_data_left = 0;
         let mut next_push_data_left = 0;
@@ -634,7 +806,10 @@ impl<F: Field> BytecodeCircuitConfig<F> {
                     length,
                     push_data_size: F::from(push_data_size),
                 };
-                self.set_row(region, row.clone())?;
+                let assigned_code_hash = self.set_row(region, row.clone())?;
+                if idx == 0 {
+                    header_code_hash = Some(assigned_code_hash);
+                }
 
                 trace!("bytecode.set_row({:?})", row);
 
@@ -646,7 +821,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
             }
         }
 
-        Ok(())
+        Ok(header_code_hash)
     }
 
     fn set_padding_row(
@@ -670,10 +845,16 @@ impl<F: Field> BytecodeCircuitConfig<F> {
                 value_rlc: Value::known(F::ZERO),
                 ..Default::default()
             },
-        )
+        )?;
+        Ok(())
     }
 
-    fn set_row(&self, region: &mut Region<'_, F>, row: BytecodeCircuitRow<F>) -> Result<(), Error> {
+    /// Assign the row, and return its code hash cell.
+    fn set_row(
+        &self,
+        region: &mut Region<'_, F>,
+        row: BytecodeCircuitRow<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
         let offset = row.offset;
         // q_enable
         region.assign_fixed(
@@ -720,17 +901,18 @@ impl<F: Field> BytecodeCircuitConfig<F> {
                 || Value::known(value),
             )?;
         }
-        for (name, column, value) in [
-            ("code_hash", self.bytecode_table.code_hash, row.code_hash),
-            ("value_rlc", self.value_rlc, row.value_rlc),
-        ] {
-            region.assign_advice(
-                || format!("assign {} {}", name, offset),
-                column,
-                offset,
-                || value,
-            )?;
-        }
+        let code_hash = region.assign_advice(
+            || format!("assign code_hash {}", offset),
+            self.bytecode_table.code_hash,
+            offset,
+            || row.code_hash,
+        )?;
+        region.assign_advice(
+            || format!("assign value_rlc {}", offset),
+            self.value_rlc,
+            offset,
+            || row.value_rlc,
+        )?;
 
         self.push_data_left_is_zero.assign(
             region,
@@ -741,7 +923,7 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         self.index_length_diff_is_zero
             .assign(region, offset, Value::known(row.diff()))?;
 
-        Ok(())
+        Ok(code_hash)
     }
 
     fn annotate_circuit(&self, region: &mut Region<F>) {
@@ -759,6 +941,9 @@ impl<F: Field> BytecodeCircuitConfig<F> {
         region.name_column(|| "BYTECODE_push_data_left", self.push_data_left);
         region.name_column(|| "BYTECODE_push_data_size", self.push_data_size);
         region.name_column(|| "BYTECODE_value_rlc", self.value_rlc);
+        region.name_column(|| "BYTECODE_q_registry", self.q_registry);
+        region.name_column(|| "BYTECODE_registry_tag", self.registry_tag);
+        region.name_column(|| "BYTECODE_registry_value", self.registry_value);
     }
 
     /// load fixed tables
@@ -801,6 +986,8 @@ pub struct BytecodeCircuit<F: Field> {
     pub size: usize,
     /// Overwrite
     pub overwrite: UnrolledBytecode<F>,
+    /// Registered bytecodes, bound to their code hash without keccak
+    pub registry: BytecodeRegistry<F>,
 }
 
 impl<F: Field> BytecodeCircuit<F> {
@@ -810,6 +997,7 @@ impl<F: Field> BytecodeCircuit<F> {
             bytecodes,
             size,
             overwrite: Default::default(),
+            registry: Default::default(),
         }
     }
 
@@ -818,10 +1006,30 @@ impl<F: Field> BytecodeCircuit<F> {
         let bytecodes: Vec<UnrolledBytecode<F>> = block
             .bytecodes
             .values()
-            .map(|b| unroll(b.bytes.clone()))
+            .map(|b| unroll_with_codehash(b.hash, b.bytes.clone()))
             .collect();
         Self::new(bytecodes, bytecode_size)
     }
+
+    /// Creates bytecode circuit from block and bytecode_size, referencing the
+    /// bytecodes of `registry` by code hash.  Only the block bytecodes whose
+    /// code hash isn't registered are unrolled and need a keccak.
+    pub fn new_from_block_with_registry(
+        block: &witness::Block<F>,
+        bytecode_size: usize,
+        registry: &BytecodeRegistry<F>,
+    ) -> Self {
+        let bytecodes: Vec<UnrolledBytecode<F>> = block
+            .bytecodes
+            .values()
+            .filter(|b| !registry.contains(&b.hash))
+            .map(|b| unroll_with_codehash(b.hash, b.bytes.clone()))
+            .collect();
+        BytecodeCircuit {
+            registry: registry.clone(),
+            ..Self::new(bytecodes, bytecode_size)
+        }
+    }
 }
 
 impl<F: Field> SubCircuit<F> for BytecodeCircuit<F> {
@@ -862,6 +1070,7 @@ impl<F: Field> SubCircuit<F> for BytecodeCircuit<F> {
             layouter,
             self.size,
             &self.bytecodes,
+            &self.registry,
             &self.overwrite,
             challenges,
             false,
@@ -17,7 +17,11 @@ impl<F: Field> Circuit<F> for BytecodeCircuit<F> {
     type Params = ();
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // The registry is assigned to fixed columns, so it's kept.
+        Self {
+            registry: self.registry.clone(),
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -57,6 +57,17 @@ pub fn test_bytecode_circuit_unrolled<F: Field>(
     assert_eq!(result.is_ok(), success, "proof must be {}", error_msg);
 }
 
+#[test]
+fn bytecode_unrolling_with_codehash() {
+    // Bytecodes of a block are unrolled with the code hash the block carries.
+    let bytecode =
+        crate::witness::Bytecode::new(vec![OpcodeId::PUSH1.as_u8(), 0x01, OpcodeId::STOP.as_u8()]);
+    assert_eq!(
+        unroll_with_codehash::<Fr>(bytecode.hash, bytecode.bytes.clone()),
+        unroll(bytecode.bytes.clone())
+    );
+}
+
 /// Verify unrolling code
 #[test]
 fn bytecode_unrolling() {
@@ -323,3 +334,42 @@ fn bytecode_soundness_bug_1() {
     let prover = MockProver::<Fr>::run(k, &circuit, Vec::new()).unwrap();
     prover.assert_satisfied_par();
 }
+
+/// Test that registered bytecodes skip the keccak and are bound to the
+/// registry
+#[test]
+fn bytecode_registry() {
+    let k = 9;
+    let registered = vec![OpcodeId::PUSH1.as_u8(), 0x01, OpcodeId::STOP.as_u8()];
+    let mut registry = BytecodeRegistry::<Fr>::default();
+    let code_hash = registry.register(registered.clone());
+    assert_eq!(registry.register(registered.clone()), code_hash);
+    assert!(registry.contains(&code_hash));
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.num_rows(), registered.len() + 1);
+
+    // Only the keccak of the unregistered bytecode is loaded
+    let circuit = BytecodeCircuit::<Fr> {
+        registry,
+        ..BytecodeCircuit::new(vec![unroll(vec![OpcodeId::ADD.as_u8()])], 2usize.pow(k))
+    };
+    let prover = MockProver::<Fr>::run(k, &circuit, Vec::new()).unwrap();
+    prover.assert_satisfied_par();
+
+    // Change a byte of the registered bytecode
+    let mut invalid_byte = unroll(registered.clone());
+    invalid_byte.rows[2].value = Fr::from(2u64);
+    // Change the code hash of the registered bytecode
+    let mut invalid_hash = unroll(registered);
+    for row in invalid_hash.rows.iter_mut() {
+        row.code_hash += Word::one();
+    }
+    for overwrite in [invalid_byte, invalid_hash] {
+        let circuit = BytecodeCircuit {
+            overwrite,
+            ..circuit.clone()
+        };
+        let prover = MockProver::<Fr>::run(k, &circuit, Vec::new()).unwrap();
+        assert!(prover.verify_par().is_err());
+    }
+}
@@ -1,6 +1,12 @@
 //! The Copy circuit implements constraints and lookups for read-write steps for
 //! copied bytes while execution opcodes such as CALLDATACOPY, CODECOPY, LOGS,
 //! etc.
+//!
+//! Each step copies a 32-byte word. The bytes of a step outside of the copied
+//! range are masked: a front mask before the first copied byte of a memory
+//! side, and a back mask after the last copied byte. Memory words are looked
+//! up in the RW table once per step, and the bytes of the other data types
+//! one by one.
 pub(crate) mod util;
 
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
@@ -19,18 +25,27 @@ use eth_types::{Field, Word};
 use gadgets::{
     binary_number::BinaryNumberChip,
     less_than::{LtChip, LtConfig, LtInstruction},
-    util::{and, not, or, Expr},
+    util::{and, not, sum, Expr},
 };
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector,
+        VirtualCells,
+    },
     poly::Rotation,
 };
 use itertools::Itertools;
-use std::{collections::HashMap, marker::PhantomData};
+use std::{array, collections::HashMap, marker::PhantomData};
 
 use crate::{
-    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    evm_circuit::{
+        param::N_BYTES_WORD,
+        util::{
+            constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+            rlc,
+        },
+    },
     table::{
         BytecodeFieldTag, BytecodeTable, CopyTable, LookupTable, RwTable, TxContextFieldTag,
         TxTable,
@@ -48,13 +63,21 @@ pub struct CopyCircuitConfig<F> {
     pub q_step: Selector,
     /// Whether the row is the last read-write pair for a copy event.
     pub is_last: Column<Advice>,
-    /// The value copied in this copy step.
-    pub value: Column<Advice>,
-    /// Whether the row is padding.
-    pub is_pad: Column<Advice>,
-    /// In case of a bytecode tag, this denotes whether or not the copied byte
-    /// is an opcode or push data byte.
-    pub is_code: Column<Advice>,
+    /// The bytes of the word copied in this copy step.
+    pub value: [Column<Advice>; N_BYTES_WORD],
+    /// The bytes of the word before the write, for a memory write step.
+    pub value_prev: [Column<Advice>; N_BYTES_WORD],
+    /// Whether each byte of the word is padding.
+    pub is_pad: [Column<Advice>; N_BYTES_WORD],
+    /// Whether each byte of the word is a masked memory byte before the copied
+    /// bytes.
+    pub is_front_mask: [Column<Advice>; N_BYTES_WORD],
+    /// Whether each byte of the word is a masked byte after the copied bytes.
+    pub is_back_mask: [Column<Advice>; N_BYTES_WORD],
+    /// The memory word index of the step.
+    pub word_num: Column<Advice>,
+    /// The RLC accumulator of the bytes copied up to this step.
+    pub value_acc: Column<Advice>,
     /// Whether the row is enabled or not.
     pub q_enable: Column<Fixed>,
     /// The Copy Table contains the columns that are exposed via the lookup
@@ -64,6 +87,9 @@ pub struct CopyCircuitConfig<F> {
     /// Since `src_addr` and `src_addr_end` are u64, 8 bytes are sufficient for
     /// the Lt chip.
     pub addr_lt_addr_end: LtConfig<F, 8>,
+    /// Lt chip to check: src_addr + copied bytes of the step <= src_addr_end,
+    /// i.e. the step has no padding.
+    pub data_end_le_addr_end: LtConfig<F, 8>,
     // External tables
     /// TxTable
     pub tx_table: TxTable,
@@ -89,6 +115,32 @@ pub struct CopyCircuitConfigArgs<F: Field> {
     pub challenges: Challenges<Expression<F>>,
 }
 
+/// `randomness^len` for the number `len` of set flags of `mask`, which must
+/// be set on a prefix of its bytes: `len == m` iff `mask[m - 1] && !mask[m]`.
+fn pow_of_mask_len<F: Field>(
+    mask: &[Expression<F>; N_BYTES_WORD],
+    powers: &[Expression<F>; N_BYTES_WORD],
+) -> Expression<F> {
+    sum::expr((0..=N_BYTES_WORD).map(|len| {
+        let is_set = if len == 0 {
+            1.expr()
+        } else {
+            mask[len - 1].clone()
+        };
+        let is_unset = if len == N_BYTES_WORD {
+            1.expr()
+        } else {
+            not::expr(mask[len].clone())
+        };
+        let power = if len == 0 {
+            1.expr()
+        } else {
+            powers[len - 1].clone()
+        };
+        is_set * is_unset * power
+    }))
+}
+
 impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
     type ConfigArgs = CopyCircuitConfigArgs<F>;
 
@@ -107,9 +159,13 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
     ) -> Self {
         let q_step = meta.complex_selector();
         let is_last = meta.advice_column();
-        let value = meta.advice_column_in(SecondPhase);
-        let is_code = meta.advice_column();
-        let is_pad = meta.advice_column();
+        let value = array::from_fn(|_| meta.advice_column());
+        let value_prev = array::from_fn(|_| meta.advice_column());
+        let is_pad = array::from_fn(|_| meta.advice_column());
+        let is_front_mask = array::from_fn(|_| meta.advice_column());
+        let is_back_mask = array::from_fn(|_| meta.advice_column());
+        let word_num = meta.advice_column();
+        let value_acc = meta.advice_column_in(SecondPhase);
         let is_first = copy_table.is_first;
         let id = copy_table.id;
         let addr = copy_table.addr;
@@ -119,6 +175,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         let rw_counter = copy_table.rw_counter;
         let rwc_inc_left = copy_table.rwc_inc_left;
         let tag = copy_table.tag;
+        let keccak_powers = challenges.keccak_powers_of_randomness::<N_BYTES_WORD>();
 
         // annotate table columns
         tx_table.annotate_columns(meta);
@@ -126,12 +183,64 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         bytecode_table.annotate_columns(meta);
         copy_table.annotate_columns(meta);
 
+        let query_bytes = |meta: &mut VirtualCells<F>,
+                           columns: [Column<Advice>; N_BYTES_WORD],
+                           rotation: Rotation| {
+            columns.map(|column| meta.query_advice(column, rotation))
+        };
+        // Whether each byte of the word carries a copied byte, i.e. it is not
+        // masked.
+        let is_data = move |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            let is_front_mask = query_bytes(meta, is_front_mask, rotation);
+            let is_back_mask = query_bytes(meta, is_back_mask, rotation);
+            array::from_fn::<_, N_BYTES_WORD, _>(|i| {
+                1.expr() - is_front_mask[i].clone() - is_back_mask[i].clone()
+            })
+        };
+        // The number of bytes copied by the step.
+        let data_len = move |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            sum::expr(is_data(meta, rotation))
+        };
+        // `randomness^len` for the number `len` of back masked bytes, which are
+        // set on a prefix of the reversed bytes.
+        let pow_of_back_len = |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            let mut is_back_mask = query_bytes(meta, is_back_mask, rotation);
+            is_back_mask.reverse();
+            pow_of_mask_len(&is_back_mask, &keccak_powers)
+        };
+        // The RLC of the copied bytes of the word, scaled by `randomness^len`
+        // for the number `len` of back masked bytes.  Padded bytes are 0.
+        let masked_word_rlc = |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            let is_data = is_data(meta, rotation);
+            let is_pad = query_bytes(meta, is_pad, rotation);
+            let value = query_bytes(meta, value, rotation);
+            let copied = (0..N_BYTES_WORD)
+                .rev()
+                .map(|i| is_data[i].clone() * not::expr(is_pad[i].clone()) * value[i].clone())
+                .collect::<Vec<_>>();
+            rlc::expr(&copied, challenges.keccak_input())
+        };
+        // The RLC of the memory word, as in the RW table.
+        let word_rlc = |meta: &mut VirtualCells<F>,
+                        columns: [Column<Advice>; N_BYTES_WORD],
+                        rotation: Rotation| {
+            let mut bytes = query_bytes(meta, columns, rotation);
+            bytes.reverse();
+            rlc::expr(&bytes, challenges.evm_word())
+        };
+
         let addr_lt_addr_end = LtChip::configure(
             meta,
             |meta| meta.query_selector(q_step),
             |meta| meta.query_advice(addr, Rotation::cur()),
             |meta| meta.query_advice(src_addr_end, Rotation::cur()),
         );
+        let data_end_le_addr_end = LtChip::configure(
+            meta,
+            |meta| meta.query_selector(q_step),
+            |meta| meta.query_advice(addr, Rotation::cur()) + data_len(meta, Rotation::cur()),
+            |meta| meta.query_advice(src_addr_end, Rotation::cur()) + 1.expr(),
+        );
 
         meta.create_gate("verify row", |meta| {
             let mut cb = BaseConstraintBuilder::default();
@@ -159,6 +268,64 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 ]),
             );
 
+            let is_memory = tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta);
+            let front_mask = query_bytes(meta, is_front_mask, Rotation::cur());
+            let back_mask = query_bytes(meta, is_back_mask, Rotation::cur());
+            let pad = query_bytes(meta, is_pad, Rotation::cur());
+            let data = is_data(meta, Rotation::cur());
+            for i in 0..N_BYTES_WORD {
+                cb.require_boolean("is_front_mask is boolean", front_mask[i].clone());
+                cb.require_boolean("is_back_mask is boolean", back_mask[i].clone());
+                cb.require_boolean("is_pad is boolean", pad[i].clone());
+                cb.require_zero(
+                    "is_front_mask and is_back_mask are exclusive",
+                    and::expr([front_mask[i].clone(), back_mask[i].clone()]),
+                );
+                cb.require_zero(
+                    "is_pad only for copied bytes",
+                    pad[i].clone() * not::expr(data[i].clone()),
+                );
+                if i + 1 < N_BYTES_WORD {
+                    cb.require_zero(
+                        "is_front_mask is set on a prefix of the word",
+                        and::expr([front_mask[i + 1].clone(), not::expr(front_mask[i].clone())]),
+                    );
+                    cb.require_zero(
+                        "is_back_mask is set on a suffix of the word",
+                        and::expr([back_mask[i].clone(), not::expr(back_mask[i + 1].clone())]),
+                    );
+                    cb.require_zero(
+                        "is_pad is set on a suffix of the copied bytes",
+                        and::expr([
+                            pad[i].clone(),
+                            data[i + 1].clone(),
+                            not::expr(pad[i + 1].clone()),
+                        ]),
+                    );
+                }
+            }
+            cb.require_zero(
+                "is_front_mask leaves a byte of the word",
+                front_mask[N_BYTES_WORD - 1].clone(),
+            );
+            cb.require_zero(
+                "is_front_mask only for memory",
+                not::expr(is_memory.clone()) * front_mask[0].clone(),
+            );
+            cb.condition(
+                and::expr([not::expr(meta.query_selector(q_step)), is_memory.clone()]),
+                |cb| {
+                    let value = query_bytes(meta, value, Rotation::cur());
+                    let value_prev = query_bytes(meta, value_prev, Rotation::cur());
+                    for i in 0..N_BYTES_WORD {
+                        cb.require_zero(
+                            "value == value_prev for masked memory write",
+                            not::expr(data[i].clone()) * (value[i].clone() - value_prev[i].clone()),
+                        );
+                    }
+                },
+            );
+
             let not_last_two_rows = 1.expr()
                 - meta.query_advice(is_last, Rotation::cur())
                 - meta.query_advice(is_last, Rotation::next());
@@ -179,8 +346,8 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                         tag.value(Rotation(2))(meta),
                     );
                     cb.require_equal(
-                        "rows[0].addr + 1 == rows[2].addr",
-                        meta.query_advice(addr, Rotation::cur()) + 1.expr(),
+                        "rows[0].addr + data_len == rows[2].addr",
+                        meta.query_advice(addr, Rotation::cur()) + data_len(meta, Rotation::cur()),
                         meta.query_advice(addr, Rotation(2)),
                     );
                     cb.require_equal(
@@ -188,16 +355,60 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                         meta.query_advice(src_addr_end, Rotation::cur()),
                         meta.query_advice(src_addr_end, Rotation(2)),
                     );
+                    cb.require_equal(
+                        "rows[0].bytes_left - data_len == rows[2].bytes_left",
+                        meta.query_advice(bytes_left, Rotation::cur())
+                            - data_len(meta, Rotation::cur()),
+                        meta.query_advice(bytes_left, Rotation(2)),
+                    );
+                    cb.require_zero(
+                        "is_front_mask only for the first step",
+                        meta.query_advice(is_front_mask[0], Rotation(2)),
+                    );
+                    cb.require_zero(
+                        "the steps after a back masked byte are fully masked",
+                        and::expr([
+                            meta.query_advice(is_back_mask[N_BYTES_WORD - 1], Rotation::cur()),
+                            not::expr(meta.query_advice(is_back_mask[0], Rotation(2))),
+                        ]),
+                    );
+                    cb.require_equal(
+                        "rows[2].value_acc == rows[0].value_acc * r^data_len + copied bytes",
+                        meta.query_advice(value_acc, Rotation(2))
+                            * pow_of_back_len(meta, Rotation(2)),
+                        meta.query_advice(value_acc, Rotation::cur())
+                            * keccak_powers[N_BYTES_WORD - 1].clone()
+                            + masked_word_rlc(meta, Rotation(2)),
+                    );
                 },
             );
 
-            let rw_diff = and::expr([
-                or::expr([
-                    tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta),
-                    tag.value_equals(CopyDataType::TxLog, Rotation::cur())(meta),
-                ]),
-                not::expr(meta.query_advice(is_pad, Rotation::cur())),
-            ]);
+            cb.condition(
+                meta.query_advice(is_last, Rotation::cur())
+                    + meta.query_advice(is_last, Rotation::next()),
+                |cb| {
+                    cb.require_equal(
+                        "bytes_left == data_len for last step",
+                        meta.query_advice(bytes_left, Rotation::cur()),
+                        data_len(meta, Rotation::cur()),
+                    );
+                },
+            );
+
+            // A memory step accesses its word unless it is fully masked.
+            let is_word_access = is_memory.clone() * not::expr(back_mask[0].clone());
+            cb.condition(is_word_access.clone(), |cb| {
+                cb.require_equal(
+                    "addr == word_num * 32 + front_len for memory",
+                    meta.query_advice(word_num, Rotation::cur()) * N_BYTES_WORD.expr()
+                        + sum::expr(front_mask.clone()),
+                    meta.query_advice(addr, Rotation::cur()),
+                );
+            });
+
+            let rw_diff = is_word_access
+                + tag.value_equals(CopyDataType::TxLog, Rotation::cur())(meta)
+                    * data_len(meta, Rotation::cur());
             cb.condition(
                 not::expr(meta.query_advice(is_last, Rotation::cur())),
                 |cb| {
@@ -232,8 +443,8 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 ]),
                 |cb| {
                     cb.require_equal(
-                        "value == rlc_acc at the last row for RlcAcc",
-                        meta.query_advice(value, Rotation::cur()),
+                        "value_acc == rlc_acc at the last row for RlcAcc",
+                        meta.query_advice(value_acc, Rotation::cur()),
                         meta.query_advice(rlc_acc, Rotation::cur()),
                     );
                 },
@@ -245,98 +456,63 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
         meta.create_gate("verify step (q_step == 1)", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
-            cb.require_zero(
-                "bytes_left == 1 for last step",
-                and::expr([
-                    meta.query_advice(is_last, Rotation::next()),
-                    1.expr() - meta.query_advice(bytes_left, Rotation::cur()),
-                ]),
-            );
-            cb.condition(
-                not::expr(meta.query_advice(is_last, Rotation::next()))
-                    * (not::expr(tag.value_equals(CopyDataType::Padding, Rotation::cur())(
-                        meta,
-                    ))),
-                |cb| {
-                    cb.require_equal(
-                        "bytes_left == bytes_left_next + 1 for non-last step",
-                        meta.query_advice(bytes_left, Rotation::cur()),
-                        meta.query_advice(bytes_left, Rotation(2)) + 1.expr(),
-                    );
-                },
-            );
-            cb.condition(
-                not::expr(tag.value_equals(CopyDataType::RlcAcc, Rotation::next())(
-                    meta,
-                )),
-                |cb| {
+            cb.condition(meta.query_advice(is_first, Rotation::cur()), |cb| {
+                for rotation in [Rotation::cur(), Rotation::next()] {
                     cb.require_equal(
-                        "write value == read value (if not rlc acc)",
-                        meta.query_advice(value, Rotation::cur()),
-                        meta.query_advice(value, Rotation::next()),
+                        "value_acc == copied bytes for first step",
+                        meta.query_advice(value_acc, rotation) * pow_of_back_len(meta, rotation),
+                        masked_word_rlc(meta, rotation),
                     );
-                },
-            );
-            cb.condition(meta.query_advice(is_first, Rotation::cur()), |cb| {
+                }
                 cb.require_equal(
-                    "write value == read value (is_first == 1)",
-                    meta.query_advice(value, Rotation::cur()),
-                    meta.query_advice(value, Rotation::next()),
+                    "write bytes_left == read bytes_left for first step",
+                    meta.query_advice(bytes_left, Rotation::cur()),
+                    meta.query_advice(bytes_left, Rotation::next()),
                 );
             });
-            cb.require_zero(
-                "value == 0 when is_pad == 1 for read",
-                and::expr([
-                    meta.query_advice(is_pad, Rotation::cur()),
-                    meta.query_advice(value, Rotation::cur()),
-                ]),
-            );
+            cb.condition(meta.query_advice(is_last, Rotation::next()), |cb| {
+                cb.require_equal(
+                    "write value_acc == read value_acc for last step",
+                    meta.query_advice(value_acc, Rotation::cur()),
+                    meta.query_advice(value_acc, Rotation::next()),
+                );
+            });
+
+            // The bytes at or after `src_addr_end` are padding: none if the
+            // step ends before it, all if it starts after it.
+            let addr = meta.query_advice(addr, Rotation::cur());
+            let src_addr_end = meta.query_advice(src_addr_end, Rotation::cur());
+            let data_len = data_len(meta, Rotation::cur());
+            let is_lt = addr_lt_addr_end.is_lt(meta, None);
             cb.require_equal(
-                "is_pad == 1 - (src_addr < src_addr_end) for read row",
-                1.expr() - addr_lt_addr_end.is_lt(meta, None),
-                meta.query_advice(is_pad, Rotation::cur()),
+                "pad_len == number of copied bytes at or after src_addr_end for read row",
+                sum::expr(query_bytes(meta, is_pad, Rotation::cur())),
+                not::expr(data_end_le_addr_end.is_lt(meta, None))
+                    * (is_lt.clone() * (addr + data_len.clone() - src_addr_end)
+                        + not::expr(is_lt) * data_len),
             );
             cb.require_zero(
                 "is_pad == 0 for write row",
-                meta.query_advice(is_pad, Rotation::next()),
+                sum::expr(query_bytes(meta, is_pad, Rotation::next())),
             );
 
             cb.gate(meta.query_selector(q_step))
         });
 
-        meta.create_gate("verify_step (q_step == 0)", |meta| {
-            let mut cb = BaseConstraintBuilder::default();
-
-            cb.require_equal(
-                "rows[2].value == rows[0].value * r + rows[1].value",
-                meta.query_advice(value, Rotation(2)),
-                meta.query_advice(value, Rotation::cur()) * challenges.keccak_input()
-                    + meta.query_advice(value, Rotation::next()),
-            );
-
-            cb.gate(and::expr([
-                meta.query_fixed(q_enable, Rotation::cur()),
-                not::expr(meta.query_selector(q_step)),
-                not::expr(meta.query_advice(is_last, Rotation::cur())),
-                tag.value_equals(CopyDataType::RlcAcc, Rotation::cur())(meta),
-                not::expr(meta.query_advice(is_pad, Rotation::cur())),
-            ]))
-        });
-
         meta.lookup_any("Memory lookup", |meta| {
             let cond = meta.query_fixed(q_enable, Rotation::cur())
                 * tag.value_equals(CopyDataType::Memory, Rotation::cur())(meta)
-                * not::expr(meta.query_advice(is_pad, Rotation::cur()));
+                * not::expr(meta.query_advice(is_back_mask[0], Rotation::cur()));
             vec![
                 meta.query_advice(rw_counter, Rotation::cur()),
                 not::expr(meta.query_selector(q_step)),
                 Target::Memory.expr(),
                 meta.query_advice(id, Rotation::cur()), // call_id
-                meta.query_advice(addr, Rotation::cur()), // memory address
-                0.expr(),
+                meta.query_advice(word_num, Rotation::cur()), // memory word index
                 0.expr(),
-                meta.query_advice(value, Rotation::cur()),
                 0.expr(),
+                word_rlc(meta, value, Rotation::cur()),
+                word_rlc(meta, value_prev, Rotation::cur()),
                 0.expr(),
                 0.expr(),
             ]
@@ -346,69 +522,90 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             .collect()
         });
 
-        meta.lookup_any("TxLog lookup", |meta| {
-            let cond = meta.query_fixed(q_enable, Rotation::cur())
-                * tag.value_equals(CopyDataType::TxLog, Rotation::cur())(meta);
-            vec![
-                meta.query_advice(rw_counter, Rotation::cur()),
-                1.expr(),
-                Target::TxLog.expr(),
-                meta.query_advice(id, Rotation::cur()), // tx_id
-                meta.query_advice(addr, Rotation::cur()), // byte_index || field_tag || log_id
-                0.expr(),
-                0.expr(),
-                meta.query_advice(value, Rotation::cur()),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-            ]
-            .into_iter()
-            .zip(rw_table.table_exprs(meta).into_iter())
-            .map(|(arg, table)| (cond.clone() * arg, table))
-            .collect()
-        });
+        // The tx log bytes of a step are written one by one, from the first
+        // one.
+        for i in 0..N_BYTES_WORD {
+            meta.lookup_any("TxLog lookup", |meta| {
+                let cond = meta.query_fixed(q_enable, Rotation::cur())
+                    * tag.value_equals(CopyDataType::TxLog, Rotation::cur())(meta)
+                    * is_data(meta, Rotation::cur())[i].clone();
+                vec![
+                    meta.query_advice(rw_counter, Rotation::cur()) + i.expr(),
+                    1.expr(),
+                    Target::TxLog.expr(),
+                    meta.query_advice(id, Rotation::cur()), // tx_id
+                    meta.query_advice(addr, Rotation::cur()) + i.expr(), // byte_index || field_tag || log_id
+                    0.expr(),
+                    0.expr(),
+                    meta.query_advice(value[i], Rotation::cur()),
+                    0.expr(),
+                    0.expr(),
+                    0.expr(),
+                ]
+                .into_iter()
+                .zip(rw_table.table_exprs(meta).into_iter())
+                .map(|(arg, table)| (cond.clone() * arg, table))
+                .collect()
+            });
+        }
 
-        meta.lookup_any("Bytecode lookup", |meta| {
-            let cond = meta.query_fixed(q_enable, Rotation::cur())
-                * tag.value_equals(CopyDataType::Bytecode, Rotation::cur())(meta)
-                * not::expr(meta.query_advice(is_pad, Rotation::cur()));
-            vec![
-                meta.query_advice(id, Rotation::cur()),
-                BytecodeFieldTag::Byte.expr(),
-                meta.query_advice(addr, Rotation::cur()),
-                meta.query_advice(is_code, Rotation::cur()),
-                meta.query_advice(value, Rotation::cur()),
-            ]
-            .into_iter()
-            .zip(bytecode_table.table_exprs(meta).into_iter())
-            .map(|(arg, table)| (cond.clone() * arg, table))
-            .collect()
-        });
+        // `is_pad` implies `is_data`, so `is_data - is_pad` is 1 for the copied
+        // bytes that are not padded.  `is_code` is not copied, so it's left out
+        // of the lookup.
+        for i in 0..N_BYTES_WORD {
+            meta.lookup_any("Bytecode lookup", |meta| {
+                let cond = meta.query_fixed(q_enable, Rotation::cur())
+                    * tag.value_equals(CopyDataType::Bytecode, Rotation::cur())(meta)
+                    * (is_data(meta, Rotation::cur())[i].clone()
+                        - meta.query_advice(is_pad[i], Rotation::cur()));
+                let [code_hash, field_tag, index, _is_code, byte] = bytecode_table
+                    .table_exprs(meta)
+                    .try_into()
+                    .expect("bytecode table has 5 columns");
+                vec![
+                    (meta.query_advice(id, Rotation::cur()), code_hash),
+                    (BytecodeFieldTag::Byte.expr(), field_tag),
+                    (meta.query_advice(addr, Rotation::cur()) + i.expr(), index),
+                    (meta.query_advice(value[i], Rotation::cur()), byte),
+                ]
+                .into_iter()
+                .map(|(arg, table)| (cond.clone() * arg, table))
+                .collect()
+            });
+        }
 
-        meta.lookup_any("Tx calldata lookup", |meta| {
-            let cond = meta.query_fixed(q_enable, Rotation::cur())
-                * tag.value_equals(CopyDataType::TxCalldata, Rotation::cur())(meta)
-                * not::expr(meta.query_advice(is_pad, Rotation::cur()));
-            vec![
-                meta.query_advice(id, Rotation::cur()),
-                TxContextFieldTag::CallData.expr(),
-                meta.query_advice(addr, Rotation::cur()),
-                meta.query_advice(value, Rotation::cur()),
-            ]
-            .into_iter()
-            .zip(tx_table.table_exprs(meta).into_iter())
-            .map(|(arg, table)| (cond.clone() * arg, table))
-            .collect()
-        });
+        for i in 0..N_BYTES_WORD {
+            meta.lookup_any("Tx calldata lookup", |meta| {
+                let cond = meta.query_fixed(q_enable, Rotation::cur())
+                    * tag.value_equals(CopyDataType::TxCalldata, Rotation::cur())(meta)
+                    * (is_data(meta, Rotation::cur())[i].clone()
+                        - meta.query_advice(is_pad[i], Rotation::cur()));
+                vec![
+                    meta.query_advice(id, Rotation::cur()),
+                    TxContextFieldTag::CallData.expr(),
+                    meta.query_advice(addr, Rotation::cur()) + i.expr(),
+                    meta.query_advice(value[i], Rotation::cur()),
+                ]
+                .into_iter()
+                .zip(tx_table.table_exprs(meta).into_iter())
+                .map(|(arg, table)| (cond.clone() * arg, table))
+                .collect()
+            });
+        }
 
         Self {
             q_step,
             is_last,
             value,
+            value_prev,
             is_pad,
-            is_code,
+            is_front_mask,
+            is_back_mask,
+            word_num,
+            value_acc,
             q_enable,
             addr_lt_addr_end,
+            data_end_le_addr_end,
             copy_table,
             tx_table,
             rw_table,
@@ -419,21 +616,24 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
 
 impl<F: Field> CopyCircuitConfig<F> {
     /// Assign an individual copy event to the Copy Circuit.
+    #[allow(clippy::too_many_arguments)]
     pub fn assign_copy_event(
         &self,
         region: &mut Region<F>,
         offset: &mut usize,
         tag_chip: &BinaryNumberChip<F, CopyDataType, 3>,
         lt_chip: &LtChip<F, 8>,
+        data_end_lt_chip: &LtChip<F, 8>,
         challenges: Challenges<Value<F>>,
         copy_event: &CopyEvent,
     ) -> Result<(), Error> {
-        for (step_idx, (tag, table_row, circuit_row)) in
+        for (step_idx, (tag, table_row, circuit_row, word_row)) in
             CopyTable::assignments(copy_event, challenges)
                 .iter()
                 .enumerate()
         {
             let is_read = step_idx % 2 == 0;
+            let step = step_idx / 2;
 
             // Copy table assignments
             for (&column, &(value, label)) in
@@ -441,16 +641,12 @@ impl<F: Field> CopyCircuitConfig<F> {
                     .iter()
                     .zip_eq(table_row)
             {
-                // Leave sr_addr_end and bytes_left unassigned when !is_read
-                if !is_read && (label == "src_addr_end" || label == "bytes_left") {
-                } else {
-                    region.assign_advice(
-                        || format!("{} at row: {}", label, offset),
-                        column,
-                        *offset,
-                        || value,
-                    )?;
-                }
+                region.assign_advice(
+                    || format!("{} at row: {}", label, offset),
+                    column,
+                    *offset,
+                    || value,
+                )?;
             }
 
             // q_step
@@ -465,8 +661,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                 || Value::known(F::ONE),
             )?;
 
-            // is_last, value, is_pad, is_code
-            for (column, &(value, label)) in [self.is_last, self.value, self.is_pad, self.is_code]
+            for (column, &(value, label)) in [self.is_last, self.word_num, self.value_acc]
                 .iter()
                 .zip_eq(circuit_row)
             {
@@ -477,18 +672,46 @@ impl<F: Field> CopyCircuitConfig<F> {
                     || value,
                 )?;
             }
+            for (index, byte_row) in word_row.iter().enumerate() {
+                for (column, &(value, label)) in [
+                    self.value[index],
+                    self.value_prev[index],
+                    self.is_pad[index],
+                    self.is_front_mask[index],
+                    self.is_back_mask[index],
+                ]
+                .iter()
+                .zip_eq(byte_row)
+                {
+                    region.assign_advice(
+                        || format!("{} {} at row: {}", label, index, *offset),
+                        *column,
+                        *offset,
+                        || value,
+                    )?;
+                }
+            }
 
             // tag
             tag_chip.assign(region, *offset, tag)?;
 
-            // lt chip
+            // lt chips
             if is_read {
+                let bytes_done = copy_event.src_bytes_done(step) as u64;
+                let data_len = copy_event.src_data_range(step).len() as u64;
+                let addr = copy_event.src_addr + bytes_done;
                 lt_chip.assign(
                     region,
                     *offset,
-                    F::from(copy_event.src_addr + u64::try_from(step_idx).unwrap() / 2u64),
+                    F::from(addr),
                     F::from(copy_event.src_addr_end),
                 )?;
+                data_end_lt_chip.assign(
+                    region,
+                    *offset,
+                    F::from(addr + data_len),
+                    F::from(copy_event.src_addr_end + 1),
+                )?;
             }
 
             *offset += 1;
@@ -505,26 +728,39 @@ impl<F: Field> CopyCircuitConfig<F> {
         max_copy_rows: usize,
         challenges: Challenges<Value<F>>,
     ) -> Result<(), Error> {
-        let copy_rows_needed = copy_events.iter().map(|c| c.bytes.len() * 2).sum::<usize>();
+        let copy_rows_needed = copy_events.iter().map(|c| c.num_steps() * 2).sum::<usize>();
 
         // The `+ 2` is used to take into account the two extra empty copy rows needed
-        // to satisfy the query at `Rotation(2)` performed inside of the
-        // `rows[2].value == rows[0].value * r + rows[1].value` requirement in the RLC
-        // Accumulation gate.
+        // to satisfy the queries at `Rotation(2)` performed inside of the
+        // step transition constraints.
         assert!(copy_rows_needed + 2 <= max_copy_rows);
 
         let tag_chip = BinaryNumberChip::construct(self.copy_table.tag);
         let lt_chip = LtChip::construct(self.addr_lt_addr_end);
+        let data_end_lt_chip = LtChip::construct(self.data_end_le_addr_end);
 
         lt_chip.load(layouter)?;
+        data_end_lt_chip.load(layouter)?;
 
         layouter.assign_region(
             || "assign copy table",
             |mut region| {
                 region.name_column(|| "is_last", self.is_last);
-                region.name_column(|| "value", self.value);
-                region.name_column(|| "is_code", self.is_code);
-                region.name_column(|| "is_pad", self.is_pad);
+                region.name_column(|| "word_num", self.word_num);
+                region.name_column(|| "value_acc", self.value_acc);
+                for index in 0..N_BYTES_WORD {
+                    region.name_column(|| format!("value_{}", index), self.value[index]);
+                    region.name_column(|| format!("value_prev_{}", index), self.value_prev[index]);
+                    region.name_column(|| format!("is_pad_{}", index), self.is_pad[index]);
+                    region.name_column(
+                        || format!("is_front_mask_{}", index),
+                        self.is_front_mask[index],
+                    );
+                    region.name_column(
+                        || format!("is_back_mask_{}", index),
+                        self.is_back_mask[index],
+                    );
+                }
 
                 let mut offset = 0;
                 for copy_event in copy_events.iter() {
@@ -533,17 +769,33 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &mut offset,
                         &tag_chip,
                         &lt_chip,
+                        &data_end_lt_chip,
                         challenges,
                         copy_event,
                     )?;
                 }
 
                 for _ in 0..max_copy_rows - copy_rows_needed - 2 {
-                    self.assign_padding_row(&mut region, &mut offset, false, &tag_chip, &lt_chip)?;
+                    self.assign_padding_row(
+                        &mut region,
+                        &mut offset,
+                        false,
+                        &tag_chip,
+                        &lt_chip,
+                        &data_end_lt_chip,
+                    )?;
                 }
 
-                self.assign_padding_row(&mut region, &mut offset, true, &tag_chip, &lt_chip)?;
-                self.assign_padding_row(&mut region, &mut offset, true, &tag_chip, &lt_chip)?;
+                for _ in 0..2 {
+                    self.assign_padding_row(
+                        &mut region,
+                        &mut offset,
+                        true,
+                        &tag_chip,
+                        &lt_chip,
+                        &data_end_lt_chip,
+                    )?;
+                }
 
                 Ok(())
             },
@@ -557,6 +809,7 @@ impl<F: Field> CopyCircuitConfig<F> {
         is_last_two: bool,
         tag_chip: &BinaryNumberChip<F, CopyDataType, 3>,
         lt_chip: &LtChip<F, 8>,
+        data_end_lt_chip: &LtChip<F, 8>,
     ) -> Result<(), Error> {
         if !is_last_two {
             // q_enable
@@ -572,94 +825,48 @@ impl<F: Field> CopyCircuitConfig<F> {
             }
         }
 
-        // is_first
-        region.assign_advice(
-            || format!("assign is_first {}", *offset),
-            self.copy_table.is_first,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // is_last
-        region.assign_advice(
-            || format!("assign is_last {}", *offset),
-            self.is_last,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // id
-        region.assign_advice(
-            || format!("assign id {}", *offset),
-            self.copy_table.id,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // addr
-        region.assign_advice(
-            || format!("assign addr {}", *offset),
-            self.copy_table.addr,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // src_addr_end
-        region.assign_advice(
-            || format!("assign src_addr_end {}", *offset),
-            self.copy_table.src_addr_end,
-            *offset,
-            || Value::known(F::ONE),
-        )?;
-        // bytes_left
-        region.assign_advice(
-            || format!("assign bytes_left {}", *offset),
-            self.copy_table.bytes_left,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // value
-        region.assign_advice(
-            || format!("assign value {}", *offset),
-            self.value,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // rlc_acc
-        region.assign_advice(
-            || format!("assign rlc_acc {}", *offset),
-            self.copy_table.rlc_acc,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // is_code
-        region.assign_advice(
-            || format!("assign is_code {}", *offset),
-            self.is_code,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // is_pad
-        region.assign_advice(
-            || format!("assign is_pad {}", *offset),
-            self.is_pad,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // rw_counter
-        region.assign_advice(
-            || format!("assign rw_counter {}", *offset),
-            self.copy_table.rw_counter,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
-        // rwc_inc_left
-        region.assign_advice(
-            || format!("assign rwc_inc_left {}", *offset),
-            self.copy_table.rwc_inc_left,
-            *offset,
-            || Value::known(F::ZERO),
-        )?;
+        for (column, label, value) in [
+            (self.copy_table.is_first, "is_first", F::ZERO),
+            (self.is_last, "is_last", F::ZERO),
+            (self.copy_table.id, "id", F::ZERO),
+            (self.copy_table.addr, "addr", F::ZERO),
+            (self.copy_table.src_addr_end, "src_addr_end", F::ONE),
+            (self.copy_table.bytes_left, "bytes_left", F::ZERO),
+            (self.copy_table.rlc_acc, "rlc_acc", F::ZERO),
+            (self.word_num, "word_num", F::ZERO),
+            (self.value_acc, "value_acc", F::ZERO),
+            (self.copy_table.rw_counter, "rw_counter", F::ZERO),
+            (self.copy_table.rwc_inc_left, "rwc_inc_left", F::ZERO),
+        ] {
+            region.assign_advice(
+                || format!("assign {} {}", label, *offset),
+                column,
+                *offset,
+                || Value::known(value),
+            )?;
+        }
+        // The padding steps are fully masked.
+        for index in 0..N_BYTES_WORD {
+            for (column, label, value) in [
+                (self.value[index], "value", F::ZERO),
+                (self.value_prev[index], "value_prev", F::ZERO),
+                (self.is_pad[index], "is_pad", F::ZERO),
+                (self.is_front_mask[index], "is_front_mask", F::ZERO),
+                (self.is_back_mask[index], "is_back_mask", F::ONE),
+            ] {
+                region.assign_advice(
+                    || format!("assign {} {} {}", label, index, *offset),
+                    column,
+                    *offset,
+                    || Value::known(value),
+                )?;
+            }
+        }
         // tag
         tag_chip.assign(region, *offset, &CopyDataType::Padding)?;
-        // Assign LT gadget
+        // Assign LT gadgets
         lt_chip.assign(region, *offset, F::ZERO, F::ONE)?;
+        data_end_lt_chip.assign(region, *offset, F::ZERO, F::from(2))?;
 
         *offset += 1;
 
@@ -763,7 +970,7 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
             block
                 .copy_events
                 .iter()
-                .map(|c| c.bytes.len() * 2)
+                .map(|c| c.num_steps() * 2)
                 .sum::<usize>()
                 + 2,
             block.circuits_params.max_copy_rows,
@@ -97,6 +97,40 @@ fn gen_calldatacopy_data() -> CircuitInputBuilder {
     builder
 }
 
+fn gen_unaligned_copy_data() -> CircuitInputBuilder {
+    let code = bytecode! {
+        // copy into the end of the first word, the second word and the start
+        // of the third word
+        PUSH1(0x45) // length
+        PUSH1(0x03) // offset
+        PUSH1(0x11) // dest_offset
+        CALLDATACOPY
+        // hash the same bytes, read from memory
+        PUSH1(0x45) // length
+        PUSH1(0x11) // offset
+        SHA3
+        STOP
+    };
+    let test_ctx = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        |mut txs, accs| {
+            txs[0]
+                .from(accs[1].address)
+                .to(accs[0].address)
+                .input(rand_bytes(0x50).into());
+        },
+        |block, _txs| block,
+    )
+    .unwrap();
+    let block: GethData = test_ctx.into();
+    let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    builder
+}
+
 fn gen_codecopy_data() -> CircuitInputBuilder {
     let code = bytecode! {
         PUSH32(Word::from(0x20))
@@ -196,6 +230,36 @@ fn copy_circuit_valid_calldatacopy() {
     assert_eq!(test_copy_circuit_from_block(14, block), Ok(()));
 }
 
+#[test]
+fn copy_circuit_valid_unaligned_copy() {
+    let builder = gen_unaligned_copy_data();
+    // 0x11 masked bytes, 0x45 copied bytes and 0x0a masked bytes in 3 words
+    for copy_event in &builder.block.copy_events {
+        assert_eq!(copy_event.num_steps(), 3);
+    }
+    assert_eq!(builder.block.copy_events[0].dst_front_len(), 0x11);
+    assert_eq!(builder.block.copy_events[1].src_front_len(), 0x11);
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    assert_eq!(test_copy_circuit_from_block(10, block), Ok(()));
+}
+
+#[test]
+fn copy_circuit_invalid_unaligned_copy() {
+    let mut builder = gen_unaligned_copy_data();
+
+    // modify a masked byte of the first destination word, which must be kept
+    builder.block.copy_events[0].dst_memory_prev[0] =
+        builder.block.copy_events[0].dst_memory_prev[0].wrapping_add(1);
+
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+    let errors = test_copy_circuit_from_block(10, block).expect_err("result is not an error");
+    assert!(errors.iter().all(|error| matches!(
+        error,
+        VerifyFailure::Lookup { name, .. } if *name == "Memory lookup"
+    )));
+}
+
 #[test]
 fn copy_circuit_valid_codecopy() {
     let builder = gen_codecopy_data();
@@ -276,9 +340,11 @@ fn copy_circuit_invalid_extcodecopy() {
 fn copy_circuit_invalid_sha3() {
     let mut builder = gen_sha3_data();
 
-    // modify first byte of first copy event
-    builder.block.copy_events[0].bytes[0].0 =
-        builder.block.copy_events[0].bytes[0].0.wrapping_add(1);
+    // modify first byte of first copy event, together with its source memory
+    let copy_event = &mut builder.block.copy_events[0];
+    copy_event.bytes[0].0 = copy_event.bytes[0].0.wrapping_add(1);
+    let src_front_len = copy_event.src_front_len();
+    copy_event.src_memory[src_front_len] = copy_event.bytes[0].0;
 
     let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
 
@@ -292,9 +358,11 @@ fn copy_circuit_invalid_sha3() {
 fn copy_circuit_invalid_tx_log() {
     let mut builder = gen_tx_log_data();
 
-    // modify first byte of first copy event
-    builder.block.copy_events[0].bytes[0].0 =
-        builder.block.copy_events[0].bytes[0].0.wrapping_add(1);
+    // modify first byte of first copy event, together with its source memory
+    let copy_event = &mut builder.block.copy_events[0];
+    copy_event.bytes[0].0 = copy_event.bytes[0].0.wrapping_add(1);
+    let src_front_len = copy_event.src_front_len();
+    copy_event.src_memory[src_front_len] = copy_event.bytes[0].0;
 
     let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
 
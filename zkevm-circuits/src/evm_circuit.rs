@@ -21,14 +21,14 @@ pub use self::EvmCircuit as TestEvmCircuit;
 
 pub use crate::witness;
 use crate::{
-    evm_circuit::param::{MAX_STEP_HEIGHT, STEP_STATE_HEIGHT},
+    evm_circuit::param::EvmLayout,
     table::{
         BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, LookupTable, RwTable, TxTable,
     },
     util::{Challenges, SubCircuit, SubCircuitConfig},
 };
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::{evm_types::ChainSpec, Field};
 use execution::ExecutionConfig;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
@@ -49,6 +49,8 @@ pub struct EvmCircuitConfig<F> {
     copy_table: CopyTable,
     keccak_table: KeccakTable,
     exp_table: ExpTable,
+    // Rules of the chain the fixed table is built for
+    chain_spec: ChainSpec,
 }
 
 /// Circuit configuration arguments
@@ -71,6 +73,10 @@ pub struct EvmCircuitConfigArgs<F: Field> {
     pub exp_table: ExpTable,
     /// Taiko
     pub is_taiko: bool,
+    /// Rules of the proven chain
+    pub chain_spec: ChainSpec,
+    /// Layout of the steps
+    pub layout: EvmLayout,
 }
 
 impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
@@ -89,6 +95,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             keccak_table,
             exp_table,
             is_taiko,
+            chain_spec,
+            layout,
         }: Self::ConfigArgs,
     ) -> Self {
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
@@ -106,6 +114,8 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             &keccak_table,
             &exp_table,
             is_taiko,
+            chain_spec,
+            layout,
         ));
 
         meta.annotate_lookup_any_column(byte_table[0], || "byte_range");
@@ -131,6 +141,7 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
             copy_table,
             keccak_table,
             exp_table,
+            chain_spec,
         }
     }
 }
@@ -146,7 +157,11 @@ impl<F: Field> EvmCircuitConfig<F> {
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::ZERO; 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(
+                        fixed_table_tags
+                            .iter()
+                            .flat_map(|tag| tag.build(self.chain_spec)),
+                    )
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -185,6 +200,7 @@ pub struct EvmCircuit<F: Field> {
     /// Block
     pub block: Option<Block<F>>,
     fixed_table_tags: Vec<FixedTableTag>,
+    parallel_assignment: bool,
 }
 
 impl<F: Field> EvmCircuit<F> {
@@ -193,6 +209,7 @@ impl<F: Field> EvmCircuit<F> {
         Self {
             block: Some(block),
             fixed_table_tags: FixedTableTag::iter().collect(),
+            parallel_assignment: true,
         }
     }
 
@@ -200,9 +217,17 @@ impl<F: Field> EvmCircuit<F> {
         Self {
             block: Some(block),
             fixed_table_tags,
+            parallel_assignment: true,
         }
     }
 
+    /// Enable or disable the assignment of the execution steps on worker
+    /// threads, which is enabled by default.
+    pub fn with_parallel_assignment(mut self, parallel_assignment: bool) -> Self {
+        self.parallel_assignment = parallel_assignment;
+        self
+    }
+
     /// Calculate which rows are "actually" used in the circuit
     pub fn get_active_rows(block: &Block<F>) -> (Vec<usize>, Vec<usize>) {
         let max_offset = Self::get_num_rows_required(block);
@@ -227,7 +252,9 @@ impl<F: Field> EvmCircuit<F> {
         let mut num_rows = 0;
         for transaction in &block.txs {
             for step in &transaction.steps {
-                num_rows += step.execution_state().get_step_height(block.is_taiko());
+                num_rows += step
+                    .execution_state()
+                    .get_step_height(block.evm_circuit_params());
             }
         }
 
@@ -240,9 +267,12 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
     type Config = EvmCircuitConfig<F>;
 
     fn unusable_rows() -> usize {
-        // Most columns are queried at MAX_STEP_HEIGHT + STEP_STATE_HEIGHT distinct rotations, so
-        // returns (MAX_STEP_HEIGHT + STEP_STATE_HEIGHT + 3) unusable rows.
-        MAX_STEP_HEIGHT + STEP_STATE_HEIGHT + 3
+        // The parameters are not known here, so returns the unusable rows of the
+        // tallest layout, see `EvmCircuitParams::unusable_rows`.
+        EvmLayout::iter()
+            .map(|layout| layout.unusable_rows())
+            .max()
+            .unwrap()
     }
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
@@ -254,7 +284,7 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         let num_rows_required_for_execution_steps: usize = Self::get_num_rows_required(block);
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(block.chain_spec).count())
             .sum();
         (
             std::cmp::max(
@@ -273,10 +303,17 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
+        // The constraints of the gadgets are the ones of the chain of the block
+        assert_eq!(
+            block.chain_spec, config.chain_spec,
+            "Chain spec of the block and of the config differ"
+        );
 
         config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
         config.load_byte_table(layouter)?;
-        config.execution.assign_block(layouter, block, challenges)
+        config
+            .execution
+            .assign_block(layouter, block, challenges, self.parallel_assignment)
     }
 }
 
@@ -308,6 +345,7 @@ pub(crate) mod cached {
     use super::*;
     use halo2_proofs::halo2curves::bn256::Fr;
     use lazy_static::lazy_static;
+    use std::{collections::HashMap, sync::Mutex};
 
     struct Cache {
         cs: ConstraintSystem<Fr>,
@@ -316,21 +354,17 @@ pub(crate) mod cached {
 
     lazy_static! {
         /// Cached values of the ConstraintSystem after the EVM Circuit configuration and the EVM
-        /// Circuit configuration.  These values are calculated just once.
-        static ref CACHE: Cache = {
-            let mut meta = ConstraintSystem::<Fr>::default();
-            let config = EvmCircuit::<Fr>::configure(&mut meta);
-            Cache { cs: meta, config }
-        };
+        /// Circuit configuration, for each of its parameters.  These values are calculated just
+        /// once.
+        static ref CACHE: Mutex<HashMap<EvmCircuitParams, &'static Cache>> = Default::default();
+    }
 
-        static ref CACHE_WITH_TAIKO: Cache = {
+    fn cache(params: EvmCircuitParams) -> &'static Cache {
+        *CACHE.lock().unwrap().entry(params).or_insert_with(|| {
             let mut meta = ConstraintSystem::<Fr>::default();
-            let config = EvmCircuit::<Fr>::configure_with_params(
-                &mut meta,
-                EvmCircuitParams { is_taiko: true },
-            );
-            Cache { cs: meta, config }
-        };
+            let config = EvmCircuit::<Fr>::configure_with_params(&mut meta, params);
+            Box::leak(Box::new(Cache { cs: meta, config }))
+        })
     }
 
     /// Wrapper over the EvmCircuit that behaves the same way and also
@@ -359,15 +393,11 @@ pub(crate) mod cached {
 
         fn configure_with_params(
             meta: &mut ConstraintSystem<Fr>,
-            EvmCircuitParams { is_taiko }: Self::Params,
+            params: Self::Params,
         ) -> Self::Config {
-            if is_taiko {
-                *meta = CACHE_WITH_TAIKO.cs.clone();
-                CACHE_WITH_TAIKO.config.clone()
-            } else {
-                *meta = CACHE.cs.clone();
-                CACHE.config.clone()
-            }
+            let cache = cache(params);
+            *meta = cache.cs.clone();
+            cache.config.clone()
         }
 
         fn synthesize(
@@ -387,9 +417,22 @@ pub(crate) mod cached {
 }
 
 /// Evm Circuit configuration parameters
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct EvmCircuitParams {
-    is_taiko: bool,
+    /// Taiko
+    pub is_taiko: bool,
+    /// Rules of the proven chain
+    pub chain_spec: ChainSpec,
+    /// Layout of the steps
+    pub layout: EvmLayout,
+}
+
+impl EvmCircuitParams {
+    /// Number of unusable rows of the EVM circuit configured with these
+    /// parameters
+    pub fn unusable_rows(&self) -> usize {
+        self.layout.unusable_rows()
+    }
 }
 
 impl<F: Field> CircuitExt<F> for EvmCircuit<F> {
@@ -402,7 +445,7 @@ impl<F: Field> CircuitExt<F> for EvmCircuit<F> {
     }
 }
 
-// Always exported because of `EXECUTION_STATE_HEIGHT_MAP`
+// Always exported because of `execution_state_height_map`
 impl<F: Field> Circuit<F> for EvmCircuit<F> {
     type Config = (EvmCircuitConfig<F>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
@@ -417,14 +460,16 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
     }
 
     fn params(&self) -> Self::Params {
-        EvmCircuitParams {
-            is_taiko: self.block.as_ref().unwrap().is_taiko(),
-        }
+        self.block.as_ref().unwrap().evm_circuit_params()
     }
 
     fn configure_with_params(
         meta: &mut ConstraintSystem<F>,
-        EvmCircuitParams { is_taiko }: Self::Params,
+        EvmCircuitParams {
+            is_taiko,
+            chain_spec,
+            layout,
+        }: Self::Params,
     ) -> Self::Config {
         let tx_table = TxTable::construct(meta);
         let rw_table = RwTable::construct(meta);
@@ -450,6 +495,8 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
                     keccak_table,
                     exp_table,
                     is_taiko,
+                    chain_spec,
+                    layout,
                 },
             ),
             challenges,
@@ -485,7 +532,7 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
             .load(&mut layouter, block.bytecodes.values(), &challenges)?;
         config
             .block_table
-            .load(&mut layouter, &block.context, challenges.evm_word())?;
+            .load(&mut layouter, block.contexts(), challenges.evm_word())?;
         config.copy_table.load(&mut layouter, block, &challenges)?;
         config
             .keccak_table
@@ -499,27 +546,183 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
 #[cfg(test)]
 mod evm_circuit_stats {
     use crate::{
-        evm_circuit::EvmCircuit,
+        evm_circuit::{
+            param::EvmLayout,
+            step::{ExecutionState, HasExecutionState},
+            EvmCircuit, EvmCircuitParams,
+        },
         test_util::CircuitTestBuilder,
         util::{unusable_rows, SubCircuit},
-        witness::block_convert,
+        witness::{block_convert, Block},
     };
-    use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
+    use bus_mapping::{circuit_input_builder::CircuitsParams, evm::OpcodeId, mock::BlockData};
 
-    use eth_types::{bytecode, geth_types::GethData};
+    use eth_types::{
+        bytecode,
+        evm_types::{ChainSpec, Hardfork},
+        geth_types::GethData,
+        Bytecode, ToWord, Word,
+    };
     use halo2_proofs::{self, dev::MockProver, halo2curves::bn256::Fr};
 
-    use mock::test_ctx::{
-        helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
-        TestContext,
+    use mock::{
+        eth,
+        test_ctx::{
+            helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+            TestContext,
+        },
+        TestContextBuilder, MOCK_ACCOUNTS,
     };
+    use strum::IntoEnumIterator;
 
     #[test]
     fn evm_circuit_unusable_rows() {
-        assert_eq!(
-            EvmCircuit::<Fr>::unusable_rows(),
-            unusable_rows::<Fr, EvmCircuit::<Fr>>(Default::default()),
+        let mut max_unusable_rows = 0;
+        for layout in EvmLayout::iter() {
+            let params = EvmCircuitParams {
+                layout,
+                ..Default::default()
+            };
+            let unusable_rows = unusable_rows::<Fr, EvmCircuit<Fr>>(params);
+            assert_eq!(params.unusable_rows(), unusable_rows, "{:?}", layout);
+            max_unusable_rows = max_unusable_rows.max(unusable_rows);
+        }
+        assert_eq!(EvmCircuit::<Fr>::unusable_rows(), max_unusable_rows);
+    }
+
+    fn add_and_mstore_block() -> Block<Fr> {
+        let mut code = Bytecode::default();
+        code.push(32, Word::MAX);
+        code.push(32, Word::one());
+        code.write_op(OpcodeId::ADD);
+        code.push(1, Word::zero());
+        code.write_op(OpcodeId::MSTORE);
+        code.op_stop();
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |b, _| b,
         )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert::<Fr>(&builder.block, &builder.code_db).unwrap()
+    }
+
+    #[test]
+    fn evm_circuit_layouts() {
+        let block = add_and_mstore_block();
+        for layout in EvmLayout::iter() {
+            let block = Block {
+                evm_layout: layout,
+                ..block.clone()
+            };
+            let k = block.get_test_degree();
+            let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
+            let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied_par();
+        }
+    }
+
+    /// Witness of a block with a transaction executed with the rules of
+    /// `chain_spec`, calling `code` or creating a contract with `code` as init
+    /// code.  The storage slot 0 of the callee is set to 1.
+    fn hardfork_block(chain_spec: ChainSpec, code: Bytecode, is_create: bool) -> Block<Fr> {
+        let blocks = TestContextBuilder::new()
+            .chain_spec(chain_spec)
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[0])
+                    .code(code.clone())
+                    .storage(vec![(Word::zero(), Word::one())].into_iter());
+            })
+            .account(|acc| {
+                acc.address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            })
+            .tx(|tx| {
+                tx.from(MOCK_ACCOUNTS[1]);
+                if is_create {
+                    tx.input(code.code().into());
+                } else {
+                    tx.to(MOCK_ACCOUNTS[0]);
+                }
+            })
+            .build()
+            .unwrap();
+        let geth_data = &blocks[0];
+        let mut builder =
+            BlockData::new_from_geth_data(geth_data.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        assert_eq!(block.chain_spec, chain_spec);
+        block
+    }
+
+    #[test]
+    fn evm_circuit_hardforks() {
+        let push0 = bytecode! {
+            PUSH0
+            POP
+            STOP
+        };
+        let basefee = bytecode! {
+            BASEFEE
+            POP
+            STOP
+        };
+        // Clearing the slot is refunded differently by London
+        let sstore_refund = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            SSTORE
+            STOP
+        };
+        // Refunded before London only
+        let selfdestruct_refund = bytecode! {
+            PUSH20(MOCK_ACCOUNTS[1].to_word())
+            SELFDESTRUCT
+        };
+        // Init code is charged per word from Shanghai
+        let init_code = bytecode! {
+            PUSH32(Word::MAX)
+            POP
+            STOP
+        };
+
+        for hardfork in Hardfork::iter() {
+            let chain_spec = ChainSpec::new(hardfork);
+            for (code, is_create, opcode) in [
+                (push0.clone(), false, Some(OpcodeId::PUSH0)),
+                (basefee.clone(), false, Some(OpcodeId::BASEFEE)),
+                (sstore_refund.clone(), false, None),
+                (selfdestruct_refund.clone(), false, None),
+                (init_code.clone(), true, None),
+            ] {
+                let block = hardfork_block(chain_spec, code, is_create);
+                if let Some(opcode) = opcode {
+                    let is_invalid = block.txs[0]
+                        .steps
+                        .iter()
+                        .any(|step| step.execution_state() == ExecutionState::ErrorInvalidOpcode);
+                    assert_eq!(
+                        is_invalid,
+                        !chain_spec.is_opcode_available(opcode),
+                        "{:?} {:?}",
+                        hardfork,
+                        opcode
+                    );
+                }
+                let k = block.get_test_degree();
+                let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block);
+                let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+                prover.assert_satisfied_par();
+            }
+        }
     }
 
     #[test]
@@ -586,4 +789,40 @@ mod evm_circuit_stats {
         assert_eq!(prover1.fixed(), prover2.fixed());
         assert_eq!(prover1.permutation(), prover2.permutation());
     }
+
+    #[test]
+    fn parallel_assignment_matches_sequential() {
+        // Enough steps to be split in several chunks
+        let mut code = Bytecode::default();
+        for _ in 0..200 {
+            code.push(1, Word::one());
+            code.write_op(OpcodeId::POP);
+        }
+        code.op_stop();
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |b, _| b,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        let k = block.get_test_degree();
+
+        let circuit = EvmCircuit::<Fr>::get_test_cicuit_from_block(block.clone());
+        let parallel = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+        parallel.assert_satisfied_par();
+
+        let circuit =
+            EvmCircuit::<Fr>::get_test_cicuit_from_block(block).with_parallel_assignment(false);
+        let sequential = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+
+        assert_eq!(parallel.advice(), sequential.advice());
+        assert_eq!(parallel.permutation(), sequential.permutation());
+    }
 }
@@ -1,15 +1,10 @@
 use super::{
-    param::{
-        BLOCK_TABLE_LOOKUPS, BYTECODE_TABLE_LOOKUPS, COPY_TABLE_LOOKUPS, EXP_TABLE_LOOKUPS,
-        FIXED_TABLE_LOOKUPS, KECCAK_TABLE_LOOKUPS, N_BYTE_LOOKUPS, N_COPY_COLUMNS,
-        N_PHASE1_COLUMNS, RW_TABLE_LOOKUPS, TX_TABLE_LOOKUPS,
-    },
+    param::EvmLayout,
     step::HasExecutionState,
-    util::{instrumentation::Instrument, CachedRegion, CellManager, StoredExpression},
+    util::{instrumentation::Instrument, Assignments, CachedRegion, CellManager, StoredExpression},
 };
 use crate::{
     evm_circuit::{
-        param::{EVM_LOOKUP_COLS, MAX_STEP_HEIGHT, N_PHASE2_COLUMNS, STEP_WIDTH},
         step::{ExecutionState, Step},
         table::Table,
         util::{
@@ -23,7 +18,7 @@ use crate::{
     table::LookupTable,
     util::{query_expression, Challenges, Expr},
 };
-use eth_types::{evm_unimplemented, Field};
+use eth_types::{evm_types::ChainSpec, evm_unimplemented, Field};
 use gadgets::util::not;
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
@@ -33,9 +28,11 @@ use halo2_proofs::{
     },
     poly::Rotation,
 };
+use rayon::prelude::*;
 use std::{
     collections::{BTreeSet, HashMap},
     iter,
+    ops::Range,
 };
 use strum::IntoEnumIterator;
 
@@ -182,6 +179,10 @@ use sstore::SstoreGadget;
 use stop::StopGadget;
 use swap::SwapGadget;
 
+/// Number of consecutive steps assigned by a worker thread in the parallel
+/// assignment of the steps.
+const PARALLEL_CHUNK_STEPS: usize = 64;
+
 pub(crate) trait ExecutionGadget<F: Field> {
     const NAME: &'static str;
 
@@ -217,7 +218,8 @@ pub struct ExecutionConfig<F> {
     q_step_first: Selector,
     // Selector enabled in the row where the last execution step starts.
     q_step_last: Selector,
-    advices: [Column<Advice>; STEP_WIDTH],
+    advices: Vec<Column<Advice>>,
+    layout: EvmLayout,
     step: Step<F>,
     pub(crate) height_map: HashMap<ExecutionState, usize>,
     stored_expressions_map: HashMap<ExecutionState, Vec<StoredExpression<F>>>,
@@ -333,6 +335,8 @@ impl<F: Field> ExecutionConfig<F> {
         keccak_table: &dyn LookupTable<F>,
         exp_table: &dyn LookupTable<F>,
         is_taiko: bool,
+        chain_spec: ChainSpec,
+        layout: EvmLayout,
     ) -> Self {
         let mut instrument = Instrument::default();
         let q_usable = meta.complex_selector();
@@ -344,23 +348,20 @@ impl<F: Field> ExecutionConfig<F> {
         let q_step_first = meta.complex_selector();
         let q_step_last = meta.complex_selector();
 
-        let advices = [(); STEP_WIDTH]
-            .iter()
-            .enumerate()
-            .map(|(n, _)| {
-                if n < EVM_LOOKUP_COLS {
+        let lookup_columns = layout.lookup_columns();
+        let advices = (0..layout.step_width())
+            .map(|n| {
+                if n < lookup_columns {
                     meta.advice_column_in(ThirdPhase)
-                } else if n < EVM_LOOKUP_COLS + N_PHASE2_COLUMNS {
+                } else if n < lookup_columns + layout.n_phase2_columns() {
                     meta.advice_column_in(SecondPhase)
                 } else {
                     meta.advice_column_in(FirstPhase)
                 }
             })
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
+            .collect::<Vec<_>>();
 
-        let step_curr = Step::new(meta, advices, 0, false);
+        let step_curr = Step::new(meta, &advices, layout, 0, false);
         let mut height_map = HashMap::new();
 
         meta.create_gate("Constrain execution state", |meta| {
@@ -464,7 +465,8 @@ impl<F: Field> ExecutionConfig<F> {
                 (|| {
                     Box::new(Self::configure_gadget(
                         meta,
-                        advices,
+                        &advices,
+                        layout,
                         q_usable,
                         q_step,
                         num_rows_until_next_step,
@@ -476,6 +478,7 @@ impl<F: Field> ExecutionConfig<F> {
                         &mut stored_expressions_map,
                         &mut instrument,
                         is_taiko,
+                        chain_spec,
                     ))
                 })()
             };
@@ -491,7 +494,8 @@ impl<F: Field> ExecutionConfig<F> {
             num_rows_inv,
             q_step_first,
             q_step_last,
-            advices,
+            advices: advices.clone(),
+            layout,
             // internal states
             begin_tx_gadget: configure_gadget!(),
             end_block_gadget: configure_gadget!(),
@@ -610,7 +614,8 @@ impl<F: Field> ExecutionConfig<F> {
     #[allow(clippy::too_many_arguments)]
     fn configure_gadget<G: ExecutionGadget<F>>(
         meta: &mut ConstraintSystem<F>,
-        advices: [Column<Advice>; STEP_WIDTH],
+        advices: &[Column<Advice>],
+        layout: EvmLayout,
         q_usable: Selector,
         q_step: Column<Advice>,
         num_rows_until_next_step: Column<Advice>,
@@ -622,17 +627,19 @@ impl<F: Field> ExecutionConfig<F> {
         stored_expressions_map: &mut HashMap<ExecutionState, Vec<StoredExpression<F>>>,
         instrument: &mut Instrument,
         is_taiko: bool,
+        chain_spec: ChainSpec,
     ) -> G {
         // Configure the gadget with the max height first so we can find out the actual
         // height
         let height = {
-            let dummy_step_next = Step::new(meta, advices, MAX_STEP_HEIGHT, true);
+            let dummy_step_next = Step::new(meta, advices, layout, layout.max_step_height(), true);
             let mut cb = EVMConstraintBuilder::new(
                 step_curr.clone(),
                 dummy_step_next,
                 challenges,
                 G::EXECUTION_STATE,
                 is_taiko,
+                chain_spec,
             );
             G::configure(&mut cb);
             let (_, _, height) = cb.build();
@@ -640,13 +647,14 @@ impl<F: Field> ExecutionConfig<F> {
         };
 
         // Now actually configure the gadget with the correct minimal height
-        let step_next = &Step::new(meta, advices, height, true);
+        let step_next = &Step::new(meta, advices, layout, height, true);
         let mut cb = EVMConstraintBuilder::new(
             step_curr.clone(),
             step_next.clone(),
             challenges,
             G::EXECUTION_STATE,
             is_taiko,
+            chain_spec,
         );
 
         let gadget = G::configure(&mut cb);
@@ -891,13 +899,21 @@ impl<F: Field> ExecutionConfig<F> {
 
     /// Assign block
     /// When exact is enabled, assign exact steps in block without padding for
-    /// unit test purpose
+    /// unit test purpose.
+    /// When `parallel` is enabled, the witness of the steps is computed on
+    /// worker threads, see [`Self::assign_steps_parallel`].
     pub fn assign_block(
         &self,
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
         challenges: &Challenges<Value<F>>,
+        parallel: bool,
     ) -> Result<(), Error> {
+        // The step heights of the block are the ones of its layout
+        assert_eq!(
+            block.evm_layout, self.layout,
+            "EVM circuit layout of the block and of the config differ"
+        );
         layouter.assign_region(
             || "Execution step",
             |mut region| {
@@ -917,7 +933,7 @@ impl<F: Field> ExecutionConfig<F> {
                 let end_block_not_last = &block.end_block_not_last;
                 let end_block_last = &block.end_block_last;
                 // Collect all steps
-                let mut steps = block
+                let steps: Vec<_> = block
                     .txs
                     .iter()
                     .flat_map(|tx| {
@@ -926,37 +942,41 @@ impl<F: Field> ExecutionConfig<F> {
                             .map(move |step| (tx, &tx.calls[step.call_index], step))
                     })
                     .chain(std::iter::once((&dummy_tx, &last_call, end_block_not_last)))
-                    .peekable();
+                    .collect();
 
                 let evm_rows = block.circuits_params.max_evm_rows;
                 let no_padding = evm_rows == 0;
 
                 // part1: assign real steps
-                loop {
-                    let (transaction, call, step) = steps.next().expect("should not be empty");
-                    let next = steps.peek();
-                    if next.is_none() {
-                        break;
+                if parallel {
+                    offset =
+                        self.assign_steps_parallel(&mut region, offset, block, &steps, challenges)?;
+                } else {
+                    for (idx, &(transaction, call, step)) in
+                        steps[..steps.len() - 1].iter().enumerate()
+                    {
+                        let height = step
+                            .execution_state()
+                            .get_step_height(block.evm_circuit_params());
+
+                        // Assign the step witness
+                        self.assign_exec_step(
+                            &mut region,
+                            offset,
+                            block,
+                            transaction,
+                            call,
+                            step,
+                            height,
+                            Some(steps[idx + 1]),
+                            challenges,
+                        )?;
+
+                        // q_step logic
+                        self.assign_q_step(&mut region, offset, height)?;
+
+                        offset += height;
                     }
-                    let height = step.execution_state().get_step_height(block.is_taiko());
-
-                    // Assign the step witness
-                    self.assign_exec_step(
-                        &mut region,
-                        offset,
-                        block,
-                        transaction,
-                        call,
-                        step,
-                        height,
-                        next.copied(),
-                        challenges,
-                    )?;
-
-                    // q_step logic
-                    self.assign_q_step(&mut region, offset, height)?;
-
-                    offset += height;
                 }
 
                 // part2: assign non-last EndBlock steps when padding needed
@@ -969,7 +989,8 @@ impl<F: Field> ExecutionConfig<F> {
                         );
                         // return Err(Error::Synthesis);
                     }
-                    let height = ExecutionState::EndBlock.get_step_height(block.is_taiko());
+                    let height =
+                        ExecutionState::EndBlock.get_step_height(block.evm_circuit_params());
                     debug_assert_eq!(height, 1);
                     let last_row = evm_rows - 1;
                     log::trace!(
@@ -996,7 +1017,7 @@ impl<F: Field> ExecutionConfig<F> {
                 }
 
                 // part3: assign the last EndBlock at offset `evm_rows - 1`
-                let height = ExecutionState::EndBlock.get_step_height(block.is_taiko());
+                let height = ExecutionState::EndBlock.get_step_height(block.evm_circuit_params());
                 debug_assert_eq!(height, 1);
                 log::trace!("assign last EndBlock at offset {}", offset);
                 self.assign_exec_step(
@@ -1036,23 +1057,24 @@ impl<F: Field> ExecutionConfig<F> {
     }
 
     fn annotate_circuit(&self, region: &mut Region<F>) {
+        let lookups = self.layout.lookup_config();
         let groups = [
-            ("EVM_lookup_fixed", FIXED_TABLE_LOOKUPS),
-            ("EVM_lookup_tx", TX_TABLE_LOOKUPS),
-            ("EVM_lookup_rw", RW_TABLE_LOOKUPS),
-            ("EVM_lookup_bytecode", BYTECODE_TABLE_LOOKUPS),
-            ("EVM_lookup_block", BLOCK_TABLE_LOOKUPS),
-            ("EVM_lookup_copy", COPY_TABLE_LOOKUPS),
-            ("EVM_lookup_keccak", KECCAK_TABLE_LOOKUPS),
-            ("EVM_lookup_exp", EXP_TABLE_LOOKUPS),
-            ("EVM_adv_phase2", N_PHASE2_COLUMNS),
-            ("EVM_copy", N_COPY_COLUMNS),
-            ("EVM_lookup_byte", N_BYTE_LOOKUPS),
-            ("EVM_adv_phase1", N_PHASE1_COLUMNS),
+            ("EVM_lookup_fixed", lookups[0].1),
+            ("EVM_lookup_tx", lookups[1].1),
+            ("EVM_lookup_rw", lookups[2].1),
+            ("EVM_lookup_bytecode", lookups[3].1),
+            ("EVM_lookup_block", lookups[4].1),
+            ("EVM_lookup_copy", lookups[5].1),
+            ("EVM_lookup_keccak", lookups[6].1),
+            ("EVM_lookup_exp", lookups[7].1),
+            ("EVM_adv_phase2", self.layout.n_phase2_columns()),
+            ("EVM_copy", self.layout.n_copy_columns()),
+            ("EVM_lookup_byte", self.layout.n_byte_lookups()),
+            ("EVM_adv_phase1", self.layout.n_phase1_columns()),
         ];
         let mut group_index = 0;
         let mut index = 0;
-        for col in self.advices {
+        for &col in &self.advices {
             let (name, length) = groups[group_index];
             region.name_column(|| format!("{}_{}", name, index), col);
             index += 1;
@@ -1136,10 +1158,24 @@ impl<F: Field> ExecutionConfig<F> {
             region,
             challenges,
             self.advices.to_vec(),
-            MAX_STEP_HEIGHT * 3,
+            self.layout.max_step_height() * 3,
             offset,
         );
+        self.assign_exec_step_cached(region, offset, block, transaction, call, step, height, next)
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn assign_exec_step_cached(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        transaction: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+        height: usize,
+        next: Option<(&Transaction, &Call, &ExecStep)>,
+    ) -> Result<(), Error> {
         // Also set the witness of the next step.
         // These may be used in stored expressions and
         // so their witness values need to be known to be able
@@ -1158,6 +1194,100 @@ impl<F: Field> ExecutionConfig<F> {
         self.assign_exec_step_int(region, offset, block, transaction, call, step)
     }
 
+    /// Assign every step of `steps` but the last one, which is only used as
+    /// the next step of the one before it, starting at `offset`. Returns the
+    /// offset following the assigned steps.
+    ///
+    /// The row offset of each step is known upfront from its height, and each
+    /// step is assigned in its own `CachedRegion`, so chunks of steps are
+    /// assigned on worker threads into detached regions and replayed in step
+    /// order afterwards, giving the same assignment as the sequential path.
+    /// Steps are processed by windows of a few chunks per thread to bound the
+    /// memory held by recorded assignments.
+    fn assign_steps_parallel(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        block: &Block<F>,
+        steps: &[(&Transaction, &Call, &ExecStep)],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<usize, Error> {
+        let offsets = Self::step_offsets(offset, block, steps);
+        let num_steps = steps.len() - 1;
+        let window = PARALLEL_CHUNK_STEPS * rayon::current_num_threads();
+        for window_start in (0..num_steps).step_by(window) {
+            let window_end = num_steps.min(window_start + window);
+            let chunks: Vec<_> = (window_start..window_end)
+                .step_by(PARALLEL_CHUNK_STEPS)
+                .map(|start| start..window_end.min(start + PARALLEL_CHUNK_STEPS))
+                .collect();
+            let assignments = chunks
+                .into_par_iter()
+                .map(|range| self.assign_steps_detached(block, steps, &offsets, range, challenges))
+                .collect::<Result<Vec<_>, Error>>()?;
+            for assignments in assignments {
+                assignments.replay(region)?;
+            }
+            for idx in window_start..window_end {
+                self.assign_q_step(region, offsets[idx], offsets[idx + 1] - offsets[idx])?;
+            }
+        }
+        Ok(offsets[num_steps])
+    }
+
+    /// Row offset of each step of `steps` when starting at `offset`.
+    fn step_offsets(
+        offset: usize,
+        block: &Block<F>,
+        steps: &[(&Transaction, &Call, &ExecStep)],
+    ) -> Vec<usize> {
+        steps
+            .iter()
+            .scan(offset, |offset, (_, _, step)| {
+                let step_offset = *offset;
+                *offset += step
+                    .execution_state()
+                    .get_step_height(block.evm_circuit_params());
+                Some(step_offset)
+            })
+            .collect()
+    }
+
+    /// Assign the steps in `range` into detached regions, each one together
+    /// with the step following it, and return the recorded assignments.
+    fn assign_steps_detached(
+        &self,
+        block: &Block<F>,
+        steps: &[(&Transaction, &Call, &ExecStep)],
+        offsets: &[usize],
+        range: Range<usize>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<Assignments<F>, Error> {
+        let mut assignments = Assignments::default();
+        for idx in range {
+            let (transaction, call, step) = steps[idx];
+            let offset = offsets[idx];
+            let mut region = CachedRegion::<'_, '_, F>::new_detached(
+                challenges,
+                self.advices.to_vec(),
+                self.layout.max_step_height() * 3,
+                offset,
+            );
+            self.assign_exec_step_cached(
+                &mut region,
+                offset,
+                block,
+                transaction,
+                call,
+                step,
+                offsets[idx + 1] - offset,
+                Some(steps[idx + 1]),
+            )?;
+            assignments.extend(region.into_assignments());
+        }
+        Ok(assignments)
+    }
+
     fn assign_exec_step_int(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
@@ -1168,7 +1298,7 @@ impl<F: Field> ExecutionConfig<F> {
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.step
-            .assign_exec_step(region, offset, block, call, step)?;
+            .assign_exec_step(region, offset, block, transaction, call, step)?;
 
         macro_rules! assign_exec_step {
             ($gadget:expr) => {
@@ -10,7 +10,6 @@ use crate::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            is_precompiled,
             math_gadget::{
                 ConstantDivisionGadget, ContractCreateGadget, IsEqualGadget, IsZeroGadget,
                 MulWordByU64Gadget, RangeCheckGadget,
@@ -103,6 +102,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             tx_caller_address_is_zero.expr(),
             false.expr(),
         );
+        // Bind the block number of the step state to the block including the tx,
+        // so that block table lookups use the right block when proving a batch.
+        cb.tx_context_lookup(
+            tx_id.expr(),
+            TxContextFieldTag::BlockNumber,
+            None,
+            cb.curr.state.block_number.expr(),
+        );
         let [tx_gas_price, tx_value] = [TxContextFieldTag::GasPrice, TxContextFieldTag::Value]
             .map(|field_tag| cb.tx_context_as_word(tx_id.expr(), field_tag, None));
 
@@ -141,8 +148,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         // Calculate gas cost of init code for EIP-3860.
         let init_code_gas_cost = select::expr(
             tx_is_create.expr(),
-            tx_call_data_word_length.quotient().expr()
-                * eth_types::evm_types::INIT_CODE_WORD_GAS.expr(),
+            tx_call_data_word_length.quotient().expr() * cb.chain_spec.init_code_word_gas().expr(),
             0.expr(),
         );
 
@@ -178,17 +184,20 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         ); // rwc_delta += 1
 
-        // Query coinbase address.
+        // Query coinbase address, warm from Shanghai.
         let coinbase = cb.query_cell();
         let is_coinbase_warm = cb.query_bool();
         cb.block_lookup(BlockContextFieldTag::Coinbase.expr(), None, coinbase.expr());
-        cb.account_access_list_write(
-            tx_id.expr(),
-            coinbase.expr(),
-            1.expr(),
-            is_coinbase_warm.expr(),
-            None,
-        ); // rwc_delta += 1
+        let coinbase_rws = cb.chain_spec.is_coinbase_warm() as u64;
+        if cb.chain_spec.is_coinbase_warm() {
+            cb.account_access_list_write(
+                tx_id.expr(),
+                coinbase.expr(),
+                1.expr(),
+                is_coinbase_warm.expr(),
+                None,
+            ); // rwc_delta += 1
+        }
 
         // Read code_hash of callee
         let phase2_code_hash = cb.query_cell_phase2();
@@ -296,7 +305,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write Account (Caller) Nonce
                 //   - Write TxAccessListAccount (Caller)
                 //   - Write TxAccessListAccount (Callee)
-                //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                //   - Write TxAccessListAccount (Coinbase) for EIP-3651 from Shanghai
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
                 //   - Write CallContext Depth
@@ -312,7 +321,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsRoot
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
-                rw_counter: Delta(22.expr() + transfer_with_gas_fee.rw_delta()),
+                rw_counter: Delta((21 + coinbase_rws).expr() + transfer_with_gas_fee.rw_delta()),
                 call_id: To(call_id.expr()),
                 is_root: To(true.expr()),
                 is_create: To(tx_is_create.expr()),
@@ -353,10 +362,10 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write Account Nonce
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651 from Shanghai
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
-                    rw_counter: Delta(9.expr() + transfer_with_gas_fee.rw_delta()),
+                    rw_counter: Delta((8 + coinbase_rws).expr() + transfer_with_gas_fee.rw_delta()),
                     call_id: To(call_id.expr()),
                     ..StepStateTransition::any()
                 });
@@ -398,7 +407,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write Account Nonce
                     //   - Write TxAccessListAccount (Caller)
                     //   - Write TxAccessListAccount (Callee)
-                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651
+                    //   - Write TxAccessListAccount (Coinbase) for EIP-3651 from Shanghai
                     //   - Read Account CodeHash
                     //   - a TransferWithGasFeeGadget
                     //   - Write CallContext Depth
@@ -414,7 +423,9 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                     //   - Write CallContext IsRoot
                     //   - Write CallContext IsCreate
                     //   - Write CallContext CodeHash
-                    rw_counter: Delta(22.expr() + transfer_with_gas_fee.rw_delta()),
+                    rw_counter: Delta(
+                        (21 + coinbase_rws).expr() + transfer_with_gas_fee.rw_delta(),
+                    ),
                     call_id: To(call_id.expr()),
                     is_root: To(true.expr()),
                     is_create: To(tx_is_create.expr()),
@@ -471,13 +482,14 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let mut rws = StepRws::new(block, step);
         rws.offset_add(7);
 
-        let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
+        let is_coinbase_warm =
+            block.chain_spec.is_coinbase_warm() && rws.next().tx_access_list_value_pair().1;
         let mut callee_code_hash = zero;
-        if !is_precompiled(&tx.callee_address) && !tx.is_create {
+        let is_precompiled = block.chain_spec.is_precompiled(&tx.callee_address);
+        if !is_precompiled && !tx.is_create {
             callee_code_hash = rws.next().account_value_pair().1;
         }
-        let callee_exists =
-            is_precompiled(&tx.callee_address) || (!tx.is_create && !callee_code_hash.is_zero());
+        let callee_exists = is_precompiled || (!tx.is_create && !callee_code_hash.is_zero());
         let caller_balance_sub_fee_pair = rws.next().account_value_pair();
         let must_create = tx.is_create;
         if (!callee_exists && !tx.value.is_zero()) || must_create {
@@ -604,7 +616,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             offset,
             Value::known(
                 block
-                    .context
+                    .tx_context(tx)
                     .coinbase
                     .to_scalar()
                     .expect("unexpected Address -> Scalar conversion failure"),
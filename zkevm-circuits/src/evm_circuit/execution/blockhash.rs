@@ -98,13 +98,13 @@ impl<F: Field> ExecutionGadget<F> for BlockHashGadget<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         block: &Block<F>,
-        _: &Transaction,
+        tx: &Transaction,
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
         self.same_context.assign_exec_step(region, offset, step)?;
 
-        let current_block_number = block.context.number;
+        let current_block_number = block.tx_context(tx).number;
         let current_block_number = current_block_number
             .to_scalar()
             .expect("unexpected U256 -> Scalar conversion failure");
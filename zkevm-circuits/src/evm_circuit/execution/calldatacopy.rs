@@ -17,12 +17,13 @@ use crate::{
     table::CallContextFieldTag,
     util::Expr,
 };
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
-use eth_types::{evm_types::GasCost, Field, ToScalar};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent},
+    evm::OpcodeId,
+};
+use eth_types::{evm_types::GasCost, Field};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-use std::cmp::min;
-
 #[derive(Clone, Debug)]
 pub(crate) struct CallDataCopyGadget<F> {
     same_context: SameContextGadget<F>,
@@ -204,32 +205,29 @@ impl<F: Field> ExecutionGadget<F> for CallDataCopyGadget<F> {
         self.data_offset
             .assign(region, offset, data_offset, F::from(call_data_length))?;
 
-        // rw_counter increase from copy lookup is `length` memory writes + a variable
-        // number of memory reads.
-        let copy_rwc_inc = length
-            + if call.is_root {
-                // no memory reads when reading from tx call data.
-                0
-            } else {
-                // memory reads when reading from memory of caller is capped by call_data_length
-                // - data_offset.
-                min(
-                    length.as_u64(),
-                    u64::try_from(data_offset)
-                        .ok()
-                        .and_then(|offset| call_data_length.checked_sub(offset))
-                        .unwrap_or_default(),
-                )
-            };
-        self.copy_rwc_inc.assign(
-            region,
-            offset,
-            Value::known(
-                copy_rwc_inc
-                    .to_scalar()
-                    .expect("unexpected U256 -> Scalar conversion failure"),
-            ),
-        )?;
+        // rw_counter increase from copy lookup is the number of memory words
+        // written + the number of memory words read from the caller, where the
+        // source address is capped by call_data_length.
+        let src_addr_end = call_data_offset + call_data_length;
+        let src_addr = u64::try_from(data_offset)
+            .ok()
+            .and_then(|offset| offset.checked_add(call_data_offset))
+            .unwrap_or(src_addr_end)
+            .min(src_addr_end);
+        let src_type = if call.is_root {
+            CopyDataType::TxCalldata
+        } else {
+            CopyDataType::Memory
+        };
+        let copy_rwc_inc = CopyEvent::rw_counter_delta_of(
+            src_type,
+            src_addr,
+            CopyDataType::Memory,
+            memory_offset.low_u64(),
+            length.low_u64(),
+        );
+        self.copy_rwc_inc
+            .assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
 
         // Memory expansion
         let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
@@ -1,5 +1,5 @@
 use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use eth_types::{Field, ToBigEndian, U256};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -16,7 +16,7 @@ use crate::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::Delta,
             },
-            memory_gadget::BufferReaderGadget,
+            memory_gadget::{BufferReaderGadget, MemoryWordsGadget},
             not, select, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -50,6 +50,9 @@ pub(crate) struct CallDataLoadGadget<F> {
     /// Gadget to read from tx calldata, which we validate against the word
     /// pushed to stack.
     buffer_reader: BufferReaderGadget<F, N_BYTES_WORD, N_BYTES_MEMORY_ADDRESS>,
+    /// The two words of the caller's memory that contain the call data of an
+    /// internal call.
+    memory_words: MemoryWordsGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
@@ -129,6 +132,26 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
 
         let buffer_reader = BufferReaderGadget::construct(cb, src_addr.expr(), src_addr_end);
 
+        // For an internal call, the call data comes from memory.
+        let memory_words = MemoryWordsGadget::construct(cb, src_addr.expr());
+        cb.condition(
+            and::expr([
+                data_offset.not_overflow(),
+                not::expr(cb.curr.state.is_root.expr()),
+            ]),
+            |cb| {
+                for idx in 0..2 {
+                    cb.memory_lookup(
+                        0.expr(),
+                        memory_words.word_index() + idx.expr(),
+                        memory_words.word(idx),
+                        memory_words.word(idx),
+                        Some(src_id.expr()),
+                    );
+                }
+            },
+        );
+
         let mut calldata_word: Vec<_> = (0..N_BYTES_WORD)
             .map(|idx| {
                 // For a root call, the call data comes from tx's data field.
@@ -155,11 +178,10 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
                         not::expr(cb.curr.state.is_root.expr()),
                     ]),
                     |cb| {
-                        cb.memory_lookup(
-                            0.expr(),
-                            src_addr.expr() + idx.expr(),
+                        cb.require_equal(
+                            "call data byte is the memory byte",
                             buffer_reader.byte(idx),
-                            Some(src_id.expr()),
+                            memory_words.byte(idx),
                         );
                     },
                 );
@@ -199,6 +221,7 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
             call_data_offset,
             data_offset,
             buffer_reader,
+            memory_words,
         }
     }
 
@@ -243,7 +266,17 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
             .min(src_addr_end);
 
         let mut calldata_bytes = vec![0u8; N_BYTES_WORD];
+        let mut words = [U256::zero(); 2];
         if offset_not_overflow {
+            if !call.is_root {
+                // Fetch the memory words.
+                words = [0, 1].map(|idx| {
+                    block
+                        .get_rws(step, OFFSET_RW_MEMORY_INDICES + idx)
+                        .memory_value()
+                });
+            }
+            let memory_bytes: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
             for (i, byte) in calldata_bytes.iter_mut().enumerate() {
                 if call.is_root {
                     // Fetch from tx call data.
@@ -253,13 +286,12 @@ impl<F: Field> ExecutionGadget<F> for CallDataLoadGadget<F> {
                 } else {
                     // Fetch from memory.
                     if src_addr + (i as u64) < call.call_data_offset + call.call_data_length {
-                        *byte = block
-                            .get_rws(step, OFFSET_RW_MEMORY_INDICES + i)
-                            .memory_value();
+                        *byte = memory_bytes[(src_addr % N_BYTES_WORD as u64) as usize + i];
                     }
                 }
             }
         }
+        self.memory_words.assign(region, offset, src_addr, words)?;
 
         self.buffer_reader.assign(
             region,
@@ -22,7 +22,10 @@ use crate::{
     table::{AccountFieldTag, CallContextFieldTag},
     util::Expr,
 };
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, precompile::is_precompiled};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent},
+    evm::OpcodeId,
+};
 use eth_types::{
     evm_types::GAS_STIPEND_CALL_WITH_VALUE, Field, ToAddress, ToLittleEndian, ToScalar, U256,
 };
@@ -65,6 +68,8 @@ pub(crate) struct CallOpGadget<F> {
     precompile_return_length: Cell<F>,
     precompile_return_length_zero: IsZeroGadget<F>,
     return_data_copy_size: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    input_copy_rwc_inc: Cell<F>,
+    output_copy_rwc_inc: Cell<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
@@ -184,10 +189,14 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
 
         // whether the call is to a precompiled contract.
-        // precompile contracts are stored from address 0x01 to 0x09.
+        // precompile contracts are stored from address 0x01 to num_precompiles.
         let is_code_address_zero = IsZeroGadget::construct(cb, call_gadget.callee_address_expr());
-        let is_precompile_lt =
-            LtGadget::construct(cb, call_gadget.callee_address_expr(), 0x0A.expr());
+        let num_precompiles = cb.chain_spec.num_precompiles() as u64;
+        let is_precompile_lt = LtGadget::construct(
+            cb,
+            call_gadget.callee_address_expr(),
+            (num_precompiles + 1).expr(),
+        );
         let is_precompile = and::expr([
             not::expr(is_code_address_zero.expr()),
             is_precompile_lt.expr(),
@@ -200,6 +209,9 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             precompile_return_length.expr(),
             call_gadget.rd_address.length(),
         );
+        // rw_counter increases of the copies of the precompile input and output.
+        let input_copy_rwc_inc = cb.query_cell();
+        let output_copy_rwc_inc = cb.query_cell();
 
         // Verify transfer only for CALL opcode in the successful case.  If value == 0,
         // skip the transfer (this is necessary for non-existing accounts, which
@@ -320,7 +332,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                         0.expr(),
                         call_gadget.cd_address.length(),
                         0.expr(),
-                        2.expr() * call_gadget.cd_address.length(), // reads + writes
+                        input_copy_rwc_inc.expr(),
                     );
                 });
 
@@ -348,7 +360,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                             call_gadget.rd_address.offset(),
                             return_data_copy_size.min(),
                             0.expr(),
-                            2.expr() * return_data_copy_size.min(), // reads + writes
+                            output_copy_rwc_inc.expr(),
                         );
                     },
                 );
@@ -589,6 +601,8 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             precompile_return_length,
             precompile_return_length_zero,
             return_data_copy_size,
+            input_copy_rwc_inc,
+            output_copy_rwc_inc,
         }
     }
 
@@ -774,16 +788,20 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let code_address: F = callee_address.to_address().to_scalar().unwrap();
         self.is_code_address_zero
             .assign(region, offset, code_address)?;
+        let num_precompiles = block.chain_spec.num_precompiles() as u64;
         self.is_precompile_lt
-            .assign(region, offset, code_address, 0x0Au64.into())?;
-        if is_precompiled(&callee_address.to_address()) {
+            .assign(region, offset, code_address, F::from(num_precompiles + 1))?;
+        let is_precompiled = block
+            .chain_spec
+            .is_precompiled(&callee_address.to_address());
+        if is_precompiled {
             self.precompile_gadget.assign(
                 region,
                 offset,
                 callee_address.to_address().0[19].into(),
             )?;
         }
-        let precompile_return_length = if is_precompiled(&callee_address.to_address()) {
+        let precompile_return_length = if is_precompiled {
             let value_rw = block.get_rws(step, 27 + rw_offset);
             assert_eq!(
                 value_rw.field_tag(),
@@ -809,6 +827,36 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             precompile_return_length.to_scalar().unwrap(),
             rd_length.to_scalar().unwrap(),
         )?;
+        let (input_copy_rwc_inc, output_copy_rwc_inc) = if is_precompiled {
+            (
+                CopyEvent::rw_counter_delta_of(
+                    CopyDataType::Memory,
+                    cd_offset.low_u64(),
+                    CopyDataType::Memory,
+                    0,
+                    cd_length.low_u64(),
+                ),
+                CopyEvent::rw_counter_delta_of(
+                    CopyDataType::Memory,
+                    0,
+                    CopyDataType::Memory,
+                    rd_offset.low_u64(),
+                    std::cmp::min(precompile_return_length, rd_length).low_u64(),
+                ),
+            )
+        } else {
+            (0, 0)
+        };
+        self.input_copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(F::from(input_copy_rwc_inc)),
+        )?;
+        self.output_copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(F::from(output_copy_rwc_inc)),
+        )?;
 
         Ok(())
     }
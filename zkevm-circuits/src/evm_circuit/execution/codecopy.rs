@@ -1,5 +1,8 @@
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
-use eth_types::{evm_types::GasCost, Field, ToScalar, ToWord};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent},
+    evm::OpcodeId,
+};
+use eth_types::{evm_types::GasCost, Field, ToWord};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 use crate::{
@@ -180,15 +183,17 @@ impl<F: Field> ExecutionGadget<F> for CodeCopyGadget<F> {
         )?;
         self.memory_copier_gas
             .assign(region, offset, size.as_u64(), memory_expansion_cost)?;
-        // rw_counter increase from copy table lookup is number of bytes copied.
-        self.copy_rwc_inc.assign(
-            region,
-            offset,
-            Value::known(
-                size.to_scalar()
-                    .expect("unexpected U256 -> Scalar conversion failure"),
-            ),
-        )?;
+        // rw_counter increase from copy table lookup is the number of memory
+        // words written.
+        let copy_rwc_inc = CopyEvent::rw_counter_delta_of(
+            CopyDataType::Bytecode,
+            code_offset.low_u64(),
+            CopyDataType::Memory,
+            dest_offset.low_u64(),
+            size.low_u64(),
+        );
+        self.copy_rwc_inc
+            .assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
 
         Ok(())
     }
@@ -11,10 +11,10 @@ use crate::{
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
-    table::{CallContextFieldTag, TxContextFieldTag},
+    table::{BlockContextFieldTag, CallContextFieldTag, TxContextFieldTag},
     util::Expr,
 };
-use eth_types::Field;
+use eth_types::{Field, ToScalar};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 #[derive(Clone, Debug)]
@@ -24,6 +24,8 @@ pub(crate) struct EndBlockGadget<F> {
     is_empty_block: IsZeroGadget<F>,
     max_rws: Cell<F>,
     max_txs: Cell<F>,
+    block_number: Cell<F>,
+    chunk_offsets: [Cell<F>; 3],
 }
 
 const EMPTY_BLOCK_N_RWS: u64 = 0;
@@ -57,6 +59,41 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
             cb.call_context_lookup(0.expr(), None, CallContextFieldTag::TxId, total_txs.expr());
         });
 
+        // 1c. An empty witness ends where it starts in the block table, while
+        // the last EndTx checks the end of a witness with txs.  Without a tx,
+        // the step has no block number, so the block of the witness is looked
+        // up by its number.
+        let block_number = cb.query_cell();
+        let chunk_offsets = [
+            (
+                BlockContextFieldTag::RwcOffset,
+                BlockContextFieldTag::RwcEnd,
+                1.expr(),
+            ),
+            (
+                BlockContextFieldTag::TxIdOffset,
+                BlockContextFieldTag::TxIdEnd,
+                0.expr(),
+            ),
+            (
+                BlockContextFieldTag::CumulativeGasUsedOffset,
+                BlockContextFieldTag::CumulativeGasUsedEnd,
+                0.expr(),
+            ),
+        ]
+        .map(|(offset_tag, end_tag, delta)| {
+            let offset = cb.query_cell();
+            cb.condition(is_empty_block.expr(), |cb| {
+                cb.block_lookup(offset_tag.expr(), Some(block_number.expr()), offset.expr());
+                cb.block_lookup(
+                    end_tag.expr(),
+                    Some(block_number.expr()),
+                    offset.expr() + delta,
+                );
+            });
+            offset
+        });
+
         // 2. If total_txs == max_txs, we know we have covered all txs from the
         // tx_table. If not, we need to check that the rest of txs in the
         // table are padding.
@@ -107,6 +144,8 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
             total_txs,
             total_txs_is_max_txs,
             is_empty_block,
+            block_number,
+            chunk_offsets,
         }
     }
 
@@ -122,7 +161,7 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
         self.is_empty_block
             .assign(region, offset, F::from(u64::from(step.rwc) - 1))?;
         let max_rws = F::from(block.circuits_params.max_rws as u64);
-        let max_rws_assigned = self.max_rws.assign(region, offset, Value::known(max_rws))?;
+        self.max_rws.assign(region, offset, Value::known(max_rws))?;
 
         let total_txs = F::from(block.txs.len() as u64);
         let max_txs = F::from(block.circuits_params.max_txs as u64);
@@ -130,12 +169,24 @@ impl<F: Field> ExecutionGadget<F> for EndBlockGadget<F> {
             .assign(region, offset, Value::known(total_txs))?;
         self.total_txs_is_max_txs
             .assign(region, offset, total_txs, max_txs)?;
-        let max_txs_assigned = self.max_txs.assign(region, offset, Value::known(max_txs))?;
+        self.max_txs.assign(region, offset, Value::known(max_txs))?;
+        self.block_number.assign(
+            region,
+            offset,
+            Value::known(block.context.number.to_scalar().unwrap()),
+        )?;
+        for (cell, value) in self.chunk_offsets.iter().zip([
+            block.context.rwc_offset,
+            block.context.tx_id_offset,
+            block.context.cumulative_gas_used_offset,
+        ]) {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
         // When rw_indices is not empty, we're at the last row (at a fixed offset),
         // where we need to access the max_rws and max_txs constant.
         if !step.rw_indices_len() == 0 {
-            region.constrain_constant(max_rws_assigned, max_rws)?;
-            region.constrain_constant(max_txs_assigned, max_txs)?;
+            region.constrain_constant(&self.max_rws, offset, max_rws)?;
+            region.constrain_constant(&self.max_txs, offset, max_txs)?;
         }
         Ok(())
     }
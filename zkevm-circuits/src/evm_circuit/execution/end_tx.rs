@@ -10,7 +10,7 @@ use crate::{
                 Transition::{Delta, Same},
             },
             math_gadget::{
-                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, MinMaxGadget,
+                AddWordsGadget, ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, MinMaxGadget,
                 MinMaxWordGadget, MulWordByU64Gadget,
             },
             CachedRegion, Cell, Word,
@@ -24,7 +24,7 @@ use bus_mapping::{
     circuit_input_builder::protocol_instance::{self},
     operation::Target,
 };
-use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, Field, ToLittleEndian, ToScalar};
+use eth_types::{Field, ToLittleEndian, ToScalar};
 use gadgets::util::not;
 use halo2_proofs::{circuit::Value, plonk::Error};
 use strum::EnumCount;
@@ -45,8 +45,15 @@ pub(crate) struct EndTxGadget<F> {
     mul_effective_tip_by_gas_used: MulWordByU64Gadget<F>,
     coinbase: Cell<F>,
     coinbase_reward: UpdateBalanceGadget<F, 2, true>,
+    prev_tx_cumulative_gas_used: Cell<F>,
     current_cumulative_gas_used: Cell<F>,
     is_first_tx: IsEqualGadget<F>,
+    rwc_offset: Cell<F>,
+    tx_id_offset: Cell<F>,
+    cumulative_gas_used_offset: Cell<F>,
+    is_first_chunk: IsZeroGadget<F>,
+    prev_tx_block_number: Cell<F>,
+    is_same_block_as_prev_tx: IsEqualGadget<F>,
     is_persistent: Cell<F>,
     tx_gas_tip_cap: Word<F>,
     // taiko
@@ -76,11 +83,9 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
         // Calculate effective gas to refund
         let gas_used = tx_gas.expr() - cb.curr.state.gas_left.expr();
-        let max_refund = ConstantDivisionGadget::construct(
-            cb,
-            gas_used.clone(),
-            MAX_REFUND_QUOTIENT_OF_GAS_USED as u64,
-        );
+        let max_refund_quotient = cb.chain_spec.max_refund_quotient();
+        let max_refund =
+            ConstantDivisionGadget::construct(cb, gas_used.clone(), max_refund_quotient);
         let refund = cb.query_cell();
         cb.tx_refund_read(tx_id.expr(), refund.expr());
         let effective_refund = MinMaxGadget::construct(cb, max_refund.quotient(), refund.expr());
@@ -100,16 +105,64 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
         let is_first_tx = IsEqualGadget::construct(cb, tx_id.expr(), 1.expr());
 
+        // When a block is split into chunks, the first tx of a chunk follows the
+        // txs of the previous chunks, which are counted in the block table.
+        let [rwc_offset, tx_id_offset, cumulative_gas_used_offset] = [
+            BlockContextFieldTag::RwcOffset,
+            BlockContextFieldTag::TxIdOffset,
+            BlockContextFieldTag::CumulativeGasUsedOffset,
+        ]
+        .map(|tag| {
+            let cell = cb.query_cell();
+            cb.block_lookup(tag.expr(), None, cell.expr());
+            cell
+        });
+        let is_first_chunk = IsZeroGadget::construct(cb, tx_id_offset.expr());
+
+        // When proving a batch of blocks, the block of the previous tx tells whether
+        // this tx is the first one of its block.  The first tx of the batch has no
+        // previous tx, so it's compared against the number of the previous block,
+        // or against its own block when it continues a block split into chunks.
+        cb.tx_context_lookup(
+            tx_id.expr(),
+            TxContextFieldTag::BlockNumber,
+            None,
+            cb.curr.state.block_number.expr(),
+        );
+        let prev_tx_block_number = cb.query_cell();
+        cb.condition(is_first_tx.expr(), |cb| {
+            cb.require_equal(
+                "prev_tx_block_number is the previous block when tx is first tx of the block",
+                prev_tx_block_number.expr(),
+                cb.curr.state.block_number.expr() - is_first_chunk.expr(),
+            );
+        });
+        cb.condition(not::expr(is_first_tx.expr()), |cb| {
+            cb.tx_context_lookup(
+                tx_id.expr() - 1.expr(),
+                TxContextFieldTag::BlockNumber,
+                None,
+                prev_tx_block_number.expr(),
+            );
+        });
+        let block_number = cb.curr.state.block_number.expr();
+        let is_same_block_as_prev_tx =
+            IsEqualGadget::construct(cb, prev_tx_block_number.expr(), block_number);
+        let is_first_tx_in_block = not::expr(is_same_block_as_prev_tx.expr());
+
         // Add gas_used * effective_tip to coinbase's balance
         let coinbase = cb.query_cell();
         cb.block_lookup(BlockContextFieldTag::Coinbase.expr(), None, coinbase.expr());
         let base_fee = cb.query_word_rlc();
 
         if cb.is_taiko {
-            cb.condition(is_first_tx.expr(), |cb| {
-                cb.require_zero("base_fee is zero when tx is first tx", base_fee.expr());
+            cb.condition(is_first_tx_in_block.expr(), |cb| {
+                cb.require_zero(
+                    "base_fee is zero when tx is first tx of the block",
+                    base_fee.expr(),
+                );
             });
-            cb.condition(not::expr(is_first_tx.expr()), |cb| {
+            cb.condition(not::expr(is_first_tx_in_block.expr()), |cb| {
                 cb.block_lookup(BlockContextFieldTag::BaseFee.expr(), None, base_fee.expr());
             });
         } else {
@@ -182,20 +235,36 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             cb.curr.state.log_id.expr(),
         );
 
-        let current_cumulative_gas_used = cb.query_cell();
+        let prev_tx_cumulative_gas_used = cb.query_cell();
         cb.condition(is_first_tx.expr(), |cb| {
-            cb.require_zero(
-                "current_cumulative_gas_used is zero when tx is first tx",
-                current_cumulative_gas_used.expr(),
+            cb.require_equal(
+                "prev_tx_cumulative_gas_used is the one of the previous chunks when tx is first tx",
+                prev_tx_cumulative_gas_used.expr(),
+                cumulative_gas_used_offset.expr(),
             );
         });
-
         cb.condition(1.expr() - is_first_tx.expr(), |cb| {
             cb.tx_receipt_lookup(
                 0.expr(),
                 tx_id.expr() - 1.expr(),
                 TxReceiptFieldTag::CumulativeGasUsed,
+                prev_tx_cumulative_gas_used.expr(),
+            );
+        });
+
+        // Cumulative gas used restarts at the first tx of every block
+        let current_cumulative_gas_used = cb.query_cell();
+        cb.condition(is_first_tx_in_block.expr(), |cb| {
+            cb.require_zero(
+                "current_cumulative_gas_used is zero when tx is first tx of the block",
+                current_cumulative_gas_used.expr(),
+            );
+        });
+        cb.condition(is_same_block_as_prev_tx.expr(), |cb| {
+            cb.require_equal(
+                "current_cumulative_gas_used is the one of the previous tx in the block",
                 current_cumulative_gas_used.expr(),
+                prev_tx_cumulative_gas_used.expr(),
             );
         });
 
@@ -203,7 +272,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             1.expr(),
             tx_id.expr(),
             TxReceiptFieldTag::CumulativeGasUsed,
-            gas_used + current_cumulative_gas_used.expr(),
+            gas_used.clone() + current_cumulative_gas_used.expr(),
         );
 
         let begin_tx_rw_counter = if cb.is_taiko { 11.expr() } else { 10.expr() };
@@ -237,6 +306,25 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
                     call_id: Same,
                     ..StepStateTransition::any()
                 });
+
+                // The last tx ends the witness at the values of the block table
+                // committed by the PI circuit, which a chunk following this one
+                // starts from.
+                cb.block_lookup(
+                    BlockContextFieldTag::RwcEnd.expr(),
+                    None,
+                    rwc_offset.expr() + cb.next.state.rw_counter.expr(),
+                );
+                cb.block_lookup(
+                    BlockContextFieldTag::TxIdEnd.expr(),
+                    None,
+                    tx_id_offset.expr() + tx_id.expr(),
+                );
+                cb.block_lookup(
+                    BlockContextFieldTag::CumulativeGasUsedEnd.expr(),
+                    None,
+                    gas_used + current_cumulative_gas_used.expr(),
+                );
             },
         );
 
@@ -255,8 +343,15 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             mul_effective_tip_by_gas_used,
             coinbase,
             coinbase_reward,
+            prev_tx_cumulative_gas_used,
             current_cumulative_gas_used,
             is_first_tx,
+            rwc_offset,
+            tx_id_offset,
+            cumulative_gas_used_offset,
+            is_first_chunk,
+            prev_tx_block_number,
+            is_same_block_as_prev_tx,
             is_persistent,
             tx_gas_tip_cap,
             mul_base_fee_by_gas_used,
@@ -292,10 +387,18 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             F::from(refund),
         )?;
 
-        let base_fee = if block.is_taiko() && tx.is_first_tx() {
+        let context = block.tx_context(tx);
+        let is_first_chunk = context.tx_id_offset == 0;
+        let prev_tx_block_number = if tx.is_first_tx() {
+            F::from(tx.block_number) - F::from(is_first_chunk as u64)
+        } else {
+            F::from(block.txs[tx.id - 2].block_number)
+        };
+        let is_first_tx_in_block = prev_tx_block_number != F::from(tx.block_number);
+        let base_fee = if block.is_taiko() && is_first_tx_in_block {
             0.into()
         } else {
-            block.context.base_fee
+            context.base_fee
         };
         self.tx_gas_tip_cap
             .assign(region, offset, Some(tx.gas_tip_cap.to_le_bytes()))?;
@@ -355,8 +458,7 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             region,
             offset,
             Value::known(
-                block
-                    .context
+                context
                     .coinbase
                     .to_scalar()
                     .expect("unexpected Address -> Scalar conversion failure"),
@@ -396,8 +498,8 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
                 treasury_balance,
             )?;
         }
-        let current_cumulative_gas_used: u64 = if tx.id == 1 {
-            0
+        let prev_tx_cumulative_gas_used: u64 = if tx.id == 1 {
+            context.cumulative_gas_used_offset
         } else {
             // first transaction needs TxReceiptFieldTag::COUNT(3) lookups to tx receipt,
             // while later transactions need 4 (with one extra cumulative gas read) lookups
@@ -408,6 +510,17 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             rw.receipt_value()
         };
 
+        let current_cumulative_gas_used = if is_first_tx_in_block {
+            0
+        } else {
+            prev_tx_cumulative_gas_used
+        };
+
+        self.prev_tx_cumulative_gas_used.assign(
+            region,
+            offset,
+            Value::known(F::from(prev_tx_cumulative_gas_used)),
+        )?;
         self.current_cumulative_gas_used.assign(
             region,
             offset,
@@ -415,6 +528,25 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
         )?;
         self.is_first_tx
             .assign(region, offset, F::from(tx.id as u64), F::ONE)?;
+        self.rwc_offset
+            .assign(region, offset, Value::known(F::from(context.rwc_offset)))?;
+        self.tx_id_offset
+            .assign(region, offset, Value::known(F::from(context.tx_id_offset)))?;
+        self.cumulative_gas_used_offset.assign(
+            region,
+            offset,
+            Value::known(F::from(context.cumulative_gas_used_offset)),
+        )?;
+        self.is_first_chunk
+            .assign(region, offset, F::from(context.tx_id_offset))?;
+        self.prev_tx_block_number
+            .assign(region, offset, Value::known(prev_tx_block_number))?;
+        self.is_same_block_as_prev_tx.assign(
+            region,
+            offset,
+            prev_tx_block_number,
+            F::from(tx.block_number),
+        )?;
         self.is_persistent.assign(
             region,
             offset,
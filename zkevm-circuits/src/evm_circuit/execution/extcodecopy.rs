@@ -16,8 +16,8 @@ use crate::{
     },
     table::{AccountFieldTag, CallContextFieldTag},
 };
-use bus_mapping::circuit_input_builder::CopyDataType;
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
 use gadgets::util::Expr;
 use halo2_proofs::{circuit::Value, plonk::Error};
 
@@ -207,15 +207,15 @@ impl<F: Field> ExecutionGadget<F> for ExtcodecopyGadget<F> {
         self.code_offset
             .assign(region, offset, code_offset, F::from(code_size))?;
 
-        self.copy_rwc_inc.assign(
-            region,
-            offset,
-            Value::known(
-                memory_length
-                    .to_scalar()
-                    .expect("unexpected U256 -> Scalar conversion failure"),
-            ),
-        )?;
+        let copy_rwc_inc = CopyEvent::rw_counter_delta_of(
+            CopyDataType::Bytecode,
+            code_offset.low_u64(),
+            CopyDataType::Memory,
+            memory_offset.low_u64(),
+            memory_length.low_u64(),
+        );
+        self.copy_rwc_inc
+            .assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
 
         let (_, memory_expansion_gas_cost) = self.memory_expansion.assign(
             region,
@@ -18,10 +18,10 @@ use crate::{
     util::{build_tx_log_expression, Expr},
 };
 use array_init::array_init;
-use bus_mapping::circuit_input_builder::CopyDataType;
+use bus_mapping::circuit_input_builder::{CopyDataType, CopyEvent};
 use eth_types::{
     evm_types::{GasCost, OpcodeId},
-    Field, ToScalar, U256,
+    Field, ToScalar,
 };
 use halo2_proofs::{circuit::Value, plonk::Error};
 
@@ -119,6 +119,24 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
         // check memory copy
         let memory_address = MemoryAddressGadget::construct(cb, mstart, msize);
 
+        // topic and data lengths are recorded for the RLP encoding of the receipt
+        cb.condition(is_persistent.expr(), |cb| {
+            cb.tx_log_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::TopicLength,
+                0.expr(),
+                topic_count.clone(),
+            );
+            cb.tx_log_lookup(
+                tx_id.expr(),
+                cb.curr.state.log_id.expr() + 1.expr(),
+                TxLogFieldTag::DataLength,
+                0.expr(),
+                memory_address.length(),
+            );
+        });
+
         // Calculate the next memory size and the gas cost for this memory
         // access
         let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
@@ -248,17 +266,21 @@ impl<F: Field> ExecutionGadget<F> for LogGadget<F> {
             .assign(region, offset, Value::known(F::from(is_persistent)))?;
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
-        // rw_counter increase from copy table lookup is `msize` memory reads + `msize`
-        // log writes when `is_persistent` is true.
-        self.copy_rwc_inc.assign(
-            region,
-            offset,
-            Value::known(
-                ((msize + msize) * U256::from(is_persistent))
-                    .to_scalar()
-                    .expect("unexpected U256 -> Scalar conversion failure"),
-            ),
-        )?;
+        // rw_counter increase from copy table lookup is the number of memory
+        // words read + `msize` log writes when `is_persistent` is true.
+        let copy_rwc_inc = if call.is_persistent {
+            CopyEvent::rw_counter_delta_of(
+                CopyDataType::Memory,
+                memory_start.low_u64(),
+                CopyDataType::TxLog,
+                0,
+                msize.low_u64(),
+            )
+        } else {
+            0
+        };
+        self.copy_rwc_inc
+            .assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
 
         Ok(())
     }
@@ -1,7 +1,7 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_WORD},
         step::ExecutionState,
         util::{
             common_gadget::SameContextGadget,
@@ -11,14 +11,15 @@ use crate::{
             },
             from_bytes,
             math_gadget::IsEqualGadget,
-            memory_gadget::MemoryExpansionGadget,
+            memory_gadget::{MemoryExpansionGadget, MemoryWordsGadget},
             not, CachedRegion, MemoryAddress, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
-use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian};
+use array_init::array_init;
+use eth_types::{evm_types::OpcodeId, Field, ToLittleEndian, U256};
 use halo2_proofs::plonk::Error;
 
 #[derive(Clone, Debug)]
@@ -26,6 +27,8 @@ pub(crate) struct MemoryGadget<F> {
     same_context: SameContextGadget<F>,
     address: MemoryAddress<F>,
     value: Word<F>,
+    memory_words: MemoryWordsGadget<F>,
+    words_prev: [Word<F>; 2],
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     is_mload: IsEqualGadget<F>,
     is_mstore8: IsEqualGadget<F>,
@@ -70,35 +73,57 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
             value.expr(),
         );
 
+        // The accessed bytes are in the two memory words from the word that
+        // contains the address, and `words_prev` are the words before the
+        // access. A read has the same words before and after.
+        let memory_words = MemoryWordsGadget::construct(cb, from_bytes::expr(&address.cells));
+        let words_prev = array_init(|_| cb.query_word_rlc());
+
         cb.condition(is_mstore8.expr(), |cb| {
+            cb.require_equal(
+                "MSTORE8 writes the least significant byte",
+                value.cells[0].expr(),
+                memory_words.byte(0),
+            );
+            memory_words.require_unchanged_outside(cb, &words_prev, 1);
             cb.memory_lookup(
                 1.expr(),
-                from_bytes::expr(&address.cells),
-                value.cells[0].expr(),
+                memory_words.word_index(),
+                memory_words.word(0),
+                words_prev[0].expr(),
                 None,
             );
         });
 
         cb.condition(is_not_mstore8, |cb| {
-            for idx in 0..32 {
+            for idx in 0..N_BYTES_WORD {
+                cb.require_equal(
+                    "value bytes are the memory bytes",
+                    value.cells[N_BYTES_WORD - 1 - idx].expr(),
+                    memory_words.byte(idx),
+                );
+            }
+            memory_words.require_unchanged_outside(cb, &words_prev, N_BYTES_WORD);
+            for (idx, word_prev) in words_prev.iter().enumerate() {
                 cb.memory_lookup(
                     is_store.clone(),
-                    from_bytes::expr(&address.cells) + idx.expr(),
-                    value.cells[31 - idx].expr(),
+                    memory_words.word_index() + idx.expr(),
+                    memory_words.word(idx),
+                    word_prev.expr(),
                     None,
                 );
             }
         });
 
         // State transition
-        // - `rw_counter` needs to be increased by 34 when is_not_mstore8, otherwise to be increased
-        //   by 31
+        // - `rw_counter` needs to be increased by 4 when is_not_mstore8, otherwise to be increased
+        //   by 3
         // - `program_counter` needs to be increased by 1
         // - `stack_pointer` needs to be increased by 2 when is_store, otherwise to be same
         // - `memory_size` needs to be set to `next_memory_size`
         let gas_cost = OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost();
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(34.expr() - is_mstore8.expr() * 31.expr()),
+            rw_counter: Delta(4.expr() - is_mstore8.expr()),
             program_counter: Delta(1.expr()),
             stack_pointer: Delta(is_store * 2.expr()),
             gas_left: Delta(-gas_cost),
@@ -111,6 +136,8 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
             same_context,
             address,
             value,
+            memory_words,
+            words_prev,
             memory_expansion,
             is_mload,
             is_mstore8,
@@ -159,6 +186,27 @@ impl<F: Field> ExecutionGadget<F> for MemoryGadget<F> {
             F::from(OpcodeId::MSTORE8.as_u64()),
         )?;
 
+        // Memory words, the second one is not accessed by MSTORE8
+        let num_words = if is_mstore8 == F::ONE { 1 } else { 2 };
+        let memory_rws: Vec<_> = (0..num_words)
+            .map(|idx| block.get_rws(step, 2 + idx))
+            .collect();
+        let words = array_init(|idx| {
+            memory_rws
+                .get(idx)
+                .map_or(U256::zero(), |rw| rw.memory_value())
+        });
+        let words_prev: [U256; 2] = array_init(|idx| {
+            memory_rws
+                .get(idx)
+                .map_or(U256::zero(), |rw| rw.memory_value_prev())
+        });
+        self.memory_words
+            .assign(region, offset, address.as_u64(), words)?;
+        for (word, value) in self.words_prev.iter().zip(words_prev) {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+
         // Memory expansion
         self.memory_expansion.assign(
             region,
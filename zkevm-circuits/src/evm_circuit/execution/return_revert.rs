@@ -18,7 +18,11 @@ use crate::{
     table::{AccountFieldTag, CallContextFieldTag},
     util::Expr,
 };
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, state_db::CodeDB};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent},
+    evm::OpcodeId,
+    state_db::CodeDB,
+};
 use eth_types::{Field, ToScalar, U256};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
@@ -32,6 +36,7 @@ pub(crate) struct ReturnRevertGadget<F> {
     restore_context: RestoreContextGadget<F>,
 
     copy_length: MinMaxGadget<F, N_BYTES_MEMORY_ADDRESS>,
+    copy_length_is_zero: IsZeroGadget<F>,
     copy_rw_increase: Cell<F>,
     copy_rw_increase_is_zero: IsZeroGadget<F>,
 
@@ -86,9 +91,9 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         // Case A in the specs.
         cb.condition(is_create.clone() * is_success.expr(), |cb| {
             cb.require_equal(
-                "increase rw counter once for each memory to bytecode byte copied",
-                copy_rw_increase.expr(),
-                range.length(),
+                "increase rw counter for memory to bytecode copy iff length is not 0",
+                copy_rw_increase_is_zero.expr(),
+                not::expr(range.has_length()),
             );
         });
 
@@ -168,24 +173,31 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         });
 
         // Case D in the specs.
-        let (return_data_offset, return_data_length, copy_length) = cb.condition(
-            not::expr(is_create.clone()) * not::expr(is_root.clone()),
-            |cb| {
-                let [return_data_offset, return_data_length] = [
-                    CallContextFieldTag::ReturnDataOffset,
-                    CallContextFieldTag::ReturnDataLength,
-                ]
-                .map(|field_tag| cb.call_context(None, field_tag));
-                let copy_length =
-                    MinMaxGadget::construct(cb, return_data_length.expr(), range.length());
-                cb.require_equal(
-                    "increase rw counter twice for each memory to memory byte copied",
-                    copy_length.min() + copy_length.min(),
-                    copy_rw_increase.expr(),
-                );
-                (return_data_offset, return_data_length, copy_length)
-            },
-        );
+        let (return_data_offset, return_data_length, copy_length, copy_length_is_zero) = cb
+            .condition(
+                not::expr(is_create.clone()) * not::expr(is_root.clone()),
+                |cb| {
+                    let [return_data_offset, return_data_length] = [
+                        CallContextFieldTag::ReturnDataOffset,
+                        CallContextFieldTag::ReturnDataLength,
+                    ]
+                    .map(|field_tag| cb.call_context(None, field_tag));
+                    let copy_length =
+                        MinMaxGadget::construct(cb, return_data_length.expr(), range.length());
+                    let copy_length_is_zero = IsZeroGadget::construct(cb, copy_length.min());
+                    cb.require_equal(
+                        "increase rw counter for memory to memory copy iff copy_length is not 0",
+                        copy_rw_increase_is_zero.expr(),
+                        copy_length_is_zero.expr(),
+                    );
+                    (
+                        return_data_offset,
+                        return_data_length,
+                        copy_length,
+                        copy_length_is_zero,
+                    )
+                },
+            );
         cb.condition(
             not::expr(is_create.clone())
                 * not::expr(is_root.clone())
@@ -220,6 +232,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
             range,
             is_success,
             copy_length,
+            copy_length_is_zero,
             copy_rw_increase,
             copy_rw_increase_is_zero,
             return_data_offset,
@@ -273,12 +286,15 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
                 F::from(call.return_data_length),
                 F::from(length.as_u64()),
             )?;
+            self.copy_length_is_zero.assign(
+                region,
+                offset,
+                F::from(std::cmp::min(call.return_data_length, length.as_u64())),
+            )?;
         }
 
         if call.is_create() && call.is_success {
-            let values: Vec<_> = (3..3 + length.as_usize())
-                .map(|index| block.get_rws(step, index).memory_value())
-                .collect();
+            let values = block.get_memory_bytes(step, 3, memory_offset.low_u64(), length.as_u64());
             let mut code_hash = CodeDB::hash(&values).to_fixed_bytes();
             code_hash.reverse();
             self.code_hash.assign(
@@ -289,9 +305,21 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         }
 
         let copy_rw_increase = if call.is_create() && call.is_success {
-            length.as_u64()
+            CopyEvent::rw_counter_delta_of(
+                CopyDataType::Memory,
+                memory_offset.low_u64(),
+                CopyDataType::Bytecode,
+                0,
+                length.as_u64(),
+            )
         } else if !call.is_root {
-            2 * std::cmp::min(call.return_data_length, length.as_u64())
+            CopyEvent::rw_counter_delta_of(
+                CopyDataType::Memory,
+                memory_offset.low_u64(),
+                CopyDataType::Memory,
+                call.return_data_offset,
+                std::cmp::min(call.return_data_length, length.as_u64()),
+            )
         } else {
             0
         };
@@ -303,7 +331,7 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
         let is_contract_deployment = call.is_create() && call.is_success && !length.is_zero();
         if !call.is_root {
             let rw_counter_offset = 3 + if is_contract_deployment {
-                5 + length.as_u64()
+                5 + copy_rw_increase
             } else {
                 0
             };
@@ -19,7 +19,10 @@ use crate::{
     table::CallContextFieldTag,
     util::Expr,
 };
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent},
+    evm::OpcodeId,
+};
 use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
 use gadgets::util::not;
 use halo2_proofs::{circuit::Value, plonk::Error};
@@ -241,17 +244,17 @@ impl<F: Field> ExecutionGadget<F> for ReturnDataCopyGadget<F> {
         self.memory_copier_gas
             .assign(region, offset, size.as_u64(), memory_expansion_cost)?;
 
-        // rw_counter always increases by `size` reads and `size` writes
-        let copy_rwc_inc = size + size;
-        self.copy_rwc_inc.assign(
-            region,
-            offset,
-            Value::known(
-                copy_rwc_inc
-                    .to_scalar()
-                    .expect("unexpected U256 -> Scalar conversion failure"),
-            ),
-        )?;
+        // rw_counter increases by the number of memory words read from the
+        // last callee and written to the current call.
+        let copy_rwc_inc = CopyEvent::rw_counter_delta_of(
+            CopyDataType::Memory,
+            return_data_offset.low_u64() + data_offset.low_u64(),
+            CopyDataType::Memory,
+            dest_offset.low_u64(),
+            size.low_u64(),
+        );
+        self.copy_rwc_inc
+            .assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
 
         self.in_bound_check.assign(
             region,
@@ -1,5 +1,8 @@
-use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar};
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, CopyEvent},
+    evm::OpcodeId,
+};
+use eth_types::{evm_types::GasCost, Field, ToLittleEndian};
 use gadgets::util::{not, Expr};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
@@ -121,18 +124,19 @@ impl<F: Field> ExecutionGadget<F> for Sha3Gadget<F> {
         self.sha3_rlc
             .assign(region, offset, Some(sha3_output.to_le_bytes()))?;
 
-        self.copy_rwc_inc.assign(
-            region,
-            offset,
-            Value::known(
-                size.to_scalar()
-                    .expect("unexpected U256 -> Scalar conversion failure"),
-            ),
-        )?;
+        // rw_counter increase from copy table lookup is the number of memory
+        // words read.
+        let copy_rwc_inc = CopyEvent::rw_counter_delta_of(
+            CopyDataType::Memory,
+            memory_offset.low_u64(),
+            CopyDataType::RlcAcc,
+            0,
+            size.low_u64(),
+        );
+        self.copy_rwc_inc
+            .assign(region, offset, Value::known(F::from(copy_rwc_inc)))?;
 
-        let values: Vec<u8> = (3..3 + (size.low_u64() as usize))
-            .map(|i| block.get_rws(step, i).memory_value())
-            .collect();
+        let values = block.get_memory_bytes(step, 3, memory_offset.low_u64(), size.low_u64());
 
         let rlc_acc = region
             .challenges()
@@ -230,6 +230,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
 
 #[derive(Clone, Debug)]
 pub(crate) struct SstoreTxRefundGadget<F> {
+    sstore_clears_schedule: u64,
     tx_refund_old: Cell<F>,
     tx_refund_new: Expression<F>,
     value: Cell<F>,
@@ -287,13 +288,15 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         let recreate_slot =
             not::expr(prev_eq_value) * not::expr(original_eq_prev) * (value_prev_is_zero);
 
+        let sstore_clears_schedule = cb.chain_spec.sstore_clears_schedule().as_u64();
         let tx_refund_new = tx_refund_old.expr()
-            + delete_slot * GasCost::SSTORE_CLEARS_SCHEDULE.expr()
+            + delete_slot * sstore_clears_schedule.expr()
             + reset_existing * (GasCost::SSTORE_RESET.expr() - GasCost::WARM_ACCESS.expr())
             + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr())
-            - recreate_slot * (GasCost::SSTORE_CLEARS_SCHEDULE.expr());
+            - recreate_slot * sstore_clears_schedule.expr();
 
         Self {
+            sstore_clears_schedule,
             value,
             value_prev,
             original_value,
@@ -359,7 +362,13 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             region.word_rlc(value_prev),
         )?;
         debug_assert_eq!(
-            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
+            calc_expected_tx_refund(
+                self.sstore_clears_schedule,
+                tx_refund_old,
+                value,
+                value_prev,
+                original_value
+            ),
             tx_refund
         );
         Ok(())
@@ -367,6 +376,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
 }
 
 fn calc_expected_tx_refund(
+    sstore_clears_schedule: u64,
     tx_refund_old: u64,
     value: eth_types::Word,
     value_prev: eth_types::Word,
@@ -397,11 +407,11 @@ fn calc_expected_tx_refund(
         if !original_value.is_zero() {
             if value_prev.is_zero() {
                 // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+                tx_refund_new -= sstore_clears_schedule
             }
             if value.is_zero() {
                 // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
+                tx_refund_new += sstore_clears_schedule
             }
         }
 
@@ -1,10 +1,14 @@
 use super::{table::Table, EvmCircuitParams};
-use crate::evm_circuit::{step::ExecutionState, EvmCircuit};
+use crate::evm_circuit::{
+    step::{ExecutionState, StepState},
+    EvmCircuit,
+};
 use halo2_proofs::{
     halo2curves::bn256::Fr,
     plonk::{Circuit, ConstraintSystem},
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Mutex};
+use strum_macros::EnumIter;
 
 // Step dimension
 pub(crate) const STEP_WIDTH: usize = 128;
@@ -73,6 +77,99 @@ pub const KECCAK_TABLE_LOOKUPS: usize = 1;
 /// Exp Table lookups done in EVMCircuit
 pub const EXP_TABLE_LOOKUPS: usize = 1;
 
+/// Shape of the EVM circuit steps, trading advice columns for rows.
+///
+/// The execution gadgets are the same in all the layouts, which only differ in
+/// the number of advice columns of each kind and in the maximum height of a
+/// step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, EnumIter)]
+pub enum EvmLayout {
+    /// Twice the columns of [`EvmLayout::Standard`], with steps half as tall.
+    Wide,
+    /// The columns described by the constants of this module.
+    #[default]
+    Standard,
+    /// About two thirds of the columns of [`EvmLayout::Standard`], with steps
+    /// twice as tall.
+    Narrow,
+}
+
+impl EvmLayout {
+    /// Number of columns of a kind that has `count` columns in the standard
+    /// layout.
+    fn scale(&self, count: usize) -> usize {
+        match self {
+            Self::Wide => count * 2,
+            Self::Standard => count,
+            Self::Narrow => (count + 1) / 2,
+        }
+    }
+
+    /// Step height
+    pub fn max_step_height(&self) -> usize {
+        match self {
+            Self::Wide => (MAX_STEP_HEIGHT + 1) / 2,
+            Self::Standard => MAX_STEP_HEIGHT,
+            Self::Narrow => MAX_STEP_HEIGHT * 2,
+        }
+    }
+
+    /// Lookups done per row.
+    pub fn lookup_config(&self) -> Vec<(Table, usize)> {
+        LOOKUP_CONFIG
+            .iter()
+            .map(|&(table, count)| (table, self.scale(count)))
+            .collect()
+    }
+
+    /// Amount of lookup columns in the EVM circuit dedicated to lookups.
+    pub fn lookup_columns(&self) -> usize {
+        self.lookup_config().iter().map(|(_, count)| count).sum()
+    }
+
+    /// Number of Advice Phase2 columns in the EVM circuit
+    pub fn n_phase2_columns(&self) -> usize {
+        self.scale(N_PHASE2_COLUMNS)
+    }
+
+    /// Number of copy columns
+    pub fn n_copy_columns(&self) -> usize {
+        self.scale(N_COPY_COLUMNS)
+    }
+
+    /// Number of byte lookup columns
+    pub fn n_byte_lookups(&self) -> usize {
+        self.scale(N_BYTE_LOOKUPS)
+    }
+
+    /// Number of Advice Phase1 columns in the EVM circuit
+    pub fn n_phase1_columns(&self) -> usize {
+        // The state of the next step is queried in STEP_STATE_HEIGHT rows, so its
+        // phase1 cells, which can also use the copy columns, can't be spread over
+        // a taller step.
+        let n_step_state_columns = (StepState::<Fr>::n_phase1_cells() + STEP_STATE_HEIGHT - 1)
+            / STEP_STATE_HEIGHT
+            - self.n_copy_columns();
+        self.scale(N_PHASE1_COLUMNS).max(n_step_state_columns)
+    }
+
+    /// Number of advice columns of a step.
+    pub fn step_width(&self) -> usize {
+        self.lookup_columns()
+            + self.n_phase2_columns()
+            + self.n_copy_columns()
+            + self.n_byte_lookups()
+            + self.n_phase1_columns()
+    }
+
+    /// Number of unusable rows of the EVM circuit with this layout.
+    pub fn unusable_rows(&self) -> usize {
+        // Most columns are queried at max_step_height + STEP_STATE_HEIGHT distinct
+        // rotations, so returns (max_step_height + STEP_STATE_HEIGHT + 3) unusable rows.
+        self.max_step_height() + STEP_STATE_HEIGHT + 3
+    }
+}
+
 /// Maximum number of bytes that an integer can fit in field without wrapping
 /// around.
 pub(crate) const MAX_N_BYTES_INTEGER: usize = 31;
@@ -106,21 +203,39 @@ pub(crate) const N_BYTES_GAS: usize = N_BYTES_U64;
 // Number of bytes that will be used for call data's size.
 pub(crate) const N_BYTES_CALLDATASIZE: usize = N_BYTES_U64;
 
+type HeightMaps = HashMap<EvmCircuitParams, &'static HashMap<ExecutionState, usize>>;
+
 lazy_static::lazy_static! {
-    // Step slot height in evm circuit
-    pub(crate) static ref EXECUTION_STATE_HEIGHT_MAP : HashMap<ExecutionState, usize> = get_step_height_map();
-    pub(crate) static ref EXECUTION_STATE_HEIGHT_MAP_WITH_TAIKO : HashMap<ExecutionState, usize> = get_step_height_map_with_taiko();
+    // Step slot heights in evm circuit, for each of its configurations
+    static ref EXECUTION_STATE_HEIGHT_MAPS: Mutex<HeightMaps> = Default::default();
 }
-fn get_step_height_map() -> HashMap<ExecutionState, usize> {
-    let mut meta = ConstraintSystem::<Fr>::default();
-    let circuit = EvmCircuit::configure(&mut meta);
 
-    circuit.0.execution.height_map
+/// Step slot heights of the EVM circuit configured with `params`, which are
+/// calculated on first use.
+pub(crate) fn execution_state_height_map(
+    params: EvmCircuitParams,
+) -> &'static HashMap<ExecutionState, usize> {
+    if let Some(height_map) = EXECUTION_STATE_HEIGHT_MAPS
+        .lock()
+        .unwrap()
+        .get(&params)
+        .copied()
+    {
+        return height_map;
+    }
+    // Configure the circuit without holding the lock, if another thread raced us
+    // the first map inserted is kept.
+    let height_map = get_step_height_map(params);
+    *EXECUTION_STATE_HEIGHT_MAPS
+        .lock()
+        .unwrap()
+        .entry(params)
+        .or_insert_with(|| Box::leak(Box::new(height_map)))
 }
 
-fn get_step_height_map_with_taiko() -> HashMap<ExecutionState, usize> {
+fn get_step_height_map(params: EvmCircuitParams) -> HashMap<ExecutionState, usize> {
     let mut meta = ConstraintSystem::<Fr>::default();
-    let circuit = EvmCircuit::configure_with_params(&mut meta, EvmCircuitParams { is_taiko: true });
+    let circuit = EvmCircuit::configure_with_params(&mut meta, params);
 
     circuit.0.execution.height_map
 }
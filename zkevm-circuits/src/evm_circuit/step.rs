@@ -1,12 +1,13 @@
 use super::{
-    param::EXECUTION_STATE_HEIGHT_MAP_WITH_TAIKO,
+    param::{execution_state_height_map, EvmLayout},
     util::{CachedRegion, CellManager, CellType},
+    EvmCircuitParams,
 };
 use crate::{
     evm_circuit::{
-        param::{EXECUTION_STATE_HEIGHT_MAP, MAX_STEP_HEIGHT, STEP_STATE_HEIGHT, STEP_WIDTH},
+        param::STEP_STATE_HEIGHT,
         util::Cell,
-        witness::{Block, Call, ExecStep},
+        witness::{Block, Call, ExecStep, Transaction},
     },
     util::Expr,
 };
@@ -360,6 +361,28 @@ impl ExecutionState {
             || self.halts_in_exception()
     }
 
+    /// Execution states assigned by a `DummyGadget`, which doesn't constrain
+    /// the step.
+    pub fn is_dummy(&self) -> bool {
+        matches!(
+            self,
+            Self::CREATE
+                | Self::CREATE2
+                | Self::SELFDESTRUCT
+                | Self::ErrorOutOfGasStaticMemoryExpansion
+                | Self::ErrorOutOfGasDynamicMemoryExpansion
+                | Self::ErrorOutOfGasAccountAccess
+                | Self::ErrorOutOfGasSHA3
+                | Self::ErrorOutOfGasEXTCODECOPY
+                | Self::ErrorOutOfGasCREATE2
+                | Self::ErrorOutOfGasSELFDESTRUCT
+                | Self::ErrorOutOfGasCodeStore
+                | Self::ErrorDepth
+                | Self::ErrorContractAddressCollision
+                | Self::ErrorInvalidCreationCode
+        )
+    }
+
     pub fn responsible_opcodes(&self) -> Vec<ResponsibleOp> {
         if matches!(self, Self::ErrorStack) {
             return OpcodeId::valid_opcodes()
@@ -520,16 +543,12 @@ impl ExecutionState {
         .collect()
     }
 
-    pub fn get_step_height_option(&self, is_taiko: bool) -> Option<usize> {
-        if is_taiko {
-            EXECUTION_STATE_HEIGHT_MAP_WITH_TAIKO.get(self).copied()
-        } else {
-            EXECUTION_STATE_HEIGHT_MAP.get(self).copied()
-        }
+    pub fn get_step_height_option(&self, params: EvmCircuitParams) -> Option<usize> {
+        execution_state_height_map(params).get(self).copied()
     }
 
-    pub fn get_step_height(&self, is_taiko: bool) -> usize {
-        self.get_step_height_option(is_taiko)
+    pub fn get_step_height(&self, params: EvmCircuitParams) -> usize {
+        self.get_step_height_option(params)
             .unwrap_or_else(|| panic!("Execution state unknown: {:?}", self))
     }
 }
@@ -573,6 +592,11 @@ pub(crate) struct DynamicSelectorHalf<F> {
 }
 
 impl<F: Field> DynamicSelectorHalf<F> {
+    /// Number of cells of a selector of `count` targets
+    pub(crate) fn n_cells(count: usize) -> usize {
+        (count + 1) / 2 + 1
+    }
+
     pub(crate) fn new(cell_manager: &mut CellManager<F>, count: usize) -> Self {
         let target_pairs = cell_manager.query_cells(CellType::StoragePhase1, (count + 1) / 2);
         let target_odd = cell_manager.query_cell(CellType::StoragePhase1);
@@ -686,6 +710,16 @@ pub(crate) struct StepState<F> {
     pub(crate) reversible_write_counter: Cell<F>,
     /// The counter for log index
     pub(crate) log_id: Cell<F>,
+    /// The number of the block which includes the current transaction
+    pub(crate) block_number: Cell<F>,
+}
+
+impl<F: Field> StepState<F> {
+    /// Number of `StoragePhase1` cells of the state: the execution state
+    /// selector, and the fields following it but `code_hash`.
+    pub(crate) fn n_phase1_cells() -> usize {
+        DynamicSelectorHalf::<F>::n_cells(ExecutionState::amount()) + 11
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -697,16 +731,17 @@ pub(crate) struct Step<F> {
 impl<F: Field> Step<F> {
     pub(crate) fn new(
         meta: &mut ConstraintSystem<F>,
-        advices: [Column<Advice>; STEP_WIDTH],
+        advices: &[Column<Advice>],
+        layout: EvmLayout,
         offset: usize,
         is_next: bool,
     ) -> Self {
         let height = if is_next {
             STEP_STATE_HEIGHT // Query only the state of the next step.
         } else {
-            MAX_STEP_HEIGHT // Query the entire current step.
+            layout.max_step_height() // Query the entire current step.
         };
-        let mut cell_manager = CellManager::new(meta, height, &advices, offset);
+        let mut cell_manager = CellManager::new(meta, height, advices, layout, offset);
         let state = {
             StepState {
                 execution_state: DynamicSelectorHalf::new(
@@ -724,6 +759,7 @@ impl<F: Field> Step<F> {
                 memory_word_size: cell_manager.query_cell(CellType::StoragePhase1),
                 reversible_write_counter: cell_manager.query_cell(CellType::StoragePhase1),
                 log_id: cell_manager.query_cell(CellType::StoragePhase1),
+                block_number: cell_manager.query_cell(CellType::StoragePhase1),
             }
         };
         Self {
@@ -746,6 +782,7 @@ impl<F: Field> Step<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         _block: &Block<F>,
+        transaction: &Transaction,
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
@@ -795,6 +832,11 @@ impl<F: Field> Step<F> {
         self.state
             .log_id
             .assign(region, offset, Value::known(F::from(step.log_id as u64)))?;
+        self.state.block_number.assign(
+            region,
+            offset,
+            Value::known(F::from(transaction.block_number)),
+        )?;
         Ok(())
     }
 }
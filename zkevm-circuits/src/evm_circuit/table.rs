@@ -3,7 +3,7 @@ use crate::{
     impl_expr,
 };
 use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
-use eth_types::Field;
+use eth_types::{evm_types::ChainSpec, Address, Field};
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
 use strum::IntoEnumIterator;
@@ -32,7 +32,7 @@ pub enum FixedTableTag {
 impl_expr!(FixedTableTag);
 
 impl FixedTableTag {
-    pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
+    pub fn build<F: Field>(&self, chain_spec: ChainSpec) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
             Self::Zero => Box::new((0..1).map(move |_| [tag, F::ZERO, F::ZERO, F::ZERO])),
@@ -79,8 +79,20 @@ impl FixedTableTag {
             })),
             Self::ResponsibleOpcode => {
                 Box::new(ExecutionState::iter().flat_map(move |execution_state| {
-                    execution_state.responsible_opcodes().into_iter().map(
-                        move |responsible_opcode| {
+                    let mut responsible_opcodes = execution_state.responsible_opcodes();
+                    responsible_opcodes.retain(|op| chain_spec.is_opcode_available(op.opcode()));
+                    // Opcodes introduced by a later hardfork are invalid ones
+                    if execution_state == ExecutionState::ErrorInvalidOpcode {
+                        responsible_opcodes.extend(
+                            OpcodeId::valid_opcodes()
+                                .into_iter()
+                                .filter(|&op| !chain_spec.is_opcode_available(op))
+                                .map(ResponsibleOp::Op),
+                        );
+                    }
+                    responsible_opcodes
+                        .into_iter()
+                        .map(move |responsible_opcode| {
                             let (op, aux) = match responsible_opcode {
                                 ResponsibleOp::Op(op) => (op, F::ZERO),
                                 ResponsibleOp::InvalidStackPtr(op, stack_ptr) => {
@@ -93,8 +105,7 @@ impl FixedTableTag {
                                 F::from(op.as_u64()),
                                 aux,
                             ]
-                        },
-                    )
+                        })
                 }))
             }
             Self::Pow2 => Box::new((0..256).map(move |value| {
@@ -107,27 +118,33 @@ impl FixedTableTag {
             })),
             Self::ConstantGasCost => Box::new(
                 OpcodeId::iter()
-                    .filter(move |opcode| opcode.constant_gas_cost().0 > 0)
+                    .filter(move |&opcode| chain_spec.constant_gas_cost(opcode).0 > 0)
                     .map(move |opcode| {
                         [
                             tag,
                             F::from(opcode.as_u64()),
-                            F::from(opcode.constant_gas_cost().0),
+                            F::from(chain_spec.constant_gas_cost(opcode).0),
                             F::ZERO,
                         ]
                     }),
             ),
-            Self::PrecompileInfo => Box::new(PrecompileCalls::iter().map(move |precompile| {
-                [
-                    tag,
-                    F::from({
-                        let state: ExecutionState = precompile.into();
-                        state.as_u64()
+            Self::PrecompileInfo => Box::new(
+                PrecompileCalls::iter()
+                    .filter(move |&precompile| {
+                        chain_spec.is_precompiled(&Address::from_low_u64_be(precompile.into()))
+                    })
+                    .map(move |precompile| {
+                        [
+                            tag,
+                            F::from({
+                                let state: ExecutionState = precompile.into();
+                                state.as_u64()
+                            }),
+                            F::from(u64::from(precompile)),
+                            F::from(precompile.base_gas_cost().0),
+                        ]
                     }),
-                    F::from(u64::from(precompile)),
-                    F::from(precompile.base_gas_cost().0),
-                ]
-            })),
+            ),
         }
     }
 }
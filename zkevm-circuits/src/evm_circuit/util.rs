@@ -1,17 +1,15 @@
 use crate::{
     evm_circuit::{
-        param::{
-            LOOKUP_CONFIG, N_BYTES_MEMORY_ADDRESS, N_BYTE_LOOKUPS, N_COPY_COLUMNS, N_PHASE2_COLUMNS,
-        },
+        param::{EvmLayout, N_BYTES_MEMORY_ADDRESS},
         table::Table,
     },
     util::{query_expression, Challenges, Expr},
     witness::{Block, ExecStep, Rw, RwMap},
 };
 use bus_mapping::state_db::CodeDB;
-use eth_types::{Address, Field, ToLittleEndian, ToWord, U256};
+use eth_types::{Field, ToLittleEndian, ToWord, U256};
 use halo2_proofs::{
-    circuit::{AssignedCell, Region, Value},
+    circuit::{Region, Value},
     plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, VirtualCells},
     poly::Rotation,
 };
@@ -60,7 +58,7 @@ impl<F: Field> Cell<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<AssignedCell<F, F>, Error> {
+    ) -> Result<(), Error> {
         region.assign_advice(
             || {
                 format!(
@@ -86,8 +84,58 @@ impl<F: Field> Expr<F> for &Cell<F> {
         self.expression.clone()
     }
 }
+
+#[derive(Debug)]
+enum Assignment<F> {
+    Advice(Column<Advice>, usize, Value<F>),
+    // The assigned value, and the constant it's constrained to
+    Constant(Column<Advice>, usize, F, F),
+}
+
+/// Advice assignments recorded by a detached [`CachedRegion`], to be replayed
+/// into the real region afterwards.
+#[derive(Debug)]
+pub(crate) struct Assignments<F>(Vec<Assignment<F>>);
+
+impl<F> Default for Assignments<F> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<F: Field> Assignments<F> {
+    /// Append the assignments of `other`, which must come after `self`.
+    pub(crate) fn extend(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    /// Assign the recorded values into `region`, in the order they were
+    /// recorded.
+    pub(crate) fn replay(&self, region: &mut Region<'_, F>) -> Result<(), Error> {
+        for assignment in self.0.iter() {
+            match assignment {
+                Assignment::Advice(column, offset, value) => {
+                    region.assign_advice(|| "replayed advice", *column, *offset, || *value)?;
+                }
+                Assignment::Constant(column, offset, value, constant) => {
+                    let cell = region.assign_advice(
+                        || "replayed constant",
+                        *column,
+                        *offset,
+                        || Value::known(*value),
+                    )?;
+                    region.constrain_constant(cell.cell(), *constant)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 pub struct CachedRegion<'r, 'b, F: Field> {
-    region: &'r mut Region<'b, F>,
+    // `None` when detached, in which case assignments are recorded instead
+    region: Option<&'r mut Region<'b, F>>,
+    recorded: Assignments<F>,
     advice: Vec<Vec<F>>,
     challenges: &'r Challenges<Value<F>>,
     advice_columns: Vec<Column<Advice>>,
@@ -103,9 +151,39 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
         advice_columns: Vec<Column<Advice>>,
         height: usize,
         height_start: usize,
+    ) -> Self {
+        Self::new_inner(
+            Some(region),
+            challenges,
+            advice_columns,
+            height,
+            height_start,
+        )
+    }
+
+    /// New cached region which isn't backed by a halo2 region. Its
+    /// assignments are recorded and can be taken with
+    /// [`Self::into_assignments`], which allows assigning steps on worker
+    /// threads.
+    pub(crate) fn new_detached(
+        challenges: &'r Challenges<Value<F>>,
+        advice_columns: Vec<Column<Advice>>,
+        height: usize,
+        height_start: usize,
+    ) -> Self {
+        Self::new_inner(None, challenges, advice_columns, height, height_start)
+    }
+
+    fn new_inner(
+        region: Option<&'r mut Region<'b, F>>,
+        challenges: &'r Challenges<Value<F>>,
+        advice_columns: Vec<Column<Advice>>,
+        height: usize,
+        height_start: usize,
     ) -> Self {
         Self {
             region,
+            recorded: Assignments::default(),
             advice: vec![vec![F::ZERO; height]; advice_columns.len()],
             challenges,
             width_start: advice_columns[0].index(),
@@ -114,6 +192,11 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
         }
     }
 
+    /// Assignments recorded by a detached region.
+    pub(crate) fn into_assignments(self) -> Assignments<F> {
+        self.recorded
+    }
+
     /// This method replicates the assignment of 1 row at height_start (which
     /// must be already assigned via the CachedRegion) into a range of rows
     /// indicated by offset_begin, offset_end. It can be used as a "quick"
@@ -139,8 +222,16 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
             }
             let annotation: &String = &annotation().into();
             for offset in offset_begin..offset_end {
-                self.region
-                    .assign_advice(|| annotation, *column, offset, || Value::known(v))?;
+                match self.region.as_mut() {
+                    Some(region) => {
+                        region.assign_advice(|| annotation, *column, offset, || Value::known(v))?;
+                    }
+                    None => {
+                        self.recorded
+                            .0
+                            .push(Assignment::Advice(*column, offset, Value::known(v)))
+                    }
+                }
             }
         }
 
@@ -154,26 +245,34 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
         column: Column<Advice>,
         offset: usize,
         to: V,
-    ) -> Result<AssignedCell<VR, F>, Error>
+    ) -> Result<(), Error>
     where
         V: Fn() -> Value<VR> + 'v,
         for<'vr> Assigned<F>: From<&'vr VR>,
         A: Fn() -> AR,
         AR: Into<String>,
     {
-        // Actually set the value
-        let res = self.region.assign_advice(annotation, column, offset, &to);
+        // Actually set the value, or record it when detached
+        match self.region.as_mut() {
+            Some(region) => {
+                region.assign_advice(annotation, column, offset, &to)?;
+            }
+            None => {
+                let value = to().map(|f| Assigned::from(&f).evaluate());
+                self.recorded
+                    .0
+                    .push(Assignment::Advice(column, offset, value));
+            }
+        }
         // Cache the value
         // Note that the `value_field` in `AssignedCell` might be `Value::unkonwn` if
         // the column has different phase than current one, so we call to `to`
         // again here to cache the value.
-        if res.is_ok() {
-            to().map(|f| {
-                self.advice[column.index() - self.width_start][offset - self.height_start] =
-                    Assigned::from(&f).evaluate();
-            });
-        }
-        res
+        to().map(|f| {
+            self.advice[column.index() - self.width_start][offset - self.height_start] =
+                Assigned::from(&f).evaluate();
+        });
+        Ok(())
     }
 
     pub fn get_fixed(&self, _row_index: usize, _column_index: usize, _rotation: Rotation) -> F {
@@ -198,19 +297,36 @@ impl<'r, 'b, F: Field> CachedRegion<'r, 'b, F> {
         self.word_rlc(CodeDB::empty_code_hash().to_word())
     }
 
-    /// Constrains a cell to have a constant value.
+    /// Constrains a cell, which must be already assigned via the
+    /// CachedRegion, to have a constant value.
     ///
     /// Returns an error if the cell is in a column where equality has not been
     /// enabled.
-    pub fn constrain_constant<VR>(
+    pub(crate) fn constrain_constant(
         &mut self,
-        cell: AssignedCell<F, F>,
-        constant: VR,
-    ) -> Result<(), Error>
-    where
-        VR: Into<Assigned<F>>,
-    {
-        self.region.constrain_constant(cell.cell(), constant.into())
+        cell: &Cell<F>,
+        offset: usize,
+        constant: F,
+    ) -> Result<(), Error> {
+        let offset = offset + cell.rotation;
+        let value = self.advice[cell.column.index() - self.width_start][offset - self.height_start];
+        match self.region.as_mut() {
+            Some(region) => {
+                let assigned = region.assign_advice(
+                    || "constant",
+                    cell.column,
+                    offset,
+                    || Value::known(value),
+                )?;
+                region.constrain_constant(assigned.cell(), constant)
+            }
+            None => {
+                self.recorded
+                    .0
+                    .push(Assignment::Constant(cell.column, offset, value, constant));
+                Ok(())
+            }
+        }
     }
 }
 
@@ -329,6 +445,7 @@ impl<F: Field> CellManager<F> {
         meta: &mut ConstraintSystem<F>,
         height: usize,
         advices: &[Column<Advice>],
+        layout: EvmLayout,
         height_offset: usize,
     ) -> Self {
         // Setup the columns and query the cells
@@ -352,7 +469,7 @@ impl<F: Field> CellManager<F> {
         let mut column_idx = 0;
 
         // Mark columns used for lookups in Phase3
-        for &(table, count) in LOOKUP_CONFIG {
+        for (table, count) in layout.lookup_config() {
             for _ in 0usize..count {
                 columns[column_idx].cell_type = CellType::Lookup(table);
                 column_idx += 1;
@@ -360,20 +477,20 @@ impl<F: Field> CellManager<F> {
         }
 
         // Mark columns used for Phase2 constraints
-        for _ in 0..N_PHASE2_COLUMNS {
+        for _ in 0..layout.n_phase2_columns() {
             columns[column_idx].cell_type = CellType::StoragePhase2;
             column_idx += 1;
         }
 
         // Mark columns used for copy constraints
-        for _ in 0..N_COPY_COLUMNS {
+        for _ in 0..layout.n_copy_columns() {
             meta.enable_equality(advices[column_idx]);
             columns[column_idx].cell_type = CellType::StoragePermutation;
             column_idx += 1;
         }
 
         // Mark columns used for byte lookup
-        for _ in 0..N_BYTE_LOOKUPS {
+        for _ in 0..layout.n_byte_lookups() {
             columns[column_idx].cell_type = CellType::LookupByte;
             assert_eq!(advices[column_idx].column_type().phase(), 0);
             column_idx += 1;
@@ -479,16 +596,12 @@ impl<F: Field, const N: usize> RandomLinearCombination<F, N> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         bytes: Option<[u8; N]>,
-    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
-        bytes.map_or(Err(Error::Synthesis), |bytes| {
-            self.cells
-                .iter()
-                .zip(bytes.iter())
-                .map(|(cell, byte)| {
-                    cell.assign(region, offset, Value::known(F::from(*byte as u64)))
-                })
-                .collect()
-        })
+    ) -> Result<(), Error> {
+        let bytes = bytes.ok_or(Error::Synthesis)?;
+        for (cell, byte) in self.cells.iter().zip(bytes.iter()) {
+            cell.assign(region, offset, Value::known(F::from(*byte as u64)))?;
+        }
+        Ok(())
     }
 }
 
@@ -619,10 +732,6 @@ pub(crate) fn transpose_val_ret<F, E>(value: Value<Result<F, E>>) -> Result<Valu
     ret
 }
 
-pub(crate) fn is_precompiled(address: &Address) -> bool {
-    address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
-}
-
 /// Helper struct to read rw operations from a step sequentially.
 pub(crate) struct StepRws<'a> {
     rws: &'a RwMap,
@@ -196,6 +196,7 @@ impl<F: Field> RestoreContextGadget<F> {
             memory_word_size: To(caller_memory_word_size.expr()),
             reversible_write_counter: To(reversible_write_counter),
             log_id: Same,
+            block_number: Same,
         });
 
         Self {
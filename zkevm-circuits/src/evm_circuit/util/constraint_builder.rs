@@ -12,7 +12,7 @@ use crate::{
     util::{build_tx_log_expression, Challenges, Expr},
 };
 use bus_mapping::{operation::Target, state_db::EMPTY_CODE_HASH_LE};
-use eth_types::Field;
+use eth_types::{evm_types::ChainSpec, Field};
 use gadgets::util::not;
 use halo2_proofs::{
     circuit::Value,
@@ -57,6 +57,7 @@ pub(crate) struct StepStateTransition<F: Field> {
     pub(crate) memory_word_size: Transition<Expression<F>>,
     pub(crate) reversible_write_counter: Transition<Expression<F>>,
     pub(crate) log_id: Transition<Expression<F>>,
+    pub(crate) block_number: Transition<Expression<F>>,
 }
 
 impl<F: Field> StepStateTransition<F> {
@@ -82,6 +83,7 @@ impl<F: Field> StepStateTransition<F> {
             memory_word_size: Transition::Any,
             reversible_write_counter: Transition::Any,
             log_id: Transition::Any,
+            block_number: Transition::Any,
         }
     }
 }
@@ -281,6 +283,8 @@ pub(crate) struct EVMConstraintBuilder<'a, F> {
     stored_expressions: Vec<StoredExpression<F>>,
     // in taiko context
     pub(crate) is_taiko: bool,
+    // rules of the proven chain
+    pub(crate) chain_spec: ChainSpec,
 }
 
 impl<'a, F: Field> ConstrainBuilderCommon<F> for EVMConstraintBuilder<'a, F> {
@@ -303,6 +307,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         challenges: &'a Challenges<Expression<F>>,
         execution_state: ExecutionState,
         is_taiko: bool,
+        chain_spec: ChainSpec,
     ) -> Self {
         Self {
             max_degree: MAX_DEGREE,
@@ -325,6 +330,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             constraints_location: ConstraintLocation::Step,
             stored_expressions: Vec::new(),
             is_taiko,
+            chain_spec,
         }
     }
 
@@ -512,6 +518,7 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         constrain!(memory_word_size);
         constrain!(reversible_write_counter);
         constrain!(log_id);
+        constrain!(block_number);
     }
 
     // Fixed
@@ -672,17 +679,20 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
     }
 
     // block
+    /// Lookup a field of the block table.  When `number` is `None` the field
+    /// of the block including the current transaction is used.
     pub(crate) fn block_lookup(
         &mut self,
         tag: Expression<F>,
         number: Option<Expression<F>>,
         val: Expression<F>,
     ) {
+        let number = number.unwrap_or_else(|| self.curr.state.block_number.expr());
         self.add_lookup(
             "Block lookup",
             Lookup::Block {
                 field_tag: tag,
-                number: number.unwrap_or_else(|| 0.expr()),
+                number,
                 value: val,
             },
         );
@@ -1153,11 +1163,14 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
 
     // Memory
 
+    /// Lookup of the memory word at `word_index` (memory address divided by
+    /// 32), with `value` and `value_prev` the RLC of the word bytes.
     pub(crate) fn memory_lookup(
         &mut self,
         is_write: Expression<F>,
-        memory_address: Expression<F>,
-        byte: Expression<F>,
+        word_index: Expression<F>,
+        value: Expression<F>,
+        value_prev: Expression<F>,
         call_id: Option<Expression<F>>,
     ) {
         self.rw_lookup(
@@ -1166,11 +1179,11 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
             Target::Memory,
             RwValues::new(
                 call_id.unwrap_or_else(|| self.curr.state.call_id.expr()),
-                memory_address,
-                0.expr(),
+                word_index,
                 0.expr(),
-                byte,
                 0.expr(),
+                value,
+                value_prev,
                 0.expr(),
                 0.expr(),
             ),
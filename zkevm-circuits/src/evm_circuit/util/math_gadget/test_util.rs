@@ -4,19 +4,19 @@ use strum::IntoEnumIterator;
 
 use crate::{
     evm_circuit::{
-        param::{MAX_STEP_HEIGHT, N_PHASE2_COLUMNS, STEP_WIDTH},
+        param::EvmLayout,
         step::{ExecutionState, Step},
         table::{FixedTableTag, Table},
         util::{
             constraint_builder::EVMConstraintBuilder, rlc, CachedRegion, CellType, Expr,
-            StoredExpression, LOOKUP_CONFIG,
+            StoredExpression,
         },
         Advice, Column, Fixed,
     },
     table::LookupTable,
     util::Challenges,
 };
-use eth_types::{Field, Word, U256};
+use eth_types::{evm_types::ChainSpec, Field, Word, U256};
 pub(crate) use halo2_proofs::circuit::{Layouter, Value};
 use halo2_proofs::{
     circuit::SimpleFloorPlanner,
@@ -63,7 +63,7 @@ where
 {
     q_usable: Selector,
     fixed_table: [Column<Fixed>; 4],
-    advices: [Column<Advice>; STEP_WIDTH],
+    advices: Vec<Column<Advice>>,
     step: Step<F>,
     stored_expressions: Vec<StoredExpression<F>>,
     math_gadget_container: G,
@@ -107,31 +107,29 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
         let q_usable = meta.selector();
         let fixed_table = [(); 4].map(|_| meta.fixed_column());
 
-        let lookup_column_count: usize = LOOKUP_CONFIG.iter().map(|(_, count)| *count).sum();
-        let advices = [(); STEP_WIDTH]
-            .iter()
-            .enumerate()
-            .map(|(n, _)| {
+        let layout = EvmLayout::default();
+        let lookup_column_count = layout.lookup_columns();
+        let advices = (0..layout.step_width())
+            .map(|n| {
                 if n < lookup_column_count {
                     meta.advice_column_in(ThirdPhase)
-                } else if n < lookup_column_count + N_PHASE2_COLUMNS {
+                } else if n < lookup_column_count + layout.n_phase2_columns() {
                     meta.advice_column_in(SecondPhase)
                 } else {
                     meta.advice_column_in(FirstPhase)
                 }
             })
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
+            .collect::<Vec<_>>();
 
-        let step_curr = Step::new(meta, advices, 0, false);
-        let step_next = Step::new(meta, advices, MAX_STEP_HEIGHT, true);
+        let step_curr = Step::new(meta, &advices, layout, 0, false);
+        let step_next = Step::new(meta, &advices, layout, layout.max_step_height(), true);
         let mut cb = EVMConstraintBuilder::new(
             step_curr.clone(),
             step_next,
             &challenges_exprs,
             ExecutionState::STOP,
             false,
+            ChainSpec::default(),
         );
         let math_gadget_container = G::configure_gadget_container(&mut cb);
         let (constraints, stored_expressions, _) = cb.build();
@@ -192,8 +190,8 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                 let cached_region = &mut CachedRegion::<'_, '_, F>::new(
                     &mut region,
                     &challenge_values,
-                    config.advices.to_vec(),
-                    MAX_STEP_HEIGHT * 3,
+                    config.advices.clone(),
+                    EvmLayout::default().max_step_height() * 3,
                     offset,
                 );
                 config.step.state.execution_state.assign(
@@ -232,7 +230,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                                         | FixedTableTag::Range1024
                                 )
                             })
-                            .flat_map(|tag| tag.build()),
+                            .flat_map(|tag| tag.build(ChainSpec::default())),
                     )
                     .enumerate()
                 {
@@ -1,12 +1,12 @@
 use super::{constraint_builder::ConstrainBuilderCommon, CachedRegion};
 use crate::{
     evm_circuit::{
-        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_WORD},
         util::{
             constraint_builder::EVMConstraintBuilder,
             from_bytes,
             math_gadget::{ConstantDivisionGadget, IsZeroGadget, MinMaxGadget, RangeCheckGadget},
-            select, sum, Cell, CellType, MemoryAddress,
+            select, sum, Cell, CellType, MemoryAddress, Word,
         },
     },
     util::Expr,
@@ -536,3 +536,122 @@ impl<F: Field, const MAX_BYTES: usize, const ADDR_SIZE_IN_BYTES: usize>
         sum::expr(&self.selectors)
     }
 }
+
+/// Memory words gadget reads the two memory words that contain the 32 bytes
+/// starting at `address`. The address is decomposed as `32 * word_index +
+/// shift`, and the bytes starting at `address` are picked out of the words with
+/// one-hot shift selectors.
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryWordsGadget<F> {
+    /// Index of the first word, i.e. `address / 32`
+    word_index: [Cell<F>; N_BYTES_MEMORY_ADDRESS],
+    /// The selectors that indicate `address % 32`
+    shift: [Cell<F>; N_BYTES_WORD],
+    /// The two memory words, with the little-endian cells of a word holding
+    /// its memory bytes in reverse order
+    words: [Word<F>; 2],
+}
+
+impl<F: Field> MemoryWordsGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, address: Expression<F>) -> Self {
+        let word_index = cb.query_bytes();
+        let shift = array_init(|_| cb.query_bool());
+        let words = array_init(|_| cb.query_word_rlc());
+
+        cb.require_equal(
+            "only one shift selector is set",
+            sum::expr(&shift),
+            1.expr(),
+        );
+        cb.require_equal(
+            "address == 32 * word_index + shift",
+            address,
+            from_bytes::expr(&word_index) * N_BYTES_WORD.expr()
+                + sum::expr(
+                    shift
+                        .iter()
+                        .enumerate()
+                        .map(|(k, selector)| k.expr() * selector.expr()),
+                ),
+        );
+
+        Self {
+            word_index,
+            shift,
+            words,
+        }
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        address: u64,
+        words: [U256; 2],
+    ) -> Result<(), Error> {
+        for (cell, byte) in self
+            .word_index
+            .iter()
+            .zip((address / N_BYTES_WORD as u64).to_le_bytes())
+        {
+            cell.assign(region, offset, Value::known(F::from(byte as u64)))?;
+        }
+        for (k, selector) in self.shift.iter().enumerate() {
+            let is_shift = k as u64 == address % N_BYTES_WORD as u64;
+            selector.assign(region, offset, Value::known(F::from(is_shift as u64)))?;
+        }
+        for (word, value) in self.words.iter().zip(words) {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        Ok(())
+    }
+
+    /// Index of the first word
+    pub(crate) fn word_index(&self) -> Expression<F> {
+        from_bytes::expr(&self.word_index)
+    }
+
+    /// RLC of the word at `idx`, as in the rw table
+    pub(crate) fn word(&self, idx: usize) -> Expression<F> {
+        self.words[idx].expr()
+    }
+
+    /// The memory byte at `address + idx`
+    pub(crate) fn byte(&self, idx: usize) -> Expression<F> {
+        sum::expr(
+            self.shift
+                .iter()
+                .enumerate()
+                .map(|(k, selector)| selector.expr() * memory_byte(&self.words, k + idx)),
+        )
+    }
+
+    /// Constrain that `words_prev` hold the same bytes as the words, except
+    /// for the `len` bytes starting at `address`.
+    pub(crate) fn require_unchanged_outside(
+        &self,
+        cb: &mut EVMConstraintBuilder<F>,
+        words_prev: &[Word<F>; 2],
+        len: usize,
+    ) {
+        for idx in 0..2 * N_BYTES_WORD {
+            let in_range = sum::expr(
+                self.shift
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, _)| *k <= idx && idx < k + len)
+                    .map(|(_, selector)| selector.expr()),
+            );
+            cb.require_zero(
+                "memory bytes outside of the accessed bytes are unchanged",
+                (1.expr() - in_range)
+                    * (memory_byte(&self.words, idx) - memory_byte(words_prev, idx)),
+            );
+        }
+    }
+}
+
+// The memory byte at `idx` from the start of `words`.
+fn memory_byte<F: Field>(words: &[Word<F>; 2], idx: usize) -> Expression<F> {
+    words[idx / N_BYTES_WORD].cells[N_BYTES_WORD - 1 - idx % N_BYTES_WORD].expr()
+}
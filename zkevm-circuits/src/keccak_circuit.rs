@@ -14,12 +14,18 @@ mod test;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 pub use dev::KeccakCircuit as TestKeccakCircuit;
 
-use std::marker::PhantomData;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    marker::PhantomData,
+};
 pub use KeccakCircuitConfig as KeccakConfig;
 
 use self::{
     cell_manager::*,
-    keccak_packed_multi::{keccak_unusable_rows, multi_keccak, KeccakRow},
+    keccak_packed_multi::{
+        get_num_keccak_f, get_num_rows_per_keccak_f, keccak_unusable_rows, multi_keccak, KeccakRow,
+    },
     param::*,
     table::*,
     util::*,
@@ -31,9 +37,13 @@ use crate::{
         get_num_bits_per_rho_pi_lookup, get_num_bits_per_theta_c_lookup, get_num_rows_per_round,
         split, split_uniform, transform, transform_to, Part,
     },
+    pi_circuit::PiCircuit,
     table::{KeccakTable, LookupTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
-    witness,
+    witness::{self, block_convert},
+};
+use bus_mapping::circuit_input_builder::{
+    keccak_inputs_with_source, CircuitInputBuilder, KeccakInputSource,
 };
 use eth_types::Field;
 use gadgets::util::{and, not, select, sum, Expr};
@@ -42,6 +52,7 @@ use halo2_proofs::{
     plonk::{Column, ConstraintSystem, Error, Expression, Fixed, TableColumn, VirtualCells},
     poly::Rotation,
 };
+use itertools::Itertools;
 use log::debug;
 
 /// KeccakConfig
@@ -1002,12 +1013,12 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let rows_per_chunk = (NUM_ROUNDS + 1) * get_num_rows_per_round();
         (
             block
                 .keccak_inputs
                 .iter()
-                .map(|bytes| (bytes.len() as f64 / 136.0).ceil() as usize * rows_per_chunk)
+                .unique()
+                .map(|bytes| get_num_keccak_f(bytes.len()) * get_num_rows_per_keccak_f())
                 .sum(),
             block.circuits_params.max_keccak_rows,
         )
@@ -1027,20 +1038,42 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
 }
 
 impl<F: Field> KeccakCircuit<F> {
-    /// Creates a new circuit instance
+    /// Creates a new circuit instance. Inputs hashed more than once are only
+    /// assigned the first time.
     pub fn new(num_rows: usize, inputs: Vec<Vec<u8>>) -> Self {
         KeccakCircuit {
-            inputs,
+            inputs: inputs.into_iter().unique().collect(),
             num_rows,
             _marker: PhantomData,
         }
     }
 
+    /// Also hash `input`, unless it is already hashed.
+    pub fn with_input(mut self, input: Vec<u8>) -> Self {
+        if !self.inputs.contains(&input) {
+            self.inputs.push(input);
+        }
+        self
+    }
+
+    /// Number of rows of the circuit: the fixed number of rows when set, or
+    /// the rows of the permutations of the inputs.
+    pub fn num_rows(&self) -> usize {
+        if self.num_rows > 0 {
+            self.num_rows
+        } else {
+            self.inputs
+                .iter()
+                .map(|bytes| get_num_keccak_f(bytes.len()) * get_num_rows_per_keccak_f())
+                .sum()
+        }
+    }
+
     /// The number of keccak_f's that can be done in this circuit
     pub fn capacity(&self) -> Option<usize> {
         if self.num_rows > 0 {
             // Subtract two for unusable rows
-            Some(self.num_rows / ((NUM_ROUNDS + 1) * get_num_rows_per_round()) - 2)
+            Some(self.num_rows / get_num_rows_per_keccak_f() - 2)
         } else {
             None
         }
@@ -1052,3 +1085,108 @@ impl<F: Field> KeccakCircuit<F> {
             .expect("Too many inputs for given capacity")
     }
 }
+
+/// Keccak circuit usage of the inputs coming from one source
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeccakSourceUsage {
+    /// Number of distinct inputs assigned for this source
+    pub inputs: usize,
+    /// Number of inputs skipped because they were already hashed
+    pub duplicates: usize,
+    /// Number of keccak_f permutations
+    pub keccak_fs: usize,
+    /// Number of rows used by the permutations
+    pub rows: usize,
+}
+
+/// Report of the keccak circuit rows used by each source of keccak inputs.
+/// An input hashed by several sources is accounted to the first one, and as
+/// a duplicate for the next ones.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KeccakCapacityReport {
+    /// Usage per source
+    pub sources: BTreeMap<KeccakInputSource, KeccakSourceUsage>,
+}
+
+impl KeccakCapacityReport {
+    /// Build the report from the keccak inputs of a block, in assignment
+    /// order.
+    pub fn new(inputs: &[(KeccakInputSource, Vec<u8>)]) -> Self {
+        let mut report = Self::default();
+        let mut seen = HashSet::new();
+        for (source, input) in inputs {
+            let usage = report.sources.entry(*source).or_default();
+            if !seen.insert(input) {
+                usage.duplicates += 1;
+                continue;
+            }
+            let keccak_fs = get_num_keccak_f(input.len());
+            usage.inputs += 1;
+            usage.keccak_fs += keccak_fs;
+            usage.rows += keccak_fs * get_num_rows_per_keccak_f();
+        }
+        report
+    }
+
+    /// Build the report of the block in a [`CircuitInputBuilder`] that has
+    /// already handled all its transactions.  Besides the inputs of the
+    /// bus-mapping, it accounts for the raw public inputs hashed by the PI
+    /// circuit with [`PiCommitment::Keccak`](crate::pi_circuit::PiCommitment).
+    pub fn from_builder<F: Field>(
+        builder: &CircuitInputBuilder,
+    ) -> Result<Self, bus_mapping::Error> {
+        let mut inputs = keccak_inputs_with_source(&builder.block, &builder.code_db)?;
+        let block = block_convert::<F>(&builder.block, &builder.code_db)?;
+        inputs.push((
+            KeccakInputSource::Pi,
+            PiCircuit::new_from_block(&block).keccak_input(),
+        ));
+        Ok(Self::new(&inputs))
+    }
+
+    /// Total number of keccak_f permutations
+    pub fn keccak_fs(&self) -> usize {
+        self.sources.values().map(|usage| usage.keccak_fs).sum()
+    }
+
+    /// Total number of rows used by the permutations
+    pub fn rows(&self) -> usize {
+        self.sources.values().map(|usage| usage.rows).sum()
+    }
+}
+
+impl fmt::Display for KeccakCapacityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<10} {:>8} {:>10} {:>10} {:>10}",
+            "source", "inputs", "duplicates", "keccak_fs", "rows"
+        )?;
+        for (source, usage) in self.sources.iter() {
+            writeln!(
+                f,
+                "{:<10} {:>8} {:>10} {:>10} {:>10}",
+                format!("{:?}", source),
+                usage.inputs,
+                usage.duplicates,
+                usage.keccak_fs,
+                usage.rows
+            )?;
+        }
+        write!(
+            f,
+            "{:<10} {:>8} {:>10} {:>10} {:>10}",
+            "total",
+            self.sources
+                .values()
+                .map(|usage| usage.inputs)
+                .sum::<usize>(),
+            self.sources
+                .values()
+                .map(|usage| usage.duplicates)
+                .sum::<usize>(),
+            self.keccak_fs(),
+            self.rows()
+        )
+    }
+}
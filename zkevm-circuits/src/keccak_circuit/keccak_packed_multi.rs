@@ -6,6 +6,7 @@ use halo2_proofs::{
     plonk::{Error, Expression},
 };
 use log::debug;
+use rayon::prelude::*;
 use std::{env::var, vec};
 
 pub(crate) fn get_num_rows_per_round() -> usize {
@@ -23,6 +24,17 @@ pub(crate) fn keccak_unusable_rows() -> usize {
     UNUSABLE_ROWS_BY_KECCAK_ROWS[get_num_rows_per_round() - NUM_BYTES_PER_WORD - 1]
 }
 
+/// Number of keccak_f permutations needed to hash `num_bytes` bytes, padding
+/// included.
+pub(crate) fn get_num_keccak_f(num_bytes: usize) -> usize {
+    num_bytes / RATE + 1
+}
+
+/// Number of rows used by a keccak_f permutation.
+pub(crate) fn get_num_rows_per_keccak_f() -> usize {
+    (NUM_ROUNDS + 1) * get_num_rows_per_round()
+}
+
 pub(crate) fn get_num_bits_per_absorb_lookup() -> usize {
     get_num_bits_per_lookup(ABSORB_LOOKUP_RANGE)
 }
@@ -849,10 +861,17 @@ pub(crate) fn multi_keccak<F: Field>(
             cell_values: Vec::new(),
         });
     }
-    // Actual keccaks
-    for bytes in bytes {
-        keccak(&mut rows, bytes, challenges);
-    }
+    // Actual keccaks, whose permutations are independent so they're computed in
+    // parallel and concatenated in the order of the inputs
+    let keccak_rows: Vec<_> = bytes
+        .par_iter()
+        .map(|bytes| {
+            let mut rows = Vec::new();
+            keccak(&mut rows, bytes, challenges);
+            rows
+        })
+        .collect();
+    rows.extend(keccak_rows.into_iter().flatten());
     if let Some(capacity) = capacity {
         let padding_rows = {
             let mut rows = Vec::new();
@@ -143,3 +143,110 @@ fn test_word_parts() {
         }
     }
 }
+
+#[test]
+fn packed_multi_keccak_duplicates() {
+    let k = 15;
+    let inputs = vec![
+        (0u8..135).collect::<Vec<_>>(),
+        vec![],
+        (0u8..135).collect::<Vec<_>>(),
+        (0u8..200).collect::<Vec<_>>(),
+        vec![],
+    ];
+    let circuit = KeccakCircuit::<Fr>::new(2usize.pow(k), inputs.clone());
+    assert_eq!(circuit.inputs.len(), 3);
+    verify::<Fr>(k, inputs, true);
+}
+
+#[test]
+fn multi_keccak_matches_sequential() {
+    let inputs: Vec<Vec<u8>> = (0..20).map(|len| vec![len as u8; len * 17]).collect();
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(3)),
+        Value::known(Fr::from(5)),
+        Value::known(Fr::from(7)),
+    );
+    let parallel = multi_keccak(&inputs, challenges, None).unwrap();
+
+    let mut sequential = Vec::new();
+    for bytes in inputs.iter() {
+        keccak_packed_multi::keccak(&mut sequential, bytes, challenges);
+    }
+    // Skip the dummy first round
+    let parallel = &parallel[get_num_rows_per_round()..];
+    assert_eq!(parallel.len(), sequential.len());
+    for (parallel, sequential) in parallel.iter().zip(sequential.iter()) {
+        assert_eq!(parallel.is_final, sequential.is_final);
+        assert_eq!(parallel.length, sequential.length);
+        assert_eq!(parallel.cell_values, sequential.cell_values);
+    }
+}
+
+#[test]
+fn keccak_capacity_report() {
+    let bytecode = vec![0x60; 300];
+    let inputs = vec![
+        (KeccakInputSource::Tx, vec![1u8; 64]),
+        (KeccakInputSource::Bytecode, bytecode.clone()),
+        (KeccakInputSource::Sha3, bytecode),
+        (KeccakInputSource::Sha3, vec![]),
+        (KeccakInputSource::Pi, vec![2u8; 136]),
+    ];
+    let report = KeccakCapacityReport::new(&inputs);
+
+    let rows_per_keccak_f = get_num_rows_per_keccak_f();
+    assert_eq!(
+        report.sources[&KeccakInputSource::Bytecode],
+        KeccakSourceUsage {
+            inputs: 1,
+            duplicates: 0,
+            keccak_fs: 3,
+            rows: 3 * rows_per_keccak_f,
+        }
+    );
+    assert_eq!(
+        report.sources[&KeccakInputSource::Sha3],
+        KeccakSourceUsage {
+            inputs: 1,
+            duplicates: 1,
+            keccak_fs: 1,
+            rows: rows_per_keccak_f,
+        }
+    );
+    // 136 bytes need an extra permutation for the padding
+    assert_eq!(report.sources[&KeccakInputSource::Pi].keccak_fs, 2);
+    assert_eq!(report.keccak_fs(), 1 + 3 + 1 + 2);
+    assert_eq!(report.rows(), report.keccak_fs() * rows_per_keccak_f);
+
+    // The deduplicated inputs fit in the rows reported
+    let k = 15;
+    let circuit = KeccakCircuit::<Fr>::new(
+        2usize.pow(k),
+        inputs.into_iter().map(|(_, input)| input).collect(),
+    );
+    let witness = circuit.generate_witness(Challenges::mock(
+        Value::known(Fr::from(3)),
+        Value::known(Fr::from(5)),
+        Value::known(Fr::from(7)),
+    ));
+    assert!(witness.len() >= report.rows());
+}
+
+#[test]
+fn keccak_capacity_report_from_builder() {
+    let block: eth_types::geth_types::GethData =
+        mock::TestContext::<2, 1>::simple_ctx_with_bytecode(eth_types::bytecode! { STOP })
+            .unwrap()
+            .into();
+    let mut builder =
+        bus_mapping::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+
+    let report = KeccakCapacityReport::from_builder::<Fr>(&builder).unwrap();
+    assert_eq!(report.sources[&KeccakInputSource::Pi].inputs, 1);
+    assert!(report.sources[&KeccakInputSource::Pi].keccak_fs > 1);
+    assert!(report.sources[&KeccakInputSource::Tx].inputs > 0);
+}
@@ -24,6 +24,7 @@ pub mod evm_circuit;
 pub mod exp_circuit;
 pub mod keccak_circuit;
 pub mod pi_circuit;
+pub mod receipt_circuit;
 pub mod root_circuit;
 pub mod state_circuit;
 pub mod super_circuit;
@@ -1,26 +1,26 @@
 //! Public Input Circuit implementation
+mod keccak;
 mod param;
 
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod dev;
-
-// FIXME: ignore unused long-time running tests
-// #[cfg(any(feature = "test", test))]
-// #[cfg(any(feature = "test", test))]
-// mod test;
+#[cfg(any(feature = "test", test))]
+mod test;
 
 use eth_types::{
     geth_types::{BlockConstants, Transaction},
     sign_types::SignData,
     Address, BigEndianHash, Field, ToBigEndian, ToLittleEndian, ToScalar, Word, H256,
 };
-use halo2_proofs::plonk::{Instance, SecondPhase};
-use keccak256::plain::Keccak;
+use halo2_proofs::plonk::{Expression, Instance, SecondPhase};
+use itertools::Itertools;
+use keccak::{PiKeccakConfig, PiWord};
 use param::*;
 use std::marker::PhantomData;
 
 use crate::{
-    table::{BlockTable, LookupTable, TxFieldTag, TxTable},
+    evm_circuit::param::N_BYTES_WORD,
+    table::{BlockTable, KeccakTable, LookupTable, TxFieldTag, TxTable},
     tx_circuit::TX_LEN,
     util::{random_linear_combine_word as rlc, Challenges, SubCircuit, SubCircuitConfig},
     witness,
@@ -48,9 +48,28 @@ pub struct BlockValues {
     mix_hash: H256,
     base_fee: Word, // NOTE: BaseFee was added by EIP-1559 and is ignored in legacy headers.
     chain_id: u64,
+    rwc_offset: u64,
+    tx_id_offset: u64,
+    cumulative_gas_used_offset: u64,
+    rwc_end: u64,
+    tx_id_end: u64,
+    cumulative_gas_used_end: u64,
     history_hashes: Vec<H256>,
 }
 
+/// Values of a block following the first one in a batch
+#[derive(Clone, Default, Debug)]
+pub struct BatchBlockValues {
+    coinbase: Address,
+    gas_limit: u64,
+    number: u64,
+    timestamp: u64,
+    mix_hash: H256,
+    base_fee: Word,
+    parent_hash: H256,
+    state_root: H256,
+}
+
 /// Values of the tx table (as in the spec)
 #[derive(Default, Debug, Clone)]
 pub struct TxValues {
@@ -68,6 +87,7 @@ pub struct TxValues {
     v: u64,
     r: Word,
     s: Word,
+    block_number: u64,
 }
 
 /// Extra values (not contained in block or tx tables)
@@ -76,6 +96,7 @@ pub struct ExtraValues {
     // block_hash: H256,
     state_root: H256,
     prev_state_root: H256,
+    receipts_root: H256,
 }
 
 /// PublicData contains all the values that the PiCircuit recieves as input
@@ -92,8 +113,40 @@ pub struct PublicData {
     pub state_root: H256,
     /// Previous block root
     pub prev_state_root: H256,
+    /// Receipts root of the block header, committed as is: no circuit
+    /// rebuilds the receipts trie
+    pub receipts_root: H256,
+    /// Constants related to Ethereum block
+    pub block_constants: BlockConstants,
+    /// Blocks following the first one when a batch of consecutive blocks is
+    /// proven together
+    pub batch_blocks: Vec<BatchBlock>,
+    /// Number of rw operations of the block preceding the proven chunk, when
+    /// the block is split into chunks
+    pub rwc_offset: u64,
+    /// Number of txs of the block preceding the proven chunk
+    pub tx_id_offset: u64,
+    /// Gas used by the txs of the block preceding the proven chunk
+    pub cumulative_gas_used_offset: u64,
+    /// Block rw counter following the rw operations of the proven chunk
+    pub rwc_end: u64,
+    /// Number of txs of the block up to the end of the proven chunk
+    pub tx_id_end: u64,
+    /// Gas used by the txs of the block up to the end of the proven chunk
+    pub cumulative_gas_used_end: u64,
+}
+
+/// Public data of a block following the first one in a batch
+#[derive(Debug, Clone, Default)]
+pub struct BatchBlock {
+    /// Hash of the parent block, which is the previous block of the batch
+    pub parent_hash: H256,
+    /// State root after the block
+    pub state_root: H256,
     /// Constants related to Ethereum block
     pub block_constants: BlockConstants,
+    /// Block Transactions
+    pub transactions: Vec<eth_types::Transaction>,
 }
 
 impl Default for PublicData {
@@ -104,7 +157,15 @@ impl Default for PublicData {
             transactions: vec![],
             state_root: H256::zero(),
             prev_state_root: H256::zero(),
+            receipts_root: H256::zero(),
             block_constants: BlockConstants::default(),
+            batch_blocks: vec![],
+            rwc_offset: 0,
+            tx_id_offset: 0,
+            cumulative_gas_used_offset: 0,
+            rwc_end: 0,
+            tx_id_end: 0,
+            cumulative_gas_used_end: 0,
         }
     }
 }
@@ -128,10 +189,33 @@ impl PublicData {
             mix_hash: self.block_constants.mix_hash,
             base_fee: self.block_constants.base_fee,
             chain_id: self.chain_id.as_u64(),
+            rwc_offset: self.rwc_offset,
+            tx_id_offset: self.tx_id_offset,
+            cumulative_gas_used_offset: self.cumulative_gas_used_offset,
+            rwc_end: self.rwc_end,
+            tx_id_end: self.tx_id_end,
+            cumulative_gas_used_end: self.cumulative_gas_used_end,
             history_hashes,
         }
     }
 
+    /// Returns the values of the blocks following the first one in a batch
+    pub fn get_batch_block_values(&self) -> Vec<BatchBlockValues> {
+        self.batch_blocks
+            .iter()
+            .map(|block| BatchBlockValues {
+                coinbase: block.block_constants.coinbase,
+                gas_limit: block.block_constants.gas_limit.as_u64(),
+                number: block.block_constants.number.as_u64(),
+                timestamp: block.block_constants.timestamp.as_u64(),
+                mix_hash: block.block_constants.mix_hash,
+                base_fee: block.block_constants.base_fee,
+                parent_hash: block.parent_hash,
+                state_root: block.state_root,
+            })
+            .collect()
+    }
+
     /// Returns struct with values for the tx table
     pub fn get_tx_table_values(&self) -> Vec<TxValues> {
         let chain_id: u64 = self
@@ -139,7 +223,7 @@ impl PublicData {
             .try_into()
             .expect("Error converting chain_id to u64");
         let mut tx_vals = vec![];
-        for tx in &self.txs() {
+        for (tx, block_number) in self.txs().iter().zip(self.tx_block_numbers()) {
             let sign_data: SignData = tx
                 .sign_data(chain_id)
                 .expect("Error computing tx_sign_hash");
@@ -159,6 +243,7 @@ impl PublicData {
                 r: tx.r,
                 s: tx.s,
                 v: tx.v,
+                block_number,
             });
         }
         tx_vals
@@ -170,14 +255,63 @@ impl PublicData {
             // block_hash: self.hash.unwrap_or_else(H256::zero),
             state_root: self.state_root,
             prev_state_root: self.prev_state_root,
+            receipts_root: self.receipts_root,
         }
     }
 
+    /// Number of blocks proven together
+    pub fn num_blocks(&self) -> usize {
+        1 + self.batch_blocks.len()
+    }
+
+    /// Transactions of all the blocks, in order
     fn txs(&self) -> Vec<Transaction> {
-        self.transactions.iter().map(Transaction::from).collect()
+        self.transactions
+            .iter()
+            .chain(
+                self.batch_blocks
+                    .iter()
+                    .flat_map(|block| &block.transactions),
+            )
+            .map(Transaction::from)
+            .collect()
+    }
+
+    /// Number of the block including each transaction of [`Self::txs`]
+    fn tx_block_numbers(&self) -> Vec<u64> {
+        std::iter::repeat(self.block_constants.number.as_u64())
+            .take(self.transactions.len())
+            .chain(self.batch_blocks.iter().flat_map(|block| {
+                std::iter::repeat(block.block_constants.number.as_u64())
+                    .take(block.transactions.len())
+            }))
+            .collect()
     }
 }
 
+/// Number of rows of the `raw_public_inputs` column
+fn raw_public_inputs_len(max_txs: usize, max_calldata: usize, num_blocks: usize) -> usize {
+    // +1 empty row in block table, +1 empty row in tx_table
+    BLOCK_LEN
+        + 1
+        + EXTRA_LEN
+        + 3 * (TX_LEN * max_txs + 1)
+        + max_calldata
+        + num_blocks.saturating_sub(1) * BATCH_BLOCK_LEN
+}
+
+/// Commitment to the raw public inputs exposed in the instance column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PiCommitment {
+    /// `rand_rpi`, `RLC(raw_public_inputs, rand_rpi)`, the chain id, the state
+    /// root and the previous state root
+    #[default]
+    Rlc,
+    /// Hi and lo 128 bits of the keccak digest of the raw public inputs, each
+    /// one encoded as a 32 bytes big-endian word
+    Keccak,
+}
+
 /// Config for PiCircuit
 #[derive(Clone, Debug)]
 pub struct PiCircuitConfig<F: Field> {
@@ -185,6 +319,8 @@ pub struct PiCircuitConfig<F: Field> {
     max_txs: usize,
     /// Max number of supported calldata bytes
     max_calldata: usize,
+    /// Number of blocks proven together
+    num_blocks: usize,
 
     q_block_table: Selector,
     q_tx_table: Selector,
@@ -204,28 +340,40 @@ pub struct PiCircuitConfig<F: Field> {
     q_not_end: Selector,
     q_end: Selector,
 
-    pi: Column<Instance>, // rpi_rand, rpi_rlc, chain_ID, state_root, prev_state_root
+    // rpi_rand, rpi_rlc, chain_ID, state_root, prev_state_root, receipts_root,
+    // or the keccak digest hi and lo
+    pi: Column<Instance>,
+    keccak: Option<PiKeccakConfig>,
 
     _marker: PhantomData<F>,
     // External tables
     block_table: BlockTable,
     tx_table: TxTable,
+    keccak_table: KeccakTable,
 }
 
 /// Circuit configuration arguments
-pub struct PiCircuitConfigArgs {
+pub struct PiCircuitConfigArgs<F: Field> {
     /// Max number of supported transactions
     pub max_txs: usize,
     /// Max number of supported calldata bytes
     pub max_calldata: usize,
+    /// Number of blocks proven together
+    pub num_blocks: usize,
+    /// Commitment exposed in the instance column
+    pub commitment: PiCommitment,
     /// TxTable
     pub tx_table: TxTable,
     /// BlockTable
     pub block_table: BlockTable,
+    /// KeccakTable, only looked up with [`PiCommitment::Keccak`]
+    pub keccak_table: KeccakTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
 }
 
 impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
-    type ConfigArgs = PiCircuitConfigArgs;
+    type ConfigArgs = PiCircuitConfigArgs<F>;
 
     /// Return a new PiCircuitConfig
     fn new(
@@ -233,8 +381,12 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         Self::ConfigArgs {
             max_txs,
             max_calldata,
+            num_blocks,
+            commitment,
             block_table,
             tx_table,
+            keccak_table,
+            challenges,
         }: Self::ConfigArgs,
     ) -> Self {
         let q_block_table = meta.selector();
@@ -544,9 +696,15 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             ]
         });
 
+        let keccak = (commitment == PiCommitment::Keccak).then(|| {
+            let num_values = raw_public_inputs_len(max_txs, max_calldata, num_blocks);
+            PiKeccakConfig::configure(meta, num_values, &keccak_table, &challenges)
+        });
+
         Self {
             max_txs,
             max_calldata,
+            num_blocks,
             q_block_table,
             block_table,
             q_tx_table,
@@ -565,7 +723,9 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             q_not_end,
             q_end,
             pi,
+            keccak,
             _marker: PhantomData,
+            keccak_table,
         }
     }
 }
@@ -574,8 +734,7 @@ impl<F: Field> PiCircuitConfig<F> {
     /// Return the number of rows in the circuit
     #[inline]
     fn circuit_len(&self) -> usize {
-        // +1 empty row in block table, +1 empty row in tx_table
-        BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * self.max_txs + 1) + self.max_calldata
+        raw_public_inputs_len(self.max_txs, self.max_calldata, self.num_blocks)
     }
 
     fn assign_tx_empty_row(&self, region: &mut Region<'_, F>, offset: usize) -> Result<(), Error> {
@@ -979,6 +1138,32 @@ impl<F: Field> PiCircuitConfig<F> {
         raw_pi_vals[offset] = chain_id;
         offset += 1;
 
+        // offsets and ends of the chunk of the block
+        for offset_value in [
+            block_values.rwc_offset,
+            block_values.tx_id_offset,
+            block_values.cumulative_gas_used_offset,
+            block_values.rwc_end,
+            block_values.tx_id_end,
+            block_values.cumulative_gas_used_end,
+        ] {
+            let offset_value = F::from(offset_value);
+            region.assign_advice(
+                || "chunk offset",
+                self.block_table.value,
+                offset,
+                || Value::known(offset_value),
+            )?;
+            region.assign_advice(
+                || "chunk offset",
+                self.raw_public_inputs,
+                offset,
+                || Value::known(offset_value),
+            )?;
+            raw_pi_vals[offset] = offset_value;
+            offset += 1;
+        }
+
         for prev_hash in block_values.history_hashes {
             let prev_hash = rlc(prev_hash.to_fixed_bytes(), randomness);
             region.assign_advice(
@@ -1003,6 +1188,7 @@ impl<F: Field> PiCircuitConfig<F> {
     /// Assigns the extra fields (not in block or tx tables):
     ///   - state root
     ///   - previous block state root
+    ///   - receipts root of the block header, not proven by any circuit
     /// to the raw_public_inputs column and stores a copy in a
     /// vector for computing RLC(raw_public_inputs).
     fn assign_extra_fields(
@@ -1011,7 +1197,7 @@ impl<F: Field> PiCircuitConfig<F> {
         extra: ExtraValues,
         randomness: F,
         raw_pi_vals: &mut [F],
-    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+    ) -> Result<[AssignedCell<F, F>; EXTRA_LEN], Error> {
         let mut offset = BLOCK_LEN + 1;
         // block hash
         // let block_hash = rlc(extra.block_hash.to_fixed_bytes(), randomness);
@@ -1044,7 +1230,60 @@ impl<F: Field> PiCircuitConfig<F> {
             || Value::known(prev_state_root),
         )?;
         raw_pi_vals[offset] = prev_state_root;
-        Ok([state_root_cell, prev_state_root_cell])
+        offset += 1;
+
+        // receipts root of the block header
+        let receipts_root = rlc(extra.receipts_root.to_fixed_bytes(), randomness);
+        let receipts_root_cell = region.assign_advice(
+            || "receipts.root",
+            self.raw_public_inputs,
+            offset,
+            || Value::known(receipts_root),
+        )?;
+        raw_pi_vals[offset] = receipts_root;
+        Ok([state_root_cell, prev_state_root_cell, receipts_root_cell])
+    }
+
+    /// Assigns the values of the blocks following the first one in a batch,
+    /// after the calldata, to the raw_public_inputs column.  The header
+    /// fields are also assigned to the block_table column, and a copy is
+    /// stored in a vector for computing RLC(raw_public_inputs).
+    fn assign_batch_blocks(
+        &self,
+        region: &mut Region<'_, F>,
+        batch_blocks: Vec<BatchBlockValues>,
+        randomness: F,
+        raw_pi_vals: &mut [F],
+    ) -> Result<(), Error> {
+        assert_eq!(batch_blocks.len() + 1, self.num_blocks);
+        let mut offset =
+            BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * self.max_txs + 1) + self.max_calldata;
+        for block in batch_blocks {
+            for (index, value) in batch_block_raw_values(&block, randomness)
+                .into_iter()
+                .enumerate()
+            {
+                // The state root is the only field not in the block table
+                if index < BATCH_BLOCK_LEN - 1 {
+                    self.q_block_table.enable(region, offset)?;
+                    region.assign_advice(
+                        || "batch block",
+                        self.block_table.value,
+                        offset,
+                        || Value::known(value),
+                    )?;
+                }
+                region.assign_advice(
+                    || "batch block",
+                    self.raw_public_inputs,
+                    offset,
+                    || Value::known(value),
+                )?;
+                raw_pi_vals[offset] = value;
+                offset += 1;
+            }
+        }
+        Ok(())
     }
 
     /// Assign `rpi_rlc_acc` and `rand_rpi` columns
@@ -1121,6 +1360,8 @@ pub struct PiCircuit<F: Field> {
     pub rand_rpi: F,
     /// PublicInputs data known by the verifier
     pub public_data: PublicData,
+    /// Commitment exposed in the instance column
+    pub commitment: PiCommitment,
 }
 
 impl<F: Field> PiCircuit<F> {
@@ -1138,8 +1379,37 @@ impl<F: Field> PiCircuit<F> {
             randomness: randomness.into(),
             rand_rpi: rand_rpi.into(),
             public_data,
+            commitment: PiCommitment::default(),
         }
     }
+
+    /// Expose the `commitment` to the raw public inputs in the instance
+    /// column.  [`PiCommitment::Keccak`] needs 32 more rows per raw public
+    /// input.
+    pub fn with_commitment(mut self, commitment: PiCommitment) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Number of rows used by the circuit, with the bytes of the raw public
+    /// inputs and of their digest when committed with
+    /// [`PiCommitment::Keccak`].
+    pub fn num_rows(&self) -> usize {
+        let len = raw_public_inputs_len(
+            self.max_txs,
+            self.max_calldata,
+            self.public_data.num_blocks(),
+        );
+        match self.commitment {
+            PiCommitment::Rlc => len,
+            PiCommitment::Keccak => (len + 1) * N_BYTES_WORD,
+        }
+    }
+
+    /// Keccak input hashed with [`PiCommitment::Keccak`]
+    pub fn keccak_input(&self) -> Vec<u8> {
+        keccak_pi_input(self.max_txs, self.max_calldata, &self.public_data)
+    }
 }
 
 impl<F: Field> SubCircuit<F> for PiCircuit<F> {
@@ -1156,20 +1426,51 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
     }
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
+        let block_constants = |context: &witness::BlockContext| BlockConstants {
+            coinbase: context.coinbase,
+            timestamp: context.timestamp,
+            number: context.number.as_u64().into(),
+            mix_hash: context.mix_hash,
+            gas_limit: context.gas_limit.into(),
+            base_fee: context.base_fee,
+        };
+        // A chunk of a block only holds some of its txs, and ends at the state
+        // root following the last one.
+        let (transactions, state_root) = match block.chunk {
+            Some(chunk) => (
+                block.eth_block.transactions[chunk.initial_tx_index..chunk.end_tx_index].to_vec(),
+                H256::from_uint(&chunk.end_state_root),
+            ),
+            None => (
+                block.eth_block.transactions.clone(),
+                block.eth_block.state_root,
+            ),
+        };
         let public_data = PublicData {
             chain_id: block.context.chain_id,
             history_hashes: block.context.history_hashes.clone(),
-            transactions: block.eth_block.transactions.clone(),
-            state_root: block.eth_block.state_root,
+            transactions,
+            state_root,
             prev_state_root: H256::from_uint(&block.prev_state_root),
-            block_constants: BlockConstants {
-                coinbase: block.context.coinbase,
-                timestamp: block.context.timestamp,
-                number: block.context.number.as_u64().into(),
-                mix_hash: block.context.mix_hash,
-                gas_limit: block.context.gas_limit.into(),
-                base_fee: block.context.base_fee,
-            },
+            receipts_root: block.eth_block.receipts_root,
+            block_constants: block_constants(&block.context),
+            batch_blocks: block
+                .batch_contexts
+                .values()
+                .zip_eq(block.batch_eth_blocks.iter())
+                .map(|(context, eth_block)| BatchBlock {
+                    parent_hash: eth_block.parent_hash,
+                    state_root: eth_block.state_root,
+                    block_constants: block_constants(context),
+                    transactions: eth_block.transactions.clone(),
+                })
+                .collect(),
+            rwc_offset: block.context.rwc_offset,
+            tx_id_offset: block.context.tx_id_offset,
+            cumulative_gas_used_offset: block.context.cumulative_gas_used_offset,
+            rwc_end: block.context.rwc_end,
+            tx_id_end: block.context.tx_id_end,
+            cumulative_gas_used_end: block.context.cumulative_gas_used_end,
         };
         let rand_rpi = gen_rand_rpi::<F>(
             block.circuits_params.max_txs,
@@ -1188,9 +1489,9 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
-        let row_num = |tx_num, calldata_len| {
-            BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * tx_num + 1) + calldata_len
-        };
+        let num_blocks = block.contexts().count();
+        let row_num =
+            |tx_num, calldata_len| raw_public_inputs_len(tx_num, calldata_len, num_blocks);
         let calldata_len = block.txs.iter().map(|tx| tx.call_data.len()).sum();
         (
             row_num(block.txs.len(), calldata_len),
@@ -1203,6 +1504,11 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
 
     /// Compute the public inputs for this circuit.
     fn instance(&self) -> Vec<Vec<F>> {
+        if self.commitment == PiCommitment::Keccak {
+            let hi_lo = keccak_pi_hi_lo::<F>(self.max_txs, self.max_calldata, &self.public_data);
+            return vec![hi_lo.to_vec()];
+        }
+
         let rlc_rpi_col = raw_public_inputs_col::<F>(
             self.max_txs,
             self.max_calldata,
@@ -1211,7 +1517,11 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
         );
         assert_eq!(
             rlc_rpi_col.len(),
-            BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * self.max_txs + 1) + self.max_calldata
+            raw_public_inputs_len(
+                self.max_txs,
+                self.max_calldata,
+                self.public_data.num_blocks()
+            )
         );
 
         // Computation of raw_pulic_inputs
@@ -1237,6 +1547,10 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 self.public_data.prev_state_root.to_fixed_bytes(),
                 self.randomness,
             ),
+            rlc(
+                self.public_data.receipts_root.to_fixed_bytes(),
+                self.randomness,
+            ),
         ];
 
         vec![public_inputs]
@@ -1246,9 +1560,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
     fn synthesize_sub(
         &self,
         config: &Self::Config,
-        _challenges: &Challenges<Value<F>>,
+        challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
+        assert_eq!(
+            self.commitment == PiCommitment::Keccak,
+            config.keccak.is_some(),
+            "circuit and config commitments differ"
+        );
         layouter.assign_region(
             || "fixed u16 table",
             |mut region| {
@@ -1264,6 +1583,19 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 Ok(())
             },
         )?;
+        if let Some(keccak) = &config.keccak {
+            keccak.load(layouter)?;
+        }
+        // The words hashed by the keccak commitment are RLC encoded with the
+        // evm_word challenge, which the keccak config constrains.  Otherwise
+        // the verifier rebuilds the raw public inputs with `self.randomness`.
+        let randomness = if config.keccak.is_some() {
+            let mut randomness = F::ZERO;
+            challenges.evm_word().map(|r| randomness = r);
+            randomness
+        } else {
+            self.randomness
+        };
         let pi_cells = layouter.assign_region(
             || "region 0",
             |mut region| {
@@ -1294,16 +1626,16 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 let chain_id = config.assign_block_table(
                     &mut region,
                     block_values,
-                    self.randomness,
+                    randomness,
                     &mut raw_pi_vals,
                 )?;
 
                 // Assign extra fields
                 let extra_vals = self.public_data.get_extra_values();
-                let [state_root, prev_state_root] = config.assign_extra_fields(
+                let [state_root, prev_state_root, receipts_root] = config.assign_extra_fields(
                     &mut region,
                     extra_vals,
-                    self.randomness,
+                    randomness,
                     &mut raw_pi_vals,
                 )?;
 
@@ -1311,6 +1643,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 // Assign Tx table
                 let txs = self.public_data.get_tx_table_values();
                 assert!(txs.len() <= config.max_txs);
+                assert_eq!(self.public_data.num_blocks(), config.num_blocks);
                 let tx_default = TxValues::default();
 
                 // Add empty row
@@ -1333,7 +1666,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                         (TxFieldTag::Gas, F::from(tx.gas)),
                         (
                             TxFieldTag::GasPrice,
-                            rlc(tx.gas_price.to_le_bytes(), self.randomness),
+                            rlc(tx.gas_price.to_le_bytes(), randomness),
                         ),
                         (
                             TxFieldTag::CallerAddress,
@@ -1344,19 +1677,14 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                             tx.to_addr.to_scalar().expect("tx.to too big"),
                         ),
                         (TxFieldTag::IsCreate, F::from(tx.is_create as u64)),
-                        (
-                            TxFieldTag::Value,
-                            rlc(tx.value.to_le_bytes(), self.randomness),
-                        ),
+                        (TxFieldTag::Value, rlc(tx.value.to_le_bytes(), randomness)),
                         (TxFieldTag::CallDataLength, F::from(tx.call_data_len)),
                         (TxFieldTag::CallDataGasCost, F::from(tx.call_data_gas_cost)),
-                        (
-                            TxFieldTag::TxSignHash,
-                            rlc(tx.tx_sign_hash, self.randomness),
-                        ),
+                        (TxFieldTag::TxSignHash, rlc(tx.tx_sign_hash, randomness)),
                         (TxFieldTag::SigV, F::from(tx.v)),
-                        (TxFieldTag::SigR, rlc(tx.r.to_le_bytes(), self.randomness)),
-                        (TxFieldTag::SigS, rlc(tx.s.to_le_bytes(), self.randomness)),
+                        (TxFieldTag::SigR, rlc(tx.r.to_le_bytes(), randomness)),
+                        (TxFieldTag::SigS, rlc(tx.s.to_le_bytes(), randomness)),
+                        (TxFieldTag::BlockNumber, F::from(tx.block_number)),
                     ] {
                         config.assign_tx_row(
                             &mut region,
@@ -1435,6 +1763,23 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 let tx_table_len = TX_LEN * self.max_txs + 1;
                 config.assign_tx_empty_row(&mut region, tx_table_len + offset)?;
 
+                // Assign the blocks following the first one
+                config.assign_batch_blocks(
+                    &mut region,
+                    self.public_data.get_batch_block_values(),
+                    randomness,
+                    &mut raw_pi_vals,
+                )?;
+
+                if let Some(keccak) = &config.keccak {
+                    let words =
+                        raw_public_inputs_words(self.max_txs, self.max_calldata, &self.public_data);
+                    let hi_lo =
+                        keccak.assign(&mut region, config.raw_public_inputs, &words, challenges)?;
+                    config.assign_rlc_pi(&mut region, self.rand_rpi, raw_pi_vals)?;
+                    return Ok(hi_lo.to_vec());
+                }
+
                 // rpi_rlc and rand_rpi cols
                 let (rpi_rand, rpi_rlc) =
                     config.assign_rlc_pi(&mut region, self.rand_rpi, raw_pi_vals)?;
@@ -1445,6 +1790,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     chain_id,
                     state_root,
                     prev_state_root,
+                    receipts_root,
                 ])
             },
         )?;
@@ -1465,52 +1811,79 @@ fn raw_public_inputs_col<F: Field>(
     public_data: &PublicData,
     randomness: F, // For RLC encoding
 ) -> Vec<F> {
+    raw_public_inputs_words(max_txs, max_calldata, public_data)
+        .iter()
+        .map(|word| word.value(randomness))
+        .collect()
+}
+
+/// Compute the big-endian words of the raw_public_inputs column, hashed with
+/// [`PiCommitment::Keccak`].
+fn raw_public_inputs_words(
+    max_txs: usize,
+    max_calldata: usize,
+    public_data: &PublicData,
+) -> Vec<PiWord> {
     let block = public_data.get_block_table_values();
     let extra = public_data.get_extra_values();
     let txs = public_data.get_tx_table_values();
 
     let mut offset = 0;
-    let mut result =
-        vec![F::ZERO; BLOCK_LEN + 1 + EXTRA_LEN + 3 * (TX_LEN * max_txs + 1) + max_calldata];
+    let mut result = vec![
+        PiWord::default();
+        raw_public_inputs_len(max_txs, max_calldata, public_data.num_blocks())
+    ];
 
     //  Insert Block Values
     // zero row
-    result[offset] = F::ZERO;
+    result[offset] = PiWord::scalar(0);
     offset += 1;
     // coinbase
-    result[offset] = block.coinbase.to_scalar().unwrap();
+    result[offset] = PiWord::address(block.coinbase);
     offset += 1;
     // gas_limit
-    result[offset] = F::from(block.gas_limit);
+    result[offset] = PiWord::scalar(block.gas_limit);
     offset += 1;
     // number
-    result[offset] = F::from(block.number);
+    result[offset] = PiWord::scalar(block.number);
     offset += 1;
     // timestamp
-    result[offset] = F::from(block.timestamp);
+    result[offset] = PiWord::scalar(block.timestamp);
     offset += 1;
     // mix hash
-    let mut mix_hash_bytes = block.mix_hash.to_fixed_bytes();
-    mix_hash_bytes.reverse();
-    result[offset] = rlc(mix_hash_bytes, randomness);
+    result[offset] = PiWord::rlc_le(block.mix_hash.to_fixed_bytes());
     offset += 1;
     // base_fee
-    result[offset] = rlc(block.base_fee.to_le_bytes(), randomness);
+    result[offset] = PiWord::rlc_le(block.base_fee.to_be_bytes());
     offset += 1;
     // chain_id
-    result[offset] = F::from(block.chain_id);
+    result[offset] = PiWord::scalar(block.chain_id);
     offset += 1;
+    // chunk offsets and ends
+    for offset_value in [
+        block.rwc_offset,
+        block.tx_id_offset,
+        block.cumulative_gas_used_offset,
+        block.rwc_end,
+        block.tx_id_end,
+        block.cumulative_gas_used_end,
+    ] {
+        result[offset] = PiWord::scalar(offset_value);
+        offset += 1;
+    }
     // Previous block hashes
     for prev_hash in block.history_hashes {
-        result[offset] = rlc(prev_hash.to_fixed_bytes(), randomness);
+        result[offset] = PiWord::rlc_be(prev_hash.to_fixed_bytes());
         offset += 1;
     }
 
     // Insert Extra Values
     // block Root
-    result[BLOCK_LEN + 1] = rlc(extra.state_root.to_fixed_bytes(), randomness);
+    result[BLOCK_LEN + 1] = PiWord::rlc_be(extra.state_root.to_fixed_bytes());
     // parent block hash
-    result[BLOCK_LEN + 2] = rlc(extra.prev_state_root.to_fixed_bytes(), randomness);
+    result[BLOCK_LEN + 2] = PiWord::rlc_be(extra.prev_state_root.to_fixed_bytes());
+    // receipts root
+    result[BLOCK_LEN + 3] = PiWord::rlc_be(extra.receipts_root.to_fixed_bytes());
 
     // Insert Tx table
     offset = 0;
@@ -1524,55 +1897,91 @@ fn raw_public_inputs_col<F: Field>(
     let value_offset = index_offset + tx_table_len;
 
     // Insert zero row
-    result[id_offset + offset] = F::ZERO;
-    result[index_offset + offset] = F::ZERO;
-    result[value_offset + offset] = F::ZERO;
+    result[id_offset + offset] = PiWord::scalar(0);
+    result[index_offset + offset] = PiWord::scalar(0);
+    result[value_offset + offset] = PiWord::scalar(0);
 
     offset += 1;
 
     for i in 0..max_txs {
         let tx = if i < txs.len() { &txs[i] } else { &tx_default };
+        let mut tx_sign_hash = tx.tx_sign_hash;
+        tx_sign_hash.reverse();
 
         for val in &[
-            F::from(tx.nonce),
-            F::from(tx.gas),
-            rlc(tx.gas_price.to_le_bytes(), randomness),
-            tx.from_addr.to_scalar().expect("tx.from too big"),
-            tx.to_addr.to_scalar().expect("tx.to too big"),
-            F::from(tx.is_create as u64),
-            rlc(tx.value.to_le_bytes(), randomness),
-            F::from(tx.call_data_len),
-            F::from(tx.call_data_gas_cost),
-            rlc(tx.tx_sign_hash, randomness),
-            F::from(tx.v),
-            rlc(tx.r.to_le_bytes(), randomness),
-            rlc(tx.s.to_le_bytes(), randomness),
+            PiWord::scalar(tx.nonce),
+            PiWord::scalar(tx.gas),
+            PiWord::rlc_le(tx.gas_price.to_be_bytes()),
+            PiWord::address(tx.from_addr),
+            PiWord::address(tx.to_addr),
+            PiWord::scalar(tx.is_create as u64),
+            PiWord::rlc_le(tx.value.to_be_bytes()),
+            PiWord::scalar(tx.call_data_len),
+            PiWord::scalar(tx.call_data_gas_cost),
+            PiWord::rlc_le(tx_sign_hash),
+            PiWord::scalar(tx.v),
+            PiWord::rlc_le(tx.r.to_be_bytes()),
+            PiWord::rlc_le(tx.s.to_be_bytes()),
+            PiWord::scalar(tx.block_number),
         ] {
-            result[id_offset + offset] = F::from((i + 1) as u64);
-            result[index_offset + offset] = F::ZERO;
+            result[id_offset + offset] = PiWord::scalar((i + 1) as u64);
+            result[index_offset + offset] = PiWord::scalar(0);
             result[value_offset + offset] = *val;
 
             offset += 1;
         }
     }
-    // Tx Table CallData
+    // Tx Table CallData, after the static fields of all the txs
+    offset = tx_table_len;
     let mut calldata_count = 0;
     for (_i, tx) in public_data.txs().iter().enumerate() {
         for (_index, byte) in tx.call_data.0.iter().enumerate() {
             assert!(calldata_count < max_calldata);
-            result[value_offset + offset] = F::from(*byte as u64);
+            result[value_offset + offset] = PiWord::scalar(*byte as u64);
             offset += 1;
             calldata_count += 1;
         }
     }
     for _ in calldata_count..max_calldata {
-        result[value_offset + offset] = F::ZERO;
+        result[value_offset + offset] = PiWord::scalar(0);
         offset += 1;
     }
 
+    // Insert the blocks following the first one
+    offset += value_offset;
+    for block in public_data.get_batch_block_values() {
+        for word in batch_block_raw_words(&block) {
+            result[offset] = word;
+            offset += 1;
+        }
+    }
+
     result
 }
 
+/// Raw public inputs of a block following the first one in a batch, in the
+/// order of [`BatchBlockValues`].
+fn batch_block_raw_values<F: Field>(
+    block: &BatchBlockValues,
+    randomness: F,
+) -> [F; BATCH_BLOCK_LEN] {
+    batch_block_raw_words(block).map(|word| word.value(randomness))
+}
+
+/// Big-endian words of [`batch_block_raw_values`].
+fn batch_block_raw_words(block: &BatchBlockValues) -> [PiWord; BATCH_BLOCK_LEN] {
+    [
+        PiWord::address(block.coinbase),
+        PiWord::scalar(block.gas_limit),
+        PiWord::scalar(block.number),
+        PiWord::scalar(block.timestamp),
+        PiWord::rlc_le(block.mix_hash.to_fixed_bytes()),
+        PiWord::rlc_le(block.base_fee.to_be_bytes()),
+        PiWord::rlc_be(block.parent_hash.to_fixed_bytes()),
+        PiWord::rlc_be(block.state_root.to_fixed_bytes()),
+    ]
+}
+
 /// Computes `rand_rpi` - a commitment to the `raw_public_inputs_col` values.
 pub fn gen_rand_rpi<F: Field>(
     max_txs: usize,
@@ -1581,12 +1990,34 @@ pub fn gen_rand_rpi<F: Field>(
     randomness: F,
 ) -> F {
     let rlc_rpi_col = raw_public_inputs_col::<F>(max_txs, max_calldata, public_data, randomness);
-    let mut keccak = Keccak::default();
-    for value in rlc_rpi_col.iter() {
-        let mut tmp = value.to_repr();
-        tmp.reverse();
-        keccak.update(&tmp);
-    }
-    let rand_rpi = Word::from(keccak.digest().as_slice()) % F::MODULUS;
+    let input: Vec<u8> = rlc_rpi_col
+        .iter()
+        .flat_map(|value| {
+            let mut bytes = value.to_repr();
+            bytes.reverse();
+            bytes
+        })
+        .collect();
+    let digest = keccak::keccak(&input);
+    let rand_rpi = Word::from(digest.as_slice()) % F::MODULUS;
     rand_rpi.to_scalar().expect("rand_rpi.to_scalar")
 }
+
+/// Computes the public inputs of the circuit with [`PiCommitment::Keccak`]: the
+/// hi and lo 128 bits of the keccak digest of the big-endian bytes of the raw
+/// public inputs, each one encoded as a 32 bytes big-endian word.
+pub fn keccak_pi_hi_lo<F: Field>(
+    max_txs: usize,
+    max_calldata: usize,
+    public_data: &PublicData,
+) -> [F; 2] {
+    let input = keccak_pi_input(max_txs, max_calldata, public_data);
+    keccak::digest_hi_lo(&keccak::keccak(&input))
+}
+
+/// Keccak input hashed by the circuit with [`PiCommitment::Keccak`], to be
+/// loaded in the keccak table: the 32 big-endian bytes of each raw public
+/// input, independent of the RLC randomness.
+pub fn keccak_pi_input(max_txs: usize, max_calldata: usize, public_data: &PublicData) -> Vec<u8> {
+    keccak::raw_public_inputs_bytes(&raw_public_inputs_words(max_txs, max_calldata, public_data))
+}
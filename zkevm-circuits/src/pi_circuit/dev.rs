@@ -7,6 +7,10 @@ pub struct PiCircuitParams {
     pub max_txs: usize,
     /// Max Calldata
     pub max_calldata: usize,
+    /// Number of blocks proven together
+    pub num_blocks: usize,
+    /// Commitment exposed in the instance column
+    pub commitment: PiCommitment,
 }
 
 impl<F: Field> Circuit<F> for PiCircuit<F> {
@@ -22,23 +26,32 @@ impl<F: Field> Circuit<F> for PiCircuit<F> {
         PiCircuitParams {
             max_txs: self.max_txs,
             max_calldata: self.max_calldata,
+            num_blocks: self.public_data.num_blocks(),
+            commitment: self.commitment,
         }
     }
 
     fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
         let block_table = BlockTable::construct(meta);
         let tx_table = TxTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+        let challenge_exprs = challenges.exprs(meta);
         (
             PiCircuitConfig::new(
                 meta,
                 PiCircuitConfigArgs {
                     max_txs: params.max_txs,
                     max_calldata: params.max_calldata,
+                    num_blocks: params.num_blocks,
+                    commitment: params.commitment,
                     block_table,
                     tx_table,
+                    keccak_table,
+                    challenges: challenge_exprs,
                 },
             ),
-            Challenges::construct(meta),
+            challenges,
         )
     }
 
@@ -52,6 +65,12 @@ impl<F: Field> Circuit<F> for PiCircuit<F> {
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let challenges = challenges.values(&mut layouter);
+        if self.commitment == PiCommitment::Keccak {
+            let input = keccak_pi_input(self.max_txs, self.max_calldata, &self.public_data);
+            config
+                .keccak_table
+                .dev_load(&mut layouter, vec![&input], &challenges)?;
+        }
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
 }
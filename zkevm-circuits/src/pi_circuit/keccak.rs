@@ -0,0 +1,403 @@
+//! Keccak commitment to the raw public inputs.
+//!
+//! Each `raw_public_inputs` value is a [`PiWord`]: the 32 bytes of the
+//! big-endian encoding of a block or tx field, one byte per row, followed by
+//! the 32 bytes of the keccak digest of all of them.  The value of a word is
+//! rebuilt from its bytes as a scalar or as the RLC of its little-endian or
+//! big-endian bytes with the evm_word challenge, and copied from the
+//! `raw_public_inputs` cell.  The digest halves are exposed as public inputs.
+
+use super::param::*;
+use crate::{
+    evm_circuit::param::N_BYTES_WORD,
+    table::{KeccakTable, LookupTable},
+    util::Challenges,
+};
+use eth_types::{Address, Field, ToBigEndian, Word};
+use gadgets::util::{not, select, Expr};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, Selector},
+    poly::Rotation,
+};
+use keccak256::plain::Keccak;
+
+/// Length of the digest halves exposed as public inputs
+const N_BYTES_HALF_WORD: usize = N_BYTES_WORD / 2;
+
+/// How the value of a raw public input is encoded from its big-endian bytes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum PiEncoding {
+    /// Big-endian integer, for the fields that fit in the field
+    #[default]
+    Scalar,
+    /// RLC of the little-endian bytes, as the EVM words
+    RlcLe,
+    /// RLC of the big-endian bytes, as the hashes and roots
+    RlcBe,
+}
+
+/// Raw public input as the 32 big-endian bytes hashed by the keccak
+/// commitment
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct PiWord {
+    pub(super) bytes: [u8; N_BYTES_WORD],
+    pub(super) encoding: PiEncoding,
+}
+
+impl PiWord {
+    /// Integer value
+    pub(super) fn scalar(value: u64) -> Self {
+        Self {
+            bytes: Word::from(value).to_be_bytes(),
+            encoding: PiEncoding::Scalar,
+        }
+    }
+
+    /// Address, as the integer of its 20 bytes
+    pub(super) fn address(address: Address) -> Self {
+        let mut bytes = [0; N_BYTES_WORD];
+        bytes[N_BYTES_WORD - 20..].copy_from_slice(address.as_bytes());
+        Self {
+            bytes,
+            encoding: PiEncoding::Scalar,
+        }
+    }
+
+    /// Word RLC encoded from its little-endian bytes
+    pub(super) fn rlc_le(bytes: [u8; N_BYTES_WORD]) -> Self {
+        Self {
+            bytes,
+            encoding: PiEncoding::RlcLe,
+        }
+    }
+
+    /// Word RLC encoded from its big-endian bytes
+    pub(super) fn rlc_be(bytes: [u8; N_BYTES_WORD]) -> Self {
+        Self {
+            bytes,
+            encoding: PiEncoding::RlcBe,
+        }
+    }
+
+    /// Value of the word in the `raw_public_inputs` column
+    pub(super) fn value<F: Field>(&self, randomness: F) -> F {
+        let horner = |base: F, bytes: &mut dyn Iterator<Item = &u8>| {
+            bytes.fold(F::ZERO, |acc, &byte| acc * base + F::from(byte as u64))
+        };
+        match self.encoding {
+            PiEncoding::Scalar => horner(F::from(BYTE_POW_BASE), &mut self.bytes.iter()),
+            PiEncoding::RlcLe => horner(randomness, &mut self.bytes.iter()),
+            PiEncoding::RlcBe => horner(randomness, &mut self.bytes.iter().rev()),
+        }
+    }
+}
+
+/// Keccak input of the raw public input `words`: their concatenated
+/// big-endian bytes.
+pub(super) fn raw_public_inputs_bytes(words: &[PiWord]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.bytes).collect()
+}
+
+/// Keccak digest of `input`.
+pub(super) fn keccak(input: &[u8]) -> Vec<u8> {
+    let mut keccak = Keccak::default();
+    keccak.update(input);
+    keccak.digest()
+}
+
+/// Big-endian values of the hi and lo halves of the keccak digest.
+pub(super) fn digest_hi_lo<F: Field>(digest: &[u8]) -> [F; 2] {
+    let half = |bytes: &[u8]| F::from_u128(u128::from_be_bytes(bytes.try_into().unwrap()));
+    [
+        half(&digest[..N_BYTES_HALF_WORD]),
+        half(&digest[N_BYTES_HALF_WORD..]),
+    ]
+}
+
+/// Columns hashing the raw public inputs with the keccak table
+#[derive(Clone, Debug)]
+pub(super) struct PiKeccakConfig {
+    /// Number of `raw_public_inputs` values
+    num_values: usize,
+
+    /// Enabled on the input and digest bytes
+    q_bytes: Selector,
+    /// Enabled on the first byte of a word, or of a digest half
+    q_word_first: Selector,
+    /// Enabled on the last byte of a word, or of a digest half
+    q_word_last: Selector,
+    /// Enabled on the first input byte and on the first digest byte
+    q_rlc_first: Selector,
+    /// Enabled on the digest bytes
+    q_digest: Selector,
+    /// Enabled on the last digest byte
+    q_keccak: Selector,
+
+    byte: Column<Advice>,
+    /// Big-endian accumulator of the bytes of the current word
+    word_acc: Column<Advice>,
+    /// RLC of the bytes of the current word, from the first to the last one
+    rlc_le: Column<Advice>,
+    /// RLC of the bytes of the current word, from the last to the first one
+    rlc_be: Column<Advice>,
+    /// RLC of the input bytes, and then of the digest bytes
+    bytes_rlc: Column<Advice>,
+    fixed_u8: Column<Fixed>,
+}
+
+impl PiKeccakConfig {
+    /// Configure the commitment to `num_values` raw public inputs.
+    pub(super) fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        num_values: usize,
+        keccak_table: &KeccakTable,
+        challenges: &Challenges<Expression<F>>,
+    ) -> Self {
+        let q_bytes = meta.complex_selector();
+        let q_word_first = meta.complex_selector();
+        let q_word_last = meta.complex_selector();
+        let q_rlc_first = meta.complex_selector();
+        let q_digest = meta.complex_selector();
+        let q_keccak = meta.complex_selector();
+        let byte = meta.advice_column();
+        let word_acc = meta.advice_column();
+        let rlc_le = meta.advice_column_in(SecondPhase);
+        let rlc_be = meta.advice_column_in(SecondPhase);
+        let bytes_rlc = meta.advice_column_in(SecondPhase);
+        let fixed_u8 = meta.fixed_column();
+
+        meta.enable_equality(word_acc);
+        meta.enable_equality(rlc_le);
+        meta.enable_equality(rlc_be);
+
+        meta.create_gate("pi bytes accumulators", |meta| {
+            let q_bytes = meta.query_selector(q_bytes);
+            let q_word_first = meta.query_selector(q_word_first);
+            let q_rlc_first = meta.query_selector(q_rlc_first);
+            let q_digest = meta.query_selector(q_digest);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let word_acc = meta.query_advice(word_acc, Rotation::cur());
+            let word_acc_prev = meta.query_advice(word_acc, Rotation::prev());
+            let bytes_rlc = meta.query_advice(bytes_rlc, Rotation::cur());
+            let bytes_rlc_prev = meta.query_advice(bytes_rlc, Rotation::prev());
+
+            // The input and digest bytes are accumulated like the input_rlc and
+            // output_rlc of the keccak table.
+            let r = select::expr(q_digest, challenges.evm_word(), challenges.keccak_input());
+
+            vec![
+                q_bytes.clone()
+                    * (word_acc
+                        - not::expr(q_word_first) * word_acc_prev * BYTE_POW_BASE.expr()
+                        - byte.clone()),
+                q_bytes * (bytes_rlc - not::expr(q_rlc_first) * bytes_rlc_prev * r - byte),
+            ]
+        });
+
+        meta.create_gate("pi word rlc", |meta| {
+            let q_input = meta.query_selector(q_bytes) * not::expr(meta.query_selector(q_digest));
+            let q_word_first = meta.query_selector(q_word_first);
+            let q_word_last = meta.query_selector(q_word_last);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let rlc_le = meta.query_advice(rlc_le, Rotation::cur());
+            let rlc_le_prev = meta.query_advice(rlc_le, Rotation::prev());
+            let rlc_be = meta.query_advice(rlc_be, Rotation::cur());
+            let rlc_be_next = meta.query_advice(rlc_be, Rotation::next());
+            // The words are encoded like the EVM words of the block and tx
+            // tables.
+            let r = challenges.evm_word();
+
+            vec![
+                q_input.clone()
+                    * (rlc_le - not::expr(q_word_first) * rlc_le_prev * r.clone() - byte.clone()),
+                q_input * (rlc_be - not::expr(q_word_last) * rlc_be_next * r - byte),
+            ]
+        });
+
+        meta.lookup_any("pi byte range", |meta| {
+            let q_bytes = meta.query_selector(q_bytes);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let fixed_u8 = meta.query_fixed(fixed_u8, Rotation::cur());
+
+            vec![(q_bytes * byte, fixed_u8)]
+        });
+
+        let input_len = num_values * N_BYTES_WORD;
+        meta.lookup_any("keccak256(raw_public_inputs)", |meta| {
+            let q_keccak = meta.query_selector(q_keccak);
+            // The last input byte is one word above the last digest byte.
+            let input_rlc = meta.query_advice(bytes_rlc, Rotation(-(N_BYTES_WORD as i32)));
+            let output_rlc = meta.query_advice(bytes_rlc, Rotation::cur());
+
+            [1.expr(), input_rlc, input_len.expr(), output_rlc]
+                .into_iter()
+                .zip(keccak_table.table_exprs(meta))
+                .map(|(arg, table)| (q_keccak.clone() * arg, table))
+                .collect()
+        });
+
+        Self {
+            num_values,
+            q_bytes,
+            q_word_first,
+            q_word_last,
+            q_rlc_first,
+            q_digest,
+            q_keccak,
+            byte,
+            word_acc,
+            rlc_le,
+            rlc_be,
+            bytes_rlc,
+            fixed_u8,
+        }
+    }
+
+    /// Assign the byte range table.
+    pub(super) fn load<F: Field>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "fixed u8 table",
+            |mut region| {
+                for i in 0..(1 << 8) {
+                    region.assign_fixed(
+                        || format!("row_{}", i),
+                        self.fixed_u8,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Assign the bytes of the raw public input `words` and of their digest,
+    /// copying the values rebuilt with the evm_word challenge to the
+    /// `raw_public_inputs` cells.  Returns the cells of the hi and lo halves
+    /// of the digest.
+    pub(super) fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        raw_public_inputs: Column<Advice>,
+        words: &[PiWord],
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+        assert_eq!(words.len(), self.num_values);
+        let input = raw_public_inputs_bytes(words);
+        let digest = keccak(&input);
+        let digest_offset = input.len();
+
+        let randomness = challenges.evm_word();
+        // The big-endian RLC of a word is accumulated from its last byte.
+        let rlc_be: Vec<Value<F>> = input
+            .chunks(N_BYTES_WORD)
+            .flat_map(|bytes| {
+                let mut acc = Value::known(F::ZERO);
+                let mut rlc_be: Vec<Value<F>> = bytes
+                    .iter()
+                    .rev()
+                    .map(|&byte| {
+                        acc = acc * randomness + Value::known(F::from(byte as u64));
+                        acc
+                    })
+                    .collect();
+                rlc_be.reverse();
+                rlc_be
+            })
+            .collect();
+
+        let mut word_acc = F::ZERO;
+        let mut rlc_le = Value::known(F::ZERO);
+        let mut bytes_rlc = Value::known(F::ZERO);
+        let mut hi_lo = vec![];
+        for (offset, &byte) in input.iter().chain(digest.iter()).enumerate() {
+            let is_digest = offset >= digest_offset;
+            let (index, word_len, r) = if is_digest {
+                (
+                    offset - digest_offset,
+                    N_BYTES_HALF_WORD,
+                    challenges.evm_word(),
+                )
+            } else {
+                (offset, N_BYTES_WORD, challenges.keccak_input())
+            };
+            let is_word_first = index % word_len == 0;
+            let is_word_last = (index + 1) % word_len == 0;
+            let is_rlc_first = index == 0;
+
+            let byte = F::from(byte as u64);
+            (word_acc, rlc_le) = if is_word_first {
+                (byte, Value::known(byte))
+            } else {
+                (
+                    word_acc * F::from(BYTE_POW_BASE) + byte,
+                    rlc_le * randomness + Value::known(byte),
+                )
+            };
+            bytes_rlc = if is_rlc_first {
+                Value::known(byte)
+            } else {
+                bytes_rlc * r + Value::known(byte)
+            };
+
+            self.q_bytes.enable(region, offset)?;
+            if is_word_first {
+                self.q_word_first.enable(region, offset)?;
+            }
+            if is_word_last {
+                self.q_word_last.enable(region, offset)?;
+            }
+            if is_rlc_first {
+                self.q_rlc_first.enable(region, offset)?;
+            }
+            if is_digest {
+                self.q_digest.enable(region, offset)?;
+            }
+            if offset == digest_offset + N_BYTES_WORD - 1 {
+                self.q_keccak.enable(region, offset)?;
+            }
+
+            region.assign_advice(|| "pi byte", self.byte, offset, || Value::known(byte))?;
+            let word_acc_cell = region.assign_advice(
+                || "pi word_acc",
+                self.word_acc,
+                offset,
+                || Value::known(word_acc),
+            )?;
+            let rlc_le_cell =
+                region.assign_advice(|| "pi rlc_le", self.rlc_le, offset, || rlc_le)?;
+            let rlc_be_cell = region.assign_advice(
+                || "pi rlc_be",
+                self.rlc_be,
+                offset,
+                || rlc_be.get(offset).copied().unwrap_or(Value::known(F::ZERO)),
+            )?;
+            region.assign_advice(|| "pi bytes_rlc", self.bytes_rlc, offset, || bytes_rlc)?;
+
+            if is_digest {
+                if is_word_last {
+                    hi_lo.push(word_acc_cell);
+                }
+                continue;
+            }
+            let word_index = offset / N_BYTES_WORD;
+            let value_cell = match words[word_index].encoding {
+                PiEncoding::Scalar if is_word_last => Some(word_acc_cell),
+                PiEncoding::RlcLe if is_word_last => Some(rlc_le_cell),
+                PiEncoding::RlcBe if is_word_first => Some(rlc_be_cell),
+                _ => None,
+            };
+            if let Some(value_cell) = value_cell {
+                value_cell.copy_advice(
+                    || "raw_public_inputs",
+                    region,
+                    raw_public_inputs,
+                    word_index,
+                )?;
+            }
+        }
+
+        Ok(hi_lo.try_into().unwrap())
+    }
+}
@@ -1,5 +1,14 @@
 /// Fixed by the spec
-pub(super) const BLOCK_LEN: usize = 7 + 256;
-pub(super) const EXTRA_LEN: usize = 2;
+/// Block fields: coinbase, gas_limit, number, timestamp, mix_hash, base_fee,
+/// chain_id, the rwc, tx id and cumulative gas offsets and ends of the chunk,
+/// and the 256 previous block hashes
+pub(super) const BLOCK_LEN: usize = 13 + 256;
+/// Extra fields: state_root, prev_state_root and receipts_root
+pub(super) const EXTRA_LEN: usize = 3;
+/// Fields committed for each block of a batch following the first one:
+/// coinbase, gas_limit, number, timestamp, mix_hash, base_fee, parent_hash
+/// and state_root
+pub(super) const BATCH_BLOCK_LEN: usize = 8;
 pub(super) const ZERO_BYTE_GAS_COST: u64 = 4;
 pub(super) const NONZERO_BYTE_GAS_COST: u64 = 16;
+pub(super) const BYTE_POW_BASE: u64 = 1 << 8;
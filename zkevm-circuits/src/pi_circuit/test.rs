@@ -2,6 +2,7 @@
 use super::{dev::*, *};
 use crate::util::unusable_rows;
 use halo2_proofs::{
+    arithmetic::Field as Halo2Field,
     dev::{MockProver, VerifyFailure},
     halo2curves::bn256::Fr,
 };
@@ -16,8 +17,19 @@ fn pi_circuit_unusable_rows() {
         unusable_rows::<Fr, PiCircuit::<Fr>>(PiCircuitParams {
             max_txs: 2,
             max_calldata: 8,
+            num_blocks: 1,
+            commitment: PiCommitment::Rlc,
         }),
-    )
+    );
+    assert_eq!(
+        PiCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, PiCircuit::<Fr>>(PiCircuitParams {
+            max_txs: 2,
+            max_calldata: 8,
+            num_blocks: 1,
+            commitment: PiCommitment::Keccak,
+        }),
+    );
 }
 
 fn run<F: Field>(
@@ -25,6 +37,7 @@ fn run<F: Field>(
     max_txs: usize,
     max_calldata: usize,
     public_data: PublicData,
+    commitment: PiCommitment,
 ) -> Result<(), Vec<VerifyFailure>> {
     let mut rng = ChaCha20Rng::seed_from_u64(2);
     let randomness = F::random(&mut rng);
@@ -32,7 +45,8 @@ fn run<F: Field>(
     let mut public_data = public_data;
     public_data.chain_id = *MOCK_CHAIN_ID;
 
-    let circuit = PiCircuit::<F>::new(max_txs, max_calldata, randomness, rand_rpi, public_data);
+    let circuit = PiCircuit::<F>::new(max_txs, max_calldata, randomness, rand_rpi, public_data)
+        .with_commitment(commitment);
     let public_inputs = circuit.instance();
 
     let prover = match MockProver::run(k, &circuit, public_inputs) {
@@ -49,7 +63,10 @@ fn test_default_pi() {
     let public_data = PublicData::default();
 
     let k = 17;
-    assert_eq!(run::<Fr>(k, max_txs, max_calldata, public_data), Ok(()));
+    assert_eq!(
+        run::<Fr>(k, max_txs, max_calldata, public_data, PiCommitment::Rlc),
+        Ok(())
+    );
 }
 
 #[test]
@@ -67,7 +84,253 @@ fn test_simple_pi() {
     }
 
     let k = 17;
-    assert_eq!(run::<Fr>(k, max_txs, max_calldata, public_data), Ok(()));
+    assert_eq!(
+        run::<Fr>(k, max_txs, max_calldata, public_data, PiCommitment::Rlc),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_batch_pi() {
+    let max_txs = 8;
+    let max_calldata = 200;
+
+    let mut public_data = PublicData::default();
+    public_data.block_constants.number = 0xcafe.into();
+    public_data
+        .transactions
+        .push(CORRECT_MOCK_TXS[0].clone().into());
+    for (i, tx) in CORRECT_MOCK_TXS[1..3].iter().enumerate() {
+        let mut block = BatchBlock {
+            parent_hash: H256::from_low_u64_be(0x100 + i as u64),
+            state_root: H256::from_low_u64_be(0x200 + i as u64),
+            ..Default::default()
+        };
+        block.block_constants.number = (0xcaff + i as u64).into();
+        block.transactions.push(tx.clone().into());
+        public_data.batch_blocks.push(block);
+    }
+
+    let k = 17;
+    assert_eq!(
+        run::<Fr>(k, max_txs, max_calldata, public_data, PiCommitment::Rlc),
+        Ok(())
+    );
+}
+
+#[test]
+fn batch_pi_wrong_block() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let randomness = Fr::random(&mut rng);
+    let rand_rpi = Fr::random(&mut rng);
+    let mut public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        ..Default::default()
+    };
+    public_data.batch_blocks.push(BatchBlock {
+        state_root: H256::from_low_u64_be(0x200),
+        ..Default::default()
+    });
+
+    let circuit = PiCircuit::<Fr>::new(
+        max_txs,
+        max_calldata,
+        randomness,
+        rand_rpi,
+        public_data.clone(),
+    );
+    // The instance of a batch whose second block has another state root
+    public_data.batch_blocks[0].state_root = H256::from_low_u64_be(0x201);
+    let public_inputs =
+        PiCircuit::<Fr>::new(max_txs, max_calldata, randomness, rand_rpi, public_data).instance();
+
+    let prover = MockProver::run(17, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn chunk_pi_wrong_offset() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let randomness = Fr::random(&mut rng);
+    let rand_rpi = Fr::random(&mut rng);
+    let mut public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        rwc_offset: 100,
+        tx_id_offset: 2,
+        cumulative_gas_used_offset: 42000,
+        rwc_end: 180,
+        tx_id_end: 3,
+        cumulative_gas_used_end: 63000,
+        ..Default::default()
+    };
+    public_data
+        .transactions
+        .push(CORRECT_MOCK_TXS[0].clone().into());
+
+    let circuit = PiCircuit::<Fr>::new(
+        max_txs,
+        max_calldata,
+        randomness,
+        rand_rpi,
+        public_data.clone(),
+    );
+    let public_inputs = circuit.instance();
+    let prover = MockProver::run(17, &circuit, public_inputs).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // The instance of a chunk starting after another number of txs, or ending
+    // at another rw counter
+    for public_data in [
+        PublicData {
+            tx_id_offset: 1,
+            ..public_data.clone()
+        },
+        PublicData {
+            rwc_end: 181,
+            ..public_data
+        },
+    ] {
+        let public_inputs =
+            PiCircuit::<Fr>::new(max_txs, max_calldata, randomness, rand_rpi, public_data)
+                .instance();
+        let prover = MockProver::run(17, &circuit, public_inputs).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[test]
+fn pi_wrong_receipts_root() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let randomness = Fr::random(&mut rng);
+    let rand_rpi = Fr::random(&mut rng);
+    let mut public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        receipts_root: H256::from_low_u64_be(0x300),
+        ..Default::default()
+    };
+
+    let circuit = PiCircuit::<Fr>::new(
+        max_txs,
+        max_calldata,
+        randomness,
+        rand_rpi,
+        public_data.clone(),
+    );
+    // The instance of a block whose header has another receipts root
+    public_data.receipts_root = H256::from_low_u64_be(0x301);
+    let public_inputs =
+        PiCircuit::<Fr>::new(max_txs, max_calldata, randomness, rand_rpi, public_data).instance();
+
+    let prover = MockProver::run(17, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_default_pi_keccak() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let public_data = PublicData::default();
+
+    let k = 17;
+    assert_eq!(
+        run::<Fr>(k, max_txs, max_calldata, public_data, PiCommitment::Keccak),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_simple_pi_keccak() {
+    let max_txs = 8;
+    let max_calldata = 200;
+
+    let mut public_data = PublicData::default();
+
+    let n_tx = 4;
+    for i in 0..n_tx {
+        public_data
+            .transactions
+            .push(CORRECT_MOCK_TXS[i].clone().into());
+    }
+
+    let k = 17;
+    assert_eq!(
+        run::<Fr>(k, max_txs, max_calldata, public_data, PiCommitment::Keccak),
+        Ok(())
+    );
+}
+
+#[test]
+fn keccak_pi_wrong_digest() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let randomness = Fr::random(&mut rng);
+    let rand_rpi = Fr::random(&mut rng);
+    let public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        ..Default::default()
+    };
+
+    let circuit = PiCircuit::<Fr>::new(max_txs, max_calldata, randomness, rand_rpi, public_data)
+        .with_commitment(PiCommitment::Keccak);
+    let mut public_inputs = circuit.instance();
+    assert_eq!(
+        public_inputs,
+        vec![keccak_pi_hi_lo(max_txs, max_calldata, &circuit.public_data).to_vec()]
+    );
+    public_inputs[0][1] += Fr::ONE;
+
+    let prover = MockProver::run(17, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn keccak_pi_independent_of_randomness() {
+    let max_txs = 2;
+    let max_calldata = 8;
+    let mut public_data = PublicData {
+        chain_id: *MOCK_CHAIN_ID,
+        ..Default::default()
+    };
+    public_data
+        .transactions
+        .push(CORRECT_MOCK_TXS[0].clone().into());
+
+    // The keccak input holds the big-endian bytes of the fields, not their
+    // RLC encoding.
+    let input = keccak_pi_input(max_txs, max_calldata, &public_data);
+    let gas_price_offset = (BLOCK_LEN + 1 + EXTRA_LEN + 2 * (TX_LEN * max_txs + 1) + 3) * 32;
+    assert_eq!(
+        input[gas_price_offset..gas_price_offset + 32],
+        public_data.transactions[0].gas_price.unwrap().to_be_bytes()
+    );
+
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    let rand_rpi = Fr::random(&mut rng);
+    let instances: Vec<_> = (0..2)
+        .map(|_| {
+            let randomness = Fr::random(&mut rng);
+            let circuit = PiCircuit::<Fr>::new(
+                max_txs,
+                max_calldata,
+                randomness,
+                rand_rpi,
+                public_data.clone(),
+            )
+            .with_commitment(PiCommitment::Keccak);
+            let instance = circuit.instance();
+            let prover = MockProver::run(17, &circuit, instance.clone()).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            instance
+        })
+        .collect();
+    assert_eq!(instances[0], instances[1]);
 }
 
 fn run_size_check<F: Field>(max_txs: usize, max_calldata: usize, public_data: [PublicData; 2]) {
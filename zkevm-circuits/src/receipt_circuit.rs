@@ -0,0 +1,1251 @@
+//! The Receipt circuit rebuilds the logs of each transaction receipt from the
+//! TxReceipt and TxLog rows of the RwTable and proves the 2048-bit logs bloom
+//! of the block, which is exposed as public input together with the number of
+//! transactions and the status and cumulative gas used of each transaction.
+//!
+//! The circuit is scoped to the logs bloom and the receipt status: receipts
+//! are not RLP-encoded and the receipts trie is not hashed, so no circuit
+//! proves the receipts root of the block header. The receipts root committed
+//! by the PI circuit is taken from the header as is.
+
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+mod dev;
+#[cfg(any(feature = "test", test))]
+mod test;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::ReceiptCircuit as TestReceiptCircuit;
+
+use bus_mapping::operation::Target;
+use eth_types::{Field, ToBigEndian};
+use ethers_core::utils::keccak256;
+use gadgets::{
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+    util::{not, Expr},
+};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, SecondPhase},
+    poly::Rotation,
+};
+use log::error;
+use std::{collections::HashMap, marker::PhantomData};
+
+use crate::{
+    evm_circuit::util::constraint_builder::{BaseConstraintBuilder, ConstrainBuilderCommon},
+    table::{KeccakTable, LookupTable, RwTable, TxLogFieldTag, TxReceiptFieldTag},
+    util::{build_tx_log_expression, Challenges, SubCircuit, SubCircuitConfig},
+    witness::{self, logs_bloom, receipts_convert, Receipt, Rw, RwMap, BLOOM_BYTES},
+};
+
+/// Number of rows of an item: one row per byte of the keccak hash of the
+/// address or topic.
+pub const ITEM_ROWS: usize = 32;
+/// Number of bits of the logs bloom
+pub const BLOOM_BITS: usize = BLOOM_BYTES * 8;
+
+/// Offsets within an item of the rows whose hash byte completes one of the 3
+/// bloom bit indexes.
+const BIT_ROWS: [usize; 3] = [1, 3, 5];
+
+/// Config arguments for the Receipt circuit
+#[derive(Clone, Debug)]
+pub struct ReceiptCircuitConfigArgs<F: Field> {
+    /// RwTable
+    pub rw_table: RwTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+}
+
+/// Layout for the Receipt circuit.
+///
+/// The item region holds one item of `ITEM_ROWS` rows per transaction (a
+/// header holding its number of logs), followed by one item per log address
+/// and topic of the transaction. The bloom region holds one row per bloom bit,
+/// in increasing bit index.
+#[derive(Clone, Debug)]
+pub struct ReceiptCircuitConfig<F: Field> {
+    /// Enabled on all rows of the item region
+    pub q_enable: Column<Fixed>,
+    /// Enabled on the first row of the item region
+    pub q_head: Column<Fixed>,
+    /// Enabled on the first row of each item
+    pub q_first: Column<Fixed>,
+    /// Enabled on the last row of each item
+    pub q_last: Column<Fixed>,
+    /// Enabled on the rows of each item whose hash byte completes a bloom bit
+    /// index
+    pub q_bit: Column<Fixed>,
+    /// Enabled on the first 20 rows of each item, which hold the bytes of an
+    /// address
+    pub q_address_byte: Column<Fixed>,
+    /// Whether the item is a transaction header
+    pub is_header: Column<Advice>,
+    /// Whether the item is the address of a log
+    pub is_address: Column<Advice>,
+    /// Whether the item is a topic of a log
+    pub is_topic: Column<Advice>,
+    /// Transaction identifier
+    pub tx_id: Column<Advice>,
+    /// Log identifier, 0 in the transaction header
+    pub log_id: Column<Advice>,
+    /// Number of logs of the transaction
+    pub log_length: Column<Advice>,
+    /// Topic index
+    pub index: Column<Advice>,
+    /// RwTable row of the item (TxLog) or transaction header (TxReceipt)
+    pub rw_counter: Column<Advice>,
+    /// RwTable row of the topic length of the log, on its last item
+    pub topic_length_rw_counter: Column<Advice>,
+    /// Status of the transaction, 0 outside of a transaction header
+    pub status: Column<Advice>,
+    /// Cumulative gas used up to the transaction, 0 outside of a transaction
+    /// header
+    pub cumulative_gas_used: Column<Advice>,
+    /// RwTable row of the status of the transaction header
+    pub status_rw_counter: Column<Advice>,
+    /// RwTable row of the cumulative gas used of the transaction header
+    pub cumulative_gas_used_rw_counter: Column<Advice>,
+    /// Number of transactions, constant over the region
+    pub num_txs: Column<Advice>,
+    /// Byte of the keccak hash of the item
+    pub hash_byte: Column<Advice>,
+    /// The 3 least significant bits of `hash_byte`
+    pub hash_lo3: Column<Advice>,
+    /// Byte of the item (address or topic), 0 past its length
+    pub input_byte: Column<Advice>,
+    /// `bloom bit index + 1` on the rows completing a bloom bit index, 0
+    /// otherwise
+    pub contribution: Column<Advice>,
+    /// Accumulated RLC of the hash bytes
+    pub hash_acc: Column<Advice>,
+    /// Accumulated RLC of the input bytes, as in the keccak table
+    pub input_acc: Column<Advice>,
+    /// Accumulated value of the item, as in the RwTable
+    pub value_acc: Column<Advice>,
+    /// Whether `log_id == log_length`, i.e. this is the last log of the
+    /// transaction
+    pub is_last_log: IsZeroConfig<F>,
+    /// Enabled on the rows of the bloom region
+    pub q_bloom: Column<Fixed>,
+    /// `bloom bit index + 1`
+    pub bloom_tag: Column<Fixed>,
+    /// Weight of the bit in its bloom byte
+    pub bit_weight: Column<Fixed>,
+    /// Enabled on the least significant bit of each bloom byte
+    pub q_byte_first: Column<Fixed>,
+    /// Value of a byte, for the byte decomposition table
+    pub byte_value: Column<Fixed>,
+    /// The 3 least significant bits of `byte_value`
+    pub byte_lo3: Column<Fixed>,
+    /// Bloom bit
+    pub bloom_bit: Column<Advice>,
+    /// Accumulated value of the bloom byte
+    pub bloom_acc: Column<Advice>,
+    /// Logs bloom bytes, the number of transactions, and the status and
+    /// cumulative gas used of each transaction
+    pub instance: Column<Instance>,
+    /// RwTable
+    pub rw_table: RwTable,
+    /// KeccakTable
+    pub keccak_table: KeccakTable,
+}
+
+impl<F: Field> SubCircuitConfig<F> for ReceiptCircuitConfig<F> {
+    type ConfigArgs = ReceiptCircuitConfigArgs<F>;
+
+    /// Return a new ReceiptCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            rw_table,
+            keccak_table,
+            challenges,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let [q_enable, q_head, q_first, q_last, q_bit, q_address_byte] =
+            [(); 6].map(|_| meta.fixed_column());
+        let [is_header, is_address, is_topic] = [(); 3].map(|_| meta.advice_column());
+        let [tx_id, log_id, log_length, index] = [(); 4].map(|_| meta.advice_column());
+        let [rw_counter, topic_length_rw_counter, num_txs] = [(); 3].map(|_| meta.advice_column());
+        let [status, cumulative_gas_used, status_rw_counter, cumulative_gas_used_rw_counter] =
+            [(); 4].map(|_| meta.advice_column());
+        let [hash_byte, hash_lo3, input_byte, contribution] = [(); 4].map(|_| meta.advice_column());
+        let [hash_acc, input_acc, value_acc] = [(); 3].map(|_| meta.advice_column_in(SecondPhase));
+        let [q_bloom, bloom_tag, bit_weight, q_byte_first, byte_value, byte_lo3] =
+            [(); 6].map(|_| meta.fixed_column());
+        let [bloom_bit, bloom_acc] = [(); 2].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        meta.enable_equality(num_txs);
+        meta.enable_equality(status);
+        meta.enable_equality(cumulative_gas_used);
+        meta.enable_equality(bloom_acc);
+        meta.enable_equality(instance);
+
+        let is_last_log_inv = meta.advice_column();
+        let is_last_log = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_fixed(q_enable, Rotation::cur()),
+            |meta| {
+                meta.query_advice(log_length, Rotation::cur())
+                    - meta.query_advice(log_id, Rotation::cur())
+            },
+            is_last_log_inv,
+        );
+
+        let block_columns = [
+            is_header,
+            is_address,
+            is_topic,
+            tx_id,
+            log_id,
+            log_length,
+            index,
+            rw_counter,
+            topic_length_rw_counter,
+            status,
+            cumulative_gas_used,
+            status_rw_counter,
+            cumulative_gas_used_rw_counter,
+        ];
+
+        meta.create_gate("receipt item rows", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_first = meta.query_fixed(q_first, Rotation::cur());
+            let q_bit = meta.query_fixed(q_bit, Rotation::cur());
+            let q_address_byte = meta.query_fixed(q_address_byte, Rotation::cur());
+            let is_header = meta.query_advice(is_header, Rotation::cur());
+            let is_address = meta.query_advice(is_address, Rotation::cur());
+            let is_topic = meta.query_advice(is_topic, Rotation::cur());
+            let is_item = is_address.clone() + is_topic.clone();
+            let hash_byte = meta.query_advice(hash_byte, Rotation::cur());
+            let input_byte = meta.query_advice(input_byte, Rotation::cur());
+            let acc_columns = [hash_acc, input_acc, value_acc];
+            let [hash_acc_prev, input_acc_prev, value_acc_prev] =
+                acc_columns.map(|column| meta.query_advice(column, Rotation::prev()));
+            let [hash_acc, input_acc, value_acc] =
+                acc_columns.map(|column| meta.query_advice(column, Rotation::cur()));
+
+            cb.require_boolean("is_header is boolean", is_header.clone());
+            cb.require_boolean("is_address is boolean", is_address.clone());
+            cb.require_boolean("is_topic is boolean", is_topic.clone());
+            cb.require_boolean("at most one item kind", is_header.clone() + is_item.clone());
+
+            cb.condition(not::expr(q_first.clone()), |cb| {
+                for column in block_columns {
+                    cb.require_equal(
+                        "item fields are constant over the item",
+                        meta.query_advice(column, Rotation::cur()),
+                        meta.query_advice(column, Rotation::prev()),
+                    );
+                }
+            });
+            cb.condition(not::expr(meta.query_fixed(q_head, Rotation::cur())), |cb| {
+                cb.require_equal(
+                    "num_txs is constant over the region",
+                    meta.query_advice(num_txs, Rotation::cur()),
+                    meta.query_advice(num_txs, Rotation::prev()),
+                );
+            });
+
+            cb.require_zero(
+                "transaction header has log_id 0",
+                is_header.clone() * meta.query_advice(log_id, Rotation::cur()),
+            );
+            let status = meta.query_advice(status, Rotation::cur());
+            cb.require_boolean("status is boolean", status.clone());
+            cb.require_zero(
+                "status is 0 outside of a transaction header",
+                not::expr(is_header.clone()) * status,
+            );
+            cb.require_zero(
+                "cumulative_gas_used is 0 outside of a transaction header",
+                not::expr(is_header) * meta.query_advice(cumulative_gas_used, Rotation::cur()),
+            );
+            cb.require_zero(
+                "address has index 0",
+                is_address.clone() * meta.query_advice(index, Rotation::cur()),
+            );
+            cb.require_zero(
+                "hash bytes are 0 outside of an address or topic",
+                not::expr(is_item.clone()) * hash_byte.clone(),
+            );
+
+            let is_input_row = is_topic.clone() + is_address.clone() * q_address_byte;
+            cb.require_zero(
+                "input bytes are 0 past the input length",
+                not::expr(is_input_row.clone()) * input_byte.clone(),
+            );
+
+            cb.condition(q_first.clone(), |cb| {
+                cb.require_equal("hash_acc starts", hash_acc.clone(), hash_byte.clone());
+                cb.require_equal("input_acc starts", input_acc.clone(), input_byte.clone());
+                cb.require_equal("value_acc starts", value_acc.clone(), input_byte.clone());
+            });
+            cb.condition(not::expr(q_first), |cb| {
+                cb.require_equal(
+                    "hash_acc accumulates hash bytes",
+                    hash_acc,
+                    hash_acc_prev * challenges.evm_word() + hash_byte.clone(),
+                );
+                cb.require_equal(
+                    "input_acc accumulates input bytes",
+                    input_acc,
+                    input_acc_prev.clone()
+                        + is_input_row.clone()
+                            * (input_acc_prev * (challenges.keccak_input() - 1.expr())
+                                + input_byte.clone()),
+                );
+                // An address is a scalar in the RwTable, a topic the RLC of its
+                // bytes.
+                let value_base = is_address * 256.expr() + is_topic * challenges.evm_word();
+                cb.require_equal(
+                    "value_acc accumulates input bytes",
+                    value_acc,
+                    value_acc_prev.clone()
+                        + is_input_row * (value_acc_prev * (value_base - 1.expr()) + input_byte),
+                );
+            });
+
+            let contribution = meta.query_advice(contribution, Rotation::cur());
+            cb.condition(q_bit.clone(), |cb| {
+                cb.require_equal(
+                    "contribution is bloom bit index + 1",
+                    contribution.clone(),
+                    is_item
+                        * (meta.query_advice(hash_lo3, Rotation::prev()) * 256.expr()
+                            + hash_byte
+                            + 1.expr()),
+                );
+            });
+            cb.condition(not::expr(q_bit), |cb| {
+                cb.require_zero("no contribution", contribution);
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
+        meta.create_gate("receipt item transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_header_next = meta.query_advice(is_header, Rotation::next());
+            let is_address_next = meta.query_advice(is_address, Rotation::next());
+            let is_topic_next = meta.query_advice(is_topic, Rotation::next());
+            let tx_id_next = meta.query_advice(tx_id, Rotation::next());
+            let log_id_next = meta.query_advice(log_id, Rotation::next());
+            let log_length_next = meta.query_advice(log_length, Rotation::next());
+            let index_next = meta.query_advice(index, Rotation::next());
+            let [is_header, is_address, is_topic, tx_id, log_id, log_length, index] = [
+                is_header, is_address, is_topic, tx_id, log_id, log_length, index,
+            ]
+            .map(|column| meta.query_advice(column, Rotation::cur()));
+            let is_item = is_address.clone() + is_topic.clone();
+            let is_enabled = is_header.clone() + is_item.clone();
+            let is_item_next = is_address_next.clone() + is_topic_next.clone();
+            let is_enabled_next = is_header_next.clone() + is_item_next.clone();
+            let is_last_log = is_last_log.expr();
+            let is_last_item = is_item.clone() * not::expr(is_topic_next.clone());
+            let is_last_in_tx = (is_header.clone() + is_last_item.clone()) * is_last_log.clone();
+
+            cb.require_zero(
+                "enabled items are contiguous",
+                not::expr(is_enabled) * is_enabled_next.clone(),
+            );
+
+            // The next log of the transaction starts with its address.
+            cb.condition((is_header + is_last_item) * not::expr(is_last_log), |cb| {
+                cb.require_equal("next log starts", is_address_next.clone(), 1.expr());
+                cb.require_equal("same tx", tx_id_next.clone(), tx_id.clone());
+                cb.require_equal(
+                    "log_id increases",
+                    log_id_next.clone(),
+                    log_id.clone() + 1.expr(),
+                );
+                cb.require_equal(
+                    "same log_length",
+                    log_length_next.clone(),
+                    log_length.clone(),
+                );
+            });
+            // Topics follow the address of their log in increasing index.
+            cb.condition(is_topic_next, |cb| {
+                cb.require_equal("topic follows an item", is_item, 1.expr());
+                cb.require_equal("same tx", tx_id_next.clone(), tx_id.clone());
+                cb.require_equal("same log", log_id_next, log_id);
+                cb.require_equal("same log_length", log_length_next, log_length);
+                cb.require_equal(
+                    "topic index increases",
+                    index_next,
+                    is_topic * (index + 1.expr()),
+                );
+            });
+            // After the last log of a transaction comes the next transaction, or
+            // the end of the receipts.
+            cb.condition(is_last_in_tx, |cb| {
+                cb.require_zero("next item is not in the tx", is_item_next);
+                cb.require_zero(
+                    "next tx_id",
+                    is_header_next * (tx_id_next - tx_id.clone() - 1.expr()),
+                );
+                cb.require_zero(
+                    "last tx_id is num_txs",
+                    not::expr(is_enabled_next)
+                        * (meta.query_advice(num_txs, Rotation::cur()) - tx_id),
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_last, Rotation::cur()))
+        });
+
+        meta.create_gate("receipt first item", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_header = meta.query_advice(is_header, Rotation::cur());
+            let is_enabled = is_header.clone()
+                + meta.query_advice(is_address, Rotation::cur())
+                + meta.query_advice(is_topic, Rotation::cur());
+            cb.require_zero(
+                "no tx when the region is empty",
+                not::expr(is_enabled.clone()) * meta.query_advice(num_txs, Rotation::cur()),
+            );
+            cb.require_equal("first item is a tx header", is_enabled.clone(), is_header);
+            cb.require_zero(
+                "first tx_id is 1",
+                is_enabled * (meta.query_advice(tx_id, Rotation::cur()) - 1.expr()),
+            );
+
+            cb.gate(meta.query_fixed(q_head, Rotation::cur()))
+        });
+
+        meta.create_gate("receipt bloom bytes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let q_byte_first = meta.query_fixed(q_byte_first, Rotation::cur());
+            let bloom_bit = meta.query_advice(bloom_bit, Rotation::cur());
+            let weighted_bit = bloom_bit.clone() * meta.query_fixed(bit_weight, Rotation::cur());
+            let bloom_acc = meta.query_advice(bloom_acc, Rotation::cur());
+
+            cb.require_boolean("bloom bit is boolean", bloom_bit);
+            cb.condition(q_byte_first.clone(), |cb| {
+                cb.require_equal("bloom byte starts", bloom_acc.clone(), weighted_bit.clone());
+            });
+            cb.condition(not::expr(q_byte_first), |cb| {
+                cb.require_equal(
+                    "bloom byte accumulates bits",
+                    bloom_acc,
+                    meta.query_advice(bloom_acc, Rotation::prev()) + weighted_bit,
+                );
+            });
+
+            cb.gate(meta.query_fixed(q_bloom, Rotation::cur()))
+        });
+
+        meta.lookup_any("receipt hash byte decomposition", |meta| {
+            let q_enable = meta.query_fixed(q_enable, Rotation::cur());
+            vec![
+                (
+                    q_enable.clone() * meta.query_advice(hash_byte, Rotation::cur()),
+                    meta.query_fixed(byte_value, Rotation::cur()),
+                ),
+                (
+                    q_enable * meta.query_advice(hash_lo3, Rotation::cur()),
+                    meta.query_fixed(byte_lo3, Rotation::cur()),
+                ),
+            ]
+        });
+
+        meta.lookup_any("receipt input byte range", |meta| {
+            vec![(
+                meta.query_fixed(q_enable, Rotation::cur())
+                    * meta.query_advice(input_byte, Rotation::cur()),
+                meta.query_fixed(byte_value, Rotation::cur()),
+            )]
+        });
+
+        meta.lookup_any("receipt item keccak", |meta| {
+            let is_address = meta.query_advice(is_address, Rotation::cur());
+            let is_topic = meta.query_advice(is_topic, Rotation::cur());
+            let cond =
+                meta.query_fixed(q_last, Rotation::cur()) * (is_address.clone() + is_topic.clone());
+            vec![
+                1.expr(),
+                meta.query_advice(input_acc, Rotation::cur()),
+                is_address * 20.expr() + is_topic * 32.expr(),
+                meta.query_advice(hash_acc, Rotation::cur()),
+            ]
+            .into_iter()
+            .zip(keccak_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("receipt item TxLog", |meta| {
+            let is_address = meta.query_advice(is_address, Rotation::cur());
+            let is_topic = meta.query_advice(is_topic, Rotation::cur());
+            let cond =
+                meta.query_fixed(q_last, Rotation::cur()) * (is_address.clone() + is_topic.clone());
+            let field_tag =
+                is_address * TxLogFieldTag::Address.expr() + is_topic * TxLogFieldTag::Topic.expr();
+            vec![
+                meta.query_advice(rw_counter, Rotation::cur()),
+                1.expr(),
+                Target::TxLog.expr(),
+                meta.query_advice(tx_id, Rotation::cur()),
+                build_tx_log_expression(
+                    meta.query_advice(index, Rotation::cur()),
+                    field_tag,
+                    meta.query_advice(log_id, Rotation::cur()),
+                ),
+                0.expr(),
+                0.expr(),
+                meta.query_advice(value_acc, Rotation::cur()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            .zip(rw_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        meta.lookup_any("receipt log TopicLength", |meta| {
+            let is_topic_next = meta.query_advice(is_topic, Rotation::next());
+            let is_topic = meta.query_advice(is_topic, Rotation::cur());
+            let cond = meta.query_fixed(q_last, Rotation::cur())
+                * (meta.query_advice(is_address, Rotation::cur()) + is_topic.clone())
+                * not::expr(is_topic_next);
+            vec![
+                meta.query_advice(topic_length_rw_counter, Rotation::cur()),
+                1.expr(),
+                Target::TxLog.expr(),
+                meta.query_advice(tx_id, Rotation::cur()),
+                build_tx_log_expression(
+                    0.expr(),
+                    TxLogFieldTag::TopicLength.expr(),
+                    meta.query_advice(log_id, Rotation::cur()),
+                ),
+                0.expr(),
+                0.expr(),
+                is_topic * (meta.query_advice(index, Rotation::cur()) + 1.expr()),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+            ]
+            .into_iter()
+            .zip(rw_table.table_exprs(meta).into_iter())
+            .map(|(arg, table)| (cond.clone() * arg, table))
+            .collect()
+        });
+
+        for (name, field_tag, rw_counter, value) in [
+            (
+                "receipt tx PostStateOrStatus",
+                TxReceiptFieldTag::PostStateOrStatus,
+                status_rw_counter,
+                status,
+            ),
+            (
+                "receipt tx CumulativeGasUsed",
+                TxReceiptFieldTag::CumulativeGasUsed,
+                cumulative_gas_used_rw_counter,
+                cumulative_gas_used,
+            ),
+            (
+                "receipt tx LogLength",
+                TxReceiptFieldTag::LogLength,
+                rw_counter,
+                log_length,
+            ),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let cond = meta.query_fixed(q_last, Rotation::cur())
+                    * meta.query_advice(is_header, Rotation::cur());
+                vec![
+                    meta.query_advice(rw_counter, Rotation::cur()),
+                    1.expr(),
+                    Target::TxReceipt.expr(),
+                    meta.query_advice(tx_id, Rotation::cur()),
+                    0.expr(),
+                    field_tag.expr(),
+                    0.expr(),
+                    meta.query_advice(value, Rotation::cur()),
+                    0.expr(),
+                    0.expr(),
+                    0.expr(),
+                ]
+                .into_iter()
+                .zip(rw_table.table_exprs(meta).into_iter())
+                .map(|(arg, table)| (cond.clone() * arg, table))
+                .collect()
+            });
+        }
+
+        // Every bit set by an item is set in the bloom, and every bit set in
+        // the bloom is set by an item.
+        meta.lookup_any("receipt bloom bit set by item", |meta| {
+            vec![(
+                meta.query_fixed(q_enable, Rotation::cur())
+                    * meta.query_advice(contribution, Rotation::cur()),
+                meta.query_fixed(q_bloom, Rotation::cur())
+                    * meta.query_advice(bloom_bit, Rotation::cur())
+                    * meta.query_fixed(bloom_tag, Rotation::cur()),
+            )]
+        });
+        meta.lookup_any("receipt bloom bit from item", |meta| {
+            vec![(
+                meta.query_fixed(q_bloom, Rotation::cur())
+                    * meta.query_advice(bloom_bit, Rotation::cur())
+                    * meta.query_fixed(bloom_tag, Rotation::cur()),
+                meta.query_fixed(q_enable, Rotation::cur())
+                    * meta.query_advice(contribution, Rotation::cur()),
+            )]
+        });
+
+        Self {
+            q_enable,
+            q_head,
+            q_first,
+            q_last,
+            q_bit,
+            q_address_byte,
+            is_header,
+            is_address,
+            is_topic,
+            tx_id,
+            log_id,
+            log_length,
+            index,
+            rw_counter,
+            topic_length_rw_counter,
+            status,
+            cumulative_gas_used,
+            status_rw_counter,
+            cumulative_gas_used_rw_counter,
+            num_txs,
+            hash_byte,
+            hash_lo3,
+            input_byte,
+            contribution,
+            hash_acc,
+            input_acc,
+            value_acc,
+            is_last_log,
+            q_bloom,
+            bloom_tag,
+            bit_weight,
+            q_byte_first,
+            byte_value,
+            byte_lo3,
+            bloom_bit,
+            bloom_acc,
+            instance,
+            rw_table,
+            keccak_table,
+        }
+    }
+}
+
+/// Kind of an item of the Receipt circuit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ItemKind {
+    #[default]
+    Header,
+    Address,
+    Topic,
+}
+
+/// Witness of an item of the Receipt circuit
+#[derive(Clone, Debug, Default)]
+struct Item {
+    kind: ItemKind,
+    tx_id: usize,
+    log_id: usize,
+    log_length: usize,
+    index: usize,
+    rw_counter: usize,
+    topic_length_rw_counter: usize,
+    status: u64,
+    cumulative_gas_used: u64,
+    status_rw_counter: usize,
+    cumulative_gas_used_rw_counter: usize,
+    input: Vec<u8>,
+}
+
+/// Cells of the status and cumulative gas used of a transaction header
+type HeaderCells<F> = [AssignedCell<F, F>; 2];
+
+impl<F: Field> ReceiptCircuitConfig<F> {
+    fn assign_items(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        items: &[Item],
+        num_txs: usize,
+        max_items: usize,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<(AssignedCell<F, F>, Vec<HeaderCells<F>>), Error> {
+        let is_last_log_chip = IsZeroChip::construct(self.is_last_log.clone());
+
+        layouter.assign_region(
+            || "receipt items",
+            |mut region| {
+                let mut num_txs_cell = None;
+                let mut header_cells = Vec::new();
+                for item_idx in 0..max_items {
+                    let item = items.get(item_idx);
+                    for row in 0..ITEM_ROWS {
+                        let offset = item_idx * ITEM_ROWS + row;
+                        for (column, enabled) in [
+                            (self.q_enable, true),
+                            (self.q_head, offset == 0),
+                            (self.q_first, row == 0),
+                            (self.q_last, row == ITEM_ROWS - 1),
+                            (self.q_bit, BIT_ROWS.contains(&row)),
+                            (self.q_address_byte, row < 20),
+                        ] {
+                            region.assign_fixed(
+                                || "receipt item selector",
+                                column,
+                                offset,
+                                || Value::known(F::from(enabled as u64)),
+                            )?;
+                        }
+                        let cell = region.assign_advice(
+                            || "num_txs",
+                            self.num_txs,
+                            offset,
+                            || Value::known(F::from(num_txs as u64)),
+                        )?;
+                        num_txs_cell.get_or_insert(cell);
+                    }
+                    match item {
+                        Some(item) => header_cells.extend(self.assign_item(
+                            &mut region,
+                            item_idx * ITEM_ROWS,
+                            item,
+                            &is_last_log_chip,
+                            challenges,
+                        )?),
+                        None => {
+                            for row in 0..ITEM_ROWS {
+                                self.assign_padding_row(
+                                    &mut region,
+                                    item_idx * ITEM_ROWS + row,
+                                    &is_last_log_chip,
+                                )?;
+                            }
+                        }
+                    }
+                }
+
+                // Row queried by the transition of the last item
+                let offset = max_items * ITEM_ROWS;
+                for column in [
+                    self.q_enable,
+                    self.q_head,
+                    self.q_first,
+                    self.q_last,
+                    self.q_bit,
+                    self.q_address_byte,
+                ] {
+                    region.assign_fixed(
+                        || "receipt item selector",
+                        column,
+                        offset,
+                        || Value::known(F::ZERO),
+                    )?;
+                }
+                let cell = region.assign_advice(
+                    || "num_txs",
+                    self.num_txs,
+                    offset,
+                    || Value::known(F::from(num_txs as u64)),
+                )?;
+                num_txs_cell.get_or_insert(cell);
+                self.assign_padding_row(&mut region, offset, &is_last_log_chip)?;
+
+                Ok((
+                    num_txs_cell.expect("receipt circuit has at least one row"),
+                    header_cells,
+                ))
+            },
+        )
+    }
+
+    /// Assign an item, returning the status and cumulative gas used cells of
+    /// a transaction header.
+    fn assign_item(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        item: &Item,
+        is_last_log_chip: &IsZeroChip<F>,
+        challenges: &Challenges<Value<F>>,
+    ) -> Result<Option<HeaderCells<F>>, Error> {
+        let hash = if item.kind == ItemKind::Header {
+            [0u8; 32]
+        } else {
+            keccak256(&item.input)
+        };
+        let value_base = match item.kind {
+            ItemKind::Address => Value::known(F::from(256)),
+            _ => challenges.evm_word(),
+        };
+
+        let mut hash_acc = Value::known(F::ZERO);
+        let mut input_acc = Value::known(F::ZERO);
+        let mut value_acc = Value::known(F::ZERO);
+        let mut header_cells = None;
+        for row in 0..ITEM_ROWS {
+            let offset = offset + row;
+            let input_byte = item.input.get(row).copied().unwrap_or_default();
+            let byte = Value::known(F::from(input_byte as u64));
+            hash_acc = hash_acc * challenges.evm_word() + Value::known(F::from(hash[row] as u64));
+            if row < item.input.len() {
+                input_acc = input_acc * challenges.keccak_input() + byte;
+                value_acc = value_acc * value_base + byte;
+            }
+            let contribution = if item.kind != ItemKind::Header && BIT_ROWS.contains(&row) {
+                ((hash[row - 1] as u64 & 7) << 8) + hash[row] as u64 + 1
+            } else {
+                0
+            };
+
+            for (column, value) in [
+                (self.is_header, (item.kind == ItemKind::Header) as u64),
+                (self.is_address, (item.kind == ItemKind::Address) as u64),
+                (self.is_topic, (item.kind == ItemKind::Topic) as u64),
+                (self.tx_id, item.tx_id as u64),
+                (self.log_id, item.log_id as u64),
+                (self.log_length, item.log_length as u64),
+                (self.index, item.index as u64),
+                (self.rw_counter, item.rw_counter as u64),
+                (
+                    self.topic_length_rw_counter,
+                    item.topic_length_rw_counter as u64,
+                ),
+                (self.status_rw_counter, item.status_rw_counter as u64),
+                (
+                    self.cumulative_gas_used_rw_counter,
+                    item.cumulative_gas_used_rw_counter as u64,
+                ),
+                (self.hash_byte, hash[row] as u64),
+                (self.hash_lo3, hash[row] as u64 & 7),
+                (self.input_byte, input_byte as u64),
+                (self.contribution, contribution),
+            ] {
+                region.assign_advice(
+                    || "receipt item",
+                    column,
+                    offset,
+                    || Value::known(F::from(value)),
+                )?;
+            }
+            for (column, value) in [
+                (self.hash_acc, hash_acc),
+                (self.input_acc, input_acc),
+                (self.value_acc, value_acc),
+            ] {
+                region.assign_advice(|| "receipt item acc", column, offset, || value)?;
+            }
+            let [status, cumulative_gas_used] = [
+                (self.status, item.status),
+                (self.cumulative_gas_used, item.cumulative_gas_used),
+            ]
+            .map(|(column, value)| {
+                region.assign_advice(
+                    || "receipt tx field",
+                    column,
+                    offset,
+                    || Value::known(F::from(value)),
+                )
+            });
+            if item.kind == ItemKind::Header && row == 0 {
+                header_cells = Some([status?, cumulative_gas_used?]);
+            }
+            is_last_log_chip.assign(
+                region,
+                offset,
+                Value::known(F::from(item.log_length as u64) - F::from(item.log_id as u64)),
+            )?;
+        }
+        Ok(header_cells)
+    }
+
+    fn assign_padding_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        is_last_log_chip: &IsZeroChip<F>,
+    ) -> Result<(), Error> {
+        for column in [
+            self.is_header,
+            self.is_address,
+            self.is_topic,
+            self.tx_id,
+            self.log_id,
+            self.log_length,
+            self.index,
+            self.rw_counter,
+            self.topic_length_rw_counter,
+            self.status,
+            self.cumulative_gas_used,
+            self.status_rw_counter,
+            self.cumulative_gas_used_rw_counter,
+            self.hash_byte,
+            self.hash_lo3,
+            self.input_byte,
+            self.contribution,
+            self.hash_acc,
+            self.input_acc,
+            self.value_acc,
+        ] {
+            region.assign_advice(
+                || "receipt padding",
+                column,
+                offset,
+                || Value::known(F::ZERO),
+            )?;
+        }
+        is_last_log_chip.assign(region, offset, Value::known(F::ZERO))
+    }
+
+    fn assign_bloom(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        bloom: &[u8; BLOOM_BYTES],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        layouter.assign_region(
+            || "receipt bloom",
+            |mut region| {
+                let mut byte_cells = vec![None; BLOOM_BYTES];
+                let mut bloom_acc = 0u64;
+                for bit_idx in 0..BLOOM_BITS {
+                    let byte_idx = BLOOM_BYTES - 1 - bit_idx / 8;
+                    let bit = (bloom[byte_idx] >> (bit_idx % 8)) & 1;
+                    let weight = 1u64 << (bit_idx % 8);
+                    if bit_idx % 8 == 0 {
+                        bloom_acc = 0;
+                    }
+                    bloom_acc += bit as u64 * weight;
+
+                    for (column, value) in [
+                        (self.q_bloom, 1),
+                        (self.bloom_tag, bit_idx as u64 + 1),
+                        (self.bit_weight, weight),
+                        (self.q_byte_first, (bit_idx % 8 == 0) as u64),
+                    ] {
+                        region.assign_fixed(
+                            || "receipt bloom fixed",
+                            column,
+                            bit_idx,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                    region.assign_advice(
+                        || "bloom bit",
+                        self.bloom_bit,
+                        bit_idx,
+                        || Value::known(F::from(bit as u64)),
+                    )?;
+                    let cell = region.assign_advice(
+                        || "bloom acc",
+                        self.bloom_acc,
+                        bit_idx,
+                        || Value::known(F::from(bloom_acc)),
+                    )?;
+                    if bit_idx % 8 == 7 {
+                        byte_cells[byte_idx] = Some(cell);
+                    }
+                }
+
+                // Byte decomposition table, sharing the rows of the bloom.
+                for byte in 0..256u64 {
+                    region.assign_fixed(
+                        || "byte value",
+                        self.byte_value,
+                        byte as usize,
+                        || Value::known(F::from(byte)),
+                    )?;
+                    region.assign_fixed(
+                        || "byte lo3",
+                        self.byte_lo3,
+                        byte as usize,
+                        || Value::known(F::from(byte & 7)),
+                    )?;
+                }
+
+                Ok(byte_cells.into_iter().map(Option::unwrap).collect())
+            },
+        )
+    }
+}
+
+/// Receipt Circuit for proving the logs bloom, status and cumulative gas used
+/// of the receipts of a block, but not the receipts root
+#[derive(Clone, Default, Debug)]
+pub struct ReceiptCircuit<F: Field> {
+    /// Receipts of the transactions of the block
+    pub receipts: Vec<Receipt>,
+    /// Read-write records, holding the TxReceipt and TxLog rows of the
+    /// receipts
+    pub rws: RwMap,
+    /// Max number of rws in the RwTable
+    pub max_rws: usize,
+    /// Max number of transactions, which sizes the instance
+    pub max_txs: usize,
+    /// Max number of items (transaction headers, log addresses and topics)
+    pub max_items: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ReceiptCircuit<F> {
+    /// Return a new ReceiptCircuit
+    pub fn new(
+        receipts: Vec<Receipt>,
+        rws: RwMap,
+        max_rws: usize,
+        max_txs: usize,
+        max_items: usize,
+    ) -> Self {
+        Self {
+            receipts,
+            rws,
+            max_rws,
+            max_txs,
+            max_items,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of items needed for the receipts
+    pub fn num_items(receipts: &[Receipt]) -> usize {
+        receipts
+            .iter()
+            .map(|receipt| {
+                1 + receipt
+                    .logs
+                    .iter()
+                    .map(|log| 1 + log.topics.len())
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Logs bloom of the receipts
+    pub fn bloom(&self) -> [u8; BLOOM_BYTES] {
+        logs_bloom(&self.receipts)
+    }
+
+    fn items(&self) -> Vec<Item> {
+        let mut log_rw_counters = HashMap::new();
+        for rw in self.rws.0.get(&Target::TxLog).into_iter().flatten() {
+            if let Rw::TxLog {
+                rw_counter,
+                tx_id,
+                log_id,
+                field_tag,
+                index,
+                ..
+            } = *rw
+            {
+                log_rw_counters.insert(
+                    (tx_id, log_id as usize, field_tag as u64, index),
+                    rw_counter,
+                );
+            }
+        }
+        let mut receipt_rw_counters = HashMap::new();
+        for rw in self.rws.0.get(&Target::TxReceipt).into_iter().flatten() {
+            if let Rw::TxReceipt {
+                rw_counter,
+                is_write: true,
+                tx_id,
+                field_tag,
+                ..
+            } = *rw
+            {
+                receipt_rw_counters.insert((tx_id, field_tag as u64), rw_counter);
+            }
+        }
+        let receipt_rw_counter = |tx_id, field_tag: TxReceiptFieldTag| {
+            receipt_rw_counters
+                .get(&(tx_id, field_tag as u64))
+                .copied()
+                .unwrap_or_default()
+        };
+        let rw_counter = |tx_id, log_id, field_tag: TxLogFieldTag, index| {
+            log_rw_counters
+                .get(&(tx_id, log_id, field_tag as u64, index))
+                .copied()
+                .unwrap_or_default()
+        };
+
+        let mut items = Vec::new();
+        for receipt in self.receipts.iter() {
+            let tx_id = receipt.tx_id;
+            let log_length = receipt.logs.len();
+            items.push(Item {
+                kind: ItemKind::Header,
+                tx_id,
+                log_id: 0,
+                log_length,
+                index: 0,
+                rw_counter: receipt_rw_counter(tx_id, TxReceiptFieldTag::LogLength),
+                topic_length_rw_counter: 0,
+                status: receipt.status,
+                cumulative_gas_used: receipt.cumulative_gas_used,
+                status_rw_counter: receipt_rw_counter(tx_id, TxReceiptFieldTag::PostStateOrStatus),
+                cumulative_gas_used_rw_counter: receipt_rw_counter(
+                    tx_id,
+                    TxReceiptFieldTag::CumulativeGasUsed,
+                ),
+                input: vec![],
+            });
+            for (log_idx, log) in receipt.logs.iter().enumerate() {
+                let log_id = log_idx + 1;
+                let topic_length_rw_counter =
+                    rw_counter(tx_id, log_id, TxLogFieldTag::TopicLength, 0);
+                items.push(Item {
+                    kind: ItemKind::Address,
+                    tx_id,
+                    log_id,
+                    log_length,
+                    index: 0,
+                    rw_counter: rw_counter(tx_id, log_id, TxLogFieldTag::Address, 0),
+                    topic_length_rw_counter: if log.topics.is_empty() {
+                        topic_length_rw_counter
+                    } else {
+                        0
+                    },
+                    input: log.address.as_bytes().to_vec(),
+                    ..Default::default()
+                });
+                for (index, topic) in log.topics.iter().enumerate() {
+                    items.push(Item {
+                        kind: ItemKind::Topic,
+                        tx_id,
+                        log_id,
+                        log_length,
+                        index,
+                        rw_counter: rw_counter(tx_id, log_id, TxLogFieldTag::Topic, index),
+                        topic_length_rw_counter: if index + 1 == log.topics.len() {
+                            topic_length_rw_counter
+                        } else {
+                            0
+                        },
+                        input: topic.to_be_bytes().to_vec(),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        items
+    }
+}
+
+impl<F: Field> SubCircuit<F> for ReceiptCircuit<F> {
+    type Config = ReceiptCircuitConfig<F>;
+
+    fn unusable_rows() -> usize {
+        // No column queried at more than 3 distinct rotations, so returns 6 as
+        // minimum unusable rows.
+        6
+    }
+
+    fn new_from_block(block: &witness::Block<F>) -> Self {
+        let tx_types: Vec<u64> = block
+            .eth_blocks()
+            .flat_map(|eth_block| eth_block.transactions.iter())
+            .map(|tx| tx.transaction_type.unwrap_or_default().as_u64())
+            .collect();
+        let receipts = receipts_convert(&block.rws, &tx_types);
+        let max_items = match block.circuits_params.max_receipt_items {
+            0 => Self::num_items(&receipts),
+            max_items => max_items,
+        };
+        Self::new(
+            receipts,
+            block.rws.clone(),
+            block.circuits_params.max_rws,
+            block.circuits_params.max_txs,
+            max_items,
+        )
+    }
+
+    /// The instance holds the logs bloom bytes, the number of transactions,
+    /// and the status and cumulative gas used of each transaction, padded with
+    /// zeros up to `max_txs` transactions.
+    fn instance(&self) -> Vec<Vec<F>> {
+        let mut instance: Vec<F> = self
+            .bloom()
+            .iter()
+            .map(|byte| F::from(*byte as u64))
+            .collect();
+        instance.push(F::from(self.receipts.len() as u64));
+        for receipt in self.receipts.iter() {
+            instance.push(F::from(receipt.status));
+            instance.push(F::from(receipt.cumulative_gas_used));
+        }
+        instance.resize(BLOOM_BYTES + 1 + 2 * self.max_txs, F::ZERO);
+        vec![instance]
+    }
+
+    /// Return the minimum number of rows required to prove the block
+    fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
+        let circuit = Self::new_from_block(block);
+        let rows = |items| (items * ITEM_ROWS + 1).max(BLOOM_BITS);
+        (
+            rows(Self::num_items(&circuit.receipts)),
+            rows(circuit.max_items),
+        )
+    }
+
+    /// Make the assignments to the ReceiptCircuit
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.receipts.len() > self.max_txs {
+            error!(
+                "receipt circuit needs {} txs, but only {} are available",
+                self.receipts.len(),
+                self.max_txs
+            );
+            return Err(Error::Synthesis);
+        }
+        let items = self.items();
+        if items.len() > self.max_items {
+            error!(
+                "receipt circuit needs {} items, but only {} are available",
+                items.len(),
+                self.max_items
+            );
+            return Err(Error::Synthesis);
+        }
+
+        let (num_txs_cell, header_cells) = config.assign_items(
+            layouter,
+            &items,
+            self.receipts.len(),
+            self.max_items,
+            challenges,
+        )?;
+        let bloom_cells = config.assign_bloom(layouter, &self.bloom())?;
+
+        for (i, cell) in bloom_cells.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.instance, i)?;
+        }
+        layouter.constrain_instance(num_txs_cell.cell(), config.instance, BLOOM_BYTES)?;
+        // Transaction headers are in increasing tx_id from 1.
+        for (i, cell) in header_cells.iter().flatten().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.instance, BLOOM_BYTES + 1 + i)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+pub use super::ReceiptCircuit;
+
+use crate::{
+    receipt_circuit::{ReceiptCircuitConfig, ReceiptCircuitConfigArgs},
+    table::{KeccakTable, RwTable},
+    util::{Challenges, SubCircuit, SubCircuitConfig},
+};
+use eth_types::Field;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+impl<F: Field> Circuit<F> for ReceiptCircuit<F> {
+    type Config = (ReceiptCircuitConfig<F>, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let rw_table = RwTable::construct(meta);
+        let keccak_table = KeccakTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            ReceiptCircuitConfig::new(
+                meta,
+                ReceiptCircuitConfigArgs {
+                    rw_table,
+                    keccak_table,
+                    challenges,
+                },
+            )
+        };
+
+        (config, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&mut layouter);
+
+        config.rw_table.load(
+            &mut layouter,
+            &self.rws.table_assignments(),
+            self.max_rws,
+            challenges.evm_word(),
+        )?;
+        let keccak_inputs: Vec<Vec<u8>> = self
+            .receipts
+            .iter()
+            .flat_map(|receipt| receipt.bloom_inputs())
+            .collect();
+        config
+            .keccak_table
+            .dev_load(&mut layouter, &keccak_inputs, &challenges)?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
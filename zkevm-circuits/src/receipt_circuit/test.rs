@@ -0,0 +1,226 @@
+#![allow(unused_imports)]
+use crate::{
+    receipt_circuit::{ReceiptCircuit, BLOOM_BITS, ITEM_ROWS},
+    util::{log2_ceil, unusable_rows, SubCircuit},
+    witness::{
+        block_convert, bloom_bits, logs_bloom, receipts_root, Block, Log, Receipt, BLOOM_BYTES,
+    },
+};
+use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
+use eth_types::{bytecode, geth_types::GethData, Address, Bytecode, ToBigEndian, Word, H256};
+use ethers_core::utils::{keccak256, rlp::RlpStream};
+use halo2_proofs::{arithmetic::Field as Halo2Field, dev::MockProver, halo2curves::bn256::Fr};
+use mock::TestContext;
+
+#[test]
+fn receipt_circuit_unusable_rows() {
+    assert_eq!(
+        ReceiptCircuit::<Fr>::unusable_rows(),
+        unusable_rows::<Fr, ReceiptCircuit::<Fr>>(()),
+    )
+}
+
+#[test]
+fn receipts_root_of_known_tries() {
+    // Root of the empty trie
+    assert_eq!(
+        receipts_root(&[]),
+        H256::from_slice(
+            &hex::decode("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+                .unwrap()
+        )
+    );
+
+    // A single receipt is stored in a leaf with key `rlp(0) = 0x80`
+    let receipt = Receipt {
+        tx_id: 1,
+        tx_type: 0,
+        status: 1,
+        cumulative_gas_used: 21000,
+        logs: vec![],
+    };
+    let mut stream = RlpStream::new_list(2);
+    stream.append(&vec![0x20u8, 0x80]);
+    stream.append(&receipt.rlp_bytes());
+    assert_eq!(
+        receipts_root(&[receipt]),
+        H256(keccak256(stream.out().as_ref()))
+    );
+}
+
+#[test]
+fn logs_bloom_sets_bits_of_address_and_topics() {
+    let address = Address::repeat_byte(0x11);
+    let topic = Word::from(0xA0);
+    let receipt = Receipt {
+        logs: vec![Log {
+            address,
+            topics: vec![topic],
+            data: vec![],
+        }],
+        ..Default::default()
+    };
+
+    let mut expected = [0u8; BLOOM_BYTES];
+    for bit in bloom_bits(address.as_bytes())
+        .into_iter()
+        .chain(bloom_bits(&topic.to_be_bytes()))
+    {
+        expected[BLOOM_BYTES - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+    assert_eq!(receipt.bloom(), expected);
+    assert_eq!(logs_bloom(&[receipt.clone(), receipt]), expected);
+}
+
+fn gen_block(code: Bytecode) -> Block<Fr> {
+    gen_block_with_params(
+        code,
+        CircuitsParams {
+            max_rws: 1024,
+            ..Default::default()
+        },
+    )
+}
+
+fn gen_block_with_params(code: Bytecode, circuits_params: CircuitsParams) -> Block<Fr> {
+    let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+        .unwrap()
+        .into();
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), circuits_params)
+        .new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    block_convert::<Fr>(&builder.block, &builder.code_db).unwrap()
+}
+
+fn logs_code() -> Bytecode {
+    bytecode! {
+        PUSH32(Word::from(0x1234))
+        PUSH1(0x00)
+        MSTORE
+        // LOG2 with 32 bytes of data
+        PUSH32(Word::from(0xef))
+        PUSH32(Word::from(0xA0))
+        PUSH1(0x20)
+        PUSH1(0x00)
+        LOG2
+        // LOG0 without data
+        PUSH1(0x00)
+        PUSH1(0x00)
+        LOG0
+        STOP
+    }
+}
+
+fn run(circuit: &ReceiptCircuit<Fr>, instance: Vec<Vec<Fr>>) -> Result<(), Vec<String>> {
+    let rows = (circuit.max_items * ITEM_ROWS + 1)
+        .max(BLOOM_BITS)
+        .max(circuit.max_rws);
+    let k = log2_ceil(ReceiptCircuit::<Fr>::unusable_rows() + rows + 256);
+    let prover = MockProver::<Fr>::run(k, circuit, instance).unwrap();
+    prover
+        .verify_par()
+        .map_err(|failures| failures.iter().map(|f| f.to_string()).collect())
+}
+
+#[test]
+fn receipt_circuit_logs() {
+    let block = gen_block(logs_code());
+    let circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+
+    let logs = &circuit.receipts[0].logs;
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].topics, vec![Word::from(0xA0), Word::from(0xef)]);
+    assert_eq!(logs[0].data, Word::from(0x1234).to_be_bytes().to_vec());
+    assert!(logs[1].topics.is_empty() && logs[1].data.is_empty());
+    assert_eq!(circuit.receipts[0].status, 1);
+
+    let instance = circuit.instance();
+    assert_eq!(run(&circuit, instance), Ok(()));
+}
+
+#[test]
+fn receipt_circuit_no_logs() {
+    let block = gen_block(bytecode! { STOP });
+    let circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+    assert!(circuit.bloom().iter().all(|byte| *byte == 0));
+
+    let instance = circuit.instance();
+    assert_eq!(run(&circuit, instance), Ok(()));
+}
+
+#[test]
+fn receipt_circuit_shape_from_params() {
+    let circuits_params = CircuitsParams {
+        max_rws: 1024,
+        max_receipt_items: 8,
+        ..Default::default()
+    };
+    for code in [logs_code(), bytecode! { STOP }] {
+        let block = gen_block_with_params(code, circuits_params);
+        let circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+        assert_eq!(circuit.max_items, 8);
+
+        let instance = circuit.instance();
+        assert_eq!(
+            instance[0].len(),
+            BLOOM_BYTES + 1 + 2 * circuits_params.max_txs
+        );
+        assert_eq!(run(&circuit, instance), Ok(()));
+    }
+}
+
+#[test]
+fn receipt_circuit_wrong_bloom() {
+    let block = gen_block(logs_code());
+    let circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+
+    let mut instance = circuit.instance();
+    let byte = instance[0]
+        .iter()
+        .position(|byte| *byte == Fr::ZERO)
+        .unwrap();
+    instance[0][byte] = Fr::ONE;
+    assert!(run(&circuit, instance).is_err());
+}
+
+#[test]
+fn receipt_circuit_dropped_topic() {
+    let block = gen_block(logs_code());
+    let mut circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+
+    // Omitting a topic changes the bloom, but the TopicLength row of the log
+    // still requires it.
+    circuit.receipts[0].logs[0].topics.pop();
+    let instance = circuit.instance();
+    assert!(run(&circuit, instance).is_err());
+}
+
+#[test]
+fn receipt_circuit_wrong_status() {
+    let block = gen_block(logs_code());
+    let circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+    assert_eq!(circuit.instance()[0][BLOOM_BYTES + 1], Fr::ONE);
+
+    // The instance claims the tx failed, while its TxReceipt row has status 1.
+    let mut instance = circuit.instance();
+    instance[0][BLOOM_BYTES + 1] = Fr::ZERO;
+    assert!(run(&circuit, instance).is_err());
+
+    // Same for a witness claiming it, which the TxReceipt lookup rejects.
+    let mut circuit = circuit;
+    circuit.receipts[0].status = 0;
+    let instance = circuit.instance();
+    assert!(run(&circuit, instance).is_err());
+}
+
+#[test]
+fn receipt_circuit_wrong_cumulative_gas_used() {
+    let block = gen_block(logs_code());
+    let mut circuit = ReceiptCircuit::<Fr>::new_from_block(&block);
+
+    circuit.receipts[0].cumulative_gas_used += 1;
+    let instance = circuit.instance();
+    assert!(run(&circuit, instance).is_err());
+}
@@ -1,4 +1,5 @@
 //! The state circuit implementation.
+mod chunk;
 mod constraint_builder;
 mod lexicographic_ordering;
 mod lookups;
@@ -13,8 +14,16 @@ mod test;
 use bus_mapping::operation::Target;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 pub use dev::StateCircuit as TestStateCircuit;
+#[cfg(any(feature = "test", test, feature = "test-circuits"))]
+pub use dev::{ChunkedStateCircuitParams, StateCircuitParams};
+
+pub use chunk::{
+    check_chunk_instances, ChunkedStateCircuit, ChunkedStateCircuitConfig,
+    ChunkedStateCircuitConfigArgs, StateChunk, N_BOUNDARY_VALUES, N_CHUNK_INSTANCES,
+};
 
 use self::{
+    chunk::{BoundaryConfig, FingerprintConfig},
     constraint_builder::{MptUpdateTableQueries, RwTableQueries},
     lexicographic_ordering::LimbIndex,
 };
@@ -31,9 +40,10 @@ use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
 };
 use halo2_proofs::{
-    circuit::{Layouter, Region, Value},
+    circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{
-        Advice, Column, ConstraintSystem, Error, Expression, Fixed, SecondPhase, VirtualCells,
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, SecondPhase,
+        VirtualCells,
     },
     poly::Rotation,
 };
@@ -42,7 +52,7 @@ use lookups::{Chip as LookupsChip, Config as LookupsConfig, Queries as LookupsQu
 use multiple_precision_integer::{Chip as MpiChip, Config as MpiConfig, Queries as MpiQueries};
 use param::*;
 use random_linear_combination::{Chip as RlcChip, Config as RlcConfig, Queries as RlcQueries};
-use std::marker::PhantomData;
+use std::{iter, marker::PhantomData, ops::Range};
 
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 use std::collections::HashMap;
@@ -52,6 +62,9 @@ use std::collections::HashMap;
 pub struct StateCircuitConfig<F> {
     // Figure out why you get errors when this is Selector.
     selector: Column<Fixed>,
+    // Enables the sort keys of a row. Equal to `selector`, except for a chunked
+    // state circuit, where the head row only has its sort keys enabled.
+    keys_selector: Column<Fixed>,
     // https://github.com/privacy-scaling-explorations/zkevm-circuits/issues/407
     rw_table: RwTable,
     sort_keys: SortKeysConfig,
@@ -70,6 +83,10 @@ pub struct StateCircuitConfig<F> {
     not_first_access: Column<Advice>,
     lookups: LookupsConfig,
     power_of_randomness: [Expression<F>; N_BYTES_WORD - 1],
+    // Public boundary rows of a chunked state circuit
+    boundary: Option<BoundaryConfig>,
+    // Fingerprint of the rows of a chunked state circuit
+    fingerprint: Option<FingerprintConfig>,
     // External tables
     mpt_table: MptTable,
 }
@@ -82,6 +99,9 @@ pub struct StateCircuitConfigArgs<F: Field> {
     pub mpt_table: MptTable,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
+    /// Prove a chunk of the RwTable and expose its boundary rows in this
+    /// instance column, see [`StateCircuit::new_chunks`]
+    pub chunk_instance: Option<Column<Instance>>,
 }
 
 impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
@@ -94,20 +114,27 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
             rw_table,
             mpt_table,
             challenges,
+            chunk_instance,
         }: Self::ConfigArgs,
     ) -> Self {
+        let chunked = chunk_instance.is_some();
         let selector = meta.fixed_column();
+        let keys_selector = if chunked {
+            meta.fixed_column()
+        } else {
+            selector
+        };
         let lookups = LookupsChip::configure(meta);
         let power_of_randomness: [Expression<F>; 31] = challenges.evm_word_powers_of_randomness();
 
-        let rw_counter = MpiChip::configure(meta, selector, rw_table.rw_counter, lookups);
-        let tag = BinaryNumberChip::configure(meta, selector, Some(rw_table.tag));
-        let id = MpiChip::configure(meta, selector, rw_table.id, lookups);
-        let address = MpiChip::configure(meta, selector, rw_table.address, lookups);
+        let rw_counter = MpiChip::configure(meta, keys_selector, rw_table.rw_counter, lookups);
+        let tag = BinaryNumberChip::configure(meta, keys_selector, Some(rw_table.tag));
+        let id = MpiChip::configure(meta, keys_selector, rw_table.id, lookups);
+        let address = MpiChip::configure(meta, keys_selector, rw_table.address, lookups);
 
         let storage_key = RlcChip::configure(
             meta,
-            selector,
+            keys_selector,
             rw_table.storage_key,
             lookups,
             challenges.evm_word(),
@@ -144,21 +171,41 @@ impl<F: Field> SubCircuitConfig<F> for StateCircuitConfig<F> {
             power_of_randomness.clone(),
         );
 
+        let not_first_access = meta.advice_column();
+        let boundary = chunk_instance.map(|instance| {
+            BoundaryConfig::configure(
+                meta,
+                &rw_table,
+                storage_key,
+                lookups,
+                &challenges,
+                not_first_access,
+                initial_value,
+                state_root,
+                instance,
+            )
+        });
+        let fingerprint =
+            chunked.then(|| FingerprintConfig::configure(meta, &rw_table, &challenges, true));
+
         // annotate columns
         rw_table.annotate_columns(meta);
         mpt_table.annotate_columns(meta);
 
         let config = Self {
             selector,
+            keys_selector,
             sort_keys,
             initial_value,
             is_non_exist,
             mpt_proof_type,
             state_root,
             lexicographic_ordering,
-            not_first_access: meta.advice_column(),
+            not_first_access,
             lookups,
             power_of_randomness,
+            boundary,
+            fingerprint,
             rw_table,
             mpt_table,
         };
@@ -192,11 +239,10 @@ impl<F: Field> StateCircuitConfig<F> {
         challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         let updates = MptUpdates::mock_from(rows);
+        let (rows, _) = RwMap::table_assignments_prepad(rows, n_rows);
         layouter.assign_region(
             || "state circuit",
-            |mut region| {
-                self.assign_with_region(&mut region, rows, &updates, n_rows, challenges.evm_word())
-            },
+            |mut region| self.assign_with_region(&mut region, &rows, &updates, challenges),
         )
     }
 
@@ -205,12 +251,11 @@ impl<F: Field> StateCircuitConfig<F> {
         region: &mut Region<'_, F>,
         rows: &[Rw],
         updates: &MptUpdates,
-        n_rows: usize, // 0 means dynamically calculated from `rows`.
-        randomness: Value<F>,
+        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
+        let randomness = challenges.evm_word();
         let tag_chip = BinaryNumberChip::construct(self.sort_keys.tag);
 
-        let (rows, padding_length) = RwMap::table_assignments_prepad(rows, n_rows);
         let rows_len = rows.len();
 
         let mut state_root =
@@ -220,16 +265,26 @@ impl<F: Field> StateCircuitConfig<F> {
         self.annotate_circuit_in_region(region);
 
         for (offset, row) in rows.iter().enumerate() {
-            if offset >= padding_length {
+            if !matches!(row, Rw::Start { .. }) {
                 log::trace!("state circuit assign offset:{} row:{:#?}", offset, row);
             }
 
+            // The head row of a chunk is only constrained through its sort keys.
+            let is_chunk_head = offset == 0 && self.boundary.is_some();
             region.assign_fixed(
                 || "selector",
                 self.selector,
                 offset,
-                || Value::known(F::ONE),
+                || Value::known(if is_chunk_head { F::ZERO } else { F::ONE }),
             )?;
+            if self.keys_selector != self.selector {
+                region.assign_fixed(
+                    || "keys_selector",
+                    self.keys_selector,
+                    offset,
+                    || Value::known(F::ONE),
+                )?;
+            }
 
             tag_chip.assign(region, offset, &row.tag())?;
 
@@ -375,6 +430,37 @@ impl<F: Field> StateCircuitConfig<F> {
         Ok(())
     }
 
+    /// Expose the boundary rows of the chunk laid out in the region in the
+    /// public inputs at `instance_offset`, and accumulate the fingerprint of
+    /// the rows at the `fingerprint_rows` offsets from `fingerprint`. Returns
+    /// the fingerprint cells of the head and of the last row.
+    #[allow(clippy::too_many_arguments)]
+    fn assign_chunk(
+        &self,
+        region: &mut Region<'_, F>,
+        rows: &[Rw],
+        updates: &MptUpdates,
+        challenges: &Challenges<Value<F>>,
+        fingerprint_rows: Range<usize>,
+        instance_offset: usize,
+        fingerprint: Value<F>,
+    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+        let (Some(boundary), Some(fingerprint_config)) = (&self.boundary, &self.fingerprint) else {
+            log::error!("state circuit not configured for chunks");
+            return Err(Error::Synthesis);
+        };
+        boundary.assign(
+            region,
+            &self.rw_table,
+            self.not_first_access,
+            rows,
+            updates,
+            challenges,
+            instance_offset,
+        )?;
+        fingerprint_config.assign(region, rows, fingerprint_rows, challenges, fingerprint)
+    }
+
     fn annotate_circuit_in_region(&self, region: &mut Region<F>) {
         self.rw_table.annotate_columns_in_region(region);
         self.mpt_table.annotate_columns_in_region(region);
@@ -384,6 +470,13 @@ impl<F: Field> StateCircuitConfig<F> {
             .annotate_columns_in_region(region, "STATE");
         self.sort_keys.annotate_columns_in_region(region, "STATE");
         region.name_column(|| "STATE_selector", self.selector);
+        if let Some(boundary) = &self.boundary {
+            region.name_column(|| "STATE_keys_selector", self.keys_selector);
+            boundary.annotate_columns_in_region(region);
+        }
+        if let Some(fingerprint) = &self.fingerprint {
+            fingerprint.annotate_columns_in_region(region);
+        }
         region.name_column(|| "STATE_not_first_access", self.not_first_access);
         region.name_column(|| "STATE_phase2_initial_value", self.initial_value);
         region.name_column(|| "STATE_phase2_mpt_proof_type", self.mpt_proof_type);
@@ -423,6 +516,9 @@ pub struct StateCircuit<F> {
     pub rows: Vec<Rw>,
     updates: MptUpdates,
     pub(crate) n_rows: usize,
+    /// Position of the circuit in the chunks of a block, `None` if it proves
+    /// the whole block
+    pub chunk: Option<StateChunk>,
     #[cfg(any(feature = "test", test, feature = "test-circuits"))]
     overrides: HashMap<(dev::AdviceColumn, isize), F>,
     _marker: PhantomData<F>,
@@ -437,11 +533,70 @@ impl<F: Field> StateCircuit<F> {
             rows,
             updates,
             n_rows,
+            chunk: None,
             #[cfg(any(feature = "test", test, feature = "test-circuits"))]
             overrides: HashMap::new(),
             _marker: PhantomData::default(),
         }
     }
+
+    /// make the state circuits proving the rows of an RwMap split in
+    /// consecutive chunks of `n_rows` rows. The circuits must be configured
+    /// with a `chunk_instance`, and are linked to each other and to the RwTable
+    /// by a [`ChunkedStateCircuit`].
+    pub fn new_chunks(rw_map: RwMap, n_rows: usize) -> Result<Vec<Self>, bus_mapping::Error> {
+        let rows = rw_map.table_assignments();
+        let chunks = chunk::split_rows(&rows, n_rows)?;
+        let total = chunks.len();
+        // The MPT updates of the block, each applied in the chunk of its key.
+        let block_updates = MptUpdates::mock_from(&rows);
+        let mut state_root = block_updates.old_root();
+        Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, (head, rows, n_fillers))| {
+                let old_root = head
+                    .and_then(|head| block_updates.get(&head))
+                    .map_or(state_root, |update| update.old_root());
+                let mut circuit = Self {
+                    rows,
+                    updates: MptUpdates::default(),
+                    n_rows,
+                    chunk: Some(StateChunk {
+                        index,
+                        total,
+                        head,
+                        n_fillers,
+                    }),
+                    #[cfg(any(feature = "test", test, feature = "test-circuits"))]
+                    overrides: HashMap::new(),
+                    _marker: PhantomData::default(),
+                };
+                let rows = circuit.assigned_rows();
+                circuit.updates = block_updates.restrict(&rows, old_root);
+                state_root = chunk::tail_state_root(&rows, &circuit.updates);
+                circuit
+            })
+            .collect())
+    }
+
+    /// Rows laid out in the circuit: the head of the chunk followed by the rows,
+    /// or the rows prepadded with Start rows.
+    fn assigned_rows(&self) -> Vec<Rw> {
+        match self.chunk.and_then(|chunk| chunk.head) {
+            Some(head) => iter::once(head).chain(self.rows.iter().copied()).collect(),
+            None => RwMap::table_assignments_prepad(&self.rows, self.n_rows).0,
+        }
+    }
+
+    /// Offsets of the laid out rows in the fingerprint: all of them but the
+    /// head and the filler rows of a chunk.
+    fn fingerprint_rows(&self, n_assigned_rows: usize) -> Range<usize> {
+        match self.chunk {
+            Some(chunk) => 1..n_assigned_rows - chunk.n_fillers,
+            None => 0..n_assigned_rows,
+        }
+    }
 }
 
 impl<F: Field> SubCircuit<F> for StateCircuit<F> {
@@ -472,9 +627,37 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
         challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
+        self.synthesize_chunk(config, challenges, layouter, 0, Value::known(F::ONE))
+            .map(|_| ())
+    }
+
+    /// boundary rows of the chunk for chunked circuits
+    fn instance(&self) -> Vec<Vec<F>> {
+        match self.chunk {
+            Some(_) => vec![chunk::instance(&self.assigned_rows(), &self.updates)],
+            None => vec![],
+        }
+    }
+}
+
+impl<F: Field> StateCircuit<F> {
+    /// Make the assignments to the StateCircuit. A chunk exposes its boundary
+    /// rows in the public inputs at `instance_offset` and accumulates the
+    /// fingerprint of its rows from `fingerprint`, returning the fingerprint
+    /// cells of its head and of its last row.
+    pub(crate) fn synthesize_chunk(
+        &self,
+        config: &StateCircuitConfig<F>,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+        instance_offset: usize,
+        fingerprint: Value<F>,
+    ) -> Result<Option<[AssignedCell<F, F>; 2]>, Error> {
         config.load_aux_tables(layouter)?;
 
         let randomness = challenges.evm_word();
+        let rows = self.assigned_rows();
+        let fingerprint_rows = self.fingerprint_rows(rows.len());
 
         // Assigning to same columns in different regions should be avoided.
         // Here we use one single region to assign `overrides` to both rw table and
@@ -482,20 +665,25 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
         layouter.assign_region(
             || "state circuit",
             |mut region| {
-                config.rw_table.load_with_region(
-                    &mut region,
-                    &self.rows,
-                    self.n_rows,
-                    randomness,
-                )?;
-
-                config.assign_with_region(
-                    &mut region,
-                    &self.rows,
-                    &self.updates,
-                    self.n_rows,
-                    randomness,
-                )?;
+                config
+                    .rw_table
+                    .load_rows_with_region(&mut region, &rows, randomness)?;
+
+                config.assign_with_region(&mut region, &rows, &self.updates, challenges)?;
+                let fingerprint_cells = self
+                    .chunk
+                    .map(|_| {
+                        config.assign_chunk(
+                            &mut region,
+                            &rows,
+                            &self.updates,
+                            challenges,
+                            fingerprint_rows.clone(),
+                            instance_offset,
+                            fingerprint,
+                        )
+                    })
+                    .transpose()?;
                 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
                 {
                     let padding_length = RwMap::padding_len(self.rows.len(), self.n_rows);
@@ -513,15 +701,10 @@ impl<F: Field> SubCircuit<F> for StateCircuit<F> {
                     }
                 }
 
-                Ok(())
+                Ok(fingerprint_cells)
             },
         )
     }
-
-    /// powers of randomness for instance columns
-    fn instance(&self) -> Vec<Vec<F>> {
-        vec![]
-    }
 }
 
 fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>) -> Queries<F> {
@@ -587,7 +770,10 @@ fn queries<F: Field>(meta: &mut VirtualCells<'_, F>, c: &StateCircuitConfig<F>)
         first_different_limb: [0, 1, 2, 3]
             .map(|idx| meta.query_advice(first_different_limb.bits[idx], Rotation::cur())),
         not_first_access: meta.query_advice(c.not_first_access, Rotation::cur()),
-        last_access: 1.expr() - meta.query_advice(c.not_first_access, Rotation::next()),
+        // The selector is off on the head row of a chunk, which was the last
+        // access of its key in the previous chunk.
+        last_access: meta.query_fixed(c.selector, Rotation::cur())
+            - meta.query_advice(c.not_first_access, Rotation::next()),
         state_root: meta.query_advice(c.state_root, Rotation::cur()),
         state_root_prev: meta.query_advice(c.state_root, Rotation::prev()),
     }
@@ -0,0 +1,954 @@
+//! Split of the sorted RwTable in consecutive chunks, each proven by its own
+//! state circuit.
+//!
+//! Every chunk is laid out in `n_rows` rows. The first row is the head of the
+//! chunk: the Start row of the block for the first chunk, and a copy of the
+//! last row of the previous chunk for the others. The rows of a chunk are split
+//! at access group boundaries, so that every access to a key is proven in a
+//! single chunk. The space left at the end of a chunk is filled with reads of
+//! the last accessed key.
+//!
+//! The head and the tail (last row) of a chunk are exposed as public inputs,
+//! together with the `not_first_access` flag of the row following the head.
+//! The words of a boundary row are exposed as the high and low halves of their
+//! integer value rather than as random linear combinations, so that the public
+//! inputs don't depend on the challenges.
+//!
+//! A [`ChunkedStateCircuit`] proves the chunks of a block in one circuit, next
+//! to the RwTable looked up by the EVM circuit. Consecutive chunks share the
+//! public inputs of their common boundary row, and the product of the
+//! fingerprints of the chunks, accumulated with the challenges of the proof,
+//! must be the fingerprint of the RwTable.
+use super::{
+    lookups::Config as LookupsConfig, random_linear_combination::Config as RlcConfig, StateCircuit,
+    StateCircuitConfig, StateCircuitConfigArgs,
+};
+use crate::{
+    evm_circuit::{param::N_BYTES_WORD, util::rlc},
+    table::{LookupTable, MptTable, RwTable},
+    util::{Challenges, Expr, SubCircuitConfig},
+    witness::{MptUpdates, Rw, RwMap},
+};
+use bus_mapping::operation::Target;
+use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word};
+use gadgets::util::{expr_from_bytes, select};
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Instance, SecondPhase, Selector,
+        ThirdPhase,
+    },
+    poly::Rotation,
+};
+use std::{array, ops::Range};
+
+/// Number of public values describing a boundary row of a chunk: rw_counter,
+/// tag, id, address, field_tag, the high and low halves of the storage key, the
+/// high and low halves of the value and of the committed value, each followed
+/// by whether it is assigned as an RLC, and the high and low halves of the
+/// state root.
+pub const N_BOUNDARY_VALUES: usize = 15;
+
+/// Number of public inputs of a chunk: the head values, the `not_first_access`
+/// flag of the row following the head and the tail values.
+pub const N_CHUNK_INSTANCES: usize = 2 * N_BOUNDARY_VALUES + 1;
+
+/// Number of words of a boundary row: the value, the committed value and the
+/// state root, each decomposed in bytes on a row of its own.
+const N_BOUNDARY_WORDS: usize = 3;
+
+/// Offsets in the boundary values of the halves of each word, and of the flag
+/// telling whether it is assigned as an RLC. The state root is always an RLC.
+const BOUNDARY_WORDS: [(usize, Option<usize>); N_BOUNDARY_WORDS] =
+    [(7, Some(9)), (10, Some(12)), (13, None)];
+
+/// Position of a state circuit in the chunks of a block
+#[derive(Clone, Copy, Debug)]
+pub struct StateChunk {
+    /// Index of the chunk
+    pub index: usize,
+    /// Number of chunks of the block
+    pub total: usize,
+    /// Last row of the previous chunk, `None` for the first chunk
+    pub(super) head: Option<Rw>,
+    /// Number of filler rows at the end of the chunk
+    pub(super) n_fillers: usize,
+}
+
+type Key = (
+    u64,
+    Option<usize>,
+    Option<Address>,
+    Option<u64>,
+    Option<Word>,
+);
+
+fn key(row: &Rw) -> Key {
+    (
+        row.tag() as u64,
+        row.id(),
+        row.address(),
+        row.field_tag(),
+        row.storage_key(),
+    )
+}
+
+/// Split the sorted `rows` of a block in chunks of `n_rows` rows, returning
+/// the head, the rows and the number of filler rows of each chunk.
+pub(super) fn split_rows(
+    rows: &[Rw],
+    n_rows: usize,
+) -> Result<Vec<(Option<Rw>, Vec<Rw>, usize)>, bus_mapping::Error> {
+    // The words of the head and of the tail are decomposed on distinct rows.
+    if n_rows < 2 * N_BOUNDARY_WORDS {
+        return Err(bus_mapping::Error::InternalError(
+            "state circuit chunk must have at least 6 rows",
+        ));
+    }
+    let capacity = n_rows - 1;
+    let rows: Vec<Rw> = rows
+        .iter()
+        .copied()
+        .skip_while(|row| matches!(row, Rw::Start { .. }))
+        .collect();
+
+    // end offsets of the access groups of the rows
+    let mut group_ends = vec![];
+    for (offset, row) in rows.iter().enumerate() {
+        if offset > 0 && key(&rows[offset - 1]) != key(row) {
+            group_ends.push(offset);
+        }
+    }
+    if !rows.is_empty() {
+        group_ends.push(rows.len());
+    }
+    let group_start = |group: usize| if group == 0 { 0 } else { group_ends[group - 1] };
+
+    // Fill the chunks backwards, so that all chunks but the first one are full.
+    let mut filler_rw_counter = rows.iter().map(Rw::rw_counter).max().unwrap_or_default();
+    let mut chunks = vec![];
+    let mut end = group_ends.len();
+    while end > 0 && group_ends[end - 1] > capacity {
+        let mut start = end;
+        while start > 0 && group_ends[end - 1] - group_start(start - 1) <= capacity {
+            start -= 1;
+        }
+        if start == end {
+            return Err(bus_mapping::Error::InternalError(
+                "access group doesn't fit in a state circuit chunk",
+            ));
+        }
+        let mut chunk = rows[group_start(start)..group_ends[end - 1]].to_vec();
+        let last = chunk[chunk.len() - 1];
+        let n_fillers = capacity - chunk.len();
+        chunk.extend((0..n_fillers).map(|_| {
+            filler_rw_counter += 1;
+            filler(&last, filler_rw_counter)
+        }));
+        chunks.push((chunk, n_fillers));
+        end = start;
+    }
+    chunks.push((rows[..group_start(end)].to_vec(), 0));
+    chunks.reverse();
+
+    let mut head = None;
+    Ok(chunks
+        .into_iter()
+        .map(|(chunk, n_fillers)| {
+            let chunk_head = head;
+            head = chunk.last().copied().or(head);
+            (chunk_head, chunk, n_fillers)
+        })
+        .collect())
+}
+
+/// Read of the value of `row` at `rw_counter`, padding the access group of
+/// `row` up to the end of a chunk without changing the state.
+fn filler(row: &Rw, rw_counter: usize) -> Rw {
+    let mut filler = *row;
+    match &mut filler {
+        Rw::Memory {
+            rw_counter: counter,
+            is_write,
+            value,
+            value_prev,
+            ..
+        }
+        | Rw::Account {
+            rw_counter: counter,
+            is_write,
+            value,
+            value_prev,
+            ..
+        }
+        | Rw::AccountStorage {
+            rw_counter: counter,
+            is_write,
+            value,
+            value_prev,
+            ..
+        } => {
+            *counter = rw_counter;
+            *is_write = false;
+            *value_prev = *value;
+        }
+        Rw::TxRefund {
+            rw_counter: counter,
+            is_write,
+            value,
+            value_prev,
+            ..
+        } => {
+            *counter = rw_counter;
+            *is_write = false;
+            *value_prev = *value;
+        }
+        Rw::TxAccessListAccount {
+            rw_counter: counter,
+            is_write,
+            is_warm,
+            is_warm_prev,
+            ..
+        }
+        | Rw::TxAccessListAccountStorage {
+            rw_counter: counter,
+            is_write,
+            is_warm,
+            is_warm_prev,
+            ..
+        } => {
+            *counter = rw_counter;
+            *is_write = false;
+            *is_warm_prev = *is_warm;
+        }
+        Rw::CallContext {
+            rw_counter: counter,
+            is_write,
+            ..
+        }
+        | Rw::Stack {
+            rw_counter: counter,
+            is_write,
+            ..
+        } => {
+            *counter = rw_counter;
+            *is_write = false;
+        }
+        // TxLog rows are always writes.
+        Rw::TxLog {
+            rw_counter: counter,
+            ..
+        }
+        | Rw::TxReceipt {
+            rw_counter: counter,
+            ..
+        }
+        | Rw::Start {
+            rw_counter: counter,
+        } => *counter = rw_counter,
+    }
+    filler
+}
+
+/// High and low halves of `word`
+pub(super) fn hi_lo<F: Field>(word: Word) -> [F; 2] {
+    let bytes = word.to_le_bytes();
+    let half =
+        |bytes: &[u8]| F::from_u128(u128::from_le_bytes(bytes.try_into().expect("16 bytes")));
+    [half(&bytes[16..]), half(&bytes[..16])]
+}
+
+/// Words of a boundary row, with whether they are assigned as RLCs: the value,
+/// the committed value in the MPT `updates` of the chunk and the state root
+/// after the row.
+fn boundary_words(
+    row: &Rw,
+    updates: &MptUpdates,
+    state_root: Word,
+) -> [(Word, bool); N_BOUNDARY_WORDS] {
+    let committed_value = updates.get(row).map_or((Word::zero(), false), |update| {
+        (update.old_value(), update.value_is_word())
+    });
+    [row.value_word(), committed_value, (state_root, true)]
+}
+
+/// Public values describing a boundary row of a chunk, with the MPT `updates`
+/// of the chunk and the state root after the row.
+fn boundary_values<F: Field>(
+    row: &Rw,
+    updates: &MptUpdates,
+    state_root: Word,
+) -> [F; N_BOUNDARY_VALUES] {
+    let mut values = vec![
+        F::from(row.rw_counter() as u64),
+        F::from(row.tag() as u64),
+        F::from(row.id().unwrap_or_default() as u64),
+        row.address()
+            .unwrap_or_default()
+            .to_scalar()
+            .expect("address fits in a field element"),
+        F::from(row.field_tag().unwrap_or_default()),
+    ];
+    values.extend(hi_lo::<F>(row.storage_key().unwrap_or_default()));
+    for ((word, is_rlc), (_, is_rlc_offset)) in boundary_words(row, updates, state_root)
+        .into_iter()
+        .zip(BOUNDARY_WORDS)
+    {
+        values.extend(hi_lo::<F>(word));
+        if is_rlc_offset.is_some() {
+            values.push(F::from(is_rlc as u64));
+        }
+    }
+    values.try_into().expect("N_BOUNDARY_VALUES values")
+}
+
+/// State roots assigned to the head and the tail of a chunk laid out in `rows`:
+/// the state root changes on the last access of a key in the MPT.
+fn state_roots(rows: &[Rw], updates: &MptUpdates) -> (Word, Word) {
+    let mut state_root = updates.old_root();
+    let mut head_state_root = state_root;
+    for (offset, row) in rows.iter().enumerate() {
+        let is_last_access = rows
+            .get(offset + 1)
+            .map_or(true, |next| key(next) != key(row));
+        if is_last_access {
+            if let Some(update) = updates.get(row) {
+                assert_eq!(state_root, update.old_root());
+                state_root = update.new_root();
+            }
+        }
+        if offset == 0 {
+            head_state_root = state_root;
+        }
+    }
+    (head_state_root, state_root)
+}
+
+/// Factor of a row in the fingerprint of the RwTable: the random linear
+/// combination of the row values, followed by 1.
+fn fingerprint_factor<F: Field>(row: &Rw, evm_word: F, lookup_input: F) -> F {
+    let values = row.table_assignment_aux(evm_word).values();
+    values
+        .iter()
+        .chain(&[F::ONE])
+        .rev()
+        .fold(F::ZERO, |acc, value| acc * lookup_input + value)
+}
+
+/// Public inputs of a chunk laid out in `rows`, with the MPT `updates` of the
+/// chunk
+pub(super) fn instance<F: Field>(rows: &[Rw], updates: &MptUpdates) -> Vec<F> {
+    let head = &rows[0];
+    let tail = &rows[rows.len() - 1];
+    let head_continues = rows.get(1).map_or(false, |row| key(row) == key(head));
+    let (head_state_root, tail_state_root) = state_roots(rows, updates);
+    boundary_values(head, updates, head_state_root)
+        .into_iter()
+        .chain([F::from(head_continues as u64)])
+        .chain(boundary_values(tail, updates, tail_state_root))
+        .collect()
+}
+
+/// State root after the rows of a chunk laid out in `rows`, with the MPT
+/// `updates` of the chunk
+pub(super) fn tail_state_root(rows: &[Rw], updates: &MptUpdates) -> Word {
+    state_roots(rows, updates).1
+}
+
+/// Check the public inputs of a [`ChunkedStateCircuit`], which the circuit
+/// doesn't constrain: the first chunk starts at the Start row of the block with
+/// the state root `prev_state_root`, and every other chunk starts a new access
+/// group. The state root after the block is in the last two public inputs.
+pub fn check_chunk_instances<F: Field>(instance: &[F], prev_state_root: Word) -> bool {
+    let stride = N_BOUNDARY_VALUES + 1;
+    if instance.len() < N_CHUNK_INSTANCES || (instance.len() - N_BOUNDARY_VALUES) % stride != 0 {
+        return false;
+    }
+    let n_chunks = (instance.len() - N_BOUNDARY_VALUES) / stride;
+    let start = boundary_values(
+        &Rw::Start { rw_counter: 1 },
+        &MptUpdates::default(),
+        prev_state_root,
+    );
+
+    instance[..N_BOUNDARY_VALUES] == start
+        && (1..n_chunks).all(|index| instance[index * stride + N_BOUNDARY_VALUES] == F::ZERO)
+}
+
+/// Config exposing the boundary rows of a chunk as public inputs
+#[derive(Clone, Copy)]
+pub(super) struct BoundaryConfig {
+    q_boundary: Selector,
+    storage_key_hi: Column<Advice>,
+    storage_key_lo: Column<Advice>,
+    // Enabled on the rows decomposing a word of a boundary row
+    q_word: Selector,
+    // Enabled on the rows decomposing a state root, always an RLC
+    q_word_rlc: Selector,
+    word: Column<Advice>,
+    word_bytes: [Column<Advice>; N_BYTES_WORD],
+    word_hi: Column<Advice>,
+    word_lo: Column<Advice>,
+    word_is_rlc: Column<Advice>,
+    initial_value: Column<Advice>,
+    state_root: Column<Advice>,
+    instance: Column<Instance>,
+}
+
+impl BoundaryConfig {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        rw_table: &RwTable,
+        storage_key: RlcConfig<N_BYTES_WORD>,
+        lookups: LookupsConfig,
+        challenges: &Challenges<Expression<F>>,
+        not_first_access: Column<Advice>,
+        initial_value: Column<Advice>,
+        state_root: Column<Advice>,
+        instance: Column<Instance>,
+    ) -> Self {
+        let q_boundary = meta.selector();
+        let storage_key_hi = meta.advice_column();
+        let storage_key_lo = meta.advice_column();
+        let q_word = meta.selector();
+        let q_word_rlc = meta.selector();
+        let word = meta.advice_column_in(SecondPhase);
+        let word_bytes = [0; N_BYTES_WORD].map(|_| meta.advice_column());
+        let word_hi = meta.advice_column();
+        let word_lo = meta.advice_column();
+        let word_is_rlc = meta.advice_column();
+
+        meta.enable_equality(instance);
+        for column in [
+            rw_table.rw_counter,
+            rw_table.tag,
+            rw_table.id,
+            rw_table.address,
+            rw_table.field_tag,
+            storage_key_hi,
+            storage_key_lo,
+            rw_table.value,
+            initial_value,
+            state_root,
+            word,
+            word_hi,
+            word_lo,
+            word_is_rlc,
+            not_first_access,
+        ] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("storage key halves of a chunk boundary row", |meta| {
+            let q_boundary = meta.query_selector(q_boundary);
+            let bytes = storage_key
+                .bytes
+                .map(|byte| meta.query_advice(byte, Rotation::cur()));
+            let hi = meta.query_advice(storage_key_hi, Rotation::cur());
+            let lo = meta.query_advice(storage_key_lo, Rotation::cur());
+            vec![
+                q_boundary.clone() * (hi - expr_from_bytes(&bytes[16..])),
+                q_boundary * (lo - expr_from_bytes(&bytes[..16])),
+            ]
+        });
+
+        for byte in word_bytes {
+            lookups.range_check_u8(meta, "chunk boundary word bytes fit into u8", |meta| {
+                meta.query_advice(byte, Rotation::cur())
+            });
+        }
+
+        meta.create_gate("words of a chunk boundary row", |meta| {
+            let q_word = meta.query_selector(q_word);
+            let q_word_rlc = meta.query_selector(q_word_rlc);
+            let bytes = word_bytes.map(|byte| meta.query_advice(byte, Rotation::cur()));
+            let word = meta.query_advice(word, Rotation::cur());
+            let hi = meta.query_advice(word_hi, Rotation::cur());
+            let lo = meta.query_advice(word_lo, Rotation::cur());
+            let is_rlc = meta.query_advice(word_is_rlc, Rotation::cur());
+            // The word is assigned either as the RLC of its bytes, or as the
+            // scalar of its bytes, like the values of the RwTable.
+            let value = select::expr(
+                is_rlc.clone(),
+                rlc::expr(&bytes, challenges.evm_word()),
+                expr_from_bytes(&bytes),
+            );
+            vec![
+                q_word.clone() * is_rlc.clone() * (1.expr() - is_rlc.clone()),
+                q_word.clone() * (word - value),
+                q_word.clone() * (hi - expr_from_bytes(&bytes[16..])),
+                q_word * (lo - expr_from_bytes(&bytes[..16])),
+                q_word_rlc * (1.expr() - is_rlc),
+            ]
+        });
+
+        Self {
+            q_boundary,
+            storage_key_hi,
+            storage_key_lo,
+            q_word,
+            q_word_rlc,
+            word,
+            word_bytes,
+            word_hi,
+            word_lo,
+            word_is_rlc,
+            initial_value,
+            state_root,
+            instance,
+        }
+    }
+
+    /// Copy the public inputs at `instance_offset` into the head and tail rows
+    /// of a chunk laid out in `rows`, with the MPT `updates` of the chunk.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        rw_table: &RwTable,
+        not_first_access: Column<Advice>,
+        rows: &[Rw],
+        updates: &MptUpdates,
+        challenges: &Challenges<Value<F>>,
+        instance_offset: usize,
+    ) -> Result<(), Error> {
+        let n_rows = rows.len();
+        let (head_state_root, tail_state_root) = state_roots(rows, updates);
+        let key_columns = [
+            rw_table.rw_counter,
+            rw_table.tag,
+            rw_table.id,
+            rw_table.address,
+            rw_table.field_tag,
+            self.storage_key_hi,
+            self.storage_key_lo,
+        ];
+        let word_columns = [rw_table.value, self.initial_value, self.state_root];
+        // The words of the head are decomposed on the first rows of the chunk,
+        // and the words of the tail on the last rows.
+        let boundaries = [
+            (
+                0,
+                array::from_fn::<_, N_BOUNDARY_WORDS, _>(|i| i),
+                head_state_root,
+                instance_offset,
+            ),
+            (
+                n_rows - 1,
+                array::from_fn(|i| n_rows - 1 - i),
+                tail_state_root,
+                instance_offset + N_BOUNDARY_VALUES + 1,
+            ),
+        ];
+
+        for (offset, word_offsets, state_root, instance_offset) in boundaries {
+            self.q_boundary.enable(region, offset)?;
+            for (i, column) in key_columns.into_iter().enumerate() {
+                region.assign_advice_from_instance(
+                    || "chunk boundary",
+                    self.instance,
+                    instance_offset + i,
+                    column,
+                    offset,
+                )?;
+            }
+
+            let words = boundary_words(&rows[offset], updates, state_root);
+            for (((word_offset, column), (word, is_rlc)), (hi_lo_offset, is_rlc_offset)) in
+                word_offsets
+                    .into_iter()
+                    .zip(word_columns)
+                    .zip(words)
+                    .zip(BOUNDARY_WORDS)
+            {
+                self.q_word.enable(region, word_offset)?;
+                let value = challenges.evm_word().map(|randomness| {
+                    if is_rlc {
+                        rlc::value(&word.to_le_bytes(), randomness)
+                    } else {
+                        word.to_scalar()
+                            .expect("scalar value fits in a field element")
+                    }
+                });
+                // The word of the boundary row is copied to the row decomposing it.
+                region
+                    .assign_advice(|| "chunk boundary word", column, offset, || value)?
+                    .copy_advice(|| "chunk boundary word", region, self.word, word_offset)?;
+                for (byte_column, byte) in self.word_bytes.into_iter().zip(word.to_le_bytes()) {
+                    region.assign_advice(
+                        || "chunk boundary word byte",
+                        byte_column,
+                        word_offset,
+                        || Value::known(F::from(byte as u64)),
+                    )?;
+                }
+                for (i, column) in [self.word_hi, self.word_lo].into_iter().enumerate() {
+                    region.assign_advice_from_instance(
+                        || "chunk boundary word half",
+                        self.instance,
+                        instance_offset + hi_lo_offset + i,
+                        column,
+                        word_offset,
+                    )?;
+                }
+                match is_rlc_offset {
+                    Some(is_rlc_offset) => {
+                        region.assign_advice_from_instance(
+                            || "chunk boundary word is_rlc",
+                            self.instance,
+                            instance_offset + is_rlc_offset,
+                            self.word_is_rlc,
+                            word_offset,
+                        )?;
+                    }
+                    None => {
+                        self.q_word_rlc.enable(region, word_offset)?;
+                        region.assign_advice(
+                            || "chunk boundary word is_rlc",
+                            self.word_is_rlc,
+                            word_offset,
+                            || Value::known(F::ONE),
+                        )?;
+                    }
+                }
+            }
+        }
+        region.assign_advice_from_instance(
+            || "chunk head continues",
+            self.instance,
+            instance_offset + N_BOUNDARY_VALUES,
+            not_first_access,
+            1,
+        )?;
+        Ok(())
+    }
+
+    pub(super) fn annotate_columns_in_region<F: Field>(&self, region: &mut Region<F>) {
+        region.name_column(|| "STATE_boundary_storage_key_hi", self.storage_key_hi);
+        region.name_column(|| "STATE_boundary_storage_key_lo", self.storage_key_lo);
+        region.name_column(|| "STATE_phase2_boundary_word", self.word);
+        region.name_column(|| "STATE_boundary_word_hi", self.word_hi);
+        region.name_column(|| "STATE_boundary_word_lo", self.word_lo);
+        region.name_column(|| "STATE_boundary_word_is_rlc", self.word_is_rlc);
+        for (i, byte) in self.word_bytes.into_iter().enumerate() {
+            region.name_column(|| format!("STATE_boundary_word_byte{}", i), byte);
+        }
+    }
+}
+
+/// Config computing the fingerprint of the rows of the RwTable, the product of
+/// their [`fingerprint_factor`]. The head row and the filler rows of a chunk
+/// are left out of its fingerprint, as well as the Start rows.
+#[derive(Clone, Copy)]
+pub(super) struct FingerprintConfig {
+    q_first: Selector,
+    q_step: Selector,
+    // Whether the row is a factor of the fingerprint
+    is_included: Column<Advice>,
+    // Fingerprint of the rows up to the current one
+    fingerprint: Column<Advice>,
+}
+
+impl FingerprintConfig {
+    pub(super) fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
+        rw_table: &RwTable,
+        challenges: &Challenges<Expression<F>>,
+        chunked: bool,
+    ) -> Self {
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        let is_included = meta.advice_column();
+        let fingerprint = meta.advice_column_in(ThirdPhase);
+
+        meta.enable_equality(fingerprint);
+
+        meta.create_gate("rows included in the rw fingerprint", |meta| {
+            let is_included = meta.query_advice(is_included, Rotation::cur());
+            let q_step = meta.query_selector(q_step);
+            let is_bool = is_included.clone() * (1.expr() - is_included.clone());
+            if chunked {
+                // Any read may be left out of a chunk, as reads don't change the
+                // state: a read of the RwTable left out of every chunk makes the
+                // fingerprints differ.
+                let is_write = meta.query_advice(rw_table.is_write, Rotation::cur());
+                vec![
+                    q_step.clone() * is_bool,
+                    q_step * (1.expr() - is_included) * is_write,
+                ]
+            } else {
+                // Only the Start rows, which the EVM circuit never looks up, are
+                // left out of the RwTable.
+                let q = meta.query_selector(q_first) + q_step;
+                let tag = meta.query_advice(rw_table.tag, Rotation::cur());
+                vec![
+                    q.clone() * is_bool,
+                    q * (1.expr() - is_included) * (tag - Target::Start.expr()),
+                ]
+            }
+        });
+
+        meta.create_gate("rw fingerprint", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let q_step = meta.query_selector(q_step);
+            let is_included = meta.query_advice(is_included, Rotation::cur());
+            let values: Vec<_> = rw_table
+                .table_exprs(meta)
+                .into_iter()
+                .chain([1.expr()])
+                .collect();
+            let factor =
+                1.expr() + is_included * (rlc::expr(&values, challenges.lookup_input()) - 1.expr());
+            let fingerprint_cur = meta.query_advice(fingerprint, Rotation::cur());
+            let fingerprint_prev = meta.query_advice(fingerprint, Rotation::prev());
+            let mut constraints =
+                vec![q_step * (fingerprint_cur.clone() - fingerprint_prev * factor.clone())];
+            // The fingerprint of a chunk continues the one of the previous chunk
+            // from its head row, which was the tail of the previous chunk.
+            if !chunked {
+                constraints.push(q_first * (fingerprint_cur - factor));
+            }
+            constraints
+        });
+
+        Self {
+            q_first,
+            q_step,
+            is_included,
+            fingerprint,
+        }
+    }
+
+    /// Assign the fingerprint of the rows laid out in the region at the
+    /// `included` offsets, accumulated from `fingerprint`. Returns the
+    /// fingerprint cells of the first and of the last row.
+    pub(super) fn assign<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        rows: &[Rw],
+        included: Range<usize>,
+        challenges: &Challenges<Value<F>>,
+        mut fingerprint: Value<F>,
+    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+        let mut cells = None;
+        for (offset, row) in rows.iter().enumerate() {
+            if offset == 0 {
+                self.q_first.enable(region, offset)?;
+            } else {
+                self.q_step.enable(region, offset)?;
+            }
+
+            let is_included = included.contains(&offset) && !matches!(row, Rw::Start { .. });
+            region.assign_advice(
+                || "rw fingerprint is_included",
+                self.is_included,
+                offset,
+                || Value::known(F::from(is_included as u64)),
+            )?;
+            if is_included {
+                fingerprint = fingerprint
+                    .zip(challenges.evm_word())
+                    .zip(challenges.lookup_input())
+                    .map(|((fingerprint, evm_word), lookup_input)| {
+                        fingerprint * fingerprint_factor(row, evm_word, lookup_input)
+                    });
+            }
+
+            let cell = region.assign_advice(
+                || "rw fingerprint",
+                self.fingerprint,
+                offset,
+                || fingerprint,
+            )?;
+            cells = Some(match cells {
+                Some([first, _]) => [first, cell],
+                None => [cell.clone(), cell],
+            });
+        }
+        cells.ok_or(Error::Synthesis)
+    }
+
+    pub(super) fn annotate_columns_in_region<F: Field>(&self, region: &mut Region<F>) {
+        region.name_column(|| "STATE_rw_fingerprint_is_included", self.is_included);
+        region.name_column(|| "STATE_phase3_rw_fingerprint", self.fingerprint);
+    }
+}
+
+/// Config of the chunks of the RwTable of a block, proven in one circuit
+#[derive(Clone)]
+pub struct ChunkedStateCircuitConfig<F: Field> {
+    rw_table: RwTable,
+    // Fingerprint of the RwTable looked up by the EVM circuit
+    fingerprint: FingerprintConfig,
+    chunks: Vec<StateCircuitConfig<F>>,
+}
+
+/// Circuit configuration arguments
+pub struct ChunkedStateCircuitConfigArgs<F: Field> {
+    /// RwTable looked up by the EVM circuit
+    pub rw_table: RwTable,
+    /// MptTable
+    pub mpt_table: MptTable,
+    /// Challenges
+    pub challenges: Challenges<Expression<F>>,
+    /// Number of chunks
+    pub n_chunks: usize,
+}
+
+impl<F: Field> SubCircuitConfig<F> for ChunkedStateCircuitConfig<F> {
+    type ConfigArgs = ChunkedStateCircuitConfigArgs<F>;
+
+    /// Return a new ChunkedStateCircuitConfig
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        Self::ConfigArgs {
+            rw_table,
+            mpt_table,
+            challenges,
+            n_chunks,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        // Boundary rows of the chunks, shared by consecutive chunks
+        let instance = meta.instance_column();
+        // The fingerprint of the first chunk starts from 1.
+        let constant = meta.fixed_column();
+        meta.enable_constant(constant);
+
+        let fingerprint = FingerprintConfig::configure(meta, &rw_table, &challenges, false);
+        // Every chunk is laid out in its own columns.
+        let chunks = (0..n_chunks)
+            .map(|_| {
+                let rw_table = RwTable::construct(meta);
+                StateCircuitConfig::new(
+                    meta,
+                    StateCircuitConfigArgs {
+                        rw_table,
+                        mpt_table,
+                        challenges: challenges.clone(),
+                        chunk_instance: Some(instance),
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            rw_table,
+            fingerprint,
+            chunks,
+        }
+    }
+}
+
+/// Chunks of the RwTable of a block proven in one circuit, next to the RwTable
+/// looked up by the EVM circuit
+#[derive(Clone, Default, Debug)]
+pub struct ChunkedStateCircuit<F> {
+    /// Rows of the RwTable
+    pub rows: Vec<Rw>,
+    /// Number of rows of the RwTable, 0 means dynamically calculated from
+    /// `rows`
+    pub n_rows: usize,
+    /// State circuits of the chunks, in order
+    pub chunks: Vec<StateCircuit<F>>,
+}
+
+impl<F: Field> ChunkedStateCircuit<F> {
+    /// make the circuit proving the RwTable of `n_rows` rows of an RwMap with
+    /// its rows split in consecutive chunks of `chunk_rows` rows
+    pub fn new(
+        rw_map: RwMap,
+        n_rows: usize,
+        chunk_rows: usize,
+    ) -> Result<Self, bus_mapping::Error> {
+        Ok(Self {
+            rows: rw_map.table_assignments(),
+            n_rows,
+            chunks: StateCircuit::new_chunks(rw_map, chunk_rows)?,
+        })
+    }
+
+    /// The boundary rows of the chunks, each head followed by the
+    /// `not_first_access` flag of the row following it. The tail of a chunk is
+    /// the head of the next one.
+    pub fn instance(&self) -> Vec<Vec<F>> {
+        let instance = self
+            .chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(index, chunk)| {
+                let skip = if index == 0 { 0 } else { N_BOUNDARY_VALUES };
+                chunk.instance().remove(0).into_iter().skip(skip)
+            })
+            .collect();
+        vec![instance]
+    }
+
+    /// Make the assignments to the ChunkedStateCircuit
+    pub fn synthesize_sub(
+        &self,
+        config: &ChunkedStateCircuitConfig<F>,
+        challenges: &Challenges<Value<F>>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if self.chunks.len() != config.chunks.len() {
+            log::error!(
+                "ChunkedStateCircuit has {} chunks, configured for {}",
+                self.chunks.len(),
+                config.chunks.len()
+            );
+            return Err(Error::Synthesis);
+        }
+
+        let (rows, _) = RwMap::table_assignments_prepad(&self.rows, self.n_rows);
+        let [_, rw_fingerprint] = layouter.assign_region(
+            || "rw table",
+            |mut region| {
+                config
+                    .rw_table
+                    .load_rows_with_region(&mut region, &rows, challenges.evm_word())?;
+                config.fingerprint.assign(
+                    &mut region,
+                    &rows,
+                    0..rows.len(),
+                    challenges,
+                    Value::known(F::ONE),
+                )
+            },
+        )?;
+
+        // The fingerprints of the chunks are accumulated from one chunk to the
+        // next, starting from 1.
+        let mut fingerprint: Option<AssignedCell<F, F>> = None;
+        for (index, (chunk, chunk_config)) in self.chunks.iter().zip(&config.chunks).enumerate() {
+            let start = fingerprint
+                .as_ref()
+                .map_or(Value::known(F::ONE), |cell| cell.value().copied());
+            let [head, last] = chunk
+                .synthesize_chunk(
+                    chunk_config,
+                    challenges,
+                    layouter,
+                    index * (N_BOUNDARY_VALUES + 1),
+                    start,
+                )?
+                .ok_or(Error::Synthesis)?;
+            layouter.assign_region(
+                || "chunk fingerprint",
+                |mut region| match &fingerprint {
+                    Some(prev) => region.constrain_equal(prev.cell(), head.cell()),
+                    None => region.constrain_constant(head.cell(), F::ONE),
+                },
+            )?;
+            fingerprint = Some(last);
+        }
+
+        layouter.assign_region(
+            || "rw fingerprint of the chunks",
+            |mut region| {
+                let fingerprint = fingerprint.as_ref().ok_or(Error::Synthesis)?;
+                region.constrain_equal(fingerprint.cell(), rw_fingerprint.cell())
+            },
+        )
+    }
+}
@@ -220,24 +220,22 @@ impl<F: Field> ConstraintBuilder<F> {
         for limb in &q.address.limbs[2..] {
             self.require_zero("memory address fits into 2 limbs", limb.clone());
         }
-        // 2.3. value is a byte
-        self.add_lookup(
-            "memory value is a byte",
-            vec![(q.rw_table.value.clone(), q.lookups.u8.clone())],
-        );
-        // 2.4. Start initial value is 0
+        // 2.3. Start initial value is 0
         self.require_zero("initial Memory value is 0", q.initial_value());
-        // 2.5. state root does not change
+        // 2.4. state root does not change
         self.require_equal(
             "state_root is unchanged for Memory",
             q.state_root(),
             q.state_root_prev(),
         );
-        self.require_equal(
-            "value_prev column equals initial_value for Memory",
-            q.value_prev_column(),
-            q.initial_value(),
-        );
+        // 2.5. value_prev is the previous value of the word
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
     }
 
     fn build_stack_constraints(&mut self, q: &Queries<F>) {
@@ -1,9 +1,13 @@
 pub use super::StateCircuit;
 
 use crate::{
-    state_circuit::{StateCircuitConfig, StateCircuitConfigArgs},
+    state_circuit::{
+        ChunkedStateCircuit, ChunkedStateCircuitConfig, ChunkedStateCircuitConfigArgs,
+        StateCircuitConfig, StateCircuitConfigArgs,
+    },
     table::{MptTable, RwTable},
     util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness::MptUpdates,
 };
 use eth_types::Field;
 use halo2_proofs::{
@@ -11,22 +15,40 @@ use halo2_proofs::{
     plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
 };
 
+/// State Circuit configuration parameters
+#[derive(Default)]
+pub struct StateCircuitParams {
+    /// Prove a chunk of the RwTable, see [`StateCircuit::new_chunks`]
+    pub chunked: bool,
+}
+
 impl<F: Field> Circuit<F> for StateCircuit<F>
 where
     F: Field,
 {
     type Config = (StateCircuitConfig<F>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
-    type Params = ();
+    type Params = StateCircuitParams;
 
     fn without_witnesses(&self) -> Self {
         Self::default()
     }
 
+    fn params(&self) -> Self::Params {
+        StateCircuitParams {
+            chunked: self.chunk.is_some(),
+        }
+    }
+
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure_with_params(meta, StateCircuitParams::default())
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
         let rw_table = RwTable::construct(meta);
         let mpt_table = MptTable::construct(meta);
         let challenges = Challenges::construct(meta);
+        let chunk_instance = params.chunked.then(|| meta.instance_column());
 
         let config = {
             let challenges = challenges.exprs(meta);
@@ -36,6 +58,7 @@ where
                     rw_table,
                     mpt_table,
                     challenges,
+                    chunk_instance,
                 },
             )
         };
@@ -56,6 +79,69 @@ where
     }
 }
 
+/// Chunked State Circuit configuration parameters
+#[derive(Default)]
+pub struct ChunkedStateCircuitParams {
+    /// Number of chunks
+    pub n_chunks: usize,
+}
+
+impl<F: Field> Circuit<F> for ChunkedStateCircuit<F> {
+    type Config = (ChunkedStateCircuitConfig<F>, MptTable, Challenges);
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = ChunkedStateCircuitParams;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn params(&self) -> Self::Params {
+        ChunkedStateCircuitParams {
+            n_chunks: self.chunks.len(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure_with_params(meta, ChunkedStateCircuitParams::default())
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
+        let rw_table = RwTable::construct(meta);
+        let mpt_table = MptTable::construct(meta);
+        let challenges = Challenges::construct(meta);
+
+        let config = {
+            let challenges = challenges.exprs(meta);
+            ChunkedStateCircuitConfig::new(
+                meta,
+                ChunkedStateCircuitConfigArgs {
+                    rw_table,
+                    mpt_table,
+                    challenges,
+                    n_chunks: params.n_chunks,
+                },
+            )
+        };
+
+        (config, mpt_table, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        (config, mpt_table, challenges): Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&mut layouter);
+        // The chunks share the MPT updates of the block.
+        mpt_table.load(
+            &mut layouter,
+            &MptUpdates::mock_from(&self.rows),
+            challenges.evm_word(),
+        )?;
+        self.synthesize_sub(&config, &challenges, &mut layouter)
+    }
+}
+
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub enum AdviceColumn {
     IsWrite,
@@ -32,7 +32,7 @@ const N_ROWS: usize = 1 << 16;
 fn state_circuit_unusable_rows() {
     assert_eq!(
         StateCircuit::<Fr>::unusable_rows(),
-        unusable_rows::<Fr, StateCircuit::<Fr>>(()),
+        unusable_rows::<Fr, StateCircuit::<Fr>>(StateCircuitParams::default()),
     )
 }
 
@@ -73,7 +73,7 @@ fn verifying_key_independent_of_rw_length() {
             memory: vec![Operation::new(
                 RWCounter::from(1),
                 RW::WRITE,
-                MemoryOp::new(1, MemoryAddress::from(0), 32),
+                MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::zero()),
             )],
             ..Default::default()
         }),
@@ -97,23 +97,23 @@ fn state_circuit_simple_2() {
     let memory_op_0 = Operation::new(
         RWCounter::from(12),
         RW::WRITE,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::zero()),
     );
     let memory_op_1 = Operation::new(
         RWCounter::from(24),
         RW::READ,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::from(32)),
     );
 
     let memory_op_2 = Operation::new(
         RWCounter::from(17),
         RW::WRITE,
-        MemoryOp::new(1, MemoryAddress::from(1), 32),
+        MemoryOp::new(1, MemoryAddress::from(32), Word::from(32), Word::zero()),
     );
     let memory_op_3 = Operation::new(
         RWCounter::from(87),
         RW::READ,
-        MemoryOp::new(1, MemoryAddress::from(1), 32),
+        MemoryOp::new(1, MemoryAddress::from(32), Word::from(32), Word::from(32)),
     );
 
     let stack_op_0 = Operation::new(
@@ -176,12 +176,12 @@ fn state_circuit_simple_6() {
     let memory_op_0 = Operation::new(
         RWCounter::from(12),
         RW::WRITE,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::zero()),
     );
     let memory_op_1 = Operation::new(
         RWCounter::from(13),
         RW::READ,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::from(32)),
     );
     let storage_op_2 = Operation::new(
         RWCounter::from(19),
@@ -203,7 +203,7 @@ fn lexicographic_ordering_test_1() {
     let memory_op = Operation::new(
         RWCounter::from(12),
         RW::WRITE,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::zero()),
     );
     let storage_op = Operation::new(
         RWCounter::from(19),
@@ -225,12 +225,12 @@ fn lexicographic_ordering_test_2() {
     let memory_op_0 = Operation::new(
         RWCounter::from(12),
         RW::WRITE,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::zero()),
     );
     let memory_op_1 = Operation::new(
         RWCounter::from(13),
         RW::WRITE,
-        MemoryOp::new(1, MemoryAddress::from(0), 32),
+        MemoryOp::new(1, MemoryAddress::from(0), Word::from(32), Word::from(32)),
     );
     test_state_circuit_ok(vec![memory_op_0, memory_op_1], vec![], vec![]);
 }
@@ -477,8 +477,9 @@ fn nonlexicographic_order_tag() {
         rw_counter: 1,
         is_write: true,
         call_id: 1,
-        memory_address: 10,
-        byte: 12,
+        memory_address: 32,
+        value: U256::from(12),
+        value_prev: U256::zero(),
     };
     let second = Rw::CallContext {
         rw_counter: 2,
@@ -675,15 +676,17 @@ fn read_inconsistency() {
             rw_counter: 10,
             is_write: false,
             call_id: 1,
-            memory_address: 10,
-            byte: 0,
+            memory_address: 32,
+            value: U256::from(0),
+            value_prev: U256::zero(),
         },
         Rw::Memory {
             rw_counter: 40,
             is_write: false,
             call_id: 1,
-            memory_address: 10,
-            byte: 200,
+            memory_address: 32,
+            value: U256::from(200),
+            value_prev: U256::zero(),
         },
     ];
 
@@ -719,8 +722,9 @@ fn invalid_memory_address() {
         rw_counter: 1,
         is_write: true,
         call_id: 1,
-        memory_address: 1u64 << 32,
-        byte: 12,
+        memory_address: 32 << 32,
+        value: U256::from(12),
+        value_prev: U256::zero(),
     }];
 
     assert_error_matches(verify(rows), "memory address fits into 2 limbs");
@@ -732,8 +736,9 @@ fn bad_initial_memory_value() {
         rw_counter: 1,
         is_write: true,
         call_id: 1,
-        memory_address: 10,
-        byte: 0,
+        memory_address: 32,
+        value: U256::from(0),
+        value_prev: U256::zero(),
     }];
 
     let v = Fr::from(200);
@@ -751,23 +756,30 @@ fn bad_initial_memory_value() {
 }
 
 #[test]
-fn invalid_memory_value() {
-    let rows = vec![Rw::Memory {
-        rw_counter: 1,
-        is_write: true,
-        call_id: 1,
-        memory_address: 10,
-        byte: 1,
-    }];
-    let v = Fr::from(256);
-    let overrides = HashMap::from([
-        ((AdviceColumn::Value, 0), v),
-        ((AdviceColumn::NonEmptyWitness, 0), v.invert().unwrap()),
-    ]);
-
-    let result = verify_with_overrides(rows, overrides);
+fn invalid_memory_value_prev() {
+    let rows = vec![
+        Rw::Memory {
+            rw_counter: 1,
+            is_write: true,
+            call_id: 1,
+            memory_address: 32,
+            value: U256::from(1),
+            value_prev: U256::zero(),
+        },
+        Rw::Memory {
+            rw_counter: 2,
+            is_write: true,
+            call_id: 1,
+            memory_address: 32,
+            value: U256::from(2),
+            value_prev: U256::from(3),
+        },
+    ];
 
-    assert_error_matches(result, "memory value is a byte");
+    assert_error_matches(
+        verify(rows),
+        "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+    );
 }
 
 #[test]
@@ -960,6 +972,7 @@ fn variadic_size_check() {
         updates,
         overrides: HashMap::default(),
         n_rows: N_ROWS,
+        chunk: None,
         _marker: std::marker::PhantomData::default(),
     };
     let power_of_randomness = circuit.instance();
@@ -988,6 +1001,7 @@ fn variadic_size_check() {
         updates,
         overrides: HashMap::default(),
         n_rows: N_ROWS,
+        chunk: None,
         _marker: std::marker::PhantomData::default(),
     };
     let power_of_randomness = circuit.instance();
@@ -1019,6 +1033,134 @@ fn bad_initial_tx_receipt_value() {
     );
 }
 
+fn chunked_rw_map() -> RwMap {
+    let memory_ops = (0..5u64)
+        .flat_map(|i| {
+            let address = MemoryAddress::from(32 * i);
+            [
+                Operation::new(
+                    RWCounter::from(10 + i as usize),
+                    RW::WRITE,
+                    MemoryOp::new(1, address, Word::from(i + 1), Word::zero()),
+                ),
+                Operation::new(
+                    RWCounter::from(20 + i as usize),
+                    RW::READ,
+                    MemoryOp::new(1, address, Word::from(i + 1), Word::from(i + 1)),
+                ),
+            ]
+        })
+        .collect();
+    let stack_ops = (0..3usize)
+        .map(|i| {
+            Operation::new(
+                RWCounter::from(30 + i),
+                RW::WRITE,
+                StackOp::new(1, StackAddress::from(1020 - i), Word::from(i)),
+            )
+        })
+        .collect();
+    let storage_ops = (0..3u64)
+        .map(|i| {
+            Operation::new(
+                RWCounter::from(40 + i as usize),
+                RW::WRITE,
+                StorageOp::new(
+                    U256::from(100).to_address(),
+                    Word::from(0x40),
+                    Word::from(i + 1),
+                    Word::from(i),
+                    1usize,
+                    Word::zero(),
+                ),
+            )
+        })
+        .collect();
+
+    RwMap::from(&OperationContainer {
+        memory: memory_ops,
+        stack: stack_ops,
+        storage: storage_ops,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn chunked_state_circuit_ok() {
+    let circuit = ChunkedStateCircuit::<Fr>::new(chunked_rw_map(), 64, 6).unwrap();
+    assert!(circuit.chunks.len() > 2);
+    let instance = circuit.instance();
+
+    let block = StateCircuit::<Fr>::new(chunked_rw_map(), 64);
+    assert!(check_chunk_instances(
+        &instance[0],
+        block.updates.old_root()
+    ));
+
+    // The last chunk ends at the state root of the block.
+    let state_root = chunk::tail_state_root(&block.assigned_rows(), &block.updates);
+    assert_eq!(
+        instance[0][instance[0].len() - 2..],
+        chunk::hi_lo::<Fr>(state_root)
+    );
+
+    let prover = MockProver::<Fr>::run(17, &circuit, instance).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    // Every chunk is a valid state circuit on its own.
+    for chunk in &circuit.chunks {
+        let prover = MockProver::<Fr>::run(17, chunk, chunk.instance()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+#[test]
+fn chunked_state_circuit_unlinked_chunks() {
+    let circuit = ChunkedStateCircuit::<Fr>::new(chunked_rw_map(), 64, 6).unwrap();
+    let instance = circuit.instance();
+    let prev_state_root = StateCircuit::<Fr>::new(chunked_rw_map(), 64)
+        .updates
+        .old_root();
+
+    // The chunks must start from the state root before the block.
+    assert!(!check_chunk_instances(&instance[0], prev_state_root + 1));
+
+    // Consecutive chunks share their boundary row: the tail of a chunk can't
+    // differ from the head of the next one.
+    for index in [0, N_BOUNDARY_VALUES + 1, N_BOUNDARY_VALUES + 8] {
+        let mut instance = instance.clone();
+        instance[0][index] += Fr::ONE;
+        let prover = MockProver::<Fr>::run(17, &circuit, instance).unwrap();
+        assert!(prover.verify().is_err(), "public input {}", index);
+    }
+
+    // The chunks don't make up the RwTable without one of them.
+    let mut circuit = circuit;
+    circuit.chunks.pop();
+    let instance = circuit.instance();
+    let prover = MockProver::<Fr>::run(17, &circuit, instance).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn state_circuit_chunk_boundary_instance() {
+    let chunks = StateCircuit::<Fr>::new_chunks(chunked_rw_map(), 6).unwrap();
+
+    // A chunk can't claim a head rw_counter, value or state root other than
+    // the ones of its rows.
+    for index in [0, 8, N_BOUNDARY_VALUES - 1] {
+        let mut instance = chunks[1].instance();
+        instance[0][index] += Fr::ONE;
+        let prover = MockProver::<Fr>::run(17, &chunks[1], instance).unwrap();
+        assert!(prover.verify().is_err(), "public input {}", index);
+    }
+}
+
+#[test]
+fn state_circuit_chunk_too_small() {
+    // The words of the head and of the tail don't fit in a chunk of 2 rows.
+    assert!(StateCircuit::<Fr>::new_chunks(chunked_rw_map(), 2).is_err());
+}
+
 fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockProver<Fr> {
     let updates = MptUpdates::mock_from(&rows);
     let circuit = StateCircuit::<Fr> {
@@ -1026,6 +1168,7 @@ fn prover(rows: Vec<Rw>, overrides: HashMap<(AdviceColumn, isize), Fr>) -> MockP
         updates,
         overrides,
         n_rows: N_ROWS,
+        chunk: None,
         _marker: std::marker::PhantomData::default(),
     };
     let instance = circuit.instance();
@@ -16,6 +16,7 @@
 //! - [ ] Keccak Circuit
 //! - [ ] MPT Circuit
 //! - [x] PublicInputs Circuit
+//! - [x] Receipt Circuit
 //!
 //! And the following shared tables, with the circuits that use them:
 //!
@@ -54,15 +55,18 @@
 // #[cfg(any(feature = "test", test))]
 // pub(crate) mod test;
 
+pub mod capacity;
+
 use crate::{
     bytecode_circuit::circuit::{
         BytecodeCircuit, BytecodeCircuitConfig, BytecodeCircuitConfigArgs,
     },
     copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs},
-    evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
+    evm_circuit::{param::EvmLayout, EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs},
     exp_circuit::{ExpCircuit, ExpCircuitConfig},
     keccak_circuit::{KeccakCircuit, KeccakCircuitConfig, KeccakCircuitConfigArgs},
-    pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs},
+    pi_circuit::{PiCircuit, PiCircuitConfig, PiCircuitConfigArgs, PiCommitment},
+    receipt_circuit::{ReceiptCircuit, ReceiptCircuitConfig, ReceiptCircuitConfigArgs},
     state_circuit::{StateCircuit, StateCircuitConfig, StateCircuitConfigArgs},
     table::{
         BlockTable, BytecodeTable, CopyTable, ExpTable, KeccakTable, MptTable, RwTable, TxTable,
@@ -75,7 +79,7 @@ use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
     mock::BlockData,
 };
-use eth_types::{geth_types::GethData, Field};
+use eth_types::{evm_types::ChainSpec, geth_types::GethData, Field};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error, Expression},
@@ -96,6 +100,7 @@ pub struct SuperCircuitConfig<F: Field> {
     keccak_circuit: KeccakCircuitConfig<F>,
     pi_circuit: PiCircuitConfig<F>,
     exp_circuit: ExpCircuitConfig<F>,
+    receipt_circuit: ReceiptCircuitConfig<F>,
 }
 
 /// Circuit configuration arguments
@@ -104,6 +109,14 @@ pub struct SuperCircuitConfigArgs<F: Field> {
     pub max_txs: usize,
     /// Max calldata
     pub max_calldata: usize,
+    /// Number of blocks proven together
+    pub num_blocks: usize,
+    /// Commitment to the raw public inputs exposed by the PI circuit
+    pub pi_commitment: PiCommitment,
+    /// Layout of the EVM circuit steps
+    pub evm_layout: EvmLayout,
+    /// Hardfork rules of the proven blocks
+    pub chain_spec: ChainSpec,
     /// Mock randomness
     pub mock_randomness: F,
 }
@@ -117,6 +130,10 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
         Self::ConfigArgs {
             max_txs,
             max_calldata,
+            num_blocks,
+            pi_commitment,
+            evm_layout,
+            chain_spec,
             mock_randomness,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -154,8 +171,12 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             PiCircuitConfigArgs {
                 max_txs,
                 max_calldata,
+                num_blocks,
+                commitment: pi_commitment,
                 block_table: block_table.clone(),
                 tx_table: tx_table.clone(),
+                keccak_table: keccak_table.clone(),
+                challenges: challenges.clone(),
             },
         );
         let tx_circuit = TxCircuitConfig::new(
@@ -163,6 +184,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             TxCircuitConfigArgs {
                 tx_table: tx_table.clone(),
                 keccak_table: keccak_table.clone(),
+                block_table: block_table.clone(),
                 challenges: challenges.clone(),
             },
         );
@@ -191,9 +213,18 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 rw_table,
                 mpt_table,
                 challenges: challenges.clone(),
+                chunk_instance: None,
             },
         );
         let exp_circuit = ExpCircuitConfig::new(meta, exp_table);
+        let receipt_circuit = ReceiptCircuitConfig::new(
+            meta,
+            ReceiptCircuitConfigArgs {
+                rw_table,
+                keccak_table: keccak_table.clone(),
+                challenges: challenges.clone(),
+            },
+        );
         let evm_circuit = EvmCircuitConfig::new(
             meta,
             EvmCircuitConfigArgs {
@@ -206,6 +237,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 keccak_table,
                 exp_table,
                 is_taiko: false,
+                chain_spec,
+                layout: evm_layout,
             },
         );
 
@@ -220,6 +253,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
             keccak_circuit,
             pi_circuit,
             exp_circuit,
+            receipt_circuit,
         }
     }
 }
@@ -243,6 +277,8 @@ pub struct SuperCircuit<F: Field> {
     pub exp_circuit: ExpCircuit<F>,
     /// Keccak Circuit
     pub keccak_circuit: KeccakCircuit<F>,
+    /// Receipt Circuit
+    pub receipt_circuit: ReceiptCircuit<F>,
     /// Circuits Parameters
     pub circuits_params: CircuitsParams,
     /// Mock randomness
@@ -257,6 +293,20 @@ impl<F: Field> SuperCircuit<F> {
             TxCircuitConfig::<F>::get_num_rows_required(block.circuits_params.max_txs);
         num_rows_evm_circuit.max(num_rows_tx_circuit)
     }
+
+    /// Expose the `commitment` to the raw public inputs in the instance
+    /// column of the PI circuit.  With [`PiCommitment::Keccak`], the raw
+    /// public inputs hashed by the PI circuit are also hashed by the keccak
+    /// circuit, so that the PI circuit finds them in the keccak table.
+    pub fn with_pi_commitment(mut self, commitment: PiCommitment) -> Self {
+        if commitment == PiCommitment::Keccak {
+            self.keccak_circuit = self
+                .keccak_circuit
+                .with_input(self.pi_circuit.keccak_input());
+        }
+        self.pi_circuit = self.pi_circuit.with_commitment(commitment);
+        self
+    }
 }
 
 // Eventhough the SuperCircuit is not a subcircuit we implement the SubCircuit
@@ -275,6 +325,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             CopyCircuit::<F>::unusable_rows(),
             ExpCircuit::<F>::unusable_rows(),
             KeccakCircuit::<F>::unusable_rows(),
+            ReceiptCircuit::<F>::unusable_rows(),
         ])
         .unwrap()
     }
@@ -288,6 +339,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         let copy_circuit = CopyCircuit::new_from_block_no_external(block);
         let exp_circuit = ExpCircuit::new_from_block(block);
         let keccak_circuit = KeccakCircuit::new_from_block(block);
+        let receipt_circuit = ReceiptCircuit::new_from_block(block);
 
         SuperCircuit::<_> {
             evm_circuit,
@@ -298,6 +350,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             copy_circuit,
             exp_circuit,
             keccak_circuit,
+            receipt_circuit,
             circuits_params: block.circuits_params,
             mock_randomness: block.randomness,
         }
@@ -314,6 +367,7 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         instance.extend_from_slice(&self.state_circuit.instance());
         instance.extend_from_slice(&self.exp_circuit.instance());
         instance.extend_from_slice(&self.evm_circuit.instance());
+        instance.extend_from_slice(&self.receipt_circuit.instance());
 
         instance
     }
@@ -328,8 +382,10 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
         let tx = TxCircuit::min_num_rows_block(block);
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
+        let receipt = ReceiptCircuit::min_num_rows_block(block);
 
-        let rows: Vec<(usize, usize)> = vec![evm, state, bytecode, copy, keccak, tx, exp, pi];
+        let rows: Vec<(usize, usize)> =
+            vec![evm, state, bytecode, copy, keccak, tx, exp, pi, receipt];
         let (rows_without_padding, rows_with_padding): (Vec<usize>, Vec<usize>) =
             rows.into_iter().unzip();
         (
@@ -361,6 +417,8 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
             .synthesize_sub(&config.evm_circuit, challenges, layouter)?;
         self.pi_circuit
             .synthesize_sub(&config.pi_circuit, challenges, layouter)?;
+        self.receipt_circuit
+            .synthesize_sub(&config.receipt_circuit, challenges, layouter)?;
         Ok(())
     }
 }
@@ -370,6 +428,10 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
 pub struct SuperCircuitParams<F: Field> {
     max_txs: usize,
     max_calldata: usize,
+    num_blocks: usize,
+    pi_commitment: PiCommitment,
+    evm_layout: EvmLayout,
+    chain_spec: ChainSpec,
     mock_randomness: F,
 }
 
@@ -386,6 +448,18 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
         SuperCircuitParams {
             max_txs: self.circuits_params.max_txs,
             max_calldata: self.circuits_params.max_calldata,
+            num_blocks: self.pi_circuit.public_data.num_blocks(),
+            pi_commitment: self.pi_circuit.commitment,
+            evm_layout: self
+                .evm_circuit
+                .block
+                .as_ref()
+                .map_or_else(EvmLayout::default, |block| block.evm_layout),
+            chain_spec: self
+                .evm_circuit
+                .block
+                .as_ref()
+                .map_or_else(ChainSpec::default, |block| block.chain_spec),
             mock_randomness: self.mock_randomness,
         }
     }
@@ -396,6 +470,10 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
             SuperCircuitConfigArgs {
                 max_txs: params.max_txs,
                 max_calldata: params.max_calldata,
+                num_blocks: params.num_blocks,
+                pi_commitment: params.pi_commitment,
+                evm_layout: params.evm_layout,
+                chain_spec: params.chain_spec,
                 mock_randomness: params.mock_randomness,
             },
         )
@@ -420,7 +498,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
 
         config.block_table.load(
             &mut layouter,
-            &block.context,
+            block.contexts(),
             Value::known(block.randomness),
         )?;
 
@@ -457,6 +535,29 @@ impl<F: Field> SuperCircuit<F> {
         Ok((ret.0, ret.1, ret.2, builder))
     }
 
+    /// From the witness data of a batch of consecutive blocks, generate a
+    /// SuperCircuit instance proving all of them at once.
+    ///
+    /// Also, return with it the minimum required SRS degree for the
+    /// circuit and the Public Inputs needed.
+    #[allow(clippy::type_complexity)]
+    pub fn build_batch(
+        geth_datas: Vec<GethData>,
+        circuits_params: CircuitsParams,
+        mock_randomness: F,
+    ) -> Result<(u32, Self, Vec<Vec<F>>, CircuitInputBuilder), bus_mapping::Error> {
+        let block_data = BlockData::new_from_geth_data_batch(&geth_datas, circuits_params);
+        let mut builder = block_data.new_circuit_input_builder();
+        let blocks: Vec<_> = geth_datas
+            .iter()
+            .map(|geth_data| (&geth_data.eth_block, geth_data.geth_traces.as_slice()))
+            .collect();
+        builder.handle_blocks(&blocks)?;
+
+        let ret = Self::build_from_circuit_input_builder(&builder, mock_randomness)?;
+        Ok((ret.0, ret.1, ret.2, builder))
+    }
+
     /// From CircuitInputBuilder, generate a SuperCircuit instance with all of
     /// the sub-circuits filled with their corresponding witnesses.
     ///
@@ -465,16 +566,34 @@ impl<F: Field> SuperCircuit<F> {
     pub fn build_from_circuit_input_builder(
         builder: &CircuitInputBuilder,
         mock_randomness: F,
+    ) -> Result<(u32, Self, Vec<Vec<F>>), bus_mapping::Error> {
+        Self::build_from_circuit_input_builder_with_pi_commitment(
+            builder,
+            mock_randomness,
+            PiCommitment::Rlc,
+        )
+    }
+
+    /// Same as [`Self::build_from_circuit_input_builder`], with the PI
+    /// circuit exposing the `pi_commitment` to the raw public inputs.
+    pub fn build_from_circuit_input_builder_with_pi_commitment(
+        builder: &CircuitInputBuilder,
+        mock_randomness: F,
+        pi_commitment: PiCommitment,
     ) -> Result<(u32, Self, Vec<Vec<F>>), bus_mapping::Error> {
         let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
         block.randomness = mock_randomness;
 
+        let circuit = SuperCircuit::new_from_block(&block).with_pi_commitment(pi_commitment);
+
+        // The keccak commitment hashes the raw public inputs one byte per row.
         let (_, rows_needed) = Self::min_num_rows_block(&block);
+        let rows_needed = rows_needed
+            .max(circuit.pi_circuit.num_rows())
+            .max(circuit.keccak_circuit.num_rows());
         let k = log2_ceil(Self::unusable_rows() + rows_needed);
         log::debug!("super circuit uses k = {}", k);
 
-        let circuit = SuperCircuit::new_from_block(&block);
-
         let instance = circuit.instance();
         Ok((k, circuit, instance))
     }
@@ -0,0 +1,425 @@
+//! Capacity planner for the [`SuperCircuit`].
+//!
+//! Given the witness of a block, compute how many rows each sub-circuit needs
+//! and derive the tightest [`CircuitsParams`] that prove it, so that callers
+//! (e.g. a sequencer filling a block) don't need to guess them.
+
+use super::SuperCircuit;
+use crate::{
+    bytecode_circuit::circuit::BytecodeCircuit,
+    copy_circuit::CopyCircuit,
+    evm_circuit::{param::EvmLayout, step::HasExecutionState, EvmCircuit, EvmCircuitParams},
+    exp_circuit::{param::OFFSET_INCREMENT, ExpCircuit},
+    keccak_circuit::{
+        keccak_packed_multi::{get_num_keccak_f, get_num_rows_per_keccak_f},
+        KeccakCircuit,
+    },
+    pi_circuit::PiCircuit,
+    receipt_circuit::ReceiptCircuit,
+    state_circuit::StateCircuit,
+    tx_circuit::TxCircuit,
+    util::{log2_ceil, SubCircuit},
+    witness::{block_convert, Block},
+};
+use bus_mapping::circuit_input_builder::{
+    CircuitInputBuilder, CircuitsParams, CircuitsRowCounter, ExecStep,
+};
+use eth_types::Field;
+use std::fmt;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+/// Sub-circuits of the [`SuperCircuit`] that are sized by [`CircuitsParams`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display, EnumIter)]
+pub enum SubCircuitKind {
+    /// EVM Circuit
+    Evm,
+    /// State Circuit
+    State,
+    /// Bytecode Circuit
+    Bytecode,
+    /// Copy Circuit
+    Copy,
+    /// Keccak Circuit
+    Keccak,
+    /// Tx Circuit
+    Tx,
+    /// Exponentiation Circuit
+    Exp,
+    /// Public Input Circuit
+    Pi,
+    /// Receipt Circuit
+    Receipt,
+}
+
+/// Rows used by a sub-circuit to prove a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubCircuitRows {
+    /// The sub-circuit
+    pub kind: SubCircuitKind,
+    /// Rows required by the witness, without padding
+    pub rows: usize,
+    /// Rows assigned once padded to the tightest params
+    pub rows_padded: usize,
+}
+
+/// Row usage of every sub-circuit for a block, with the tightest
+/// [`CircuitsParams`] that fit it.
+#[derive(Clone, Debug)]
+pub struct CircuitCapacity {
+    /// Row usage per sub-circuit
+    pub rows: Vec<SubCircuitRows>,
+    /// Smallest params that can prove the block
+    pub params: CircuitsParams,
+    /// Rows at the end of the circuit that can't be used by any sub-circuit
+    pub unusable_rows: usize,
+}
+
+impl CircuitCapacity {
+    /// Measure the capacity required by the block in a [`CircuitInputBuilder`]
+    /// that has already handled all its transactions.
+    pub fn from_builder<F: Field>(
+        builder: &CircuitInputBuilder,
+    ) -> Result<Self, bus_mapping::Error> {
+        let block = block_convert::<F>(&builder.block, &builder.code_db)?;
+        Ok(Self::from_block(&block))
+    }
+
+    /// Measure the capacity required by a witness block.
+    pub fn from_block<F: Field>(block: &Block<F>) -> Self {
+        let used = |kind| Self::min_num_rows_block(kind, block).0;
+
+        let params = CircuitsParams {
+            max_rws: used(SubCircuitKind::State),
+            max_txs: block.txs.len(),
+            max_calldata: block.txs.iter().map(|tx| tx.call_data.len()).sum(),
+            max_copy_rows: used(SubCircuitKind::Copy),
+            max_exp_steps: (used(SubCircuitKind::Exp) + OFFSET_INCREMENT - 1) / OFFSET_INCREMENT,
+            max_bytecode: used(SubCircuitKind::Bytecode),
+            max_evm_rows: used(SubCircuitKind::Evm),
+            max_keccak_rows: used(SubCircuitKind::Keccak),
+            max_receipt_items: ReceiptCircuit::<F>::num_items(
+                &ReceiptCircuit::<F>::new_from_block(block).receipts,
+            ),
+        };
+
+        let mut padded_block = block.clone();
+        padded_block.circuits_params = params;
+        let rows = SubCircuitKind::iter()
+            .map(|kind| {
+                let (rows, rows_padded) = Self::min_num_rows_block(kind, &padded_block);
+                SubCircuitRows {
+                    kind,
+                    rows,
+                    rows_padded: rows.max(rows_padded),
+                }
+            })
+            .collect();
+
+        Self {
+            rows,
+            params,
+            unusable_rows: SuperCircuit::<F>::unusable_rows(),
+        }
+    }
+
+    fn min_num_rows_block<F: Field>(kind: SubCircuitKind, block: &Block<F>) -> (usize, usize) {
+        match kind {
+            SubCircuitKind::Evm => EvmCircuit::min_num_rows_block(block),
+            SubCircuitKind::State => StateCircuit::min_num_rows_block(block),
+            SubCircuitKind::Bytecode => BytecodeCircuit::min_num_rows_block(block),
+            SubCircuitKind::Copy => CopyCircuit::min_num_rows_block(block),
+            SubCircuitKind::Keccak => KeccakCircuit::min_num_rows_block(block),
+            SubCircuitKind::Tx => TxCircuit::min_num_rows_block(block),
+            SubCircuitKind::Exp => ExpCircuit::min_num_rows_block(block),
+            SubCircuitKind::Pi => PiCircuit::min_num_rows_block(block),
+            SubCircuitKind::Receipt => ReceiptCircuit::min_num_rows_block(block),
+        }
+    }
+
+    /// Maximum number of rows required among all the sub-circuits.
+    pub fn max_rows(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|rows| rows.rows_padded)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Smallest degree of a [`SuperCircuit`] that can prove the block.
+    pub fn min_k(&self) -> u32 {
+        log2_ceil(self.unusable_rows + self.max_rows())
+    }
+
+    /// Return the tightest [`CircuitsParams`] if the block fits in a
+    /// [`SuperCircuit`] of degree `k`, or the sub-circuits that overflow.
+    pub fn params_for_k(&self, k: u32) -> Result<CircuitsParams, CapacityError> {
+        let available = (1usize << k).saturating_sub(self.unusable_rows);
+        let overflows: Vec<_> = self
+            .rows
+            .iter()
+            .filter(|rows| rows.rows_padded > available)
+            .map(|rows| CapacityOverflow {
+                kind: rows.kind,
+                rows: rows.rows_padded,
+                available,
+            })
+            .collect();
+        if overflows.is_empty() {
+            Ok(self.params)
+        } else {
+            Err(CapacityError { k, overflows })
+        }
+    }
+}
+
+/// Row counter of the EVM and Keccak circuits, to check the transactions
+/// included with [`CircuitInputBuilder::try_handle_tx`] against the
+/// [`CircuitsParams`] of the [`SuperCircuit`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SuperCircuitRowCounter {
+    /// Whether the EVM circuit is configured for Taiko
+    pub is_taiko: bool,
+    /// Layout of the EVM circuit steps
+    pub evm_layout: EvmLayout,
+}
+
+impl CircuitsRowCounter for SuperCircuitRowCounter {
+    fn evm_step_rows(&self, step: &ExecStep) -> usize {
+        step.execution_state().get_step_height(EvmCircuitParams {
+            is_taiko: self.is_taiko,
+            layout: self.evm_layout,
+            ..Default::default()
+        })
+    }
+
+    fn keccak_rows(&self, inputs: &[Vec<u8>]) -> usize {
+        // The circuit keeps room for 2 permutations besides the ones of the inputs
+        let keccak_fs: usize = inputs
+            .iter()
+            .map(|input| get_num_keccak_f(input.len()))
+            .sum();
+        (keccak_fs + 2) * get_num_rows_per_keccak_f()
+    }
+}
+
+/// A sub-circuit requiring more rows than available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityOverflow {
+    /// The sub-circuit
+    pub kind: SubCircuitKind,
+    /// Rows required
+    pub rows: usize,
+    /// Rows available at the requested degree
+    pub available: usize,
+}
+
+impl CapacityOverflow {
+    /// Number of rows exceeding the available ones.
+    pub fn excess(&self) -> usize {
+        self.rows - self.available
+    }
+}
+
+/// Error returned when a block doesn't fit in a [`SuperCircuit`] of a given
+/// degree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapacityError {
+    /// Requested degree
+    pub k: u32,
+    /// Sub-circuits that overflow
+    pub overflows: Vec<CapacityOverflow>,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block doesn't fit in k = {}:", self.k)?;
+        for overflow in &self.overflows {
+            write!(
+                f,
+                " {} circuit needs {} rows, {} over the {} available;",
+                overflow.kind,
+                overflow.rows,
+                overflow.excess(),
+                overflow.available
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CapacityError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipt_circuit::BLOOM_BITS;
+    use bus_mapping::{
+        circuit_input_builder::{CircuitLimit, CircuitsUsage},
+        mock::BlockData,
+        Error,
+    };
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
+
+    fn capacity() -> CircuitCapacity {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            SHA3
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        CircuitCapacity::from_builder::<Fr>(&builder).unwrap()
+    }
+
+    #[test]
+    fn tightest_params_fit_min_k() {
+        let capacity = capacity();
+        let params = capacity.params_for_k(capacity.min_k()).unwrap();
+        assert_eq!(params.max_txs, 1);
+        assert_eq!(
+            params.max_rws,
+            capacity
+                .rows
+                .iter()
+                .find(|rows| rows.kind == SubCircuitKind::State)
+                .unwrap()
+                .rows
+        );
+        // One header item for the tx without logs
+        assert_eq!(params.max_receipt_items, 1);
+        let receipt = capacity
+            .rows
+            .iter()
+            .find(|rows| rows.kind == SubCircuitKind::Receipt)
+            .unwrap();
+        assert_eq!(receipt.rows_padded, BLOOM_BITS);
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let capacity = capacity();
+        let k = capacity.min_k() - 1;
+        let err = capacity.params_for_k(k).unwrap_err();
+        let available = (1 << k) - capacity.unusable_rows;
+        assert!(!err.overflows.is_empty());
+        for overflow in err.overflows {
+            assert_eq!(overflow.available, available);
+            assert!(overflow.excess() > 0);
+        }
+    }
+
+    fn block_2tx() -> GethData {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            SHA3
+            STOP
+        };
+        TestContext::<2, 2>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0].from(accs[1].address).to(accs[0].address);
+                txs[1].from(accs[1].address).to(accs[0].address);
+            },
+            |block, _| block,
+        )
+        .unwrap()
+        .into()
+    }
+
+    fn handle_block(block: &GethData) -> CircuitInputBuilder {
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        builder
+    }
+
+    #[test]
+    fn incremental_txs_match_handle_block() {
+        let block = block_2tx();
+        let counter = SuperCircuitRowCounter::default();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let mut prev_usage = CircuitsUsage::default();
+        for (tx, trace) in block.eth_block.transactions.iter().zip(&block.geth_traces) {
+            let usage = builder
+                .try_handle_tx(&block.eth_block, tx, trace, &counter)
+                .unwrap();
+            assert!(usage.rws > prev_usage.rws);
+            assert!(usage.evm_rows > prev_usage.evm_rows);
+            assert_eq!(usage.txs, prev_usage.txs + 1);
+            prev_usage = usage;
+        }
+        builder.finalize_block();
+
+        let expected = handle_block(&block);
+        assert_eq!(builder.block.container, expected.block.container);
+        assert_eq!(
+            format!("{:?}", builder.block.txs),
+            format!("{:?}", expected.block.txs)
+        );
+        // The usage matches the rows measured on the witness
+        let capacity = CircuitCapacity::from_builder::<Fr>(&builder).unwrap();
+        let rows = |kind| {
+            capacity
+                .rows
+                .iter()
+                .find(|rows| rows.kind == kind)
+                .unwrap()
+                .rows
+        };
+        assert_eq!(prev_usage.copy_rows, rows(SubCircuitKind::Copy));
+        assert_eq!(prev_usage.bytecode_bytes, rows(SubCircuitKind::Bytecode));
+    }
+
+    #[test]
+    fn overflowing_tx_is_rolled_back() {
+        let block = block_2tx();
+        let txs = &block.eth_block.transactions;
+        let counter = SuperCircuitRowCounter::default();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let usage = builder
+            .try_handle_tx(&block.eth_block, &txs[0], &block.geth_traces[0], &counter)
+            .unwrap();
+
+        // Leave no room for the rws of the second tx
+        let max_rws = builder.block.circuits_params.max_rws;
+        builder.block.circuits_params.max_rws = usage.rws + 1;
+        let container = builder.block.container.clone();
+        let first_tx = format!("{:?}", builder.block.txs);
+        let err = builder
+            .try_handle_tx(&block.eth_block, &txs[1], &block.geth_traces[1], &counter)
+            .unwrap_err();
+        match err {
+            Error::CircuitsOverflow(overflow) => {
+                assert_eq!(overflow.limits, vec![CircuitLimit::Rws]);
+                assert!(overflow.usage.rws > usage.rws);
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+        assert_eq!(builder.block.container, container);
+        assert_eq!(format!("{:?}", builder.block.txs), first_tx);
+        assert_eq!(builder.circuits_usage(&counter).unwrap(), usage);
+
+        // Once rolled back, the tx can be included with enough room
+        builder.block.circuits_params.max_rws = max_rws;
+        builder
+            .try_handle_tx(&block.eth_block, &txs[1], &block.geth_traces[1], &counter)
+            .unwrap();
+        builder.finalize_block();
+        assert_eq!(
+            builder.block.container,
+            handle_block(&block).block.container
+        );
+    }
+}
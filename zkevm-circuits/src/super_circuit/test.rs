@@ -1,4 +1,5 @@
 pub use super::*;
+use crate::state_circuit::{check_chunk_instances, ChunkedStateCircuit};
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use log::error;
@@ -6,8 +7,9 @@ use mock::{TestContext, MOCK_CHAIN_ID};
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
 use std::collections::HashMap;
+use strum::IntoEnumIterator;
 
-use eth_types::{address, bytecode, geth_types::GethData, Word};
+use eth_types::{address, bytecode, evm_types::ChainSpec, geth_types::GethData, Word, H256};
 
 #[test]
 fn super_circuit_degree() {
@@ -15,6 +17,10 @@ fn super_circuit_degree() {
     let params = SuperCircuitParams {
         max_txs: 1,
         max_calldata: 32,
+        num_blocks: 1,
+        pi_commitment: PiCommitment::Keccak,
+        evm_layout: EvmLayout::default(),
+        chain_spec: ChainSpec::default(),
         mock_randomness: Fr::from(0x100),
     };
     SuperCircuit::configure_with_params(&mut cs, params);
@@ -23,6 +29,21 @@ fn super_circuit_degree() {
     assert!(cs.degree() <= 9);
 }
 
+/// Parameters of the super circuit tests with `max_txs` transactions
+fn circuits_params(max_txs: usize) -> CircuitsParams {
+    CircuitsParams {
+        max_txs,
+        max_calldata: 32,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_receipt_items: 0,
+    }
+}
+
 fn test_super_circuit(block: GethData, circuits_params: CircuitsParams, mock_randomness: Fr) {
     let (k, circuit, instance, _) =
         SuperCircuit::<Fr>::build(block, circuits_params, mock_randomness).unwrap();
@@ -120,6 +141,35 @@ fn block_2tx() -> GethData {
     block
 }
 
+// Split the transactions of `block_2tx` into two consecutive blocks of one tx
+// each, with distinct hashes.  The traces stay valid because the txs don't
+// read the block context.
+fn batch_2blocks() -> Vec<GethData> {
+    let block = block_2tx();
+    let hashes = [
+        H256::from_low_u64_be(0xb10c0),
+        H256::from_low_u64_be(0xb10c1),
+    ];
+    (0..2usize)
+        .map(|i| {
+            let mut data = block.clone();
+            let number = block.eth_block.number.unwrap() + i as u64;
+            let mut tx = block.eth_block.transactions[i].clone();
+            tx.block_number = Some(number);
+            tx.block_hash = Some(hashes[i]);
+            tx.transaction_index = Some(0.into());
+            data.eth_block.number = Some(number);
+            data.eth_block.hash = Some(hashes[i]);
+            data.eth_block.transactions = vec![tx];
+            data.geth_traces = vec![block.geth_traces[i].clone()];
+            if i > 0 {
+                data.eth_block.parent_hash = hashes[i - 1];
+            }
+            data
+        })
+        .collect()
+}
+
 const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
 // High memory usage test.  Run in serial with:
@@ -128,47 +178,116 @@ const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 #[test]
 fn serial_test_super_circuit_1tx_1max_tx() {
     let block = block_1tx();
-    let circuits_params = CircuitsParams {
-        max_txs: 1,
-        max_calldata: 32,
-        max_rws: 256,
-        max_copy_rows: 256,
-        max_exp_steps: 256,
-        max_bytecode: 512,
-        max_evm_rows: 0,
-        max_keccak_rows: 0,
-    };
-    test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
+    test_super_circuit(block, circuits_params(1), Fr::from(TEST_MOCK_RANDOMNESS));
 }
+
 #[ignore]
 #[test]
 fn serial_test_super_circuit_1tx_2max_tx() {
     let block = block_1tx();
-    let circuits_params = CircuitsParams {
-        max_txs: 2,
-        max_calldata: 32,
-        max_rws: 256,
-        max_copy_rows: 256,
-        max_exp_steps: 256,
-        max_bytecode: 512,
-        max_evm_rows: 0,
-        max_keccak_rows: 0,
-    };
-    test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
+    test_super_circuit(block, circuits_params(2), Fr::from(TEST_MOCK_RANDOMNESS));
 }
+
 #[ignore]
 #[test]
 fn serial_test_super_circuit_2tx_2max_tx() {
     let block = block_2tx();
-    let circuits_params = CircuitsParams {
-        max_txs: 2,
-        max_calldata: 32,
-        max_rws: 256,
-        max_copy_rows: 256,
-        max_exp_steps: 256,
-        max_bytecode: 512,
-        max_evm_rows: 0,
-        max_keccak_rows: 0,
-    };
-    test_super_circuit(block, circuits_params, Fr::from(TEST_MOCK_RANDOMNESS));
+    test_super_circuit(block, circuits_params(2), Fr::from(TEST_MOCK_RANDOMNESS));
+}
+
+#[ignore]
+#[test]
+fn serial_test_super_circuit_1tx_keccak_pi() {
+    let block = block_1tx();
+    let block_data = BlockData::new_from_geth_data_with_params(block.clone(), circuits_params(1));
+    let mut builder = block_data.new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    let (k, circuit, instance) =
+        SuperCircuit::<Fr>::build_from_circuit_input_builder_with_pi_commitment(
+            &builder,
+            Fr::from(TEST_MOCK_RANDOMNESS),
+            PiCommitment::Keccak,
+        )
+        .unwrap();
+    // The PI circuit looks up the digest of its raw public inputs in the
+    // keccak table filled by the keccak circuit.
+    let prover = MockProver::run(k, &circuit, instance).unwrap();
+    prover.assert_satisfied_par();
+}
+
+#[ignore]
+#[test]
+fn serial_test_super_circuit_1tx_evm_layouts() {
+    let block = block_1tx();
+    let block_data = BlockData::new_from_geth_data_with_params(block.clone(), circuits_params(1));
+    let mut builder = block_data.new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    for evm_layout in EvmLayout::iter() {
+        let mut block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        block.randomness = Fr::from(TEST_MOCK_RANDOMNESS);
+        block.evm_layout = evm_layout;
+        let (_, rows_needed) = SuperCircuit::min_num_rows_block(&block);
+        let k = log2_ceil(SuperCircuit::<Fr>::unusable_rows() + rows_needed);
+        let circuit = SuperCircuit::new_from_block(&block);
+        assert_eq!(circuit.params().evm_layout, evm_layout);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
+
+#[ignore]
+#[test]
+fn serial_test_super_circuit_2blocks_batch() {
+    let (k, circuit, instance, builder) = SuperCircuit::<Fr>::build_batch(
+        batch_2blocks(),
+        circuits_params(2),
+        Fr::from(TEST_MOCK_RANDOMNESS),
+    )
+    .unwrap();
+    assert_eq!(builder.block.headers.len(), 2);
+    assert_eq!(
+        builder
+            .block
+            .txs()
+            .iter()
+            .map(|tx| tx.block_num)
+            .collect::<Vec<_>>(),
+        vec![0xcafe, 0xcaff]
+    );
+    let public_data = &circuit.pi_circuit.public_data;
+    assert_eq!(public_data.num_blocks(), 2);
+    assert_eq!(
+        public_data.batch_blocks[0].parent_hash,
+        H256::from_low_u64_be(0xb10c0)
+    );
+    assert_eq!(public_data.transactions.len(), 1);
+    assert_eq!(public_data.batch_blocks[0].transactions.len(), 1);
+    let prover = MockProver::run(k, &circuit, instance).unwrap();
+    let res = prover.verify_par();
+    if let Err(err) = res {
+        error!("Verification failures: {:#?}", err);
+        panic!("Failed verification");
+    }
+}
+
+#[ignore]
+#[test]
+fn serial_test_super_circuit_state_chunks() {
+    let mock_randomness = Fr::from(TEST_MOCK_RANDOMNESS);
+    let (_, circuit, _, _) =
+        SuperCircuit::<Fr>::build(block_1tx(), circuits_params(1), mock_randomness).unwrap();
+    let block = circuit.evm_circuit.block.as_ref().unwrap();
+
+    // The chunks of the RwTable of the block link up into its RwTable.
+    let chunked = ChunkedStateCircuit::<Fr>::new(block.rws.clone(), 0, 64).unwrap();
+    let instance = chunked.instance();
+    let prev_state_root = MptUpdates::mock_from(&block.rws.table_assignments()).old_root();
+    assert!(check_chunk_instances(&instance[0], prev_state_root));
+
+    let prover = MockProver::<Fr>::run(17, &chunked, instance).unwrap();
+    assert_eq!(prover.verify_par(), Ok(()));
 }
@@ -23,6 +23,22 @@ pub enum BlockContextFieldTag {
     ChainId,
     /// Treasury accounts for receiving block base fee
     Treasury,
+    /// Number of rw operations of the block proven before this witness, when
+    /// the block is split into chunks
+    RwcOffset,
+    /// Number of txs of the block proven before this witness, when the block
+    /// is split into chunks
+    TxIdOffset,
+    /// Cumulative gas used by the txs of the block proven before this
+    /// witness, when the block is split into chunks
+    CumulativeGasUsedOffset,
+    /// Block rw counter following the rw operations of this witness
+    RwcEnd,
+    /// Number of txs of the block up to the end of this witness
+    TxIdEnd,
+    /// Cumulative gas used by the txs of the block up to the end of this
+    /// witness
+    CumulativeGasUsedEnd,
 }
 impl_expr!(BlockContextFieldTag);
 
@@ -47,13 +63,15 @@ impl BlockTable {
         }
     }
 
-    /// Assign the `BlockTable` from a `BlockContext`.
-    pub fn load<F: Field>(
+    /// Assign the `BlockTable` from the `BlockContext`s of all the blocks in
+    /// the witness.
+    pub fn load<'a, F: Field>(
         &self,
         layouter: &mut impl Layouter<F>,
-        block: &BlockContext,
+        contexts: impl IntoIterator<Item = &'a BlockContext>,
         randomness: Value<F>,
     ) -> Result<(), Error> {
+        let contexts: Vec<_> = contexts.into_iter().collect();
         layouter.assign_region(
             || "block table",
             |mut region| {
@@ -69,7 +87,10 @@ impl BlockTable {
                 offset += 1;
 
                 let block_table_columns = <BlockTable as LookupTable<F>>::advice_columns(self);
-                for row in block.table_assignments(randomness) {
+                for row in contexts
+                    .iter()
+                    .flat_map(|context| context.table_assignments(randomness))
+                {
                     for (&column, value) in block_table_columns.iter().zip_eq(row) {
                         region.assign_advice(
                             || format!("block table row {}", offset),
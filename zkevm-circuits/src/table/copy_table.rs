@@ -1,7 +1,9 @@
 use super::*;
+use crate::evm_circuit::param::N_BYTES_WORD;
 
 type CopyTableRow<F> = [(Value<F>, &'static str); 8];
-type CopyCircuitRow<F> = [(Value<F>, &'static str); 4];
+type CopyCircuitRow<F> = [(Value<F>, &'static str); 3];
+type CopyCircuitWordRow<F> = [[(Value<F>, &'static str); 5]; N_BYTES_WORD];
 
 /// Copy Table, used to verify copies of byte chunks between Memory, Bytecode,
 /// TxLogs and TxCallData.
@@ -15,8 +17,10 @@ pub struct CopyTable {
     /// 2. RLC encoding of bytecode hash for CopyDataType::Bytecode
     /// 3. Transaction ID for CopyDataType::TxCalldata, CopyDataType::TxLog
     pub id: Column<Advice>,
-    /// The source/destination address for this copy step.  Can be memory
-    /// address, byte index in the bytecode, tx call data, and tx log data.
+    /// The source/destination address of the first copied byte of this copy
+    /// step.  Can be memory address, byte index in the bytecode, tx call data,
+    /// and tx log data.  Fully masked steps keep the address of the next
+    /// copied byte.
     pub addr: Column<Advice>,
     /// The end of the source buffer for the copy event.  Any data read from an
     /// address greater than or equal to this value will be 0.
@@ -54,12 +58,22 @@ impl CopyTable {
         }
     }
 
-    /// Generate the copy table and copy circuit assignments from a copy event.
+    /// Generate the copy table and copy circuit assignments from a copy event:
+    /// the copy table row, the copy circuit row and the copy circuit bytes of
+    /// the word of every row.
+    #[allow(clippy::type_complexity)]
     pub fn assignments<F: Field>(
         copy_event: &CopyEvent,
         challenges: Challenges<Value<F>>,
-    ) -> Vec<(CopyDataType, CopyTableRow<F>, CopyCircuitRow<F>)> {
+    ) -> Vec<(
+        CopyDataType,
+        CopyTableRow<F>,
+        CopyCircuitRow<F>,
+        CopyCircuitWordRow<F>,
+    )> {
         let mut assignments = Vec::new();
+        let num_steps = copy_event.num_steps();
+        let length = copy_event.bytes.len();
         // rlc_acc
         let rlc_acc = if copy_event.dst_type == CopyDataType::RlcAcc {
             let values = copy_event
@@ -73,35 +87,18 @@ impl CopyTable {
         } else {
             Value::known(F::ZERO)
         };
-        let mut value_acc = Value::known(F::ZERO);
-        for (step_idx, (is_read_step, copy_step)) in copy_event
-            .bytes
-            .iter()
-            .flat_map(|(value, is_code)| {
-                let read_step = CopyStep {
-                    value: *value,
-                    is_code: if copy_event.src_type == CopyDataType::Bytecode {
-                        Some(*is_code)
-                    } else {
-                        None
-                    },
-                };
-                let write_step = CopyStep {
-                    value: *value,
-                    is_code: if copy_event.dst_type == CopyDataType::Bytecode {
-                        Some(*is_code)
-                    } else {
-                        None
-                    },
-                };
-                once((true, read_step)).chain(once((false, write_step)))
-            })
-            .enumerate()
-        {
+        let dst_memory = copy_event.dst_memory();
+        // Accumulators of the read side (index 0) and the write side (index 1).
+        let mut value_acc = [Value::known(F::ZERO); 2];
+        for step_idx in 0..num_steps * 2 {
+            let is_read_step = step_idx % 2 == 0;
+            let side = step_idx % 2;
+            let step = step_idx / 2;
+
             // is_first
             let is_first = Value::known(if step_idx == 0 { F::ONE } else { F::ZERO });
             // is last
-            let is_last = if step_idx == copy_event.bytes.len() * 2 - 1 {
+            let is_last = if step_idx == num_steps * 2 - 1 {
                 Value::known(F::ONE)
             } else {
                 Value::known(F::ZERO)
@@ -121,14 +118,28 @@ impl CopyTable {
                 copy_event.dst_type
             };
 
-            // addr
-            let copy_step_addr: u64 =
-                if is_read_step {
-                    copy_event.src_addr
-                } else {
-                    copy_event.dst_addr
-                } + (u64::try_from(step_idx).unwrap() - if is_read_step { 0 } else { 1 }) / 2u64;
+            // the bytes of the word that are copied, and the number of bytes
+            // copied by this side before this step
+            let (start_addr, front_len, side_steps, data_range, bytes_done) = if is_read_step {
+                (
+                    copy_event.src_addr,
+                    copy_event.src_front_len(),
+                    copy_event.src_num_steps(),
+                    copy_event.src_data_range(step),
+                    copy_event.src_bytes_done(step),
+                )
+            } else {
+                (
+                    copy_event.dst_addr,
+                    copy_event.dst_front_len(),
+                    copy_event.dst_num_steps(),
+                    copy_event.dst_data_range(step),
+                    copy_event.dst_bytes_done(step),
+                )
+            };
 
+            // addr
+            let copy_step_addr = start_addr + bytes_done as u64;
             let addr = if tag == CopyDataType::TxLog {
                 Value::known(
                     build_tx_log_address(
@@ -144,26 +155,59 @@ impl CopyTable {
             };
 
             // bytes_left
-            let bytes_left = u64::try_from(copy_event.bytes.len() * 2 - step_idx).unwrap() / 2;
-            // value
-            let value = if copy_event.dst_type == CopyDataType::RlcAcc {
-                if is_read_step {
-                    Value::known(F::from(copy_step.value as u64))
-                } else {
-                    value_acc = value_acc * challenges.keccak_input()
-                        + Value::known(F::from(copy_step.value as u64));
-                    value_acc
-                }
+            let bytes_left = (length - bytes_done) as u64;
+
+            // word_num
+            let word_num = if tag == CopyDataType::Memory {
+                (start_addr - front_len as u64) / N_BYTES_WORD as u64 + step as u64
             } else {
-                Value::known(F::from(copy_step.value as u64))
+                0
             };
-            // is_pad
-            let is_pad = Value::known(F::from(
-                (is_read_step && copy_step_addr >= copy_event.src_addr_end) as u64,
-            ));
 
-            // is_code
-            let is_code = Value::known(copy_step.is_code.map_or(F::ZERO, |v| F::from(v as u64)));
+            let bool_value = |value: bool| Value::known(F::from(value as u64));
+            let bytes = array::from_fn(|index| {
+                let is_data = data_range.contains(&index);
+                // index of the copied byte in the copy event
+                let byte_idx = bytes_done + index - data_range.start.min(index);
+                // value, value_prev
+                let word_idx = step * N_BYTES_WORD + index;
+                let (value, value_prev) = match tag {
+                    CopyDataType::Memory if step >= side_steps => (0, 0),
+                    CopyDataType::Memory if is_read_step => (
+                        copy_event.src_memory[word_idx],
+                        copy_event.src_memory[word_idx],
+                    ),
+                    CopyDataType::Memory => {
+                        (dst_memory[word_idx], copy_event.dst_memory_prev[word_idx])
+                    }
+                    _ if is_data => (copy_event.bytes[byte_idx].0, copy_event.bytes[byte_idx].0),
+                    _ => (0, 0),
+                };
+                // is_pad
+                let is_pad = is_read_step
+                    && is_data
+                    && start_addr + byte_idx as u64 >= copy_event.src_addr_end;
+                [
+                    (Value::known(F::from(value as u64)), "value"),
+                    (Value::known(F::from(value_prev as u64)), "value_prev"),
+                    (bool_value(is_pad), "is_pad"),
+                    (bool_value(index < data_range.start), "is_front_mask"),
+                    (bool_value(index >= data_range.end), "is_back_mask"),
+                ]
+            });
+
+            // value_acc
+            for index in data_range.clone() {
+                let byte_idx = bytes_done + index - data_range.start;
+                let copied =
+                    if is_read_step && start_addr + byte_idx as u64 >= copy_event.src_addr_end {
+                        0
+                    } else {
+                        copy_event.bytes[byte_idx].0
+                    };
+                value_acc[side] = value_acc[side] * challenges.keccak_input()
+                    + Value::known(F::from(copied as u64));
+            }
 
             assignments.push((
                 tag,
@@ -188,10 +232,10 @@ impl CopyTable {
                 ],
                 [
                     (is_last, "is_last"),
-                    (value, "value"),
-                    (is_pad, "is_pad"),
-                    (is_code, "is_code"),
+                    (Value::known(F::from(word_num)), "word_num"),
+                    (value_acc[side], "value_acc"),
                 ],
+                bytes,
             ));
         }
         assignments
@@ -221,7 +265,7 @@ impl CopyTable {
                 let tag_chip = BinaryNumberChip::construct(self.tag);
                 let copy_table_columns = <CopyTable as LookupTable<F>>::advice_columns(self);
                 for copy_event in block.copy_events.iter() {
-                    for (tag, row, _) in Self::assignments(copy_event, *challenges) {
+                    for (tag, row, _, _) in Self::assignments(copy_event, *challenges) {
                         for (&column, (value, label)) in copy_table_columns.iter().zip_eq(row) {
                             region.assign_advice(
                                 || format!("{} at row: {}", label, offset),
@@ -127,6 +127,16 @@ impl RwTable {
         challenges: Value<F>,
     ) -> Result<(), Error> {
         let (rows, _) = RwMap::table_assignments_prepad(rws, n_rows);
+        self.load_rows_with_region(region, &rows, challenges)
+    }
+
+    /// Assign rows already laid out in the RwTable, one per offset.
+    pub(crate) fn load_rows_with_region<F: Field>(
+        &self,
+        region: &mut Region<'_, F>,
+        rows: &[Rw],
+        challenges: Value<F>,
+    ) -> Result<(), Error> {
         for (offset, row) in rows.iter().enumerate() {
             self.assign(region, offset, &row.table_assignment(challenges))?;
         }
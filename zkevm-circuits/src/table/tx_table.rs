@@ -39,6 +39,9 @@ pub enum TxFieldTag {
     SigR,
     /// Signature field S.
     SigS,
+    /// Number of the block which includes the transaction, used to tell
+    /// apart the transactions of each block when proving a batch.
+    BlockNumber,
 }
 impl_expr!(TxFieldTag);
 
@@ -51,6 +54,10 @@ pub enum TxLogFieldTag {
     Topic,
     /// Data field
     Data,
+    /// Number of topics
+    TopicLength,
+    /// Byte length of data
+    DataLength,
 }
 impl_expr!(TxLogFieldTag);
 
@@ -13,7 +13,7 @@ use crate::bytecode_circuit::circuit::{
 #[cfg(feature = "for-a7")]
 use crate::copy_circuit::{CopyCircuit, CopyCircuitConfig, CopyCircuitConfigArgs};
 #[cfg(feature = "for-a7")]
-use crate::evm_circuit::{EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs};
+use crate::evm_circuit::{param::EvmLayout, EvmCircuit, EvmCircuitConfig, EvmCircuitConfigArgs};
 #[cfg(feature = "for-a7")]
 use crate::exp_circuit::{ExpCircuit, ExpCircuitConfig};
 #[cfg(feature = "for-a7")]
@@ -35,7 +35,7 @@ use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams, ProtocolInstance},
     mock::BlockData,
 };
-use eth_types::{geth_types::GethData, Field};
+use eth_types::{evm_types::ChainSpec, geth_types::GethData, Field};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     plonk::{Circuit, ConstraintSystem, Error, Expression},
@@ -85,6 +85,8 @@ pub struct SuperCircuitConfig<F: Field> {
 pub struct SuperCircuitConfigArgs<F: Field> {
     /// Challenges expressions
     pub challenges: Challenges<Expression<F>>,
+    /// Hardfork rules of the proven block
+    pub chain_spec: ChainSpec,
 }
 
 impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
@@ -93,8 +95,13 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
     /// Configure SuperCircuitConfig
     fn new(
         meta: &mut ConstraintSystem<F>,
-        Self::ConfigArgs { challenges }: Self::ConfigArgs,
+        Self::ConfigArgs {
+            challenges,
+            chain_spec,
+        }: Self::ConfigArgs,
     ) -> Self {
+        #[cfg(not(feature = "for-a7"))]
+        let _ = chain_spec;
         #[cfg(feature = "for-a7")]
         let tx_table = TxTable::construct(meta);
         #[cfg(feature = "for-a7")]
@@ -150,6 +157,8 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                 keccak_table: keccak_table.clone(),
                 exp_table,
                 is_taiko: true,
+                chain_spec,
+                layout: EvmLayout::default(),
             },
         );
 
@@ -178,6 +187,7 @@ impl<F: Field> SubCircuitConfig<F> for SuperCircuitConfig<F> {
                     rw_table,
                     mpt_table,
                     challenges: challenges.clone(),
+                    chunk_instance: None,
                 },
             );
 
@@ -405,20 +415,20 @@ impl<F: Field> SubCircuit<F> for SuperCircuit<F> {
 impl<F: Field> Circuit<F> for SuperCircuit<F> {
     type Config = (SuperCircuitConfig<F>, Challenges);
     type FloorPlanner = SimpleFloorPlanner;
-    type Params = ();
+    type Params = ChainSpec;
 
     fn without_witnesses(&self) -> Self {
         Self::default()
     }
 
+    fn params(&self) -> Self::Params {
+        self.block.chain_spec
+    }
+
     fn configure_with_params(
         meta: &mut ConstraintSystem<F>,
-        _params: Self::Params,
+        chain_spec: Self::Params,
     ) -> Self::Config {
-        Self::configure(meta)
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let challenges = Challenges::construct(meta);
         let challenge_exprs = challenges.exprs(meta);
         (
@@ -426,12 +436,17 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
                 meta,
                 SuperCircuitConfigArgs {
                     challenges: challenge_exprs,
+                    chain_spec,
                 },
             ),
             challenges,
         )
     }
 
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure_with_params(meta, ChainSpec::default())
+    }
+
     fn synthesize(
         &self,
         (config, challenges): Self::Config,
@@ -441,7 +456,7 @@ impl<F: Field> Circuit<F> for SuperCircuit<F> {
         let randomness = challenges.evm_word();
         config
             .block_table
-            .load(&mut layouter, &self.block.context, randomness)?;
+            .load(&mut layouter, self.block.contexts(), randomness)?;
         config.keccak_table.dev_load(
             &mut layouter,
             self.block
@@ -169,6 +169,7 @@ fn taiko_serial_test_super_circuit_1tx_3max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        max_receipt_items: 0,
     };
     test_super_circuit(block, protocol_instance, circuits_params);
 }
@@ -186,6 +187,7 @@ fn taiko_serial_test_super_circuit_2tx_3max_tx() {
         max_bytecode: 512,
         max_evm_rows: 0,
         max_keccak_rows: 0,
+        max_receipt_items: 0,
     };
     test_super_circuit(block, protocol_instance, circuits_params);
 }
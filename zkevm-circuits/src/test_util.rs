@@ -1,8 +1,15 @@
 //! Testing utilities
 
 use crate::{
+    bytecode_circuit::circuit::BytecodeCircuit,
+    copy_circuit::CopyCircuit,
     evm_circuit::{cached::EvmCircuitCached, EvmCircuit},
+    exp_circuit::ExpCircuit,
+    keccak_circuit::KeccakCircuit,
+    pi_circuit::PiCircuit,
     state_circuit::StateCircuit,
+    taiko_super_circuit::SuperCircuit as TaikoSuperCircuit,
+    tx_circuit::TxCircuit,
     util::SubCircuit,
     witness::{Block, Rw},
 };
@@ -11,12 +18,18 @@ use bus_mapping::{
     mock::BlockData,
 };
 use eth_types::geth_types::GethData;
-use std::cmp;
+use std::{cmp, collections::HashMap};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
 
 use crate::util::log2_ceil;
-use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
 use mock::TestContext;
 
+pub mod fuzz;
+pub mod soundness;
+pub mod trace_inspector;
+
 #[cfg(test)]
 #[ctor::ctor]
 fn init_env_logger() {
@@ -26,13 +39,52 @@ fn init_env_logger() {
 
 const NUM_BLINDING_ROWS: usize = 64;
 
+/// Circuits that the [`CircuitTestBuilder`] can verify a block with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Display, EnumIter)]
+pub enum TestCircuit {
+    /// EVM Circuit
+    Evm,
+    /// State Circuit
+    State,
+    /// Bytecode Circuit
+    Bytecode,
+    /// Copy Circuit
+    Copy,
+    /// Keccak Circuit
+    Keccak,
+    /// Tx Circuit
+    Tx,
+    /// Exponentiation Circuit
+    Exp,
+    /// Public Input Circuit
+    Pi,
+    /// Taiko Super Circuit, with the Taiko Public Input Circuit
+    TaikoSuper,
+}
+
+impl TestCircuit {
+    /// Circuits run by default on a block: every sub-circuit, with the Taiko
+    /// Super Circuit in place of the Public Input Circuit for Taiko blocks.
+    pub fn defaults(is_taiko: bool) -> Vec<Self> {
+        Self::iter()
+            .filter(|circuit| match circuit {
+                Self::Pi => !is_taiko,
+                Self::TaikoSuper => is_taiko,
+                _ => true,
+            })
+            .collect()
+    }
+}
+
 #[allow(clippy::type_complexity)]
-/// Struct used to easily generate tests for EVM &| State circuits being able to
+/// Struct used to easily generate tests for the circuits being able to
 /// customize all of the steps involved in the testing itself.
 ///
-/// By default, the tests run through `prover.assert_satisfied_par()` but the
-/// builder pattern provides functions that allow to pass different functions
-/// that the prover should execute when verifying the CTB correctness.
+/// By default, the block is verified by every circuit of
+/// [`TestCircuit::defaults`] through `prover.assert_satisfied_at_rows_par()`,
+/// but the builder pattern provides functions that allow to select the
+/// circuits and to pass different functions that the prover should execute
+/// when verifying the CTB correctness.
 ///
 /// The CTB also includes a mechanism to recieve calls that will modify the
 /// block produced from the [`TestContext`] and apply them before starting to
@@ -81,8 +133,8 @@ pub struct CircuitTestBuilder<const NACC: usize, const NTX: usize> {
     test_ctx: Option<TestContext<NACC, NTX>>,
     circuits_params: Option<CircuitsParams>,
     block: Option<Block<Fr>>,
-    evm_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
-    state_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
+    circuits: Option<Vec<TestCircuit>>,
+    checks: HashMap<TestCircuit, Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>>,
     block_modifiers: Vec<Box<dyn Fn(&mut Block<Fr>)>>,
 }
 
@@ -93,18 +145,8 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             test_ctx: None,
             circuits_params: None,
             block: None,
-            evm_checks: Box::new(|prover, gate_rows, lookup_rows| {
-                prover.assert_satisfied_at_rows_par(
-                    gate_rows.iter().cloned(),
-                    lookup_rows.iter().cloned(),
-                )
-            }),
-            state_checks: Box::new(|prover, gate_rows, lookup_rows| {
-                prover.assert_satisfied_at_rows_par(
-                    gate_rows.iter().cloned(),
-                    lookup_rows.iter().cloned(),
-                )
-            }),
+            circuits: None,
+            checks: HashMap::new(),
             block_modifiers: vec![],
         }
     }
@@ -145,26 +187,47 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         self
     }
 
+    /// Allows to select the circuits that verify the block instead of the
+    /// [`TestCircuit::defaults`].
+    pub fn circuits(mut self, circuits: &[TestCircuit]) -> Self {
+        self.circuits = Some(circuits.to_vec());
+        self
+    }
+
+    #[allow(clippy::type_complexity)]
+    /// Allows to provide checks different than the default ones for the
+    /// verification of `circuit`.
+    ///
+    /// The checks receive the gate and lookup rows to verify.  Only the active
+    /// rows are passed for the EVM and State circuits, and all the usable rows
+    /// for the other circuits.
+    pub fn circuit_checks(
+        mut self,
+        circuit: TestCircuit,
+        checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
+    ) -> Self {
+        self.checks.insert(circuit, checks);
+        self
+    }
+
     #[allow(clippy::type_complexity)]
     /// Allows to provide checks different than the default ones for the State
     /// Circuit verification.
     pub fn state_checks(
-        mut self,
+        self,
         state_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     ) -> Self {
-        self.state_checks = state_checks;
-        self
+        self.circuit_checks(TestCircuit::State, state_checks)
     }
 
     #[allow(clippy::type_complexity)]
     /// Allows to provide checks different than the default ones for the EVM
     /// Circuit verification.
     pub fn evm_checks(
-        mut self,
+        self,
         evm_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     ) -> Self {
-        self.evm_checks = evm_checks;
-        self
+        self.circuit_checks(TestCircuit::Evm, evm_checks)
     }
 
     #[allow(clippy::type_complexity)]
@@ -182,7 +245,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
     /// Triggers the `CircuitTestBuilder` to convert the [`TestContext`] if any,
     /// into a [`Block`] and apply the default or provided block_modifiers or
-    /// circuit checks to the provers generated for the State and EVM circuits.
+    /// circuit checks to the provers generated for the selected circuits.
     pub fn run(self) {
         let params = if let Some(block) = self.block.as_ref() {
             block.circuits_params
@@ -216,36 +279,79 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             panic!("No attribute to build a block was passed to the CircuitTestBuilder")
         };
 
-        // Run evm circuit test
-        {
-            let k = block.get_test_degree();
-
-            let (active_gate_rows, active_lookup_rows) = EvmCircuit::<Fr>::get_active_rows(&block);
+        let circuits = self
+            .circuits
+            .unwrap_or_else(|| TestCircuit::defaults(block.protocol_instance.is_some()));
+        for circuit in circuits {
+            log::debug!("verify block with the {} circuit", circuit);
+            let (prover, gate_rows, lookup_rows) = match circuit {
+                TestCircuit::Evm => {
+                    let k = block.get_test_degree();
 
-            let circuit = EvmCircuitCached::get_test_cicuit_from_block(block.clone());
-            let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+                    let (active_gate_rows, active_lookup_rows) =
+                        EvmCircuit::<Fr>::get_active_rows(&block);
 
-            self.evm_checks.as_ref()(prover, &active_gate_rows, &active_lookup_rows)
-        }
+                    let circuit = EvmCircuitCached::get_test_cicuit_from_block(block.clone());
+                    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+                    (prover, active_gate_rows, active_lookup_rows)
+                }
+                // TODO: use randomness as one of the circuit public input, since randomness in
+                // state circuit and evm circuit must be same
+                TestCircuit::State => {
+                    let rows_needed = StateCircuit::<Fr>::min_num_rows_block(&block).1;
+                    let k = cmp::max(log2_ceil(rows_needed + NUM_BLINDING_ROWS), 18);
+                    let state_circuit = StateCircuit::<Fr>::new(block.rws.clone(), params.max_rws);
+                    let instance = state_circuit.instance();
+                    let prover = MockProver::<Fr>::run(k, &state_circuit, instance).unwrap();
+                    // Skip verification of Start rows to accelerate testing
+                    let non_start_rows_len = state_circuit
+                        .rows
+                        .iter()
+                        .filter(|rw| !matches!(rw, Rw::Start { .. }))
+                        .count();
+                    let rows: Vec<usize> =
+                        (params.max_rws - non_start_rows_len..params.max_rws).collect();
+                    (prover, rows.clone(), rows)
+                }
+                TestCircuit::Bytecode => run_sub_circuit::<BytecodeCircuit<Fr>>(&block),
+                TestCircuit::Copy => run_sub_circuit::<CopyCircuit<Fr>>(&block),
+                TestCircuit::Keccak => run_sub_circuit::<KeccakCircuit<Fr>>(&block),
+                TestCircuit::Tx => run_sub_circuit::<TxCircuit<Fr>>(&block),
+                TestCircuit::Exp => run_sub_circuit::<ExpCircuit<Fr>>(&block),
+                TestCircuit::Pi => run_sub_circuit::<PiCircuit<Fr>>(&block),
+                TestCircuit::TaikoSuper => run_sub_circuit::<TaikoSuperCircuit<Fr>>(&block),
+            };
 
-        // Run state circuit test
-        // TODO: use randomness as one of the circuit public input, since randomness in
-        // state circuit and evm circuit must be same
-        {
-            let rows_needed = StateCircuit::<Fr>::min_num_rows_block(&block).1;
-            let k = cmp::max(log2_ceil(rows_needed + NUM_BLINDING_ROWS), 18);
-            let state_circuit = StateCircuit::<Fr>::new(block.rws, params.max_rws);
-            let instance = state_circuit.instance();
-            let prover = MockProver::<Fr>::run(k, &state_circuit, instance).unwrap();
-            // Skip verification of Start rows to accelerate testing
-            let non_start_rows_len = state_circuit
-                .rows
-                .iter()
-                .filter(|rw| !matches!(rw, Rw::Start { .. }))
-                .count();
-            let rows = (params.max_rws - non_start_rows_len..params.max_rws).collect();
-
-            self.state_checks.as_ref()(prover, &rows, &rows);
+            match self.checks.get(&circuit) {
+                Some(checks) => checks.as_ref()(prover, &gate_rows, &lookup_rows),
+                None => prover.assert_satisfied_at_rows_par(
+                    gate_rows.iter().cloned(),
+                    lookup_rows.iter().cloned(),
+                ),
+            }
         }
     }
 }
+
+/// Run the [`MockProver`] of the circuit `C` on the block, and return it with
+/// the usable rows of the circuit as gate and lookup rows.
+fn run_sub_circuit<C: SubCircuit<Fr> + Circuit<Fr>>(
+    block: &Block<Fr>,
+) -> (MockProver<Fr>, Vec<usize>, Vec<usize>) {
+    let k = sub_circuit_degree::<C>(block);
+    let circuit = C::new_from_block(block);
+    let prover = MockProver::<Fr>::run(k, &circuit, circuit.instance()).unwrap();
+    let usable_rows: Vec<usize> = (0..(1 << k) - C::unusable_rows()).collect();
+    (prover, usable_rows.clone(), usable_rows)
+}
+
+/// Degree of the circuit `C` fitting the block.
+pub(crate) fn sub_circuit_degree<C: SubCircuit<Fr>>(block: &Block<Fr>) -> u32 {
+    let (rows, padded_rows) = C::min_num_rows_block(block);
+    // The circuits also load the tables they look up to, which are sized
+    // like the ones of the EVM circuit.
+    cmp::max(
+        block.get_test_degree(),
+        log2_ceil(C::unusable_rows() + cmp::max(rows, padded_rows)),
+    )
+}
@@ -0,0 +1,171 @@
+//! Harness running the call trees generated by [`mock::program`] through the
+//! circuits.
+//!
+//! Failing cases are shrunk by proptest, and their seeds are saved under
+//! `proptest-regressions/` so that they are replayed first on the next runs.
+//! Commit these files to keep the failures as regression fixtures.
+
+use crate::{
+    copy_circuit::CopyCircuit,
+    evm_circuit::{cached::EvmCircuitCached, EvmCircuit},
+    state_circuit::StateCircuit,
+    super_circuit::capacity::SuperCircuitRowCounter,
+    util::{log2_ceil, SubCircuit},
+    witness::{block_convert, Block, Rw},
+};
+use bus_mapping::{
+    circuit_input_builder::{CircuitsOverflow, CircuitsParams},
+    mock::BlockData,
+};
+use halo2_proofs::{
+    dev::{MockProver, VerifyFailure},
+    halo2curves::bn256::Fr,
+};
+use mock::program::CallTree;
+use std::cmp;
+
+/// Circuit parameters the generated call trees are checked with.
+pub const FUZZ_PARAMS: CircuitsParams = CircuitsParams {
+    max_rws: 1 << 13,
+    max_txs: 1,
+    max_calldata: 0x40,
+    max_copy_rows: 1 << 14,
+    max_exp_steps: 1 << 10,
+    max_bytecode: 1 << 13,
+    max_evm_rows: 0,
+    max_keccak_rows: 0,
+    max_receipt_items: 0,
+};
+
+/// Reason why a call tree isn't verified by the circuits.
+#[derive(Debug)]
+pub enum CallTreeError {
+    /// The call tree doesn't fit in the circuit parameters
+    Overflow(Box<CircuitsOverflow>),
+    /// The tracer, the witness generation or a circuit failed
+    Failure(String),
+}
+
+/// Trace `tree`, generate its witness and verify it with the EVM, State and
+/// Copy circuits.
+pub fn check_call_tree(tree: &CallTree, params: CircuitsParams) -> Result<(), CallTreeError> {
+    let failure = |stage: &str, err: &dyn std::fmt::Debug| {
+        CallTreeError::Failure(format!("{}: {:?}", stage, err))
+    };
+
+    let block = tree.build().map_err(|err| failure("tracer", &err))?;
+    let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), params)
+        .new_circuit_input_builder();
+    for (tx, trace) in block.eth_block.transactions.iter().zip(&block.geth_traces) {
+        builder
+            .try_handle_tx(
+                &block.eth_block,
+                tx,
+                trace,
+                &SuperCircuitRowCounter::default(),
+            )
+            .map_err(|err| match err {
+                bus_mapping::Error::CircuitsOverflow(overflow) => CallTreeError::Overflow(overflow),
+                err => failure("circuit input builder", &err),
+            })?;
+    }
+    builder.finalize_block();
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db)
+        .map_err(|err| failure("witness", &err))?;
+
+    verify_evm(&block).map_err(|err| failure("evm circuit", &err))?;
+    verify_state(&block).map_err(|err| failure("state circuit", &err))?;
+    verify_copy(&block).map_err(|err| failure("copy circuit", &err))?;
+    Ok(())
+}
+
+fn verify_evm(block: &Block<Fr>) -> Result<(), Vec<VerifyFailure>> {
+    let k = block.get_test_degree();
+    let (active_gate_rows, active_lookup_rows) = EvmCircuit::<Fr>::get_active_rows(block);
+    let circuit = EvmCircuitCached::get_test_cicuit_from_block(block.clone());
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+    prover.verify_at_rows_par(active_gate_rows.into_iter(), active_lookup_rows.into_iter())
+}
+
+fn verify_state(block: &Block<Fr>) -> Result<(), Vec<VerifyFailure>> {
+    let max_rws = block.circuits_params.max_rws;
+    let k = cmp::max(log2_ceil(max_rws + StateCircuit::<Fr>::unusable_rows()), 18);
+    let circuit = StateCircuit::<Fr>::new(block.rws.clone(), max_rws);
+    let instance = circuit.instance();
+    let prover = MockProver::<Fr>::run(k, &circuit, instance).unwrap();
+    // Skip verification of Start rows to accelerate testing
+    let non_start_rows_len = circuit
+        .rows
+        .iter()
+        .filter(|rw| !matches!(rw, Rw::Start { .. }))
+        .count();
+    let rows: Vec<usize> = (max_rws - non_start_rows_len..max_rws).collect();
+    prover.verify_at_rows_par(rows.iter().cloned(), rows.iter().cloned())
+}
+
+fn verify_copy(block: &Block<Fr>) -> Result<(), Vec<VerifyFailure>> {
+    let k = cmp::max(
+        block.get_test_degree(),
+        log2_ceil(block.circuits_params.max_copy_rows + CopyCircuit::<Fr>::unusable_rows()),
+    );
+    let circuit = CopyCircuit::<Fr>::new_from_block(block);
+    let prover = MockProver::<Fr>::run(k, &circuit, circuit.instance()).unwrap();
+    prover.verify_par()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::program::{arb_call_tree, ProgramConfig};
+    use proptest::{prelude::*, test_runner::TestCaseError};
+
+    /// Parameters fitting the call trees of [`small_config`], to keep the
+    /// circuits small.
+    const SMALL_FUZZ_PARAMS: CircuitsParams = CircuitsParams {
+        max_rws: 1 << 10,
+        max_copy_rows: 1 << 10,
+        max_bytecode: 1 << 10,
+        ..FUZZ_PARAMS
+    };
+
+    fn small_config() -> ProgramConfig {
+        ProgramConfig {
+            max_contracts: 2,
+            max_ops: 4,
+            max_memory: 0x40,
+        }
+    }
+
+    fn check(tree: &CallTree, params: CircuitsParams) -> Result<(), TestCaseError> {
+        match check_call_tree(tree, params) {
+            Ok(()) => Ok(()),
+            Err(CallTreeError::Overflow(overflow)) => {
+                Err(TestCaseError::reject(format!("{:?}", overflow.limits)))
+            }
+            Err(CallTreeError::Failure(failure)) => Err(TestCaseError::fail(failure)),
+        }
+    }
+
+    proptest! {
+        // A couple of small call trees, checked on every run.
+        #![proptest_config(ProptestConfig::with_cases(2))]
+
+        #[test]
+        fn fuzz_small_call_trees(tree in arb_call_tree(small_config())) {
+            check(&tree, SMALL_FUZZ_PARAMS)?;
+        }
+    }
+
+    proptest! {
+        // Each case runs several MockProvers, keep the default run short.
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        // High memory usage test.  Run in serial with:
+        // `cargo test [...] serial_ -- --ignored --test-threads 1`
+        #[ignore]
+        #[test]
+        fn serial_fuzz_call_trees(tree in arb_call_tree(ProgramConfig::default())) {
+            check(&tree, FUZZ_PARAMS)?;
+        }
+    }
+}
@@ -0,0 +1,520 @@
+//! Witness mutation harness checking that the circuits reject tampered
+//! witnesses.
+//!
+//! A [`SubCircuit`] is synthesized as usual, then some of its advice cells are
+//! overwritten before the [`MockProver`] checks the constraints.  The cells
+//! are named by the annotation given to their column with `name_column`, or
+//! by `advice[<index>]` for the columns without annotation.
+//!
+//! [`mutation_campaign`] overwrites every assigned cell of a range of rows in
+//! turn and reports the mutations that are still accepted by the circuit,
+//! which point to under-constrained witnesses.
+
+use super::sub_circuit_degree;
+use crate::{util::SubCircuit, witness::Block};
+use halo2_proofs::{
+    arithmetic::Field as Halo2Field,
+    circuit::{layouter::SyncDeps, Layouter, Value},
+    dev::{CellValue, MockProver},
+    halo2curves::{bn256::Fr, ff::PrimeField},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    marker::PhantomData,
+    ops::Range,
+};
+
+/// Value written to an advice cell after the synthesis of the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdviceOverride {
+    /// Annotation of the column, or `advice[<index>]`
+    pub column: String,
+    /// Row of the cell
+    pub row: usize,
+    /// Value written to the cell
+    pub value: Fr,
+}
+
+impl AdviceOverride {
+    /// Override the cell of `column` at `row` with `value`.
+    pub fn new(column: impl Into<String>, row: usize, value: Fr) -> Self {
+        Self {
+            column: column.into(),
+            row,
+            value,
+        }
+    }
+}
+
+/// Mutation of an assigned cell that the circuit still accepts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcceptedMutation {
+    /// The mutated cell and its new value
+    pub cell: AdviceOverride,
+    /// Value assigned to the cell by the circuit
+    pub original: Fr,
+}
+
+impl fmt::Display for AcceptedMutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {:?} -> {:?}",
+            self.cell.column, self.cell.row, self.original, self.cell.value
+        )
+    }
+}
+
+/// Names of the advice columns of a circuit, indexed by column index.
+#[derive(Clone, Debug, Default)]
+pub struct AdviceColumnNames(BTreeMap<usize, String>);
+
+impl AdviceColumnNames {
+    /// Name of the advice column with index `index`.
+    pub fn name(&self, index: usize) -> String {
+        self.0
+            .get(&index)
+            .cloned()
+            .unwrap_or_else(|| format!("advice[{}]", index))
+    }
+}
+
+/// Synthesize the circuit `C` of the block, overwrite the `overrides` cells
+/// and return the [`MockProver`] along with the names of the advice columns.
+///
+/// Panics if an override names an advice column that the circuit doesn't
+/// annotate or assign.
+pub fn run_with_overrides<C: SubCircuit<Fr> + Circuit<Fr>>(
+    block: &Block<Fr>,
+    overrides: Vec<AdviceOverride>,
+) -> (MockProver<Fr>, AdviceColumnNames) {
+    let k = sub_circuit_degree::<C>(block);
+    let circuit = MutatedCircuit(C::new_from_block(block));
+    let instance = circuit.0.instance();
+
+    MUTATION.with(|mutation| *mutation.borrow_mut() = Some(Mutation::new(overrides)));
+    let prover = MockProver::<Fr>::run(k, &circuit, instance);
+    let mutation = MUTATION.with(|mutation| mutation.borrow_mut().take().unwrap());
+    let prover = prover.unwrap();
+
+    let missing: Vec<_> = mutation
+        .overrides
+        .iter()
+        .filter(|o| mutation.column(&o.column).is_none())
+        .map(|o| o.column.as_str())
+        .collect();
+    assert!(missing.is_empty(), "unknown advice columns {:?}", missing);
+
+    (prover, mutation.names())
+}
+
+/// Assert that the [`MockProver`] of the circuit `C` rejects the block once
+/// the `overrides` cells are overwritten.
+pub fn assert_overrides_rejected<C: SubCircuit<Fr> + Circuit<Fr>>(
+    block: &Block<Fr>,
+    overrides: Vec<AdviceOverride>,
+) {
+    let (prover, _) = run_with_overrides::<C>(block, overrides.clone());
+    assert!(
+        prover.verify_par().is_err(),
+        "overrides accepted: {:?}",
+        overrides
+    );
+}
+
+/// Deltas added in turn to each mutated cell: off by one in both directions,
+/// and a value far out of the range of the usual byte, limb and u64 cells.
+fn mutation_deltas() -> [Fr; 3] {
+    [Fr::ONE, -Fr::ONE, Fr::from_u128(1 << 64)]
+}
+
+/// Overwrite in turn each assigned advice cell of the circuit `C` in `rows`
+/// whose column is selected by `columns(column name)`, and return the
+/// mutations that are still accepted by the [`MockProver`].
+///
+/// `rows` holds the rows of the witness, the ones outside of it are padding
+/// and left alone.  Each cell is shifted by each of the
+/// [`mutation_deltas`], whatever its value.  Each mutation runs the
+/// [`MockProver`] again, so `rows` and `columns` should keep the campaign to a
+/// few cells.
+pub fn mutation_campaign<C: SubCircuit<Fr> + Circuit<Fr>>(
+    block: &Block<Fr>,
+    rows: Range<usize>,
+    columns: impl Fn(&str) -> bool,
+) -> Vec<AcceptedMutation> {
+    let (prover, names) = run_with_overrides::<C>(block, vec![]);
+    prover
+        .verify_par()
+        .expect("the unmodified witness must be accepted");
+
+    let cells: Vec<_> = prover
+        .advice()
+        .iter()
+        .enumerate()
+        .map(|(index, column)| (names.name(index), column))
+        .filter(|(name, _)| columns(name))
+        .flat_map(|(name, column)| {
+            column
+                .iter()
+                .enumerate()
+                .skip(rows.start)
+                .take(rows.len())
+                .filter_map(move |(row, cell)| match cell {
+                    CellValue::Assigned(value) => Some((name.clone(), row, *value)),
+                    _ => None,
+                })
+        })
+        .collect();
+    drop(prover);
+
+    log::info!("mutating {} cells", cells.len());
+    cells
+        .into_iter()
+        .flat_map(|(column, row, original)| {
+            mutation_deltas().map(|delta| {
+                (
+                    AdviceOverride::new(column.clone(), row, original + delta),
+                    original,
+                )
+            })
+        })
+        .filter_map(|(cell, original)| {
+            let (prover, _) = run_with_overrides::<C>(block, vec![cell.clone()]);
+            prover
+                .verify_par()
+                .is_ok()
+                .then_some(AcceptedMutation { cell, original })
+        })
+        .collect()
+}
+
+thread_local! {
+    // `FloorPlanner::synthesize` only receives the circuit through a generic
+    // type, so the overrides are handed to `MutatingFloorPlanner` here.
+    static MUTATION: RefCell<Option<Mutation>> = RefCell::new(None);
+}
+
+#[derive(Debug, Default)]
+struct Mutation {
+    overrides: Vec<AdviceOverride>,
+    annotations: HashMap<String, Column<Advice>>,
+    columns: BTreeMap<usize, Column<Advice>>,
+}
+
+impl Mutation {
+    fn new(overrides: Vec<AdviceOverride>) -> Self {
+        Self {
+            overrides,
+            ..Default::default()
+        }
+    }
+
+    fn column(&self, name: &str) -> Option<Column<Advice>> {
+        self.annotations.get(name).copied().or_else(|| {
+            self.columns
+                .values()
+                .find(|column| format!("advice[{}]", column.index()) == name)
+                .copied()
+        })
+    }
+
+    fn names(&self) -> AdviceColumnNames {
+        AdviceColumnNames(
+            self.annotations
+                .iter()
+                .map(|(name, column)| (column.index(), name.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Circuit synthesized by `C` with the [`MutatingFloorPlanner`].
+struct MutatedCircuit<C>(C);
+
+impl<C: Circuit<Fr>> Circuit<Fr> for MutatedCircuit<C> {
+    type Config = C::Config;
+    type FloorPlanner = MutatingFloorPlanner<C::FloorPlanner>;
+    type Params = C::Params;
+
+    fn without_witnesses(&self) -> Self {
+        Self(self.0.without_witnesses())
+    }
+
+    fn params(&self) -> Self::Params {
+        self.0.params()
+    }
+
+    fn configure_with_params(
+        meta: &mut ConstraintSystem<Fr>,
+        params: Self::Params,
+    ) -> Self::Config {
+        C::configure_with_params(meta, params)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        self.0.synthesize(config, layouter)
+    }
+}
+
+/// Floor planner running `P`, then writing the pending overrides.
+struct MutatingFloorPlanner<P>(PhantomData<P>);
+
+impl<P: FloorPlanner> FloorPlanner for MutatingFloorPlanner<P> {
+    fn synthesize<F: Halo2Field, CS: Assignment<F> + SyncDeps, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        let mut mutation = MUTATION.with(|mutation| mutation.borrow_mut().take());
+        let result = match mutation.as_mut() {
+            Some(mutation) => {
+                let mut cs = MutatingAssignment { cs, mutation };
+                P::synthesize(&mut cs, circuit, config, constants)
+                    .and_then(|_| cs.write_overrides())
+            }
+            None => P::synthesize(cs, circuit, config, constants),
+        };
+        MUTATION.with(|cell| *cell.borrow_mut() = mutation);
+        result
+    }
+}
+
+/// [`Assignment`] recording the advice columns, to resolve the overrides
+/// once the circuit is synthesized.
+struct MutatingAssignment<'a, CS> {
+    cs: &'a mut CS,
+    mutation: &'a mut Mutation,
+}
+
+impl<'a, CS> MutatingAssignment<'a, CS> {
+    fn write_overrides<F: Halo2Field>(&mut self) -> Result<(), Error>
+    where
+        CS: Assignment<F>,
+    {
+        for o in self.mutation.overrides.iter() {
+            // Skip the unknown columns, reported by `run_with_overrides`.
+            if let Some(column) = self.mutation.column(&o.column) {
+                let value = to_field::<F>(o.value);
+                self.cs
+                    .assign_advice(|| "override", column, o.row, || Value::known(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, F: Halo2Field, CS: Assignment<F>> Assignment<F> for MutatingAssignment<'a, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.enter_region(name_fn)
+    }
+
+    fn annotate_column<A, AR>(&mut self, annotation: A, column: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let annotation = annotation().into();
+        if let Ok(advice) = Column::<Advice>::try_from(column) {
+            self.mutation.annotations.insert(annotation.clone(), advice);
+        }
+        self.cs.annotate_column(|| annotation, column)
+    }
+
+    fn exit_region(&mut self) {
+        self.cs.exit_region()
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.mutation.columns.insert(column.index(), column);
+        self.cs.assign_advice(annotation, column, row, to)
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.cs.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.cs.fill_from_row(column, row, to)
+    }
+
+    fn get_challenge(&self, challenge: Challenge) -> Value<F> {
+        self.cs.get_challenge(challenge)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name)
+    }
+}
+
+/// Map `value` to the field of the floor planner, which is always `Fr` here.
+fn to_field<F: Halo2Field>(value: Fr) -> F {
+    value
+        .to_repr()
+        .as_ref()
+        .iter()
+        .rev()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1))
+        .fold(F::ZERO, |acc, bit| {
+            let acc = acc.double();
+            if bit {
+                acc + F::ONE
+            } else {
+                acc
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        copy_circuit::CopyCircuit,
+        state_circuit::StateCircuit,
+        witness::{block_convert, RwMap},
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::TestContext;
+
+    fn block() -> Block<Fr> {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert::<Fr>(&builder.block, &builder.code_db).unwrap()
+    }
+
+    #[test]
+    fn non_boolean_is_write_rejected() {
+        let block = block();
+        let (prover, _) = run_with_overrides::<StateCircuit<Fr>>(&block, vec![]);
+        prover.assert_satisfied_par();
+
+        assert_overrides_rejected::<StateCircuit<Fr>>(
+            &block,
+            vec![AdviceOverride::new("is_write", 1, Fr::from(2))],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown advice columns")]
+    fn unknown_column_panics() {
+        run_with_overrides::<CopyCircuit<Fr>>(
+            &block(),
+            vec![AdviceOverride::new("no_such_column", 0, Fr::ONE)],
+        );
+    }
+
+    #[test]
+    fn state_circuit_rw_counter_mutation_campaign() {
+        let block = block();
+        // the rw counter is decomposed into limbs, so any change is caught
+        let (rows, padding_length) = RwMap::table_assignments_prepad(
+            &block.rws.table_assignments(),
+            block.circuits_params.max_rws,
+        );
+        let witness_rows = padding_length..std::cmp::min(rows.len(), padding_length + 8);
+        let accepted = mutation_campaign::<StateCircuit<Fr>>(&block, witness_rows, |column| {
+            column == "rw_counter"
+        });
+        assert!(
+            accepted.is_empty(),
+            "accepted mutations: {}",
+            accepted
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
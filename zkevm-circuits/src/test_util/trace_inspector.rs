@@ -0,0 +1,294 @@
+//! Inspector locating the failures of the EVM circuit in the execution trace.
+//!
+//! The EVM circuit assigns the steps of a block one after the other in its
+//! "Execution step" region, each one taking the height of its
+//! [`ExecutionState`].  [`TraceInspector`] replays this layout to map each row
+//! to the step it belongs to, so that a [`VerifyFailure`] reported by the
+//! [`MockProver`](halo2_proofs::dev::MockProver) can be traced back to its tx,
+//! call, pc and opcode.
+//!
+//! ```rust, ignore
+//! let (prover, _) = run_with_overrides::<EvmCircuit<Fr>>(&block, vec![]);
+//! if let Err(failures) = prover.verify_par() {
+//!     let inspector = TraceInspector::new(&block);
+//!     for failure in &failures {
+//!         println!("{}", inspector.report(failure));
+//!     }
+//! }
+//! ```
+
+use crate::{
+    evm_circuit::step::{ExecutionState, HasExecutionState},
+    witness::Block,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::Field;
+use halo2_proofs::dev::{FailureLocation, VerifyFailure};
+use std::{fmt, ops::Range};
+
+/// Name of the region where the EVM circuit assigns the steps
+const EXECUTION_STEP_REGION: &str = "Execution step";
+
+/// Rows of the EVM circuit assigned to an execution step
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepRows {
+    /// Rows of the step
+    pub rows: Range<usize>,
+    /// Index of the tx in the block, `None` for the EndBlock steps
+    pub tx_index: Option<usize>,
+    /// Id of the call
+    pub call_id: usize,
+    /// Program counter
+    pub pc: usize,
+    /// Opcode executed by the step
+    pub opcode: Option<OpcodeId>,
+    /// Execution state of the step
+    pub execution_state: ExecutionState,
+}
+
+impl fmt::Display for StepRows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tx_index {
+            Some(tx_index) => write!(f, "tx {} ", tx_index)?,
+            None => write!(f, "end of block ")?,
+        }
+        write!(
+            f,
+            "call {} pc {} {:?}",
+            self.call_id, self.pc, self.execution_state
+        )?;
+        if let Some(opcode) = self.opcode {
+            write!(f, " ({:?})", opcode)?;
+        }
+        write!(f, " at rows {}..{}", self.rows.start, self.rows.end)
+    }
+}
+
+/// Map from the rows of the EVM circuit to the steps of a block
+#[derive(Clone, Debug, Default)]
+pub struct TraceInspector {
+    steps: Vec<StepRows>,
+    /// Absolute row of the circuit where the "Execution step" region starts
+    region_start: usize,
+}
+
+impl TraceInspector {
+    /// Lay out the steps of the block like the EVM circuit does.
+    pub fn new<F: Field>(block: &Block<F>) -> Self {
+        let params = block.evm_circuit_params();
+        let last_call_id = block
+            .txs
+            .last()
+            .map(|tx| tx.calls[0].call_id)
+            .unwrap_or_default();
+
+        let mut steps = vec![];
+        let mut offset = 0;
+        for (tx_index, tx) in block.txs.iter().enumerate() {
+            for step in &tx.steps {
+                let height = step.execution_state().get_step_height(params);
+                steps.push(StepRows {
+                    rows: offset..offset + height,
+                    tx_index: Some(tx_index),
+                    call_id: tx.calls[step.call_index].call_id,
+                    pc: step.pc.0,
+                    opcode: step.opcode(),
+                    execution_state: step.execution_state(),
+                });
+                offset += height;
+            }
+        }
+
+        // The non-last EndBlock steps pad the circuit up to its last row, which
+        // holds the last EndBlock step.
+        let evm_rows = block.circuits_params.max_evm_rows;
+        let end_block = |rows: Range<usize>| StepRows {
+            rows,
+            tx_index: None,
+            call_id: last_call_id,
+            pc: 0,
+            opcode: None,
+            execution_state: ExecutionState::EndBlock,
+        };
+        if evm_rows != 0 && offset < evm_rows - 1 {
+            steps.push(end_block(offset..evm_rows - 1));
+            offset = evm_rows - 1;
+        }
+        steps.push(end_block(offset..offset + 1));
+
+        Self {
+            steps,
+            region_start: 0,
+        }
+    }
+
+    /// Set the absolute row where the "Execution step" region starts, used to
+    /// locate the failures reported outside of any region.  It is the first
+    /// row of the circuit when the EVM circuit is proven alone.
+    pub fn with_region_start(mut self, region_start: usize) -> Self {
+        self.region_start = region_start;
+        self
+    }
+
+    /// Steps of the block, in row order
+    pub fn steps(&self) -> &[StepRows] {
+        &self.steps
+    }
+
+    /// Step assigned at `row`
+    pub fn step_at(&self, row: usize) -> Option<&StepRows> {
+        let index = self.steps.partition_point(|step| step.rows.end <= row);
+        self.steps
+            .get(index)
+            .filter(|step| step.rows.contains(&row))
+    }
+
+    /// Step where the failure occurs, if it is located in the rows of the
+    /// execution steps.
+    pub fn failure_step(&self, failure: &VerifyFailure) -> Option<&StepRows> {
+        self.failure_offset(failure)
+            .and_then(|offset| self.step_at(offset))
+    }
+
+    /// Describe the failure along with the step where it occurs.
+    pub fn annotate(&self, failure: &VerifyFailure) -> String {
+        match self.failure_step(failure) {
+            Some(step) => format!("{}\n  in {}", failure, step),
+            None => failure.to_string(),
+        }
+    }
+
+    /// Describe the failure along with the step where it occurs and, for a
+    /// constraint not satisfied, the cells queried by the constraint.
+    pub fn report(&self, failure: &VerifyFailure) -> String {
+        let mut report = self.annotate(failure);
+        if let VerifyFailure::ConstraintNotSatisfied { cell_values, .. } = failure {
+            for (cell, value) in cell_values {
+                report += &format!("\n  {} = {}", cell, value);
+            }
+        }
+        report
+    }
+
+    /// Offset of the failure in the "Execution step" region.  Failures in
+    /// other regions, and rows outside of any region that precede the
+    /// "Execution step" region, have no offset.
+    fn failure_offset(&self, failure: &VerifyFailure) -> Option<usize> {
+        let location = match failure {
+            VerifyFailure::ConstraintNotSatisfied { location, .. }
+            | VerifyFailure::Lookup { location, .. }
+            | VerifyFailure::Permutation { location, .. } => location,
+            _ => return None,
+        };
+        match location {
+            FailureLocation::InRegion { region, offset }
+                if region
+                    .to_string()
+                    .contains(&format!("'{}'", EXECUTION_STEP_REGION)) =>
+            {
+                Some(*offset)
+            }
+            // The row of a failure outside of any region is absolute.
+            FailureLocation::OutsideRegion { row } => row.checked_sub(self.region_start),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evm_circuit::EvmCircuit,
+        test_util::soundness::{run_with_overrides, AdviceOverride},
+        witness::block_convert,
+    };
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::{halo2curves::bn256::Fr, plonk::Any};
+    use mock::TestContext;
+
+    fn block() -> Block<Fr> {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            MSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert::<Fr>(&builder.block, &builder.code_db).unwrap()
+    }
+
+    #[test]
+    fn steps_cover_rows() {
+        let block = block();
+        let inspector = TraceInspector::new(&block);
+        let steps = inspector.steps();
+
+        assert_eq!(steps[0].rows.start, 0);
+        for pair in steps.windows(2) {
+            assert_eq!(pair[0].rows.end, pair[1].rows.start);
+        }
+        assert_eq!(
+            steps.last().unwrap().execution_state,
+            ExecutionState::EndBlock
+        );
+
+        let mstore = steps
+            .iter()
+            .find(|step| step.opcode == Some(OpcodeId::MSTORE))
+            .unwrap();
+        assert_eq!(mstore.tx_index, Some(0));
+        assert_eq!(inspector.step_at(mstore.rows.end - 1), Some(mstore));
+    }
+
+    #[test]
+    fn failure_mapped_to_step() {
+        let block = block();
+        let inspector = TraceInspector::new(&block);
+        let mstore = inspector
+            .steps()
+            .iter()
+            .find(|step| step.opcode == Some(OpcodeId::MSTORE))
+            .unwrap()
+            .clone();
+
+        let (prover, _) = run_with_overrides::<EvmCircuit<Fr>>(
+            &block,
+            vec![AdviceOverride::new(
+                "EVM_rows_until_next_step",
+                mstore.rows.start,
+                Fr::from(1000),
+            )],
+        );
+        let failures = prover.verify_par().expect_err("tampered step height");
+        assert!(failures
+            .iter()
+            .any(|failure| inspector.failure_step(failure) == Some(&mstore)));
+
+        let report = inspector.report(&failures[0]);
+        assert!(report.contains("  in "), "{}", report);
+    }
+
+    #[test]
+    fn absolute_rows_shifted_by_region_start() {
+        let block = block();
+        let inspector = TraceInspector::new(&block).with_region_start(10);
+        let failure = |row| VerifyFailure::Permutation {
+            column: (Any::advice(), 0).into(),
+            location: FailureLocation::OutsideRegion { row },
+        };
+
+        assert_eq!(inspector.failure_step(&failure(5)), None);
+        assert_eq!(
+            inspector.failure_step(&failure(10)),
+            inspector.steps().first()
+        );
+    }
+}
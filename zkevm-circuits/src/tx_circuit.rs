@@ -14,14 +14,16 @@ mod test;
 pub use dev::TxCircuit as TestTxCircuit;
 
 use crate::{
-    table::{KeccakTable, TxFieldTag, TxTable},
+    table::{BlockContextFieldTag, BlockTable, KeccakTable, LookupTable, TxFieldTag, TxTable},
     util::{random_linear_combine_word as rlc, Challenges, SubCircuit, SubCircuitConfig},
     witness,
 };
 use eth_types::{geth_types::Transaction, sign_types::SignData, Field, ToLittleEndian, ToScalar};
+use gadgets::util::Expr;
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed},
+    poly::Rotation,
 };
 use itertools::Itertools;
 use log::error;
@@ -30,10 +32,10 @@ use std::marker::PhantomData;
 
 /// Number of static fields per tx: [nonce, gas, gas_price, gas_tip_cap, gas_fee_cap,
 /// caller_address, callee_address, is_create, value, call_data_length,
-/// call_data_gas_cost, tx_sign_hash, r, s, v].
+/// call_data_gas_cost, tx_sign_hash, r, s, v, block_number].
 /// Note that call data bytes are layed out in the TxTable after all the static
 /// fields arranged by txs.
-pub(crate) const TX_LEN: usize = 15;
+pub(crate) const TX_LEN: usize = 16;
 
 /// Config for TxCircuit
 #[derive(Clone, Debug)]
@@ -42,10 +44,13 @@ pub struct TxCircuitConfig<F: Field> {
     tag: Column<Fixed>,
     index: Column<Advice>,
     value: Column<Advice>,
+    /// Enabled on the BlockNumber rows
+    q_block_number: Column<Fixed>,
     sign_verify: SignVerifyConfig,
     _marker: PhantomData<F>,
     // External tables
     keccak_table: KeccakTable,
+    block_table: BlockTable,
 }
 
 /// Circuit configuration arguments
@@ -54,6 +59,8 @@ pub struct TxCircuitConfigArgs<F: Field> {
     pub tx_table: TxTable,
     /// KeccakTable
     pub keccak_table: KeccakTable,
+    /// BlockTable
+    pub block_table: BlockTable,
     /// Challenges
     pub challenges: Challenges<Expression<F>>,
 }
@@ -67,6 +74,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         Self::ConfigArgs {
             tx_table,
             keccak_table,
+            block_table,
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
@@ -74,8 +82,26 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let tag = tx_table.tag;
         let index = tx_table.index;
         let value = tx_table.value;
+        let q_block_number = meta.fixed_column();
         meta.enable_equality(value);
 
+        // Every tx belongs to one of the blocks in the block table, so that a
+        // tx can't be moved to another block of a batch.
+        meta.lookup_any("tx block number in block table", |meta| {
+            let q_block_number = meta.query_fixed(q_block_number, Rotation::cur());
+            let block_number = meta.query_advice(value, Rotation::cur());
+
+            [
+                BlockContextFieldTag::Number.expr(),
+                block_number.clone(),
+                block_number,
+            ]
+            .into_iter()
+            .zip(block_table.table_exprs(meta))
+            .map(|(arg, table)| (q_block_number.clone() * arg, table))
+            .collect()
+        });
+
         let sign_verify = SignVerifyConfig::new(meta, keccak_table.clone(), challenges);
 
         Self {
@@ -83,8 +109,10 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             tag,
             index,
             value,
+            q_block_number,
             sign_verify,
             keccak_table,
+            block_table,
             _marker: PhantomData,
         }
     }
@@ -125,6 +153,12 @@ impl<F: Field> TxCircuitConfig<F> {
             offset,
             || Value::known(F::from(index as u64)),
         )?;
+        region.assign_fixed(
+            || "q_block_number",
+            self.q_block_number,
+            offset,
+            || Value::known(F::from((tag == TxFieldTag::BlockNumber) as u64)),
+        )?;
         region.assign_advice(|| "value", self.value, offset, || value)
     }
 
@@ -148,6 +182,10 @@ pub struct TxCircuit<F: Field> {
     pub sign_verify: SignVerifyChip<F>,
     /// List of Transactions
     pub txs: Vec<Transaction>,
+    /// Number of the block including each transaction.  The padding
+    /// transactions are assigned the number of the first block, or zero when
+    /// it's missing.
+    pub block_numbers: Vec<u64>,
     /// Chain ID
     pub chain_id: u64,
 }
@@ -160,10 +198,18 @@ impl<F: Field> TxCircuit<F> {
             max_calldata,
             sign_verify: SignVerifyChip::new(max_txs),
             txs,
+            block_numbers: Vec::new(),
             chain_id,
         }
     }
 
+    /// Set the number of the block including each transaction, required when
+    /// the transactions span a batch of blocks.
+    pub fn with_block_numbers(mut self, block_numbers: Vec<u64>) -> Self {
+        self.block_numbers = block_numbers;
+        self
+    }
+
     /// Return the minimum number of rows required to prove an input of a
     /// particular size.
     pub fn min_num_rows(txs_len: usize, call_data_len: usize) -> usize {
@@ -200,6 +246,12 @@ impl<F: Field> TxCircuit<F> {
                     } else {
                         &tx_default
                     };
+                    let block_number = self
+                        .block_numbers
+                        .get(i)
+                        .or(self.block_numbers.first())
+                        .copied()
+                        .unwrap_or_default();
 
                     for (tag, value) in [
                         (TxFieldTag::Nonce, Value::known(F::from(tx.nonce.as_u64()))),
@@ -268,6 +320,7 @@ impl<F: Field> TxCircuit<F> {
                                 .evm_word()
                                 .map(|challenge| rlc(tx.s.to_le_bytes(), challenge)),
                         ),
+                        (TxFieldTag::BlockNumber, Value::known(F::from(block_number))),
                     ] {
                         let assigned_cell =
                             config.assign_row(&mut region, offset, i + 1, tag, 0, value)?;
@@ -338,12 +391,12 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             block.circuits_params.max_calldata,
             block.context.chain_id.as_u64(),
             block
-                .eth_block
-                .transactions
-                .iter()
+                .eth_blocks()
+                .flat_map(|eth_block| eth_block.transactions.iter())
                 .map(|tx| tx.into())
                 .collect(),
         )
+        .with_block_numbers(block.txs.iter().map(|tx| tx.block_number).collect())
     }
 
     /// Return the minimum number of rows required to prove the block
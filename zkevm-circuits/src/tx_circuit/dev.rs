@@ -1,9 +1,10 @@
 pub use super::TxCircuit;
 
 use crate::{
-    table::{KeccakTable, TxTable},
+    table::{BlockTable, KeccakTable, TxTable},
     tx_circuit::{TxCircuitConfig, TxCircuitConfigArgs},
     util::{Challenges, SubCircuit, SubCircuitConfig},
+    witness::BlockContext,
 };
 use bus_mapping::circuit_input_builder::keccak_inputs_tx_circuit;
 use eth_types::Field;
@@ -11,6 +12,7 @@ use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
     plonk::{Circuit, ConstraintSystem, Error},
 };
+use itertools::Itertools;
 use log::error;
 
 impl<F: Field> Circuit<F> for TxCircuit<F> {
@@ -25,6 +27,7 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let tx_table = TxTable::construct(meta);
         let keccak_table = KeccakTable::construct(meta);
+        let block_table = BlockTable::construct(meta);
         let challenges = Challenges::construct(meta);
 
         let config = {
@@ -34,6 +37,7 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
                 TxCircuitConfigArgs {
                     tx_table,
                     keccak_table,
+                    block_table,
                     challenges,
                 },
             )
@@ -57,6 +61,21 @@ impl<F: Field> Circuit<F> for TxCircuit<F> {
             })?,
             &challenges,
         )?;
+        // Only the numbers of the blocks including the txs are looked up, and
+        // txs without a block number are assigned block zero.
+        let contexts: Vec<BlockContext> = self
+            .block_numbers
+            .iter()
+            .chain(std::iter::once(&0))
+            .unique()
+            .map(|&number| BlockContext {
+                number: number.into(),
+                ..Default::default()
+            })
+            .collect();
+        config
+            .block_table
+            .load(&mut layouter, &contexts, challenges.evm_word())?;
         self.synthesize_sub(&config, &challenges, &mut layouter)
     }
 }
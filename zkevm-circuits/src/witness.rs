@@ -6,8 +6,20 @@ mod block;
 pub use block::{block_convert, protocol_instancetable_assignments, Block, BlockContext};
 mod bytecode;
 pub use bytecode::Bytecode;
+#[cfg(any(feature = "coverage", test))]
+mod coverage;
+#[cfg(any(feature = "coverage", test))]
+pub use coverage::{
+    reached_dummy_execution_states, record_execution_states, unreached_execution_states, Coverage,
+    CoverageReport, COVERAGE_ENV,
+};
 mod mpt;
 pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates};
+mod receipt;
+pub use receipt::{
+    accrue_bloom, bloom_bits, keccak_inputs_receipts_trie, logs_bloom, receipts_convert,
+    receipts_root, Log, Receipt, BLOOM_BYTES,
+};
 mod rw;
 pub use bus_mapping::circuit_input_builder::ExecStep;
 pub use rw::{Rw, RwMap, RwRow};
@@ -1,19 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::{
-    evm_circuit::{detect_fixed_table_tags, util::rlc, EvmCircuit},
+    evm_circuit::{
+        detect_fixed_table_tags, param::EvmLayout, util::rlc, EvmCircuit, EvmCircuitParams,
+    },
     exp_circuit::param::OFFSET_INCREMENT,
     table::{BlockContextFieldTag, PiFieldTag},
-    util::{log2_ceil, rlc_be_bytes, SubCircuit},
+    util::{log2_ceil, rlc_be_bytes},
 };
 use bus_mapping::{
     circuit_input_builder::{
-        self, protocol_instance, CircuitsParams, CopyEvent, ExpEvent, ProtocolInstance,
-        ANCHOR_METHOD_SIGNATURE,
+        self, protocol_instance, ChunkContext, CircuitsParams, CopyEvent, ExpEvent,
+        ProtocolInstance, ANCHOR_METHOD_SIGNATURE,
     },
+    operation::{Operation, TxReceiptField},
     Error,
 };
-use eth_types::{Address, Field, ToBigEndian, ToLittleEndian, ToScalar, ToWord, Word, H256};
+use eth_types::{
+    evm_types::ChainSpec, Address, Field, ToBigEndian, ToLittleEndian, ToScalar, ToWord, Word, H256,
+};
 use halo2_proofs::circuit::Value;
 
 use super::{tx::tx_convert, Bytecode, ExecStep, Rw, RwMap, Transaction};
@@ -38,6 +43,9 @@ pub struct Block<F> {
     pub bytecodes: HashMap<Word, Bytecode>,
     /// The block context
     pub context: BlockContext,
+    /// Contexts of the blocks following the first one when a batch of blocks is
+    /// proven together, indexed by block number.
+    pub batch_contexts: BTreeMap<u64, BlockContext>,
     /// Copy events for the copy circuit's table.
     pub copy_events: Vec<CopyEvent>,
     /// Exponentiation traces for the exponentiation circuit's table.
@@ -54,8 +62,17 @@ pub struct Block<F> {
     pub keccak_inputs: Vec<Vec<u8>>,
     /// Original Block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
+    /// Original Blocks from geth following `eth_block` when proving a batch
+    pub batch_eth_blocks: Vec<eth_types::Block<eth_types::Transaction>>,
     /// Protocol Instance
     pub protocol_instance: Option<ProtocolInstance>,
+    /// Boundaries of the chunk of the block proven by this witness, when the
+    /// block is split into several proofs
+    pub chunk: Option<ChunkContext>,
+    /// Layout of the EVM circuit proving the block
+    pub evm_layout: EvmLayout,
+    /// Rules of the chain the block belongs to
+    pub chain_spec: ChainSpec,
 }
 
 /// Assignments for pi table
@@ -109,11 +126,58 @@ impl<F: Field> Block<F> {
         self.rws[step.rw_index(index)]
     }
 
+    /// Get the `length` memory bytes starting at `address`, from the memory
+    /// word read-write records of the step starting at `index`.
+    pub(crate) fn get_memory_bytes(
+        &self,
+        step: &ExecStep,
+        index: usize,
+        address: u64,
+        length: u64,
+    ) -> Vec<u8> {
+        if length == 0 {
+            return vec![];
+        }
+        let shift = address % 32;
+        let num_words = (shift + length + 31) / 32;
+        (index..index + num_words as usize)
+            .flat_map(|index| self.get_rws(step, index).memory_value().to_be_bytes())
+            .skip(shift as usize)
+            .take(length as usize)
+            .collect()
+    }
+
     /// Set protocol instance means in taiko context
     pub fn is_taiko(&self) -> bool {
         self.protocol_instance.is_some()
     }
 
+    /// Parameters of the EVM circuit proving the block
+    pub fn evm_circuit_params(&self) -> EvmCircuitParams {
+        EvmCircuitParams {
+            is_taiko: self.is_taiko(),
+            chain_spec: self.chain_spec,
+            layout: self.evm_layout,
+        }
+    }
+
+    /// Contexts of all the blocks in the witness, in increasing block number.
+    pub fn contexts(&self) -> impl Iterator<Item = &BlockContext> {
+        std::iter::once(&self.context).chain(self.batch_contexts.values())
+    }
+
+    /// Original blocks from geth of all the blocks in the witness.
+    pub fn eth_blocks(&self) -> impl Iterator<Item = &eth_types::Block<eth_types::Transaction>> {
+        std::iter::once(&self.eth_block).chain(self.batch_eth_blocks.iter())
+    }
+
+    /// Context of the block which includes the transaction.
+    pub fn tx_context(&self, tx: &Transaction) -> &BlockContext {
+        self.batch_contexts
+            .get(&tx.block_number)
+            .unwrap_or(&self.context)
+    }
+
     // pub(crate) fn mock_protocol_instance(&mut self, prover: Option<H160>) {
     //     self.protocol_instance = Some(ProtocolInstance::default());
     // }
@@ -127,7 +191,7 @@ impl<F: Field> Block<F> {
         let num_rows_required_for_rw_table: usize = self.circuits_params.max_rws;
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(self)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(self.chain_spec).count())
             .sum();
         let num_rows_required_for_bytecode_table: usize = self
             .bytecodes
@@ -135,7 +199,7 @@ impl<F: Field> Block<F> {
             .map(|bytecode| bytecode.bytes.len() + 1)
             .sum();
         let num_rows_required_for_copy_table: usize =
-            self.copy_events.iter().map(|c| c.bytes.len() * 2).sum();
+            self.copy_events.iter().map(|c| c.num_steps() * 2).sum();
         let num_rows_required_for_keccak_table: usize = self.keccak_inputs.len();
         let num_rows_required_for_tx_table: usize =
             self.txs.iter().map(|tx| 9 + tx.call_data.len()).sum();
@@ -157,7 +221,7 @@ impl<F: Field> Block<F> {
         ])
         .unwrap();
 
-        let k = log2_ceil(EvmCircuit::<F>::unusable_rows() + rows_needed);
+        let k = log2_ceil(self.evm_circuit_params().unusable_rows() + rows_needed);
         log::debug!(
             "num_rows_requred_for rw_table={}, fixed_table={}, bytecode_table={}, \
             copy_table={}, keccak_table={}, tx_table={}, exp_table={}",
@@ -199,6 +263,21 @@ pub struct BlockContext {
     pub chain_id: Word,
     /// The block hash
     pub block_hash: Word,
+    /// Number of rw operations of the block preceding this witness, when the
+    /// block is split into chunks
+    pub rwc_offset: u64,
+    /// Number of txs of the block preceding this witness, when the block is
+    /// split into chunks
+    pub tx_id_offset: u64,
+    /// Gas used by the txs of the block preceding this witness, when the
+    /// block is split into chunks
+    pub cumulative_gas_used_offset: u64,
+    /// Block rw counter following the rw operations of this witness
+    pub rwc_end: u64,
+    /// Number of txs of the block up to the end of this witness
+    pub tx_id_end: u64,
+    /// Gas used by the txs of the block up to the end of this witness
+    pub cumulative_gas_used_end: u64,
 }
 
 impl BlockContext {
@@ -208,43 +287,43 @@ impl BlockContext {
             vec![
                 [
                     Value::known(F::from(BlockContextFieldTag::Coinbase as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     Value::known(self.coinbase.to_scalar().unwrap()),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::Treasury as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     Value::known(self.treasury.unwrap_or_default().to_scalar().unwrap()),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::Timestamp as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     Value::known(self.timestamp.to_scalar().unwrap()),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::Number as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     Value::known(self.number.to_scalar().unwrap()),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::Difficulty as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     rlc_be_bytes(&self.mix_hash.to_fixed_bytes(), randomness),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::GasLimit as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     Value::known(F::from(self.gas_limit)),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::BaseFee as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     randomness
                         .map(|randomness| rlc::value(&self.base_fee.to_le_bytes(), randomness)),
                 ],
                 [
                     Value::known(F::from(BlockContextFieldTag::ChainId as u64)),
-                    Value::known(F::ZERO),
+                    Value::known(self.number.to_scalar().unwrap()),
                     randomness
                         .map(|randomness| rlc::value(&self.chain_id.to_le_bytes(), randomness)),
                 ],
@@ -254,6 +333,38 @@ impl BlockContext {
                     randomness
                         .map(|randomness| rlc::value(&self.block_hash.to_le_bytes(), randomness)),
                 ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::RwcOffset as u64)),
+                    Value::known(self.number.to_scalar().unwrap()),
+                    Value::known(F::from(self.rwc_offset)),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::TxIdOffset as u64)),
+                    Value::known(self.number.to_scalar().unwrap()),
+                    Value::known(F::from(self.tx_id_offset)),
+                ],
+                [
+                    Value::known(F::from(
+                        BlockContextFieldTag::CumulativeGasUsedOffset as u64,
+                    )),
+                    Value::known(self.number.to_scalar().unwrap()),
+                    Value::known(F::from(self.cumulative_gas_used_offset)),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::RwcEnd as u64)),
+                    Value::known(self.number.to_scalar().unwrap()),
+                    Value::known(F::from(self.rwc_end)),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::TxIdEnd as u64)),
+                    Value::known(self.number.to_scalar().unwrap()),
+                    Value::known(F::from(self.tx_id_end)),
+                ],
+                [
+                    Value::known(F::from(BlockContextFieldTag::CumulativeGasUsedEnd as u64)),
+                    Value::known(self.number.to_scalar().unwrap()),
+                    Value::known(F::from(self.cumulative_gas_used_end)),
+                ],
             ],
             {
                 let len_history = self.history_hashes.len();
@@ -275,6 +386,28 @@ impl BlockContext {
     }
 }
 
+impl BlockContext {
+    fn from_head(head: &circuit_input_builder::BlockHead, chain_id: Word) -> Self {
+        Self {
+            coinbase: head.coinbase,
+            treasury: Some(*protocol_instance::TREASURY),
+            gas_limit: head.gas_limit,
+            number: head.number,
+            timestamp: head.timestamp,
+            mix_hash: head.mix_hash,
+            base_fee: head.base_fee,
+            history_hashes: head.history_hashes.clone(),
+            chain_id,
+            block_hash: head
+                .eth_block
+                .hash
+                .map(|hash| hash.to_word())
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
 impl From<&circuit_input_builder::Block> for BlockContext {
     fn from(block: &circuit_input_builder::Block) -> Self {
         Self {
@@ -292,6 +425,33 @@ impl From<&circuit_input_builder::Block> for BlockContext {
                 .hash
                 .map(|hash| hash.to_word())
                 .unwrap_or_default(),
+            rwc_offset: block.chunk.map_or(0, |chunk| chunk.rwc_offset() as u64),
+            tx_id_offset: block.chunk.map_or(0, |chunk| chunk.tx_id_offset() as u64),
+            cumulative_gas_used_offset: block
+                .chunk
+                .map_or(0, |chunk| chunk.initial_cumulative_gas_used),
+            // A whole block ends where its EndBlock step starts.
+            rwc_end: block
+                .chunk
+                .map_or(block.block_steps.end_block_last.rwc.0, |chunk| {
+                    chunk.end_rwc
+                }) as u64,
+            tx_id_end: block
+                .chunk
+                .map_or(block.txs.len(), |chunk| chunk.end_tx_index) as u64,
+            cumulative_gas_used_end: block.chunk.map_or_else(
+                || {
+                    block
+                        .container
+                        .tx_receipt
+                        .iter()
+                        .map(Operation::op)
+                        .filter(|op| op.field == TxReceiptField::CumulativeGasUsed)
+                        .max_by_key(|op| op.tx_id)
+                        .map_or(0, |op| op.value)
+                },
+                |chunk| chunk.end_cumulative_gas_used,
+            ),
         }
     }
 }
@@ -303,10 +463,29 @@ pub fn block_convert<F: Field>(
 ) -> Result<Block<F>, Error> {
     let rws = RwMap::from(&block.container);
     rws.check_value();
-    Ok(Block {
+    let context = BlockContext::from(block);
+    let witness_block = Block {
         // randomness: F::from(0x100), // Special value to reveal elements after RLC
         randomness: F::from(0xcafeu64),
-        context: block.into(),
+        context: context.clone(),
+        batch_contexts: block
+            .headers
+            .values()
+            .skip(1)
+            .map(|head| {
+                (
+                    head.number.as_u64(),
+                    // The end of the witness is looked up in the block of its
+                    // last tx.
+                    BlockContext {
+                        rwc_end: context.rwc_end,
+                        tx_id_end: context.tx_id_end,
+                        cumulative_gas_used_end: context.cumulative_gas_used_end,
+                        ..BlockContext::from_head(head, block.chain_id)
+                    },
+                )
+            })
+            .collect(),
         rws,
         txs: block
             .txs()
@@ -330,11 +509,28 @@ pub fn block_convert<F: Field>(
         circuits_params: block.circuits_params,
         exp_circuit_pad_to: <usize>::default(),
         prev_state_root: block.prev_state_root,
-        // Use EVM Circuit's related inputs for keccak inputs
-        // Otherwise, it will fail due to tx.v in keccak_inputs_tx_circuit
+        // Use EVM Circuit's and Receipt Circuit's related inputs for keccak
+        // inputs. Otherwise, it will fail due to tx.v in keccak_inputs_tx_circuit
         // keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
-        keccak_inputs: block.sha3_inputs.clone(),
+        keccak_inputs: block
+            .sha3_inputs
+            .iter()
+            .cloned()
+            .chain(circuit_input_builder::keccak_inputs_receipt_circuit(block))
+            .collect(),
         eth_block: block.eth_block.clone(),
+        batch_eth_blocks: block
+            .headers
+            .values()
+            .skip(1)
+            .map(|head| head.eth_block.clone())
+            .collect(),
         protocol_instance: block.protocol_instance.clone(),
-    })
+        chunk: block.chunk,
+        evm_layout: EvmLayout::default(),
+        chain_spec: block.chain_spec,
+    };
+    #[cfg(feature = "coverage")]
+    super::coverage::record_block(&witness_block);
+    Ok(witness_block)
 }
@@ -0,0 +1,169 @@
+//! Coverage of the execution states of the EVM circuit reached by the blocks
+//! converted to witnesses.
+//!
+//! With the `coverage` feature, [`block_convert`](super::block_convert) adds
+//! the execution states of every converted block to the coverage report of the
+//! process, next to the steps, RW targets and copy data types recorded by the
+//! bus mapping (see [`bus_mapping::coverage`]).  The execution states that are
+//! never reached point to gadgets without tests, and the dummy execution states
+//! that are reached point to traces that the circuit doesn't constrain.  The
+//! `coverage` binary prints the report merged from the runs of a test corpus.
+
+use super::{Block, ExecStep};
+use crate::evm_circuit::step::{ExecutionState, HasExecutionState};
+use bus_mapping::coverage::count;
+use eth_types::Field;
+use std::fmt;
+use strum::IntoEnumIterator;
+
+pub use bus_mapping::coverage::{Coverage, COVERAGE_ENV};
+
+/// Add the execution states of the steps of the block to the coverage.
+pub fn record_execution_states<F: Field>(coverage: &mut Coverage, block: &Block<F>) {
+    for step in steps(block) {
+        let state = step.execution_state();
+        let opcode = step
+            .opcode()
+            .map_or_else(|| "-".to_string(), |opcode| format!("{:?}", opcode));
+        let error = step
+            .error
+            .as_ref()
+            .map_or_else(|| "-".to_string(), |error| format!("{:?}", error));
+        count(&mut coverage.execution_states, format!("{:?}", state), 1);
+        count(
+            &mut coverage.steps,
+            format!("{:?}/{}/{}", state, opcode, error),
+            1,
+        );
+    }
+}
+
+/// Steps of the block, with the last EndBlock step
+fn steps<F>(block: &Block<F>) -> impl Iterator<Item = &ExecStep> {
+    block
+        .txs
+        .iter()
+        .flat_map(|tx| &tx.steps)
+        .chain([&block.end_block_last])
+}
+
+/// Execution states without any step
+pub fn unreached_execution_states(coverage: &Coverage) -> Vec<ExecutionState> {
+    ExecutionState::iter()
+        .filter(|state| {
+            !coverage
+                .execution_states
+                .contains_key(&format!("{:?}", state))
+        })
+        .collect()
+}
+
+/// Dummy execution states with steps, which the EVM circuit doesn't constrain
+pub fn reached_dummy_execution_states(coverage: &Coverage) -> Vec<ExecutionState> {
+    ExecutionState::iter()
+        .filter(|state| {
+            state.is_dummy()
+                && coverage
+                    .execution_states
+                    .contains_key(&format!("{:?}", state))
+        })
+        .collect()
+}
+
+/// Report of a coverage, listing the unreached and the reached dummy execution
+/// states after the counts
+#[derive(Clone, Copy, Debug)]
+pub struct CoverageReport<'a>(pub &'a Coverage);
+
+impl fmt::Display for CoverageReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        writeln!(f, "unreached execution states:")?;
+        for state in unreached_execution_states(self.0) {
+            writeln!(f, "  {:?}", state)?;
+        }
+        writeln!(f, "dummy execution states reached (unconstrained):")?;
+        for state in reached_dummy_execution_states(self.0) {
+            writeln!(f, "  {:?}", state)?;
+        }
+        Ok(())
+    }
+}
+
+/// Add the execution states of the block to the coverage report of the
+/// process, and warn about the steps assigned to dummy gadgets.
+#[cfg(feature = "coverage")]
+pub(crate) fn record_block<F: Field>(block: &Block<F>) {
+    for step in steps(block).filter(|step| step.execution_state().is_dummy()) {
+        log::warn!(
+            "step {:?} at pc {} is assigned to a dummy gadget",
+            step.execution_state(),
+            step.pc.0
+        );
+    }
+    bus_mapping::coverage::update(|coverage| record_execution_states(coverage, block));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::witness::block_convert;
+    use bus_mapping::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::TestContext;
+
+    fn block() -> Block<Fr> {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0x00)
+            RETURN
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        block_convert::<Fr>(&builder.block, &builder.code_db).unwrap()
+    }
+
+    #[test]
+    fn coverage_records_execution_states() {
+        let block = block();
+        let mut coverage = Coverage::default();
+        record_execution_states(&mut coverage, &block);
+
+        assert_eq!(coverage.execution_states["PUSH"], 2);
+        assert_eq!(coverage.steps["PUSH/PUSH1/-"], 2);
+        assert!(coverage.execution_states.contains_key("RETURN_REVERT"));
+        assert!(unreached_execution_states(&coverage).contains(&ExecutionState::SHA3));
+        assert!(!unreached_execution_states(&coverage).contains(&ExecutionState::BeginTx));
+        assert!(reached_dummy_execution_states(&coverage).is_empty());
+
+        let mut merged = coverage.clone();
+        merged.merge(&coverage);
+        assert_eq!(merged.execution_states["PUSH"], 4);
+    }
+
+    #[test]
+    fn coverage_report_flags_dummy_execution_states() {
+        let mut coverage = Coverage::default();
+        record_execution_states(&mut coverage, &block());
+        coverage
+            .execution_states
+            .insert(format!("{:?}", ExecutionState::CREATE2), 1);
+
+        assert_eq!(
+            reached_dummy_execution_states(&coverage),
+            vec![ExecutionState::CREATE2]
+        );
+        let report = CoverageReport(&coverage).to_string();
+        let dummies = report
+            .split("dummy execution states reached (unconstrained):")
+            .nth(1)
+            .unwrap();
+        assert!(dummies.contains("CREATE2"), "{}", report);
+    }
+}
@@ -53,6 +53,18 @@ impl MptUpdates {
         key(row).map(|key| *self.updates.get(&key).expect("missing key in mpt updates"))
     }
 
+    /// The updates of the keys accessed in `rows`, applied from `old_root`
+    pub(crate) fn restrict(&self, rows: &[Rw], old_root: Word) -> Self {
+        MptUpdates {
+            old_root,
+            updates: rows
+                .iter()
+                .filter_map(key)
+                .map(|key| (key, self.updates[&key]))
+                .collect(),
+        }
+    }
+
     pub(crate) fn mock_from(rows: &[Rw]) -> Self {
         let mock_old_root = Word::from(0xcafeu64);
         let map: BTreeMap<_, _> = rows
@@ -113,15 +125,39 @@ impl MptUpdates {
 
 impl MptUpdate {
     pub(crate) fn value_assignments<F: Field>(&self, word_randomness: F) -> (F, F) {
-        let assign = |x: Word| match self.key {
+        let assign = |x: Word| {
+            if self.value_is_word() {
+                rlc::value(&x.to_le_bytes(), word_randomness)
+            } else {
+                x.to_scalar().unwrap()
+            }
+        };
+
+        (assign(self.new_value), assign(self.old_value))
+    }
+
+    /// Whether the values are assigned as the RLC of their bytes rather than
+    /// as scalars.
+    pub(crate) fn value_is_word(&self) -> bool {
+        !matches!(
+            self.key,
             Key::Account {
                 field_tag: AccountFieldTag::Nonce | AccountFieldTag::NonExisting,
                 ..
-            } => x.to_scalar().unwrap(),
-            _ => rlc::value(&x.to_le_bytes(), word_randomness),
-        };
+            }
+        )
+    }
 
-        (assign(self.new_value), assign(self.old_value))
+    pub(crate) fn old_value(&self) -> Word {
+        self.old_value
+    }
+
+    pub(crate) fn old_root(&self) -> Word {
+        self.old_root
+    }
+
+    pub(crate) fn new_root(&self) -> Word {
+        self.new_root
     }
 
     pub(crate) fn root_assignments<F: Field>(&self, word_randomness: F) -> (F, F) {
@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+use bus_mapping::operation::Target;
+use eth_types::{Address, ToBigEndian, Word, H256};
+use ethers_core::utils::{keccak256, rlp::RlpStream};
+
+use crate::table::{TxLogFieldTag, TxReceiptFieldTag};
+
+use super::{Rw, RwMap};
+
+/// Number of bytes of the logs bloom filter
+pub const BLOOM_BYTES: usize = 256;
+
+/// Log entry of a transaction receipt
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Log {
+    /// Address of the contract which emitted the log
+    pub address: Address,
+    /// Topics of the log
+    pub topics: Vec<Word>,
+    /// Data of the log
+    pub data: Vec<u8>,
+}
+
+/// Transaction receipt, rebuilt from the TxReceipt and TxLog rows of the
+/// RwTable.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// The transaction identifier in the block
+    pub tx_id: usize,
+    /// EIP-2718 type of the transaction, 0 for legacy transactions
+    pub tx_type: u64,
+    /// Whether the transaction succeeded
+    pub status: u64,
+    /// Gas used in the block up to and including this transaction
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    /// Bloom filter of the logs of this receipt
+    pub fn bloom(&self) -> [u8; BLOOM_BYTES] {
+        let mut bloom = [0u8; BLOOM_BYTES];
+        for input in self.bloom_inputs() {
+            accrue_bloom(&mut bloom, &input);
+        }
+        bloom
+    }
+
+    /// Byte strings hashed into the bloom filter: the address and each topic
+    /// of every log.
+    pub fn bloom_inputs(&self) -> Vec<Vec<u8>> {
+        self.logs
+            .iter()
+            .flat_map(|log| {
+                std::iter::once(log.address.as_bytes().to_vec())
+                    .chain(log.topics.iter().map(|topic| topic.to_be_bytes().to_vec()))
+            })
+            .collect()
+    }
+
+    /// EIP-2718 encoding of the receipt, as stored in the receipts trie.
+    pub fn rlp_bytes(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&self.status);
+        stream.append(&self.cumulative_gas_used);
+        stream.append(&self.bloom().to_vec());
+        stream.begin_list(self.logs.len());
+        for log in self.logs.iter() {
+            stream.begin_list(3);
+            stream.append(&log.address);
+            stream.begin_list(log.topics.len());
+            for topic in log.topics.iter() {
+                stream.append(&H256(topic.to_be_bytes()));
+            }
+            stream.append(&log.data);
+        }
+        let encoding = stream.out().to_vec();
+        if self.tx_type == 0 {
+            encoding
+        } else {
+            std::iter::once(self.tx_type as u8)
+                .chain(encoding)
+                .collect()
+        }
+    }
+}
+
+/// Set the 3 bits selected by `keccak256(input)` in the bloom filter.
+pub fn accrue_bloom(bloom: &mut [u8; BLOOM_BYTES], input: &[u8]) {
+    for bit in bloom_bits(input) {
+        bloom[BLOOM_BYTES - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+}
+
+/// Indexes, counted from the least significant bit of the bloom filter read
+/// as a big-endian number, of the 3 bits selected by `keccak256(input)`.
+pub fn bloom_bits(input: &[u8]) -> [usize; 3] {
+    let hash = keccak256(input);
+    [0, 2, 4].map(|i| ((hash[i] as usize & 7) << 8) | hash[i + 1] as usize)
+}
+
+/// Bloom filter of all the logs of the block
+pub fn logs_bloom(receipts: &[Receipt]) -> [u8; BLOOM_BYTES] {
+    let mut bloom = [0u8; BLOOM_BYTES];
+    for receipt in receipts {
+        for (byte, receipt_byte) in bloom.iter_mut().zip(receipt.bloom()) {
+            *byte |= receipt_byte;
+        }
+    }
+    bloom
+}
+
+/// Rebuild the receipts of the `tx_types.len()` transactions of a block from
+/// the RwTable. `tx_types` holds the EIP-2718 type of each transaction, in
+/// block order.
+pub fn receipts_convert(rws: &RwMap, tx_types: &[u64]) -> Vec<Receipt> {
+    let mut receipts: Vec<Receipt> = tx_types
+        .iter()
+        .enumerate()
+        .map(|(idx, tx_type)| Receipt {
+            tx_id: idx + 1,
+            tx_type: *tx_type,
+            ..Default::default()
+        })
+        .collect();
+
+    for rw in rws.0.get(&Target::TxReceipt).into_iter().flatten() {
+        if let Rw::TxReceipt {
+            is_write: true,
+            tx_id,
+            field_tag,
+            value,
+            ..
+        } = *rw
+        {
+            let receipt = &mut receipts[tx_id - 1];
+            match field_tag {
+                TxReceiptFieldTag::PostStateOrStatus => receipt.status = value,
+                TxReceiptFieldTag::CumulativeGasUsed => receipt.cumulative_gas_used = value,
+                TxReceiptFieldTag::LogLength => receipt.logs.resize(value as usize, Log::default()),
+            }
+        }
+    }
+
+    // Topics and data bytes are written in index order by the LOG steps, but
+    // sort them anyway so that the result doesn't depend on the RwMap layout.
+    let mut log_rws = BTreeMap::new();
+    for rw in rws.0.get(&Target::TxLog).into_iter().flatten() {
+        if let Rw::TxLog {
+            tx_id,
+            log_id,
+            field_tag,
+            index,
+            value,
+            ..
+        } = *rw
+        {
+            log_rws.insert((tx_id, log_id, field_tag as u64, index), (field_tag, value));
+        }
+    }
+    for ((tx_id, log_id, _, _), (field_tag, value)) in log_rws {
+        let log = &mut receipts[tx_id - 1].logs[log_id as usize - 1];
+        match field_tag {
+            TxLogFieldTag::Address => log.address = Address::from_slice(&value.to_be_bytes()[12..]),
+            TxLogFieldTag::Topic => log.topics.push(value),
+            TxLogFieldTag::Data => log.data.push(value.as_u64() as u8),
+            TxLogFieldTag::TopicLength | TxLogFieldTag::DataLength => (),
+        }
+    }
+
+    receipts
+}
+
+/// Root of the receipts trie, a Merkle Patricia Trie mapping `rlp(index)` to
+/// the encoding of each receipt.
+pub fn receipts_root(receipts: &[Receipt]) -> H256 {
+    ordered_trie(receipts).0
+}
+
+/// Inputs of the keccak hashes needed to build the receipts trie, in the
+/// order the nodes are hashed.
+pub fn keccak_inputs_receipts_trie(receipts: &[Receipt]) -> Vec<Vec<u8>> {
+    ordered_trie(receipts).1
+}
+
+fn ordered_trie(receipts: &[Receipt]) -> (H256, Vec<Vec<u8>>) {
+    let mut items: Vec<(Vec<u8>, Vec<u8>)> = receipts
+        .iter()
+        .enumerate()
+        .map(|(idx, receipt)| {
+            let key = ethers_core::utils::rlp::encode(&idx);
+            let nibbles = key
+                .iter()
+                .flat_map(|byte| [byte >> 4, byte & 0xf])
+                .collect();
+            (nibbles, receipt.rlp_bytes())
+        })
+        .collect();
+    items.sort();
+
+    let mut hashed = Vec::new();
+    let root = if items.is_empty() {
+        // keccak256 of the empty string encoding
+        vec![0x80]
+    } else {
+        trie_node(&items, 0, &mut hashed)
+    };
+    hashed.push(root.clone());
+    (H256(keccak256(&root)), hashed)
+}
+
+/// Encoding of the node holding `items`, whose keys share their first `depth`
+/// nibbles. Children of 32 bytes or more are referenced by hash, and pushed to
+/// `hashed`.
+fn trie_node(items: &[(Vec<u8>, Vec<u8>)], depth: usize, hashed: &mut Vec<Vec<u8>>) -> Vec<u8> {
+    if let [(key, value)] = items {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&key[depth..], true));
+        stream.append(value);
+        return stream.out().to_vec();
+    }
+
+    // Keys of an ordered trie are distinct and none is a prefix of another,
+    // since `rlp(index)` of two indexes only share a prefix if they have the
+    // same length.
+    let first = &items[0].0;
+    let last = &items[items.len() - 1].0;
+    let shared = first[depth..]
+        .iter()
+        .zip(last[depth..].iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if shared > 0 {
+        let child = trie_node(items, depth + shared, hashed);
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&hex_prefix(&first[depth..depth + shared], false));
+        append_child(&mut stream, child, hashed);
+        return stream.out().to_vec();
+    }
+
+    let mut stream = RlpStream::new_list(17);
+    for nibble in 0..16 {
+        let children: Vec<_> = items
+            .iter()
+            .filter(|(key, _)| key[depth] == nibble)
+            .cloned()
+            .collect();
+        if children.is_empty() {
+            stream.append_empty_data();
+        } else {
+            let child = trie_node(&children, depth + 1, hashed);
+            append_child(&mut stream, child, hashed);
+        }
+    }
+    stream.append_empty_data();
+    stream.out().to_vec()
+}
+
+fn append_child(stream: &mut RlpStream, child: Vec<u8>, hashed: &mut Vec<Vec<u8>>) {
+    if child.len() < 32 {
+        stream.append_raw(&child, 1);
+    } else {
+        stream.append(&H256(keccak256(&child)));
+        hashed.push(child);
+    }
+}
+
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 } + (nibbles.len() % 2) as u8;
+    let mut bytes = vec![];
+    let rest = if nibbles.len() % 2 == 1 {
+        bytes.push((flag << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        bytes.push(flag << 4);
+        nibbles
+    };
+    bytes.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    bytes
+}
@@ -229,8 +229,11 @@ pub enum Rw {
         rw_counter: usize,
         is_write: bool,
         call_id: usize,
+        // address of the first byte of the word, a multiple of 32
         memory_address: u64,
-        byte: u8,
+        // the bytes of the word in memory order, as a big-endian word
+        value: Word,
+        value_prev: Word,
     },
     /// TxLog
     TxLog {
@@ -388,9 +391,16 @@ impl Rw {
         }
     }
 
-    pub(crate) fn memory_value(&self) -> u8 {
+    pub(crate) fn memory_value(&self) -> Word {
+        match self {
+            Self::Memory { value, .. } => *value,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn memory_value_prev(&self) -> Word {
         match self {
-            Self::Memory { byte, .. } => *byte,
+            Self::Memory { value_prev, .. } => *value_prev,
             _ => unreachable!(),
         }
     }
@@ -521,7 +531,10 @@ impl Rw {
             | Self::AccountStorage {
                 account_address, ..
             } => Some(*account_address),
-            Self::Memory { memory_address, .. } => Some(U256::from(*memory_address).to_address()),
+            // memory is keyed by word index
+            Self::Memory { memory_address, .. } => {
+                Some(U256::from(*memory_address / 32).to_address())
+            }
             Self::Stack { stack_pointer, .. } => {
                 Some(U256::from(*stack_pointer as u64).to_address())
             }
@@ -574,43 +587,54 @@ impl Rw {
     }
 
     pub(crate) fn value_assignment<F: Field>(&self, randomness: F) -> F {
+        let (value, is_word) = self.value_word();
+        if is_word {
+            rlc::value(&value.to_le_bytes(), randomness)
+        } else {
+            value.to_scalar().unwrap()
+        }
+    }
+
+    /// Value of the row, and whether it is assigned as the RLC of its bytes
+    /// rather than as a scalar.
+    pub(crate) fn value_word(&self) -> (Word, bool) {
         match self {
-            Self::Start { .. } => F::ZERO,
+            Self::Start { .. } => (Word::zero(), false),
             Self::CallContext {
                 field_tag, value, ..
             } => {
-                match field_tag {
-                    // Only these two tags have values that may not fit into a scalar, so we need to
-                    // RLC.
-                    CallContextFieldTag::CodeHash | CallContextFieldTag::Value => {
-                        rlc::value(&value.to_le_bytes(), randomness)
-                    }
-                    _ => value.to_scalar().unwrap(),
-                }
+                // Only these two tags have values that may not fit into a scalar, so we need to
+                // RLC.
+                let is_word = matches!(
+                    field_tag,
+                    CallContextFieldTag::CodeHash | CallContextFieldTag::Value
+                );
+                (*value, is_word)
             }
             Self::Account {
                 value, field_tag, ..
-            } => match field_tag {
-                AccountFieldTag::CodeHash | AccountFieldTag::Balance => {
-                    rlc::value(&value.to_le_bytes(), randomness)
-                }
-                AccountFieldTag::Nonce | AccountFieldTag::NonExisting => value.to_scalar().unwrap(),
-            },
-            Self::AccountStorage { value, .. } | Self::Stack { value, .. } => {
-                rlc::value(&value.to_le_bytes(), randomness)
-            }
+            } => (
+                *value,
+                matches!(
+                    field_tag,
+                    AccountFieldTag::CodeHash | AccountFieldTag::Balance
+                ),
+            ),
+            Self::AccountStorage { value, .. }
+            | Self::Stack { value, .. }
+            | Self::Memory { value, .. } => (*value, true),
 
             Self::TxLog {
                 field_tag, value, ..
-            } => match field_tag {
-                TxLogFieldTag::Topic => rlc::value(&value.to_le_bytes(), randomness),
-                _ => value.to_scalar().unwrap(),
-            },
+            } => (*value, matches!(field_tag, TxLogFieldTag::Topic)),
 
             Self::TxAccessListAccount { is_warm, .. }
-            | Self::TxAccessListAccountStorage { is_warm, .. } => F::from(*is_warm as u64),
-            Self::Memory { byte, .. } => F::from(u64::from(*byte)),
-            Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => F::from(*value),
+            | Self::TxAccessListAccountStorage { is_warm, .. } => {
+                (Word::from(*is_warm as u64), false)
+            }
+            Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => {
+                (Word::from(*value), false)
+            }
         }
     }
 
@@ -628,7 +652,7 @@ impl Rw {
                     value_prev.to_scalar().unwrap()
                 }
             }),
-            Self::AccountStorage { value_prev, .. } => {
+            Self::AccountStorage { value_prev, .. } | Self::Memory { value_prev, .. } => {
                 Some(rlc::value(&value_prev.to_le_bytes(), randomness))
             }
             Self::TxAccessListAccount { is_warm_prev, .. }
@@ -638,7 +662,6 @@ impl Rw {
             Self::TxRefund { value_prev, .. } => Some(F::from(*value_prev)),
             Self::Start { .. }
             | Self::Stack { .. }
-            | Self::Memory { .. }
             | Self::CallContext { .. }
             | Self::TxLog { .. }
             | Self::TxReceipt { .. } => None,
@@ -824,7 +847,8 @@ impl From<&operation::OperationContainer> for RwMap {
                     memory_address: u64::from_le_bytes(
                         op.op().address().to_le_bytes()[..8].try_into().unwrap(),
                     ),
-                    byte: op.op().value(),
+                    value: *op.op().value(),
+                    value_prev: *op.op().value_prev(),
                 })
                 .collect(),
         );
@@ -842,6 +866,8 @@ impl From<&operation::OperationContainer> for RwMap {
                         TxLogField::Address => TxLogFieldTag::Address,
                         TxLogField::Topic => TxLogFieldTag::Topic,
                         TxLogField::Data => TxLogFieldTag::Data,
+                        TxLogField::TopicLength => TxLogFieldTag::TopicLength,
+                        TxLogField::DataLength => TxLogFieldTag::DataLength,
                     },
                     index: op.op().index,
                     value: op.op().value,
@@ -17,6 +17,8 @@ use super::{Call, ExecStep};
 pub struct Transaction {
     /// The transaction identifier in the block
     pub id: usize,
+    /// The number of the block which includes the transaction
+    pub block_number: u64,
     /// The sender account nonce of the transaction
     pub nonce: u64,
     /// The gas limit of the transaction
@@ -171,6 +173,12 @@ impl Transaction {
                 Value::known(F::ZERO),
                 rlc_be_bytes(&self.s.to_be_bytes(), challenges.evm_word()),
             ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+                Value::known(F::ZERO),
+                Value::known(F::from(self.block_number)),
+            ],
         ];
         let tx_calldata = self
             .call_data
@@ -198,6 +206,7 @@ pub(super) fn tx_convert(
     let tx_sign_hash = sign_data.map(|sign_data| H256::from(&sign_data.msg_hash.to_bytes()));
     Transaction {
         id,
+        block_number: tx.block_num,
         nonce: tx.tx.nonce.as_u64(),
         gas: tx.gas(),
         gas_price: tx.tx.gas_price,
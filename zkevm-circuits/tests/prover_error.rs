@@ -6,6 +6,7 @@
 use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
 use env_logger::Env;
 use eth_types::{
+    evm_types::ChainSpec,
     geth_types::{Account, GethData},
     Block, Bytes, Error, Transaction, Word, H160, U256,
 };
@@ -92,6 +93,7 @@ fn prover_error() {
         eth_block,
         geth_traces,
         accounts,
+        chain_spec: ChainSpec::default(),
     };
     let mut builder = BlockData::new_from_geth_data_with_params(geth_data.clone(), circuit_params)
         .new_circuit_input_builder();